@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS airlock_grants (
+    id TEXT PRIMARY KEY NOT NULL,
+    tool_name TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    workspace_id TEXT,
+    run_id TEXT,
+    chat_id TEXT,
+    path_prefix TEXT,
+    host TEXT,
+    max_level INTEGER NOT NULL,
+    source_command_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    expires_at INTEGER,
+    revoked_at INTEGER,
+    use_count INTEGER NOT NULL DEFAULT 0,
+    last_used_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_airlock_grants_tool_active
+ON airlock_grants(tool_name, revoked_at);
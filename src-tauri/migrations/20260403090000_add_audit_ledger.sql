@@ -0,0 +1,35 @@
+CREATE TABLE IF NOT EXISTS audit_ledger (
+    seq INTEGER PRIMARY KEY NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    command_id TEXT NOT NULL,
+    run_id TEXT,
+    workspace_id TEXT,
+    tool_name TEXT,
+    skill TEXT,
+    arguments TEXT NOT NULL,
+    airlock_level INTEGER,
+    decision TEXT NOT NULL,
+    success INTEGER,
+    result_digest TEXT,
+    touched_paths TEXT NOT NULL,
+    prev_hash TEXT NOT NULL,
+    entry_hash TEXT NOT NULL UNIQUE
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_ledger_run
+ON audit_ledger(run_id, seq);
+
+CREATE INDEX IF NOT EXISTS idx_audit_ledger_workspace
+ON audit_ledger(workspace_id, recorded_at);
+
+CREATE TRIGGER IF NOT EXISTS audit_ledger_no_update
+BEFORE UPDATE ON audit_ledger
+BEGIN
+    SELECT RAISE(ABORT, 'audit ledger is append-only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_ledger_no_delete
+BEFORE DELETE ON audit_ledger
+BEGIN
+    SELECT RAISE(ABORT, 'audit ledger is append-only');
+END;
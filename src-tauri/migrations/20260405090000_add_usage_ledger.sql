@@ -0,0 +1,43 @@
+CREATE TABLE IF NOT EXISTS usage_ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recorded_at INTEGER NOT NULL,
+    provider_id TEXT NOT NULL,
+    provider_type TEXT NOT NULL,
+    model TEXT NOT NULL,
+    workspace_id TEXT,
+    agent_spec_id TEXT,
+    run_id TEXT,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    cached_prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+    total_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    priced INTEGER NOT NULL DEFAULT 1,
+    estimated INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_recorded
+ON usage_ledger(recorded_at);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_workspace
+ON usage_ledger(workspace_id, recorded_at);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_agent
+ON usage_ledger(agent_spec_id, recorded_at);
+
+CREATE INDEX IF NOT EXISTS idx_usage_ledger_run
+ON usage_ledger(run_id);
+
+CREATE TABLE IF NOT EXISTS usage_budgets (
+    id TEXT PRIMARY KEY NOT NULL,
+    scope TEXT NOT NULL,
+    scope_id TEXT,
+    period TEXT NOT NULL,
+    limit_usd REAL NOT NULL,
+    mode TEXT NOT NULL,
+    warn_ratio REAL NOT NULL DEFAULT 0.8,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,2 @@
+ALTER TABLE chat_runtime_telemetry
+    ADD COLUMN last_provider TEXT;
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS archived_messages (
+    id TEXT PRIMARY KEY,
+    chat_id TEXT NOT NULL,
+    summary_message_id TEXT NOT NULL,
+    role TEXT NOT NULL,
+    content TEXT NOT NULL,
+    artifacts_json TEXT,
+    source_rowid INTEGER NOT NULL,
+    created_at DATETIME,
+    archived_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(chat_id) REFERENCES chats(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_archived_messages_summary
+    ON archived_messages(summary_message_id, source_rowid);
+CREATE INDEX IF NOT EXISTS idx_archived_messages_chat_id
+    ON archived_messages(chat_id);
+
+ALTER TABLE chat_compaction_state ADD COLUMN summary_message_id TEXT;
+
+ALTER TABLE chats ADD COLUMN forked_from_chat_id TEXT;
+ALTER TABLE chats ADD COLUMN forked_from_message_id TEXT;
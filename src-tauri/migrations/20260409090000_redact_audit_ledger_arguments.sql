@@ -0,0 +1,4 @@
+ALTER TABLE audit_ledger ADD COLUMN arguments_sha256 TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_audit_ledger_command
+ON audit_ledger(command_id);
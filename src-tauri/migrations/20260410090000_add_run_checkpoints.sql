@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS run_checkpoints (
+    run_id TEXT PRIMARY KEY NOT NULL,
+    chat_id TEXT,
+    agent_name TEXT NOT NULL,
+    status TEXT NOT NULL,
+    input TEXT NOT NULL,
+    checkpoint_json TEXT NOT NULL,
+    pending_approvals_json TEXT NOT NULL DEFAULT '[]',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_run_checkpoints_status_updated
+ON run_checkpoints(status, updated_at DESC);
@@ -0,0 +1 @@
+ALTER TABLE airlock_grants ADD COLUMN shell_command TEXT;
@@ -4,13 +4,15 @@ use crate::ai::agent::events::AgentEvent;
 use crate::ai::agent::runtime::{AgentContent, AgentMessage};
 use crate::ai::agent::workflow::{
     is_tool_allowed_by_spec, tool_call_signature, AgentState, StepResult, WorkflowStep,
-    CANCELLED_RUN_MESSAGE, LAST_EXECUTED_TOOL_SIGNATURE_CONTEXT_KEY, truncate_to_max_bytes,
+    CANCELLED_RUN_MESSAGE, INTERRUPTED_TOOL_CALLS_CONTEXT_KEY,
+    LAST_EXECUTED_TOOL_SIGNATURE_CONTEXT_KEY, truncate_to_max_bytes,
 };
 use crate::ai::specs::manifest::AgentSpec;
 use crate::models::neural::{
     AirlockLevel, CommandPriority, CommandStatus, QueuedCommand, RainyPayload,
 };
-use crate::services::{get_tool_policy, SkillExecutor};
+use crate::services::airlock_policy::{PolicyDecision, PolicyRequest};
+use crate::services::{AirlockPolicyEngine, SkillExecutor};
 use chrono::Utc;
 use std::sync::Arc;
 
@@ -41,7 +43,11 @@ fn tool_output_to_content(output: String) -> AgentContent {
     }
 }
 
-fn resolve_airlock_level_for_tool(spec: &AgentSpec, tool_name: &str) -> AirlockLevel {
+fn resolve_airlock_level_for_tool(
+    spec: &AgentSpec,
+    tool_name: &str,
+    decision: &PolicyDecision,
+) -> AirlockLevel {
     if let Some(level) = spec.airlock.tool_levels.get(tool_name) {
         return match (*level).clamp(0, 2) {
             0 => AirlockLevel::Safe,
@@ -52,9 +58,7 @@ fn resolve_airlock_level_for_tool(spec: &AgentSpec, tool_name: &str) -> AirlockL
     if crate::services::mcp_service::McpService::is_mcp_tool(tool_name) {
         return AirlockLevel::Safe;
     }
-    get_tool_policy(tool_name)
-        .map(|policy| policy.airlock_level)
-        .unwrap_or(AirlockLevel::Dangerous)
+    decision.airlock_level.unwrap_or(AirlockLevel::Dangerous)
 }
 
 fn build_command_for_tool_call(
@@ -79,6 +83,11 @@ fn build_command_for_tool_call(
             tool_access_policy: Some(state.tool_access_policy.clone()),
             tool_access_policy_version: None,
             tool_access_policy_hash: None,
+            agent_spec_id: Some(state.spec.id.clone()),
+            run_id: state.run_id.clone(),
+            chat_id: state.chat_id.clone(),
+            max_airlock_level: state.spec.airlock.max_level,
+            checkout_root: state.checkout_root.clone(),
             ..Default::default()
         },
         status: CommandStatus::Pending,
@@ -125,6 +134,14 @@ impl WorkflowStep for ActStep {
             }
         };
         let executed_tool_signature = tool_call_signature(tool_calls.as_slice());
+        // A resumed run may re-enter the act step for calls that were already
+        // in flight when it stopped. They may have taken effect, so they go
+        // back to the operator instead of running again unasked.
+        let interrupted = state
+            .context
+            .remove(INTERRUPTED_TOOL_CALLS_CONTEXT_KEY)
+            .is_some_and(|signature| signature == executed_tool_signature);
+        let policy_engine = AirlockPolicyEngine::load_for_paths(&state.allowed_paths);
 
         let mut results = Vec::new();
 
@@ -160,16 +177,48 @@ impl WorkflowStep for ActStep {
                 continue;
             }
 
+            let decision = policy_engine.evaluate(&PolicyRequest {
+                tool: &function_name,
+                args: Some(&params),
+                workspace_id: Some(&state.workspace_id),
+                agent_spec_id: Some(&state.spec.id),
+            });
+            if decision.is_denied() {
+                let blocked_msg = format!(
+                    "Tool '{}' blocked by Airlock policy: {}",
+                    function_name, decision.reason
+                );
+                on_event(AgentEvent::ToolResult {
+                    id: call.id.clone(),
+                    result: blocked_msg.clone(),
+                });
+                results.push(AgentMessage {
+                    role: "tool".to_string(),
+                    content: AgentContent::text(blocked_msg),
+                    tool_calls: None,
+                    tool_call_id: Some(call.id.clone()),
+                });
+                continue;
+            }
+
             // Resolve the tool's skill/method routing.
-            // First try the static built-in policy map; if not found, look in the
+            // First try the policy engine (rule files + built-in map); if not found, look in the
             // third-party Wasm skill registry. This makes Wasm skills fully first-class
             // citizens in the agent chat loop.
             let (skill, method_str, airlock_level) =
                 if crate::services::mcp_service::McpService::is_mcp_tool(&function_name) {
-                    let level = resolve_airlock_level_for_tool(state.spec.as_ref(), &function_name);
+                    let level = resolve_airlock_level_for_tool(
+                        state.spec.as_ref(),
+                        &function_name,
+                        &decision,
+                    );
                     ("mcp".to_string(), function_name.clone(), level)
-                } else if let Some(policy) = get_tool_policy(&function_name) {
-                    let level = resolve_airlock_level_for_tool(state.spec.as_ref(), &function_name);
+                } else if let Some(policy) = decision.tool_policy() {
+                    let level = resolve_airlock_level_for_tool(
+                        state.spec.as_ref(),
+                        &function_name,
+                        &decision,
+                    );
                     (
                         policy.skill.as_str().to_string(),
                         function_name.clone(),
@@ -213,7 +262,11 @@ impl WorkflowStep for ActStep {
                         .map(|s| s.id)
                         .unwrap_or_else(|| function_name.clone());
 
-                    let level = resolve_airlock_level_for_tool(state.spec.as_ref(), &function_name);
+                    let level = resolve_airlock_level_for_tool(
+                        state.spec.as_ref(),
+                        &function_name,
+                        &decision,
+                    );
                     let effective = if level > wasm_airlock {
                         level
                     } else {
@@ -239,7 +292,12 @@ impl WorkflowStep for ActStep {
                     "Awaiting Airlock approval for {}",
                     function_name
                 )));
-                match airlock.check_permission(&command).await {
+                let permission = if interrupted {
+                    airlock.check_interrupted_permission(&command).await
+                } else {
+                    airlock.check_permission(&command).await
+                };
+                match permission {
                     Ok(true) => {}
                     Ok(false) => {
                         let blocked_msg = format!(
@@ -274,6 +332,24 @@ impl WorkflowStep for ActStep {
                         continue;
                     }
                 }
+            } else if interrupted {
+                // Nobody to ask: report the call as unfinished and let the
+                // model decide whether to retry it.
+                let skipped_msg = format!(
+                    "Tool '{}' not re-run: it was in flight when the run was interrupted",
+                    function_name
+                );
+                on_event(AgentEvent::ToolResult {
+                    id: call.id.clone(),
+                    result: skipped_msg.clone(),
+                });
+                results.push(AgentMessage {
+                    role: "tool".to_string(),
+                    content: AgentContent::text(skipped_msg),
+                    tool_calls: None,
+                    tool_call_id: Some(call.id.clone()),
+                });
+                continue;
             }
 
             // Implement Auto-Retry Logic.
@@ -348,6 +424,8 @@ impl WorkflowStep for ActStep {
                                     source: format!("tool:{}", function_name),
                                     workspace_id: state.workspace_id.clone(),
                                     timestamp: chrono::Utc::now().timestamp(),
+                                    run_id: state.run_id.clone(),
+                                    chat_id: state.chat_id.clone(),
                                 },
                             )
                             .await;
@@ -380,42 +458,40 @@ mod tests {
     use crate::ai::agent::workflow::AgentState;
     use crate::ai::specs::manifest::AgentSpec;
     use crate::models::neural::{AirlockLevel, ToolAccessPolicy};
+    use crate::services::workspace::WorkspaceManager;
+    use crate::services::{worktree, SkillExecutor};
     use serial_test::serial;
     use std::collections::HashMap;
+    use std::path::Path;
     use std::sync::Arc;
 
-    #[tokio::test]
-    #[serial]
-    async fn queued_command_carries_agent_tool_access_policy() {
-        let temp_dir = tempfile::tempdir().expect("temp dir");
+    async fn test_memory(dir: &Path) -> Arc<AgentMemory> {
         let memory_manager = Arc::new(
-            crate::services::MemoryManager::new(temp_dir.path().to_path_buf())
+            crate::services::MemoryManager::new(dir.to_path_buf())
                 .await
                 .expect("memory manager"),
         );
-        let memory = Arc::new(
-            AgentMemory::new(
-                "test-ws",
-                temp_dir.path().to_path_buf(),
-                memory_manager,
-                None,
-                None,
-            )
-            .await,
-        );
+        Arc::new(AgentMemory::new("test-ws", dir.to_path_buf(), memory_manager, None, None).await)
+    }
 
-        let policy = ToolAccessPolicy {
+    fn allow_all() -> ToolAccessPolicy {
+        ToolAccessPolicy {
             enabled: true,
             mode: "all".to_string(),
             allow: Vec::new(),
             deny: Vec::new(),
-        };
+        }
+    }
 
+    #[tokio::test]
+    #[serial]
+    async fn queued_command_carries_agent_tool_access_policy() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
         let mut state = AgentState::new(
             "test-ws".to_string(),
             vec!["/tmp/test-ws".to_string()],
-            policy.clone(),
-            memory,
+            allow_all(),
+            test_memory(temp_dir.path()).await,
             Arc::new(AgentSpec::default()),
             Arc::new(None),
             None,
@@ -455,4 +531,72 @@ mod tests {
         );
         assert_eq!(command.airlock_level, AirlockLevel::Dangerous);
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn checkout_lane_leaves_the_workspace_untouched_until_merge_back() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let workspace_dir = tempfile::tempdir().expect("workspace dir");
+        std::fs::write(workspace_dir.path().join("notes.txt"), "original\n").unwrap();
+        let workspaces = Arc::new(WorkspaceManager::in_dir(temp_dir.path().to_path_buf()));
+        let workspace = workspaces
+            .create_workspace(
+                "lane".to_string(),
+                vec![workspace_dir.path().display().to_string()],
+            )
+            .expect("workspace");
+        let skills = SkillExecutor::mock_with_workspaces(workspaces);
+
+        let checkout = worktree::isolate(workspace_dir.path().to_path_buf())
+            .await
+            .expect("checkout");
+        let root = checkout.root.display().to_string();
+        let state = AgentState::new(
+            workspace.id,
+            vec![root.clone()],
+            allow_all(),
+            test_memory(temp_dir.path()).await,
+            Arc::new(AgentSpec::default()),
+            Arc::new(None),
+            None,
+        )
+        .with_checkout_root(Some(root));
+
+        let write = build_command_for_tool_call(
+            &state,
+            "filesystem",
+            "write_file",
+            serde_json::json!({"path": "notes.txt", "content": "from the lane\n"}),
+            AirlockLevel::Sensitive,
+        );
+        let result = skills.execute(&write).await;
+        assert!(result.success, "{:?}", result.error);
+        let run = build_command_for_tool_call(
+            &state,
+            "shell",
+            "execute_command",
+            serde_json::json!({
+                "command": "git",
+                "args": ["config", "--file", "lane.cfg", "lane.note", "isolated"],
+            }),
+            AirlockLevel::Dangerous,
+        );
+        let result = skills.execute(&run).await;
+        assert!(result.success, "{:?}", result.error);
+
+        assert!(checkout.root.join("lane.cfg").is_file());
+        assert_eq!(
+            std::fs::read_to_string(workspace_dir.path().join("notes.txt")).unwrap(),
+            "original\n"
+        );
+        assert!(!workspace_dir.path().join("lane.cfg").exists());
+
+        let report = worktree::merge_back(checkout).await.expect("merge back");
+        assert!(report.conflicts.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(workspace_dir.path().join("notes.txt")).unwrap(),
+            "from the lane\n"
+        );
+        assert!(workspace_dir.path().join("lane.cfg").is_file());
+    }
 }
@@ -3,7 +3,7 @@
 // All real logic lives in AgentManager methods.
 use crate::ai::agent::manager::{
     AgentEntity, AgentManager, ChatCompactionStateDto, ChatHistoryWindowDto,
-    ChatRuntimeTelemetryDto, DEFAULT_LONG_CHAT_SCOPE_ID,
+    ChatRuntimeTelemetryDto, ChatSessionDto, DEFAULT_LONG_CHAT_SCOPE_ID,
 };
 use crate::ai::specs::manifest::AgentSpec;
 use crate::services::chat_artifacts::ChatArtifact;
@@ -160,9 +160,33 @@ pub async fn get_chat_history_window(
     chat_scope_id: String,
     cursor_rowid: Option<i64>,
     limit: Option<usize>,
+    expand_archived: Option<bool>,
 ) -> Result<ChatHistoryWindowDto, String> {
     state
-        .get_history_window(&chat_scope_id, cursor_rowid, limit.unwrap_or(100))
+        .get_history_window(
+            &chat_scope_id,
+            cursor_rowid,
+            limit.unwrap_or(100),
+            expand_archived.unwrap_or(false),
+        )
         .await
         .map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn fork_chat(
+    state: State<'_, AgentManager>,
+    chat_id: String,
+    at_message_id: String,
+) -> Result<ChatSessionDto, String> {
+    state
+        .fork_chat(&chat_id, &at_message_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => format!(
+                "Message '{}' was not found in chat '{}'",
+                at_message_id, chat_id
+            ),
+            other => other.to_string(),
+        })
+}
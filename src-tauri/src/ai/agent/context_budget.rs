@@ -1,3 +1,4 @@
+use crate::ai::agent::context_window::ContextWindow;
 use crate::ai::agent::runtime::AgentMessage;
 
 pub struct ContextBudget;
@@ -8,19 +9,18 @@ impl ContextBudget {
     /// and a boolean indicating whether the session overflowed and requires compaction.
     pub fn apply_context_guard(
         messages: &[AgentMessage],
+        context_window: &ContextWindow,
         window_tokens: usize,
     ) -> (Vec<AgentMessage>, bool) {
-        // Approximate token counting (e.g. 4 chars per token)
-        let max_chars = window_tokens * 4;
-        let mut current_chars = 0;
+        let mut current_tokens = 0;
         let mut keepers = Vec::new();
         let mut overflowed = false;
 
         // Iterate backwards to keep the most recent messages
         for msg in messages.iter().rev() {
-            let msg_len = msg.content.as_text().len();
-            if current_chars + msg_len <= max_chars {
-                current_chars += msg_len;
+            let msg_tokens = context_window.estimate_total_tokens(std::slice::from_ref(msg));
+            if current_tokens + msg_tokens <= window_tokens {
+                current_tokens += msg_tokens;
                 keepers.push(msg.clone());
             } else {
                 overflowed = true;
@@ -1,11 +1,8 @@
 // Context Window Manager — Sliding window to prevent unbounded history growth.
 // Enforces max_tokens limit by evicting oldest non-system messages when exceeded.
 
-use crate::ai::agent::runtime::AgentMessage;
-
-/// Estimated average tokens per character for English text.
-/// Conservative: 1 token ≈ 4 chars for most models.
-const CHARS_PER_TOKEN: usize = 4;
+use crate::ai::agent::runtime::{AgentContent, AgentContentPart, AgentMessage};
+use crate::ai::tokenizer::TokenCounter;
 
 /// Default maximum context window in tokens if not configured via spec.
 const DEFAULT_MAX_TOKENS: usize = 120_000;
@@ -14,10 +11,12 @@ const SEMANTIC_CONTEXT_BUDGET_DIVISOR: usize = 5; // 20%
 /// Manages the agent's context window to keep history within token limits.
 pub struct ContextWindow {
     max_tokens: usize,
+    counter: TokenCounter,
 }
 
 impl ContextWindow {
-    /// Create a new ContextWindow with the given max_tokens limit.
+    /// Create a new ContextWindow with the given max_tokens limit, counted
+    /// with the generic tokenizer estimate.
     pub fn new(max_tokens: usize) -> Self {
         Self {
             max_tokens: if max_tokens == 0 {
@@ -25,24 +24,51 @@ impl ContextWindow {
             } else {
                 max_tokens
             },
+            counter: TokenCounter::default(),
+        }
+    }
+
+    /// Create a ContextWindow that counts tokens with `model`'s tokenizer.
+    pub fn for_model(max_tokens: usize, model: &str) -> Self {
+        Self {
+            counter: TokenCounter::for_model(model),
+            ..Self::new(max_tokens)
         }
     }
 
+    pub fn count_text(&self, text: &str) -> usize {
+        self.counter.count_text(text)
+    }
+
     /// Estimate the token count for a single message.
-    fn estimate_tokens(msg: &AgentMessage) -> usize {
-        let text_len = msg.content.as_text().len();
-        // tool_calls add overhead: ~50 tokens per call for function name + args
-        let tool_overhead = msg
+    fn estimate_tokens(&self, msg: &AgentMessage) -> usize {
+        let content = match &msg.content {
+            AgentContent::Text(text) => self.counter.count_text(text),
+            AgentContent::Parts(parts) => parts
+                .iter()
+                .map(|part| match part {
+                    AgentContentPart::Text { text } => self.counter.count_text(text),
+                    AgentContentPart::ImageUrl { image_url } => self
+                        .counter
+                        .count_image(&image_url.url, image_url.detail.as_deref()),
+                })
+                .sum(),
+        };
+        let tool_calls = msg
             .tool_calls
-            .as_ref()
-            .map(|calls| calls.len() * 50)
-            .unwrap_or(0);
-        (text_len / CHARS_PER_TOKEN) + tool_overhead + 4 // +4 for role/metadata overhead
+            .iter()
+            .flatten()
+            .map(|call| {
+                self.counter
+                    .count_tool_call(&call.function.name, &call.function.arguments)
+            })
+            .sum::<usize>();
+        content + tool_calls + 4 // +4 for role/metadata overhead
     }
 
     /// Estimate the total token count for a message list.
-    pub fn estimate_total_tokens(messages: &[AgentMessage]) -> usize {
-        messages.iter().map(Self::estimate_tokens).sum()
+    pub fn estimate_total_tokens(&self, messages: &[AgentMessage]) -> usize {
+        messages.iter().map(|msg| self.estimate_tokens(msg)).sum()
     }
 
     pub fn semantic_context_budget_tokens(&self) -> usize {
@@ -50,17 +76,7 @@ impl ContextWindow {
     }
 
     pub fn truncate_text_for_tokens(&self, text: &str, token_budget: usize) -> String {
-        if token_budget == 0 {
-            return String::new();
-        }
-
-        let max_chars = token_budget.saturating_mul(CHARS_PER_TOKEN);
-        if text.chars().count() <= max_chars {
-            return text.to_string();
-        }
-
-        let truncated: String = text.chars().take(max_chars.saturating_sub(3)).collect();
-        format!("{}...", truncated)
+        self.counter.truncate(text, token_budget)
     }
 
     /// Trim the history to fit within the max_tokens limit.
@@ -69,7 +85,7 @@ impl ContextWindow {
     ///
     /// Returns the trimmed messages vector.
     pub fn trim_history(&self, messages: Vec<AgentMessage>) -> Vec<AgentMessage> {
-        let total = Self::estimate_total_tokens(&messages);
+        let total = self.estimate_total_tokens(&messages);
 
         if total <= self.max_tokens {
             return messages;
@@ -88,7 +104,7 @@ impl ContextWindow {
         }
 
         // System messages token budget
-        let system_tokens: usize = system_msgs.iter().map(Self::estimate_tokens).sum();
+        let system_tokens = self.estimate_total_tokens(&system_msgs);
         let available_tokens = self.max_tokens.saturating_sub(system_tokens);
 
         // Keep as many recent non-system messages as fit in the budget
@@ -97,7 +113,7 @@ impl ContextWindow {
 
         // Iterate from newest to oldest, keeping messages that fit
         for msg in non_system.into_iter().rev() {
-            let msg_tokens = Self::estimate_tokens(&msg);
+            let msg_tokens = self.estimate_tokens(&msg);
             if used_tokens + msg_tokens <= available_tokens {
                 kept.push(msg);
                 used_tokens += msg_tokens;
@@ -119,7 +135,6 @@ impl ContextWindow {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ai::agent::runtime::AgentContent;
 
     fn make_msg(role: &str, text: &str) -> AgentMessage {
         AgentMessage {
@@ -1,5 +1,9 @@
 use super::protocol::{SpecialistRole, SpecialistStatus, SupervisorPlan};
-use crate::ai::provider_types::{ProviderStreamUsage, ProviderToolLifecycleState, ToolCall};
+use crate::ai::provider_types::{
+    ProviderServed, ProviderStreamUsage, ProviderToolLifecycleState, ToolCall,
+};
+use crate::services::process_table::ProcessState;
+use crate::services::usage_ledger::BudgetStatus;
 use serde::Serialize;
 
 #[derive(Clone, Debug, Serialize)]
@@ -129,6 +133,26 @@ pub struct ContextCompactionPayload {
     pub best_practice: Option<String>,
 }
 
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutputPayload {
+    pub process_id: String,
+    /// `stdout` or `stderr`.
+    pub stream: String,
+    pub chunk: String,
+    /// Byte cursor of `chunk` in the process output buffer.
+    pub cursor: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessExitedPayload {
+    pub process_id: String,
+    pub state: ProcessState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(tag = "type", content = "data", rename_all = "snake_case")]
 pub enum AgentEvent {
@@ -155,4 +179,13 @@ pub enum AgentEvent {
     SpecialistCompleted(SpecialistCompletedPayload),
     SpecialistFailed(SpecialistFailedPayload),
     SupervisorSummary(SupervisorSummaryPayload),
+    ProcessOutput(ProcessOutputPayload),
+    ProcessExited(ProcessExitedPayload),
+    /// A budget for this run's scope is past its warning threshold, or a
+    /// soft-warn budget is exhausted.
+    BudgetWarning(BudgetStatus),
+    /// A hard-stop budget is exhausted; the model call was not dispatched.
+    BudgetExceeded(BudgetStatus),
+    /// The provider the router dispatched a model call to.
+    ProviderServed(ProviderServed),
 }
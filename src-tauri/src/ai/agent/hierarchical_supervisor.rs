@@ -2,6 +2,7 @@ use super::events::{
     AgentEvent, SpecialistCompletedPayload, SpecialistEventPayload, SpecialistFailedPayload,
     SupervisorSummaryPayload,
 };
+use super::planner::{self, PlanLimits, PlannedLanes};
 use super::protocol::{
     SpecialistAssignment, SpecialistOutcome, SpecialistRole, SpecialistStatus, SupervisorPlan,
 };
@@ -11,9 +12,11 @@ use super::specialist::SpecialistAgent;
 use crate::ai::agent::memory::AgentMemory;
 use crate::ai::router::IntelligentRouter;
 use crate::ai::specs::manifest::{AgentSpec, DelegationPolicy, RuntimeConfig, RuntimeMode};
+use crate::services::run_checkpoints::{RunProgress, RunRecorder};
+use crate::services::worktree::{self, Worktree};
 use crate::services::{agent_kill_switch::AgentKillSwitch, airlock::AirlockService, SkillExecutor};
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tokio::task::JoinSet;
@@ -28,22 +31,32 @@ pub struct HierarchicalSupervisorAgent {
     pub airlock_service: Arc<Option<AirlockService>>,
     pub kill_switch: Option<AgentKillSwitch>,
     pub runtime_registry: Option<Arc<RuntimeRegistry>>,
+    pub recorder: Option<RunRecorder>,
 }
 
-#[derive(Clone)]
+/// Checkpointed state of a hierarchical run: the branch plan and the
+/// artifacts of every root branch that has finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HierarchicalProgress {
+    run_id: String,
+    plan: HierarchicalPlan,
+    artifacts: Vec<BranchArtifact>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct BranchNode {
     assignment: SpecialistAssignment,
     child: Option<Box<BranchNode>>,
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct HierarchicalPlan {
     summary: String,
     should_delegate: bool,
     roots: Vec<BranchNode>,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct BranchArtifact {
     agent_id: String,
     role: String,
@@ -60,6 +73,9 @@ struct BranchArtifact {
     spawn_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     child: Option<Box<BranchArtifact>>,
+    /// How the branch's isolated checkout merged back.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    merge: Option<String>,
 }
 
 impl HierarchicalSupervisorAgent {
@@ -207,6 +223,8 @@ impl HierarchicalSupervisorAgent {
                     spawn_reason: Some("research_required_before_execution".to_string()),
                     depth: 1,
                     depends_on: vec![],
+                    success_criteria: None,
+                    tool_budget: None,
                 },
                 child: if can_chain {
                     Some(Box::new(BranchNode {
@@ -220,6 +238,8 @@ impl HierarchicalSupervisorAgent {
                             spawn_reason: Some("delegated_from_parent_research".to_string()),
                             depth: 2,
                             depends_on: vec!["research-1".to_string()],
+                            success_criteria: None,
+                            tool_budget: None,
                         },
                         child: None,
                     }))
@@ -239,6 +259,8 @@ impl HierarchicalSupervisorAgent {
                     spawn_reason: Some("research_required".to_string()),
                     depth: 1,
                     depends_on: vec![],
+                    success_criteria: None,
+                    tool_budget: None,
                 },
                 child: None,
             });
@@ -254,6 +276,8 @@ impl HierarchicalSupervisorAgent {
                     spawn_reason: Some("execution_required".to_string()),
                     depth: 1,
                     depends_on: vec![],
+                    success_criteria: None,
+                    tool_budget: None,
                 },
                 child: if needs_verification && can_chain {
                     Some(Box::new(BranchNode {
@@ -267,6 +291,8 @@ impl HierarchicalSupervisorAgent {
                             spawn_reason: Some("verification_child_requested".to_string()),
                             depth: 2,
                             depends_on: vec!["executor-1".to_string()],
+                            success_criteria: None,
+                            tool_budget: None,
                         },
                         child: None,
                     }))
@@ -288,6 +314,8 @@ impl HierarchicalSupervisorAgent {
                     spawn_reason: Some("memory_required".to_string()),
                     depth: 1,
                     depends_on: vec![],
+                    success_criteria: None,
+                    tool_budget: None,
                 },
                 child: None,
             });
@@ -308,6 +336,71 @@ impl HierarchicalSupervisorAgent {
         }
     }
 
+    /// Let the model planner lay out the branches once the policy allows
+    /// delegation. Chains only, since each branch hands its outcome to a
+    /// single child.
+    async fn build_plan(&self, input: &str) -> HierarchicalPlan {
+        let runtime = &self.spec.runtime;
+        let heuristic = Self::build_plan_for_runtime(runtime, input);
+        if !heuristic.should_delegate {
+            return heuristic;
+        }
+        let model = self
+            .options
+            .model
+            .clone()
+            .or_else(|| self.spec.model.clone())
+            .unwrap_or("gemini-2.0-flash".to_string());
+        let limits = PlanLimits {
+            max_assignments: runtime.delegation.max_threads.max(1) as usize,
+            max_depth: runtime.delegation.max_depth.max(1),
+            chains_only: true,
+            allow_verifier: runtime.verification_required,
+            custom_roles: PlanLimits::custom_roles(runtime),
+        };
+        match planner::request_plan(&self.router, &model, input, &limits, None).await {
+            Ok(planned) => Self::plan_from_chains(planned),
+            Err(error) => {
+                tracing::warn!(
+                    "Hierarchical planner failed, using heuristic plan: {}",
+                    error
+                );
+                heuristic
+            }
+        }
+    }
+
+    fn plan_from_chains(planned: PlannedLanes) -> HierarchicalPlan {
+        fn chain(
+            assignment: SpecialistAssignment,
+            rest: &mut Vec<SpecialistAssignment>,
+        ) -> BranchNode {
+            let child = rest
+                .iter()
+                .position(|candidate| candidate.depends_on.contains(&assignment.agent_id))
+                .map(|index| Box::new(chain(rest.remove(index), rest)));
+            BranchNode { assignment, child }
+        }
+
+        let (roots, mut rest): (Vec<_>, Vec<_>) = planned
+            .assignments
+            .into_iter()
+            .partition(|assignment| assignment.depends_on.is_empty());
+        let roots: Vec<BranchNode> = roots
+            .into_iter()
+            .map(|root| chain(root, &mut rest))
+            .collect();
+        HierarchicalPlan {
+            summary: if planned.summary.is_empty() {
+                "Hierarchical delegation plan activated".to_string()
+            } else {
+                planned.summary
+            },
+            should_delegate: !roots.is_empty(),
+            roots,
+        }
+    }
+
     fn flatten_assignments(nodes: &[BranchNode], out: &mut Vec<SpecialistAssignment>) {
         for node in nodes {
             out.push(node.assignment.clone());
@@ -531,6 +624,7 @@ impl HierarchicalSupervisorAgent {
         assignment: &SpecialistAssignment,
         input: String,
         on_event: &F,
+        checkout: Option<&Worktree>,
     ) -> Result<SpecialistOutcome, String>
     where
         F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
@@ -543,7 +637,7 @@ impl HierarchicalSupervisorAgent {
             Some("Queued for execution".to_string()),
         );
 
-        let specialist = SpecialistAgent::new(
+        let mut specialist = SpecialistAgent::new(
             assignment.role.clone(),
             self.spec.clone(),
             self.options.clone(),
@@ -553,6 +647,9 @@ impl HierarchicalSupervisorAgent {
             self.airlock_service.clone(),
             self.kill_switch.clone(),
         );
+        if let Some(checkout) = checkout {
+            specialist = specialist.in_checkout(checkout);
+        }
 
         let (tx, rx) = mpsc::channel(128);
         let emitter = tokio::spawn(Self::emit_branch_messages(
@@ -661,6 +758,9 @@ impl HierarchicalSupervisorAgent {
         }
     }
 
+    /// Run a chain, in its own checkout when any of its lanes can write.
+    /// The chain shares that checkout, so a child sees its parent's changes
+    /// before they are merged back.
     async fn execute_branch<F>(
         &self,
         run_id: &str,
@@ -668,11 +768,73 @@ impl HierarchicalSupervisorAgent {
         input: String,
         on_event: &F,
     ) -> Result<BranchArtifact, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let checkout = if Self::chain_writes(&self.spec.runtime, node) {
+            SpecialistAgent::open_checkout(&self.options).await
+        } else {
+            None
+        };
+        let mut artifact = self
+            .run_branch(run_id, node, input, on_event, checkout.as_ref())
+            .await?;
+        if let Some(checkout) = checkout {
+            self.merge_branch(checkout, &mut artifact).await;
+        }
+        Ok(artifact)
+    }
+
+    fn chain_writes(runtime: &RuntimeConfig, node: &BranchNode) -> bool {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            if SpecialistAgent::needs_checkout(runtime, &node.assignment.role) {
+                return true;
+            }
+            current = node.child.as_deref();
+        }
+        false
+    }
+
+    /// Merge a finished branch's checkout back. A failed branch or a
+    /// cancelled run is discarded unmerged; conflicts mark the branch and
+    /// keep its checkout for review.
+    async fn merge_branch(&self, checkout: Worktree, artifact: &mut BranchArtifact) {
+        let cancelled = self
+            .kill_switch
+            .as_ref()
+            .is_some_and(|kill_switch| kill_switch.is_triggered());
+        if cancelled || artifact.status != "completed" {
+            worktree::discard(checkout).await;
+            return;
+        }
+        match worktree::merge_back(checkout).await {
+            Ok(report) => {
+                if !report.conflicts.is_empty() {
+                    artifact.status = "conflicted".to_string();
+                }
+                artifact.merge = Some(report.summary());
+            }
+            Err(error) => {
+                artifact.status = "failed".to_string();
+                artifact.merge = Some(format!("Merge failed: {}", error));
+            }
+        }
+    }
+
+    async fn run_branch<F>(
+        &self,
+        run_id: &str,
+        node: &BranchNode,
+        input: String,
+        on_event: &F,
+        checkout: Option<&Worktree>,
+    ) -> Result<BranchArtifact, String>
     where
         F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
     {
         let parent = match self
-            .execute_single_assignment(run_id, &node.assignment, input.clone(), on_event)
+            .execute_single_assignment(run_id, &node.assignment, input.clone(), on_event, checkout)
             .await
         {
             Ok(outcome) => outcome,
@@ -694,6 +856,7 @@ impl HierarchicalSupervisorAgent {
                     parent_agent_id: node.assignment.parent_agent_id.clone(),
                     spawn_reason: node.assignment.spawn_reason.clone(),
                     child: None,
+                    merge: None,
                 });
             }
         };
@@ -705,6 +868,7 @@ impl HierarchicalSupervisorAgent {
                     &child_node.assignment,
                     Self::build_child_input(&parent, &input),
                     on_event,
+                    checkout,
                 )
                 .await
             {
@@ -724,6 +888,7 @@ impl HierarchicalSupervisorAgent {
                     parent_agent_id: child_outcome.parent_agent_id,
                     spawn_reason: child_outcome.spawn_reason,
                     child: None,
+                    merge: None,
                 })),
                 Err(error) => Some(Box::new(BranchArtifact {
                     agent_id: child_node.assignment.agent_id.clone(),
@@ -742,6 +907,7 @@ impl HierarchicalSupervisorAgent {
                     parent_agent_id: child_node.assignment.parent_agent_id.clone(),
                     spawn_reason: child_node.assignment.spawn_reason.clone(),
                     child: None,
+                    merge: None,
                 })),
             }
         } else {
@@ -764,6 +930,7 @@ impl HierarchicalSupervisorAgent {
             parent_agent_id: parent.parent_agent_id,
             spawn_reason: parent.spawn_reason,
             child,
+            merge: None,
         })
     }
 
@@ -834,7 +1001,7 @@ Return one complete, precise answer for the user.\n\
     {
         let mut single_spec = self.spec.clone();
         single_spec.runtime.mode = RuntimeMode::Single;
-        let runtime = AgentRuntime::new(
+        let mut runtime = AgentRuntime::new(
             single_spec,
             self.options.clone(),
             self.router.clone(),
@@ -844,6 +1011,9 @@ Return one complete, precise answer for the user.\n\
             self.kill_switch.clone(),
             self.runtime_registry.clone(),
         );
+        if let Some(recorder) = self.recorder.clone() {
+            runtime = runtime.with_recorder(recorder);
+        }
         runtime.run_single(input, on_event).await
     }
 
@@ -851,22 +1021,73 @@ Return one complete, precise answer for the user.\n\
     where
         F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
     {
-        let plan = Self::build_plan_for_runtime(&self.spec.runtime, input);
+        let plan = self.build_plan(input).await;
         if !plan.should_delegate {
             return self.fallback_to_single(input, on_event).await;
         }
 
-        let run_id = uuid::Uuid::new_v4().to_string();
-        let event_plan = Self::plan_for_events(&plan);
+        let progress = HierarchicalProgress {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            plan,
+            artifacts: Vec::new(),
+        };
+        self.record(&progress).await;
+        self.drive(input, progress, on_event).await
+    }
+
+    /// Continue a checkpointed run; root branches that already finished keep
+    /// their artifacts and are not run again.
+    pub async fn resume<F>(
+        &self,
+        input: &str,
+        progress: HierarchicalProgress,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        on_event(AgentEvent::Status(format!(
+            "Resuming hierarchical run with {} finished branch(es)",
+            progress.artifacts.len()
+        )));
+        self.drive(input, progress, on_event).await
+    }
+
+    async fn drive<F>(
+        &self,
+        input: &str,
+        mut progress: HierarchicalProgress,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let run_id = progress.run_id.clone();
+        let event_plan = Self::plan_for_events(&progress.plan);
         on_event(AgentEvent::SupervisorPlanCreated(event_plan));
 
         if let Some(registry) = self.runtime_registry.as_ref() {
             registry
-                .start_hierarchical_run(&run_id, &Self::registration_assignments(&plan.roots))
+                .start_hierarchical_run(
+                    &run_id,
+                    &Self::registration_assignments(&progress.plan.roots),
+                )
                 .await;
             registry.update_supervisor_status(&run_id, "running").await;
         }
 
+        let mut pending_roots = Vec::new();
+        for root in &progress.plan.roots {
+            match progress
+                .artifacts
+                .iter()
+                .find(|artifact| artifact.agent_id == root.assignment.agent_id)
+            {
+                Some(artifact) => self.emit_finished_branch(&on_event, root, artifact),
+                None => pending_roots.push(root.clone()),
+            }
+        }
+
         let max_parallel =
             self.spec
                 .runtime
@@ -875,12 +1096,10 @@ Return one complete, precise answer for the user.\n\
                 .clamp(1, self.spec.runtime.delegation.max_threads.max(1)) as usize;
 
         let mut join_set: JoinSet<Result<BranchArtifact, String>> = JoinSet::new();
-        let mut roots_iter = plan.roots.iter();
-        let mut artifacts = Vec::new();
+        let mut roots_iter = pending_roots.into_iter();
 
         for _ in 0..max_parallel {
             if let Some(root) = roots_iter.next() {
-                let root = root.clone();
                 let input = input.to_string();
                 let this = self.clone_for_spawn();
                 let on_event_clone = on_event.clone();
@@ -895,9 +1114,9 @@ Return one complete, precise answer for the user.\n\
         while let Some(joined) = join_set.join_next().await {
             let artifact =
                 joined.map_err(|e| format!("Hierarchical branch join failure: {}", e))??;
-            artifacts.push(artifact);
+            progress.artifacts.push(artifact);
+            self.record(&progress).await;
             if let Some(root) = roots_iter.next() {
-                let root = root.clone();
                 let input = input.to_string();
                 let this = self.clone_for_spawn();
                 let on_event_clone = on_event.clone();
@@ -909,6 +1128,7 @@ Return one complete, precise answer for the user.\n\
             }
         }
 
+        let artifacts = progress.artifacts;
         let summary = self
             .synthesize_with_main_agent(input, &artifacts, on_event.clone())
             .await?;
@@ -933,6 +1153,36 @@ Return one complete, precise answer for the user.\n\
         Ok(summary)
     }
 
+    /// Replay a branch restored from a checkpoint so the DAG shows it as done.
+    fn emit_finished_branch<F>(&self, on_event: &F, node: &BranchNode, artifact: &BranchArtifact)
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let mut current = Some((node, artifact));
+        while let Some((node, artifact)) = current {
+            let status = if artifact.status == "completed" {
+                SpecialistStatus::Completed
+            } else {
+                SpecialistStatus::Failed
+            };
+            self.emit_status(
+                on_event,
+                &node.assignment,
+                status,
+                Some("Finished before the run was interrupted".to_string()),
+            );
+            current = node.child.as_deref().zip(artifact.child.as_deref());
+        }
+    }
+
+    async fn record(&self, progress: &HierarchicalProgress) {
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder
+                .record(RunProgress::Hierarchical(progress.clone()))
+                .await;
+        }
+    }
+
     fn clone_for_spawn(&self) -> Self {
         Self {
             spec: self.spec.clone(),
@@ -943,6 +1193,7 @@ Return one complete, precise answer for the user.\n\
             airlock_service: self.airlock_service.clone(),
             kill_switch: self.kill_switch.clone(),
             runtime_registry: self.runtime_registry.clone(),
+            recorder: self.recorder.clone(),
         }
     }
 }
@@ -961,6 +1212,41 @@ mod tests {
         }
     }
 
+    fn planned(agent_id: &str, role: SpecialistRole, depends_on: &[&str]) -> SpecialistAssignment {
+        SpecialistAssignment {
+            agent_id: agent_id.to_string(),
+            role,
+            title: agent_id.to_string(),
+            instructions: String::new(),
+            parent_agent_id: depends_on.first().map(|id| id.to_string()),
+            branch_id: Some(agent_id.to_string()),
+            spawn_reason: Some("planned".to_string()),
+            depth: depends_on.len() as u8 + 1,
+            depends_on: depends_on.iter().map(|id| id.to_string()).collect(),
+            success_criteria: None,
+            tool_budget: Some(12),
+        }
+    }
+
+    #[test]
+    fn planned_chains_become_branch_trees() {
+        let plan = HierarchicalSupervisorAgent::plan_from_chains(PlannedLanes {
+            summary: String::new(),
+            assignments: vec![
+                planned("verify", SpecialistRole::Verifier, &["build"]),
+                planned("build", SpecialistRole::Executor, &[]),
+                planned("notes", SpecialistRole::MemoryScribe, &[]),
+            ],
+        });
+        assert!(plan.should_delegate);
+        assert_eq!(plan.roots.len(), 2);
+        let build = &plan.roots[0];
+        assert_eq!(build.assignment.agent_id, "build");
+        let child = build.child.as_ref().expect("verifier chained under build");
+        assert_eq!(child.assignment.agent_id, "verify");
+        assert!(plan.roots[1].child.is_none());
+    }
+
     #[test]
     fn plan_stays_on_main_agent_for_simple_requests() {
         let plan = HierarchicalSupervisorAgent::build_plan_for_runtime(
@@ -3,6 +3,7 @@ use crate::db::Database;
 use crate::services::chat_artifacts::ChatArtifact;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 /// @deprecated — kept only for backward-compatible migration of the legacy single-scope chat.
@@ -40,6 +41,11 @@ pub struct ChatHistoryMessageDto {
     pub artifacts: Option<Vec<ChatArtifact>>,
     pub created_at: String,
     pub cursor_rowid: i64,
+    /// Messages a compaction summary replaced. Archived messages keep the
+    /// rowid they had in `messages` as their cursor.
+    pub archived_message_count: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_messages: Option<Vec<ChatHistoryMessageDto>>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -60,6 +66,7 @@ pub struct ChatCompactionStateDto {
     pub compaction_count: i64,
     pub compressed_at: String,
     pub updated_at: String,
+    pub summary_message_id: Option<String>,
 }
 
 #[derive(Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -72,6 +79,7 @@ pub struct ChatRuntimeTelemetryDto {
     pub workspace_memory_enabled: bool,
     pub workspace_memory_root: Option<String>,
     pub last_model: Option<String>,
+    pub last_provider: Option<String>,
     pub prompt_tokens: i64,
     pub completion_tokens: i64,
     pub total_tokens: i64,
@@ -87,8 +95,31 @@ pub struct ChatSessionDto {
     pub updated_at: String,
     pub message_count: i64,
     pub last_message_at: Option<String>,
+    pub forked_from_chat_id: Option<String>,
+    pub forked_from_message_id: Option<String>,
 }
 
+type HistoryRow = (
+    i64,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    String,
+    i64,
+);
+
+type ArchivedRow = (
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    i64,
+    Option<String>,
+);
+
 impl AgentManager {
     pub fn new(pool: Pool<Sqlite>) -> Self {
         Self { db: Arc::new(pool) }
@@ -175,16 +206,21 @@ impl AgentManager {
         Ok(messages)
     }
 
+    /// Page backwards through a chat. With `expand_archived`, compaction
+    /// summaries carry the messages they replaced (nested summaries included).
     pub async fn get_history_window(
         &self,
         chat_scope_id: &str,
         cursor_rowid: Option<i64>,
         limit: usize,
+        expand_archived: bool,
     ) -> Result<ChatHistoryWindowDto, sqlx::Error> {
         let safe_limit = limit.clamp(1, 200) as i64;
         let rows = if let Some(cursor) = cursor_rowid {
-            sqlx::query_as::<_, (i64, String, String, String, String, Option<String>, String)>(
-                "SELECT rowid, id, chat_id, role, content, artifacts_json, created_at
+            sqlx::query_as::<_, HistoryRow>(
+                "SELECT rowid, id, chat_id, role, content, artifacts_json, created_at,
+                    (SELECT COUNT(1) FROM archived_messages
+                     WHERE archived_messages.summary_message_id = messages.id)
                  FROM messages
                  WHERE chat_id = ? AND rowid < ?
                  ORDER BY rowid DESC
@@ -196,8 +232,10 @@ impl AgentManager {
             .fetch_all(&*self.db)
             .await?
         } else {
-            sqlx::query_as::<_, (i64, String, String, String, String, Option<String>, String)>(
-                "SELECT rowid, id, chat_id, role, content, artifacts_json, created_at
+            sqlx::query_as::<_, HistoryRow>(
+                "SELECT rowid, id, chat_id, role, content, artifacts_json, created_at,
+                    (SELECT COUNT(1) FROM archived_messages
+                     WHERE archived_messages.summary_message_id = messages.id)
                  FROM messages
                  WHERE chat_id = ?
                  ORDER BY rowid DESC
@@ -223,26 +261,17 @@ impl AgentManager {
             false
         };
 
-        let mut messages: Vec<ChatHistoryMessageDto> = rows
-            .into_iter()
-            .map(
-                |(cursor_rowid, id, chat_id, role, content, artifacts_json, created_at)| {
-                    ChatHistoryMessageDto {
-                        id,
-                        chat_scope_id: chat_id,
-                        role,
-                        content,
-                        artifacts: artifacts_json.as_deref().and_then(|value| {
-                            serde_json::from_str::<Vec<ChatArtifact>>(value).ok()
-                        }),
-                        created_at,
-                        cursor_rowid,
-                    }
-                },
-            )
-            .collect();
+        let mut messages: Vec<ChatHistoryMessageDto> =
+            rows.into_iter().map(history_message).collect();
         messages.reverse();
 
+        if expand_archived && messages.iter().any(|m| m.archived_message_count > 0) {
+            let mut archive = self.load_archive(chat_scope_id).await?;
+            for message in &mut messages {
+                attach_archived(message, &mut archive);
+            }
+        }
+
         Ok(ChatHistoryWindowDto {
             messages,
             has_more,
@@ -250,6 +279,42 @@ impl AgentManager {
         })
     }
 
+    /// Every archived message of a chat, grouped by the summary that replaced it.
+    async fn load_archive(
+        &self,
+        chat_id: &str,
+    ) -> Result<HashMap<String, Vec<ChatHistoryMessageDto>>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, ArchivedRow>(
+            "SELECT id, summary_message_id, role, content, artifacts_json, source_rowid, created_at
+             FROM archived_messages
+             WHERE chat_id = ?
+             ORDER BY source_rowid ASC",
+        )
+        .bind(chat_id)
+        .fetch_all(&*self.db)
+        .await?;
+
+        let mut archive: HashMap<String, Vec<ChatHistoryMessageDto>> = HashMap::new();
+        for (id, summary_message_id, role, content, artifacts_json, source_rowid, created_at) in
+            rows
+        {
+            archive
+                .entry(summary_message_id)
+                .or_default()
+                .push(history_message((
+                    source_rowid,
+                    id,
+                    chat_id.to_string(),
+                    role,
+                    content,
+                    artifacts_json,
+                    created_at.unwrap_or_default(),
+                    0,
+                )));
+        }
+        Ok(archive)
+    }
+
     pub async fn clear_history(&self, chat_id: &str) -> Result<(), sqlx::Error> {
         // Clear chat messages (per session)
         sqlx::query("DELETE FROM messages WHERE chat_id = ?")
@@ -257,6 +322,11 @@ impl AgentManager {
             .execute(&*self.db)
             .await?;
 
+        sqlx::query("DELETE FROM archived_messages WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&*self.db)
+            .await?;
+
         sqlx::query("DELETE FROM chat_compaction_state WHERE chat_id = ?")
             .bind(chat_id)
             .execute(&*self.db)
@@ -317,25 +387,35 @@ impl AgentManager {
         }
 
         let source_message_count = messages.len();
-        let delete_count = source_message_count - keep_recent_count;
-        let to_delete: Vec<String> = messages
-            .iter()
-            .take(delete_count)
-            .map(|(_, id)| id.clone())
-            .collect();
-
-        for id in to_delete {
-            sqlx::query("DELETE FROM messages WHERE id = ?")
-                .bind(id)
-                .execute(&mut *tx)
-                .await?;
-        }
+        let archive_count = source_message_count - keep_recent_count;
+        let last_archived_rowid = messages[archive_count - 1].0;
 
+        // Compacted messages move to the archive under the summary that
+        // replaces them, so the original transcript can still be expanded.
         let summary_id = uuid::Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO archived_messages
+                (id, chat_id, summary_message_id, role, content, artifacts_json, source_rowid, created_at)
+             SELECT id, chat_id, ?, role, content, artifacts_json, rowid, created_at
+             FROM messages
+             WHERE chat_id = ? AND rowid <= ?",
+        )
+        .bind(&summary_id)
+        .bind(chat_id)
+        .bind(last_archived_rowid)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM messages WHERE chat_id = ? AND rowid <= ?")
+            .bind(chat_id)
+            .bind(last_archived_rowid)
+            .execute(&mut *tx)
+            .await?;
+
         sqlx::query(
             "INSERT INTO messages (id, chat_id, role, content, artifacts_json) VALUES (?, ?, 'system', ?, NULL)",
         )
-            .bind(summary_id)
+            .bind(&summary_id)
             .bind(chat_id)
             .bind(format!("SESSION COMPACTION SUMMARY:\n{}", summary_content))
             .execute(&mut *tx)
@@ -351,8 +431,9 @@ impl AgentManager {
                 compression_model,
                 compaction_count,
                 compressed_at,
-                updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                updated_at,
+                summary_message_id
+            ) VALUES (?, ?, ?, ?, ?, ?, 1, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, ?)
             ON CONFLICT(chat_id) DO UPDATE SET
                 summary_content = excluded.summary_content,
                 source_message_count = excluded.source_message_count,
@@ -361,7 +442,8 @@ impl AgentManager {
                 compression_model = excluded.compression_model,
                 compaction_count = chat_compaction_state.compaction_count + 1,
                 compressed_at = CURRENT_TIMESTAMP,
-                updated_at = CURRENT_TIMESTAMP",
+                updated_at = CURRENT_TIMESTAMP,
+                summary_message_id = excluded.summary_message_id",
         )
         .bind(chat_id)
         .bind(summary_content)
@@ -369,6 +451,7 @@ impl AgentManager {
         .bind(source_estimated_tokens as i64)
         .bind(keep_recent_count as i64)
         .bind(compression_model)
+        .bind(&summary_id)
         .execute(&mut *tx)
         .await?;
 
@@ -391,7 +474,8 @@ impl AgentManager {
                 compression_model,
                 compaction_count,
                 compressed_at,
-                updated_at
+                updated_at,
+                summary_message_id
              FROM chat_compaction_state
              WHERE chat_id = ?",
         )
@@ -447,6 +531,24 @@ impl AgentManager {
         Ok(())
     }
 
+    /// Record which provider served the chat's latest model call
+    pub async fn record_chat_served_provider(
+        &self,
+        chat_id: &str,
+        provider_id: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE chat_runtime_telemetry
+             SET last_provider = ?, updated_at = CURRENT_TIMESTAMP
+             WHERE chat_id = ?",
+        )
+        .bind(provider_id)
+        .bind(chat_id)
+        .execute(&*self.db)
+        .await?;
+        Ok(())
+    }
+
     pub async fn get_chat_runtime_telemetry(
         &self,
         chat_id: &str,
@@ -461,6 +563,7 @@ impl AgentManager {
                 workspace_memory_enabled,
                 workspace_memory_root,
                 last_model,
+                last_provider,
                 prompt_tokens,
                 completion_tokens,
                 total_tokens,
@@ -485,11 +588,14 @@ impl AgentManager {
                 chats.created_at,
                 chats.updated_at,
                 COUNT(messages.id) AS message_count,
-                MAX(messages.created_at) AS last_message_at
+                MAX(messages.created_at) AS last_message_at,
+                chats.forked_from_chat_id,
+                chats.forked_from_message_id
              FROM chats
              LEFT JOIN messages ON messages.chat_id = chats.id
              WHERE chats.id = ?
-             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at",
+             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at,
+                chats.forked_from_chat_id, chats.forked_from_message_id",
         )
         .bind(chat_id)
         .fetch_optional(&*self.db)
@@ -508,11 +614,14 @@ impl AgentManager {
                 chats.created_at,
                 chats.updated_at,
                 COUNT(messages.id) AS message_count,
-                MAX(messages.created_at) AS last_message_at
+                MAX(messages.created_at) AS last_message_at,
+                chats.forked_from_chat_id,
+                chats.forked_from_message_id
              FROM chats
              LEFT JOIN messages ON messages.chat_id = chats.id
              WHERE chats.workspace_id = ?
-             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at
+             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at,
+                chats.forked_from_chat_id, chats.forked_from_message_id
              ORDER BY chats.updated_at DESC",
         )
         .bind(workspace_id)
@@ -551,11 +660,14 @@ impl AgentManager {
                 chats.created_at,
                 chats.updated_at,
                 COUNT(messages.id) AS message_count,
-                MAX(messages.created_at) AS last_message_at
+                MAX(messages.created_at) AS last_message_at,
+                chats.forked_from_chat_id,
+                chats.forked_from_message_id
              FROM chats
              LEFT JOIN messages ON messages.chat_id = chats.id
              WHERE chats.workspace_id = ?
-             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at
+             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at,
+                chats.forked_from_chat_id, chats.forked_from_message_id
              HAVING COUNT(messages.id) = 0
              ORDER BY chats.updated_at DESC
              LIMIT 1",
@@ -583,6 +695,10 @@ impl AgentManager {
             .bind(chat_id)
             .execute(&mut *tx)
             .await?;
+        sqlx::query("DELETE FROM archived_messages WHERE chat_id = ?")
+            .bind(chat_id)
+            .execute(&mut *tx)
+            .await?;
         sqlx::query("DELETE FROM chat_compaction_state WHERE chat_id = ?")
             .bind(chat_id)
             .execute(&mut *tx)
@@ -599,6 +715,166 @@ impl AgentManager {
         Ok(())
     }
 
+    /// Branch a chat at `at_message_id` into a new session in the same
+    /// workspace. The fork gets copies of every message up to that point, the
+    /// archive behind any compaction summary it inherits, and its own
+    /// compaction state; the source chat is left untouched.
+    pub async fn fork_chat(
+        &self,
+        chat_id: &str,
+        at_message_id: &str,
+    ) -> Result<ChatSessionDto, sqlx::Error> {
+        let compaction = self.get_latest_chat_compaction(chat_id).await?;
+        let mut tx = self.db.begin().await?;
+
+        let (agent_id, title, workspace_id) =
+            sqlx::query_as::<_, (String, Option<String>, String)>(
+                "SELECT agent_id, title, workspace_id FROM chats WHERE id = ?",
+            )
+            .bind(chat_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        let (fork_rowid,): (i64,) =
+            sqlx::query_as("SELECT rowid FROM messages WHERE id = ? AND chat_id = ?")
+                .bind(at_message_id)
+                .bind(chat_id)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+
+        let fork_id = uuid::Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO chats (id, agent_id, title, workspace_id, forked_from_chat_id, forked_from_message_id)
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&fork_id)
+        .bind(&agent_id)
+        .bind(title)
+        .bind(&workspace_id)
+        .bind(chat_id)
+        .bind(at_message_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Compaction summaries sit after the messages they kept, but only ever
+        // stand in for older turns, so a fork inherits them wherever it branches.
+        let messages = sqlx::query_as::<_, (String, String, String, Option<String>, String)>(
+            "SELECT id, role, content, artifacts_json, created_at
+             FROM messages
+             WHERE chat_id = ?
+               AND (rowid <= ? OR EXISTS (
+                    SELECT 1 FROM archived_messages
+                    WHERE archived_messages.summary_message_id = messages.id))
+             ORDER BY rowid ASC",
+        )
+        .bind(chat_id)
+        .bind(fork_rowid)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut copied_ids: HashMap<String, String> = HashMap::new();
+        for (id, role, content, artifacts_json, created_at) in messages {
+            let copy_id = uuid::Uuid::new_v4().to_string();
+            sqlx::query(
+                "INSERT INTO messages (id, chat_id, role, content, artifacts_json, created_at)
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&copy_id)
+            .bind(&fork_id)
+            .bind(role)
+            .bind(content)
+            .bind(artifacts_json)
+            .bind(created_at)
+            .execute(&mut *tx)
+            .await?;
+            copied_ids.insert(id, copy_id);
+        }
+
+        let archived = sqlx::query_as::<_, ArchivedRow>(
+            "SELECT id, summary_message_id, role, content, artifacts_json, source_rowid, created_at
+             FROM archived_messages
+             WHERE chat_id = ?
+             ORDER BY source_rowid ASC",
+        )
+        .bind(chat_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut archive: HashMap<String, Vec<ArchivedRow>> = HashMap::new();
+        for row in archived {
+            archive.entry(row.1.clone()).or_default().push(row);
+        }
+
+        let mut summaries: Vec<String> = copied_ids.keys().cloned().collect();
+        while let Some(summary_id) = summaries.pop() {
+            let Some(rows) = archive.remove(&summary_id) else {
+                continue;
+            };
+            let copy_summary_id = copied_ids[&summary_id].clone();
+            for (id, _, role, content, artifacts_json, source_rowid, created_at) in rows {
+                let copy_id = uuid::Uuid::new_v4().to_string();
+                sqlx::query(
+                    "INSERT INTO archived_messages
+                        (id, chat_id, summary_message_id, role, content, artifacts_json, source_rowid, created_at)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                )
+                .bind(&copy_id)
+                .bind(&fork_id)
+                .bind(&copy_summary_id)
+                .bind(role)
+                .bind(content)
+                .bind(artifacts_json)
+                .bind(source_rowid)
+                .bind(created_at)
+                .execute(&mut *tx)
+                .await?;
+                copied_ids.insert(id.clone(), copy_id);
+                summaries.push(id);
+            }
+        }
+
+        if let Some(state) = compaction {
+            let summary_message_id = state
+                .summary_message_id
+                .as_ref()
+                .and_then(|id| copied_ids.get(id));
+            if let Some(summary_message_id) = summary_message_id {
+                sqlx::query(
+                    "INSERT INTO chat_compaction_state (
+                        chat_id,
+                        summary_content,
+                        source_message_count,
+                        source_estimated_tokens,
+                        kept_recent_count,
+                        compression_model,
+                        compaction_count,
+                        compressed_at,
+                        updated_at,
+                        summary_message_id
+                    ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP, ?)",
+                )
+                .bind(&fork_id)
+                .bind(&state.summary_content)
+                .bind(state.source_message_count)
+                .bind(state.source_estimated_tokens)
+                .bind(state.kept_recent_count)
+                .bind(&state.compression_model)
+                .bind(state.compaction_count)
+                .bind(&state.compressed_at)
+                .bind(summary_message_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+
+        self.get_chat_session(&fork_id)
+            .await?
+            .ok_or_else(|| sqlx::Error::RowNotFound)
+    }
+
     pub async fn get_latest_workspace_chat(
         &self,
         workspace_id: &str,
@@ -611,11 +887,14 @@ impl AgentManager {
                 chats.created_at,
                 chats.updated_at,
                 COUNT(messages.id) AS message_count,
-                MAX(messages.created_at) AS last_message_at
+                MAX(messages.created_at) AS last_message_at,
+                chats.forked_from_chat_id,
+                chats.forked_from_message_id
              FROM chats
              LEFT JOIN messages ON messages.chat_id = chats.id
              WHERE chats.workspace_id = ?
-             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at
+             GROUP BY chats.id, chats.title, chats.workspace_id, chats.created_at, chats.updated_at,
+                chats.forked_from_chat_id, chats.forked_from_message_id
              ORDER BY chats.updated_at DESC
              LIMIT 1",
         )
@@ -775,6 +1054,40 @@ impl AgentManager {
     }
 }
 
+fn history_message(
+    (cursor_rowid, id, chat_id, role, content, artifacts_json, created_at, archived_message_count): HistoryRow,
+) -> ChatHistoryMessageDto {
+    ChatHistoryMessageDto {
+        id,
+        chat_scope_id: chat_id,
+        role,
+        content,
+        artifacts: artifacts_json
+            .as_deref()
+            .and_then(|value| serde_json::from_str::<Vec<ChatArtifact>>(value).ok()),
+        created_at,
+        cursor_rowid,
+        archived_message_count,
+        archived_messages: None,
+    }
+}
+
+/// Hang the archived originals under their summary, recursing into summaries
+/// that were themselves compacted away later.
+fn attach_archived(
+    message: &mut ChatHistoryMessageDto,
+    archive: &mut HashMap<String, Vec<ChatHistoryMessageDto>>,
+) {
+    let Some(mut archived) = archive.remove(&message.id) else {
+        return;
+    };
+    for child in &mut archived {
+        attach_archived(child, archive);
+    }
+    message.archived_message_count = archived.len() as i64;
+    message.archived_messages = Some(archived);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -815,7 +1128,9 @@ mod tests {
                 title TEXT,
                 workspace_id TEXT NOT NULL,
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                forked_from_chat_id TEXT,
+                forked_from_message_id TEXT
             )",
         )
         .execute(&pool)
@@ -836,6 +1151,23 @@ mod tests {
         .await
         .expect("create messages table");
 
+        sqlx::query(
+            "CREATE TABLE archived_messages (
+                id TEXT PRIMARY KEY,
+                chat_id TEXT NOT NULL,
+                summary_message_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                artifacts_json TEXT,
+                source_rowid INTEGER NOT NULL,
+                created_at DATETIME,
+                archived_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+        )
+        .execute(&pool)
+        .await
+        .expect("create archived messages table");
+
         sqlx::query(
             "CREATE TABLE chat_compaction_state (
                 chat_id TEXT PRIMARY KEY,
@@ -846,7 +1178,8 @@ mod tests {
                 compression_model TEXT NOT NULL,
                 compaction_count INTEGER NOT NULL,
                 compressed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
-                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                summary_message_id TEXT
             )",
         )
         .execute(&pool)
@@ -863,6 +1196,7 @@ mod tests {
                 workspace_memory_enabled INTEGER NOT NULL DEFAULT 0,
                 workspace_memory_root TEXT,
                 last_model TEXT,
+                last_provider TEXT,
                 prompt_tokens INTEGER NOT NULL DEFAULT 0,
                 completion_tokens INTEGER NOT NULL DEFAULT 0,
                 total_tokens INTEGER NOT NULL DEFAULT 0,
@@ -904,6 +1238,173 @@ mod tests {
         assert_eq!(contents, vec!["first", "second", "third"]);
     }
 
+    async fn seed_chat(manager: &AgentManager, chat_id: &str, contents: &[&str]) -> Vec<String> {
+        manager
+            .ensure_chat_session_with_workspace(chat_id, "Rainy Agent", "workspace")
+            .await
+            .expect("create chat");
+        let mut ids = Vec::new();
+        for (index, content) in contents.iter().enumerate() {
+            let role = if index % 2 == 0 { "user" } else { "assistant" };
+            ids.push(
+                manager
+                    .save_message(chat_id, role, content)
+                    .await
+                    .expect("save message"),
+            );
+        }
+        ids
+    }
+
+    fn contents(messages: &[ChatHistoryMessageDto]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn compaction_archives_messages_under_expandable_summary() {
+        let manager = setup_manager().await;
+        let chat_id = "chat-compaction-archive";
+        seed_chat(&manager, chat_id, &["one", "two", "three", "four"]).await;
+
+        manager
+            .compact_session(chat_id, "first summary", 1)
+            .await
+            .expect("first compaction");
+        manager.save_message(chat_id, "user", "five").await.unwrap();
+        manager
+            .compact_session(chat_id, "second summary", 1)
+            .await
+            .expect("second compaction");
+
+        let collapsed = manager
+            .get_history_window(chat_id, None, 50, false)
+            .await
+            .expect("collapsed window");
+        assert_eq!(
+            contents(&collapsed.messages),
+            vec!["five", "SESSION COMPACTION SUMMARY:\nsecond summary"]
+        );
+        assert_eq!(collapsed.messages[1].archived_message_count, 2);
+        assert!(collapsed.messages[1].archived_messages.is_none());
+
+        let expanded = manager
+            .get_history_window(chat_id, None, 50, true)
+            .await
+            .expect("expanded window");
+        let summary = &expanded.messages[1];
+        let archived = summary.archived_messages.as_deref().expect("archived");
+        assert_eq!(
+            contents(archived),
+            vec!["four", "SESSION COMPACTION SUMMARY:\nfirst summary"]
+        );
+        let nested = archived[1].archived_messages.as_deref().expect("nested");
+        assert_eq!(contents(nested), vec!["one", "two", "three"]);
+
+        let state = manager
+            .get_latest_chat_compaction(chat_id)
+            .await
+            .unwrap()
+            .expect("compaction state");
+        assert_eq!(
+            state.summary_message_id.as_deref(),
+            Some(summary.id.as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn fork_chat_branches_history_with_its_own_compaction_state() {
+        let manager = setup_manager().await;
+        let chat_id = "chat-fork-source";
+        seed_chat(&manager, chat_id, &["one", "two", "three", "four", "five"]).await;
+        manager
+            .compact_session(chat_id, "summary", 3)
+            .await
+            .expect("compaction");
+        let live = manager.get_history(chat_id).await.unwrap();
+        let at_message_id = live[1].0.clone();
+
+        let fork = manager
+            .fork_chat(chat_id, &at_message_id)
+            .await
+            .expect("fork chat");
+        assert_ne!(fork.id, chat_id);
+        assert_eq!(fork.workspace_id, "workspace");
+        assert_eq!(fork.forked_from_chat_id.as_deref(), Some(chat_id));
+        assert_eq!(
+            fork.forked_from_message_id.as_deref(),
+            Some(at_message_id.as_str())
+        );
+
+        let window = manager
+            .get_history_window(&fork.id, None, 50, true)
+            .await
+            .expect("fork window");
+        assert_eq!(
+            contents(&window.messages),
+            vec!["three", "four", "SESSION COMPACTION SUMMARY:\nsummary"]
+        );
+        let archived = window.messages[2].archived_messages.as_deref().unwrap();
+        assert_eq!(contents(archived), vec!["one", "two"]);
+
+        let state = manager
+            .get_latest_chat_compaction(&fork.id)
+            .await
+            .unwrap()
+            .expect("fork compaction state");
+        assert_eq!(
+            state.summary_message_id.as_deref(),
+            Some(window.messages[2].id.as_str())
+        );
+
+        manager
+            .save_message(&fork.id, "user", "alternative")
+            .await
+            .unwrap();
+        manager.clear_history(&fork.id).await.unwrap();
+        let source = manager
+            .get_history_window(chat_id, None, 50, true)
+            .await
+            .unwrap();
+        assert_eq!(source.messages.len(), 4);
+        assert_eq!(source.messages[3].archived_message_count, 2);
+    }
+
+    #[tokio::test]
+    async fn served_provider_survives_usage_upserts() {
+        let manager = setup_manager().await;
+        let chat_id = "chat-served-provider";
+        let upsert = |total_tokens: i64| {
+            manager.upsert_chat_runtime_telemetry(
+                chat_id,
+                "persisted_long_chat",
+                "unavailable",
+                "local",
+                "local",
+                false,
+                None,
+                Some("gpt-5"),
+                0,
+                0,
+                total_tokens,
+            )
+        };
+
+        upsert(0).await.expect("seed telemetry");
+        manager
+            .record_chat_served_provider(chat_id, "openai")
+            .await
+            .expect("record provider");
+        upsert(42).await.expect("usage upsert");
+
+        let telemetry = manager
+            .get_chat_runtime_telemetry(chat_id)
+            .await
+            .expect("load telemetry")
+            .expect("telemetry row");
+        assert_eq!(telemetry.last_provider.as_deref(), Some("openai"));
+        assert_eq!(telemetry.total_tokens, 42);
+    }
+
     #[tokio::test]
     async fn ensure_default_local_agent_named_refreshes_existing_spec() {
         let manager = setup_manager().await;
@@ -2,7 +2,8 @@ use crate::ai::router::IntelligentRouter;
 use crate::services::embedder::EmbeddingTaskType;
 use crate::services::memory_vault::dedup::{DedupDecision, DedupGate};
 use crate::services::memory_vault::distiller::MemoryDistiller;
-use crate::services::memory_vault::types::RawMemoryTurn;
+use crate::services::memory_vault::governance::{self, MemoryStatus};
+use crate::services::memory_vault::types::{DistilledMemory, MemoryCategory, RawMemoryTurn};
 use crate::services::memory_vault::MemorySensitivity;
 use chrono::{TimeZone, Utc};
 use reqwest::Client;
@@ -262,51 +263,63 @@ impl AgentMemory {
 
             for mem in distilled {
                 // Embed the distilled content
-                let embedder = manager.resolve_gemini_embedder().ok().flatten();
+                let embedder = manager.resolve_embedder().ok().flatten();
                 let embedding = if let Some(ref emb) = embedder {
                     emb.embed_text_for_model_with_task_strict(
                         &mem.content,
-                        crate::services::memory_vault::EMBEDDING_MODEL,
+                        &emb.profile().model,
                         EmbeddingTaskType::RetrievalDocument,
                     )
                     .await
                     .ok()
+                    .map(|vector| (vector, emb.profile().model.clone()))
                 } else {
                     None
                 };
 
                 // Dedup gate
-                let decision = if let Some(ref emb) = embedding {
-                    dedup_gate.gate(&workspace_id, mem.clone(), emb).await
+                let decision = if let Some((ref emb, ref model)) = embedding {
+                    dedup_gate
+                        .gate(&workspace_id, mem.clone(), emb, model)
+                        .await
                 } else {
-                    DedupDecision::Insert(mem.clone())
+                    DedupDecision::Insert {
+                        memory: mem.clone(),
+                        related: Vec::new(),
+                    }
                 };
 
                 match decision {
-                    DedupDecision::Insert(distilled) => {
+                    DedupDecision::Insert { memory, related } => {
+                        let contradicted =
+                            match distiller.find_contradictions(&memory, &related).await {
+                                Ok(ids) => ids,
+                                Err(e) => {
+                                    tracing::warn!("Memory contradiction check failed: {}", e);
+                                    Vec::new()
+                                }
+                            };
+                        // Corrections retire what they correct straight away; other
+                        // memories the distiller was unsure of wait for review before
+                        // they are retrieved or retire anything.
+                        let status = if memory.category == MemoryCategory::Correction
+                            || memory.provenance.confidence >= governance::REVIEW_CONFIDENCE
+                        {
+                            MemoryStatus::Active
+                        } else {
+                            MemoryStatus::Pending
+                        };
+
                         let entry_id = uuid::Uuid::new_v4().to_string();
-                        let mut metadata = HashMap::new();
-                        metadata.insert(
-                            "_category".to_string(),
-                            distilled.category.as_str().to_string(),
-                        );
-                        metadata.insert(
-                            "_importance".to_string(),
-                            format!("{:.2}", distilled.importance),
-                        );
-
-                        let tags = vec![
-                            format!("workspace:{}", workspace_id),
-                            "source:distilled".to_string(),
-                            format!("category:{}", distilled.category.as_str()),
-                            "agent_memory".to_string(),
-                        ];
+                        let mut metadata = distilled_metadata(&memory, status);
+                        governance::set_superseded_ids(&mut metadata, &contradicted);
 
-                        let _ = manager
+                        let tags = distilled_tags(&workspace_id, &memory);
+                        let stored = manager
                             .store_workspace_memory(
                                 &workspace_id,
-                                entry_id,
-                                distilled.content,
+                                entry_id.clone(),
+                                memory.content,
                                 "distilled".to_string(),
                                 tags,
                                 metadata,
@@ -314,27 +327,29 @@ impl AgentMemory {
                                 MemorySensitivity::Internal,
                             )
                             .await;
+
+                        if stored.is_ok()
+                            && status == MemoryStatus::Active
+                            && !contradicted.is_empty()
+                        {
+                            let retired = match manager.vault().await {
+                                Ok(vault) => vault
+                                    .supersede(&contradicted, &entry_id)
+                                    .await
+                                    .map_err(|e| e.to_string()),
+                                Err(e) => Err(e.to_string()),
+                            };
+                            if let Err(e) = retired {
+                                tracing::warn!("Failed to retire superseded memories: {}", e);
+                            }
+                        }
                     }
                     DedupDecision::Update {
                         existing_id,
                         merged,
                     } => {
-                        let mut metadata = HashMap::new();
-                        metadata.insert(
-                            "_category".to_string(),
-                            merged.category.as_str().to_string(),
-                        );
-                        metadata.insert(
-                            "_importance".to_string(),
-                            format!("{:.2}", merged.importance),
-                        );
-
-                        let tags = vec![
-                            format!("workspace:{}", workspace_id),
-                            "source:distilled".to_string(),
-                            format!("category:{}", merged.category.as_str()),
-                            "agent_memory".to_string(),
-                        ];
+                        let metadata = distilled_metadata(&merged, MemoryStatus::Active);
+                        let tags = distilled_tags(&workspace_id, &merged);
 
                         // Re-store with same ID to update
                         let _ = manager
@@ -512,3 +527,32 @@ fn derive_source_from_tags(tags: &[String]) -> String {
         .find_map(|tag| tag.strip_prefix("source:").map(|value| value.to_string()))
         .unwrap_or_else(|| "agent_memory".to_string())
 }
+
+/// Reserved vault metadata for a distilled memory: category, importance,
+/// review status and provenance.
+fn distilled_metadata(memory: &DistilledMemory, status: MemoryStatus) -> HashMap<String, String> {
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "_category".to_string(),
+        memory.category.as_str().to_string(),
+    );
+    metadata.insert(
+        "_importance".to_string(),
+        format!("{:.2}", memory.importance),
+    );
+    metadata.insert(
+        governance::STATUS_KEY.to_string(),
+        status.as_str().to_string(),
+    );
+    memory.provenance.write_to(&mut metadata);
+    metadata
+}
+
+fn distilled_tags(workspace_id: &str, memory: &DistilledMemory) -> Vec<String> {
+    vec![
+        format!("workspace:{}", workspace_id),
+        "source:distilled".to_string(),
+        format!("category:{}", memory.category.as_str()),
+        "agent_memory".to_string(),
+    ]
+}
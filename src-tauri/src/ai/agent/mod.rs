@@ -7,6 +7,7 @@ pub mod events;
 pub mod hierarchical_supervisor;
 pub mod manager;
 pub mod memory;
+pub mod planner;
 pub mod prompt_guard;
 pub mod protocol;
 pub mod runtime;
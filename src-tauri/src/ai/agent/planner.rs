@@ -0,0 +1,535 @@
+//! Model-driven task planning for the supervisors.
+//!
+//! The planner asks the model for a typed plan DAG (lanes, dependencies,
+//! success criteria and tool budgets) and validates it against the runtime's
+//! delegation limits before any specialist starts. Callers fall back to their
+//! keyword heuristics when the request fails or the plan is rejected.
+
+use super::protocol::{SpecialistAssignment, SpecialistOutcome, SpecialistRole, SupervisorPlan};
+use crate::ai::provider_types::{AIError, ChatCompletionRequest, ChatMessage};
+use crate::ai::router::IntelligentRouter;
+use crate::ai::specs::manifest::RuntimeConfig;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+pub const DEFAULT_TOOL_BUDGET: u32 = 12;
+pub const MAX_TOOL_BUDGET: u32 = 40;
+const MAX_OUTCOME_CONTEXT_CHARS: usize = 600;
+
+const PLANNER_SYSTEM_PROMPT: &str = r#"You plan work for a team of specialist agents. Decide whether the request needs specialists at all, and if so split it into lanes.
+
+Roles:
+- research: gathers evidence from files, docs and the web; never changes anything
+- executor: edits files, runs commands and performs the requested changes
+- verifier: checks the final state with read-only tools; must depend on the lanes it checks
+- memory_scribe: saves or recalls long-term memory facts the user stated or asked about
+
+Output a JSON object:
+{"delegate": true, "summary": "...", "assignments": [{"id": "research-1", "role": "research", "title": "...", "instructions": "...", "depends_on": [], "success_criteria": "...", "tool_budget": 8}]}
+
+- Set "delegate" to false with no assignments when a single agent can answer directly (questions, small talk, quick lookups).
+- ids are short, unique slugs. depends_on lists ids whose results a lane needs before it starts; lanes without dependencies run in parallel.
+- instructions are self-contained; success_criteria say what the lane must show to count as done.
+- tool_budget is the most tool calls the lane should need.
+- Stay within the limits given with the request."#;
+
+const REPLAN_SYSTEM_PROMPT: &str = r#"You repair a specialist plan that went wrong mid-run. You get the original request, the lanes that already completed, and the problems (failed lanes or a verifier rejection).
+
+Plan ONLY the follow-up lanes needed to fix the problems. New lanes may depend on completed lane ids; reuse no existing id. Return {"delegate": false, "assignments": []} when retrying cannot help.
+
+Output a JSON object:
+{"delegate": true, "summary": "...", "assignments": [{"id": "executor-2", "role": "executor", "title": "...", "instructions": "...", "depends_on": ["research-1"], "success_criteria": "...", "tool_budget": 8}]}
+
+Roles: research (read-only evidence), executor (changes), verifier (read-only checks, must depend on the lanes it checks), memory_scribe (memory facts)."#;
+
+/// Line the verifier ends its reply with; anything but `PASS` rejects.
+const VERDICT_PREFIX: &str = "VERDICT:";
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+struct PlannedAssignment {
+    id: String,
+    role: SpecialistRole,
+    title: String,
+    instructions: String,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    success_criteria: String,
+    #[serde(default)]
+    tool_budget: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, schemars::JsonSchema)]
+struct PlannerOutput {
+    delegate: bool,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    assignments: Vec<PlannedAssignment>,
+}
+
+/// Shape a plan must respect, derived from the runtime's `DelegationConfig`.
+#[derive(Debug, Clone)]
+pub struct PlanLimits {
+    pub max_assignments: usize,
+    /// Longest dependency chain, the first lane counting as depth 1.
+    pub max_depth: u8,
+    /// Hierarchical branches: a lane has at most one parent and one child.
+    pub chains_only: bool,
+    pub allow_verifier: bool,
+    /// Roles the spec declares beyond the built-in four, as (id, description).
+    pub custom_roles: Vec<(String, String)>,
+}
+
+impl PlanLimits {
+    pub fn custom_roles(runtime: &RuntimeConfig) -> Vec<(String, String)> {
+        runtime
+            .specialists
+            .iter()
+            .filter(|definition| {
+                !SpecialistRole::BUILT_IN
+                    .iter()
+                    .any(|role| role.as_str() == definition.id)
+            })
+            .map(|definition| (definition.id.clone(), definition.description.clone()))
+            .collect()
+    }
+}
+
+/// A validated plan; no assignments means the main agent answers alone.
+#[derive(Debug, Clone)]
+pub struct PlannedLanes {
+    pub summary: String,
+    pub assignments: Vec<SpecialistAssignment>,
+}
+
+/// What went wrong in the previous round, for a follow-up plan.
+pub struct ReplanContext<'a> {
+    pub previous: &'a SupervisorPlan,
+    pub completed: &'a [SpecialistOutcome],
+    pub problems: &'a [String],
+}
+
+fn limits_block(limits: &PlanLimits) -> String {
+    let mut lines = vec![
+        format!("- at most {} lanes", limits.max_assignments),
+        format!(
+            "- dependency chains at most {} lanes deep",
+            limits.max_depth
+        ),
+        "- at most one verifier".to_string(),
+    ];
+    if limits.chains_only {
+        lines
+            .push("- each lane depends on at most one lane and feeds at most one lane".to_string());
+    }
+    if !limits.allow_verifier {
+        lines.push("- no verifier lanes".to_string());
+    }
+    let mut block = format!("Limits:\n{}", lines.join("\n"));
+    if !limits.custom_roles.is_empty() {
+        let roles = limits
+            .custom_roles
+            .iter()
+            .map(|(id, description)| format!("- {}: {}", id, description))
+            .collect::<Vec<_>>();
+        block.push_str(&format!("\n\nAdditional roles:\n{}", roles.join("\n")));
+    }
+    block
+}
+
+fn truncate_chars(input: &str, max_chars: usize) -> String {
+    if input.chars().count() <= max_chars {
+        return input.to_string();
+    }
+    let mut truncated = input.chars().take(max_chars).collect::<String>();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Ask `model` for a plan and validate it. `replan` switches to a follow-up
+/// plan that may build on completed lanes.
+pub async fn request_plan(
+    router: &Arc<RwLock<IntelligentRouter>>,
+    model: &str,
+    input: &str,
+    limits: &PlanLimits,
+    replan: Option<&ReplanContext<'_>>,
+) -> Result<PlannedLanes, String> {
+    let (system, user, known_ids, taken_ids) = match replan {
+        None => (
+            PLANNER_SYSTEM_PROMPT,
+            format!("Request:\n{}\n\n{}", input, limits_block(limits)),
+            HashSet::new(),
+            HashSet::new(),
+        ),
+        Some(context) => {
+            let completed = context
+                .completed
+                .iter()
+                .map(|outcome| {
+                    format!(
+                        "[{}] ({}) {}\n{}",
+                        outcome.agent_id,
+                        outcome.role.as_str(),
+                        outcome.summary,
+                        truncate_chars(&outcome.response, MAX_OUTCOME_CONTEXT_CHARS)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let user = format!(
+                "Request:\n{}\n\nCompleted lanes:\n{}\n\nProblems:\n- {}\n\n{}",
+                input,
+                if completed.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    completed.join("\n\n")
+                },
+                context.problems.join("\n- "),
+                limits_block(limits)
+            );
+            let known = context
+                .completed
+                .iter()
+                .map(|outcome| outcome.agent_id.clone())
+                .collect::<HashSet<_>>();
+            let taken = context
+                .previous
+                .assignments
+                .iter()
+                .map(|assignment| assignment.agent_id.clone())
+                .collect::<HashSet<_>>();
+            (REPLAN_SYSTEM_PROMPT, user, known, taken)
+        }
+    };
+
+    let request = ChatCompletionRequest {
+        messages: vec![ChatMessage::system(system), ChatMessage::user(user)],
+        model: model.to_string(),
+        temperature: Some(0.1),
+        max_tokens: Some(2048),
+        ..Default::default()
+    };
+    let output = match router
+        .read()
+        .await
+        .complete_structured::<PlannerOutput>(request)
+        .await
+    {
+        Ok(output) => output,
+        Err(AIError::SchemaViolation(e)) => {
+            return Err(format!("Planner returned a non-conforming plan: {}", e))
+        }
+        Err(e) => return Err(format!("Planner LLM call failed: {}", e)),
+    };
+
+    let assignments = if output.delegate {
+        validate(output.assignments, limits, &known_ids, &taken_ids)?
+    } else {
+        Vec::new()
+    };
+    Ok(PlannedLanes {
+        summary: output.summary.trim().to_string(),
+        assignments,
+    })
+}
+
+/// Check a planned DAG against `limits` and turn it into assignments.
+/// `known_ids` are completed lanes new ones may depend on; `taken_ids` may
+/// not be reused.
+fn validate(
+    planned: Vec<PlannedAssignment>,
+    limits: &PlanLimits,
+    known_ids: &HashSet<String>,
+    taken_ids: &HashSet<String>,
+) -> Result<Vec<SpecialistAssignment>, String> {
+    if planned.len() > limits.max_assignments {
+        return Err(format!(
+            "Plan has {} lanes; the limit is {}",
+            planned.len(),
+            limits.max_assignments
+        ));
+    }
+
+    let mut ids = HashSet::new();
+    for lane in &planned {
+        let id = lane.id.trim();
+        if id.is_empty() {
+            return Err("Plan has a lane without an id".to_string());
+        }
+        if !ids.insert(id.to_string()) || taken_ids.contains(id) || known_ids.contains(id) {
+            return Err(format!("Lane id '{}' is not unique", id));
+        }
+    }
+
+    if let Some(lane) = planned.iter().find(|lane| {
+        matches!(&lane.role, SpecialistRole::Custom(id)
+            if !limits.custom_roles.iter().any(|(declared, _)| declared == id))
+    }) {
+        return Err(format!(
+            "Lane '{}' uses unknown role '{}'",
+            lane.id,
+            lane.role.as_str()
+        ));
+    }
+
+    let verifiers = planned
+        .iter()
+        .filter(|lane| lane.role == SpecialistRole::Verifier)
+        .count();
+    if verifiers > 1 || (verifiers == 1 && !limits.allow_verifier) {
+        return Err(format!("Plan has {} verifier lanes", verifiers));
+    }
+
+    let mut dependents: HashMap<&str, usize> = HashMap::new();
+    for lane in &planned {
+        for dependency in &lane.depends_on {
+            let dependency = dependency.trim();
+            if dependency == lane.id.trim() {
+                return Err(format!("Lane '{}' depends on itself", lane.id));
+            }
+            if !ids.contains(dependency) && !known_ids.contains(dependency) {
+                return Err(format!(
+                    "Lane '{}' depends on unknown lane '{}'",
+                    lane.id, dependency
+                ));
+            }
+            *dependents.entry(dependency).or_default() += 1;
+        }
+        if lane.role == SpecialistRole::Verifier && lane.depends_on.is_empty() {
+            return Err(format!("Verifier '{}' has no lanes to check", lane.id));
+        }
+        if limits.chains_only && lane.depends_on.len() > 1 {
+            return Err(format!("Lane '{}' has more than one parent", lane.id));
+        }
+    }
+    if limits.chains_only {
+        if let Some((id, _)) = dependents.iter().find(|(_, count)| **count > 1) {
+            return Err(format!("Lane '{}' has more than one child", id));
+        }
+    }
+
+    let by_id = planned
+        .iter()
+        .map(|lane| (lane.id.trim(), lane))
+        .collect::<HashMap<_, _>>();
+    let mut depths: HashMap<String, u8> = HashMap::new();
+    for lane in &planned {
+        lane_depth(lane.id.trim(), &by_id, &mut depths, &mut Vec::new())?;
+    }
+    if let Some((id, depth)) = depths.iter().find(|(_, depth)| **depth > limits.max_depth) {
+        return Err(format!(
+            "Lane '{}' sits {} lanes deep; the limit is {}",
+            id, depth, limits.max_depth
+        ));
+    }
+
+    Ok(planned
+        .into_iter()
+        .map(|lane| {
+            let id = lane.id.trim().to_string();
+            let depends_on = lane
+                .depends_on
+                .iter()
+                .map(|dependency| dependency.trim().to_string())
+                .collect::<Vec<_>>();
+            SpecialistAssignment {
+                depth: depths.get(&id).copied().unwrap_or(1),
+                parent_agent_id: if limits.chains_only {
+                    depends_on.first().cloned()
+                } else {
+                    None
+                },
+                branch_id: Some(id.clone()),
+                spawn_reason: Some("planned".to_string()),
+                agent_id: id,
+                role: lane.role,
+                title: lane.title,
+                instructions: lane.instructions,
+                depends_on,
+                success_criteria: Some(lane.success_criteria).filter(|c| !c.trim().is_empty()),
+                tool_budget: Some(
+                    lane.tool_budget
+                        .unwrap_or(DEFAULT_TOOL_BUDGET)
+                        .clamp(1, MAX_TOOL_BUDGET),
+                ),
+            }
+        })
+        .collect())
+}
+
+/// Depth of `id` in the plan DAG; dependencies on completed lanes count as 0.
+fn lane_depth(
+    id: &str,
+    by_id: &HashMap<&str, &PlannedAssignment>,
+    depths: &mut HashMap<String, u8>,
+    visiting: &mut Vec<String>,
+) -> Result<u8, String> {
+    if let Some(depth) = depths.get(id) {
+        return Ok(*depth);
+    }
+    let Some(lane) = by_id.get(id) else {
+        return Ok(0);
+    };
+    if visiting.iter().any(|visited| visited == id) {
+        return Err(format!("Plan has a dependency cycle through '{}'", id));
+    }
+    visiting.push(id.to_string());
+    let mut depth = 0u8;
+    for dependency in &lane.depends_on {
+        depth = depth.max(lane_depth(dependency.trim(), by_id, depths, visiting)?);
+    }
+    visiting.pop();
+    let depth = depth.saturating_add(1);
+    depths.insert(id.to_string(), depth);
+    Ok(depth)
+}
+
+/// Why the verifier rejected the work, if it did. Replies without a verdict
+/// line are accepted, matching verifiers that predate the convention.
+pub fn verifier_rejection(response: &str) -> Option<String> {
+    let verdict = response
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find_map(|line| line.strip_prefix(VERDICT_PREFIX))?
+        .trim();
+    if verdict.to_ascii_uppercase().starts_with("PASS") {
+        return None;
+    }
+    let reason = verdict
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .trim_start_matches([':', '-', ' '])
+        .trim();
+    Some(if reason.is_empty() {
+        "Verifier rejected the result".to_string()
+    } else {
+        format!("Verifier rejected the result: {}", reason)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lane(id: &str, role: SpecialistRole, depends_on: &[&str]) -> PlannedAssignment {
+        PlannedAssignment {
+            id: id.to_string(),
+            role,
+            title: format!("{} lane", id),
+            instructions: "Do the work".to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            success_criteria: "Done".to_string(),
+            tool_budget: Some(500),
+        }
+    }
+
+    fn limits(chains_only: bool) -> PlanLimits {
+        PlanLimits {
+            max_assignments: 3,
+            max_depth: if chains_only { 2 } else { 3 },
+            chains_only,
+            allow_verifier: true,
+            custom_roles: vec![("docs_editor".to_string(), "Edits docs".to_string())],
+        }
+    }
+
+    #[test]
+    fn validate_accepts_a_dag_and_rejects_limit_violations() {
+        let none = HashSet::new();
+        let plan = validate(
+            vec![
+                lane("research-1", SpecialistRole::Research, &[]),
+                lane("executor-1", SpecialistRole::Executor, &["research-1"]),
+                lane("verifier-1", SpecialistRole::Verifier, &["executor-1"]),
+            ],
+            &limits(false),
+            &none,
+            &none,
+        )
+        .expect("valid plan");
+        assert_eq!(plan[2].depth, 3);
+        assert_eq!(plan[2].tool_budget, Some(MAX_TOOL_BUDGET));
+        assert_eq!(plan[1].parent_agent_id, None);
+
+        let too_deep = validate(
+            vec![
+                lane("a", SpecialistRole::Research, &[]),
+                lane("b", SpecialistRole::Executor, &["a"]),
+                lane("c", SpecialistRole::Verifier, &["b"]),
+            ],
+            &limits(true),
+            &none,
+            &none,
+        );
+        assert!(too_deep.unwrap_err().contains("deep"));
+
+        let cycle = validate(
+            vec![
+                lane("a", SpecialistRole::Research, &["b"]),
+                lane("b", SpecialistRole::Executor, &["a"]),
+            ],
+            &limits(false),
+            &none,
+            &none,
+        );
+        assert!(cycle.unwrap_err().contains("cycle"));
+
+        let unknown = validate(
+            vec![lane("a", SpecialistRole::Executor, &["ghost"])],
+            &limits(false),
+            &none,
+            &none,
+        );
+        assert!(unknown.is_err());
+
+        let undeclared = validate(
+            vec![lane(
+                "a",
+                SpecialistRole::from("security_reviewer".to_string()),
+                &[],
+            )],
+            &limits(false),
+            &none,
+            &none,
+        );
+        assert!(undeclared.unwrap_err().contains("unknown role"));
+        let declared = validate(
+            vec![lane(
+                "a",
+                SpecialistRole::from("docs_editor".to_string()),
+                &[],
+            )],
+            &limits(false),
+            &none,
+            &none,
+        );
+        assert!(declared.is_ok());
+
+        let completed = HashSet::from(["research-1".to_string()]);
+        let followup = validate(
+            vec![lane(
+                "executor-2",
+                SpecialistRole::Executor,
+                &["research-1"],
+            )],
+            &limits(true),
+            &completed,
+            &completed,
+        )
+        .expect("follow-up may depend on completed lanes");
+        assert_eq!(followup[0].depth, 1);
+        assert_eq!(followup[0].parent_agent_id.as_deref(), Some("research-1"));
+    }
+
+    #[test]
+    fn verifier_rejection_reads_the_last_verdict_line() {
+        assert_eq!(verifier_rejection("All good.\nVERDICT: PASS"), None);
+        assert_eq!(verifier_rejection("No verdict given"), None);
+        assert_eq!(
+            verifier_rejection("Checked.\nVERDICT: FAIL: tests still fail").as_deref(),
+            Some("Verifier rejected the result: tests still fail")
+        );
+    }
+}
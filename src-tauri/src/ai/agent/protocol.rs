@@ -1,35 +1,91 @@
 use serde::{Deserialize, Serialize};
 
+/// Serialized as its plain id; ids other than the built-in four name roles
+/// declared in `RuntimeConfig::specialists`.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
-#[serde(rename_all = "snake_case")]
+#[serde(from = "String", into = "String")]
 pub enum SpecialistRole {
     Research,
     Executor,
     Verifier,
     /// Dedicated sub-agent for explicit memory read/write operations (save facts, recall context)
     MemoryScribe,
+    Custom(String),
 }
 
 impl SpecialistRole {
-    pub fn as_str(&self) -> &'static str {
+    pub const BUILT_IN: [SpecialistRole; 4] = [
+        SpecialistRole::Research,
+        SpecialistRole::Executor,
+        SpecialistRole::Verifier,
+        SpecialistRole::MemoryScribe,
+    ];
+
+    pub fn as_str(&self) -> &str {
         match self {
             SpecialistRole::Research => "research",
             SpecialistRole::Executor => "executor",
             SpecialistRole::Verifier => "verifier",
             SpecialistRole::MemoryScribe => "memory_scribe",
+            SpecialistRole::Custom(id) => id,
         }
     }
 
-    pub fn display_name(&self) -> &'static str {
+    pub fn display_name(&self) -> String {
         match self {
-            SpecialistRole::Research => "Research Agent",
-            SpecialistRole::Executor => "Executor Agent",
-            SpecialistRole::Verifier => "Verifier Agent",
-            SpecialistRole::MemoryScribe => "Memory Scribe",
+            SpecialistRole::Research => "Research Agent".to_string(),
+            SpecialistRole::Executor => "Executor Agent".to_string(),
+            SpecialistRole::Verifier => "Verifier Agent".to_string(),
+            SpecialistRole::MemoryScribe => "Memory Scribe".to_string(),
+            SpecialistRole::Custom(id) => id
+                .split('_')
+                .filter(|word| !word.is_empty())
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
         }
     }
 }
 
+impl From<String> for SpecialistRole {
+    fn from(id: String) -> Self {
+        match id.as_str() {
+            "research" => SpecialistRole::Research,
+            "executor" => SpecialistRole::Executor,
+            "verifier" => SpecialistRole::Verifier,
+            "memory_scribe" => SpecialistRole::MemoryScribe,
+            _ => SpecialistRole::Custom(id),
+        }
+    }
+}
+
+impl From<SpecialistRole> for String {
+    fn from(role: SpecialistRole) -> Self {
+        match role {
+            SpecialistRole::Custom(id) => id,
+            role => role.as_str().to_string(),
+        }
+    }
+}
+
+/// Roles are free-form ids in planner output; the planner checks them
+/// against the spec.
+impl schemars::JsonSchema for SpecialistRole {
+    fn schema_name() -> String {
+        "SpecialistRole".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        String::json_schema(gen)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum SpecialistStatus {
@@ -60,6 +116,12 @@ pub struct SpecialistAssignment {
     pub depth: u8,
     #[serde(default)]
     pub depends_on: Vec<String>,
+    /// What the lane must show before it counts as done; set by the planner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub success_criteria: Option<String>,
+    /// Upper bound on tool calls for the lane; set by the planner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_budget: Option<u32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -7,10 +7,14 @@ use crate::ai::agent::hierarchical_supervisor::HierarchicalSupervisorAgent;
 use crate::ai::agent::memory::AgentMemory;
 use crate::ai::agent::runtime_registry::RuntimeRegistry;
 use crate::ai::agent::supervisor::SupervisorAgent;
-use crate::ai::agent::workflow::{AgentState, ThinkStep, Workflow};
+use crate::ai::agent::workflow::{
+    tool_call_signature, AgentState, ThinkStep, Workflow, WorkflowProgress,
+    INTERRUPTED_TOOL_CALLS_CONTEXT_KEY,
+};
 use crate::ai::router::IntelligentRouter;
 use crate::ai::specs::manifest::{AgentSpec, RuntimeMode};
 use crate::services::agent_kill_switch::AgentKillSwitch;
+use crate::services::run_checkpoints::{RunProgress, RunRecorder};
 use crate::services::SkillExecutor;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
@@ -47,6 +51,16 @@ pub struct RuntimeOptions {
     /// Whether workspace memory overlay is active for this run.
     #[serde(default)]
     pub workspace_memory_enabled: bool,
+    /// Run identifier, used to scope "approve for this run" Airlock grants.
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Chat session identifier, used to scope "approve for this session" grants.
+    #[serde(default)]
+    pub chat_id: Option<String>,
+    /// Isolated checkout standing in for the workspace root, set for
+    /// specialist lanes that write in parallel.
+    #[serde(default)]
+    pub checkout_root: Option<String>,
 }
 
 /// The core runtime that orchestrates the agent's thinking process
@@ -59,6 +73,8 @@ pub struct AgentRuntime {
     airlock_service: Arc<Option<crate::services::airlock::AirlockService>>,
     kill_switch: Option<AgentKillSwitch>,
     runtime_registry: Option<Arc<RuntimeRegistry>>,
+    /// Checkpoints the run so it can be resumed after a restart.
+    recorder: Option<RunRecorder>,
     history: Arc<Mutex<Vec<AgentMessage>>>,
     /// Sliding window of request timestamps for rate limiting.
     request_timestamps: Arc<Mutex<VecDeque<std::time::Instant>>>,
@@ -306,11 +322,17 @@ impl AgentRuntime {
             airlock_service,
             kill_switch,
             runtime_registry,
+            recorder: None,
             history: Arc::new(Mutex::new(Vec::new())),
             request_timestamps: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
 
+    pub fn with_recorder(mut self, recorder: RunRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
     /// Compute the vault workspace key honoring persistence isolation settings.
     /// - `per_connector_isolation` → append connector_id
     /// - `session_scope: "per_user"` → append user_id
@@ -577,43 +599,44 @@ Rules:
             return String::new();
         }
 
-        let budget_tokens = context_window.semantic_context_budget_tokens();
-        let mut remaining_chars = budget_tokens.saturating_mul(4);
+        let mut remaining_tokens = context_window.semantic_context_budget_tokens();
         let mut block = String::new();
         let header = format!(
             "\n\n--- RELEVANT CONTEXT FROM WORKSPACE MEMORY ({:?}) ---\n",
             result.mode
         );
         block.push_str(&header);
-        remaining_chars = remaining_chars.saturating_sub(header.len());
+        remaining_tokens = remaining_tokens.saturating_sub(context_window.count_text(&header));
 
         for (i, entry) in result.entries.iter().enumerate() {
-            if remaining_chars < 32 {
+            if remaining_tokens < 8 {
                 break;
             }
 
             let prefix = format!("[{}] ", i + 1);
-            let content_budget_tokens = (remaining_chars / 4).saturating_sub(8);
+            let content_budget_tokens = remaining_tokens.saturating_sub(4);
             let content =
                 context_window.truncate_text_for_tokens(&entry.content, content_budget_tokens);
             let line = format!("{}{}\n", prefix, content);
-            if line.len() > remaining_chars {
+            let line_tokens = context_window.count_text(&line);
+            if line_tokens > remaining_tokens {
                 break;
             }
             block.push_str(&line);
-            remaining_chars = remaining_chars.saturating_sub(line.len());
+            remaining_tokens = remaining_tokens.saturating_sub(line_tokens);
         }
 
         if let Some(reason) = &result.reason {
             let footer = format!("fallback_reason: {}\n", reason);
-            if footer.len() <= remaining_chars {
+            let footer_tokens = context_window.count_text(&footer);
+            if footer_tokens <= remaining_tokens {
                 block.push_str(&footer);
-                remaining_chars = remaining_chars.saturating_sub(footer.len());
+                remaining_tokens = remaining_tokens.saturating_sub(footer_tokens);
             }
         }
 
         let end = "----------------------------------------------\n";
-        if end.len() <= remaining_chars {
+        if context_window.count_text(end) <= remaining_tokens {
             block.push_str(end);
         }
         block
@@ -628,34 +651,67 @@ Rules:
             self.spec.runtime.mode,
             RuntimeMode::ParallelSupervisor | RuntimeMode::Supervisor
         ) {
-            let supervisor = SupervisorAgent {
-                spec: self.spec.clone(),
-                options: self.options.clone(),
-                router: self.router.clone(),
-                skills: self.skills.clone(),
-                memory: self.memory.clone(),
-                airlock_service: self.airlock_service.clone(),
-                kill_switch: self.kill_switch.clone(),
-                runtime_registry: self.runtime_registry.clone(),
-            };
-            return supervisor.run(input, on_event).await;
+            return self.supervisor().run(input, on_event).await;
         }
         if self.spec.runtime.mode == RuntimeMode::HierarchicalSupervisor {
-            let supervisor = HierarchicalSupervisorAgent {
-                spec: self.spec.clone(),
-                options: self.options.clone(),
-                router: self.router.clone(),
-                skills: self.skills.clone(),
-                memory: self.memory.clone(),
-                airlock_service: self.airlock_service.clone(),
-                kill_switch: self.kill_switch.clone(),
-                runtime_registry: self.runtime_registry.clone(),
-            };
-            return supervisor.run(input, on_event).await;
+            return self.hierarchical_supervisor().run(input, on_event).await;
         }
         self.run_single(input, on_event).await
     }
 
+    /// Like `run`, but picks up from a checkpoint left by an earlier attempt
+    /// at the same input.
+    pub async fn run_from<F>(
+        &self,
+        input: &str,
+        progress: RunProgress,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        match progress {
+            RunProgress::Started => self.run(input, on_event).await,
+            RunProgress::Workflow(progress) => self.resume_single(progress, on_event).await,
+            RunProgress::Supervisor(progress) => {
+                self.supervisor().resume(input, *progress, on_event).await
+            }
+            RunProgress::Hierarchical(progress) => {
+                self.hierarchical_supervisor()
+                    .resume(input, progress, on_event)
+                    .await
+            }
+        }
+    }
+
+    fn supervisor(&self) -> SupervisorAgent {
+        SupervisorAgent {
+            spec: self.spec.clone(),
+            options: self.options.clone(),
+            router: self.router.clone(),
+            skills: self.skills.clone(),
+            memory: self.memory.clone(),
+            airlock_service: self.airlock_service.clone(),
+            kill_switch: self.kill_switch.clone(),
+            runtime_registry: self.runtime_registry.clone(),
+            recorder: self.recorder.clone(),
+        }
+    }
+
+    fn hierarchical_supervisor(&self) -> HierarchicalSupervisorAgent {
+        HierarchicalSupervisorAgent {
+            spec: self.spec.clone(),
+            options: self.options.clone(),
+            router: self.router.clone(),
+            skills: self.skills.clone(),
+            memory: self.memory.clone(),
+            airlock_service: self.airlock_service.clone(),
+            kill_switch: self.kill_switch.clone(),
+            runtime_registry: self.runtime_registry.clone(),
+            recorder: self.recorder.clone(),
+        }
+    }
+
     pub async fn run_single<F>(&self, input: &str, on_event: F) -> Result<String, String>
     where
         F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
@@ -704,20 +760,7 @@ Rules:
         }
 
         // 1. Initialize State
-        let mut state = AgentState::new(
-            self.options.workspace_id.clone(),
-            self.options.allowed_paths.clone().unwrap_or_default(),
-            crate::models::neural::ToolAccessPolicy {
-                enabled: true,
-                mode: self.spec.airlock.tool_policy.mode.clone(),
-                allow: self.spec.airlock.tool_policy.allow.clone(),
-                deny: self.spec.airlock.tool_policy.deny.clone(),
-            },
-            self.memory.clone(),
-            Arc::new(self.spec.clone()),
-            self.airlock_service.clone(),
-            self.kill_switch.clone(),
-        );
+        let mut state = self.new_state();
 
         let system_prompt = format!(
             "{}{}",
@@ -733,8 +776,11 @@ Rules:
             tool_call_id: None,
         });
 
-        let context_window =
-            ContextWindow::new(self.spec.memory_config.effective_max_tokens() as usize);
+        let model = self.model();
+        let context_window = ContextWindow::for_model(
+            self.spec.memory_config.effective_max_tokens() as usize,
+            &model,
+        );
 
         // --- SEMANTIC RETRIEVAL (Hive Mind Seed) ---
         // Retrieve relevant context from the encrypted memory vault using the user input
@@ -745,7 +791,13 @@ Rules:
         let effective_ws = self.effective_workspace_id();
         let mm = self.memory.manager();
         if let Ok(mut result) = mm
-            .search_semantic_detailed(&effective_ws, input, 5, &self.spec.memory_config.strategy)
+            .search_semantic_detailed(
+                &effective_ws,
+                input,
+                5,
+                &self.spec.memory_config.strategy,
+                self.spec.memory_config.retrieval.rerank,
+            )
             .await
         {
             if !result.confidential_entry_ids.is_empty() {
@@ -765,6 +817,8 @@ Rules:
                             tool_access_policy: None,
                             tool_access_policy_version: None,
                             tool_access_policy_hash: None,
+                            run_id: self.options.run_id.clone(),
+                            chat_id: self.options.chat_id.clone(),
                             ..Default::default()
                         },
                         status: crate::models::neural::CommandStatus::Pending,
@@ -879,6 +933,7 @@ Rules:
         let (guarded_messages, overflowed) =
             crate::ai::agent::context_budget::ContextBudget::apply_context_guard(
                 &state.messages,
+                &context_window,
                 context_window.semantic_context_budget_tokens() * 5, // Full budget approximation
             );
         if overflowed {
@@ -892,39 +947,115 @@ Rules:
         let trimmed_count = pre_trim_len - state.messages.len();
         let history_len = history_len.saturating_sub(trimmed_count);
 
-        // 2. Build the Workflow Graph
-        // In the future, this could be loaded from JSON.
-        // For now, we build the standard "ReAct" loop: Think -> Act -> Think
-        // NOTE: We pass spec and options to Workflow::new. This requires updating workflow.rs.
-        let mut workflow =
-            Workflow::new(self.spec.clone(), self.options.clone(), "think".to_string());
+        // 2. Build the Workflow Graph and 3. Execute it
+        let final_state = self
+            .build_workflow("think".to_string(), history_len)
+            .execute(state, self.skills.clone(), on_event.clone())
+            .await
+            .map_err(|e| format!("Workflow execution failed: {}", e))?;
+
+        self.complete(final_state, history_len, on_event).await
+    }
+
+    /// Continue a single-agent run from its last checkpointed step.
+    async fn resume_single<F>(
+        &self,
+        progress: WorkflowProgress,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let mut state = self.new_state();
+        state.messages = progress.messages;
+        state.context = progress.context;
+        let Some(next_step) = progress.next_step else {
+            return self.complete(state, progress.history_len, on_event).await;
+        };
+
+        // The act step was cut short: its tool calls may or may not have run.
+        if next_step == "act" {
+            if let Some(calls) = state.messages.last().and_then(|m| m.tool_calls.as_deref()) {
+                state.context.insert(
+                    INTERRUPTED_TOOL_CALLS_CONTEXT_KEY.to_string(),
+                    tool_call_signature(calls),
+                );
+            }
+        }
+
+        on_event(AgentEvent::Status(format!(
+            "Resuming interrupted run after {} steps",
+            progress.steps_completed
+        )));
+        let final_state = self
+            .build_workflow(next_step, progress.history_len)
+            .resume_after(progress.steps_completed)
+            .execute(state, self.skills.clone(), on_event.clone())
+            .await
+            .map_err(|e| format!("Workflow execution failed: {}", e))?;
+
+        self.complete(final_state, progress.history_len, on_event)
+            .await
+    }
+
+    fn new_state(&self) -> AgentState {
+        AgentState::new(
+            self.options.workspace_id.clone(),
+            self.options.allowed_paths.clone().unwrap_or_default(),
+            crate::models::neural::ToolAccessPolicy {
+                enabled: true,
+                mode: self.spec.airlock.tool_policy.mode.clone(),
+                allow: self.spec.airlock.tool_policy.allow.clone(),
+                deny: self.spec.airlock.tool_policy.deny.clone(),
+            },
+            self.memory.clone(),
+            Arc::new(self.spec.clone()),
+            self.airlock_service.clone(),
+            self.kill_switch.clone(),
+        )
+        .with_run_scope(self.options.run_id.clone(), self.options.chat_id.clone())
+        .with_checkout_root(self.options.checkout_root.clone())
+    }
+
+    fn model(&self) -> String {
+        self.options
+            .model
+            .clone()
+            .or_else(|| self.spec.model.clone())
+            .unwrap_or("gemini-2.0-flash".to_string())
+    }
+
+    /// The standard "ReAct" loop: Think -> Act -> Think, starting at `start_step`.
+    fn build_workflow(&self, start_step: String, history_len: usize) -> Workflow {
+        let mut workflow = Workflow::new(self.spec.clone(), self.options.clone(), start_step)
+            .with_recorder(self.recorder.clone(), history_len);
 
         // Step 1: Think (Router/LLM)
-        let think_step = Box::new(ThinkStep {
+        workflow.add_step(Box::new(ThinkStep {
             router: self.router.clone(),
-            model: self
-                .options
-                .model
-                .clone()
-                .or_else(|| self.spec.model.clone())
-                .unwrap_or("gemini-2.0-flash".to_string()),
+            model: self.model(),
             allow_streaming: self.options.streaming_enabled.unwrap_or(false),
             reasoning_effort: self.options.reasoning_effort.clone(),
             temperature: self.options.temperature.or(self.spec.temperature),
             max_tokens: self.options.max_tokens.or(self.spec.max_tokens),
-        });
-        workflow.add_step(think_step);
+        }));
 
         // Step 2: Act (Skill Executor)
-        let act_step = Box::new(ActStep);
-        workflow.add_step(act_step);
+        workflow.add_step(Box::new(ActStep));
+        workflow
+    }
 
-        // 3. Execute Workflow
-        let on_event_clone = on_event.clone();
-        let final_state = workflow
-            .execute(state, self.skills.clone(), on_event_clone)
-            .await
-            .map_err(|e| format!("Workflow execution failed: {}", e))?;
+    /// Fold a finished workflow back into chat history and long-term memory.
+    async fn complete<F>(
+        &self,
+        final_state: AgentState,
+        history_len: usize,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let effective_ws = self.effective_workspace_id();
 
         // 4. Update persistent history — append the user input + all new responses
         let last_message = final_state.messages.last().ok_or("No response generated")?;
@@ -967,6 +1098,8 @@ Rules:
                             source: "agent_conversation".to_string(),
                             workspace_id: effective_ws.clone(),
                             timestamp: chrono::Utc::now().timestamp(),
+                            run_id: self.options.run_id.clone(),
+                            chat_id: self.options.chat_id.clone(),
                         },
                     )
                     .await;
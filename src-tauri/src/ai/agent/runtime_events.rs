@@ -1,4 +1,4 @@
-use crate::ai::provider_types::{ProviderStreamUsage, ProviderToolLifecycleEvent};
+use crate::ai::provider_types::{ProviderServed, ProviderStreamUsage, ProviderToolLifecycleEvent};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -31,6 +31,8 @@ pub enum RuntimeStreamEvent {
         finish_reason: Option<String>,
     },
     Raw(serde_json::Value),
+    /// Emitted by the router once the turn completes
+    ProviderServed(ProviderServed),
 }
 
 pub type RuntimeEventCallback = Arc<dyn Fn(RuntimeStreamEvent) + Send + Sync>;
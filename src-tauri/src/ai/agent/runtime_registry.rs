@@ -1,6 +1,6 @@
 use super::protocol::{SpecialistRole, SpecialistStatus};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -51,6 +51,9 @@ pub struct ToolUsageByRole {
     pub executor: u64,
     pub verifier: u64,
     pub memory_scribe: u64,
+    /// Roles declared in the spec, keyed by role id.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, u64>,
 }
 
 #[derive(Clone, Debug, Serialize, Default)]
@@ -306,6 +309,13 @@ impl RuntimeRegistry {
             SpecialistRole::Executor => state.tool_usage_by_role.executor += 1,
             SpecialistRole::Verifier => state.tool_usage_by_role.verifier += 1,
             SpecialistRole::MemoryScribe => state.tool_usage_by_role.memory_scribe += 1,
+            SpecialistRole::Custom(id) => {
+                *state
+                    .tool_usage_by_role
+                    .custom
+                    .entry(id.clone())
+                    .or_default() += 1
+            }
         }
     }
 
@@ -423,11 +433,15 @@ mod tests {
             )
             .await;
         registry.record_tool_use(&SpecialistRole::Research).await;
+        registry
+            .record_tool_use(&SpecialistRole::from("docs_editor".to_string()))
+            .await;
 
         let snapshot = registry.snapshot().await;
         assert_eq!(snapshot.active_supervisor_runs, 1);
         assert_eq!(snapshot.active_specialists, 2);
         assert_eq!(snapshot.tool_usage_by_role.research, 1);
+        assert_eq!(snapshot.tool_usage_by_role.custom["docs_editor"], 1);
         assert_eq!(snapshot.supervisors[0].specialists.len(), 2);
     }
 }
@@ -4,14 +4,31 @@ use super::protocol::{
 use crate::ai::agent::memory::AgentMemory;
 use crate::ai::agent::runtime::{AgentRuntime, RuntimeOptions};
 use crate::ai::router::IntelligentRouter;
-use crate::ai::specs::manifest::{AgentSpec, RuntimeMode};
+use crate::ai::specs::manifest::{AgentSpec, RuntimeConfig, RuntimeMode, SpecialistDefinition};
+use crate::services::worktree::{self, Worktree};
 use crate::services::{agent_kill_switch::AgentKillSwitch, airlock::AirlockService, SkillExecutor};
 use chrono::Utc;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tokio::sync::{mpsc, RwLock};
 
+/// Tools that change the workspace or run arbitrary commands in it.
+const WRITE_LIKE_TOOLS: &[&str] = &[
+    "write_file",
+    "append_file",
+    "edit_file",
+    "apply_patch",
+    "mkdir",
+    "move_file",
+    "delete_file",
+    "execute_command",
+    "start_process",
+    "write_process_stdin",
+];
+
 pub struct SpecialistAgent {
     role: SpecialistRole,
+    definition: SpecialistDefinition,
     spec: AgentSpec,
     options: RuntimeOptions,
     router: Arc<RwLock<IntelligentRouter>>,
@@ -19,6 +36,7 @@ pub struct SpecialistAgent {
     memory: Arc<AgentMemory>,
     airlock_service: Arc<Option<AirlockService>>,
     kill_switch: Option<AgentKillSwitch>,
+    checkout_note: Option<String>,
 }
 
 impl SpecialistAgent {
@@ -33,6 +51,7 @@ impl SpecialistAgent {
         kill_switch: Option<AgentKillSwitch>,
     ) -> Self {
         Self {
+            definition: Self::definition(&spec.runtime, &role),
             role,
             spec,
             options,
@@ -41,6 +60,44 @@ impl SpecialistAgent {
             memory,
             airlock_service,
             kill_switch,
+            checkout_note: None,
+        }
+    }
+
+    /// Root the specialist's file and shell tools in an isolated checkout
+    /// instead of the workspace.
+    pub fn in_checkout(mut self, worktree: &Worktree) -> Self {
+        let root = worktree.root.display().to_string();
+        self.checkout_note = Some(format!(
+            "You are working in an isolated checkout at {} that stands in for the workspace {}. Use paths inside the checkout; your changes are merged back when you finish.",
+            root,
+            worktree.source.display()
+        ));
+        self.options.allowed_paths = Some(vec![root.clone()]);
+        self.options.checkout_root = Some(root);
+        self
+    }
+
+    /// Whether `role` should run in its own checkout: the runtime isolates
+    /// writers and the role can change files.
+    pub fn needs_checkout(runtime: &RuntimeConfig, role: &SpecialistRole) -> bool {
+        runtime.delegation.isolate_writers
+            && Self::definition(runtime, role)
+                .tools
+                .iter()
+                .any(|tool| WRITE_LIKE_TOOLS.contains(&tool.as_str()))
+    }
+
+    /// Check out the workspace the options point at. Without one, or when
+    /// git cannot create the checkout, the lane shares the workspace.
+    pub async fn open_checkout(options: &RuntimeOptions) -> Option<Worktree> {
+        let source = options.allowed_paths.as_ref()?.first()?;
+        match worktree::isolate(PathBuf::from(source)).await {
+            Ok(checkout) => Some(checkout),
+            Err(error) => {
+                tracing::warn!("Specialist runs without isolation: {}", error);
+                None
+            }
         }
     }
 
@@ -76,9 +133,12 @@ impl SpecialistAgent {
                 "file_exists",
                 "get_file_info",
                 "search_files",
+                "search_code",
                 "mkdir",
                 "write_file",
                 "append_file",
+                "edit_file",
+                "apply_patch",
                 "move_file",
                 "delete_file",
                 "git_status",
@@ -91,6 +151,11 @@ impl SpecialistAgent {
                 "update_recurring_task",
                 "delete_recurring_task",
                 "execute_command",
+                "start_process",
+                "read_process_output",
+                "write_process_stdin",
+                "wait_process",
+                "kill_process",
             ],
             SpecialistRole::Verifier => &[
                 "read_file",
@@ -101,6 +166,7 @@ impl SpecialistAgent {
                 "file_exists",
                 "get_file_info",
                 "search_files",
+                "search_code",
                 "git_status",
                 "git_diff",
                 "git_log",
@@ -120,11 +186,12 @@ impl SpecialistAgent {
                 "search_files",
                 "ingest_document",
             ],
+            SpecialistRole::Custom(_) => &[],
         }
     }
 
-    fn role_prompt(&self) -> &'static str {
-        match self.role {
+    fn builtin_prompt(role: &SpecialistRole) -> &'static str {
+        match role {
             SpecialistRole::Research => {
                 "You are the Research Agent. Gather evidence, inspect relevant sources, and return concise findings only. Do not claim code changes."
             }
@@ -132,23 +199,56 @@ impl SpecialistAgent {
                 "You are the Executor Agent. Make the smallest correct changes necessary using only your allowed tools. Verify critical actions with readbacks when possible."
             }
             SpecialistRole::Verifier => {
-                "You are the Verifier Agent. Validate outputs using read-only tools. Never claim success without direct evidence from tool results."
+                "You are the Verifier Agent. Validate outputs using read-only tools. Never claim success without direct evidence from tool results. End your reply with a final line `VERDICT: PASS` or `VERDICT: FAIL: <reason>`."
             }
             SpecialistRole::MemoryScribe => {
                 "You are the Memory Scribe. Your sole job is to persist important facts, preferences, and user details to long-term memory using save_memory, and to surface relevant context using recall_memory. Be precise and factual — save exactly what was stated, with appropriate tags like [\"user\", \"preference\"] or [\"project\", \"context\"]."
             }
+            SpecialistRole::Custom(_) => "",
+        }
+    }
+
+    /// The role as the spec declares it. Built-in roles start from their
+    /// defaults, and a declared prompt or tool list replaces the default one.
+    pub fn definition(runtime: &RuntimeConfig, role: &SpecialistRole) -> SpecialistDefinition {
+        let mut definition = runtime
+            .specialist(role.as_str())
+            .cloned()
+            .unwrap_or_else(|| SpecialistDefinition {
+                id: role.as_str().to_string(),
+                ..Default::default()
+            });
+        if definition.system_prompt.trim().is_empty() {
+            definition.system_prompt = match Self::builtin_prompt(role) {
+                "" => format!(
+                    "You are the {}. Complete your assignment using only your allowed tools and report concise results.",
+                    role.display_name()
+                ),
+                prompt => prompt.to_string(),
+            };
+        }
+        if definition.tools.is_empty() {
+            definition.tools = Self::allowed_tools(role)
+                .iter()
+                .map(|tool| (*tool).to_string())
+                .collect();
         }
+        definition
     }
 
     fn build_specialist_spec(&self) -> AgentSpec {
         let mut spec = self.spec.clone();
         spec.runtime.mode = RuntimeMode::Single;
         spec.airlock.tool_policy.mode = "allowlist".to_string();
-        spec.airlock.tool_policy.allow = Self::allowed_tools(&self.role)
-            .iter()
-            .map(|tool| (*tool).to_string())
-            .collect();
+        spec.airlock.tool_policy.allow = self.definition.tools.clone();
         spec.airlock.tool_policy.deny.clear();
+        if let Some(ceiling) = self.definition.airlock_ceiling {
+            spec.airlock.max_level = Some(
+                spec.airlock
+                    .max_level
+                    .map_or(ceiling, |level| level.min(ceiling)),
+            );
+        }
         if matches!(
             self.spec.runtime.mode,
             RuntimeMode::ParallelSupervisor | RuntimeMode::Supervisor
@@ -175,15 +275,34 @@ impl SpecialistAgent {
         let agent_id = assignment.agent_id.clone();
         let depends_on = assignment.depends_on.clone();
         let started_at_ms = Utc::now().timestamp_millis();
-        let role_prompt = format!(
-            "{}\n\nAssignment: {}\nInstructions: {}\nOutput language: English.",
-            self.role_prompt(),
-            assignment.title,
-            assignment.instructions
+        let mut role_prompt = format!(
+            "{}\n\nAssignment: {}\nInstructions: {}",
+            self.definition.system_prompt, assignment.title, assignment.instructions
         );
+        if let Some(criteria) = assignment.success_criteria.as_deref() {
+            role_prompt.push_str(&format!("\nSuccess criteria: {}", criteria));
+        }
+        if let Some(budget) = assignment.tool_budget {
+            role_prompt.push_str(&format!("\nTool budget: at most {} tool calls.", budget));
+        }
+        if let Some(note) = self.checkout_note.as_deref() {
+            role_prompt.push_str(&format!("\n{}", note));
+        }
+        role_prompt.push_str("\nOutput language: English.");
 
         let mut options = self.options.clone();
         options.custom_system_prompt = Some(role_prompt);
+        if let Some(model) = self.definition.model.clone() {
+            options.model = Some(model);
+        }
+        if let Some(steps) = self.definition.max_steps {
+            options.max_steps = Some(steps);
+        }
+        if let Some(budget) = assignment.tool_budget {
+            // A think and an act step per tool round, plus the final answer
+            let cap = budget as usize * 2 + 2;
+            options.max_steps = Some(options.max_steps.map_or(cap, |steps| steps.min(cap)));
+        }
 
         let runtime = AgentRuntime::new(
             spec,
@@ -217,15 +336,7 @@ impl SpecialistAgent {
                     if let Ok(mut count) = tool_count_flag.lock() {
                         *count += 1;
                     }
-                    if matches!(
-                        call.function.name.as_str(),
-                        "write_file"
-                            | "append_file"
-                            | "mkdir"
-                            | "move_file"
-                            | "delete_file"
-                            | "execute_command"
-                    ) {
+                    if WRITE_LIKE_TOOLS.contains(&call.function.name.as_str()) {
                         if let Ok(mut flag) = tool_flag.lock() {
                             *flag = true;
                         }
@@ -312,4 +423,56 @@ mod tests {
         assert!(tools.contains(&"delete_file"));
         assert!(tools.contains(&"execute_command"));
     }
+
+    #[test]
+    fn declared_roles_override_builtin_defaults() {
+        let mut runtime = RuntimeConfig::default();
+        runtime.specialists = vec![
+            SpecialistDefinition {
+                id: "security_reviewer".to_string(),
+                tools: vec!["read_file".to_string(), "git_diff".to_string()],
+                airlock_ceiling: Some(0),
+                ..Default::default()
+            },
+            SpecialistDefinition {
+                id: "verifier".to_string(),
+                system_prompt: "Check the migration only.".to_string(),
+                ..Default::default()
+            },
+        ];
+
+        let reviewer = SpecialistAgent::definition(
+            &runtime,
+            &SpecialistRole::from("security_reviewer".to_string()),
+        );
+        assert_eq!(reviewer.tools, vec!["read_file", "git_diff"]);
+        assert!(reviewer.system_prompt.contains("Security Reviewer"));
+
+        let verifier = SpecialistAgent::definition(&runtime, &SpecialistRole::Verifier);
+        assert_eq!(verifier.system_prompt, "Check the migration only.");
+        assert!(verifier.tools.iter().any(|tool| tool == "git_diff"));
+    }
+
+    #[test]
+    fn only_writers_get_checkouts_when_isolation_is_on() {
+        let mut runtime = RuntimeConfig::default();
+        assert!(!SpecialistAgent::needs_checkout(
+            &runtime,
+            &SpecialistRole::Executor
+        ));
+
+        runtime.delegation.isolate_writers = true;
+        assert!(SpecialistAgent::needs_checkout(
+            &runtime,
+            &SpecialistRole::Executor
+        ));
+        assert!(!SpecialistAgent::needs_checkout(
+            &runtime,
+            &SpecialistRole::Verifier
+        ));
+        assert!(!SpecialistAgent::needs_checkout(
+            &runtime,
+            &SpecialistRole::Research
+        ));
+    }
 }
@@ -2,6 +2,7 @@ use super::events::{
     AgentEvent, SpecialistCompletedPayload, SpecialistEventPayload, SpecialistFailedPayload,
     SupervisorSummaryPayload,
 };
+use super::planner::{self, PlanLimits, PlannedLanes, ReplanContext};
 use super::protocol::{
     SpecialistAssignment, SpecialistOutcome, SpecialistRole, SpecialistStatus, SupervisorMessage,
     SupervisorPlan,
@@ -12,9 +13,11 @@ use super::specialist::SpecialistAgent;
 use crate::ai::agent::memory::AgentMemory;
 use crate::ai::router::IntelligentRouter;
 use crate::ai::specs::manifest::{AgentSpec, DelegationPolicy, RuntimeConfig, RuntimeMode};
+use crate::services::run_checkpoints::{RunProgress, RunRecorder};
+use crate::services::worktree::{self, Worktree};
 use crate::services::{agent_kill_switch::AgentKillSwitch, airlock::AirlockService, SkillExecutor};
 use chrono::Utc;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
@@ -29,6 +32,21 @@ pub struct SupervisorAgent {
     pub airlock_service: Arc<Option<AirlockService>>,
     pub kill_switch: Option<AgentKillSwitch>,
     pub runtime_registry: Option<Arc<RuntimeRegistry>>,
+    pub recorder: Option<RunRecorder>,
+}
+
+/// Checkpointed state of a supervisor run: the plan so far, the round being
+/// executed and every lane that has finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorProgress {
+    run_id: String,
+    /// All rounds merged, as the synthesis step sees them.
+    plan: SupervisorPlan,
+    round: SupervisorPlan,
+    lanes: LaneState,
+    /// Index into `lanes.failures` where the current round begins.
+    round_start: usize,
+    replans: usize,
 }
 
 #[derive(Clone, Serialize)]
@@ -48,6 +66,8 @@ impl SupervisorAgent {
     const MAX_DEPENDENCY_CONTEXT_CHARS: usize = 4 * 1024;
     const DEFAULT_PARALLEL_LANE_CAP: usize = 2;
     const MAX_SYNTHESIS_CONTEXT_CHARS: usize = 14 * 1024;
+    /// Follow-up plans allowed after a failed or rejected round.
+    const MAX_REPLANS: usize = 1;
 
     fn should_use_research(input: &str) -> bool {
         let input = input.to_ascii_lowercase();
@@ -220,6 +240,8 @@ impl SupervisorAgent {
                 spawn_reason: Some("parallel_file_review".to_string()),
                 depth: 1,
                 depends_on: vec![],
+                success_criteria: None,
+                tool_budget: None,
             });
         }
 
@@ -234,6 +256,8 @@ impl SupervisorAgent {
                 spawn_reason: Some("parallel_instruction_markdown_review".to_string()),
                 depth: 1,
                 depends_on: vec![],
+                success_criteria: None,
+                tool_budget: None,
             });
         }
 
@@ -247,8 +271,142 @@ impl SupervisorAgent {
         )
     }
 
-    fn build_plan(&self, input: &str) -> SupervisorPlan {
-        Self::build_plan_for_runtime(&self.spec.runtime, input)
+    fn model(&self) -> String {
+        self.options
+            .model
+            .clone()
+            .or_else(|| self.spec.model.clone())
+            .unwrap_or_else(|| "gemini-2.0-flash".to_string())
+    }
+
+    fn max_lanes(runtime: &RuntimeConfig) -> usize {
+        if Self::is_parallel_mode(runtime) {
+            runtime
+                .max_specialists
+                .clamp(1, Self::DEFAULT_PARALLEL_LANE_CAP as u8) as usize
+        } else {
+            runtime.max_specialists.clamp(1, 4) as usize
+        }
+    }
+
+    fn plan_limits(runtime: &RuntimeConfig) -> PlanLimits {
+        let max_assignments = Self::max_lanes(runtime).min(runtime.delegation.max_threads as usize);
+        PlanLimits {
+            max_assignments,
+            max_depth: max_assignments as u8,
+            chains_only: false,
+            allow_verifier: runtime.verification_required && max_assignments >= 2,
+            custom_roles: PlanLimits::custom_roles(runtime),
+        }
+    }
+
+    /// Ask the model planner for lanes. Parallel mode still stays on the main
+    /// agent unless delegation was requested explicitly, and a planner error
+    /// falls back to the keyword heuristics.
+    async fn build_plan(&self, input: &str) -> SupervisorPlan {
+        let runtime = &self.spec.runtime;
+        if Self::is_parallel_mode(runtime) && !Self::should_delegate_explicitly(input) {
+            return Self::build_plan_for_runtime(runtime, input);
+        }
+        match planner::request_plan(
+            &self.router,
+            &self.model(),
+            input,
+            &Self::plan_limits(runtime),
+            None,
+        )
+        .await
+        {
+            Ok(planned) => Self::plan_from_lanes(
+                runtime,
+                "Plan specialist lanes with the model planner",
+                planned,
+            ),
+            Err(error) => {
+                tracing::warn!("Supervisor planner failed, using heuristic plan: {}", error);
+                Self::build_plan_for_runtime(runtime, input)
+            }
+        }
+    }
+
+    fn plan_from_lanes(
+        runtime: &RuntimeConfig,
+        step: &str,
+        planned: PlannedLanes,
+    ) -> SupervisorPlan {
+        let verification_required = planned
+            .assignments
+            .iter()
+            .any(|assignment| assignment.role == SpecialistRole::Verifier);
+        let mut steps = vec![step.to_string()];
+        steps.extend(planned.assignments.iter().map(|assignment| {
+            if assignment.depends_on.is_empty() {
+                format!(
+                    "{} ({}): {}",
+                    assignment.agent_id,
+                    assignment.role.as_str(),
+                    assignment.title
+                )
+            } else {
+                format!(
+                    "{} ({}) after {}: {}",
+                    assignment.agent_id,
+                    assignment.role.as_str(),
+                    assignment.depends_on.join(", "),
+                    assignment.title
+                )
+            }
+        }));
+        let mut plan =
+            Self::assemble_plan(runtime, steps, planned.assignments, verification_required);
+        if !planned.summary.is_empty() {
+            plan.summary = planned.summary;
+        }
+        plan
+    }
+
+    fn assemble_plan(
+        runtime: &RuntimeConfig,
+        steps: Vec<String>,
+        assignments: Vec<SpecialistAssignment>,
+        verification_required: bool,
+    ) -> SupervisorPlan {
+        let max_specialists = Self::max_lanes(runtime);
+        SupervisorPlan {
+            summary: if Self::is_parallel_mode(runtime) {
+                format!(
+                    "Parallel Supervisor active · {} lane(s) max",
+                    max_specialists
+                )
+            } else {
+                "Supervisor orchestration activated".to_string()
+            },
+            steps,
+            assignments,
+            verification_required,
+            mode: Some(if Self::is_parallel_mode(runtime) {
+                "parallel_supervisor".to_string()
+            } else {
+                "supervisor".to_string()
+            }),
+            delegation_policy: Some(
+                match runtime.delegation.policy {
+                    DelegationPolicy::ExplicitOnly => "explicit_only",
+                    DelegationPolicy::HybridIntentGated => "hybrid_intent_gated",
+                    DelegationPolicy::AutoHeuristic => "auto_heuristic",
+                }
+                .to_string(),
+            ),
+            max_depth: Some(runtime.delegation.max_depth),
+            max_threads: Some(runtime.delegation.max_threads),
+            max_parallel_subagents: Some(max_specialists as u8),
+            internal_coordination_language: Some("english".to_string()),
+            final_response_language_mode: Some(if Self::is_parallel_mode(runtime) {
+                "english".to_string()
+            } else {
+                runtime.language_policy.final_response_language_mode.clone()
+            }),
+        }
     }
 
     fn build_plan_for_runtime(runtime: &RuntimeConfig, input: &str) -> SupervisorPlan {
@@ -304,6 +462,8 @@ impl SupervisorAgent {
                     spawn_reason: Some("research_required".to_string()),
                     depth: 1,
                     depends_on: vec![],
+                    success_criteria: None,
+                    tool_budget: None,
                 });
                 steps.push("Research Agent reviews code and supporting references".to_string());
             }
@@ -331,6 +491,8 @@ impl SupervisorAgent {
                 spawn_reason: Some("execution_required".to_string()),
                 depth: 1,
                 depends_on: executor_depends_on.clone(),
+                success_criteria: None,
+                tool_budget: None,
             });
             steps.push(if executor_depends_on.is_empty() {
                 "Executor Agent performs workspace actions while Research Agent gathers parallel context".to_string()
@@ -350,6 +512,8 @@ impl SupervisorAgent {
                 spawn_reason: Some("memory_required".to_string()),
                 depth: 1,
                 depends_on: vec![],
+                success_criteria: None,
+                tool_budget: None,
             });
             steps.push(
                 "Memory Scribe persists facts and user context to long-term memory".to_string(),
@@ -359,13 +523,7 @@ impl SupervisorAgent {
         let has_executor = base_assignments
             .iter()
             .any(|assignment| assignment.role == SpecialistRole::Executor);
-        let max_specialists = if Self::is_parallel_mode(runtime) {
-            runtime
-                .max_specialists
-                .clamp(1, Self::DEFAULT_PARALLEL_LANE_CAP as u8) as usize
-        } else {
-            runtime.max_specialists.clamp(1, 4) as usize
-        };
+        let max_specialists = Self::max_lanes(runtime);
         let verification_required =
             runtime.verification_required && has_executor && max_specialists >= 2;
 
@@ -414,52 +572,20 @@ impl SupervisorAgent {
                 spawn_reason: Some("verification_required".to_string()),
                 depth: 1,
                 depends_on: vec!["executor-1".to_string()],
+                success_criteria: None,
+                tool_budget: None,
             });
             steps.push(
                 "Verifier Agent validates the resulting state with read-only checks".to_string(),
             );
         }
 
-        SupervisorPlan {
-            summary: if Self::is_parallel_mode(runtime) {
-                format!(
-                    "Parallel Supervisor active · {} lane(s) max",
-                    max_specialists
-                )
-            } else {
-                "Supervisor orchestration activated".to_string()
-            },
-            steps,
-            assignments,
-            verification_required,
-            mode: Some(if Self::is_parallel_mode(runtime) {
-                "parallel_supervisor".to_string()
-            } else {
-                "supervisor".to_string()
-            }),
-            delegation_policy: Some(
-                match runtime.delegation.policy {
-                    DelegationPolicy::ExplicitOnly => "explicit_only",
-                    DelegationPolicy::HybridIntentGated => "hybrid_intent_gated",
-                    DelegationPolicy::AutoHeuristic => "auto_heuristic",
-                }
-                .to_string(),
-            ),
-            max_depth: Some(runtime.delegation.max_depth),
-            max_threads: Some(runtime.delegation.max_threads),
-            max_parallel_subagents: Some(max_specialists as u8),
-            internal_coordination_language: Some("english".to_string()),
-            final_response_language_mode: Some(if Self::is_parallel_mode(runtime) {
-                "english".to_string()
-            } else {
-                runtime.language_policy.final_response_language_mode.clone()
-            }),
-        }
+        Self::assemble_plan(runtime, steps, assignments, verification_required)
     }
 
     fn should_run_verifier(outcomes: &[SpecialistOutcome]) -> bool {
         outcomes.iter().any(|outcome| {
-            outcome.role == SpecialistRole::Executor && outcome.used_write_like_tools
+            outcome.role != SpecialistRole::Verifier && outcome.used_write_like_tools
         })
     }
 
@@ -864,7 +990,7 @@ Return one concise, polished, user-facing answer.\n\
     {
         let mut single_spec = self.spec.clone();
         single_spec.runtime.mode = RuntimeMode::Single;
-        let runtime = AgentRuntime::new(
+        let mut runtime = AgentRuntime::new(
             single_spec,
             self.options.clone(),
             self.router.clone(),
@@ -874,74 +1000,52 @@ Return one concise, polished, user-facing answer.\n\
             self.kill_switch.clone(),
             self.runtime_registry.clone(),
         );
+        if let Some(recorder) = self.recorder.clone() {
+            runtime = runtime.with_recorder(recorder);
+        }
         runtime.run_single(input, on_event).await
     }
 
-    pub async fn run<F>(&self, input: &str, on_event: F) -> Result<String, String>
-    where
-        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
-    {
-        let run_id = uuid::Uuid::new_v4().to_string();
-        let plan = self.build_plan(input);
-        if plan.assignments.is_empty() {
-            return self.fallback_to_single(input, on_event).await;
-        }
-        let assignments_for_registry: Vec<(String, SpecialistRole, Vec<String>)> = plan
-            .assignments
-            .iter()
-            .map(|assignment| {
-                (
-                    assignment.agent_id.clone(),
-                    assignment.role.clone(),
-                    assignment.depends_on.clone(),
-                )
-            })
-            .collect();
-
-        if let Some(registry) = self.runtime_registry.as_ref() {
-            registry
-                .start_supervisor_run(&run_id, &assignments_for_registry)
-                .await;
-            registry.update_supervisor_status(&run_id, "planning").await;
-        }
-
-        on_event(AgentEvent::SupervisorPlanCreated(plan.clone()));
-
-        let (tx, rx) = mpsc::channel::<SupervisorMessage>(128);
-        let on_event_arc = Arc::new(on_event.clone());
-        let registry_for_events = self.runtime_registry.clone();
-        let emitter = tokio::spawn(Self::emit_messages(rx, on_event_arc, registry_for_events));
-        Self::emit_pending_assignments(&run_id, &tx, &plan.assignments).await;
-
-        let mut outcomes = Vec::new();
-        let mut completed_outcomes: HashMap<String, SpecialistOutcome> = HashMap::new();
-        let mut failures: Vec<LaneFailure> = Vec::new();
-        let mut failed_agent_ids: HashSet<String> = HashSet::new();
+    /// Run the plan's non-verifier lanes in dependency order, at most
+    /// `max_parallel` at a time. Lanes whose dependencies failed are skipped.
+    /// Isolated lanes are merged back as they finish, so dependents see
+    /// their changes in the workspace.
+    async fn execute_lanes(
+        &self,
+        input: &str,
+        progress: &mut SupervisorProgress,
+        tx: &mpsc::Sender<SupervisorMessage>,
+    ) {
+        let run_id = progress.run_id.clone();
+        let run_id = run_id.as_str();
         let max_parallel = if Self::is_parallel_mode(&self.spec.runtime) {
             self.spec.runtime.max_specialists.clamp(1, 2) as usize
         } else {
             self.spec.runtime.max_specialists.clamp(1, 4) as usize
         };
-
-        if let Some(registry) = self.runtime_registry.as_ref() {
-            registry.update_supervisor_status(&run_id, "running").await;
-        }
-        let mut remaining: HashMap<String, SpecialistAssignment> = plan
+        // Lanes that finished before an interruption are not run again.
+        let mut remaining: HashMap<String, SpecialistAssignment> = progress
+            .round
             .assignments
-            .clone()
-            .into_iter()
+            .iter()
             .filter(|a| a.role != SpecialistRole::Verifier)
-            .map(|assignment| (assignment.agent_id.clone(), assignment))
+            .filter(|a| {
+                !progress.lanes.completed.contains_key(&a.agent_id)
+                    && !progress.lanes.failed_ids.contains(&a.agent_id)
+            })
+            .map(|assignment| (assignment.agent_id.clone(), assignment.clone()))
             .collect();
         let mut running_ids: HashSet<String> = HashSet::new();
         let mut join_set: JoinSet<(SpecialistAssignment, Result<SpecialistOutcome, String>)> =
             JoinSet::new();
+        let mut checkouts: HashMap<String, Worktree> = HashMap::new();
 
         loop {
+            let lanes = &mut progress.lanes;
             let skipped_ids: Vec<String> = remaining
                 .values()
                 .filter(|assignment| {
-                    !Self::failed_dependencies(assignment, &failed_agent_ids).is_empty()
+                    !Self::failed_dependencies(assignment, &lanes.failed_ids).is_empty()
                 })
                 .map(|assignment| assignment.agent_id.clone())
                 .collect();
@@ -949,19 +1053,19 @@ Return one concise, polished, user-facing answer.\n\
             for skipped_id in skipped_ids {
                 if let Some(assignment) = remaining.remove(&skipped_id) {
                     let failed_dependencies =
-                        Self::failed_dependencies(&assignment, &failed_agent_ids);
+                        Self::failed_dependencies(&assignment, &lanes.failed_ids);
                     let error = format!(
                         "Skipped because required prior lane(s) failed: {}",
                         failed_dependencies.join(", ")
                     );
-                    failures.push(LaneFailure {
+                    lanes.failures.push(LaneFailure {
                         agent_id: assignment.agent_id.clone(),
                         role: assignment.role.clone(),
                         error: error.clone(),
                     });
-                    failed_agent_ids.insert(assignment.agent_id.clone());
+                    lanes.failed_ids.insert(assignment.agent_id.clone());
                     tx.send(SupervisorMessage::SpecialistFailed {
-                        run_id: run_id.clone(),
+                        run_id: run_id.to_string(),
                         agent_id: assignment.agent_id,
                         role: assignment.role,
                         error,
@@ -980,7 +1084,7 @@ Return one concise, polished, user-facing answer.\n\
                 .values()
                 .filter(|assignment| {
                     !running_ids.contains(&assignment.agent_id)
-                        && Self::missing_dependencies(assignment, &completed_outcomes).is_empty()
+                        && Self::missing_dependencies(assignment, &lanes.completed).is_empty()
                 })
                 .map(|assignment| assignment.agent_id.clone())
                 .collect();
@@ -993,7 +1097,7 @@ Return one concise, polished, user-facing answer.\n\
                     continue;
                 };
                 running_ids.insert(assignment.agent_id.clone());
-                let specialist = SpecialistAgent::new(
+                let mut specialist = SpecialistAgent::new(
                     assignment.role.clone(),
                     self.spec.clone(),
                     self.options.clone(),
@@ -1003,10 +1107,16 @@ Return one concise, polished, user-facing answer.\n\
                     self.airlock_service.clone(),
                     self.kill_switch.clone(),
                 );
+                if SpecialistAgent::needs_checkout(&self.spec.runtime, &assignment.role) {
+                    if let Some(checkout) = SpecialistAgent::open_checkout(&self.options).await {
+                        specialist = specialist.in_checkout(&checkout);
+                        checkouts.insert(assignment.agent_id.clone(), checkout);
+                    }
+                }
                 let specialist_input =
-                    Self::build_specialist_input(input, &assignment, &completed_outcomes);
+                    Self::build_specialist_input(input, &assignment, &lanes.completed);
                 let tx_clone = tx.clone();
-                let run_id_clone = run_id.clone();
+                let run_id_clone = run_id.to_string();
                 let assignment_clone = assignment.clone();
                 join_set.spawn(async move {
                     let result = specialist
@@ -1027,7 +1137,7 @@ Return one concise, polished, user-facing answer.\n\
                 }
 
                 for assignment in remaining.into_values() {
-                    let unresolved = Self::missing_dependencies(&assignment, &completed_outcomes);
+                    let unresolved = Self::missing_dependencies(&assignment, &lanes.completed);
                     let error = if unresolved.is_empty() {
                         "Lane could not be scheduled".to_string()
                     } else {
@@ -1036,14 +1146,14 @@ Return one concise, polished, user-facing answer.\n\
                             unresolved.join(", ")
                         )
                     };
-                    failures.push(LaneFailure {
+                    lanes.failures.push(LaneFailure {
                         agent_id: assignment.agent_id.clone(),
                         role: assignment.role.clone(),
                         error: error.clone(),
                     });
-                    failed_agent_ids.insert(assignment.agent_id.clone());
+                    lanes.failed_ids.insert(assignment.agent_id.clone());
                     tx.send(SupervisorMessage::SpecialistFailed {
-                        run_id: run_id.clone(),
+                        run_id: run_id.to_string(),
                         agent_id: assignment.agent_id,
                         role: assignment.role,
                         error,
@@ -1060,28 +1170,39 @@ Return one concise, polished, user-facing answer.\n\
             }
 
             if let Some(joined) = join_set.join_next().await {
+                let joined = match joined {
+                    Ok((assignment, result)) => match checkouts.remove(&assignment.agent_id) {
+                        Some(checkout) => {
+                            Ok((assignment, self.merge_checkout(checkout, result).await))
+                        }
+                        None => Ok((assignment, result)),
+                    },
+                    Err(join_error) => Err(join_error),
+                };
                 match joined {
                     Ok((assignment, Ok(outcome))) => {
                         running_ids.remove(&assignment.agent_id);
                         tx.send(SupervisorMessage::SpecialistCompleted {
-                            run_id: run_id.clone(),
+                            run_id: run_id.to_string(),
                             outcome: outcome.clone(),
                         })
                         .await
                         .ok();
-                        completed_outcomes.insert(assignment.agent_id.clone(), outcome.clone());
-                        outcomes.push(outcome);
+                        lanes
+                            .completed
+                            .insert(assignment.agent_id.clone(), outcome.clone());
+                        lanes.outcomes.push(outcome);
                     }
                     Ok((assignment, Err(error))) => {
                         running_ids.remove(&assignment.agent_id);
-                        failed_agent_ids.insert(assignment.agent_id.clone());
-                        failures.push(LaneFailure {
+                        lanes.failed_ids.insert(assignment.agent_id.clone());
+                        lanes.failures.push(LaneFailure {
                             agent_id: assignment.agent_id.clone(),
                             role: assignment.role.clone(),
                             error: error.clone(),
                         });
                         tx.send(SupervisorMessage::SpecialistFailed {
-                            run_id: run_id.clone(),
+                            run_id: run_id.to_string(),
                             agent_id: assignment.agent_id,
                             role: assignment.role,
                             error,
@@ -1096,183 +1217,455 @@ Return one concise, polished, user-facing answer.\n\
                     }
                     Err(join_error) => {
                         let error = format!("Specialist task join failure: {}", join_error);
-                        failures.push(LaneFailure {
+                        lanes.failures.push(LaneFailure {
                             agent_id: "unknown".to_string(),
                             role: SpecialistRole::Executor,
                             error,
                         });
                     }
                 }
+                self.record(progress).await;
             }
         }
 
-        if plan.verification_required && Self::should_run_verifier(&outcomes) {
-            if let Some(registry) = self.runtime_registry.as_ref() {
-                registry
-                    .update_supervisor_status(&run_id, "verifying")
-                    .await;
+        for checkout in checkouts.into_values() {
+            worktree::discard(checkout).await;
+        }
+    }
+
+    /// Bring an isolated lane's changes back into the workspace. A failed
+    /// lane or a cancelled run is discarded unmerged; conflicts fail the
+    /// lane and keep its checkout for review.
+    async fn merge_checkout(
+        &self,
+        checkout: Worktree,
+        result: Result<SpecialistOutcome, String>,
+    ) -> Result<SpecialistOutcome, String> {
+        let cancelled = self
+            .kill_switch
+            .as_ref()
+            .is_some_and(|kill_switch| kill_switch.is_triggered());
+        let mut outcome = match result {
+            Ok(outcome) if !cancelled => outcome,
+            other => {
+                worktree::discard(checkout).await;
+                return other;
             }
-            on_event(AgentEvent::SpecialistStatusChanged(
-                SpecialistEventPayload {
-                    run_id: run_id.clone(),
-                    agent_id: "verifier-1".to_string(),
-                    role: SpecialistRole::Verifier,
-                    status: SpecialistStatus::Verifying,
-                    parent_agent_id: None,
-                    branch_id: Some("verification".to_string()),
-                    spawn_reason: Some("verification_required".to_string()),
-                    depth: Some(1),
-                    depends_on: vec!["executor-1".to_string()],
-                    detail: Some("Verifier Agent validating resulting state".to_string()),
-                    active_tool: None,
-                    started_at_ms: None,
-                    finished_at_ms: None,
-                    tool_count: Some(0),
-                    write_like_used: Some(false),
-                },
-            ));
-            if let Some(assignment) = plan
-                .assignments
-                .iter()
-                .find(|assignment| assignment.role == SpecialistRole::Verifier)
-                .cloned()
-            {
-                let missing_dependencies =
-                    Self::missing_dependencies(&assignment, &completed_outcomes);
-                if !missing_dependencies.is_empty() {
-                    let error = format!(
-                        "Skipped because required prior lane(s) did not complete successfully: {}",
-                        missing_dependencies.join(", ")
-                    );
-                    failures.push(LaneFailure {
-                        agent_id: assignment.agent_id.clone(),
-                        role: SpecialistRole::Verifier,
-                        error: error.clone(),
-                    });
-                    tx.send(SupervisorMessage::SpecialistFailed {
-                        run_id: run_id.clone(),
-                        agent_id: assignment.agent_id,
-                        role: SpecialistRole::Verifier,
-                        error,
-                        depends_on: assignment.depends_on,
-                        started_at_ms: None,
-                        finished_at_ms: Some(Utc::now().timestamp_millis()),
-                        tool_count: Some(0),
-                        write_like_used: Some(false),
-                    })
-                    .await
-                    .ok();
-                    drop(tx);
-                    let _ = emitter.await;
-
-                    let summary = self
-                        .synthesize_with_main_agent(&plan, &outcomes, &failures, on_event.clone())
-                        .await?;
-                    on_event(AgentEvent::SupervisorSummary(SupervisorSummaryPayload {
-                        run_id: run_id.clone(),
-                        summary: summary.clone(),
-                    }));
-                    if let Some(registry) = self.runtime_registry.as_ref() {
-                        registry.finish_supervisor_run(&run_id, "failed").await;
-                    }
-                    return Ok(summary);
-                }
+        };
+        let report = worktree::merge_back(checkout).await?;
+        if !report.conflicts.is_empty() {
+            return Err(report.summary());
+        }
+        outcome
+            .response
+            .push_str(&format!("\n\n{}", report.summary()));
+        Ok(outcome)
+    }
 
-                let verifier = SpecialistAgent::new(
-                    SpecialistRole::Verifier,
-                    self.spec.clone(),
-                    self.options.clone(),
-                    self.router.clone(),
-                    self.skills.clone(),
-                    self.memory.clone(),
-                    self.airlock_service.clone(),
-                    self.kill_switch.clone(),
-                );
-                let verifier_input =
-                    Self::build_specialist_input(input, &assignment, &completed_outcomes);
-                match verifier
-                    .run(&run_id, assignment.clone(), verifier_input, tx.clone())
-                    .await
-                {
-                    Ok(outcome) => {
-                        tx.send(SupervisorMessage::SpecialistCompleted {
-                            run_id: run_id.clone(),
-                            outcome: outcome.clone(),
-                        })
-                        .await
-                        .ok();
-                        completed_outcomes.insert(assignment.agent_id.clone(), outcome.clone());
-                        outcomes.push(outcome);
-                    }
-                    Err(error) => {
-                        failures.push(LaneFailure {
-                            agent_id: assignment.agent_id.clone(),
-                            role: SpecialistRole::Verifier,
-                            error: error.clone(),
-                        });
-                        tx.send(SupervisorMessage::SpecialistFailed {
-                            run_id: run_id.clone(),
-                            agent_id: assignment.agent_id,
-                            role: SpecialistRole::Verifier,
-                            error,
-                            depends_on: assignment.depends_on,
-                            started_at_ms: None,
-                            finished_at_ms: Some(Utc::now().timestamp_millis()),
-                            tool_count: None,
-                            write_like_used: None,
-                        })
-                        .await
-                        .ok();
-                    }
-                }
+    /// Run the verifier lane of `plan` when an executor wrote
+    /// something. Returns the verifier's rejection, if any; a verifier that
+    /// errors or cannot start is recorded as a lane failure instead.
+    async fn verify<F>(
+        &self,
+        input: &str,
+        progress: &mut SupervisorProgress,
+        tx: &mpsc::Sender<SupervisorMessage>,
+        on_event: &F,
+    ) -> Option<String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let run_id = progress.run_id.as_str();
+        let plan = &progress.round;
+        let lanes = &mut progress.lanes;
+        let assignment = plan
+            .assignments
+            .iter()
+            .find(|assignment| assignment.role == SpecialistRole::Verifier)
+            .cloned()?;
+        if !plan.verification_required {
+            return None;
+        }
+        // Already ran before the run was interrupted.
+        if let Some(outcome) = lanes.completed.get(&assignment.agent_id) {
+            return planner::verifier_rejection(&outcome.response);
+        }
+        if lanes.failed_ids.contains(&assignment.agent_id) {
+            return None;
+        }
+
+        if !Self::should_run_verifier(&lanes.outcomes) {
+            tx.send(SupervisorMessage::SpecialistStatus {
+                run_id: run_id.to_string(),
+                agent_id: assignment.agent_id.clone(),
+                role: SpecialistRole::Verifier,
+                status: SpecialistStatus::Completed,
+                detail: Some("Verification skipped: no lane used write-like actions".to_string()),
+                active_tool: None,
+                depends_on: assignment.depends_on.clone(),
+                started_at_ms: None,
+                finished_at_ms: Some(Utc::now().timestamp_millis()),
+                tool_count: Some(0),
+                write_like_used: Some(false),
+            })
+            .await
+            .ok();
+            return None;
+        }
+
+        if let Some(registry) = self.runtime_registry.as_ref() {
+            registry.update_supervisor_status(run_id, "verifying").await;
+        }
+        on_event(AgentEvent::SpecialistStatusChanged(
+            SpecialistEventPayload {
+                run_id: run_id.to_string(),
+                agent_id: assignment.agent_id.clone(),
+                role: SpecialistRole::Verifier,
+                status: SpecialistStatus::Verifying,
+                parent_agent_id: None,
+                branch_id: assignment.branch_id.clone(),
+                spawn_reason: assignment.spawn_reason.clone(),
+                depth: Some(assignment.depth.max(1)),
+                depends_on: assignment.depends_on.clone(),
+                detail: Some("Verifier Agent validating resulting state".to_string()),
+                active_tool: None,
+                started_at_ms: None,
+                finished_at_ms: None,
+                tool_count: Some(0),
+                write_like_used: Some(false),
+            },
+        ));
+
+        let missing_dependencies = Self::missing_dependencies(&assignment, &lanes.completed);
+        if !missing_dependencies.is_empty() {
+            let error = format!(
+                "Skipped because required prior lane(s) did not complete successfully: {}",
+                missing_dependencies.join(", ")
+            );
+            lanes.failures.push(LaneFailure {
+                agent_id: assignment.agent_id.clone(),
+                role: SpecialistRole::Verifier,
+                error: error.clone(),
+            });
+            lanes.failed_ids.insert(assignment.agent_id.clone());
+            tx.send(SupervisorMessage::SpecialistFailed {
+                run_id: run_id.to_string(),
+                agent_id: assignment.agent_id,
+                role: SpecialistRole::Verifier,
+                error,
+                depends_on: assignment.depends_on,
+                started_at_ms: None,
+                finished_at_ms: Some(Utc::now().timestamp_millis()),
+                tool_count: Some(0),
+                write_like_used: Some(false),
+            })
+            .await
+            .ok();
+            return None;
+        }
+
+        let verifier = SpecialistAgent::new(
+            SpecialistRole::Verifier,
+            self.spec.clone(),
+            self.options.clone(),
+            self.router.clone(),
+            self.skills.clone(),
+            self.memory.clone(),
+            self.airlock_service.clone(),
+            self.kill_switch.clone(),
+        );
+        let verifier_input = Self::build_specialist_input(input, &assignment, &lanes.completed);
+        match verifier
+            .run(run_id, assignment.clone(), verifier_input, tx.clone())
+            .await
+        {
+            Ok(outcome) => {
+                tx.send(SupervisorMessage::SpecialistCompleted {
+                    run_id: run_id.to_string(),
+                    outcome: outcome.clone(),
+                })
+                .await
+                .ok();
+                let rejection = planner::verifier_rejection(&outcome.response);
+                lanes
+                    .completed
+                    .insert(assignment.agent_id.clone(), outcome.clone());
+                lanes.outcomes.push(outcome);
+                rejection
             }
-        } else if plan.verification_required {
-            if let Some(assignment) = plan
-                .assignments
-                .iter()
-                .find(|assignment| assignment.role == SpecialistRole::Verifier)
-            {
-                tx.send(SupervisorMessage::SpecialistStatus {
-                    run_id: run_id.clone(),
+            Err(error) => {
+                lanes.failures.push(LaneFailure {
                     agent_id: assignment.agent_id.clone(),
                     role: SpecialistRole::Verifier,
-                    status: SpecialistStatus::Completed,
-                    detail: Some(
-                        "Verification skipped: executor completed without write-like actions"
-                            .to_string(),
-                    ),
-                    active_tool: None,
-                    depends_on: assignment.depends_on.clone(),
+                    error: error.clone(),
+                });
+                lanes.failed_ids.insert(assignment.agent_id.clone());
+                tx.send(SupervisorMessage::SpecialistFailed {
+                    run_id: run_id.to_string(),
+                    agent_id: assignment.agent_id,
+                    role: SpecialistRole::Verifier,
+                    error,
+                    depends_on: assignment.depends_on,
                     started_at_ms: None,
                     finished_at_ms: Some(Utc::now().timestamp_millis()),
-                    tool_count: Some(0),
-                    write_like_used: Some(false),
+                    tool_count: None,
+                    write_like_used: None,
                 })
                 .await
                 .ok();
+                None
             }
         }
+    }
+
+    /// What a replan has to fix: lane failures of the latest round and a
+    /// verifier rejection.
+    fn round_problems(failures: &[LaneFailure], rejection: Option<&String>) -> Vec<String> {
+        failures
+            .iter()
+            .map(|failure| {
+                format!(
+                    "{} ({}) failed: {}",
+                    failure.agent_id,
+                    failure.role.as_str(),
+                    failure.error
+                )
+            })
+            .chain(rejection.cloned())
+            .collect()
+    }
+
+    /// Ask the planner for follow-up lanes; `None` when it declines or fails.
+    async fn replan(
+        &self,
+        input: &str,
+        plan: &SupervisorPlan,
+        lanes: &LaneState,
+        problems: &[String],
+    ) -> Option<SupervisorPlan> {
+        let context = ReplanContext {
+            previous: plan,
+            completed: &lanes.outcomes,
+            problems,
+        };
+        let runtime = &self.spec.runtime;
+        match planner::request_plan(
+            &self.router,
+            &self.model(),
+            input,
+            &Self::plan_limits(runtime),
+            Some(&context),
+        )
+        .await
+        {
+            Ok(planned) if !planned.assignments.is_empty() => Some(Self::plan_from_lanes(
+                runtime,
+                "Replan after failed or rejected lanes",
+                planned,
+            )),
+            Ok(_) => None,
+            Err(error) => {
+                tracing::warn!("Supervisor replanning failed: {}", error);
+                None
+            }
+        }
+    }
+
+    pub async fn run<F>(&self, input: &str, on_event: F) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let plan = self.build_plan(input).await;
+        if plan.assignments.is_empty() {
+            return self.fallback_to_single(input, on_event).await;
+        }
+        let progress = SupervisorProgress {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            round: plan.clone(),
+            plan,
+            lanes: LaneState::default(),
+            round_start: 0,
+            replans: 0,
+        };
+        self.record(&progress).await;
+        self.drive(input, progress, on_event).await
+    }
+
+    /// Continue a checkpointed run: finished lanes keep their outcomes and
+    /// only the rest of the current round (and any replans) still runs.
+    pub async fn resume<F>(
+        &self,
+        input: &str,
+        progress: SupervisorProgress,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        on_event(AgentEvent::Status(format!(
+            "Resuming supervisor run with {} finished lane(s)",
+            progress.lanes.outcomes.len() + progress.lanes.failures.len()
+        )));
+        self.drive(input, progress, on_event).await
+    }
+
+    async fn drive<F>(
+        &self,
+        input: &str,
+        mut progress: SupervisorProgress,
+        on_event: F,
+    ) -> Result<String, String>
+    where
+        F: Fn(AgentEvent) + Send + Sync + 'static + Clone,
+    {
+        let run_id = progress.run_id.clone();
+        let assignments_for_registry: Vec<(String, SpecialistRole, Vec<String>)> = progress
+            .plan
+            .assignments
+            .iter()
+            .map(|assignment| {
+                (
+                    assignment.agent_id.clone(),
+                    assignment.role.clone(),
+                    assignment.depends_on.clone(),
+                )
+            })
+            .collect();
+
+        if let Some(registry) = self.runtime_registry.as_ref() {
+            registry
+                .start_supervisor_run(&run_id, &assignments_for_registry)
+                .await;
+            registry.update_supervisor_status(&run_id, "planning").await;
+        }
+
+        on_event(AgentEvent::SupervisorPlanCreated(progress.plan.clone()));
+
+        let (tx, rx) = mpsc::channel::<SupervisorMessage>(128);
+        let on_event_arc = Arc::new(on_event.clone());
+        let registry_for_events = self.runtime_registry.clone();
+        let emitter = tokio::spawn(Self::emit_messages(rx, on_event_arc, registry_for_events));
+        Self::emit_pending_assignments(&run_id, &tx, &progress.plan.assignments).await;
+        Self::emit_finished_lanes(&run_id, &tx, &progress).await;
+
+        if let Some(registry) = self.runtime_registry.as_ref() {
+            registry.update_supervisor_status(&run_id, "running").await;
+        }
+        self.execute_lanes(input, &mut progress, &tx).await;
+        let mut rejection = self.verify(input, &mut progress, &tx, &on_event).await;
+        self.record(&progress).await;
+
+        // Replan when a lane failed or the verifier rejected the work
+        while progress.replans < Self::MAX_REPLANS {
+            let problems = Self::round_problems(
+                &progress.lanes.failures[progress.round_start..],
+                rejection.as_ref(),
+            );
+            if problems.is_empty() {
+                break;
+            }
+            if let Some(registry) = self.runtime_registry.as_ref() {
+                registry.update_supervisor_status(&run_id, "planning").await;
+            }
+            let Some(followup) = self
+                .replan(input, &progress.plan, &progress.lanes, &problems)
+                .await
+            else {
+                break;
+            };
+            progress.replans += 1;
+            progress.round_start = progress.lanes.failures.len();
+            on_event(AgentEvent::SupervisorPlanCreated(followup.clone()));
+            Self::emit_pending_assignments(&run_id, &tx, &followup.assignments).await;
+            if let Some(registry) = self.runtime_registry.as_ref() {
+                registry.update_supervisor_status(&run_id, "running").await;
+            }
+            progress.plan.steps.extend(followup.steps.iter().cloned());
+            progress
+                .plan
+                .assignments
+                .extend(followup.assignments.iter().cloned());
+            progress.round = followup;
+            self.record(&progress).await;
+            self.execute_lanes(input, &mut progress, &tx).await;
+            rejection = self.verify(input, &mut progress, &tx, &on_event).await;
+            self.record(&progress).await;
+        }
+        let succeeded = Self::round_problems(
+            &progress.lanes.failures[progress.round_start..],
+            rejection.as_ref(),
+        )
+        .is_empty();
 
         drop(tx);
         let _ = emitter.await;
 
+        let lanes = &progress.lanes;
         let summary = self
-            .synthesize_with_main_agent(&plan, &outcomes, &failures, on_event.clone())
+            .synthesize_with_main_agent(
+                &progress.plan,
+                &lanes.outcomes,
+                &lanes.failures,
+                on_event.clone(),
+            )
             .await?;
         on_event(AgentEvent::SupervisorSummary(SupervisorSummaryPayload {
             run_id: run_id.clone(),
             summary: summary.clone(),
         }));
         if let Some(registry) = self.runtime_registry.as_ref() {
-            let final_status = if failures.is_empty() {
-                "completed"
-            } else {
-                "failed"
-            };
+            let final_status = if succeeded { "completed" } else { "failed" };
             registry.finish_supervisor_run(&run_id, final_status).await;
         }
         Ok(summary)
     }
+
+    /// Replay lanes restored from a checkpoint so the DAG shows them as done.
+    async fn emit_finished_lanes(
+        run_id: &str,
+        tx: &mpsc::Sender<SupervisorMessage>,
+        progress: &SupervisorProgress,
+    ) {
+        for outcome in &progress.lanes.outcomes {
+            tx.send(SupervisorMessage::SpecialistCompleted {
+                run_id: run_id.to_string(),
+                outcome: outcome.clone(),
+            })
+            .await
+            .ok();
+        }
+        for failure in &progress.lanes.failures {
+            let Some(assignment) = progress
+                .plan
+                .assignments
+                .iter()
+                .find(|assignment| assignment.agent_id == failure.agent_id)
+            else {
+                continue;
+            };
+            tx.send(SupervisorMessage::SpecialistFailed {
+                run_id: run_id.to_string(),
+                agent_id: assignment.agent_id.clone(),
+                role: assignment.role.clone(),
+                error: failure.error.clone(),
+                depends_on: assignment.depends_on.clone(),
+                started_at_ms: None,
+                finished_at_ms: None,
+                tool_count: None,
+                write_like_used: None,
+            })
+            .await
+            .ok();
+        }
+    }
+
+    async fn record(&self, progress: &SupervisorProgress) {
+        if let Some(recorder) = self.recorder.as_ref() {
+            recorder
+                .record(RunProgress::Supervisor(Box::new(progress.clone())))
+                .await;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1289,6 +1682,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn planner_limits_follow_lane_caps() {
+        let mut runtime = test_runtime(2, true);
+        let limits = SupervisorAgent::plan_limits(&runtime);
+        assert_eq!(limits.max_assignments, 2);
+        assert!(limits.allow_verifier);
+        assert!(!limits.chains_only);
+
+        runtime.max_specialists = 1;
+        assert!(!SupervisorAgent::plan_limits(&runtime).allow_verifier);
+    }
+
     #[test]
     fn build_plan_prioritizes_executor_when_limited() {
         let runtime = test_runtime(2, true);
@@ -1394,6 +1799,8 @@ mod tests {
             spawn_reason: None,
             depth: 1,
             depends_on: vec!["research-1".to_string()],
+            success_criteria: None,
+            tool_budget: None,
         };
         let mut outcomes = HashMap::new();
         outcomes.insert(
@@ -1440,6 +1847,8 @@ mod tests {
                     spawn_reason: None,
                     depth: 1,
                     depends_on: vec![],
+                    success_criteria: None,
+                    tool_budget: None,
                 },
                 SpecialistAssignment {
                     agent_id: "executor-1".to_string(),
@@ -1451,6 +1860,8 @@ mod tests {
                     spawn_reason: None,
                     depth: 1,
                     depends_on: vec!["research-1".to_string()],
+                    success_criteria: None,
+                    tool_budget: None,
                 },
             ],
             verification_required: false,
@@ -1519,6 +1930,8 @@ mod tests {
             spawn_reason: None,
             depth: 1,
             depends_on: vec!["research-1".to_string()],
+            success_criteria: None,
+            tool_budget: None,
         };
 
         let missing = SupervisorAgent::missing_dependencies(&assignment, &HashMap::new());
@@ -1586,7 +1999,15 @@ mod tests {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct LaneState {
+    outcomes: Vec<SpecialistOutcome>,
+    completed: HashMap<String, SpecialistOutcome>,
+    failures: Vec<LaneFailure>,
+    failed_ids: HashSet<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct LaneFailure {
     agent_id: String,
     role: SpecialistRole,
@@ -118,6 +118,9 @@ mod tests {
             workspace_memory_context: None,
             workspace_memory_root: None,
             workspace_memory_enabled: false,
+            run_id: None,
+            chat_id: None,
+            checkout_root: None,
         };
 
         // We can't easily run() without a real SkillExecutor/Router,
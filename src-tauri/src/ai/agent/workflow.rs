@@ -8,11 +8,13 @@ use crate::ai::agent::runtime_events::{
 };
 use crate::ai::provider_types::{
     ChatCompletionRequest, FunctionCall, ProviderStreamUsage, ProviderToolCallDelta, ToolCall,
+    UsageScope,
 };
 use crate::ai::router::IntelligentRouter;
 use crate::ai::specs::manifest::AgentSpec;
 use crate::models::neural::ToolAccessPolicy;
 use crate::services::agent_kill_switch::AgentKillSwitch;
+use crate::services::run_checkpoints::{RunProgress, RunRecorder};
 use crate::services::SkillExecutor;
 use chrono::Utc;
 use schemars::JsonSchema;
@@ -25,16 +27,21 @@ use tokio::sync::RwLock;
 const MAX_MODEL_MESSAGE_BYTES: usize = 95 * 1024;
 pub const CANCELLED_RUN_MESSAGE: &str = "Execution cancelled.";
 pub(crate) const LAST_EXECUTED_TOOL_SIGNATURE_CONTEXT_KEY: &str = "last_executed_tool_signature";
+/// Signature of tool calls that were in flight when a resumed run stopped.
+pub(crate) const INTERRUPTED_TOOL_CALLS_CONTEXT_KEY: &str = "interrupted_tool_calls";
 pub const FILESYSTEM_TOOL_NAMES: &[&str] = &[
     "read_file",
     "read_many_files",
     "write_file",
     "append_file",
+    "edit_file",
+    "apply_patch",
     "list_files",
     "list_files_detailed",
     "file_exists",
     "get_file_info",
     "search_files",
+    "search_code",
     "read_file_chunk",
     "mkdir",
     "delete_file",
@@ -72,9 +79,20 @@ fn emit_usage_event(
         prompt_tokens: usage.prompt_tokens,
         completion_tokens: usage.completion_tokens,
         total_tokens: usage.total_tokens,
+        cached_prompt_tokens: usage.cached_prompt_tokens,
+        reasoning_tokens: usage.reasoning_tokens,
     }));
 }
 
+fn emit_served_event(
+    on_event: &Arc<dyn Fn(AgentEvent) + Send + Sync>,
+    served: Option<&crate::ai::provider_types::ProviderServed>,
+) {
+    if let Some(served) = served {
+        on_event(AgentEvent::ProviderServed(served.clone()));
+    }
+}
+
 pub(crate) fn tool_call_signature(calls: &[ToolCall]) -> String {
     calls.iter()
         .map(|call| format!("{}::{}", call.function.name, call.function.arguments.trim()))
@@ -101,6 +119,7 @@ async fn request_plaintext_followup(
         .map_err(|e| format!("ThinkStep Recovery Failed: {}", e))?;
 
     emit_usage_event(&on_event, recovery.model.clone(), &recovery.usage);
+    emit_served_event(&on_event, recovery.served_by.as_ref());
 
     Ok((
         recovery.content.unwrap_or_default(),
@@ -173,6 +192,12 @@ pub struct AgentState {
     pub spec: Arc<AgentSpec>,
     pub airlock_service: Arc<Option<crate::services::airlock::AirlockService>>,
     pub kill_switch: Option<AgentKillSwitch>,
+    /// Run and chat identifiers stamped on tool commands for Airlock grants.
+    pub run_id: Option<String>,
+    pub chat_id: Option<String>,
+    /// Checkout the run's file and shell tools resolve against instead of
+    /// the registered workspace.
+    pub checkout_root: Option<String>,
 }
 
 impl AgentState {
@@ -195,8 +220,22 @@ impl AgentState {
             spec,
             airlock_service,
             kill_switch,
+            run_id: None,
+            chat_id: None,
+            checkout_root: None,
         }
     }
+
+    pub fn with_run_scope(mut self, run_id: Option<String>, chat_id: Option<String>) -> Self {
+        self.run_id = run_id;
+        self.chat_id = chat_id;
+        self
+    }
+
+    pub fn with_checkout_root(mut self, checkout_root: Option<String>) -> Self {
+        self.checkout_root = checkout_root;
+        self
+    }
 }
 
 /// Result of a workflow step execution
@@ -225,6 +264,19 @@ pub trait WorkflowStep: Debug + Send + Sync {
     ) -> Result<StepResult, String>;
 }
 
+/// Where a single-agent run stands after a completed step: enough to rebuild
+/// its `AgentState` and carry on after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowProgress {
+    pub messages: Vec<AgentMessage>,
+    pub context: HashMap<String, String>,
+    /// Step to run next; `None` once the workflow has finished.
+    pub next_step: Option<String>,
+    pub steps_completed: usize,
+    /// Prior chat history messages included in `messages` after the system prompt.
+    pub history_len: usize,
+}
+
 /// The Workflow Graph Container
 pub struct Workflow {
     #[allow(dead_code)] // @TODO Configuration usage in steps
@@ -233,6 +285,9 @@ pub struct Workflow {
     pub options: RuntimeOptions,
     pub steps: HashMap<String, Box<dyn WorkflowStep>>,
     pub start_step: String,
+    recorder: Option<RunRecorder>,
+    history_len: usize,
+    steps_completed: usize,
 }
 
 impl Workflow {
@@ -242,9 +297,40 @@ impl Workflow {
             options,
             steps: HashMap::new(),
             start_step,
+            recorder: None,
+            history_len: 0,
+            steps_completed: 0,
         }
     }
 
+    /// Checkpoint the run through `recorder` after every step.
+    pub fn with_recorder(mut self, recorder: Option<RunRecorder>, history_len: usize) -> Self {
+        self.recorder = recorder;
+        self.history_len = history_len;
+        self
+    }
+
+    /// Count steps a resumed run already spent against its step budget.
+    pub fn resume_after(mut self, steps_completed: usize) -> Self {
+        self.steps_completed = steps_completed;
+        self
+    }
+
+    async fn checkpoint(&self, state: &AgentState, next_step: Option<String>, steps: usize) {
+        let Some(recorder) = self.recorder.as_ref() else {
+            return;
+        };
+        recorder
+            .record(RunProgress::Workflow(WorkflowProgress {
+                messages: state.messages.clone(),
+                context: state.context.clone(),
+                next_step,
+                steps_completed: steps,
+                history_len: self.history_len,
+            }))
+            .await;
+    }
+
     pub fn add_step(&mut self, step: Box<dyn WorkflowStep>) {
         self.steps.insert(step.id(), step);
     }
@@ -260,7 +346,7 @@ impl Workflow {
     {
         let mut state = initial_state;
         let mut current_step_id = Some(self.start_step.clone());
-        let mut steps_count = 0;
+        let mut steps_count = self.steps_completed;
         const DEFAULT_MAX_STEPS: usize = 50;
         const ABSOLUTE_MAX_STEPS: usize = 200;
         let max_steps = self
@@ -268,6 +354,8 @@ impl Workflow {
             .max_steps
             .unwrap_or(DEFAULT_MAX_STEPS)
             .clamp(4, ABSOLUTE_MAX_STEPS);
+        self.checkpoint(&state, current_step_id.clone(), steps_count)
+            .await;
 
         while let Some(step_id) = current_step_id {
             if state
@@ -334,6 +422,8 @@ Please narrow the task or ask me to continue with a focused next step.]",
             // Transition
             current_step_id = result.next_step;
             steps_count += 1;
+            self.checkpoint(&state, current_step_id.clone(), steps_count)
+                .await;
         }
 
         Ok(state)
@@ -409,6 +499,8 @@ impl WorkflowStep for ThinkStep {
                         source: "agent_conversation".to_string(),
                         workspace_id: state.workspace_id.clone(),
                         timestamp: chrono::Utc::now().timestamp(),
+                        run_id: state.run_id.clone(),
+                        chat_id: state.chat_id.clone(),
                     })
                     .await;
             }
@@ -461,11 +553,27 @@ impl WorkflowStep for ThinkStep {
             presence_penalty: None,
             stop: None,
             reasoning_effort: self.reasoning_effort.clone(),
+            usage_scope: Some(UsageScope {
+                workspace_id: Some(state.workspace_id.clone()),
+                agent_spec_id: Some(state.spec.id.clone()),
+                run_id: state.run_id.clone(),
+            }),
             ..Default::default()
         };
 
         // 3. Call Router — providers with tool-call streaming can keep the turn live.
         let router_guard = self.router.read().await;
+        if let Some(scope) = request.usage_scope.as_ref() {
+            if let Some(check) = router_guard.check_budgets(scope).await {
+                for status in check.warnings() {
+                    on_event(AgentEvent::BudgetWarning(status.clone()));
+                }
+                if let Some(status) = check.blocking() {
+                    on_event(AgentEvent::BudgetExceeded(status.clone()));
+                    return Err(format!("Budget exceeded: {}", status.describe()));
+                }
+            }
+        }
         let selected_capabilities = router_guard.selected_provider_capabilities(&request).await;
         let supports_tool_call_streaming = self.allow_streaming
             && selected_capabilities
@@ -546,6 +654,9 @@ impl WorkflowStep for ThinkStep {
                         }
                     }
                     RuntimeStreamEvent::Raw(_) => {}
+                    RuntimeStreamEvent::ProviderServed(served) => {
+                        event_clone(AgentEvent::ProviderServed(served));
+                    }
                 });
 
             router_guard
@@ -613,6 +724,7 @@ impl WorkflowStep for ThinkStep {
                 .map_err(|e| format!("ThinkStep Failed: {}", e))?;
 
             emit_usage_event(&event_fn, response.model.clone(), &response.usage);
+            emit_served_event(&event_fn, response.served_by.as_ref());
 
             let mut content = response.content.clone().unwrap_or_default();
             let mut resolved_tool_calls = response.tool_calls.clone();
@@ -810,6 +922,9 @@ mod tests {
             workspace_memory_context: None,
             workspace_memory_root: None,
             workspace_memory_enabled: false,
+            run_id: None,
+            chat_id: None,
+            checkout_root: None,
         };
 
         let mut workflow = Workflow::new(spec.clone(), options, "start".to_string());
@@ -11,6 +11,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Usage analytics service
+#[derive(Debug)]
 pub struct UsageAnalytics {
     /// Usage data per provider
     usage_data: Arc<RwLock<HashMap<ProviderId, ProviderUsage>>>,
@@ -232,6 +233,7 @@ mod tests {
             prompt_tokens: 100,
             completion_tokens: 50,
             total_tokens: 150,
+            ..Default::default()
         };
 
         analytics.record_usage(&provider_id, usage).await;
@@ -250,6 +252,7 @@ mod tests {
             prompt_tokens: 100,
             completion_tokens: 50,
             total_tokens: 150,
+            ..Default::default()
         };
 
         analytics.record_usage(&provider_id, usage).await;
@@ -1,5 +1,6 @@
 // Rainy MaTE - macOS Keychain Integration
-// Secure storage for API keys using security-framework
+// Secure storage for API keys using security-framework, with an encrypted
+// credential file on platforms without a supported keychain
 
 #[cfg(test)]
 use std::collections::HashMap;
@@ -49,8 +50,7 @@ impl KeychainManager {
 
         #[cfg(all(not(test), not(target_os = "macos")))]
         {
-            let _ = api_key;
-            Err("Keychain not supported on this platform".to_string())
+            credentials_file::store(&account, api_key)
         }
     }
 
@@ -93,7 +93,7 @@ impl KeychainManager {
 
         #[cfg(all(not(test), not(target_os = "macos")))]
         {
-            Err("Keychain not supported on this platform".to_string())
+            credentials_file::get(&account)
         }
     }
 
@@ -133,7 +133,158 @@ impl KeychainManager {
 
         #[cfg(all(not(test), not(target_os = "macos")))]
         {
-            Err("Keychain not supported on this platform".to_string())
+            credentials_file::delete(&account)
+        }
+    }
+}
+
+/// Re-seal the credential file under a rotated vault master key. Returns the
+/// previous file contents so a failed rotation can put them back.
+pub fn reencrypt_credentials_file(
+    old_key: &[u8],
+    new_key: &[u8],
+) -> Result<Option<Vec<u8>>, String> {
+    #[cfg(all(not(test), not(target_os = "macos")))]
+    {
+        credentials_file::reencrypt(old_key, new_key)
+    }
+
+    #[cfg(any(test, target_os = "macos"))]
+    {
+        let _ = (old_key, new_key);
+        Ok(None)
+    }
+}
+
+/// Put back credential file contents returned by `reencrypt_credentials_file`.
+pub fn restore_credentials_file(backup: &[u8]) {
+    #[cfg(all(not(test), not(target_os = "macos")))]
+    credentials_file::restore(backup);
+
+    #[cfg(any(test, target_os = "macos"))]
+    let _ = backup;
+}
+
+/// Credential store for platforms without a supported keychain: one JSON map of
+/// account → secret, sealed with the vault master key of the configured key provider.
+#[cfg(all(not(test), not(target_os = "macos")))]
+mod credentials_file {
+    use crate::services::memory_vault::crypto::{decrypt_bytes, encrypt_bytes};
+    use crate::services::memory_vault::key_provider::{configured_provider, write_private_file};
+    use crate::services::settings::SettingsManager;
+    use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, OnceLock};
+
+    const FILE_NAME: &str = "credentials.enc";
+    const SCOPE: &str = "keychain";
+    const ENTRY: &str = "credentials-v1";
+
+    #[derive(Serialize, Deserialize)]
+    struct SealedCredentials {
+        nonce: String,
+        ciphertext: String,
+    }
+
+    /// Serializes read-modify-write cycles on the file.
+    fn file_lock() -> &'static Mutex<()> {
+        static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+        LOCK.get_or_init(|| Mutex::new(()))
+    }
+
+    fn path() -> PathBuf {
+        SettingsManager::new().data_dir().join(FILE_NAME)
+    }
+
+    fn read_map(path: &Path, key: &[u8]) -> Result<HashMap<String, String>, String> {
+        let Ok(json) = std::fs::read(path) else {
+            return Ok(HashMap::new());
+        };
+        let sealed: SealedCredentials =
+            serde_json::from_slice(&json).map_err(|e| format!("Corrupt credential file: {}", e))?;
+        let decode = |field: &str| {
+            BASE64_STANDARD
+                .decode(field.as_bytes())
+                .map_err(|e| format!("Corrupt credential file: {}", e))
+        };
+        let plaintext = decrypt_bytes(
+            key,
+            SCOPE,
+            ENTRY,
+            &decode(&sealed.ciphertext)?,
+            &decode(&sealed.nonce)?,
+        )?;
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Corrupt credential file: {}", e))
+    }
+
+    fn write_map(path: &Path, key: &[u8], map: &HashMap<String, String>) -> Result<(), String> {
+        let plaintext = serde_json::to_vec(map)
+            .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
+        let payload = encrypt_bytes(key, SCOPE, ENTRY, &plaintext)?;
+        let sealed = SealedCredentials {
+            nonce: BASE64_STANDARD.encode(payload.nonce),
+            ciphertext: BASE64_STANDARD.encode(payload.ciphertext),
+        };
+        let json = serde_json::to_vec(&sealed)
+            .map_err(|e| format!("Failed to serialize credentials: {}", e))?;
+        write_private_file(path, &json)
+    }
+
+    fn update(account: &str, value: Option<&str>) -> Result<(), String> {
+        let _guard = file_lock()
+            .lock()
+            .map_err(|_| "Credential file lock poisoned".to_string())?;
+        let key = configured_provider().get_or_create_master_key()?;
+        let path = path();
+        let mut map = read_map(&path, &key)?;
+        match value {
+            Some(value) => {
+                map.insert(account.to_string(), value.to_string());
+            }
+            None => {
+                if map.remove(account).is_none() {
+                    return Ok(());
+                }
+            }
+        }
+        write_map(&path, &key, &map)
+    }
+
+    pub fn get(account: &str) -> Result<Option<String>, String> {
+        let path = path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let key = configured_provider().get_or_create_master_key()?;
+        Ok(read_map(&path, &key)?.remove(account))
+    }
+
+    pub fn store(account: &str, value: &str) -> Result<(), String> {
+        update(account, Some(value))
+    }
+
+    pub fn delete(account: &str) -> Result<(), String> {
+        update(account, None)
+    }
+
+    pub fn reencrypt(old_key: &[u8], new_key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let _guard = file_lock()
+            .lock()
+            .map_err(|_| "Credential file lock poisoned".to_string())?;
+        let path = path();
+        let Ok(backup) = std::fs::read(&path) else {
+            return Ok(None);
+        };
+        let map = read_map(&path, old_key)?;
+        write_map(&path, new_key, &map)?;
+        Ok(Some(backup))
+    }
+
+    pub fn restore(backup: &[u8]) {
+        if let Err(e) = write_private_file(&path(), backup) {
+            tracing::error!("Failed to restore credential file: {}", e);
         }
     }
 }
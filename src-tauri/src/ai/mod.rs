@@ -15,6 +15,8 @@ pub mod provider_trait;
 pub mod provider_types;
 pub mod providers;
 pub mod router;
+pub mod structured_output;
+pub mod tokenizer;
 
 // PHASE 4: Unified Model System
 pub mod mode_selector;
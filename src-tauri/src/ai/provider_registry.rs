@@ -106,12 +106,11 @@ impl ProviderRegistry {
         let latency = start.elapsed().as_millis() as u64;
 
         // Update stats
-        let mut provider_mut = self.providers.get_mut(id).unwrap();
         let tokens = result
             .as_ref()
             .map(|r| r.usage.total_tokens as u64)
             .unwrap_or(0);
-        provider_mut.update_stats(result.is_ok(), latency, tokens);
+        provider.update_stats(result.is_ok(), latency, tokens);
 
         result
     }
@@ -128,12 +127,11 @@ impl ProviderRegistry {
         let latency = start.elapsed().as_millis() as u64;
 
         // Update stats
-        let mut provider_mut = self.providers.get_mut(id).unwrap();
         let tokens = result
             .as_ref()
             .map(|r| r.usage.total_tokens as u64)
             .unwrap_or(0);
-        provider_mut.update_stats(result.is_ok(), latency, tokens);
+        provider.update_stats(result.is_ok(), latency, tokens);
 
         result
     }
@@ -147,14 +145,14 @@ impl ProviderRegistry {
             .providers
             .get(id)
             .ok_or_else(|| AIError::ProviderNotFound(id.to_string()))?;
-        Ok(provider.stats().clone())
+        Ok(provider.stats())
     }
 
     /// Get all provider statistics
     pub fn get_all_stats(&self) -> Vec<(ProviderId, crate::ai::provider_trait::ProviderStats)> {
         self.providers
             .iter()
-            .map(|p| (p.key().clone(), p.value().stats().clone()))
+            .map(|p| (p.key().clone(), p.value().stats()))
             .collect()
     }
 
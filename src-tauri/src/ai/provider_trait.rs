@@ -136,6 +136,13 @@ pub trait AIProviderFactory: Send + Sync {
     fn validate_config(config: &crate::ai::provider_types::ProviderConfig) -> ProviderResult<()>;
 }
 
+/// Weight of the newest sample in the latency moving averages
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+/// Recent samples kept for percentile estimates
+const LATENCY_WINDOW: usize = 50;
+/// Samples required before a percentile is trusted
+const MIN_PERCENTILE_SAMPLES: usize = 5;
+
 /// Provider statistics
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ProviderStats {
@@ -147,10 +154,20 @@ pub struct ProviderStats {
     pub failed_requests: u64,
     /// Average latency in milliseconds
     pub avg_latency_ms: f64,
+    /// Exponentially weighted moving average of request latency
+    #[serde(default)]
+    pub ewma_latency_ms: f64,
+    /// Exponentially weighted moving average of time to first streamed token
+    #[serde(default)]
+    pub ewma_first_token_ms: f64,
     /// Total tokens used
     pub total_tokens: u64,
     /// Last request timestamp
     pub last_request: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(skip)]
+    recent_latency_ms: std::collections::VecDeque<u64>,
+    #[serde(skip)]
+    recent_first_token_ms: std::collections::VecDeque<u64>,
 }
 
 impl Default for ProviderStats {
@@ -160,26 +177,85 @@ impl Default for ProviderStats {
             successful_requests: 0,
             failed_requests: 0,
             avg_latency_ms: 0.0,
+            ewma_latency_ms: 0.0,
+            ewma_first_token_ms: 0.0,
             total_tokens: 0,
             last_request: None,
+            recent_latency_ms: std::collections::VecDeque::new(),
+            recent_first_token_ms: std::collections::VecDeque::new(),
+        }
+    }
+}
+
+impl ProviderStats {
+    /// Share of requests that succeeded; 1.0 before any request
+    pub fn success_rate(&self) -> f64 {
+        if self.total_requests == 0 {
+            1.0
+        } else {
+            self.successful_requests as f64 / self.total_requests as f64
         }
     }
+
+    /// 95th percentile request latency over the recent window
+    pub fn latency_p95_ms(&self) -> Option<u64> {
+        percentile(&self.recent_latency_ms, 0.95)
+    }
+
+    /// 95th percentile time to first token over the recent window
+    pub fn first_token_p95_ms(&self) -> Option<u64> {
+        percentile(&self.recent_first_token_ms, 0.95)
+    }
+
+    fn record_first_token(&mut self, latency_ms: u64) {
+        self.ewma_first_token_ms = ewma(
+            self.ewma_first_token_ms,
+            latency_ms,
+            self.recent_first_token_ms.is_empty(),
+        );
+        push_sample(&mut self.recent_first_token_ms, latency_ms);
+    }
 }
 
-/// Provider with statistics
+fn ewma(current: f64, sample: u64, first: bool) -> f64 {
+    if first {
+        sample as f64
+    } else {
+        LATENCY_EWMA_ALPHA * sample as f64 + (1.0 - LATENCY_EWMA_ALPHA) * current
+    }
+}
+
+fn push_sample(window: &mut std::collections::VecDeque<u64>, sample: u64) {
+    if window.len() == LATENCY_WINDOW {
+        window.pop_front();
+    }
+    window.push_back(sample);
+}
+
+fn percentile(window: &std::collections::VecDeque<u64>, quantile: f64) -> Option<u64> {
+    if window.len() < MIN_PERCENTILE_SAMPLES {
+        return None;
+    }
+    let mut sorted: Vec<u64> = window.iter().copied().collect();
+    sorted.sort_unstable();
+    let rank = ((sorted.len() as f64 * quantile).ceil() as usize).clamp(1, sorted.len());
+    Some(sorted[rank - 1])
+}
+
+/// Provider with statistics. Clones share the same live statistics.
 #[derive(Clone)]
 pub struct ProviderWithStats {
     /// The provider
     pub provider: Arc<dyn AIProvider>,
     /// Provider statistics
-    pub stats: ProviderStats,
+    stats: Arc<std::sync::RwLock<ProviderStats>>,
 }
 
 impl std::fmt::Debug for ProviderWithStats {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ProviderWithStats")
             .field("provider_id", self.provider.id())
-            .field("stats", &self.stats)
+            .field("stats", &self.stats())
             .finish()
     }
 }
@@ -189,28 +265,46 @@ impl ProviderWithStats {
     pub fn new(provider: Arc<dyn AIProvider>) -> Self {
         Self {
             provider,
-            stats: ProviderStats::default(),
+            stats: Arc::new(std::sync::RwLock::new(ProviderStats::default())),
         }
     }
 
     /// Update statistics after a request
-    pub fn update_stats(&mut self, success: bool, latency_ms: u64, tokens: u64) {
-        self.stats.total_requests += 1;
+    pub fn update_stats(&self, success: bool, latency_ms: u64, tokens: u64) {
+        let mut stats = self.stats.write().unwrap_or_else(|e| e.into_inner());
+        stats.total_requests += 1;
         if success {
-            self.stats.successful_requests += 1;
+            stats.successful_requests += 1;
         } else {
-            self.stats.failed_requests += 1;
+            stats.failed_requests += 1;
         }
 
         // Update average latency
-        if self.stats.total_requests > 0 {
-            let total_latency = self.stats.avg_latency_ms * (self.stats.total_requests - 1) as f64;
-            self.stats.avg_latency_ms =
-                (total_latency + latency_ms as f64) / self.stats.total_requests as f64;
+        if stats.total_requests > 0 {
+            let total_latency = stats.avg_latency_ms * (stats.total_requests - 1) as f64;
+            stats.avg_latency_ms =
+                (total_latency + latency_ms as f64) / stats.total_requests as f64;
+        }
+        // Failures often return fast; only successful calls describe real latency
+        if success {
+            stats.ewma_latency_ms = ewma(
+                stats.ewma_latency_ms,
+                latency_ms,
+                stats.recent_latency_ms.is_empty(),
+            );
+            push_sample(&mut stats.recent_latency_ms, latency_ms);
         }
 
-        self.stats.total_tokens += tokens;
-        self.stats.last_request = Some(chrono::Utc::now());
+        stats.total_tokens += tokens;
+        stats.last_request = Some(chrono::Utc::now());
+    }
+
+    /// Record how long a streamed request took to produce its first token
+    pub fn record_first_token(&self, latency_ms: u64) {
+        self.stats
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .record_first_token(latency_ms);
     }
 
     /// Get the provider
@@ -218,8 +312,27 @@ impl ProviderWithStats {
         self.provider.as_ref()
     }
 
-    /// Get the statistics
-    pub fn stats(&self) -> &ProviderStats {
-        &self.stats
+    /// Snapshot of the statistics
+    pub fn stats(&self) -> ProviderStats {
+        self.stats.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_ewma_and_p95_track_successful_samples() {
+        let mut stats = ProviderStats::default();
+        assert_eq!(stats.first_token_p95_ms(), None);
+
+        for sample in [100, 100, 100, 100, 100, 100, 100, 100, 100, 1_000] {
+            stats.record_first_token(sample);
+        }
+        assert_eq!(stats.first_token_p95_ms(), Some(1_000));
+        // 0.2 * 1000 + 0.8 * 100
+        assert!((stats.ewma_first_token_ms - 280.0).abs() < 1e-9);
+        assert_eq!(stats.success_rate(), 1.0);
     }
 }
@@ -307,6 +307,17 @@ impl ChatMessage {
         }
     }
 
+    /// Create a new assistant message
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: MessageContent::Text(content.into()),
+            name: None,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
     /// Helper to get text content from message regardless of variant
     pub fn text(&self) -> String {
         match &self.content {
@@ -398,6 +409,10 @@ pub struct ChatCompletionRequest {
     pub prompt_cache_retention: Option<String>,
     /// Whether to enforce JSON mode
     pub json_mode: bool,
+    /// JSON Schema the response must conform to; mapped to each provider's
+    /// native structured-output mechanism where one exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<schemars::schema::RootSchema>,
     /// Optional reasoning effort / thinking level for compatible models
     pub reasoning_effort: Option<String>,
     /// Reasoning settings (bool/object depending on provider)
@@ -444,6 +459,21 @@ pub struct ChatCompletionRequest {
     pub context_management: Option<Vec<serde_json::Value>>,
     /// Truncation strategy (`auto` or `disabled`)
     pub truncation: Option<String>,
+    /// Who the call is billed to in the local usage ledger; never sent upstream
+    #[serde(skip)]
+    pub usage_scope: Option<UsageScope>,
+}
+
+/// Attribution for a model call, used for usage accounting and spend budgets.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageScope {
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub agent_spec_id: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
 }
 
 impl Default for ChatCompletionRequest {
@@ -469,6 +499,7 @@ impl Default for ChatCompletionRequest {
             provider_options: None,
             prompt_cache_retention: None,
             json_mode: false,
+            response_schema: None,
             reasoning_effort: None,
             reasoning: None,
             include_reasoning: None,
@@ -492,6 +523,7 @@ impl Default for ChatCompletionRequest {
             background: None,
             context_management: None,
             truncation: None,
+            usage_scope: None,
         }
     }
 }
@@ -515,10 +547,27 @@ pub struct ChatCompletionResponse {
     /// Provider-specific metadata for follow-up turns and diagnostics
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider_metadata: Option<serde_json::Value>,
+    /// Which provider the router dispatched this response to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<ProviderServed>,
+}
+
+/// Provider that actually served a routed request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderServed {
+    /// Provider ID
+    pub provider_id: String,
+    /// 1-based dispatch attempt that succeeded
+    pub attempt: u32,
+    /// Whether the winner was a hedged (second) request
+    pub hedged: bool,
+    /// Time to first streamed token, for streamed requests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_token_ms: Option<u64>,
 }
 
 /// Token usage information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TokenUsage {
     /// Prompt tokens
     pub prompt_tokens: u32,
@@ -526,6 +575,12 @@ pub struct TokenUsage {
     pub completion_tokens: u32,
     /// Total tokens
     pub total_tokens: u32,
+    /// Prompt tokens served from the provider's prompt cache (included in `prompt_tokens`)
+    #[serde(default)]
+    pub cached_prompt_tokens: u32,
+    /// Reasoning/thinking tokens (included in `completion_tokens`)
+    #[serde(default)]
+    pub reasoning_tokens: u32,
 }
 
 impl TokenUsage {
@@ -535,6 +590,7 @@ impl TokenUsage {
             prompt_tokens,
             completion_tokens,
             total_tokens: prompt_tokens + completion_tokens,
+            ..Default::default()
         }
     }
 }
@@ -580,6 +636,22 @@ pub struct ProviderStreamUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    #[serde(default)]
+    pub cached_prompt_tokens: u32,
+    #[serde(default)]
+    pub reasoning_tokens: u32,
+}
+
+impl ProviderStreamUsage {
+    pub fn to_token_usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+            total_tokens: self.total_tokens,
+            cached_prompt_tokens: self.cached_prompt_tokens,
+            reasoning_tokens: self.reasoning_tokens,
+        }
+    }
 }
 
 /// Partial tool call information emitted while the provider is still streaming arguments.
@@ -650,6 +722,10 @@ pub enum AIError {
     UnsupportedCapability(String),
     /// Configuration error
     Configuration(String),
+    /// Spend budget hard stop
+    BudgetExceeded(String),
+    /// Response did not conform to the requested schema
+    SchemaViolation(String),
     /// Internal error
     Internal(String),
 }
@@ -667,6 +743,8 @@ impl std::fmt::Display for AIError {
             AIError::ModelNotFound(msg) => write!(f, "Model not found: {}", msg),
             AIError::UnsupportedCapability(msg) => write!(f, "Unsupported capability: {}", msg),
             AIError::Configuration(msg) => write!(f, "Configuration error: {}", msg),
+            AIError::BudgetExceeded(msg) => write!(f, "Budget exceeded: {}", msg),
+            AIError::SchemaViolation(msg) => write!(f, "Schema violation: {}", msg),
             AIError::Internal(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -674,6 +752,79 @@ impl std::fmt::Display for AIError {
 
 impl std::error::Error for AIError {}
 
+/// Marker appended to rate-limit messages carrying the server's retry hint
+const RETRY_AFTER_MARKER: &str = "(retry after ";
+
+impl AIError {
+    /// Whether retrying the same request may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            AIError::RateLimit(_)
+                | AIError::APIError(_)
+                | AIError::NetworkError(_)
+                | AIError::Timeout(_)
+                | AIError::Internal(_)
+        )
+    }
+
+    /// Attach a `Retry-After` hint to a rate-limit error
+    pub fn with_retry_after(self, retry_after: Option<std::time::Duration>) -> Self {
+        match (self, retry_after) {
+            (AIError::RateLimit(msg), Some(delay)) => AIError::RateLimit(format!(
+                "{} {}{}ms)",
+                msg,
+                RETRY_AFTER_MARKER,
+                delay.as_millis()
+            )),
+            (error, _) => error,
+        }
+    }
+
+    /// Server-suggested wait before retrying a rate-limited request, from an
+    /// attached `Retry-After` hint or a "try again in 1.5s" style message
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        let AIError::RateLimit(msg) = self else {
+            return None;
+        };
+        if let Some(start) = msg.rfind(RETRY_AFTER_MARKER) {
+            let rest = &msg[start + RETRY_AFTER_MARKER.len()..];
+            let millis = rest.split("ms)").next()?.parse::<u64>().ok()?;
+            return Some(std::time::Duration::from_millis(millis));
+        }
+
+        let lower = msg.to_lowercase();
+        let start = lower.find("try again in ")? + "try again in ".len();
+        let rest = &lower[start..];
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let value = rest[..number_len].parse::<f64>().ok()?;
+        let seconds = if rest[number_len..].starts_with("ms") {
+            value / 1000.0
+        } else {
+            value
+        };
+        std::time::Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+/// Parse a `Retry-After` header given either as delay-seconds or an HTTP date
+pub fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<std::time::Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(std::time::Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
 /// Provider result type
 pub type ProviderResult<T> = Result<T, AIError>;
 
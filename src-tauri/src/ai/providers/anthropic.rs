@@ -3,10 +3,11 @@
 
 use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
 use crate::ai::provider_types::{
-    AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
-    EmbeddingResponse, ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId,
-    ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
+    retry_after_header, AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage,
+    EmbeddingRequest, EmbeddingResponse, ProviderCapabilities, ProviderConfig, ProviderHealth,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
 };
+use crate::ai::structured_output;
 use async_trait::async_trait;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -16,6 +17,8 @@ use std::sync::Arc;
 const ANTHROPIC_API_BASE: &str = "https://api.anthropic.com/v1";
 /// Anthropic API version
 const ANTHROPIC_API_VERSION: &str = "2023-06-01";
+/// Tool forced via `tool_choice` to carry schema-constrained responses
+const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
 
 /// Anthropic provider
 pub struct AnthropicProvider {
@@ -51,6 +54,10 @@ struct AnthropicChatRequest {
     stop_sequences: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
     stream: bool,
 }
 
@@ -67,6 +74,10 @@ struct AnthropicChatResponse {
 #[derive(Debug, Deserialize)]
 struct AnthropicContentBlock {
     text: Option<String>,
+    /// Tool name on `tool_use` blocks
+    name: Option<String>,
+    /// Tool arguments on `tool_use` blocks
+    input: Option<serde_json::Value>,
 }
 
 /// Anthropic token usage
@@ -74,6 +85,8 @@ struct AnthropicContentBlock {
 struct AnthropicUsage {
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
 }
 
 /// Anthropic streaming event
@@ -264,6 +277,17 @@ impl AIProvider for AnthropicProvider {
                 .or_else(|| Some(Self::default_max_tokens(&request.model))),
             temperature: request.temperature,
             top_p: request.top_p,
+            tools: request.response_schema.as_ref().map(|schema| {
+                vec![serde_json::json!({
+                    "name": STRUCTURED_OUTPUT_TOOL,
+                    "description": "Return the final answer as structured data.",
+                    "input_schema": structured_output::schema_value(schema),
+                })]
+            }),
+            tool_choice: request
+                .response_schema
+                .as_ref()
+                .map(|_| serde_json::json!({ "type": "tool", "name": STRUCTURED_OUTPUT_TOOL })),
             stop_sequences: request.stop,
             system,
             stream: false,
@@ -283,11 +307,12 @@ impl AIProvider for AnthropicProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: AnthropicError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let chat_response: AnthropicChatResponse = response
@@ -295,13 +320,22 @@ impl AIProvider for AnthropicProvider {
             .await
             .map_err(|e| AIError::APIError(format!("Failed to parse response: {}", e)))?;
 
-        // Extract text content from content blocks
-        let content = chat_response
+        // Structured output arrives as the forced tool call's input; otherwise
+        // extract text content from content blocks
+        let structured = chat_response
             .content
             .iter()
-            .filter_map(|block| block.text.clone())
-            .collect::<Vec<_>>()
-            .join("");
+            .find(|block| block.name.as_deref() == Some(STRUCTURED_OUTPUT_TOOL))
+            .and_then(|block| block.input.as_ref())
+            .map(|input| input.to_string());
+        let content = structured.unwrap_or_else(|| {
+            chat_response
+                .content
+                .iter()
+                .filter_map(|block| block.text.clone())
+                .collect::<Vec<_>>()
+                .join("")
+        });
 
         if content.is_empty() {
             return Err(AIError::APIError(
@@ -313,15 +347,22 @@ impl AIProvider for AnthropicProvider {
             content: Some(content),
             tool_calls: None,
             model: chat_response.model,
-            usage: TokenUsage {
-                prompt_tokens: chat_response.usage.input_tokens,
-                completion_tokens: chat_response.usage.output_tokens,
-                total_tokens: chat_response.usage.input_tokens + chat_response.usage.output_tokens,
+            usage: {
+                let usage = &chat_response.usage;
+                let prompt_tokens = usage.input_tokens + usage.cache_read_input_tokens;
+                TokenUsage {
+                    prompt_tokens,
+                    completion_tokens: usage.output_tokens,
+                    total_tokens: prompt_tokens + usage.output_tokens,
+                    cached_prompt_tokens: usage.cache_read_input_tokens,
+                    reasoning_tokens: 0,
+                }
             },
             finish_reason: chat_response
                 .stop_reason
                 .unwrap_or_else(|| "stop".to_string()),
             provider_metadata: None,
+            served_by: None,
         })
     }
 
@@ -348,6 +389,8 @@ impl AIProvider for AnthropicProvider {
             top_p: request.top_p,
             stop_sequences: request.stop,
             system,
+            tools: None,
+            tool_choice: None,
             stream: true,
         };
 
@@ -365,11 +408,12 @@ impl AIProvider for AnthropicProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: AnthropicError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let mut stream = response.bytes_stream();
@@ -7,9 +7,9 @@
 
 use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
 use crate::ai::provider_types::{
-    AIError, ChatCompletionRequest, ChatCompletionResponse, EmbeddingRequest, EmbeddingResponse,
-    MessageContent, ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId,
-    ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
+    retry_after_header, AIError, ChatCompletionRequest, ChatCompletionResponse, EmbeddingRequest,
+    EmbeddingResponse, MessageContent, ProviderCapabilities, ProviderConfig, ProviderHealth,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
 };
 use async_trait::async_trait;
 use futures::StreamExt;
@@ -124,7 +124,7 @@ struct GeminiThinkingConfig {
     thinking_level: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GeminiGenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -133,6 +133,10 @@ struct GeminiGenerationConfig {
     max_output_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     thinking_config: Option<GeminiThinkingConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -170,6 +174,8 @@ struct GeminiUsage {
     prompt_token_count: Option<u32>,
     candidates_token_count: Option<u32>,
     total_token_count: Option<u32>,
+    cached_content_token_count: Option<u32>,
+    thoughts_token_count: Option<u32>,
 }
 
 // SSE streaming chunk from Gemini
@@ -337,6 +343,7 @@ fn build_gemini_request_parts(
                 thinking_config: thinking_level.map(|lvl| GeminiThinkingConfig {
                     thinking_level: lvl,
                 }),
+                ..Default::default()
             })
         } else {
             None
@@ -512,6 +519,31 @@ fn clean_schema_for_gemini(value: &mut serde_json::Value) {
     }
 }
 
+/// Constrain the generation config to JSON matching `schema` via
+/// `responseMimeType` + `responseSchema`.
+fn apply_response_schema(
+    config: Option<GeminiGenerationConfig>,
+    schema: Option<&schemars::schema::RootSchema>,
+) -> ProviderResult<Option<GeminiGenerationConfig>> {
+    let Some(schema) = schema else {
+        return Ok(config);
+    };
+    let mut schema = serde_json::to_value(schema)
+        .map_err(|e| AIError::InvalidRequest(format!("Invalid response schema: {}", e)))?;
+    let definitions = schema
+        .as_object()
+        .and_then(|root| root.get("definitions"))
+        .and_then(|value| value.as_object())
+        .cloned();
+    resolve_local_refs(&mut schema, definitions.as_ref());
+    clean_schema_for_gemini(&mut schema);
+
+    let mut config = config.unwrap_or_default();
+    config.response_mime_type = Some("application/json".to_string());
+    config.response_schema = Some(schema);
+    Ok(Some(config))
+}
+
 /// Convert our internal Tool list to Gemini's functionDeclarations format.
 fn build_gemini_tools(
     tools: &[crate::ai::provider_types::Tool],
@@ -561,7 +593,9 @@ fn build_gemini_tools(
 
 #[cfg(test)]
 mod tests {
-    use super::{build_gemini_tools, clean_schema_for_gemini, resolve_local_refs};
+    use super::{
+        apply_response_schema, build_gemini_tools, clean_schema_for_gemini, resolve_local_refs,
+    };
     use crate::ai::provider_types::{FunctionDefinition, Tool};
 
     #[test]
@@ -632,6 +666,30 @@ mod tests {
         assert!(!json.contains("definitions"));
         assert!(!json.contains("$ref"));
     }
+
+    #[test]
+    fn response_schema_sets_json_mime_type_and_sanitized_schema() {
+        #[derive(schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Verdict {
+            approved: bool,
+            reasons: Vec<String>,
+            note: Option<String>,
+        }
+
+        let schema = schemars::schema_for!(Verdict);
+        let config = apply_response_schema(None, Some(&schema))
+            .expect("schema should convert")
+            .expect("config should be created");
+        let json = serde_json::to_value(&config).expect("serialize");
+        assert_eq!(json["responseMimeType"], "application/json");
+        assert_eq!(json["responseSchema"]["type"], "OBJECT");
+        assert_eq!(
+            json["responseSchema"]["properties"]["note"]["type"],
+            "STRING"
+        );
+        assert!(!json.to_string().contains("$schema"));
+    }
 }
 
 pub struct GeminiProviderAdapter {
@@ -728,6 +786,8 @@ impl AIProvider for GeminiProviderAdapter {
             request.max_tokens,
             thinking_level,
         );
+        let generation_config =
+            apply_response_schema(generation_config, request.response_schema.as_ref())?;
 
         let gemini_tools = match request.tools.as_deref() {
             Some(tools) => build_gemini_tools(tools)?,
@@ -754,11 +814,14 @@ impl AIProvider for GeminiProviderAdapter {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error_text = response.text().await.unwrap_or_default();
-            return Err(AIError::APIError(format!(
-                "Gemini API error {}: {}",
-                status, error_text
-            )));
+            let message = format!("Gemini API error {}: {}", status, error_text);
+            return Err(if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                AIError::RateLimit(message).with_retry_after(retry_after)
+            } else {
+                AIError::APIError(message)
+            });
         }
 
         let gemini_response: GeminiResponse = response
@@ -823,16 +886,21 @@ impl AIProvider for GeminiProviderAdapter {
             );
         }
 
-        let (prompt_tokens, completion_tokens, total_tokens) =
-            if let Some(usage) = gemini_response.usage_metadata {
-                (
-                    usage.prompt_token_count.unwrap_or(0),
-                    usage.candidates_token_count.unwrap_or(0),
-                    usage.total_token_count.unwrap_or(0),
-                )
-            } else {
-                (0, 0, 0)
-            };
+        // Gemini reports thinking tokens separately from candidate tokens;
+        // both are billed as output.
+        let usage = gemini_response
+            .usage_metadata
+            .map(|usage| {
+                let reasoning_tokens = usage.thoughts_token_count.unwrap_or(0);
+                TokenUsage {
+                    prompt_tokens: usage.prompt_token_count.unwrap_or(0),
+                    completion_tokens: usage.candidates_token_count.unwrap_or(0) + reasoning_tokens,
+                    total_tokens: usage.total_token_count.unwrap_or(0),
+                    cached_prompt_tokens: usage.cached_content_token_count.unwrap_or(0),
+                    reasoning_tokens,
+                }
+            })
+            .unwrap_or_default();
 
         Ok(ChatCompletionResponse {
             content: if text.is_empty() { None } else { Some(text) },
@@ -842,13 +910,10 @@ impl AIProvider for GeminiProviderAdapter {
                 Some(tool_calls)
             },
             model: request.model,
-            usage: TokenUsage {
-                prompt_tokens,
-                completion_tokens,
-                total_tokens,
-            },
+            usage,
             finish_reason,
             provider_metadata: None,
+            served_by: None,
         })
     }
 
@@ -874,6 +939,8 @@ impl AIProvider for GeminiProviderAdapter {
             request.max_tokens,
             thinking_level,
         );
+        let generation_config =
+            apply_response_schema(generation_config, request.response_schema.as_ref())?;
 
         let gemini_tools = match request.tools.as_deref() {
             Some(tools) => build_gemini_tools(tools)?,
@@ -900,11 +967,14 @@ impl AIProvider for GeminiProviderAdapter {
 
         let status = response.status();
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let text = response.text().await.unwrap_or_default();
-            return Err(AIError::APIError(format!(
-                "Gemini stream error {}: {}",
-                status, text
-            )));
+            let message = format!("Gemini stream error {}: {}", status, text);
+            return Err(if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                AIError::RateLimit(message).with_retry_after(retry_after)
+            } else {
+                AIError::APIError(message)
+            });
         }
 
         let mut stream = response.bytes_stream();
@@ -0,0 +1,1057 @@
+// Local / OpenAI-compatible Provider
+// Talks to any server exposing `/v1/chat/completions` and `/v1/embeddings`
+// (Ollama, llama.cpp server, vLLM, LM Studio) so agents can run fully offline
+
+use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
+use crate::ai::provider_types::{
+    retry_after_header, AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage,
+    EmbeddingRequest, EmbeddingResponse, FunctionCall, MessageContent, ProviderCapabilities,
+    ProviderConfig, ProviderEventCallback, ProviderHealth, ProviderId, ProviderResult,
+    ProviderStreamEvent, ProviderStreamUsage, ProviderToolCallDelta, ProviderToolLifecycleEvent,
+    ProviderToolLifecycleState, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
+    ToolCall, ToolChoice,
+};
+use crate::ai::structured_output;
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Default Ollama OpenAI-compatible endpoint
+const OLLAMA_API_BASE: &str = "http://localhost:11434/v1";
+/// Context window assumed when the config does not set `context_window`
+const DEFAULT_CONTEXT_TOKENS: u32 = 32_768;
+/// Output budget assumed when the config does not set `max_output_tokens`
+const DEFAULT_OUTPUT_TOKENS: u32 = 4096;
+
+/// Provider for self-hosted OpenAI-compatible servers
+pub struct LocalProvider {
+    /// Provider configuration
+    config: ProviderConfig,
+    /// HTTP client
+    client: reqwest::Client,
+    /// Base URL ending in `/v1`
+    base_url: String,
+    /// Models from the last successful listing, reused for capability checks
+    cached_models: tokio::sync::RwLock<Option<Vec<String>>>,
+}
+
+/// Chat response (OpenAI-compatible)
+#[derive(Debug, Deserialize)]
+struct LocalChatResponse {
+    #[serde(default)]
+    model: Option<String>,
+    choices: Vec<LocalChoice>,
+    #[serde(default)]
+    usage: Option<LocalUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalChoice {
+    message: LocalMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalMessage {
+    #[serde(default)]
+    content: Option<MessageContent>,
+    #[serde(default)]
+    tool_calls: Option<Vec<LocalToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalToolCall {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    function: LocalFunctionCall,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LocalFunctionCall {
+    #[serde(default)]
+    name: String,
+    /// Most servers send a JSON string; some send the arguments object itself.
+    #[serde(default)]
+    arguments: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LocalUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+    #[serde(default)]
+    total_tokens: Option<u32>,
+    #[serde(default)]
+    prompt_tokens_details: Option<LocalTokenDetails>,
+    #[serde(default)]
+    completion_tokens_details: Option<LocalTokenDetails>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LocalTokenDetails {
+    #[serde(default)]
+    cached_tokens: u32,
+    #[serde(default)]
+    reasoning_tokens: u32,
+}
+
+impl LocalUsage {
+    fn to_token_usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_tokens,
+            completion_tokens: self.completion_tokens,
+            total_tokens: self
+                .total_tokens
+                .unwrap_or(self.prompt_tokens + self.completion_tokens),
+            cached_prompt_tokens: self
+                .prompt_tokens_details
+                .as_ref()
+                .map_or(0, |details| details.cached_tokens),
+            reasoning_tokens: self
+                .completion_tokens_details
+                .as_ref()
+                .map_or(0, |details| details.reasoning_tokens),
+        }
+    }
+}
+
+/// Streaming chunk (OpenAI-compatible)
+#[derive(Debug, Deserialize)]
+struct LocalStreamChunk {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    choices: Vec<LocalStreamChoice>,
+    #[serde(default)]
+    usage: Option<LocalUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalStreamChoice {
+    #[serde(default)]
+    delta: LocalDelta,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct LocalDelta {
+    #[serde(default)]
+    content: Option<String>,
+    /// llama.cpp, vLLM and LM Studio name the reasoning field differently.
+    #[serde(default, alias = "reasoning")]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    tool_calls: Option<Vec<LocalToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalToolCallDelta {
+    #[serde(default)]
+    index: u32,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default, rename = "type")]
+    tool_type: Option<String>,
+    #[serde(default)]
+    function: Option<LocalFunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalFunctionDelta {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalEmbeddingResponse {
+    data: Vec<LocalEmbeddingData>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    usage: Option<LocalUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalEmbeddingData {
+    embedding: Vec<f32>,
+}
+
+/// `/v1/models` listing
+#[derive(Debug, Deserialize)]
+struct LocalModelList {
+    data: Vec<LocalModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalModelEntry {
+    id: String,
+}
+
+/// Ollama `/api/tags` listing
+#[derive(Debug, Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaModel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModel {
+    name: String,
+}
+
+/// Accumulates one streamed response and turns SSE payloads into provider events
+#[derive(Debug, Default)]
+struct LocalStreamState {
+    model: Option<String>,
+    tool_calls: BTreeMap<u32, ProviderToolCallDelta>,
+    finish_reason: Option<String>,
+}
+
+impl LocalStreamState {
+    /// Handle one `data:` payload. Returns false once the server sent `[DONE]`.
+    fn handle_data(&mut self, data: &str, callback: &ProviderEventCallback) -> bool {
+        if data == "[DONE]" {
+            return false;
+        }
+        let Ok(chunk) = serde_json::from_str::<LocalStreamChunk>(data) else {
+            return true;
+        };
+        if chunk.model.is_some() {
+            self.model = chunk.model;
+        }
+
+        for choice in chunk.choices {
+            if let Some(content) = choice.delta.content.filter(|c| !c.is_empty()) {
+                callback(ProviderStreamEvent::TextDelta(content));
+            }
+            if let Some(thought) = choice.delta.reasoning_content.filter(|t| !t.is_empty()) {
+                callback(ProviderStreamEvent::ThoughtDelta(thought));
+            }
+            for delta in choice.delta.tool_calls.unwrap_or_default() {
+                let (name, arguments) = delta
+                    .function
+                    .map(|f| (f.name, f.arguments))
+                    .unwrap_or_default();
+                let mapped = ProviderToolCallDelta {
+                    index: delta.index,
+                    id: delta.id,
+                    r#type: delta.tool_type,
+                    name,
+                    arguments,
+                };
+                let state = match self.tool_calls.get_mut(&delta.index) {
+                    Some(existing) => {
+                        if mapped.id.is_some() {
+                            existing.id = mapped.id.clone();
+                        }
+                        if mapped.name.is_some() {
+                            existing.name = mapped.name.clone();
+                        }
+                        if let Some(arguments) = mapped.arguments.as_ref() {
+                            existing
+                                .arguments
+                                .get_or_insert_with(String::new)
+                                .push_str(arguments);
+                        }
+                        ProviderToolLifecycleState::ArgumentsDelta
+                    }
+                    None => {
+                        self.tool_calls.insert(delta.index, mapped.clone());
+                        ProviderToolLifecycleState::Announced
+                    }
+                };
+                callback(ProviderStreamEvent::ToolCallDelta(
+                    ProviderToolLifecycleEvent {
+                        state,
+                        tool_call: mapped,
+                    },
+                ));
+            }
+            if let Some(reason) = choice.finish_reason {
+                self.finish_reason = Some(reason);
+            }
+        }
+
+        if let Some(usage) = chunk.usage {
+            let usage = usage.to_token_usage();
+            callback(ProviderStreamEvent::Usage(ProviderStreamUsage {
+                model: self.model.clone(),
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                total_tokens: usage.total_tokens,
+                cached_prompt_tokens: usage.cached_prompt_tokens,
+                reasoning_tokens: usage.reasoning_tokens,
+            }));
+        }
+        true
+    }
+
+    /// Emit `Ready` for every accumulated tool call and the completion event.
+    /// Some servers finish tool turns with `stop`, so readiness does not depend
+    /// on the finish reason.
+    fn finish(&mut self, callback: &ProviderEventCallback) {
+        for tool_call in self.tool_calls.values() {
+            callback(ProviderStreamEvent::ToolCallDelta(
+                ProviderToolLifecycleEvent {
+                    state: ProviderToolLifecycleState::Ready,
+                    tool_call: tool_call.clone(),
+                },
+            ));
+        }
+        let finish_reason = match self.finish_reason.take() {
+            Some(reason) if reason == "stop" && !self.tool_calls.is_empty() => {
+                Some("tool_calls".to_string())
+            }
+            other => other,
+        };
+        callback(ProviderStreamEvent::Completed { finish_reason });
+    }
+}
+
+impl LocalProvider {
+    /// Create a new local provider
+    pub fn new(config: ProviderConfig) -> ProviderResult<Self> {
+        let base_url = Self::normalize_base_url(
+            config
+                .base_url
+                .as_deref()
+                .filter(|url| !url.trim().is_empty())
+                .unwrap_or(OLLAMA_API_BASE),
+        );
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout))
+            .build()
+            .map_err(|e| AIError::Configuration(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            config,
+            client,
+            base_url,
+            cached_models: tokio::sync::RwLock::new(None),
+        })
+    }
+
+    /// Accept both `http://host:port` and `http://host:port/v1`
+    fn normalize_base_url(url: &str) -> String {
+        let trimmed = url.trim().trim_end_matches('/');
+        if trimmed.ends_with("/v1") {
+            trimmed.to_string()
+        } else {
+            format!("{}/v1", trimmed)
+        }
+    }
+
+    /// Server root, used for Ollama's native `/api/tags`
+    fn server_root(&self) -> &str {
+        self.base_url.trim_end_matches("/v1")
+    }
+
+    /// Attach the bearer token when one is configured; local servers usually need none
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self.config.api_key.as_deref().filter(|key| !key.is_empty()) {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    fn param_u32(&self, key: &str) -> Option<u32> {
+        self.config
+            .params
+            .get(key)
+            .and_then(Value::as_u64)
+            .and_then(|value| u32::try_from(value).ok())
+    }
+
+    fn convert_message(message: &ChatMessage) -> Value {
+        let mut value = json!({
+            "role": message.role,
+            "content": message.content,
+        });
+        if let Some(name) = message.name.as_ref() {
+            value["name"] = json!(name);
+        }
+        if let Some(tool_calls) = message.tool_calls.as_ref().filter(|c| !c.is_empty()) {
+            value["tool_calls"] = Value::Array(
+                tool_calls
+                    .iter()
+                    .map(|call| {
+                        json!({
+                            "id": call.id,
+                            "type": call.r#type,
+                            "function": {
+                                "name": call.function.name,
+                                "arguments": call.function.arguments,
+                            },
+                        })
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(tool_call_id) = message.tool_call_id.as_ref() {
+            value["tool_call_id"] = json!(tool_call_id);
+        }
+        value
+    }
+
+    /// Build the `/chat/completions` body
+    fn build_chat_body(request: &ChatCompletionRequest, stream: bool) -> Value {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request
+                .messages
+                .iter()
+                .map(Self::convert_message)
+                .collect::<Vec<_>>(),
+            "stream": stream,
+        });
+        let object = body.as_object_mut().expect("chat body is an object");
+        if let Some(temperature) = request.temperature {
+            object.insert("temperature".to_string(), json!(temperature));
+        }
+        if let Some(max_tokens) = request.max_tokens.or(request.max_completion_tokens) {
+            object.insert("max_tokens".to_string(), json!(max_tokens));
+        }
+        if let Some(top_p) = request.top_p {
+            object.insert("top_p".to_string(), json!(top_p));
+        }
+        if let Some(stop) = request.stop.as_ref() {
+            object.insert("stop".to_string(), json!(stop));
+        }
+        if let Some(seed) = request.seed {
+            object.insert("seed".to_string(), json!(seed));
+        }
+        if let Some(tools) = request.tools.as_ref().filter(|tools| !tools.is_empty()) {
+            object.insert("tools".to_string(), json!(tools));
+            let tool_choice = match request.tool_choice.as_ref() {
+                Some(ToolChoice::None) => json!("none"),
+                Some(ToolChoice::Tool(tool)) => json!({
+                    "type": "function",
+                    "function": { "name": tool.function.name },
+                }),
+                Some(ToolChoice::Auto) | None => json!("auto"),
+            };
+            object.insert("tool_choice".to_string(), tool_choice);
+            if let Some(parallel) = request.parallel_tool_calls {
+                object.insert("parallel_tool_calls".to_string(), json!(parallel));
+            }
+        }
+        if let Some(schema) = request.response_schema.as_ref() {
+            object.insert(
+                "response_format".to_string(),
+                structured_output::openai_response_format(schema),
+            );
+        } else if request.json_mode {
+            object.insert(
+                "response_format".to_string(),
+                json!({ "type": "json_object" }),
+            );
+        }
+        if stream {
+            object.insert(
+                "stream_options".to_string(),
+                json!({ "include_usage": true }),
+            );
+        }
+        body
+    }
+
+    /// Turn a non-2xx response into an AIError, tolerating plain-text bodies
+    async fn error_from_response(response: reqwest::Response) -> AIError {
+        let status = response.status();
+        let retry_after = retry_after_header(response.headers());
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|value| {
+                value
+                    .pointer("/error/message")
+                    .or_else(|| value.get("error"))
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .unwrap_or(body);
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                AIError::Authentication(message)
+            }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                AIError::RateLimit(message).with_retry_after(retry_after)
+            }
+            reqwest::StatusCode::NOT_FOUND => AIError::ModelNotFound(message),
+            reqwest::StatusCode::BAD_REQUEST => AIError::InvalidRequest(message),
+            _ => AIError::APIError(format!("Local provider error ({}): {}", status, message)),
+        }
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> ProviderResult<reqwest::Response> {
+        let response = self
+            .authorize(self.client.post(format!("{}{}", self.base_url, path)))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AIError::NetworkError(format!("Request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(Self::error_from_response(response).await);
+        }
+        Ok(response)
+    }
+
+    fn convert_tool_calls(calls: Vec<LocalToolCall>) -> Vec<ToolCall> {
+        calls
+            .into_iter()
+            .enumerate()
+            .map(|(index, call)| ToolCall {
+                id: call
+                    .id
+                    .unwrap_or_else(|| format!("local_tool_call_{}", index)),
+                r#type: "function".to_string(),
+                extra_content: None,
+                function: FunctionCall {
+                    name: call.function.name,
+                    arguments: match call.function.arguments {
+                        Some(Value::String(arguments)) => arguments,
+                        Some(Value::Null) | None => "{}".to_string(),
+                        Some(other) => other.to_string(),
+                    },
+                },
+                airlock_level: None,
+            })
+            .collect()
+    }
+
+    async fn list_models(&self) -> ProviderResult<Vec<String>> {
+        let models = self.fetch_models().await?;
+        *self.cached_models.write().await = Some(models.clone());
+        Ok(models)
+    }
+
+    async fn fetch_models(&self) -> ProviderResult<Vec<String>> {
+        let openai_models = self
+            .authorize(self.client.get(format!("{}/models", self.base_url)))
+            .send()
+            .await;
+        if let Ok(response) = openai_models {
+            if response.status().is_success() {
+                if let Ok(list) = response.json::<LocalModelList>().await {
+                    return Ok(list.data.into_iter().map(|model| model.id).collect());
+                }
+            }
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/api/tags", self.server_root()))
+            .send()
+            .await
+            .map_err(|e| AIError::NetworkError(format!("Failed to list models: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(Self::error_from_response(response).await);
+        }
+        let tags: OllamaTags = response
+            .json()
+            .await
+            .map_err(|e| AIError::APIError(format!("Failed to parse model list: {}", e)))?;
+        Ok(tags.models.into_iter().map(|model| model.name).collect())
+    }
+}
+
+#[async_trait]
+impl AIProvider for LocalProvider {
+    fn id(&self) -> &ProviderId {
+        &self.config.id
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        self.config.provider_type
+    }
+
+    async fn capabilities(&self) -> ProviderResult<ProviderCapabilities> {
+        let cached = self.cached_models.read().await.clone();
+        let models = match cached {
+            Some(models) => models,
+            None => self
+                .list_models()
+                .await
+                .unwrap_or_else(|_| vec![self.config.model.clone()]),
+        };
+        Ok(ProviderCapabilities {
+            chat_completions: true,
+            embeddings: true,
+            streaming: true,
+            function_calling: true,
+            tool_call_streaming: true,
+            vision: self
+                .config
+                .params
+                .get("vision")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            web_search: false,
+            max_context_tokens: self
+                .param_u32("context_window")
+                .unwrap_or(DEFAULT_CONTEXT_TOKENS),
+            max_output_tokens: self
+                .param_u32("max_output_tokens")
+                .unwrap_or(DEFAULT_OUTPUT_TOKENS),
+            models,
+        })
+    }
+
+    async fn health_check(&self) -> ProviderResult<ProviderHealth> {
+        match self.list_models().await {
+            Ok(models) if models.is_empty() => Ok(ProviderHealth::Degraded),
+            Ok(_) => Ok(ProviderHealth::Healthy),
+            Err(AIError::RateLimit(_)) => Ok(ProviderHealth::Degraded),
+            Err(_) => Ok(ProviderHealth::Unhealthy),
+        }
+    }
+
+    async fn complete(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> ProviderResult<ChatCompletionResponse> {
+        let body = Self::build_chat_body(&request, false);
+        let chat_response: LocalChatResponse = self
+            .post("/chat/completions", &body)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AIError::APIError(format!("Failed to parse response: {}", e)))?;
+
+        let choice = chat_response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AIError::APIError("No response choices".to_string()))?;
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .filter(|calls| !calls.is_empty())
+            .map(Self::convert_tool_calls);
+        let finish_reason = match choice.finish_reason {
+            Some(reason) if reason != "stop" || tool_calls.is_none() => reason,
+            Some(_) => "tool_calls".to_string(),
+            None if tool_calls.is_some() => "tool_calls".to_string(),
+            None => "stop".to_string(),
+        };
+
+        Ok(ChatCompletionResponse {
+            content: choice.message.content.map(|content| content.text()),
+            tool_calls,
+            model: chat_response.model.unwrap_or(request.model),
+            usage: chat_response
+                .usage
+                .map(|usage| usage.to_token_usage())
+                .unwrap_or_else(|| TokenUsage::new(0, 0)),
+            finish_reason,
+            provider_metadata: None,
+            served_by: None,
+        })
+    }
+
+    async fn complete_event_stream(
+        &self,
+        request: ChatCompletionRequest,
+        callback: ProviderEventCallback,
+    ) -> ProviderResult<()> {
+        let body = Self::build_chat_body(&request, true);
+        let response = self.post("/chat/completions", &body).await?;
+
+        let mut stream = response.bytes_stream();
+        let mut buffer = String::new();
+        let mut state = LocalStreamState::default();
+
+        'stream: while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| AIError::NetworkError(format!("Stream error: {}", e)))?;
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+
+            while let Some(pos) = buffer.find('\n') {
+                let line = buffer.drain(..=pos).collect::<String>();
+                if let Some(data) = line.trim().strip_prefix("data:") {
+                    if !state.handle_data(data.trim(), &callback) {
+                        break 'stream;
+                    }
+                }
+            }
+        }
+
+        state.finish(&callback);
+        Ok(())
+    }
+
+    async fn complete_stream(
+        &self,
+        request: ChatCompletionRequest,
+        callback: StreamingCallback,
+    ) -> ProviderResult<()> {
+        let adapter: ProviderEventCallback = Arc::new(move |event| match event {
+            ProviderStreamEvent::TextDelta(content) => callback(StreamingChunk {
+                content,
+                thought: None,
+                is_final: false,
+                finish_reason: None,
+            }),
+            ProviderStreamEvent::ThoughtDelta(thought) => callback(StreamingChunk {
+                content: String::new(),
+                thought: Some(thought),
+                is_final: false,
+                finish_reason: None,
+            }),
+            ProviderStreamEvent::Completed { finish_reason } => callback(StreamingChunk {
+                content: String::new(),
+                thought: None,
+                is_final: true,
+                finish_reason,
+            }),
+            ProviderStreamEvent::ToolCallDelta(_)
+            | ProviderStreamEvent::Usage(_)
+            | ProviderStreamEvent::Raw(_) => {}
+        });
+
+        self.complete_event_stream(request, adapter).await
+    }
+
+    async fn embed(&self, request: EmbeddingRequest) -> ProviderResult<EmbeddingResponse> {
+        let body = json!({
+            "model": request.model,
+            "input": request.input,
+        });
+        let embedding_response: LocalEmbeddingResponse = self
+            .post("/embeddings", &body)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AIError::APIError(format!("Failed to parse response: {}", e)))?;
+
+        let data = embedding_response
+            .data
+            .into_iter()
+            .next()
+            .ok_or_else(|| AIError::APIError("No embedding data".to_string()))?;
+
+        Ok(EmbeddingResponse {
+            embedding: data.embedding,
+            model: embedding_response.model.unwrap_or(request.model),
+            usage: embedding_response
+                .usage
+                .map(|usage| TokenUsage {
+                    prompt_tokens: usage.prompt_tokens,
+                    completion_tokens: 0,
+                    total_tokens: usage.total_tokens.unwrap_or(usage.prompt_tokens),
+                    ..Default::default()
+                })
+                .unwrap_or_else(|| TokenUsage::new(0, 0)),
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        &self.config.model
+    }
+
+    async fn available_models(&self) -> ProviderResult<Vec<String>> {
+        self.list_models().await
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+}
+
+/// Local provider factory, used for both `ProviderType::Local` and `ProviderType::Custom`
+pub struct LocalProviderFactory;
+
+#[async_trait]
+impl AIProviderFactory for LocalProviderFactory {
+    async fn create(config: ProviderConfig) -> ProviderResult<Arc<dyn AIProvider>> {
+        Self::validate_config(&config)?;
+        Ok(Arc::new(LocalProvider::new(config)?))
+    }
+
+    fn validate_config(config: &ProviderConfig) -> ProviderResult<()> {
+        if config.model.is_empty() {
+            return Err(AIError::InvalidRequest("Model is required".to_string()));
+        }
+
+        let base_url = config.base_url.as_deref().map(str::trim).unwrap_or("");
+        if base_url.is_empty() {
+            if config.provider_type == ProviderType::Custom {
+                return Err(AIError::Configuration(
+                    "Base URL is required for custom providers".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+        url::Url::parse(base_url)
+            .map_err(|e| AIError::Configuration(format!("Invalid base URL: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::provider_types::{FunctionDefinition, Tool};
+    use std::sync::Mutex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn config(base_url: Option<String>) -> ProviderConfig {
+        ProviderConfig {
+            id: ProviderId::new("local-test"),
+            provider_type: ProviderType::Local,
+            api_key: None,
+            base_url,
+            model: "llama3.1".to_string(),
+            params: std::collections::HashMap::new(),
+            enabled: true,
+            priority: 1,
+            rate_limit: None,
+            timeout: 10,
+        }
+    }
+
+    /// Serve canned responses keyed by request path, one connection at a time.
+    async fn mock_server(routes: Vec<(&'static str, &'static str, String)>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let request = read_request(&mut socket).await;
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                let (status, content_type, body) = routes
+                    .iter()
+                    .find(|(route, _, _)| *route == path)
+                    .map(|(_, content_type, body)| ("200 OK", *content_type, body.clone()))
+                    .unwrap_or(("404 Not Found", "text/plain", "not found".to_string()));
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    content_type,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// Read headers plus a `Content-Length` body so the client never sees a reset.
+    async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let Ok(n) = socket.read(&mut buf).await else {
+                break;
+            };
+            if n == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..n]);
+            let text = String::from_utf8_lossy(&data);
+            if let Some(header_end) = text.find("\r\n\r\n") {
+                let content_length = text[..header_end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())
+                            .flatten()
+                    })
+                    .unwrap_or(0);
+                if data.len() >= header_end + 4 + content_length {
+                    break;
+                }
+            }
+        }
+        String::from_utf8_lossy(&data).to_string()
+    }
+
+    #[test]
+    fn test_normalize_base_url() {
+        assert_eq!(
+            LocalProvider::normalize_base_url("http://localhost:11434"),
+            "http://localhost:11434/v1"
+        );
+        assert_eq!(
+            LocalProvider::normalize_base_url("http://localhost:1234/v1/"),
+            "http://localhost:1234/v1"
+        );
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(LocalProviderFactory::validate_config(&config(None)).is_ok());
+
+        let mut custom = config(None);
+        custom.provider_type = ProviderType::Custom;
+        assert!(LocalProviderFactory::validate_config(&custom).is_err());
+        custom.base_url = Some("http://gpu-box:8000/v1".to_string());
+        assert!(LocalProviderFactory::validate_config(&custom).is_ok());
+    }
+
+    #[test]
+    fn test_chat_body_includes_tools_and_tool_messages() {
+        let request = ChatCompletionRequest {
+            model: "llama3.1".to_string(),
+            messages: vec![
+                ChatMessage::user("list files"),
+                ChatMessage {
+                    role: "assistant".to_string(),
+                    content: "".into(),
+                    name: None,
+                    tool_calls: Some(vec![ToolCall {
+                        id: "call_1".to_string(),
+                        r#type: "function".to_string(),
+                        extra_content: None,
+                        function: FunctionCall {
+                            name: "list_files".to_string(),
+                            arguments: "{\"path\":\".\"}".to_string(),
+                        },
+                        airlock_level: None,
+                    }]),
+                    tool_call_id: None,
+                },
+                ChatMessage {
+                    role: "tool".to_string(),
+                    content: "a.txt".into(),
+                    name: None,
+                    tool_calls: None,
+                    tool_call_id: Some("call_1".to_string()),
+                },
+            ],
+            tools: Some(vec![Tool {
+                r#type: "function".to_string(),
+                function: FunctionDefinition {
+                    name: "list_files".to_string(),
+                    description: "List files".to_string(),
+                    parameters: json!({ "type": "object" }),
+                },
+            }]),
+            tool_choice: Some(ToolChoice::Auto),
+            ..Default::default()
+        };
+
+        let body = LocalProvider::build_chat_body(&request, true);
+        assert_eq!(body["tools"][0]["function"]["name"], "list_files");
+        assert_eq!(body["tool_choice"], "auto");
+        assert_eq!(
+            body["messages"][1]["tool_calls"][0]["function"]["name"],
+            "list_files"
+        );
+        assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+        assert_eq!(body["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn test_stream_state_emits_tool_call_lifecycle() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let callback: ProviderEventCallback = Arc::new(move |event: ProviderStreamEvent| {
+            sink.lock().unwrap().push(event);
+        });
+
+        let mut state = LocalStreamState::default();
+        for data in [
+            r#"{"model":"qwen","choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_9","type":"function","function":{"name":"read_file","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"path\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"a.txt\"}"}}]},"finish_reason":"stop"}]}"#,
+        ] {
+            assert!(state.handle_data(data, &callback));
+        }
+        assert!(!state.handle_data("[DONE]", &callback));
+        state.finish(&callback);
+
+        let events = events.lock().unwrap();
+        let ready = events
+            .iter()
+            .find_map(|event| match event {
+                ProviderStreamEvent::ToolCallDelta(lifecycle)
+                    if matches!(lifecycle.state, ProviderToolLifecycleState::Ready) =>
+                {
+                    Some(lifecycle.tool_call.clone())
+                }
+                _ => None,
+            })
+            .expect("ready event");
+        assert_eq!(ready.id.as_deref(), Some("call_9"));
+        assert_eq!(ready.name.as_deref(), Some("read_file"));
+        assert_eq!(ready.arguments.as_deref(), Some("{\"path\":\"a.txt\"}"));
+        assert!(matches!(
+            events.last(),
+            Some(ProviderStreamEvent::Completed { finish_reason: Some(reason) }) if reason == "tool_calls"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_complete_against_mock_server() {
+        let base_url = mock_server(vec![(
+            "/v1/chat/completions",
+            "application/json",
+            json!({
+                "model": "llama3.1",
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": { "name": "read_file", "arguments": { "path": "a.txt" } }
+                        }]
+                    },
+                    "finish_reason": "tool_calls"
+                }],
+                "usage": { "prompt_tokens": 12, "completion_tokens": 5 }
+            })
+            .to_string(),
+        )])
+        .await;
+
+        let provider = LocalProvider::new(config(Some(base_url))).unwrap();
+        let response = provider
+            .complete(ChatCompletionRequest {
+                model: "llama3.1".to_string(),
+                messages: vec![ChatMessage::user("read a.txt")],
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let calls = response.tool_calls.expect("tool calls");
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments, "{\"path\":\"a.txt\"}");
+        assert_eq!(response.usage.total_tokens, 17);
+    }
+
+    #[tokio::test]
+    async fn test_available_models_falls_back_to_ollama_tags() {
+        let base_url = mock_server(vec![(
+            "/api/tags",
+            "application/json",
+            json!({ "models": [{ "name": "llama3.1:8b" }, { "name": "qwen2.5-coder" }] })
+                .to_string(),
+        )])
+        .await;
+
+        let provider = LocalProvider::new(config(Some(base_url))).unwrap();
+        assert_eq!(
+            provider.available_models().await.unwrap(),
+            vec!["llama3.1:8b".to_string(), "qwen2.5-coder".to_string()]
+        );
+        assert_eq!(
+            provider.health_check().await.unwrap(),
+            ProviderHealth::Healthy
+        );
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod anthropic;
 pub mod gemini_adapter;
+pub mod local;
 pub mod moonshot;
 pub mod openai;
 pub mod rainy_sdk;
@@ -11,6 +12,7 @@ pub mod xai;
 // Only re-export factories (which are used for registration)
 pub use anthropic::AnthropicProviderFactory;
 pub use gemini_adapter::{GeminiProviderAdapter, GeminiProviderFactory};
+pub use local::LocalProviderFactory;
 pub use moonshot::MoonshotProviderFactory;
 pub use openai::OpenAIProviderFactory;
 pub use rainy_sdk::RainySDKProviderFactory;
@@ -20,4 +22,5 @@ pub use xai::XAIProviderFactory;
 // - rainy_sdk::RainySDKProvider
 // - openai::OpenAIProvider
 // - anthropic::AnthropicProvider
+// - local::LocalProvider
 // - xai::XAIProvider
@@ -4,9 +4,9 @@
 use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
 use crate::ai::provider_types::MessageContent;
 use crate::ai::provider_types::{
-    AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, ProviderCapabilities,
-    ProviderConfig, ProviderHealth, ProviderId, ProviderResult, ProviderType, StreamingCallback,
-    StreamingChunk, TokenUsage,
+    retry_after_header, AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage,
+    ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId, ProviderResult, ProviderType,
+    StreamingCallback, StreamingChunk, TokenUsage,
 };
 use async_trait::async_trait;
 use futures::StreamExt;
@@ -45,6 +45,8 @@ struct MoonshotChatRequest {
     presence_penalty: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
     stream: bool,
 }
 
@@ -139,6 +141,12 @@ impl MoonshotProvider {
         })
     }
 
+    /// Moonshot only offers JSON mode; schema conformance is checked by the router
+    fn response_format(request: &ChatCompletionRequest) -> Option<serde_json::Value> {
+        (request.json_mode || request.response_schema.is_some())
+            .then(|| serde_json::json!({ "type": "json_object" }))
+    }
+
     /// Convert chat messages to Moonshot format
     fn convert_messages(messages: &[ChatMessage]) -> Vec<MoonshotMessage> {
         messages
@@ -232,6 +240,7 @@ impl AIProvider for MoonshotProvider {
             top_p: request.top_p,
             frequency_penalty: request.frequency_penalty,
             presence_penalty: request.presence_penalty,
+            response_format: Self::response_format(&request),
             stop: request.stop,
             stream: false,
         };
@@ -248,11 +257,12 @@ impl AIProvider for MoonshotProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: MoonshotError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let chat_response: MoonshotChatResponse = response
@@ -278,9 +288,11 @@ impl AIProvider for MoonshotProvider {
                 prompt_tokens: chat_response.usage.prompt_tokens,
                 completion_tokens: chat_response.usage.completion_tokens,
                 total_tokens: chat_response.usage.total_tokens,
+                ..Default::default()
             },
             finish_reason: choice.finish_reason,
             provider_metadata: None,
+            served_by: None,
         })
     }
 
@@ -297,6 +309,7 @@ impl AIProvider for MoonshotProvider {
             top_p: request.top_p,
             frequency_penalty: request.frequency_penalty,
             presence_penalty: request.presence_penalty,
+            response_format: Self::response_format(&request),
             stop: request.stop,
             stream: true,
         };
@@ -313,11 +326,12 @@ impl AIProvider for MoonshotProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: MoonshotError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let mut stream = response.bytes_stream();
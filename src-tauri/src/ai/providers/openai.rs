@@ -4,10 +4,11 @@
 use crate::ai::provider_trait::{AIProvider, AIProviderFactory};
 use crate::ai::provider_types::MessageContent;
 use crate::ai::provider_types::{
-    AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
-    EmbeddingResponse, ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId,
-    ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
+    retry_after_header, AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage,
+    EmbeddingRequest, EmbeddingResponse, ProviderCapabilities, ProviderConfig, ProviderHealth,
+    ProviderId, ProviderResult, ProviderType, StreamingCallback, StreamingChunk, TokenUsage,
 };
+use crate::ai::structured_output;
 use async_trait::async_trait;
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -45,6 +46,8 @@ struct OpenAIChatRequest {
     presence_penalty: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
     stream: bool,
 }
 
@@ -79,6 +82,19 @@ struct OpenAIUsage {
     prompt_tokens: u32,
     completion_tokens: u32,
     total_tokens: u32,
+    #[serde(default)]
+    prompt_tokens_details: Option<OpenAITokenDetails>,
+    #[serde(default)]
+    completion_tokens_details: Option<OpenAITokenDetails>,
+}
+
+/// Breakdown of cached prompt and reasoning tokens
+#[derive(Debug, Deserialize)]
+struct OpenAITokenDetails {
+    #[serde(default)]
+    cached_tokens: u32,
+    #[serde(default)]
+    reasoning_tokens: u32,
 }
 
 /// OpenAI embedding request
@@ -160,6 +176,15 @@ impl OpenAIProvider {
         })
     }
 
+    /// Map `response_schema` / `json_mode` to `response_format`
+    fn response_format(request: &ChatCompletionRequest) -> Option<serde_json::Value> {
+        match request.response_schema.as_ref() {
+            Some(schema) => Some(structured_output::openai_response_format(schema)),
+            None if request.json_mode => Some(serde_json::json!({ "type": "json_object" })),
+            None => None,
+        }
+    }
+
     /// Get the HTTP client
 
     /// Convert chat messages to OpenAI format
@@ -259,6 +284,7 @@ impl AIProvider for OpenAIProvider {
             top_p: request.top_p,
             frequency_penalty: request.frequency_penalty,
             presence_penalty: request.presence_penalty,
+            response_format: Self::response_format(&request),
             stop: request.stop,
             stream: false,
         };
@@ -275,11 +301,12 @@ impl AIProvider for OpenAIProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: OpenAIError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let chat_response: OpenAIChatResponse = response
@@ -305,9 +332,20 @@ impl AIProvider for OpenAIProvider {
                 prompt_tokens: chat_response.usage.prompt_tokens,
                 completion_tokens: chat_response.usage.completion_tokens,
                 total_tokens: chat_response.usage.total_tokens,
+                cached_prompt_tokens: chat_response
+                    .usage
+                    .prompt_tokens_details
+                    .as_ref()
+                    .map_or(0, |details| details.cached_tokens),
+                reasoning_tokens: chat_response
+                    .usage
+                    .completion_tokens_details
+                    .as_ref()
+                    .map_or(0, |details| details.reasoning_tokens),
             },
             finish_reason: choice.finish_reason,
             provider_metadata: None,
+            served_by: None,
         })
     }
 
@@ -324,6 +362,7 @@ impl AIProvider for OpenAIProvider {
             top_p: request.top_p,
             frequency_penalty: request.frequency_penalty,
             presence_penalty: request.presence_penalty,
+            response_format: Self::response_format(&request),
             stop: request.stop,
             stream: true,
         };
@@ -340,11 +379,12 @@ impl AIProvider for OpenAIProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: OpenAIError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let mut stream = response.bytes_stream();
@@ -410,11 +450,12 @@ impl AIProvider for OpenAIProvider {
         let status = response.status();
 
         if !status.is_success() {
+            let retry_after = retry_after_header(response.headers());
             let error: OpenAIError = response
                 .json()
                 .await
                 .map_err(|e| AIError::APIError(format!("Failed to parse error: {}", e)))?;
-            return Err(Self::map_error(status, error));
+            return Err(Self::map_error(status, error).with_retry_after(retry_after));
         }
 
         let embedding_response: OpenAIEmbeddingResponse = response
@@ -435,6 +476,7 @@ impl AIProvider for OpenAIProvider {
                 prompt_tokens: embedding_response.usage.prompt_tokens,
                 completion_tokens: 0,
                 total_tokens: embedding_response.usage.total_tokens,
+                ..Default::default()
             },
         })
     }
@@ -45,6 +45,7 @@ impl RainySDKProvider {
             prompt_tokens: usage.prompt_tokens,
             completion_tokens: usage.completion_tokens,
             total_tokens: usage.total_tokens,
+            ..Default::default()
         })
     }
 
@@ -590,7 +591,12 @@ impl RainySDKProvider {
         sdk_request.safety_identifier = request.safety_identifier.clone();
         sdk_request.provider_options = request.provider_options.clone();
         sdk_request.prompt_cache_retention = request.prompt_cache_retention.clone();
-        sdk_request.text = request.text.clone();
+        sdk_request.text = request.text.clone().or_else(|| {
+            request
+                .response_schema
+                .as_ref()
+                .map(crate::ai::structured_output::responses_text_format)
+        });
         sdk_request.instructions = request.instructions.clone();
         sdk_request.include = request.include.clone();
         sdk_request.previous_response_id = request.previous_response_id.clone();
@@ -658,6 +664,8 @@ impl RainySDKProvider {
                 prompt_tokens,
                 completion_tokens,
                 total_tokens: prompt_tokens + completion_tokens,
+                cached_prompt_tokens: usage.cache_read_input_tokens.unwrap_or(0),
+                reasoning_tokens: 0,
             }
         })
     }
@@ -872,6 +880,7 @@ impl RainySDKProvider {
                 prompt_tokens: usage.input_tokens.unwrap_or(0),
                 completion_tokens: usage.output_tokens.unwrap_or(0),
                 total_tokens: usage.input_tokens.unwrap_or(0) + usage.output_tokens.unwrap_or(0),
+                ..Default::default()
             },
             finish_reason,
             provider_metadata: Some(json!({
@@ -883,6 +892,7 @@ impl RainySDKProvider {
                 "object": response.object,
                 "output": output,
             })),
+            served_by: None,
         })
     }
 
@@ -1046,12 +1056,14 @@ impl RainySDKProvider {
                     prompt_tokens: prompt,
                     completion_tokens: completion,
                     total_tokens: total,
+                    ..Default::default()
                 }
             },
             finish_reason: choice.finish_reason,
             provider_metadata: Some(json!({
                 "transport": "chat.completions",
             })),
+            served_by: None,
         })
     }
 
@@ -1371,6 +1383,7 @@ impl AIProvider for RainySDKProvider {
                                     completion_tokens: usage.completion_tokens.unwrap_or(0),
                                     total_tokens: usage.prompt_tokens.unwrap_or(0)
                                         + usage.completion_tokens.unwrap_or(0),
+                                    ..Default::default()
                                 }));
                             }
                         }
@@ -2,6 +2,7 @@
 // Direct integration with xAI's Grok API using OpenAI-compatible endpoints
 
 use crate::ai::provider_types::MessageContent;
+use crate::ai::structured_output;
 use crate::ai::{
     AIError, AIProvider, AIProviderFactory, ChatCompletionRequest, ChatCompletionResponse,
     ChatMessage, EmbeddingRequest, EmbeddingResponse, ProviderCapabilities, ProviderConfig,
@@ -286,6 +287,8 @@ pub struct XAIChatRequest {
     pub presence_penalty: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<serde_json::Value>,
     pub stream: bool,
 }
 
@@ -299,6 +302,11 @@ impl From<ChatCompletionRequest> for XAIChatRequest {
             top_p: req.top_p,
             frequency_penalty: req.frequency_penalty,
             presence_penalty: req.presence_penalty,
+            response_format: match req.response_schema.as_ref() {
+                Some(schema) => Some(structured_output::openai_response_format(schema)),
+                None if req.json_mode => Some(serde_json::json!({ "type": "json_object" })),
+                None => None,
+            },
             stop: req.stop,
             stream: false,
         }
@@ -350,6 +358,7 @@ impl XAIChatResponse {
                 prompt_tokens: u.prompt_tokens,
                 completion_tokens: u.completion_tokens,
                 total_tokens: u.total_tokens,
+                ..Default::default()
             })
             .unwrap_or_else(|| TokenUsage::new(0, 0));
 
@@ -366,6 +375,7 @@ impl XAIChatResponse {
             usage,
             finish_reason,
             provider_metadata: None,
+            served_by: None,
         }
     }
 }
@@ -3,6 +3,7 @@
 
 use crate::ai::provider_trait::ProviderWithStats;
 use crate::ai::provider_types::ProviderId;
+use crate::ai::router::pricing::ModelPrice;
 use std::collections::HashMap;
 
 /// Cost per 1K tokens for different providers
@@ -21,14 +22,18 @@ impl ProviderCost {
         let output_cost = (output_tokens as f64 / 1000.0) * self.output_cost_per_1k;
         input_cost + output_cost
     }
+
+    /// List price for a model from the pricing table, if known
+    pub fn for_model(model: &str) -> Option<Self> {
+        crate::ai::router::pricing::price_for_model(model).map(Self::from)
+    }
 }
 
-/// Default costs for common providers
-impl Default for ProviderCost {
-    fn default() -> Self {
+impl From<ModelPrice> for ProviderCost {
+    fn from(price: ModelPrice) -> Self {
         Self {
-            input_cost_per_1k: 0.001,  // $0.001 per 1K input tokens
-            output_cost_per_1k: 0.002, // $0.002 per 1K output tokens
+            input_cost_per_1k: price.input_per_mtok / 1000.0,
+            output_cost_per_1k: price.output_per_mtok / 1000.0,
         }
     }
 }
@@ -236,6 +241,14 @@ mod tests {
         assert_eq!(total_cost, 0.003); // $0.001 + $0.002
     }
 
+    #[test]
+    fn test_provider_cost_from_price_table() {
+        let cost = ProviderCost::for_model("gpt-4o").unwrap();
+        assert!((cost.input_cost_per_1k - 0.0025).abs() < 1e-12);
+        assert!((cost.output_cost_per_1k - 0.01).abs() < 1e-12);
+        assert!(ProviderCost::for_model("unknown-model").is_none());
+    }
+
     #[test]
     fn test_cost_optimizer_select_cheapest() {
         let mut optimizer = CostOptimizer::default();
@@ -0,0 +1,185 @@
+// Request Hedging
+// Races a second provider against a slow first one
+
+use crate::ai::provider_trait::ProviderStats;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// When to fire a hedged request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HedgePolicy {
+    /// Whether hedged requests are sent at all
+    pub enabled: bool,
+    /// Never hedge sooner than this
+    pub min_delay: Duration,
+    /// Never wait longer than this before hedging
+    pub max_delay: Duration,
+}
+
+impl Default for HedgePolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(20),
+        }
+    }
+}
+
+impl HedgePolicy {
+    /// How long to wait on `stats`' provider before hedging: its p95 time to
+    /// first token for streams, or p95 total latency otherwise. `None` when
+    /// hedging is off or the provider has too few samples to judge.
+    pub fn delay_for(&self, stats: &ProviderStats, streaming: bool) -> Option<Duration> {
+        if !self.enabled {
+            return None;
+        }
+        let p95 = if streaming {
+            stats.first_token_p95_ms()
+        } else {
+            stats.latency_p95_ms()
+        }?;
+        Some(
+            Duration::from_millis(p95)
+                .max(self.min_delay)
+                .min(self.max_delay),
+        )
+    }
+}
+
+const NO_WINNER: usize = usize::MAX;
+
+/// Shared state of one dispatch attempt. The first lane to produce output
+/// claims the race; output from any other lane is discarded.
+#[derive(Debug)]
+pub struct HedgeRace {
+    winner: AtomicUsize,
+    claimed: Notify,
+}
+
+impl HedgeRace {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            winner: AtomicUsize::new(NO_WINNER),
+            claimed: Notify::new(),
+        })
+    }
+
+    /// Claim the race for `lane`; true if `lane` is (now) the winner
+    pub fn claim(&self, lane: usize) -> bool {
+        match self
+            .winner
+            .compare_exchange(NO_WINNER, lane, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => {
+                self.claimed.notify_one();
+                true
+            }
+            Err(current) => current == lane,
+        }
+    }
+
+    /// Lane that claimed the race, if any
+    pub fn winner(&self) -> Option<usize> {
+        match self.winner.load(Ordering::Acquire) {
+            NO_WINNER => None,
+            lane => Some(lane),
+        }
+    }
+
+    /// Resolves once a lane has claimed the race
+    pub async fn claimed(&self) {
+        self.claimed.notified().await
+    }
+}
+
+/// One provider call within a dispatch attempt
+#[derive(Debug)]
+pub struct Lane {
+    index: usize,
+    race: Arc<HedgeRace>,
+    started: Instant,
+    first_token_ms: OnceLock<u64>,
+}
+
+impl Lane {
+    pub fn new(index: usize, race: Arc<HedgeRace>) -> Arc<Self> {
+        Arc::new(Self {
+            index,
+            race,
+            started: Instant::now(),
+            first_token_ms: OnceLock::new(),
+        })
+    }
+
+    /// The lane dispatched first; the only one whose turn-start is forwarded
+    pub fn is_primary(&self) -> bool {
+        self.index == 0
+    }
+
+    /// Called for every piece of output; the first one is timed. Returns
+    /// whether the output should be forwarded, i.e. whether this lane holds
+    /// the race.
+    pub fn admit(&self) -> bool {
+        self.first_token_ms.get_or_init(|| self.elapsed_ms());
+        self.race.claim(self.index)
+    }
+
+    /// Time from dispatch to this lane's first output
+    pub fn first_token_ms(&self) -> Option<u64> {
+        self.first_token_ms.get().copied()
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_lane_to_produce_output_wins() {
+        let race = HedgeRace::new();
+        let primary = Lane::new(0, race.clone());
+        let hedge = Lane::new(1, race.clone());
+
+        assert_eq!(race.winner(), None);
+        assert!(hedge.admit());
+        assert!(!primary.admit());
+        assert!(hedge.admit());
+        assert_eq!(race.winner(), Some(1));
+        assert!(hedge.first_token_ms().is_some());
+    }
+
+    #[test]
+    fn hedge_delay_follows_p95_within_bounds() {
+        let mut policy = HedgePolicy::default();
+        let mut stats = ProviderStats::default();
+        assert_eq!(policy.delay_for(&stats, false), None);
+
+        policy.enabled = true;
+        assert_eq!(policy.delay_for(&stats, false), None);
+
+        let provider = crate::ai::router::test_support::ScriptedProvider::with_stats("p", &[]);
+        for latency in [900, 1_000, 1_100, 1_200, 3_000] {
+            provider.update_stats(true, latency, 0);
+        }
+        stats = provider.stats();
+        assert_eq!(
+            policy.delay_for(&stats, false),
+            Some(Duration::from_millis(3_000))
+        );
+        // No first-token samples yet
+        assert_eq!(policy.delay_for(&stats, true), None);
+
+        policy.max_delay = Duration::from_secs(2);
+        assert_eq!(
+            policy.delay_for(&stats, false),
+            Some(Duration::from_secs(2))
+        );
+    }
+}
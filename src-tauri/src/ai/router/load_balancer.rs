@@ -17,6 +17,8 @@ pub enum LoadBalancingStrategy {
     WeightedRoundRobin,
     /// Random selection
     Random,
+    /// Lowest latency EWMA, discounted by success rate
+    LatencyEwma,
 }
 
 /// Load balancer configuration
@@ -89,9 +91,31 @@ impl LoadBalancer {
             LoadBalancingStrategy::LeastConnections => self.select_least_connections(),
             LoadBalancingStrategy::WeightedRoundRobin => self.select_weighted_round_robin(),
             LoadBalancingStrategy::Random => self.select_random(),
+            LoadBalancingStrategy::LatencyEwma => self.ranked_by_latency().into_iter().next(),
         }
     }
 
+    /// Providers ordered best-first by expected latency. Providers without
+    /// samples rank first so every provider gets measured; a failing provider's
+    /// latency is inflated by its failure rate.
+    pub fn ranked_by_latency(&self) -> Vec<Arc<ProviderWithStats>> {
+        let mut scored: Vec<(f64, Arc<ProviderWithStats>)> = self
+            .providers
+            .iter()
+            .map(|provider| {
+                let stats = provider.stats();
+                let score = if stats.successful_requests == 0 && stats.failed_requests == 0 {
+                    0.0
+                } else {
+                    stats.ewma_latency_ms.max(1.0) / stats.success_rate().max(0.05)
+                };
+                (score, provider.clone())
+            })
+            .collect();
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        scored.into_iter().map(|(_, provider)| provider).collect()
+    }
+
     /// Round-robin selection
     fn select_round_robin(&self) -> Option<Arc<ProviderWithStats>> {
         let index = self
@@ -166,13 +190,11 @@ impl LoadBalancer {
     }
 
     /// Get configuration
-    #[allow(dead_code)]
     pub fn config(&self) -> &LoadBalancerConfig {
         &self.config
     }
 
     /// Set configuration
-    #[allow(dead_code)]
     pub fn set_config(&mut self, config: LoadBalancerConfig) {
         self.config = config;
     }
@@ -189,6 +211,41 @@ mod tests {
         assert!(lb.is_empty());
     }
 
+    #[test]
+    fn test_latency_ewma_prefers_fast_reliable_providers() {
+        use crate::ai::router::test_support::ScriptedProvider;
+
+        let providers: Vec<Arc<ProviderWithStats>> = ["slow", "fast", "flaky", "fresh"]
+            .into_iter()
+            .map(|id| ScriptedProvider::with_stats(id, &[]))
+            .collect();
+        providers[0].update_stats(true, 900, 0);
+        providers[1].update_stats(true, 200, 0);
+        providers[2].update_stats(true, 150, 0);
+        providers[2].update_stats(false, 10, 0);
+        providers[2].update_stats(false, 10, 0);
+
+        let mut lb = LoadBalancer::new(LoadBalancerConfig {
+            strategy: LoadBalancingStrategy::LatencyEwma,
+            ..Default::default()
+        });
+        for provider in &providers {
+            lb.add_provider(provider.clone());
+        }
+
+        let ranked: Vec<String> = lb
+            .ranked_by_latency()
+            .iter()
+            .map(|p| p.provider().id().to_string())
+            .collect();
+        // flaky: 150ms at a 1/3 success rate scores 450ms
+        assert_eq!(ranked, vec!["fresh", "fast", "flaky", "slow"]);
+        assert_eq!(
+            lb.select_provider().map(|p| p.provider().id().to_string()),
+            Some("fresh".to_string())
+        );
+    }
+
     #[test]
     fn test_load_balancer_strategy() {
         let config = LoadBalancerConfig {
@@ -5,8 +5,13 @@ pub mod capability_matcher;
 pub mod circuit_breaker;
 pub mod cost_optimizer;
 pub mod fallback_chain;
+pub mod hedging;
 pub mod load_balancer;
+pub mod pricing;
+pub mod retry_policy;
 pub mod router;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 // Re-exports
 pub use capability_matcher::CapabilityMatcher;
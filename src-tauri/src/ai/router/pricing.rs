@@ -0,0 +1,196 @@
+// Model Pricing
+// Per-model list prices used to cost token usage
+
+use crate::ai::provider_types::TokenUsage;
+use serde::{Deserialize, Serialize};
+
+/// List price for a model, in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPrice {
+    /// Uncached prompt tokens
+    pub input_per_mtok: f64,
+    /// Completion tokens
+    pub output_per_mtok: f64,
+    /// Prompt tokens read from cache; billed as input when unset
+    #[serde(default)]
+    pub cached_input_per_mtok: Option<f64>,
+    /// Reasoning tokens; billed as output when unset
+    #[serde(default)]
+    pub reasoning_per_mtok: Option<f64>,
+}
+
+impl ModelPrice {
+    pub const fn new(input_per_mtok: f64, output_per_mtok: f64) -> Self {
+        Self {
+            input_per_mtok,
+            output_per_mtok,
+            cached_input_per_mtok: None,
+            reasoning_per_mtok: None,
+        }
+    }
+
+    /// Price with a discounted rate for cached prompt tokens
+    pub const fn cached(input_per_mtok: f64, output_per_mtok: f64, cached_per_mtok: f64) -> Self {
+        Self {
+            input_per_mtok,
+            output_per_mtok,
+            cached_input_per_mtok: Some(cached_per_mtok),
+            reasoning_per_mtok: None,
+        }
+    }
+
+    /// Price that never accrues cost (local models)
+    pub const fn free() -> Self {
+        Self::new(0.0, 0.0)
+    }
+
+    /// Cost in USD of the given usage
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cached = usage.cached_prompt_tokens.min(usage.prompt_tokens);
+        let reasoning = usage.reasoning_tokens.min(usage.completion_tokens);
+        let uncached = usage.prompt_tokens - cached;
+        let visible_output = usage.completion_tokens - reasoning;
+
+        let per_token = |tokens: u32, rate: f64| tokens as f64 * rate / 1_000_000.0;
+        per_token(uncached, self.input_per_mtok)
+            + per_token(
+                cached,
+                self.cached_input_per_mtok.unwrap_or(self.input_per_mtok),
+            )
+            + per_token(visible_output, self.output_per_mtok)
+            + per_token(
+                reasoning,
+                self.reasoning_per_mtok.unwrap_or(self.output_per_mtok),
+            )
+    }
+}
+
+/// Built-in list prices, matched by longest prefix of the pricing key
+const PRICE_TABLE: &[(&str, ModelPrice)] = &[
+    // OpenAI
+    ("gpt-4o", ModelPrice::cached(2.50, 10.00, 1.25)),
+    ("gpt-4o-mini", ModelPrice::cached(0.15, 0.60, 0.075)),
+    ("gpt-4.1", ModelPrice::cached(2.00, 8.00, 0.50)),
+    ("gpt-4.1-mini", ModelPrice::cached(0.40, 1.60, 0.10)),
+    ("gpt-4.1-nano", ModelPrice::cached(0.10, 0.40, 0.025)),
+    ("gpt-5", ModelPrice::cached(1.25, 10.00, 0.125)),
+    ("gpt-5-mini", ModelPrice::cached(0.25, 2.00, 0.025)),
+    ("gpt-5-nano", ModelPrice::cached(0.05, 0.40, 0.005)),
+    ("o3", ModelPrice::cached(2.00, 8.00, 0.50)),
+    ("o3-mini", ModelPrice::cached(1.10, 4.40, 0.55)),
+    ("o4-mini", ModelPrice::cached(1.10, 4.40, 0.275)),
+    ("text-embedding-3-small", ModelPrice::new(0.02, 0.0)),
+    ("text-embedding-3-large", ModelPrice::new(0.13, 0.0)),
+    // Anthropic
+    ("claude-3-haiku", ModelPrice::cached(0.25, 1.25, 0.03)),
+    ("claude-3-5-haiku", ModelPrice::cached(0.80, 4.00, 0.08)),
+    ("claude-haiku-4-5", ModelPrice::cached(1.00, 5.00, 0.10)),
+    ("claude-3-5-sonnet", ModelPrice::cached(3.00, 15.00, 0.30)),
+    ("claude-3-7-sonnet", ModelPrice::cached(3.00, 15.00, 0.30)),
+    ("claude-sonnet-4", ModelPrice::cached(3.00, 15.00, 0.30)),
+    ("claude-opus-4", ModelPrice::cached(15.00, 75.00, 1.50)),
+    ("claude-opus-4-5", ModelPrice::cached(5.00, 25.00, 0.50)),
+    // Google
+    ("gemini-2.0-flash", ModelPrice::cached(0.10, 0.40, 0.025)),
+    ("gemini-2.5-flash", ModelPrice::cached(0.30, 2.50, 0.075)),
+    (
+        "gemini-2.5-flash-lite",
+        ModelPrice::cached(0.10, 0.40, 0.025),
+    ),
+    ("gemini-2.5-pro", ModelPrice::cached(1.25, 10.00, 0.31)),
+    ("gemini-3-flash", ModelPrice::cached(0.50, 3.00, 0.05)),
+    ("gemini-3-pro", ModelPrice::cached(2.00, 12.00, 0.20)),
+    // xAI
+    ("grok-3", ModelPrice::cached(3.00, 15.00, 0.75)),
+    ("grok-3-mini", ModelPrice::cached(0.30, 0.50, 0.075)),
+    ("grok-4", ModelPrice::cached(3.00, 15.00, 0.75)),
+    // Moonshot
+    ("kimi-k2", ModelPrice::cached(0.60, 2.50, 0.15)),
+];
+
+/// Key used to look a model up in the price table: routing prefixes and the
+/// vendor path (`openai/gpt-5` → `gpt-5`) are stripped and the result lowercased.
+pub fn pricing_key(model: &str) -> String {
+    let normalized = crate::ai::model_catalog::normalize_model_slug(model.trim());
+    let base = normalized.rsplit('/').next().unwrap_or(normalized);
+    base.to_lowercase()
+}
+
+/// Built-in list price for a model, if known
+pub fn price_for_model(model: &str) -> Option<ModelPrice> {
+    let key = pricing_key(model);
+    PRICE_TABLE
+        .iter()
+        .filter(|(prefix, _)| key.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_prefers_longest_prefix() {
+        assert_eq!(
+            price_for_model("gpt-4o-mini-2024-07-18").map(|p| p.input_per_mtok),
+            Some(0.15)
+        );
+        assert_eq!(
+            price_for_model("gpt-4o-2024-08-06").map(|p| p.input_per_mtok),
+            Some(2.50)
+        );
+        assert_eq!(
+            price_for_model("claude-opus-4-5-20251101").map(|p| p.output_per_mtok),
+            Some(25.00)
+        );
+        assert_eq!(
+            price_for_model("claude-opus-4-1").map(|p| p.output_per_mtok),
+            Some(75.00)
+        );
+    }
+
+    #[test]
+    fn lookup_strips_routing_and_vendor_prefixes() {
+        assert_eq!(pricing_key("rainy:openai/GPT-5-mini"), "gpt-5-mini");
+        assert!(price_for_model("rainy:openai/gpt-5-mini").is_some());
+        assert!(price_for_model("gemini:gemini-2.5-pro").is_some());
+        assert!(price_for_model("llama3.1:8b").is_none());
+    }
+
+    #[test]
+    fn cost_prices_cached_and_reasoning_tokens_separately() {
+        let price = ModelPrice {
+            input_per_mtok: 2.0,
+            output_per_mtok: 8.0,
+            cached_input_per_mtok: Some(0.5),
+            reasoning_per_mtok: Some(4.0),
+        };
+        let usage = TokenUsage {
+            prompt_tokens: 1_000_000,
+            completion_tokens: 1_000_000,
+            total_tokens: 2_000_000,
+            cached_prompt_tokens: 400_000,
+            reasoning_tokens: 250_000,
+        };
+
+        // 0.6M * 2 + 0.4M * 0.5 + 0.75M * 8 + 0.25M * 4
+        let cost = price.cost(&usage);
+        assert!((cost - (1.2 + 0.2 + 6.0 + 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cost_falls_back_to_base_rates() {
+        let price = ModelPrice::new(1.0, 2.0);
+        let usage = TokenUsage {
+            prompt_tokens: 500_000,
+            completion_tokens: 500_000,
+            total_tokens: 1_000_000,
+            cached_prompt_tokens: 500_000,
+            reasoning_tokens: 500_000,
+        };
+        assert!((price.cost(&usage) - 1.5).abs() < 1e-9);
+        assert_eq!(ModelPrice::free().cost(&usage), 0.0);
+    }
+}
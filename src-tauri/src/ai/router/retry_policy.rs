@@ -0,0 +1,100 @@
+// Retry Policy
+// Exponential backoff with jitter between dispatch attempts
+
+use crate::ai::provider_types::AIError;
+use rand::Rng;
+use std::time::Duration;
+
+/// Backoff between retries of a failed provider call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Delay ceiling before the first retry
+    pub base_delay: Duration,
+    /// Upper bound on the exponential ceiling
+    pub max_delay: Duration,
+    /// Longest server-requested wait we will honor before giving up on the hint
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(8),
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential ceiling for the given 0-based retry
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_delay)
+    }
+
+    /// Delay before the given 0-based retry after `error`. A server-provided
+    /// `Retry-After` hint wins; otherwise "equal jitter" keeps at least half of
+    /// the exponential ceiling and randomizes the rest.
+    pub fn delay_for(&self, retry: u32, error: &AIError) -> Duration {
+        if let Some(hint) = error.retry_after() {
+            return hint.min(self.max_retry_after);
+        }
+        let ceiling = self.backoff(retry);
+        let half = ceiling / 2;
+        let jitter_ms = half.as_millis() as u64;
+        let jitter = if jitter_ms == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_millis(rand::thread_rng().gen_range(0..=jitter_ms))
+        };
+        half + jitter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(250));
+        assert_eq!(policy.backoff(2), Duration::from_millis(1_000));
+        assert_eq!(policy.backoff(10), Duration::from_secs(8));
+        assert_eq!(policy.backoff(40), Duration::from_secs(8));
+
+        for retry in 0..6 {
+            let delay = policy.delay_for(retry, &AIError::NetworkError("reset".to_string()));
+            assert!(delay >= policy.backoff(retry) / 2);
+            assert!(delay <= policy.backoff(retry));
+        }
+    }
+
+    #[test]
+    fn retry_after_hints_override_backoff() {
+        let policy = RetryPolicy::default();
+
+        let hinted = AIError::RateLimit("slow down".to_string())
+            .with_retry_after(Some(Duration::from_millis(1_500)));
+        assert_eq!(hinted.retry_after(), Some(Duration::from_millis(1_500)));
+        assert_eq!(policy.delay_for(0, &hinted), Duration::from_millis(1_500));
+
+        let from_message =
+            AIError::RateLimit("Rate limit reached. Please try again in 2.5s.".to_string());
+        assert_eq!(
+            policy.delay_for(3, &from_message),
+            Duration::from_millis(2_500)
+        );
+
+        let excessive = AIError::RateLimit("quota".to_string())
+            .with_retry_after(Some(Duration::from_secs(3_600)));
+        assert_eq!(policy.delay_for(0, &excessive), policy.max_retry_after);
+
+        // Hints only apply to rate limits
+        let other = AIError::APIError("try again in 30s".to_string());
+        assert_eq!(other.retry_after(), None);
+        assert!(!AIError::InvalidRequest("bad".to_string()).is_retryable());
+    }
+}
@@ -1,19 +1,38 @@
 // Intelligent Router
 // Orchestrates load balancing, cost optimization, capability matching, and fallback
 
-use crate::ai::agent::runtime_events::RuntimeEventCallback;
+use crate::ai::agent::runtime_events::{
+    RuntimeContentDelta, RuntimeEventCallback, RuntimeStreamEvent,
+};
+use crate::ai::features::usage_analytics::UsageAnalytics;
 use crate::ai::provider_trait::ProviderWithStats;
 use crate::ai::provider_types::{
-    AIError, ChatCompletionRequest, ChatCompletionResponse, EmbeddingRequest, EmbeddingResponse,
-    ProviderCapabilities, ProviderEventCallback, ProviderId, ProviderResult, ProviderStreamEvent,
-    ProviderType, StreamingCallback,
+    AIError, ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
+    EmbeddingResponse, ProviderCapabilities, ProviderEventCallback, ProviderId, ProviderResult,
+    ProviderServed, ProviderStreamEvent, ProviderStreamUsage, ProviderType, StreamingCallback,
+    StreamingChunk, TokenUsage, UsageScope,
 };
 use crate::ai::router::fallback_chain::FallbackStrategy;
-use crate::ai::router::load_balancer::LoadBalancingStrategy;
+use crate::ai::router::hedging::{HedgePolicy, HedgeRace, Lane};
+use crate::ai::router::load_balancer::{LoadBalancerConfig, LoadBalancingStrategy};
+use crate::ai::router::pricing::{self, ModelPrice};
+use crate::ai::router::retry_policy::RetryPolicy;
 use crate::ai::router::{
     CapabilityMatcher, CircuitBreaker, CostOptimizer, FallbackChain, LoadBalancer,
 };
-use std::sync::Arc;
+use crate::ai::structured_output;
+use crate::ai::tokenizer::{self, TokenCounter};
+use crate::services::usage_ledger::{BudgetCheck, UsageLedger, UsageRecord};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Repair round-trips allowed when a structured reply fails validation
+const STRUCTURED_REPAIR_ATTEMPTS: usize = 1;
 
 /// Router configuration
 #[derive(Debug, Clone)]
@@ -28,16 +47,22 @@ pub struct RouterConfig {
     pub enable_capability_matching: bool,
     /// Maximum retry attempts
     pub max_retries: usize,
+    /// Backoff between retries
+    pub retry: RetryPolicy,
+    /// Hedged requests against slow providers
+    pub hedging: HedgePolicy,
 }
 
 impl Default for RouterConfig {
     fn default() -> Self {
         Self {
-            load_balancing_strategy: LoadBalancingStrategy::RoundRobin,
+            load_balancing_strategy: LoadBalancingStrategy::LatencyEwma,
             fallback_strategy: FallbackStrategy::SkipUnhealthy,
             enable_cost_optimization: true,
             enable_capability_matching: true,
             max_retries: 3,
+            retry: RetryPolicy::default(),
+            hedging: HedgePolicy::default(),
         }
     }
 }
@@ -57,6 +82,66 @@ pub struct IntelligentRouter {
     circuit_breakers: std::collections::HashMap<ProviderId, CircuitBreaker>,
     /// Configuration
     config: RouterConfig,
+    /// Persisted usage ledger; spend is only accounted once attached
+    usage_ledger: Option<Arc<UsageLedger>>,
+    /// In-memory token totals per provider
+    usage_analytics: UsageAnalytics,
+}
+
+/// Result of a routed call and the provider that produced it
+struct Served<T> {
+    provider: Arc<ProviderWithStats>,
+    value: T,
+    info: ProviderServed,
+}
+
+/// Await an in-flight call, or never resolve when the slot is empty
+async fn poll_lane<F: Future + Unpin>(lane: &mut Option<F>) -> F::Output {
+    match lane {
+        Some(call) => call.await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Usage observed on a streamed completion. Providers that never report usage
+/// fall back to a tokenizer estimate of the prompt and streamed output.
+#[derive(Default)]
+struct StreamUsageTap {
+    reported: Mutex<Option<ProviderStreamUsage>>,
+    output: Mutex<String>,
+}
+
+impl StreamUsageTap {
+    fn observe_usage(&self, usage: &ProviderStreamUsage) {
+        if let Ok(mut reported) = self.reported.lock() {
+            *reported = Some(usage.clone());
+        }
+    }
+
+    fn observe_text(&self, text: &str) {
+        if let Ok(mut output) = self.output.lock() {
+            output.push_str(text);
+        }
+    }
+
+    /// Model reported by the provider (if any), the usage and whether it was estimated.
+    fn finish(&self, request: &ChatCompletionRequest) -> (Option<String>, TokenUsage, bool) {
+        if let Some(usage) = self.reported.lock().ok().and_then(|r| r.clone()) {
+            let usage_tokens = usage.to_token_usage();
+            IntelligentRouter::calibrate_prompt_estimate(request, &usage_tokens);
+            return (usage.model.clone(), usage_tokens, false);
+        }
+        let output_tokens = self
+            .output
+            .lock()
+            .map(|output| TokenCounter::for_model(&request.model).count_text(&output))
+            .unwrap_or(0);
+        let usage = TokenUsage::new(
+            IntelligentRouter::estimate_prompt_tokens(request),
+            output_tokens as u32,
+        );
+        (None, usage, true)
+    }
 }
 
 impl IntelligentRouter {
@@ -83,12 +168,102 @@ impl IntelligentRouter {
     /// Create a new intelligent router
     pub fn new(config: RouterConfig) -> Self {
         Self {
-            load_balancer: LoadBalancer::default(),
+            load_balancer: LoadBalancer::new(LoadBalancerConfig {
+                strategy: config.load_balancing_strategy,
+                ..Default::default()
+            }),
             cost_optimizer: CostOptimizer::default(),
             capability_matcher: CapabilityMatcher::default(),
             fallback_chain: FallbackChain::default(),
             circuit_breakers: std::collections::HashMap::new(),
             config,
+            usage_ledger: None,
+            usage_analytics: UsageAnalytics::new(),
+        }
+    }
+
+    /// Attach the usage ledger used for spend accounting and budget enforcement
+    pub fn set_usage_ledger(&mut self, ledger: Arc<UsageLedger>) {
+        self.usage_ledger = Some(ledger);
+    }
+
+    /// In-memory token totals per provider since startup
+    #[allow(dead_code)]
+    pub fn usage_analytics(&self) -> &UsageAnalytics {
+        &self.usage_analytics
+    }
+
+    /// Spend against every budget that applies to `scope`, if a ledger is attached
+    pub async fn check_budgets(&self, scope: &UsageScope) -> Option<BudgetCheck> {
+        let ledger = self.usage_ledger.as_ref()?;
+        match ledger.check_budgets(scope).await {
+            Ok(check) => Some(check),
+            Err(e) => {
+                tracing::warn!("Budget check failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Refuse the call when a hard-stop budget for its scope is exhausted
+    async fn enforce_budgets(&self, request: &ChatCompletionRequest) -> ProviderResult<()> {
+        let scope = request.usage_scope.clone().unwrap_or_default();
+        if let Some(check) = self.check_budgets(&scope).await {
+            if let Some(status) = check.blocking() {
+                return Err(AIError::BudgetExceeded(status.describe()));
+            }
+        }
+        Ok(())
+    }
+
+    fn estimate_prompt_tokens(request: &ChatCompletionRequest) -> u32 {
+        TokenCounter::for_model(&request.model).count_request(request) as u32
+    }
+
+    /// Compare the provider-reported prompt size with the raw estimate so
+    /// later estimates for the same tokenizer family are corrected.
+    fn calibrate_prompt_estimate(request: &ChatCompletionRequest, usage: &TokenUsage) {
+        let estimated = TokenCounter::uncalibrated(&request.model).count_request(request);
+        tokenizer::observe_prompt_usage(&request.model, estimated as u32, usage.prompt_tokens);
+    }
+
+    /// Record a successful call in the analytics and, when attached, the ledger
+    async fn record_usage(
+        &self,
+        provider: &ProviderWithStats,
+        model: Option<&str>,
+        scope: Option<&UsageScope>,
+        usage: TokenUsage,
+        estimated: bool,
+    ) {
+        let provider = provider.provider();
+        self.usage_analytics
+            .record_usage(provider.id(), usage.clone())
+            .await;
+
+        let Some(ledger) = &self.usage_ledger else {
+            return;
+        };
+        let model = model
+            .filter(|model| !model.is_empty() && *model != "default")
+            .unwrap_or_else(|| provider.default_model());
+        let provider_type = provider.provider_type();
+        let price = if provider_type == ProviderType::Local {
+            Some(ModelPrice::free())
+        } else {
+            pricing::price_for_model(model)
+        };
+        let record = UsageRecord {
+            provider_id: provider.id().to_string(),
+            provider_type: provider_type.to_string(),
+            model: model.to_string(),
+            scope: scope.cloned().unwrap_or_default(),
+            cost_usd: price.map(|price| price.cost(&usage)),
+            usage,
+            estimated,
+        };
+        if let Err(e) = ledger.record(&record).await {
+            tracing::warn!("Usage ledger: {}", e);
         }
     }
 
@@ -136,72 +311,367 @@ impl IntelligentRouter {
         self.load_balancer.providers().to_vec()
     }
 
-    /// Complete a chat request with intelligent routing
-    pub async fn complete(
+    /// Providers to try for `request`, best first: the routing choice, then
+    /// (unless the model pins a provider) every other provider able to serve
+    /// the request, ranked by observed latency.
+    async fn candidates(
         &self,
-        request: ChatCompletionRequest,
-    ) -> ProviderResult<ChatCompletionResponse> {
-        if request.model != "default" {
-            crate::ai::model_catalog::ensure_supported_model_slug(&request.model)
-                .map_err(AIError::InvalidRequest)?;
+        request: &ChatCompletionRequest,
+        streaming: bool,
+    ) -> VecDeque<Arc<ProviderWithStats>> {
+        let mut candidates = VecDeque::new();
+        let Some(selected) = self.select_provider(request).await else {
+            return candidates;
+        };
+        candidates.push_back(selected);
+
+        let model = request.model.to_lowercase();
+        if crate::ai::model_catalog::requires_rainy_provider(&model)
+            || crate::ai::model_catalog::is_explicit_gemini_model(&model)
+        {
+            return candidates;
+        }
+
+        for provider in self.load_balancer.ranked_by_latency() {
+            let id = provider.provider().id();
+            if candidates.iter().any(|c| c.provider().id() == id) {
+                continue;
+            }
+            let Ok(caps) = provider.provider().capabilities().await else {
+                continue;
+            };
+            let serves_model = request.model == "default"
+                || caps
+                    .models
+                    .iter()
+                    .any(|m| m.eq_ignore_ascii_case(&request.model));
+            if caps.chat_completions && (caps.streaming || !streaming) && serves_model {
+                candidates.push_back(provider);
+            }
+        }
+        candidates
+    }
+
+    /// Pop the next candidate whose circuit breaker admits a request. Open
+    /// circuits are skipped without spending a retry.
+    async fn next_open(
+        &self,
+        candidates: &mut VecDeque<Arc<ProviderWithStats>>,
+    ) -> Option<Arc<ProviderWithStats>> {
+        while let Some(provider) = candidates.pop_front() {
+            match self.circuit_breakers.get(provider.provider().id()) {
+                Some(cb) if !cb.allow_request().await => {
+                    tracing::warn!(
+                        "Circuit breaker open for provider {}, skipping",
+                        provider.provider().id()
+                    );
+                }
+                _ => return Some(provider),
+            }
+        }
+        None
+    }
+
+    async fn record_outcome(&self, provider: &ProviderWithStats, lane: &Lane, success: bool) {
+        provider.update_stats(success, lane.elapsed_ms(), 0);
+        if let Some(cb) = self.circuit_breakers.get(provider.provider().id()) {
+            if success {
+                cb.record_success().await;
+            } else {
+                cb.record_failure().await;
+            }
+        }
+        if success {
+            if let Some(first_token_ms) = lane.first_token_ms() {
+                provider.record_first_token(first_token_ms);
+            }
+        }
+    }
+
+    /// Run `call` against candidate providers until one succeeds.
+    ///
+    /// Retryable failures rotate the provider to the back of the queue and
+    /// back off (honoring `Retry-After`) before it is tried again;
+    /// non-retryable failures drop it. With hedging enabled, a second
+    /// provider is raced against the first once the first has gone its p95
+    /// without output.
+    async fn dispatch<T, F, Fut>(
+        &self,
+        request: &ChatCompletionRequest,
+        streaming: bool,
+        call: F,
+    ) -> ProviderResult<Served<T>>
+    where
+        T: Send,
+        F: Fn(Arc<ProviderWithStats>, Arc<Lane>) -> Fut + Sync,
+        Fut: Future<Output = ProviderResult<T>> + Send,
+    {
+        let mut candidates = self.candidates(request, streaming).await;
+        if candidates.is_empty() {
+            return Err(Self::pinned_provider_error(&request.model)
+                .unwrap_or_else(|| AIError::Internal("No providers available".to_string())));
         }
+
         let mut last_error = None;
+        let mut recent_failures: Vec<(ProviderId, AIError)> = Vec::new();
+        let mut backoffs = 0;
+
+        for attempt in 1..=self.config.max_retries as u32 {
+            let Some(primary) = self.next_open(&mut candidates).await else {
+                break;
+            };
+            if let Some((_, error)) = recent_failures
+                .iter()
+                .find(|(id, _)| id == primary.provider().id())
+            {
+                let delay = self.config.retry.delay_for(backoffs, error);
+                backoffs += 1;
+                tracing::debug!(
+                    "Retrying provider {} in {}ms",
+                    primary.provider().id(),
+                    delay.as_millis()
+                );
+                tokio::time::sleep(delay).await;
+            }
 
-        for attempt in 0..self.config.max_retries {
-            // Select provider based on strategy
-            let provider = self.select_provider(&request).await;
+            let hedge_delay = self.config.hedging.delay_for(&primary.stats(), streaming);
+            let (outcome, dispatched) = self
+                .run_attempt(primary, hedge_delay, &mut candidates, &call)
+                .await;
+            if let Some(mut served) = outcome {
+                served.info.attempt = attempt;
+                return Ok(served);
+            }
 
-            if let Some(provider) = provider {
+            recent_failures.clear();
+            for (provider, error) in dispatched {
+                let Some(error) = error else {
+                    // Cancelled loser of a hedge race
+                    candidates.push_back(provider);
+                    continue;
+                };
                 let provider_id = provider.provider().id().clone();
-
-                // Check circuit breaker
-                let circuit_breaker = self.circuit_breakers.get(&provider_id);
-                if let Some(cb) = circuit_breaker {
-                    if !cb.allow_request().await {
-                        tracing::warn!(
-                            "Circuit breaker open for provider {}, skipping",
-                            provider_id
-                        );
-                        continue;
-                    }
+                tracing::warn!(
+                    "Provider {} failed on attempt {}: {}",
+                    provider_id,
+                    attempt,
+                    error
+                );
+                if error.is_retryable() {
+                    candidates.push_back(provider);
+                    recent_failures.push((provider_id, error.clone()));
                 }
+                last_error = Some(error);
+            }
+        }
 
-                // Execute request
-                let result = provider.provider().complete(request.clone()).await;
+        Err(last_error
+            .unwrap_or_else(|| AIError::Internal("All provider attempts failed".to_string())))
+    }
 
-                match result {
-                    Ok(response) => {
-                        // Record success
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_success().await;
-                        }
+    /// One dispatch attempt: `primary`, plus a hedge drawn from `candidates`
+    /// if the primary produces nothing within `hedge_delay`. Returns the
+    /// served result, if any, and every provider dispatched with its error
+    /// (`None` when its call was cancelled).
+    async fn run_attempt<T, F, Fut>(
+        &self,
+        primary: Arc<ProviderWithStats>,
+        hedge_delay: Option<Duration>,
+        candidates: &mut VecDeque<Arc<ProviderWithStats>>,
+        call: &F,
+    ) -> (
+        Option<Served<T>>,
+        Vec<(Arc<ProviderWithStats>, Option<AIError>)>,
+    )
+    where
+        T: Send,
+        F: Fn(Arc<ProviderWithStats>, Arc<Lane>) -> Fut + Sync,
+        Fut: Future<Output = ProviderResult<T>> + Send,
+    {
+        let race = HedgeRace::new();
+        let primary_lane = Lane::new(0, race.clone());
+        let mut lanes = vec![(primary.clone(), primary_lane.clone(), None)];
+        let mut primary_call = Some(Box::pin(call(primary, primary_lane)));
+        let mut hedge_call: Option<Pin<Box<Fut>>> = None;
+
+        let mut hedge_armed = hedge_delay.is_some();
+        let hedge_timer = tokio::time::sleep(hedge_delay.unwrap_or_default());
+        tokio::pin!(hedge_timer);
+
+        loop {
+            if let Some(winner) = race.winner() {
+                // Output is flowing from the winner; stop paying for the loser
+                hedge_armed = false;
+                if winner == 0 {
+                    hedge_call = None;
+                } else {
+                    primary_call = None;
+                }
+            }
+            if primary_call.is_none() && hedge_call.is_none() {
+                break;
+            }
 
-                        return Ok(response);
-                    }
-                    Err(e) => {
-                        // Record failure
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_failure().await;
+            let (index, result) = tokio::select! {
+                result = poll_lane(&mut primary_call) => (0, result),
+                result = poll_lane(&mut hedge_call) => (1, result),
+                _ = &mut hedge_timer, if hedge_armed => {
+                    hedge_armed = false;
+                    if primary_call.is_some() && race.winner().is_none() {
+                        if let Some(hedge) = self.next_open(candidates).await {
+                            tracing::info!(
+                                "Hedging request to provider {} after no output from {}",
+                                hedge.provider().id(),
+                                lanes[0].0.provider().id()
+                            );
+                            let lane = Lane::new(1, race.clone());
+                            hedge_call = Some(Box::pin(call(hedge.clone(), lane.clone())));
+                            lanes.push((hedge, lane, None));
                         }
-
-                        last_error = Some(e.clone());
-                        tracing::warn!(
-                            "Provider {} failed on attempt {}: {}",
-                            provider_id,
-                            attempt + 1,
-                            e
-                        );
                     }
+                    continue;
                 }
+                _ = race.claimed() => continue,
+            };
+            if index == 0 {
+                primary_call = None;
             } else {
-                return Err(Self::pinned_provider_error(&request.model)
-                    .unwrap_or_else(|| AIError::Internal("No providers available".to_string())));
+                hedge_call = None;
+            }
+
+            let (provider, lane, _) = &lanes[index];
+            match result {
+                Ok(value) => {
+                    if !race.claim(index) {
+                        // Lost the race after its output was discarded
+                        continue;
+                    }
+                    self.record_outcome(provider, lane, true).await;
+                    let served = Served {
+                        provider: provider.clone(),
+                        info: ProviderServed {
+                            provider_id: provider.provider().id().to_string(),
+                            attempt: 0,
+                            hedged: index > 0,
+                            first_token_ms: lane.first_token_ms(),
+                        },
+                        value,
+                    };
+                    return (Some(served), Self::dispatched(lanes));
+                }
+                Err(error) => {
+                    self.record_outcome(provider, lane, false).await;
+                    lanes[index].2 = Some(error);
+                    if race.winner() == Some(index) {
+                        // Failed after its output was already forwarded
+                        break;
+                    }
+                }
             }
         }
+        (None, Self::dispatched(lanes))
+    }
 
-        // All attempts failed
-        Err(last_error
-            .unwrap_or_else(|| AIError::Internal("All provider attempts failed".to_string())))
+    fn dispatched(
+        lanes: Vec<(Arc<ProviderWithStats>, Arc<Lane>, Option<AIError>)>,
+    ) -> Vec<(Arc<ProviderWithStats>, Option<AIError>)> {
+        lanes
+            .into_iter()
+            .map(|(provider, _, error)| (provider, error))
+            .collect()
+    }
+
+    /// Complete a chat request with intelligent routing
+    pub async fn complete(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> ProviderResult<ChatCompletionResponse> {
+        if request.model != "default" {
+            crate::ai::model_catalog::ensure_supported_model_slug(&request.model)
+                .map_err(AIError::InvalidRequest)?;
+        }
+        self.enforce_budgets(&request).await?;
+
+        let served = self
+            .dispatch(&request, false, |provider, _lane| {
+                let request = request.clone();
+                async move { provider.provider().complete(request).await }
+            })
+            .await?;
+
+        let mut response = served.value;
+        Self::calibrate_prompt_estimate(&request, &response.usage);
+        let model = if response.model.is_empty() {
+            &request.model
+        } else {
+            &response.model
+        };
+        self.record_usage(
+            &served.provider,
+            Some(model.as_str()),
+            request.usage_scope.as_ref(),
+            response.usage.clone(),
+            false,
+        )
+        .await;
+        response.served_by = Some(served.info);
+        Ok(response)
+    }
+
+    /// Complete a request whose reply must deserialize into `T`.
+    ///
+    /// The schema for `T` is sent as `response_schema` so providers with native
+    /// structured output constrain decoding. Replies are still validated
+    /// locally; a non-conforming one gets a repair retry listing the violations.
+    pub async fn complete_structured<T>(
+        &self,
+        mut request: ChatCompletionRequest,
+    ) -> ProviderResult<T>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        let schema = schemars::schema_for!(T);
+        let insert_at = request
+            .messages
+            .iter()
+            .take_while(|message| message.role == "system")
+            .count();
+        request.messages.insert(
+            insert_at,
+            ChatMessage::system(structured_output::schema_instructions(&schema)),
+        );
+        request.response_schema = Some(schema.clone());
+        request.stream = false;
+
+        let mut attempt = 0;
+        loop {
+            let response = self.complete(request.clone()).await?;
+            let text = response.content.unwrap_or_default();
+            let problems = match structured_output::extract_json(&text) {
+                None => vec!["reply is not valid JSON".to_string()],
+                Some(value) => match structured_output::validate(&schema, &value) {
+                    Ok(()) => match serde_json::from_value::<T>(value) {
+                        Ok(parsed) => return Ok(parsed),
+                        Err(e) => vec![e.to_string()],
+                    },
+                    Err(errors) => errors,
+                },
+            };
+
+            if attempt >= STRUCTURED_REPAIR_ATTEMPTS {
+                return Err(AIError::SchemaViolation(problems.join("; ")));
+            }
+            attempt += 1;
+            tracing::warn!(
+                "Structured reply failed validation ({} problems); retrying",
+                problems.len()
+            );
+            request.messages.push(ChatMessage::assistant(text));
+            request.messages.push(ChatMessage::user(format!(
+                "Your reply does not match the required JSON Schema:\n- {}\n\nReply again with only the corrected JSON.",
+                problems.join("\n- ")
+            )));
+        }
     }
 
     /// Complete a chat request with streaming
@@ -214,66 +684,46 @@ impl IntelligentRouter {
             crate::ai::model_catalog::ensure_supported_model_slug(&request.model)
                 .map_err(AIError::InvalidRequest)?;
         }
-        let mut last_error = None;
-
-        for attempt in 0..self.config.max_retries {
-            // Select provider based on strategy
-            let provider = self.select_provider(&request).await;
-
-            if let Some(provider) = provider {
-                let provider_id = provider.provider().id().clone();
-
-                // Check circuit breaker
-                let circuit_breaker = self.circuit_breakers.get(&provider_id);
-                if let Some(cb) = circuit_breaker {
-                    if !cb.allow_request().await {
-                        tracing::warn!(
-                            "Circuit breaker open for provider {}, skipping",
-                            provider_id
-                        );
-                        continue;
-                    }
-                }
-
-                // Execute request
-                let result = provider
-                    .provider()
-                    .complete_stream(request.clone(), Arc::clone(&callback))
-                    .await;
-
-                match result {
-                    Ok(()) => {
-                        // Record success
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_success().await;
+        self.enforce_budgets(&request).await?;
+
+        let served = self
+            .dispatch(&request, true, |provider, lane| {
+                let request = request.clone();
+                let tap = Arc::new(StreamUsageTap::default());
+                let tapped: StreamingCallback = {
+                    let tap = Arc::clone(&tap);
+                    let callback = Arc::clone(&callback);
+                    Arc::new(move |chunk: StreamingChunk| {
+                        if !lane.admit() {
+                            return;
                         }
-
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        // Record failure
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_failure().await;
+                        tap.observe_text(&chunk.content);
+                        if let Some(thought) = &chunk.thought {
+                            tap.observe_text(thought);
                         }
-
-                        last_error = Some(e.clone());
-                        tracing::warn!(
-                            "Provider {} failed on attempt {}: {}",
-                            provider_id,
-                            attempt + 1,
-                            e
-                        );
-                    }
+                        callback(chunk);
+                    })
+                };
+                async move {
+                    provider
+                        .provider()
+                        .complete_stream(request, tapped)
+                        .await
+                        .map(|()| tap)
                 }
-            } else {
-                return Err(Self::pinned_provider_error(&request.model)
-                    .unwrap_or_else(|| AIError::Internal("No providers available".to_string())));
-            }
-        }
-
-        // All attempts failed
-        Err(last_error
-            .unwrap_or_else(|| AIError::Internal("All provider attempts failed".to_string())))
+            })
+            .await?;
+
+        let (_, usage, estimated) = served.value.finish(&request);
+        self.record_usage(
+            &served.provider,
+            Some(request.model.as_str()),
+            request.usage_scope.as_ref(),
+            usage,
+            estimated,
+        )
+        .await;
+        Ok(())
     }
 
     /// Complete a chat request with the provider event stream.
@@ -287,61 +737,52 @@ impl IntelligentRouter {
             crate::ai::model_catalog::ensure_supported_model_slug(&request.model)
                 .map_err(AIError::InvalidRequest)?;
         }
-        let mut last_error = None;
-
-        for attempt in 0..self.config.max_retries {
-            let provider = self.select_provider(&request).await;
-
-            if let Some(provider) = provider {
-                let provider_id = provider.provider().id().clone();
-
-                if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                    if !cb.allow_request().await {
-                        tracing::warn!(
-                            "Circuit breaker open for provider {}, skipping",
-                            provider_id
-                        );
-                        continue;
-                    }
-                }
-
-                let result = provider
-                    .provider()
-                    .complete_event_stream(request.clone(), Arc::clone(&callback))
-                    .await;
-
-                match result {
-                    Ok(()) => {
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_success().await;
+        self.enforce_budgets(&request).await?;
+
+        let served = self
+            .dispatch(&request, true, |provider, lane| {
+                let request = request.clone();
+                let tap = Arc::new(StreamUsageTap::default());
+                let tapped: ProviderEventCallback = {
+                    let tap = Arc::clone(&tap);
+                    let callback = Arc::clone(&callback);
+                    Arc::new(move |event: ProviderStreamEvent| {
+                        if !lane.admit() {
+                            return;
                         }
-
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_failure().await;
+                        match &event {
+                            ProviderStreamEvent::TextDelta(text)
+                            | ProviderStreamEvent::ThoughtDelta(text) => tap.observe_text(text),
+                            ProviderStreamEvent::Usage(usage) => tap.observe_usage(usage),
+                            _ => {}
                         }
-
-                        last_error = Some(e.clone());
-                        tracing::warn!(
-                            "Provider {} failed on attempt {}: {}",
-                            provider_id,
-                            attempt + 1,
-                            e
-                        );
-                    }
+                        callback(event);
+                    })
+                };
+                async move {
+                    provider
+                        .provider()
+                        .complete_event_stream(request, tapped)
+                        .await
+                        .map(|()| tap)
                 }
-            } else {
-                return Err(Self::pinned_provider_error(&request.model)
-                    .unwrap_or_else(|| AIError::Internal("No providers available".to_string())));
-            }
-        }
-
-        Err(last_error
-            .unwrap_or_else(|| AIError::Internal("All provider attempts failed".to_string())))
+            })
+            .await?;
+
+        let (model, usage, estimated) = served.value.finish(&request);
+        self.record_usage(
+            &served.provider,
+            Some(model.as_deref().unwrap_or(&request.model)),
+            request.usage_scope.as_ref(),
+            usage,
+            estimated,
+        )
+        .await;
+        Ok(())
     }
 
+    /// Complete a chat request with runtime stream events. The provider that
+    /// served the turn is reported as a final `ProviderServed` event.
     pub async fn complete_runtime_stream(
         &self,
         request: ChatCompletionRequest,
@@ -351,59 +792,55 @@ impl IntelligentRouter {
             crate::ai::model_catalog::ensure_supported_model_slug(&request.model)
                 .map_err(AIError::InvalidRequest)?;
         }
-        let mut last_error = None;
-
-        for attempt in 0..self.config.max_retries {
-            let provider = self.select_provider(&request).await;
-
-            if let Some(provider) = provider {
-                let provider_id = provider.provider().id().clone();
-
-                if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                    if !cb.allow_request().await {
-                        tracing::warn!(
-                            "Circuit breaker open for provider {}, skipping",
-                            provider_id
-                        );
-                        continue;
-                    }
-                }
-
-                let result = provider
-                    .provider()
-                    .complete_runtime_stream(request.clone(), Arc::clone(&callback))
-                    .await;
-
-                match result {
-                    Ok(()) => {
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_success().await;
+        self.enforce_budgets(&request).await?;
+
+        let served = self
+            .dispatch(&request, true, |provider, lane| {
+                let request = request.clone();
+                let tap = Arc::new(StreamUsageTap::default());
+                let tapped: RuntimeEventCallback = {
+                    let tap = Arc::clone(&tap);
+                    let callback = Arc::clone(&callback);
+                    Arc::new(move |event: RuntimeStreamEvent| {
+                        // A hedge must not open a second turn
+                        let forward = match &event {
+                            RuntimeStreamEvent::TurnStarted { .. } => lane.is_primary(),
+                            _ => lane.admit(),
+                        };
+                        if !forward {
+                            return;
                         }
-
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        if let Some(cb) = self.circuit_breakers.get(&provider_id) {
-                            cb.record_failure().await;
+                        match &event {
+                            RuntimeStreamEvent::ContentDelta(RuntimeContentDelta {
+                                delta, ..
+                            }) => tap.observe_text(delta),
+                            RuntimeStreamEvent::Usage(usage) => tap.observe_usage(usage),
+                            _ => {}
                         }
-
-                        last_error = Some(e.clone());
-                        tracing::warn!(
-                            "Provider {} failed on attempt {}: {}",
-                            provider_id,
-                            attempt + 1,
-                            e
-                        );
-                    }
+                        callback(event);
+                    })
+                };
+                async move {
+                    provider
+                        .provider()
+                        .complete_runtime_stream(request, tapped)
+                        .await
+                        .map(|()| tap)
                 }
-            } else {
-                return Err(Self::pinned_provider_error(&request.model)
-                    .unwrap_or_else(|| AIError::Internal("No providers available".to_string())));
-            }
-        }
-
-        Err(last_error
-            .unwrap_or_else(|| AIError::Internal("All provider attempts failed".to_string())))
+            })
+            .await?;
+
+        let (model, usage, estimated) = served.value.finish(&request);
+        self.record_usage(
+            &served.provider,
+            Some(model.as_deref().unwrap_or(&request.model)),
+            request.usage_scope.as_ref(),
+            usage,
+            estimated,
+        )
+        .await;
+        callback(RuntimeStreamEvent::ProviderServed(served.info));
+        Ok(())
     }
 
     /// Generate embeddings with intelligent routing
@@ -438,6 +875,14 @@ impl IntelligentRouter {
                         if let Some(cb) = self.circuit_breakers.get(&provider_id) {
                             cb.record_success().await;
                         }
+                        self.record_usage(
+                            &provider,
+                            Some(response.model.as_str()),
+                            None,
+                            response.usage.clone(),
+                            false,
+                        )
+                        .await;
 
                         return Ok(response);
                     }
@@ -522,11 +967,7 @@ impl IntelligentRouter {
 
         // If cost optimization is enabled, try cost optimizer first
         if self.config.enable_cost_optimization {
-            let estimated_input = request
-                .messages
-                .iter()
-                .map(|m| m.content.text().len() as u32 / 4) // Rough estimate: 4 chars per token
-                .sum::<u32>();
+            let estimated_input = Self::estimate_prompt_tokens(request);
             let estimated_output = request.max_tokens.unwrap_or(1000);
 
             if let Some(provider) = self
@@ -639,6 +1080,10 @@ impl IntelligentRouter {
 
     /// Set configuration
     pub fn set_config(&mut self, config: RouterConfig) {
+        self.load_balancer.set_config(LoadBalancerConfig {
+            strategy: config.load_balancing_strategy,
+            ..self.load_balancer.config().clone()
+        });
         self.config = config;
     }
 }
@@ -657,6 +1102,7 @@ pub struct RouterStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ai::router::test_support::ScriptedProvider;
 
     #[test]
     fn test_router_creation() {
@@ -673,4 +1119,302 @@ mod tests {
         let router = IntelligentRouter::new(config);
         assert_eq!(router.config().max_retries, 5);
     }
+
+    #[tokio::test]
+    async fn test_hard_stop_budget_blocks_dispatch() {
+        use crate::services::usage_ledger::{
+            BudgetMode, BudgetPeriod, BudgetScope, UsageBudgetInput,
+        };
+
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory sqlite pool");
+        let ledger = Arc::new(UsageLedger::new(pool));
+        ledger.init().await.unwrap();
+        ledger
+            .upsert_budget(UsageBudgetInput {
+                id: None,
+                scope: BudgetScope::Workspace,
+                scope_id: Some("ws-1".to_string()),
+                period: BudgetPeriod::Monthly,
+                limit_usd: 1.0,
+                mode: BudgetMode::HardStop,
+                warn_ratio: None,
+                enabled: None,
+            })
+            .await
+            .unwrap();
+        let scope = UsageScope {
+            workspace_id: Some("ws-1".to_string()),
+            ..Default::default()
+        };
+        ledger
+            .record(&UsageRecord {
+                provider_id: "openai".to_string(),
+                provider_type: "openai".to_string(),
+                model: "gpt-4o".to_string(),
+                scope: scope.clone(),
+                usage: TokenUsage::new(1_000, 1_000),
+                cost_usd: Some(1.5),
+                estimated: false,
+            })
+            .await
+            .unwrap();
+
+        let mut router = IntelligentRouter::default();
+        router.set_usage_ledger(ledger);
+
+        let blocked = router
+            .complete(ChatCompletionRequest {
+                usage_scope: Some(scope),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(blocked, Err(AIError::BudgetExceeded(_))));
+
+        // Other workspaces still reach provider selection.
+        let other = router
+            .complete(ChatCompletionRequest {
+                usage_scope: Some(UsageScope {
+                    workspace_id: Some("ws-2".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(other, Err(AIError::Internal(_))));
+    }
+
+    #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+    struct Verdict {
+        approved: bool,
+        reasons: Vec<String>,
+    }
+
+    fn router_with(provider: Arc<ScriptedProvider>) -> IntelligentRouter {
+        let mut router = IntelligentRouter::default();
+        router.add_provider(Arc::new(ProviderWithStats::new(provider)));
+        router
+    }
+
+    #[tokio::test]
+    async fn test_complete_structured_parses_and_attaches_schema() {
+        let provider = Arc::new(ScriptedProvider::new(
+            "scripted",
+            &["```json\n{\"approved\": true, \"reasons\": [\"tests pass\"]}\n```"],
+        ));
+        let router = router_with(provider.clone());
+
+        let verdict: Verdict = router
+            .complete_structured(ChatCompletionRequest {
+                messages: vec![ChatMessage::user("Review this")],
+                ..Default::default()
+            })
+            .await
+            .expect("structured reply");
+        assert!(verdict.approved);
+        assert_eq!(verdict.reasons, vec!["tests pass".to_string()]);
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].response_schema.is_some());
+        assert_eq!(requests[0].messages[0].role, "system");
+    }
+
+    #[tokio::test]
+    async fn test_complete_structured_repairs_invalid_reply_once() {
+        let provider = Arc::new(ScriptedProvider::new(
+            "scripted",
+            &[
+                r#"{"approved": "yes"}"#,
+                r#"{"approved": false, "reasons": []}"#,
+            ],
+        ));
+        let router = router_with(provider.clone());
+
+        let verdict: Verdict = router
+            .complete_structured(ChatCompletionRequest::default())
+            .await
+            .expect("repaired reply");
+        assert!(!verdict.approved);
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let repair = requests[1].messages.last().unwrap().text();
+        assert!(repair.contains("/approved: expected boolean"));
+        assert!(repair.contains("missing required property \"reasons\""));
+    }
+
+    #[tokio::test]
+    async fn test_complete_structured_gives_up_after_repair() {
+        let provider = Arc::new(ScriptedProvider::new(
+            "scripted",
+            &["not json", "still not json"],
+        ));
+        let router = router_with(provider);
+
+        let result = router
+            .complete_structured::<Verdict>(ChatCompletionRequest::default())
+            .await;
+        assert!(matches!(result, Err(AIError::SchemaViolation(_))));
+    }
+
+    /// Router ranked purely by observed latency, with near-instant backoff
+    fn latency_router(
+        providers: &[&Arc<ScriptedProvider>],
+        hedging: HedgePolicy,
+    ) -> (IntelligentRouter, Vec<Arc<ProviderWithStats>>) {
+        let mut router = IntelligentRouter::new(RouterConfig {
+            enable_cost_optimization: false,
+            enable_capability_matching: false,
+            retry: RetryPolicy {
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(5),
+                ..Default::default()
+            },
+            hedging,
+            ..Default::default()
+        });
+        let tracked = providers
+            .iter()
+            .map(|provider| {
+                let tracked = Arc::new(ProviderWithStats::new((*provider).clone()));
+                router.add_provider(tracked.clone());
+                tracked
+            })
+            .collect();
+        (router, tracked)
+    }
+
+    fn fast_hedging() -> HedgePolicy {
+        HedgePolicy {
+            enabled: true,
+            min_delay: Duration::from_millis(20),
+            max_delay: Duration::from_millis(20),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_provider_is_retried_after_retry_after() {
+        let provider = Arc::new(ScriptedProvider::results(
+            "only",
+            vec![
+                Err(AIError::RateLimit("busy".to_string())
+                    .with_retry_after(Some(Duration::from_millis(30)))),
+                Ok("done".to_string()),
+            ],
+        ));
+        let (router, _) = latency_router(&[&provider], HedgePolicy::default());
+
+        let started = std::time::Instant::now();
+        let response = router
+            .complete(ChatCompletionRequest::default())
+            .await
+            .expect("retried reply");
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        assert_eq!(response.content.as_deref(), Some("done"));
+        let served = response.served_by.expect("served_by");
+        assert_eq!(served.provider_id, "only");
+        assert_eq!(served.attempt, 2);
+        assert!(!served.hedged);
+        assert_eq!(provider.request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_error_fails_over_without_retrying() {
+        let broken = Arc::new(ScriptedProvider::results(
+            "broken",
+            vec![
+                Err(AIError::Authentication("bad key".to_string())),
+                Ok("unreachable".to_string()),
+            ],
+        ));
+        let healthy = Arc::new(ScriptedProvider::new("healthy", &["ok"]));
+        let (router, _) = latency_router(&[&broken, &healthy], HedgePolicy::default());
+
+        let response = router
+            .complete(ChatCompletionRequest::default())
+            .await
+            .expect("failover reply");
+        assert_eq!(response.content.as_deref(), Some("ok"));
+        assert_eq!(response.served_by.map(|s| s.attempt), Some(2));
+        assert_eq!(broken.request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_is_skipped_without_spending_an_attempt() {
+        let tripped = Arc::new(ScriptedProvider::new("tripped", &["unreachable"]));
+        let healthy = Arc::new(ScriptedProvider::new("healthy", &["ok"]));
+        let (mut router, _) = latency_router(&[&tripped, &healthy], HedgePolicy::default());
+        router.config.max_retries = 1;
+        let breaker = &router.circuit_breakers[&ProviderId::new("tripped")];
+        for _ in 0..5 {
+            breaker.record_failure().await;
+        }
+
+        let response = router
+            .complete(ChatCompletionRequest::default())
+            .await
+            .expect("reply from the healthy provider");
+        let served = response.served_by.expect("served_by");
+        assert_eq!(served.provider_id, "healthy");
+        assert_eq!(served.attempt, 1);
+        assert_eq!(tripped.request_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_provider_is_hedged_after_p95() {
+        let slow = Arc::new(
+            ScriptedProvider::new("slow", &["slow reply"]).with_delay(Duration::from_millis(500)),
+        );
+        let fast = Arc::new(ScriptedProvider::new("fast", &["fast reply"]));
+        let (router, tracked) = latency_router(&[&slow, &fast], fast_hedging());
+        for _ in 0..5 {
+            tracked[0].update_stats(true, 10, 0);
+            tracked[1].update_stats(true, 50, 0);
+        }
+
+        let response = router
+            .complete(ChatCompletionRequest::default())
+            .await
+            .expect("hedged reply");
+        assert_eq!(response.content.as_deref(), Some("fast reply"));
+        let served = response.served_by.expect("served_by");
+        assert_eq!(served.provider_id, "fast");
+        assert!(served.hedged);
+        assert_eq!(served.attempt, 1);
+        // The cancelled primary was dispatched but records no sample
+        assert_eq!(slow.request_count(), 1);
+        assert_eq!(tracked[0].stats().total_requests, 5);
+    }
+
+    #[tokio::test]
+    async fn test_hedged_stream_forwards_only_the_winner() {
+        let slow = Arc::new(
+            ScriptedProvider::new("slow", &["slow reply"]).with_delay(Duration::from_millis(500)),
+        );
+        let fast = Arc::new(ScriptedProvider::new("fast", &["fast reply"]));
+        let (router, tracked) = latency_router(&[&slow, &fast], fast_hedging());
+        for _ in 0..5 {
+            tracked[0].update_stats(true, 10, 0);
+            tracked[0].record_first_token(10);
+            tracked[1].update_stats(true, 50, 0);
+        }
+
+        let received = Arc::new(Mutex::new(String::new()));
+        let sink = Arc::clone(&received);
+        router
+            .complete_stream(
+                ChatCompletionRequest::default(),
+                Arc::new(move |chunk: StreamingChunk| {
+                    sink.lock().unwrap().push_str(&chunk.content);
+                }),
+            )
+            .await
+            .expect("hedged stream");
+        assert_eq!(received.lock().unwrap().as_str(), "fast reply");
+        assert_eq!(tracked[1].stats().successful_requests, 6);
+    }
 }
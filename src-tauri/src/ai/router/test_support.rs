@@ -0,0 +1,139 @@
+// Router test support
+// Scripted provider shared by the router component tests
+
+use crate::ai::provider_trait::{AIProvider, ProviderWithStats};
+use crate::ai::provider_types::{
+    AIError, ChatCompletionRequest, ChatCompletionResponse, EmbeddingRequest, EmbeddingResponse,
+    ProviderCapabilities, ProviderConfig, ProviderHealth, ProviderId, ProviderResult, ProviderType,
+    StreamingCallback, StreamingChunk, TokenUsage,
+};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Provider that replays canned replies and records what it was sent
+pub struct ScriptedProvider {
+    id: ProviderId,
+    config: ProviderConfig,
+    replies: Mutex<VecDeque<ProviderResult<String>>>,
+    delay: Option<Duration>,
+    pub requests: Mutex<Vec<ChatCompletionRequest>>,
+}
+
+impl ScriptedProvider {
+    pub fn new(id: &str, replies: &[&str]) -> Self {
+        Self::results(id, replies.iter().map(|r| Ok(r.to_string())).collect())
+    }
+
+    pub fn results(id: &str, replies: Vec<ProviderResult<String>>) -> Self {
+        Self {
+            id: ProviderId::new(id),
+            config: Default::default(),
+            replies: Mutex::new(replies.into()),
+            delay: None,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Wait this long before answering (or streaming the first chunk)
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Wrap in the router's stats-tracking handle
+    pub fn with_stats(id: &str, replies: &[&str]) -> Arc<ProviderWithStats> {
+        Arc::new(ProviderWithStats::new(Arc::new(Self::new(id, replies))))
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    async fn next_reply(&self, request: ChatCompletionRequest) -> ProviderResult<String> {
+        self.requests.lock().unwrap().push(request);
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        self.replies
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or_else(|| Err(AIError::APIError("script exhausted".to_string())))
+    }
+}
+
+#[async_trait::async_trait]
+impl AIProvider for ScriptedProvider {
+    fn id(&self) -> &ProviderId {
+        &self.id
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::Custom
+    }
+
+    async fn capabilities(&self) -> ProviderResult<ProviderCapabilities> {
+        Ok(ProviderCapabilities {
+            streaming: true,
+            ..Default::default()
+        })
+    }
+
+    async fn health_check(&self) -> ProviderResult<ProviderHealth> {
+        Ok(ProviderHealth::Healthy)
+    }
+
+    async fn complete(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> ProviderResult<ChatCompletionResponse> {
+        let reply = self.next_reply(request).await?;
+        Ok(ChatCompletionResponse {
+            content: Some(reply),
+            tool_calls: None,
+            model: "scripted-model".to_string(),
+            usage: TokenUsage::new(10, 10),
+            finish_reason: "stop".to_string(),
+            provider_metadata: None,
+            served_by: None,
+        })
+    }
+
+    async fn complete_stream(
+        &self,
+        request: ChatCompletionRequest,
+        callback: StreamingCallback,
+    ) -> ProviderResult<()> {
+        let reply = self.next_reply(request).await?;
+        callback(StreamingChunk {
+            content: reply,
+            thought: None,
+            is_final: false,
+            finish_reason: None,
+        });
+        callback(StreamingChunk {
+            content: String::new(),
+            thought: None,
+            is_final: true,
+            finish_reason: Some("stop".to_string()),
+        });
+        Ok(())
+    }
+
+    async fn embed(&self, _request: EmbeddingRequest) -> ProviderResult<EmbeddingResponse> {
+        Err(AIError::UnsupportedCapability("embeddings".to_string()))
+    }
+
+    fn default_model(&self) -> &str {
+        "scripted-model"
+    }
+
+    async fn available_models(&self) -> ProviderResult<Vec<String>> {
+        Ok(vec!["scripted-model".to_string()])
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+}
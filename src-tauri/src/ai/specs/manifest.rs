@@ -64,6 +64,32 @@ pub struct RuntimeConfig {
     pub delegation: DelegationConfig,
     #[serde(default)]
     pub language_policy: LanguagePolicyConfig,
+    /// Extra specialist roles, or overrides of the built-in four by id.
+    #[serde(default)]
+    pub specialists: Vec<SpecialistDefinition>,
+}
+
+/// A specialist role declared in the spec. Supervisors schedule it like the
+/// built-in roles; `id` is the role name on the wire.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecialistDefinition {
+    pub id: String,
+    /// What the role is for; shown to the planner when it picks lanes.
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub system_prompt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Tool allowlist for the role.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_steps: Option<usize>,
+    /// Highest Airlock level (0 safe, 1 sensitive, 2 dangerous) the role may run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airlock_ceiling: Option<u8>,
 }
 
 fn default_max_specialists() -> u8 {
@@ -113,6 +139,11 @@ pub struct DelegationConfig {
     pub job_max_runtime_seconds: u32,
     #[serde(default)]
     pub final_synthesis_required: bool,
+    /// Run write-capable specialists in private git worktrees and merge
+    /// their changes back when they finish, so parallel lanes cannot
+    /// clobber each other's files.
+    #[serde(default)]
+    pub isolate_writers: bool,
 }
 
 impl Default for DelegationConfig {
@@ -124,6 +155,7 @@ impl Default for DelegationConfig {
             max_parallel_subagents: default_max_specialists(),
             job_max_runtime_seconds: default_job_max_runtime_seconds(),
             final_synthesis_required: true,
+            isolate_writers: false,
         }
     }
 }
@@ -163,6 +195,7 @@ impl Default for RuntimeConfig {
             verification_required: default_verification_required(),
             delegation: DelegationConfig::default(),
             language_policy: LanguagePolicyConfig::default(),
+            specialists: Vec::new(),
         }
     }
 }
@@ -197,6 +230,25 @@ impl RuntimeConfig {
         self.max_specialists = self.max_specialists.clamp(1, 4);
         self.delegation.normalize();
         self.language_policy.normalize();
+        let mut seen = std::collections::HashSet::new();
+        self.specialists.retain_mut(|definition| {
+            definition.id = definition
+                .id
+                .trim()
+                .to_ascii_lowercase()
+                .replace([' ', '-'], "_");
+            !definition.id.is_empty() && seen.insert(definition.id.clone())
+        });
+        for definition in &mut self.specialists {
+            definition.max_steps = definition.max_steps.map(|steps| steps.clamp(4, 200));
+            definition.airlock_ceiling = definition.airlock_ceiling.map(|level| level.min(2));
+        }
+    }
+
+    pub fn specialist(&self, id: &str) -> Option<&SpecialistDefinition> {
+        self.specialists
+            .iter()
+            .find(|definition| definition.id == id)
     }
 }
 
@@ -217,6 +269,10 @@ pub struct AirlockConfig {
 
     #[serde(default)]
     pub rate_limits: AirlockRateLimits,
+
+    /// Tools resolving above this level are blocked outright.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_level: Option<u8>,
 }
 
 impl Default for AirlockConfig {
@@ -226,6 +282,7 @@ impl Default for AirlockConfig {
             tool_levels: HashMap::new(),
             scopes: AirlockScopes::default(),
             rate_limits: AirlockRateLimits::default(),
+            max_level: None,
         }
     }
 }
@@ -323,6 +380,9 @@ pub struct MemoryConfig {
 pub struct RetrievalConfig {
     pub retention_days: u32,
     pub max_tokens: u32,
+    /// Rescore hybrid results locally against the query text
+    #[serde(default)]
+    pub rerank: bool,
 }
 
 impl Default for RetrievalConfig {
@@ -330,6 +390,7 @@ impl Default for RetrievalConfig {
         Self {
             retention_days: 30,
             max_tokens: 32000,
+            rerank: false,
         }
     }
 }
@@ -0,0 +1,471 @@
+// Structured Output
+// Provider-agnostic JSON Schema helpers: native request mappings, JSON
+// extraction from free text and a local validator for providers that cannot
+// constrain decoding themselves.
+
+use schemars::schema::RootSchema;
+use serde_json::{Map, Value};
+
+/// Name used for the schema in provider payloads (`[A-Za-z0-9_-]`, max 64 chars)
+pub fn schema_name(schema: &RootSchema) -> String {
+    let title = schema
+        .schema
+        .metadata
+        .as_ref()
+        .and_then(|meta| meta.title.as_deref())
+        .unwrap_or("response");
+    let name: String = title
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    if name.is_empty() {
+        "response".to_string()
+    } else {
+        name
+    }
+}
+
+/// Schema as a plain JSON value, without the `$schema` meta keyword
+pub fn schema_value(schema: &RootSchema) -> Value {
+    let mut value = serde_json::to_value(schema).unwrap_or_else(|_| Value::Object(Map::new()));
+    if let Some(root) = value.as_object_mut() {
+        root.remove("$schema");
+    }
+    value
+}
+
+/// OpenAI-style `response_format` payload (also accepted by xAI and most
+/// OpenAI-compatible servers). Strict mode is left off because schemars output
+/// does not satisfy its "every property required" rule.
+pub fn openai_response_format(schema: &RootSchema) -> Value {
+    serde_json::json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": schema_name(schema),
+            "schema": schema_value(schema),
+            "strict": false,
+        },
+    })
+}
+
+/// Responses API `text` payload carrying the schema as `text.format`
+pub fn responses_text_format(schema: &RootSchema) -> Value {
+    serde_json::json!({
+        "format": {
+            "type": "json_schema",
+            "name": schema_name(schema),
+            "schema": schema_value(schema),
+            "strict": false,
+        },
+    })
+}
+
+/// System instruction describing the expected output, for prompting-only paths
+pub fn schema_instructions(schema: &RootSchema) -> String {
+    format!(
+        "Respond with a single JSON value that conforms to this JSON Schema. \
+         Do not wrap it in markdown or add any commentary.\n\n{}",
+        schema_value(schema)
+    )
+}
+
+/// Pull a JSON value out of a model reply, tolerating markdown fences and
+/// leading or trailing prose.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+
+    if let Some(fenced) = strip_code_fence(trimmed) {
+        if let Ok(value) = serde_json::from_str(fenced) {
+            return Some(value);
+        }
+    }
+
+    let start = trimmed.find(['{', '['])?;
+    let close = if trimmed[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = trimmed.rfind(close)?;
+    if end <= start {
+        return None;
+    }
+    serde_json::from_str(&trimmed[start..=end]).ok()
+}
+
+fn strip_code_fence(text: &str) -> Option<&str> {
+    let start = text.find("```")?;
+    let after = &text[start + 3..];
+    let body_start = after.find('\n')? + 1;
+    let body = &after[body_start..];
+    let end = body.find("```")?;
+    Some(body[..end].trim())
+}
+
+/// Validate `value` against `schema`, returning every violation found.
+///
+/// Covers the subset of JSON Schema that schemars emits: `type`, `enum`,
+/// `const`, object/array/string/number bounds, `allOf`/`anyOf`/`oneOf` and
+/// local `$ref`s into `definitions`.
+pub fn validate(schema: &RootSchema, value: &Value) -> Result<(), Vec<String>> {
+    let root = schema_value(schema);
+    let definitions = root
+        .get("definitions")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let mut errors = Vec::new();
+    Validator {
+        definitions: &definitions,
+    }
+    .check(&root, value, "", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+struct Validator<'a> {
+    definitions: &'a Map<String, Value>,
+}
+
+impl Validator<'_> {
+    fn check(&self, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                errors.push(format!("{}: no value is allowed here", display_path(path)));
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference
+                .strip_prefix("#/definitions/")
+                .and_then(|name| self.definitions.get(name))
+            {
+                Some(target) => self.check(target, value, path, errors),
+                None => errors.push(format!(
+                    "{}: unresolvable reference {}",
+                    display_path(path),
+                    reference
+                )),
+            }
+        }
+
+        if let Some(types) = schema.get("type") {
+            let allowed: Vec<&str> = match types {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+                _ => vec![],
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|t| matches_type(t, value)) {
+                errors.push(format!(
+                    "{}: expected {}, got {}",
+                    display_path(path),
+                    allowed.join(" or "),
+                    type_name(value)
+                ));
+                return;
+            }
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(value) {
+                errors.push(format!(
+                    "{}: {} is not one of {}",
+                    display_path(path),
+                    value,
+                    Value::Array(options.clone())
+                ));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                errors.push(format!(
+                    "{}: expected {}, got {}",
+                    display_path(path),
+                    expected,
+                    value
+                ));
+            }
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.check(sub, value, path, errors);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any.iter().any(|sub| self.passes(sub, value, path)) {
+                errors.push(format!(
+                    "{}: does not match any allowed alternative",
+                    display_path(path)
+                ));
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matched = one
+                .iter()
+                .filter(|sub| self.passes(sub, value, path))
+                .count();
+            if matched != 1 {
+                errors.push(format!(
+                    "{}: must match exactly one alternative, matched {}",
+                    display_path(path),
+                    matched
+                ));
+            }
+        }
+
+        match value {
+            Value::Object(object) => self.check_object(schema, object, path, errors),
+            Value::Array(items) => self.check_array(schema, items, path, errors),
+            Value::String(text) => {
+                let len = text.chars().count() as u64;
+                if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                    if len < min {
+                        errors.push(format!(
+                            "{}: shorter than {} characters",
+                            display_path(path),
+                            min
+                        ));
+                    }
+                }
+                if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                    if len > max {
+                        errors.push(format!(
+                            "{}: longer than {} characters",
+                            display_path(path),
+                            max
+                        ));
+                    }
+                }
+            }
+            Value::Number(number) => {
+                let n = number.as_f64().unwrap_or_default();
+                if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                    if n < min {
+                        errors.push(format!("{}: {} is below {}", display_path(path), n, min));
+                    }
+                }
+                if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                    if n > max {
+                        errors.push(format!("{}: {} is above {}", display_path(path), n, max));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn passes(&self, schema: &Value, value: &Value, path: &str) -> bool {
+        let mut scratch = Vec::new();
+        self.check(schema, value, path, &mut scratch);
+        scratch.is_empty()
+    }
+
+    fn check_object(
+        &self,
+        schema: &Map<String, Value>,
+        object: &Map<String, Value>,
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    errors.push(format!(
+                        "{}: missing required property \"{}\"",
+                        display_path(path),
+                        name
+                    ));
+                }
+            }
+        }
+        for (key, field) in object {
+            let field_path = format!("{}/{}", path, key);
+            match properties.and_then(|props| props.get(key)) {
+                Some(field_schema) => self.check(field_schema, field, &field_path, errors),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => errors.push(format!(
+                        "{}: unexpected property \"{}\"",
+                        display_path(path),
+                        key
+                    )),
+                    Some(extra @ Value::Object(_)) => self.check(extra, field, &field_path, errors),
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        errors: &mut Vec<String>,
+    ) {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                errors.push(format!("{}: fewer than {} items", display_path(path), min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max {
+                errors.push(format!("{}: more than {} items", display_path(path), max));
+            }
+        }
+        match schema.get("items") {
+            Some(Value::Array(tuple)) => {
+                for (index, (item, item_schema)) in items.iter().zip(tuple).enumerate() {
+                    self.check(item_schema, item, &format!("{}/{}", path, index), errors);
+                }
+            }
+            Some(item_schema) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.check(item_schema, item, &format!("{}/{}", path, index), errors);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().map(|n| n.fract() == 0.0).unwrap_or(false)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemars::{schema_for, JsonSchema};
+    use serde_json::json;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Step {
+        title: String,
+        #[schemars(range(min = 0, max = 10))]
+        weight: u8,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    enum Priority {
+        Low,
+        High,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    struct Plan {
+        goal: String,
+        steps: Vec<Step>,
+        priority: Priority,
+        notes: Option<String>,
+    }
+
+    #[test]
+    fn accepts_conforming_value() {
+        let schema = schema_for!(Plan);
+        let value = json!({
+            "goal": "ship",
+            "steps": [{ "title": "build", "weight": 3 }],
+            "priority": "High",
+            "notes": null,
+        });
+        assert_eq!(validate(&schema, &value), Ok(()));
+    }
+
+    #[test]
+    fn reports_each_violation_with_its_path() {
+        let schema = schema_for!(Plan);
+        let value = json!({
+            "steps": [{ "title": 7, "weight": 11 }],
+            "priority": "Urgent",
+        });
+        let errors = validate(&schema, &value).unwrap_err();
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("missing required property \"goal\"")));
+        assert!(errors
+            .iter()
+            .any(|e| e.starts_with("/steps/0/title: expected string")));
+        assert!(errors.iter().any(|e| e.starts_with("/steps/0/weight")));
+        assert!(errors.iter().any(|e| e.starts_with("/priority")));
+    }
+
+    #[test]
+    fn extracts_json_from_fenced_or_chatty_replies() {
+        assert_eq!(
+            extract_json("```json\n{\"a\": 1}\n```"),
+            Some(json!({ "a": 1 }))
+        );
+        assert_eq!(
+            extract_json("Here you go: [1, 2] hope that helps"),
+            Some(json!([1, 2]))
+        );
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn provider_payloads_carry_sanitized_name_and_schema() {
+        let schema = schema_for!(Plan);
+        let format = openai_response_format(&schema);
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["json_schema"]["name"], "Plan");
+        assert!(format["json_schema"]["schema"].get("$schema").is_none());
+        assert_eq!(
+            responses_text_format(&schema)["format"]["schema"]["required"],
+            json!(["goal", "priority", "steps"])
+        );
+    }
+}
@@ -0,0 +1,567 @@
+//! Token accounting keyed by model family.
+//!
+//! OpenAI models are counted with their real BPE vocabulary (`o200k_base`,
+//! or `cl100k_base` for GPT-4 and GPT-3.5) from `tiktoken-rs`, which bundles
+//! the vocabulary files. xAI publishes no tokenizer, so Grok is counted with
+//! `o200k_base` as the closest public BPE. Anthropic, Gemini and open-weight
+//! models have no tokenizer available offline and fall back to an estimator
+//! that mirrors their pre-tokenization: per-digit SentencePiece splits,
+//! whole short words, and per-script costs for CJK and other non-Latin text.
+//! Images are priced from their pixel dimensions using each provider's
+//! published formula.
+//!
+//! Provider-reported prompt usage feeds back into a per-family correction
+//! factor, so approximate counts (everything but OpenAI's own vocabularies)
+//! converge on what the provider actually bills.
+
+use crate::ai::provider_types::{ChatCompletionRequest, ChatMessage, ContentPart, MessageContent};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Mutex, OnceLock};
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+/// Role markers and separators the chat templates add around each message.
+const MESSAGE_OVERHEAD: usize = 4;
+/// Wrapper tokens around a tool call beyond its name and arguments.
+const TOOL_CALL_OVERHEAD: usize = 8;
+/// Dimensions assumed for images whose size cannot be read (remote URLs).
+const UNKNOWN_IMAGE_SIZE: (u32, u32) = (1024, 1024);
+
+/// Observations needed before calibration changes estimates.
+const CALIBRATION_MIN_SAMPLES: u64 = 3;
+/// Weight of each new observation in the running ratio.
+const CALIBRATION_ALPHA: f64 = 0.1;
+/// Prompts this small are dominated by fixed overhead and skew the ratio.
+const CALIBRATION_MIN_ESTIMATE: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerFamily {
+    /// OpenAI GPT and o-series, counted with the exact BPE vocabulary.
+    OpenAi,
+    /// xAI Grok, counted with `o200k_base` and calibrated.
+    Xai,
+    Anthropic,
+    Gemini,
+    /// SentencePiece-style vocabularies: Llama, Mistral, Qwen, DeepSeek.
+    Generic,
+}
+
+/// A BPE vocabulary bundled with `tiktoken-rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    O200kBase,
+    Cl100kBase,
+}
+
+impl Encoding {
+    fn bpe(self) -> &'static CoreBPE {
+        match self {
+            Self::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Self::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        }
+    }
+}
+
+/// Per-family costs, in tokens, for each pre-tokenized piece.
+struct Profile {
+    /// Latin words up to this many chars are usually a single token.
+    whole_word_chars: usize,
+    /// Chars per token once a word is split.
+    word_chars_per_token: f64,
+    /// Chars per token for non-ASCII alphabetic scripts (Cyrillic, Greek, ...).
+    non_ascii_chars_per_token: f64,
+    /// Tokens per CJK character.
+    cjk_tokens_per_char: f64,
+    /// Digits merged per token.
+    digits_per_token: usize,
+    /// Chars per token in punctuation and symbol runs.
+    punct_chars_per_token: f64,
+}
+
+impl TokenizerFamily {
+    pub fn for_model(model: &str) -> Self {
+        let slug = crate::ai::model_catalog::normalize_model_slug(model).to_ascii_lowercase();
+        let name = slug.rsplit('/').next().unwrap_or(&slug);
+        let is = |prefixes: &[&str]| prefixes.iter().any(|p| slug.starts_with(p));
+        if is(&["x-ai/", "xai/"]) || name.starts_with("grok") {
+            Self::Xai
+        } else if is(&["openai/"])
+            || ["gpt-", "chatgpt", "o1", "o3", "o4"]
+                .iter()
+                .any(|p| name.starts_with(p))
+        {
+            Self::OpenAi
+        } else if is(&["anthropic/"]) || name.starts_with("claude") {
+            Self::Anthropic
+        } else if is(&["google/"]) || name.starts_with("gemini") || name.starts_with("gemma") {
+            Self::Gemini
+        } else {
+            Self::Generic
+        }
+    }
+
+    /// The real vocabulary for `model`, when one is available offline.
+    fn encoding(&self, model: &str) -> Option<Encoding> {
+        match self {
+            Self::OpenAi => {
+                let slug =
+                    crate::ai::model_catalog::normalize_model_slug(model).to_ascii_lowercase();
+                let name = slug.rsplit('/').next().unwrap_or(&slug);
+                match get_tokenizer(name) {
+                    Some(Tokenizer::Cl100kBase) => Some(Encoding::Cl100kBase),
+                    // GPT-4o and everything newer use o200k.
+                    _ => Some(Encoding::O200kBase),
+                }
+            }
+            Self::Xai => Some(Encoding::O200kBase),
+            Self::Anthropic | Self::Gemini | Self::Generic => None,
+        }
+    }
+
+    /// Whether counts are exact up to message framing, so calibration would
+    /// only add noise.
+    fn is_exact(&self) -> bool {
+        matches!(self, Self::OpenAi)
+    }
+
+    fn profile(&self) -> Profile {
+        match self {
+            Self::OpenAi | Self::Xai => Profile {
+                whole_word_chars: 7,
+                word_chars_per_token: 4.2,
+                non_ascii_chars_per_token: 3.0,
+                cjk_tokens_per_char: 0.8,
+                digits_per_token: 3,
+                punct_chars_per_token: 2.0,
+            },
+            Self::Anthropic => Profile {
+                whole_word_chars: 6,
+                word_chars_per_token: 3.8,
+                non_ascii_chars_per_token: 2.2,
+                cjk_tokens_per_char: 1.2,
+                digits_per_token: 3,
+                punct_chars_per_token: 1.6,
+            },
+            Self::Gemini => Profile {
+                whole_word_chars: 7,
+                word_chars_per_token: 4.4,
+                non_ascii_chars_per_token: 3.2,
+                cjk_tokens_per_char: 0.7,
+                digits_per_token: 1,
+                punct_chars_per_token: 1.8,
+            },
+            Self::Generic => Profile {
+                whole_word_chars: 6,
+                word_chars_per_token: 3.6,
+                non_ascii_chars_per_token: 2.0,
+                cjk_tokens_per_char: 1.0,
+                digits_per_token: 1,
+                punct_chars_per_token: 1.5,
+            },
+        }
+    }
+
+    /// Tokens billed for an image of `width` x `height` pixels.
+    pub fn image_tokens(&self, width: u32, height: u32, low_detail: bool) -> usize {
+        let (w, h) = (width.max(1) as f64, height.max(1) as f64);
+        match self {
+            Self::OpenAi | Self::Xai | Self::Generic => {
+                if low_detail {
+                    return 85;
+                }
+                // Fit in 2048x2048, then shrink so the short side is at most 768
+                let fit = (2048.0 / w.max(h)).min(1.0);
+                let (w, h) = (w * fit, h * fit);
+                let shrink = (768.0 / w.min(h)).min(1.0);
+                let tiles = ((w * shrink) / 512.0).ceil() * ((h * shrink) / 512.0).ceil();
+                85 + 170 * tiles as usize
+            }
+            Self::Anthropic => {
+                // Long edge capped at 1568px and area at ~1.15 megapixels
+                let edge = (1568.0 / w.max(h)).min(1.0);
+                let area = (1_150_000.0 / (w * h)).sqrt().min(1.0);
+                let scale = edge.min(area);
+                ((w * scale) * (h * scale) / 750.0).ceil() as usize
+            }
+            Self::Gemini => {
+                if w <= 384.0 && h <= 384.0 {
+                    258
+                } else {
+                    258 * ((w / 768.0).ceil() * (h / 768.0).ceil()) as usize
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Latin,
+    NonAsciiLetter,
+    Cjk,
+    Digit,
+    Space,
+    Newline,
+    Punct,
+}
+
+fn classify(c: char) -> CharClass {
+    match c {
+        '\n' | '\r' => CharClass::Newline,
+        c if c.is_whitespace() => CharClass::Space,
+        c if c.is_ascii_digit() => CharClass::Digit,
+        c if c.is_ascii_alphabetic() || c == '\'' => CharClass::Latin,
+        c if is_cjk(c) => CharClass::Cjk,
+        c if c.is_alphabetic() => CharClass::NonAsciiLetter,
+        _ => CharClass::Punct,
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF      // Hiragana, Katakana
+        | 0x3400..=0x4DBF    // CJK Extension A
+        | 0x4E00..=0x9FFF    // CJK Unified Ideographs
+        | 0xAC00..=0xD7AF    // Hangul syllables
+        | 0xF900..=0xFAFF    // CJK compatibility ideographs
+        | 0x20000..=0x2FFFF) // CJK Extensions B+
+}
+
+/// Fractional token cost of `text` under `family`'s profile.
+fn text_cost(family: TokenizerFamily, text: &str) -> f64 {
+    let profile = family.profile();
+    let mut cost = 0.0;
+    let mut chars = text.chars().peekable();
+    while let Some(first) = chars.next() {
+        let class = classify(first);
+        let mut run = 1usize;
+        let mut bytes = first.len_utf8();
+        while let Some(&next) = chars.peek() {
+            if classify(next) != class {
+                break;
+            }
+            run += 1;
+            bytes += next.len_utf8();
+            chars.next();
+        }
+        cost += match class {
+            CharClass::Latin if run <= profile.whole_word_chars => 1.0,
+            CharClass::Latin => (run as f64 / profile.word_chars_per_token).ceil(),
+            CharClass::NonAsciiLetter => (run as f64 / profile.non_ascii_chars_per_token).ceil(),
+            CharClass::Cjk => run as f64 * profile.cjk_tokens_per_char,
+            CharClass::Digit => run.div_ceil(profile.digits_per_token) as f64,
+            // A single space merges into the following word
+            CharClass::Space if run == 1 => 0.0,
+            CharClass::Space => run.div_ceil(8) as f64,
+            CharClass::Newline => run.div_ceil(2) as f64,
+            // Emoji and other multi-byte symbols split into byte pieces
+            CharClass::Punct if bytes > run => (bytes as f64 / 2.0).ceil(),
+            CharClass::Punct => (run as f64 / profile.punct_chars_per_token).ceil(),
+        };
+    }
+    cost
+}
+
+/// Pixel dimensions of a base64 data URI image, read from its header.
+fn data_uri_dimensions(url: &str) -> Option<(u32, u32)> {
+    let (_, data) = url.strip_prefix("data:")?.split_once(";base64,")?;
+    let bytes = BASE64.decode(data.trim()).ok()?;
+    image::ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenizerCalibration {
+    pub family: TokenizerFamily,
+    pub samples: u64,
+    /// Running actual / estimated ratio applied to estimates.
+    pub ratio: f64,
+    pub estimated_total: u64,
+    pub actual_total: u64,
+    pub last_estimated: u32,
+    pub last_actual: u32,
+}
+
+fn calibrations() -> &'static Mutex<HashMap<TokenizerFamily, TokenizerCalibration>> {
+    static CALIBRATIONS: OnceLock<Mutex<HashMap<TokenizerFamily, TokenizerCalibration>>> =
+        OnceLock::new();
+    CALIBRATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Fold a provider-reported prompt size into the family's correction factor.
+/// `estimated` must come from an uncalibrated counter.
+pub fn observe_prompt_usage(model: &str, estimated: u32, actual: u32) {
+    if estimated < CALIBRATION_MIN_ESTIMATE || actual == 0 {
+        return;
+    }
+    let family = TokenizerFamily::for_model(model);
+    let sample = (actual as f64 / estimated as f64).clamp(0.25, 4.0);
+    let Ok(mut calibrations) = calibrations().lock() else {
+        return;
+    };
+    let entry = calibrations
+        .entry(family)
+        .or_insert_with(|| TokenizerCalibration {
+            family,
+            samples: 0,
+            ratio: 1.0,
+            estimated_total: 0,
+            actual_total: 0,
+            last_estimated: 0,
+            last_actual: 0,
+        });
+    entry.ratio = if entry.samples == 0 {
+        sample
+    } else {
+        entry.ratio + CALIBRATION_ALPHA * (sample - entry.ratio)
+    };
+    entry.samples += 1;
+    entry.estimated_total += estimated as u64;
+    entry.actual_total += actual as u64;
+    entry.last_estimated = estimated;
+    entry.last_actual = actual;
+    tracing::debug!(
+        "Token estimate for {:?}: estimated {} vs actual {} (ratio {:.3})",
+        family,
+        estimated,
+        actual,
+        entry.ratio
+    );
+}
+
+/// Estimated vs actual prompt usage per family since startup.
+pub fn calibration_report() -> Vec<TokenizerCalibration> {
+    let mut report = calibrations()
+        .lock()
+        .map(|calibrations| calibrations.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    report.sort_by_key(|calibration| format!("{:?}", calibration.family));
+    report
+}
+
+/// Counts tokens for one model. The calibration factor is captured at
+/// construction, so a counter gives stable answers while a prompt is packed.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenCounter {
+    family: TokenizerFamily,
+    /// Real vocabulary; `None` falls back to the family's estimator.
+    encoding: Option<Encoding>,
+    factor: f64,
+}
+
+impl Default for TokenCounter {
+    fn default() -> Self {
+        Self {
+            family: TokenizerFamily::Generic,
+            encoding: None,
+            factor: 1.0,
+        }
+    }
+}
+
+impl TokenCounter {
+    pub fn for_model(model: &str) -> Self {
+        let family = TokenizerFamily::for_model(model);
+        let factor = calibrations()
+            .lock()
+            .ok()
+            .filter(|_| !family.is_exact())
+            .and_then(|calibrations| {
+                calibrations
+                    .get(&family)
+                    .filter(|c| c.samples >= CALIBRATION_MIN_SAMPLES)
+                    .map(|c| c.ratio.clamp(0.5, 2.0))
+            })
+            .unwrap_or(1.0);
+        Self {
+            family,
+            encoding: family.encoding(model),
+            factor,
+        }
+    }
+
+    /// A counter that ignores calibration, for measuring the counter itself.
+    pub fn uncalibrated(model: &str) -> Self {
+        let family = TokenizerFamily::for_model(model);
+        Self {
+            family,
+            encoding: family.encoding(model),
+            factor: 1.0,
+        }
+    }
+
+    fn scale(&self, cost: f64) -> usize {
+        (cost * self.factor).ceil() as usize
+    }
+
+    pub fn count_text(&self, text: &str) -> usize {
+        let cost = match self.encoding {
+            Some(encoding) => encoding.bpe().encode_ordinary(text).len() as f64,
+            None => text_cost(self.family, text),
+        };
+        self.scale(cost)
+    }
+
+    /// Image tokens for a data URI or remote URL; `detail` follows the
+    /// OpenAI `low` / `high` / `auto` convention.
+    pub fn count_image(&self, url: &str, detail: Option<&str>) -> usize {
+        let (width, height) = data_uri_dimensions(url).unwrap_or(UNKNOWN_IMAGE_SIZE);
+        self.family
+            .image_tokens(width, height, detail == Some("low"))
+    }
+
+    pub fn count_tool_call(&self, name: &str, arguments: &str) -> usize {
+        TOOL_CALL_OVERHEAD + self.count_text(name) + self.count_text(arguments)
+    }
+
+    pub fn count_chat_message(&self, message: &ChatMessage) -> usize {
+        let content = match &message.content {
+            MessageContent::Text(text) => self.count_text(text),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .map(|part| match part {
+                    ContentPart::Text { text } => self.count_text(text),
+                    ContentPart::ImageUrl { image_url } => {
+                        self.count_image(&image_url.url, image_url.detail.as_deref())
+                    }
+                })
+                .sum(),
+        };
+        let tool_calls = message
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| self.count_tool_call(&call.function.name, &call.function.arguments))
+            .sum::<usize>();
+        MESSAGE_OVERHEAD + content + tool_calls
+    }
+
+    /// Prompt size of a request: messages plus tool schemas.
+    pub fn count_request(&self, request: &ChatCompletionRequest) -> usize {
+        let messages = request
+            .messages
+            .iter()
+            .map(|message| self.count_chat_message(message))
+            .sum::<usize>();
+        let tools = request
+            .tools
+            .iter()
+            .flatten()
+            .map(|tool| {
+                serde_json::to_string(tool)
+                    .map(|schema| self.count_text(&schema))
+                    .unwrap_or(0)
+            })
+            .sum::<usize>();
+        messages + tools
+    }
+
+    /// Longest prefix of `text` that fits in `budget` tokens, with `...`
+    /// appended when anything was cut.
+    pub fn truncate(&self, text: &str, budget: usize) -> String {
+        if budget == 0 {
+            return String::new();
+        }
+        let total = self.count_text(text);
+        if total <= budget {
+            return text.to_string();
+        }
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut keep = chars.len() * budget / total.max(1);
+        loop {
+            let candidate = format!("{}...", chars[..keep].iter().collect::<String>());
+            if keep == 0 || self.count_text(&candidate) <= budget {
+                return candidate;
+            }
+            keep = keep * 9 / 10;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn families_price_scripts_and_images_differently() {
+        assert_eq!(
+            TokenizerFamily::for_model("openai/gpt-4o"),
+            TokenizerFamily::OpenAi
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("rainy:x-ai/grok-4"),
+            TokenizerFamily::Xai
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("anthropic/claude-sonnet-4"),
+            TokenizerFamily::Anthropic
+        );
+        assert_eq!(
+            TokenizerFamily::for_model("gemini-3-flash-preview"),
+            TokenizerFamily::Gemini
+        );
+
+        let openai = TokenCounter::uncalibrated("gpt-4o");
+        assert_eq!(openai.count_text("Hello there, world"), 4);
+        assert_eq!(openai.count_text("1234567"), 3);
+        assert_eq!(TokenCounter::default().count_text("1234567"), 7);
+        // CJK costs far more per char than the chars/4 rule assumed
+        let cjk = "你好世界，今天天气很好";
+        assert!(openai.count_text(cjk) > cjk.chars().count() / 4 * 2);
+
+        assert_eq!(TokenizerFamily::OpenAi.image_tokens(1024, 1024, false), 765);
+        assert_eq!(TokenizerFamily::OpenAi.image_tokens(4096, 4096, true), 85);
+        assert_eq!(TokenizerFamily::Gemini.image_tokens(300, 300, false), 258);
+        assert_eq!(
+            TokenizerFamily::Anthropic.image_tokens(1000, 1000, false),
+            1334
+        );
+
+        let truncated = openai.truncate(&"word ".repeat(100), 20);
+        assert!(truncated.ends_with("..."));
+        assert!(openai.count_text(&truncated) <= 20);
+    }
+
+    #[test]
+    fn openai_models_use_their_bpe_vocabulary() {
+        // Reference counts from OpenAI's tiktoken.
+        assert_eq!(
+            TokenCounter::uncalibrated("gpt-4").count_text("tiktoken is great!"),
+            6
+        );
+        assert_eq!(
+            TokenCounter::uncalibrated("openai/gpt-4o").count_text("tiktoken is great!"),
+            6
+        );
+        assert_eq!(
+            TokenizerFamily::OpenAi.encoding("gpt-3.5-turbo"),
+            Some(Encoding::Cl100kBase)
+        );
+        assert_eq!(
+            TokenizerFamily::OpenAi.encoding("gpt-5"),
+            Some(Encoding::O200kBase)
+        );
+        assert_eq!(
+            TokenizerFamily::Xai.encoding("grok-4"),
+            Some(Encoding::O200kBase)
+        );
+        assert_eq!(TokenizerFamily::Anthropic.encoding("claude-sonnet-4"), None);
+
+        // Exact vocabularies are never rescaled by calibration.
+        for _ in 0..CALIBRATION_MIN_SAMPLES {
+            observe_prompt_usage("gpt-4o", 1_000, 1_500);
+        }
+        assert_eq!(
+            TokenCounter::for_model("gpt-4o").count_text("tiktoken is great!"),
+            6
+        );
+    }
+}
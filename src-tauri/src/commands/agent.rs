@@ -24,6 +24,9 @@ use crate::services::agent_run_control::{AgentRunControl, CancelRunResult};
 use crate::services::chat_artifacts::{
     artifact_from_tool_result, push_unique_artifact, ChatArtifact,
 };
+use crate::services::run_checkpoints::{
+    RunCheckpoint, RunCheckpointStore, RunCheckpointSummary, RunRecorder, RunStatus,
+};
 use crate::services::settings::SettingsManager;
 use crate::services::{KeychainAccessService, PromptSkillDiscoveryService, SkillExecutor};
 use chrono::Utc;
@@ -466,6 +469,9 @@ pub struct EnsureChatTitleResponse {
 pub enum WorkflowInvocationSource {
     Local,
     NativeModal,
+    /// Continuing an interrupted run from its checkpoint. Sessions behave as
+    /// for `Local`, but the prompt is already in the chat history.
+    Resumed,
 }
 
 fn is_placeholder_chat_title(value: &str) -> bool {
@@ -647,7 +653,11 @@ fn build_runtime_history(rows: Vec<(String, String, String)>) -> Vec<AgentMessag
         .collect()
 }
 
-fn estimate_history_tokens(rows: &[(String, String, String)], prompt: &str) -> usize {
+fn estimate_history_tokens(
+    rows: &[(String, String, String)],
+    prompt: &str,
+    model_id: &str,
+) -> usize {
     let mut messages = build_runtime_history(rows.to_vec());
     messages.push(AgentMessage {
         role: "user".to_string(),
@@ -655,7 +665,7 @@ fn estimate_history_tokens(rows: &[(String, String, String)], prompt: &str) -> u
         tool_calls: None,
         tool_call_id: None,
     });
-    ContextWindow::estimate_total_tokens(&messages)
+    ContextWindow::for_model(0, model_id).estimate_total_tokens(&messages)
 }
 
 fn build_compaction_transcript(
@@ -778,7 +788,7 @@ async fn maybe_compact_chat_history(
         .await
         .map_err(|e| format!("Failed to load chat history for compaction: {}", e))?;
 
-    let estimated_tokens = estimate_history_tokens(&history_rows, prompt);
+    let estimated_tokens = estimate_history_tokens(&history_rows, prompt, model_id);
     if estimated_tokens < AUTO_COMPACTION_TRIGGER_TOKENS {
         return Ok(None);
     }
@@ -1083,7 +1093,7 @@ pub async fn run_agent_workflow_internal(
         .unwrap_or_else(|| crate::ai::agent::manager::DEFAULT_LONG_CHAT_SCOPE_ID.to_string());
 
     match invocation_source {
-        WorkflowInvocationSource::Local => {
+        WorkflowInvocationSource::Local | WorkflowInvocationSource::Resumed => {
             let _ = agent_manager
                 .ensure_chat_session_with_workspace(&chat_id, "Rainy Agent", &workspace_path)
                 .await
@@ -1255,7 +1265,7 @@ pub async fn run_agent_workflow_internal(
         };
 
         match invocation_source {
-            WorkflowInvocationSource::Local => {
+            WorkflowInvocationSource::Local | WorkflowInvocationSource::Resumed => {
                 let _ = agent_manager
                     .save_message(&chat_id, "user", &prompt)
                     .await
@@ -1324,6 +1334,9 @@ pub async fn run_agent_workflow_internal(
         workspace_memory_context: None,
         workspace_memory_root: None,
         workspace_memory_enabled: false,
+        run_id: Some(run_id.clone()),
+        chat_id: Some(chat_id.clone()),
+        checkout_root: None,
     };
 
     let workspace_memory_bootstrap = crate::services::WorkspaceMemoryFiles::bootstrap(
@@ -1361,6 +1374,36 @@ pub async fn run_agent_workflow_internal(
         Arc::new(guard.clone())
     };
 
+    // A resumed run keeps the spec and options it started with; only the
+    // prompt skills and policy above are re-resolved, and then discarded.
+    let run_checkpoints = app_handle
+        .state::<Arc<RunCheckpointStore>>()
+        .inner()
+        .clone();
+    let checkpoint = if invocation_source == WorkflowInvocationSource::Resumed {
+        let checkpoint = run_checkpoints
+            .load(&run_id)
+            .await?
+            .ok_or_else(|| format!("No checkpoint found for run {}", run_id))?;
+        // Whoever was asked for these approvals is gone with the old process.
+        if let Some(airlock) = airlock_service.as_ref() {
+            let command_ids: Vec<String> = checkpoint
+                .pending_approvals
+                .iter()
+                .map(|request| request.command_id.clone())
+                .collect();
+            airlock.expire_interrupted(&command_ids).await;
+        }
+        run_checkpoints.set_pending_approvals(&run_id, &[]).await?;
+        checkpoint
+    } else {
+        RunCheckpoint::start(&prompt, spec, options.clone())?
+    };
+    let spec = checkpoint.spec.clone();
+    let options = checkpoint.options.clone();
+    let progress = checkpoint.progress.clone();
+    let recorder = RunRecorder::new(run_checkpoints, checkpoint);
+
     let run_kill_switch = AgentKillSwitch::new();
     run_control
         .register_run(run_id.clone(), run_kill_switch.clone())
@@ -1373,15 +1416,20 @@ pub async fn run_agent_workflow_internal(
         skills.clone(),
         memory,
         airlock_service,
-        Some(run_kill_switch),
+        Some(run_kill_switch.clone()),
         Some(runtime_registry.clone()),
-    );
+    )
+    .with_recorder(recorder.clone());
 
     // Load persisted conversation history into runtime so local Native Runtime
     // preserves context across turns.
-    let compaction_state =
+    // A resumed run already carries its own context; leave the chat as it is.
+    let compaction_state = if invocation_source == WorkflowInvocationSource::Resumed {
+        None
+    } else {
         maybe_compact_chat_history(&agent_manager, &router_state, &chat_id, &model_id, &prompt)
-            .await?;
+            .await?
+    };
 
     if let Some(compaction) = compaction_state {
         let _ = app_handle.emit(
@@ -1481,7 +1529,7 @@ pub async fn run_agent_workflow_internal(
     let actual_touched_paths_for_events = actual_touched_paths.clone();
     let blocked_by_airlock_for_events = blocked_by_airlock.clone();
     let response_result = runtime
-        .run(&prompt, move |event| {
+        .run_from(&prompt, progress, move |event| {
             let projected_events = {
                 let mut projector = frontend_event_projector_for_events
                     .lock()
@@ -1655,6 +1703,15 @@ pub async fn run_agent_workflow_internal(
                                 .await;
                         });
                     }
+                    AgentEvent::ProviderServed(served) => {
+                        let manager = agent_manager_clone.clone();
+                        let chat_id = chat_id_for_events.clone();
+                        tauri::async_runtime::spawn(async move {
+                            let _ = manager
+                                .record_chat_served_provider(&chat_id, &served.provider_id)
+                                .await;
+                        });
+                    }
                     _ => {}
                 }
             }
@@ -1679,11 +1736,18 @@ pub async fn run_agent_workflow_internal(
     }
 
     run_control.unregister_run(&run_id).await;
+    recorder
+        .finish(match &response_result {
+            Ok(_) => RunStatus::Completed,
+            Err(_) if run_kill_switch.is_triggered() => RunStatus::Cancelled,
+            Err(_) => RunStatus::Failed,
+        })
+        .await;
     let response = match response_result {
         Ok(response) => response,
         Err(error) => {
             match invocation_source {
-                WorkflowInvocationSource::Local => {
+                WorkflowInvocationSource::Local | WorkflowInvocationSource::Resumed => {
                     session_coordinator.abort_local_session(&chat_id)
                 }
                 WorkflowInvocationSource::NativeModal => {
@@ -1702,7 +1766,7 @@ pub async fn run_agent_workflow_internal(
     .await;
 
     match invocation_source {
-        WorkflowInvocationSource::Local => {
+        WorkflowInvocationSource::Local | WorkflowInvocationSource::Resumed => {
             session_coordinator.finish_local_session(&chat_id);
             let artifacts = collected_artifacts
                 .lock()
@@ -1772,6 +1836,70 @@ pub async fn cancel_agent_run(
     Ok(CancelAgentRunResponse { run_id, status })
 }
 
+/// Continue a run that was interrupted by an app restart (or failed) from its
+/// last checkpointed step.
+#[tauri::command]
+pub async fn resume_agent_run(
+    app_handle: tauri::AppHandle,
+    run_id: String,
+    run_checkpoints: State<'_, Arc<RunCheckpointStore>>,
+    run_control: State<'_, Arc<AgentRunControl>>,
+) -> Result<RunAgentWorkflowResponse, String> {
+    if run_control.kill_switch(&run_id).await.is_some() {
+        return Err(format!("Run {} is still in progress", run_id));
+    }
+    let checkpoint = run_checkpoints
+        .load(&run_id)
+        .await?
+        .ok_or_else(|| format!("No checkpoint found for run {}", run_id))?;
+    let model_id = checkpoint
+        .options
+        .model
+        .clone()
+        .or_else(|| checkpoint.spec.model.clone())
+        .ok_or_else(|| format!("Run {} has no model to resume with", run_id))?;
+
+    run_agent_workflow_internal(
+        app_handle,
+        checkpoint.input,
+        model_id,
+        checkpoint.options.workspace_id,
+        Some(checkpoint.spec.id),
+        checkpoint.chat_id,
+        Some(run_id),
+        checkpoint.options.reasoning_effort,
+        None,
+        WorkflowInvocationSource::Resumed,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_resumable_agent_runs(
+    run_checkpoints: State<'_, Arc<RunCheckpointStore>>,
+) -> Result<Vec<RunCheckpointSummary>, String> {
+    run_checkpoints.list_resumable().await
+}
+
+/// Drop an interrupted run's checkpoint instead of resuming it.
+#[tauri::command]
+pub async fn dismiss_agent_run(
+    run_id: String,
+    run_checkpoints: State<'_, Arc<RunCheckpointStore>>,
+    airlock_state: State<'_, AirlockServiceState>,
+) -> Result<(), String> {
+    let airlock = airlock_state.0.lock().await.clone();
+    if let (Some(checkpoint), Some(airlock)) = (run_checkpoints.load(&run_id).await?, airlock) {
+        let command_ids: Vec<String> = checkpoint
+            .pending_approvals
+            .iter()
+            .map(|request| request.command_id.clone())
+            .collect();
+        airlock.expire_interrupted(&command_ids).await;
+    }
+    run_checkpoints.delete(&run_id).await
+}
+
 #[tauri::command]
 pub async fn get_chat_session(
     agent_manager: State<'_, crate::ai::agent::manager::AgentManager>,
@@ -2044,6 +2172,7 @@ mod tests {
             tool_access_policy_source: "workspace_permissions+spec".to_string(),
             notifications_enabled: true,
             can_create_agents: true,
+            shell_sandbox: Default::default(),
         }
     }
 
@@ -3,23 +3,31 @@
 //! Tauri commands for the Airlock security system.
 //! Allows the frontend to respond to approval requests.
 
-use crate::services::AirlockService;
+use crate::services::airlock_policy::PolicyExplanation;
+use crate::services::audit_ledger::{
+    AuditLedgerEntry, AuditLedgerQuery, ChainVerification, LedgerExportFormat,
+};
+use crate::services::{AirlockGrant, AirlockService, ApprovalScope, AuditLedger, WorkspaceManager};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::{command, State};
 use tokio::sync::Mutex;
 
 pub struct AirlockServiceState(pub Arc<Mutex<Option<AirlockService>>>);
 
-/// Respond to an airlock approval request
+/// Respond to an airlock approval request, optionally granting a wider scope
 #[command]
 pub async fn respond_to_airlock(
     state: State<'_, AirlockServiceState>,
     command_id: String,
     approved: bool,
+    scope: Option<ApprovalScope>,
 ) -> Result<(), String> {
     let guard = state.0.lock().await;
     if let Some(airlock) = guard.as_ref() {
-        airlock.respond_to_approval(&command_id, approved).await
+        airlock
+            .respond_to_approval(&command_id, approved, scope.unwrap_or_default())
+            .await
     } else {
         Err("Airlock service not initialized".to_string())
     }
@@ -81,6 +89,72 @@ pub async fn send_airlock_message(
     }
 }
 
+/// List standing approval grants (active only unless `include_inactive`)
+#[command]
+pub async fn list_airlock_grants(
+    state: State<'_, AirlockServiceState>,
+    include_inactive: Option<bool>,
+) -> Result<Vec<AirlockGrant>, String> {
+    let guard = state.0.lock().await;
+    if let Some(airlock) = guard.as_ref() {
+        airlock.list_grants(include_inactive.unwrap_or(false)).await
+    } else {
+        Err("Airlock service not initialized".to_string())
+    }
+}
+
+/// Revoke a standing approval grant
+#[command]
+pub async fn revoke_airlock_grant(
+    state: State<'_, AirlockServiceState>,
+    grant_id: String,
+) -> Result<(), String> {
+    let guard = state.0.lock().await;
+    if let Some(airlock) = guard.as_ref() {
+        airlock.revoke_grant(&grant_id).await
+    } else {
+        Err("Airlock service not initialized".to_string())
+    }
+}
+
+/// Query the audit ledger by run, workspace and time range
+#[command]
+pub async fn query_audit_ledger(
+    ledger: State<'_, Arc<AuditLedger>>,
+    query: Option<AuditLedgerQuery>,
+) -> Result<Vec<AuditLedgerEntry>, String> {
+    ledger
+        .query(&query.unwrap_or_default())
+        .await
+        .map_err(|e| format!("Failed to query audit ledger: {}", e))
+}
+
+/// Re-hash the audit ledger and report the first broken link, if any
+#[command]
+pub async fn verify_audit_ledger_chain(
+    ledger: State<'_, Arc<AuditLedger>>,
+) -> Result<ChainVerification, String> {
+    ledger
+        .verify_chain()
+        .await
+        .map_err(|e| format!("Failed to verify audit ledger: {}", e))
+}
+
+/// Export matching audit ledger entries as JSON or NDJSON text
+#[command]
+pub async fn export_audit_ledger(
+    ledger: State<'_, Arc<AuditLedger>>,
+    query: Option<AuditLedgerQuery>,
+    format: Option<LedgerExportFormat>,
+) -> Result<String, String> {
+    ledger
+        .export(
+            &query.unwrap_or_default(),
+            format.unwrap_or(LedgerExportFormat::Json),
+        )
+        .await
+}
+
 /// Set headless mode (auto-approve sensitive commands)
 #[command]
 pub async fn set_headless_mode(
@@ -95,3 +169,29 @@ pub async fn set_headless_mode(
         Err("Airlock service not initialized".to_string())
     }
 }
+
+/// Explain which Airlock policy rule decides a tool call
+#[command]
+pub async fn explain_airlock_policy(
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    tool: String,
+    args: Option<serde_json::Value>,
+    workspace_id: Option<String>,
+    agent_spec_id: Option<String>,
+) -> Result<PolicyExplanation, String> {
+    let workspace_root =
+        workspace_id
+            .as_deref()
+            .and_then(|id| match workspace_manager.load_workspace(id) {
+                Ok(workspace) => workspace.allowed_paths.first().map(PathBuf::from),
+                Err(_) if std::path::Path::new(id).is_absolute() => Some(PathBuf::from(id)),
+                Err(_) => None,
+            });
+    Ok(AirlockService::explain_policy(
+        &tool,
+        args.as_ref(),
+        workspace_id.as_deref(),
+        agent_spec_id.as_deref(),
+        workspace_root.as_deref(),
+    ))
+}
@@ -226,6 +226,39 @@ pub async fn get_file_transaction(
     Ok(state.get_transaction(&transaction_id))
 }
 
+// ============ Agent Run Checkpoint Commands ============
+
+/// List the file changes an agent run made, oldest first
+#[tauri::command]
+pub async fn list_run_changes(
+    run_id: String,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<Vec<FileOpChange>, String> {
+    Ok(state.list_run_changes(&run_id).await)
+}
+
+/// Undo every file change an agent run made
+#[tauri::command]
+pub async fn rollback_run(
+    run_id: String,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<Vec<FileOpChange>, String> {
+    state.rollback_run(&run_id).await.map_err(|e| e.to_string())
+}
+
+/// Restore one file to its state before an agent run touched it
+#[tauri::command]
+pub async fn rollback_run_file(
+    run_id: String,
+    file_path: String,
+    state: State<'_, Arc<FileOperationEngine>>,
+) -> Result<Vec<FileOpChange>, String> {
+    state
+        .rollback_run_file(&run_id, &file_path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ============ Enhanced Undo/Redo Commands ============
 
 /// Enhanced undo operation
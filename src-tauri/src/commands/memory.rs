@@ -5,6 +5,9 @@
 
 use crate::services::memory::MemoryEntry;
 use crate::services::memory::MemoryManager;
+use crate::services::memory_vault::archive::{
+    ArchiveDeviceKeys, ArchiveFilters, ArchiveKey, ConflictPolicy, ImportReport, MemoryArchive,
+};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tauri::State;
@@ -602,6 +605,99 @@ pub async fn clear_workspace_vault(
     Ok(DeleteBatchResult { deleted: 0 })
 }
 
+// ─── Memory Review Queue Commands ─────────────────────────────────────
+
+#[tauri::command]
+pub async fn list_pending_memories(
+    manager: State<'_, MemoryManagerState>,
+    workspace_id: Option<String>,
+) -> Result<Vec<crate::services::memory_vault::types::DecryptedMemoryEntry>, String> {
+    let vault = manager.0.vault().await.map_err(|e| e.to_string())?;
+    vault.list_pending(workspace_id.as_deref()).await
+}
+
+#[tauri::command]
+pub async fn review_pending_memory(
+    manager: State<'_, MemoryManagerState>,
+    id: String,
+    decision: crate::services::memory_vault::governance::ReviewDecision,
+) -> Result<(), String> {
+    manager
+        .0
+        .review_pending_memory(&id, decision)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ─── Memory Archive Commands ──────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryExportSummary {
+    pub path: String,
+    pub entry_count: usize,
+    pub signer_public_key: String,
+}
+
+#[tauri::command]
+pub async fn export_workspace_memory(
+    manager: State<'_, MemoryManagerState>,
+    workspace_id: String,
+    path: String,
+    key: ArchiveKey,
+    filters: Option<ArchiveFilters>,
+) -> Result<MemoryExportSummary, String> {
+    let archive = manager
+        .0
+        .export_workspace_memory(&workspace_id, filters.unwrap_or_default(), key)
+        .await
+        .map_err(|e| e.to_string())?;
+    let bytes = serde_json::to_vec_pretty(&archive)
+        .map_err(|e| format!("Failed to encode memory archive: {}", e))?;
+    tokio::fs::write(&path, bytes)
+        .await
+        .map_err(|e| format!("Failed to write memory archive: {}", e))?;
+    Ok(MemoryExportSummary {
+        path,
+        entry_count: archive.header.entry_count,
+        signer_public_key: archive.signer_public_key,
+    })
+}
+
+#[tauri::command]
+pub async fn import_workspace_memory(
+    manager: State<'_, MemoryManagerState>,
+    path: String,
+    passphrase: Option<String>,
+    expected_signer: Option<String>,
+    target_workspace_id: Option<String>,
+    conflict_policy: Option<ConflictPolicy>,
+) -> Result<ImportReport, String> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read memory archive: {}", e))?;
+    let archive: MemoryArchive =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Invalid memory archive: {}", e))?;
+    manager
+        .0
+        .import_workspace_memory(
+            archive,
+            passphrase,
+            expected_signer,
+            target_workspace_id,
+            conflict_policy.unwrap_or_default(),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_memory_archive_keys(
+    manager: State<'_, MemoryManagerState>,
+) -> Result<ArchiveDeviceKeys, String> {
+    let vault = manager.0.vault().await.map_err(|e| e.to_string())?;
+    Ok(vault.archive_identity().await?.device_keys())
+}
+
 fn extract_tag_value(tags: &[String], key: &str) -> Option<String> {
     let prefix = format!("{}:", key);
     tags.iter()
@@ -4,10 +4,15 @@
 use crate::ai::router::fallback_chain::FallbackStrategy;
 use crate::ai::router::load_balancer::LoadBalancingStrategy;
 use crate::ai::router::router::{RouterConfig, RouterStats};
+use crate::ai::tokenizer::{self, TokenizerCalibration};
 use crate::ai::{
     ChatCompletionRequest, ChatCompletionResponse, ChatMessage, EmbeddingRequest,
     EmbeddingResponse, IntelligentRouter, ProviderId, StreamingChunk,
 };
+use crate::services::usage_ledger::{
+    UsageBudget, UsageBudgetInput, UsageLedgerEntry, UsageLedgerQuery, UsageSummary,
+};
+use crate::services::UsageLedger;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tauri::{ipc::Channel, State};
@@ -24,6 +29,7 @@ pub struct RouterConfigDto {
     pub cost_optimization_enabled: bool,
     pub capability_matching_enabled: bool,
     pub max_retries: usize,
+    pub hedging_enabled: bool,
 }
 
 impl From<&RouterConfig> for RouterConfigDto {
@@ -34,6 +40,7 @@ impl From<&RouterConfig> for RouterConfigDto {
             cost_optimization_enabled: config.enable_cost_optimization,
             capability_matching_enabled: config.enable_capability_matching,
             max_retries: config.max_retries,
+            hedging_enabled: config.hedging.enabled,
         }
     }
 }
@@ -135,6 +142,7 @@ pub async fn update_router_config(
     cost_optimization_enabled: Option<bool>,
     capability_matching_enabled: Option<bool>,
     max_retries: Option<usize>,
+    hedging_enabled: Option<bool>,
     router: State<'_, IntelligentRouterState>,
 ) -> Result<RouterConfigDto, String> {
     let mut router = router.0.write().await;
@@ -148,6 +156,7 @@ pub async fn update_router_config(
                 LoadBalancingStrategy::WeightedRoundRobin
             }
             "random" => LoadBalancingStrategy::Random,
+            "latencyewma" | "latency_ewma" | "lowest_latency" => LoadBalancingStrategy::LatencyEwma,
             _ => config.load_balancing_strategy,
         };
     }
@@ -173,6 +182,10 @@ pub async fn update_router_config(
         config.max_retries = retries;
     }
 
+    if let Some(enabled) = hedging_enabled {
+        config.hedging.enabled = enabled;
+    }
+
     router.set_config(config.clone());
     Ok(RouterConfigDto::from(&config))
 }
@@ -379,6 +392,56 @@ pub async fn router_has_providers(
     Ok(!router.get_all_providers().is_empty())
 }
 
+/// List usage ledger rows by workspace, agent, run and time range
+#[tauri::command]
+pub async fn query_usage_ledger(
+    ledger: State<'_, Arc<UsageLedger>>,
+    query: Option<UsageLedgerQuery>,
+) -> Result<Vec<UsageLedgerEntry>, String> {
+    ledger.query(&query.unwrap_or_default()).await
+}
+
+/// Total tokens and cost for matching ledger rows, per provider and model
+#[tauri::command]
+pub async fn summarize_usage(
+    ledger: State<'_, Arc<UsageLedger>>,
+    query: Option<UsageLedgerQuery>,
+) -> Result<UsageSummary, String> {
+    ledger.summarize(&query.unwrap_or_default()).await
+}
+
+/// List configured spend budgets
+#[tauri::command]
+pub async fn list_usage_budgets(
+    ledger: State<'_, Arc<UsageLedger>>,
+) -> Result<Vec<UsageBudget>, String> {
+    ledger.list_budgets().await
+}
+
+/// Create or replace a spend budget
+#[tauri::command]
+pub async fn upsert_usage_budget(
+    ledger: State<'_, Arc<UsageLedger>>,
+    budget: UsageBudgetInput,
+) -> Result<UsageBudget, String> {
+    ledger.upsert_budget(budget).await
+}
+
+/// Delete a spend budget; returns false when it did not exist
+#[tauri::command]
+pub async fn delete_usage_budget(
+    ledger: State<'_, Arc<UsageLedger>>,
+    id: String,
+) -> Result<bool, String> {
+    ledger.delete_budget(&id).await
+}
+
+/// Estimated vs provider-reported prompt tokens per tokenizer family
+#[tauri::command]
+pub async fn get_token_calibration() -> Result<Vec<TokenizerCalibration>, String> {
+    Ok(tokenizer::calibration_report())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,9 +3,15 @@
 
 use crate::ai::provider::AIProviderManager;
 use crate::commands::airlock::AirlockServiceState;
+use crate::commands::memory::MemoryManagerState;
+use crate::services::embedder::EmbedderConfig;
+use crate::services::memory_vault::key_provider::{
+    configured_provider, provider_for, set_session_passphrase, VaultKeyProviderKind,
+};
 use crate::services::settings::{ModelOption, SettingsManager, UserProfile, UserSettings};
 use crate::services::{
-    KeychainAccessService, MacOSAutoLaunchBridge, MacOSNativeNotificationBridge, WorkspaceManager,
+    BeamRpcService, KeychainAccessService, MacOSAutoLaunchBridge, MacOSNativeNotificationBridge,
+    WorkspaceManager,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -368,14 +374,22 @@ pub async fn get_embedder_provider(
     Ok(settings.get_embedder_provider().to_string())
 }
 
-/// Set embedder provider
+/// Set embedder provider, resetting model and server to its defaults, and
+/// re-embed the vault for it
 #[tauri::command]
 pub async fn set_embedder_provider(
     provider: String,
     settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
 ) -> Result<(), String> {
-    let mut settings = settings.lock().await;
-    settings.set_embedder_provider(provider)
+    let config = EmbedderConfig::new(&provider, "", None)?;
+    {
+        let mut settings = settings.lock().await;
+        settings.set_embedder_provider(config.provider)?;
+        settings.set_embedder_model(config.model)?;
+        settings.set_embedder_base_url(None)?;
+    }
+    memory.0.reload_embedder().await
 }
 
 /// Get embedder model
@@ -387,14 +401,166 @@ pub async fn get_embedder_model(
     Ok(settings.get_embedder_model().to_string())
 }
 
-/// Set embedder model
+/// Set embedder model and re-embed the vault for it
 #[tauri::command]
 pub async fn set_embedder_model(
     model: String,
     settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
+) -> Result<(), String> {
+    {
+        let mut settings = settings.lock().await;
+        settings.set_embedder_model(model)?;
+    }
+    memory.0.reload_embedder().await
+}
+
+/// Get the server URL of the OpenAI-compatible or Ollama embedder
+#[tauri::command]
+pub async fn get_embedder_base_url(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<Option<String>, String> {
+    let settings = settings.lock().await;
+    Ok(settings.get_embedder_base_url())
+}
+
+/// Set the embedder server URL; `None` restores the provider default
+#[tauri::command]
+pub async fn set_embedder_base_url(
+    base_url: Option<String>,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
 ) -> Result<(), String> {
+    if let Some(url) = base_url
+        .as_deref()
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+    {
+        url::Url::parse(url).map_err(|e| format!("Invalid embedder URL '{}': {}", url, e))?;
+    }
+    {
+        let mut settings = settings.lock().await;
+        settings.set_embedder_base_url(base_url)?;
+    }
+    memory.0.reload_embedder().await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultKeyProviderStatus {
+    pub provider: VaultKeyProviderKind,
+    pub key_file: Option<String>,
+    pub supported: Vec<VaultKeyProviderKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MasterKeyRotationReport {
+    pub vault_entries: usize,
+    pub beam_wallets: usize,
+}
+
+/// Get the vault key provider and the providers available on this platform
+#[tauri::command]
+pub async fn get_vault_key_provider(
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+) -> Result<VaultKeyProviderStatus, String> {
+    let settings = settings.lock().await;
+    Ok(VaultKeyProviderStatus {
+        provider: settings.get_vault_key_provider(),
+        key_file: settings.get_vault_key_file(),
+        supported: [
+            VaultKeyProviderKind::Keychain,
+            VaultKeyProviderKind::Passphrase,
+            VaultKeyProviderKind::File,
+            VaultKeyProviderKind::Env,
+        ]
+        .into_iter()
+        .filter(|kind| kind.is_supported())
+        .collect(),
+    })
+}
+
+/// Move the vault master key to another provider and persist the selection.
+/// `passphrase` is required when switching to the passphrase provider.
+#[tauri::command]
+pub async fn set_vault_key_provider(
+    provider: VaultKeyProviderKind,
+    key_file: Option<String>,
+    passphrase: Option<String>,
+    settings: State<'_, Arc<Mutex<SettingsManager>>>,
+    memory: State<'_, MemoryManagerState>,
+) -> Result<(), String> {
+    if !provider.is_supported() {
+        return Err(format!(
+            "Vault key provider {:?} is not supported on this platform",
+            provider
+        ));
+    }
+    if provider == VaultKeyProviderKind::Passphrase {
+        match passphrase.filter(|p| !p.is_empty()) {
+            Some(passphrase) => set_session_passphrase(Some(passphrase)),
+            None => return Err("A passphrase is required for this provider".to_string()),
+        }
+    }
+
     let mut settings = settings.lock().await;
-    settings.set_embedder_model(model)
+    let target = provider_for(
+        provider,
+        &settings.data_dir(),
+        key_file
+            .as_deref()
+            .filter(|path| !path.trim().is_empty())
+            .map(std::path::PathBuf::from),
+    );
+    memory
+        .0
+        .switch_key_provider(target)
+        .await
+        .map_err(|e| e.to_string())?;
+    settings.set_vault_key_provider(provider, key_file)
+}
+
+/// Unlock a passphrase-protected vault for this session
+#[tauri::command]
+pub async fn unlock_vault(passphrase: String) -> Result<(), String> {
+    set_session_passphrase(Some(passphrase));
+    let check = tokio::task::spawn_blocking(|| configured_provider().get_or_create_master_key())
+        .await
+        .map_err(|e| format!("Vault unlock task failed: {}", e))?;
+    if let Err(e) = check {
+        set_session_passphrase(None);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Lock the vault: forget the session passphrase and the unwrapped master key
+#[tauri::command]
+pub async fn lock_vault() -> Result<(), String> {
+    set_session_passphrase(None);
+    Ok(())
+}
+
+/// Re-encrypt the memory vault, stored credentials and Beam wallets under new master keys
+#[tauri::command]
+pub async fn rotate_master_key(
+    memory: State<'_, MemoryManagerState>,
+    beam_rpc: State<'_, Arc<BeamRpcService>>,
+) -> Result<MasterKeyRotationReport, String> {
+    let vault_entries = memory
+        .0
+        .rotate_master_key()
+        .await
+        .map_err(|e| e.to_string())?;
+    let beam = beam_rpc.inner().clone();
+    let beam_wallets = tokio::task::spawn_blocking(move || beam.rotate_master_key())
+        .await
+        .map_err(|e| format!("Wallet key rotation task failed: {}", e))??;
+    Ok(MasterKeyRotationReport {
+        vault_entries,
+        beam_wallets,
+    })
 }
 
 /// Get available models based on user's plan
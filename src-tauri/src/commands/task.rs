@@ -64,6 +64,8 @@ pub async fn create_task(
                     language: "en".to_string(),
                     auto_save: true,
                     notifications_enabled: true,
+                    command_policy: Default::default(),
+                    sandbox: Default::default(),
                 },
                 launchpad: crate::services::mate_launchpad::WorkspaceLaunchSettings::default(),
             };
@@ -117,6 +119,8 @@ pub async fn set_task_manager_workspace(
             language: "en".to_string(),
             auto_save: true,
             notifications_enabled: true,
+            command_policy: Default::default(),
+            sandbox: Default::default(),
         },
         launchpad: crate::services::mate_launchpad::WorkspaceLaunchSettings::default(),
     };
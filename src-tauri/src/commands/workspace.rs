@@ -1,11 +1,13 @@
 // Rainy MaTE - Workspace Commands
 // Tauri commands for advanced workspace management
 
+use crate::services::code_index::CodeIndexStats;
 use crate::services::{
-    EffectiveLocalAgentPolicy, LocalAgentSecurityService, MateLaunchpadService, PermissionOverride,
-    SettingsManager, Workspace, WorkspaceLaunchpadSummary, WorkspaceManager, WorkspacePermissions,
-    WorkspacePreparedLaunch,
+    CodeIndex, EffectiveLocalAgentPolicy, LocalAgentSecurityService, MateLaunchpadService,
+    PermissionOverride, SettingsManager, Workspace, WorkspaceLaunchpadSummary, WorkspaceManager,
+    WorkspacePermissions, WorkspacePreparedLaunch,
 };
+use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, State};
 use tokio::sync::Mutex;
@@ -72,6 +74,28 @@ pub async fn delete_workspace(
         .map_err(|e| e.to_string())
 }
 
+/// Index a workspace's source files for `search_code` and keep watching them
+#[tauri::command]
+pub async fn index_workspace_code(
+    id: String,
+    workspace_manager: State<'_, Arc<WorkspaceManager>>,
+    code_index: State<'_, Arc<CodeIndex>>,
+) -> Result<CodeIndexStats, String> {
+    let workspace = workspace_manager
+        .load_workspace(&id)
+        .map_err(|e| e.to_string())?;
+    let roots = workspace
+        .allowed_paths
+        .iter()
+        .map(PathBuf::from)
+        .filter(|root| root.is_absolute())
+        .collect::<Vec<_>>();
+    if roots.is_empty() {
+        return Err("Workspace has no allowed paths to index".to_string());
+    }
+    code_index.reindex(&id, roots).await
+}
+
 /// Add a permission override for a specific path
 #[tauri::command]
 pub async fn add_permission_override(
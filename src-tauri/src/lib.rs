@@ -84,7 +84,6 @@ pub fn run() {
     // Initialize Node Authenticator
     let authenticator = NodeAuthenticator::new();
     let runtime_registry = Arc::new(RuntimeRegistry::new());
-    let agent_run_control = Arc::new(AgentRunControl::new());
 
     // Initialize Neural Service (Distributed Neural System)
     let neural_service = NeuralService::new(
@@ -114,6 +113,9 @@ pub fn run() {
         browser_controller.clone(),
         mcp_service.clone(),
     ));
+    let agent_run_control = Arc::new(AgentRunControl::with_process_table(
+        skill_executor.process_table(),
+    ));
 
     // Initialize Command Poller
     // Note: It starts "stopped". Setup will start it if credentials exist.
@@ -415,9 +417,85 @@ pub fn run() {
                 })
             })?;
 
+            let airlock_grant_store =
+                Arc::new(crate::services::AirlockGrantStore::new(db.pool.clone()));
+            tauri::async_runtime::block_on(async {
+                airlock_grant_store.init().await.map_err(|e| {
+                    startup_error(format!("Failed to initialize Airlock grant store: {}", e))
+                })
+            })?;
+
+            let audit_ledger = Arc::new(crate::services::AuditLedger::new(db.pool.clone()));
+            tauri::async_runtime::block_on(async {
+                audit_ledger
+                    .init()
+                    .await
+                    .map_err(|e| startup_error(format!("Failed to initialize audit ledger: {}", e)))
+            })?;
+            {
+                let se = app.state::<Arc<SkillExecutor>>();
+                let ledger = audit_ledger.clone();
+                let run_control = app.state::<Arc<AgentRunControl>>().inner().clone();
+                let file_ops = app.state::<Arc<FileOperationEngine>>().inner().clone();
+                let app_handle = app.handle().clone();
+                tauri::async_runtime::block_on(async move {
+                    se.set_audit_ledger(ledger).await;
+                    se.set_run_control(run_control).await;
+                    se.set_file_checkpoints(file_ops).await;
+                    se.process_table().set_app_handle(app_handle).await;
+                });
+            }
+            app.manage(audit_ledger.clone());
+
+            let usage_ledger = Arc::new(crate::services::UsageLedger::new(db.pool.clone()));
+            tauri::async_runtime::block_on(async {
+                usage_ledger
+                    .init()
+                    .await
+                    .map_err(|e| startup_error(format!("Failed to initialize usage ledger: {}", e)))
+            })?;
+            {
+                let router_state = app.state::<commands::router::IntelligentRouterState>();
+                let ledger = usage_ledger.clone();
+                tauri::async_runtime::block_on(async move {
+                    router_state.0.write().await.set_usage_ledger(ledger);
+                });
+            }
+            app.manage(usage_ledger);
+
+            let code_index = Arc::new(crate::services::CodeIndex::new(db.pool.clone()));
+            tauri::async_runtime::block_on(async {
+                code_index
+                    .init()
+                    .await
+                    .map_err(|e| startup_error(format!("Failed to initialize code index: {}", e)))
+            })?;
+            {
+                let se = app.state::<Arc<SkillExecutor>>();
+                let index = code_index.clone();
+                tauri::async_runtime::block_on(async move {
+                    se.set_code_index(index).await;
+                });
+            }
+            app.manage(code_index);
+
+            let run_checkpoints =
+                Arc::new(crate::services::RunCheckpointStore::new(db.pool.clone()));
+            tauri::async_runtime::block_on(async {
+                run_checkpoints.init().await.map_err(|e| {
+                    startup_error(format!("Failed to initialize run checkpoints: {}", e))
+                })
+            })?;
+            app.manage(run_checkpoints.clone());
+
             // Initialize Airlock Service with app handle + persistence
-            let airlock =
-                AirlockService::new(app.handle().clone(), Some(airlock_message_store.clone()));
+            let airlock = AirlockService::new(
+                app.handle().clone(),
+                Some(airlock_message_store.clone()),
+                Some(airlock_grant_store),
+                Some(audit_ledger),
+                Some(run_checkpoints),
+            );
             let airlock_for_poller = airlock.clone();
 
             let airlock_state = app.state::<commands::airlock::AirlockServiceState>();
@@ -659,6 +737,10 @@ pub fn run() {
             commands::commit_file_transaction,
             commands::rollback_file_transaction,
             commands::get_file_transaction,
+            // Agent run checkpoint commands
+            commands::list_run_changes,
+            commands::rollback_run,
+            commands::rollback_run_file,
             // Enhanced undo/redo commands
             commands::undo_file_operation_enhanced,
             commands::redo_file_operation,
@@ -683,6 +765,11 @@ pub fn run() {
             commands::get_vault_detailed_stats,
             commands::delete_vault_entries_batch,
             commands::clear_workspace_vault,
+            commands::list_pending_memories,
+            commands::review_pending_memory,
+            commands::export_workspace_memory,
+            commands::import_workspace_memory,
+            commands::get_memory_archive_keys,
             // Settings commands
             commands::get_user_settings,
             commands::get_selected_model,
@@ -691,6 +778,13 @@ pub fn run() {
             commands::set_embedder_provider,
             commands::get_embedder_model,
             commands::set_embedder_model,
+            commands::get_embedder_base_url,
+            commands::set_embedder_base_url,
+            commands::get_vault_key_provider,
+            commands::set_vault_key_provider,
+            commands::unlock_vault,
+            commands::lock_vault,
+            commands::rotate_master_key,
             commands::set_theme,
             commands::set_notifications,
             commands::get_launch_at_login_status,
@@ -713,6 +807,7 @@ pub fn run() {
             commands::save_workspace,
             commands::list_workspaces,
             commands::delete_workspace,
+            commands::index_workspace_code,
             commands::add_permission_override,
             commands::remove_permission_override,
             commands::get_permission_overrides,
@@ -746,6 +841,12 @@ pub fn run() {
             commands::remove_provider_from_router,
             commands::get_router_providers,
             commands::router_has_providers,
+            commands::query_usage_ledger,
+            commands::summarize_usage,
+            commands::get_token_calibration,
+            commands::list_usage_budgets,
+            commands::upsert_usage_budget,
+            commands::delete_usage_budget,
             // Research commands
             commands::research::perform_research,
             // Unified Model commands (PHASE 4)
@@ -817,6 +918,12 @@ pub fn run() {
             commands::list_airlock_messages,
             commands::ack_airlock_message,
             commands::send_airlock_message,
+            commands::explain_airlock_policy,
+            commands::list_airlock_grants,
+            commands::revoke_airlock_grant,
+            commands::query_audit_ledger,
+            commands::verify_audit_ledger_chain,
+            commands::export_audit_ledger,
             commands::set_headless_mode,
             commands::create_external_agent_session,
             commands::send_external_agent_input,
@@ -840,6 +947,9 @@ pub fn run() {
             // Agent Workflow (Native Rust)
             commands::agent::run_agent_workflow,
             commands::agent::cancel_agent_run,
+            commands::agent::resume_agent_run,
+            commands::agent::list_resumable_agent_runs,
+            commands::agent::dismiss_agent_run,
             commands::agent::get_chat_session,
             commands::agent::list_chat_sessions,
             commands::agent::create_chat_session,
@@ -882,6 +992,7 @@ pub fn run() {
             chat_sessions::get_chat_runtime_telemetry,
             chat_sessions::clear_chat_history,
             chat_sessions::compact_session_cmd,
+            chat_sessions::fork_chat,
             crate::services::mcp_http::handle_mcp_request,
             commands::list_mcp_servers,
             commands::upsert_mcp_server,
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 // ──────────────────────────────────────────────────────────────────────────
 // Desktop Node (Nerve Center)
@@ -163,6 +163,23 @@ pub struct RainyPayload {
     /// End-user identifier from the connector (peer phone, user ID, etc.).
     #[serde(default)]
     pub user_id: Option<String>,
+    /// Agent spec that issued the tool call, used for Airlock policy rules.
+    #[serde(default)]
+    pub agent_spec_id: Option<String>,
+    /// Agent run that issued the tool call, used to scope Airlock grants.
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Chat session that issued the tool call, used to scope Airlock grants.
+    #[serde(default)]
+    pub chat_id: Option<String>,
+    /// Highest Airlock level the issuing agent may run; calls assessed above
+    /// it are denied without prompting.
+    #[serde(default)]
+    pub max_airlock_level: Option<u8>,
+    /// Isolated checkout that stands in for the workspace root; file and
+    /// shell tools resolve against it instead of the registered workspace.
+    #[serde(default)]
+    pub checkout_root: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -213,13 +230,31 @@ pub struct QueuedCommand {
     pub schema_version: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommandResult {
     pub success: bool,
     pub output: Option<String>,
     pub error: Option<String>,
     pub exit_code: Option<i32>,
+    /// Isolation applied to a spawned process (shell commands only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxReport>,
+}
+
+/// Which sandbox layers were applied to a shell command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxReport {
+    pub enabled: bool,
+    /// Landlock ABI used for filesystem rules; `None` when unavailable.
+    pub landlock_abi: Option<i32>,
+    pub network_isolated: bool,
+    pub seccomp: bool,
+    pub rlimits: bool,
+    pub env_scrubbed: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -253,6 +288,10 @@ pub struct ToolUsageByRole {
     pub research: u64,
     pub executor: u64,
     pub verifier: u64,
+    #[serde(default)]
+    pub memory_scribe: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,4 +1,5 @@
 use crate::services::agent_kill_switch::AgentKillSwitch;
+use crate::services::process_table::ProcessTable;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -6,6 +7,8 @@ use tokio::sync::RwLock;
 #[derive(Clone, Default)]
 pub struct AgentRunControl {
     runs: Arc<RwLock<HashMap<String, AgentKillSwitch>>>,
+    /// Background shell processes, killed when their run is cancelled.
+    processes: Option<ProcessTable>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -19,6 +22,13 @@ impl AgentRunControl {
         Self::default()
     }
 
+    pub fn with_process_table(processes: ProcessTable) -> Self {
+        Self {
+            processes: Some(processes),
+            ..Self::default()
+        }
+    }
+
     pub async fn register_run(&self, run_id: String, kill_switch: AgentKillSwitch) {
         let mut runs = self.runs.write().await;
         runs.insert(run_id, kill_switch);
@@ -29,13 +39,19 @@ impl AgentRunControl {
         runs.remove(run_id);
     }
 
+    pub async fn kill_switch(&self, run_id: &str) -> Option<AgentKillSwitch> {
+        self.runs.read().await.get(run_id).cloned()
+    }
+
     pub async fn cancel_run(&self, run_id: &str) -> CancelRunResult {
-        let runs = self.runs.read().await;
-        if let Some(kill_switch) = runs.get(run_id) {
-            kill_switch.trigger();
-            CancelRunResult::Cancelled
-        } else {
-            CancelRunResult::UnknownRun
+        let kill_switch = self.kill_switch(run_id).await;
+        let Some(kill_switch) = kill_switch else {
+            return CancelRunResult::UnknownRun;
+        };
+        kill_switch.trigger();
+        if let Some(processes) = self.processes.as_ref() {
+            processes.kill_run(run_id).await;
         }
+        CancelRunResult::Cancelled
     }
 }
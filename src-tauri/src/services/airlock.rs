@@ -8,10 +8,18 @@
 //! - **Level 0 (Safe)**: Read-only operations - auto-approved
 //! - **Level 1 (Sensitive)**: Write operations - requires notification
 //! - **Level 2 (Dangerous)**: Execution operations - requires explicit approval
+//!
+//! The policy level is a baseline; `AirlockRiskClassifier` adjusts it from the
+//! call arguments (e.g. `git status` vs. `git reset --hard`).
 
 use crate::models::neural::{AirlockLevel, QueuedCommand};
-use crate::services::tool_policy::get_tool_policy;
+use crate::services::airlock_policy::{AirlockPolicyEngine, PolicyDecision, PolicyRequest};
+use crate::services::airlock_risk::{AirlockRiskClassifier, ProbedFiles, RiskAssessment};
+use crate::services::audit_ledger::{AuditLedger, PermissionOutcome};
+use crate::services::file_patch;
+use crate::services::run_checkpoints::RunCheckpointStore;
 use crate::services::ThirdPartySkillRegistry;
+use crate::services::{AirlockGrant, AirlockGrantStore, ApprovalScope};
 use crate::services::{AirlockMessage, AirlockMessageStore};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -28,18 +36,25 @@ pub struct ApprovalRequest {
     pub intent: String,
     pub tool_name: Option<String>,
     pub payload_summary: String,
+    /// Unified diff the edit would make, for `edit_file` / `apply_patch`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_preview: Option<String>,
     pub airlock_level: AirlockLevel,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout_secs: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expires_at: Option<i64>,
     pub timestamp: i64,
+    /// Agent run waiting on this approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
 }
 
 /// Result of an approval request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ApprovalResult {
-    Approved,
+    /// Approved, optionally extending to later matching commands.
+    Approved(ApprovalScope),
     Rejected,
     Timeout,
 }
@@ -56,6 +71,9 @@ pub struct AirlockService {
     pending_approvals: Arc<Mutex<HashMap<String, PendingApproval>>>,
     headless_mode: Arc<AtomicBool>,
     message_store: Option<Arc<AirlockMessageStore>>,
+    grant_store: Option<Arc<AirlockGrantStore>>,
+    audit_ledger: Option<Arc<AuditLedger>>,
+    run_checkpoints: Option<Arc<RunCheckpointStore>>,
 }
 
 impl std::fmt::Debug for AirlockService {
@@ -63,6 +81,9 @@ impl std::fmt::Debug for AirlockService {
         f.debug_struct("AirlockService")
             .field("headless_mode", &self.headless_mode)
             .field("has_message_store", &self.message_store.is_some())
+            .field("has_grant_store", &self.grant_store.is_some())
+            .field("has_audit_ledger", &self.audit_ledger.is_some())
+            .field("has_run_checkpoints", &self.run_checkpoints.is_some())
             .finish()
     }
 }
@@ -131,6 +152,13 @@ impl AirlockService {
         )
     }
 
+    fn diff_preview(command: &QueuedCommand) -> Option<String> {
+        let tool = command.payload.method.as_deref()?;
+        let params = command.payload.params.as_ref()?;
+        let root = command.payload.allowed_paths.first().map(String::as_str);
+        file_patch::preview(tool, params, root)
+    }
+
     fn infer_tool_name(command: &QueuedCommand) -> Option<String> {
         if Self::is_agent_run_bootstrap(command) {
             return Some("agent.run".to_string());
@@ -155,24 +183,106 @@ impl AirlockService {
             .unwrap_or(false)
     }
 
-    fn effective_airlock_level(command: &QueuedCommand) -> AirlockLevel {
+    fn policy_engine(command: &QueuedCommand) -> AirlockPolicyEngine {
+        AirlockPolicyEngine::load_for_paths(&command.payload.allowed_paths)
+    }
+
+    fn policy_decision(
+        engine: &AirlockPolicyEngine,
+        command: &QueuedCommand,
+        tool: &str,
+    ) -> PolicyDecision {
+        engine.evaluate(&PolicyRequest {
+            tool,
+            args: command.payload.params.as_ref(),
+            workspace_id: command.workspace_id.as_deref(),
+            agent_spec_id: command.payload.agent_spec_id.as_deref(),
+        })
+    }
+
+    #[cfg(test)]
+    fn effective_airlock_level_with(
+        engine: &AirlockPolicyEngine,
+        command: &QueuedCommand,
+    ) -> AirlockLevel {
+        Self::assess_risk_with(engine, command, &ProbedFiles::new()).level
+    }
+
+    /// [`Self::assess_risk_with`] after probing the call's file targets.
+    async fn assess_risk(engine: &AirlockPolicyEngine, command: &QueuedCommand) -> RiskAssessment {
+        let probed = match Self::infer_tool_name(command) {
+            Some(tool) => {
+                let settings = engine.risk_settings();
+                AirlockRiskClassifier::new(&settings, command)
+                    .probe(&tool, command.payload.params.as_ref())
+                    .await
+            }
+            None => ProbedFiles::new(),
+        };
+        Self::assess_risk_with(engine, command, &probed)
+    }
+
+    /// Policy baseline adjusted by the argument-aware risk classifier.
+    ///
+    /// The declared level only acts as a floor when it is stricter than the
+    /// policy baseline; otherwise the classifier may lower it (e.g. read-only
+    /// `git status`). Levels pinned by a policy rule are never lowered.
+    fn assess_risk_with(
+        engine: &AirlockPolicyEngine,
+        command: &QueuedCommand,
+        probed: &ProbedFiles,
+    ) -> RiskAssessment {
         if Self::is_agent_run_bootstrap(command) {
-            return AirlockLevel::Safe;
+            return RiskAssessment {
+                level: AirlockLevel::Safe,
+                reasons: Vec::new(),
+            };
         }
         let declared = command.airlock_level;
-        let policy_level = Self::infer_tool_name(command)
-            .and_then(|tool| {
-                get_tool_policy(&tool)
-                    .map(|policy| policy.airlock_level)
-                    .or_else(|| Self::third_party_tool_level(&tool))
-            })
-            .unwrap_or(AirlockLevel::Dangerous);
-
-        if policy_level > declared {
-            policy_level
-        } else {
+        let Some(tool) = Self::infer_tool_name(command) else {
+            return RiskAssessment {
+                level: AirlockLevel::Dangerous,
+                reasons: Vec::new(),
+            };
+        };
+        let decision = Self::policy_decision(engine, command, &tool);
+        let Some(baseline) = decision
+            .airlock_level
+            .or_else(|| Self::third_party_tool_level(&tool))
+        else {
+            return RiskAssessment {
+                level: AirlockLevel::Dangerous,
+                reasons: Vec::new(),
+            };
+        };
+
+        let settings = engine.risk_settings();
+        let mut assessment = AirlockRiskClassifier::new(&settings, command).assess(
+            &tool,
+            command.payload.params.as_ref(),
+            baseline,
+            probed,
+        );
+        let floor = if declared > baseline {
             declared
+        } else if decision.level_pinned {
+            baseline
+        } else {
+            AirlockLevel::Safe
+        };
+        if floor > assessment.level {
+            assessment.level = floor;
         }
+        assessment
+    }
+
+    /// Whether the assessed `level` is above the issuing agent's Airlock
+    /// ceiling, if it declared one.
+    fn exceeds_ceiling(command: &QueuedCommand, level: AirlockLevel) -> bool {
+        command
+            .payload
+            .max_airlock_level
+            .is_some_and(|ceiling| level as u8 > ceiling)
     }
 
     fn insert_pending_approval(
@@ -202,12 +312,21 @@ impl AirlockService {
         approvals
     }
 
-    pub fn new(app: AppHandle, message_store: Option<Arc<AirlockMessageStore>>) -> Self {
+    pub fn new(
+        app: AppHandle,
+        message_store: Option<Arc<AirlockMessageStore>>,
+        grant_store: Option<Arc<AirlockGrantStore>>,
+        audit_ledger: Option<Arc<AuditLedger>>,
+        run_checkpoints: Option<Arc<RunCheckpointStore>>,
+    ) -> Self {
         Self {
             app,
             pending_approvals: Arc::new(Mutex::new(HashMap::new())),
             headless_mode: Arc::new(AtomicBool::new(false)),
             message_store,
+            grant_store,
+            audit_ledger,
+            run_checkpoints,
         }
     }
 
@@ -226,12 +345,30 @@ impl AirlockService {
 
     // Called during command execution flow
     pub async fn check_permission(&self, command: &QueuedCommand) -> Result<bool, String> {
+        let outcome = self.evaluate_permission(command).await;
+        if let Some(ledger) = self.audit_ledger.as_ref() {
+            let recorded = match outcome.as_ref() {
+                Ok(outcome) => outcome.clone(),
+                Err(_) => PermissionOutcome::denied(None, "error"),
+            };
+            ledger.note_decision(command, recorded).await;
+        }
+        outcome.map(|outcome| outcome.allowed)
+    }
+
+    async fn evaluate_permission(
+        &self,
+        command: &QueuedCommand,
+    ) -> Result<PermissionOutcome, String> {
         if Self::is_agent_run_bootstrap(command) {
             tracing::debug!(
                 "Airlock: Auto-approved agent.run bootstrap command {}",
                 command.id
             );
-            return Ok(true);
+            return Ok(PermissionOutcome::allowed(
+                Some(AirlockLevel::Safe),
+                "agent_run_bootstrap",
+            ));
         }
 
         // MCP tools use a dedicated global ask/no-ask gate in McpService.
@@ -241,42 +378,73 @@ impl AirlockService {
                 "Airlock: Bypassing MCP tool {}; delegated to MCP gate",
                 command.id
             );
-            return Ok(true);
+            return Ok(PermissionOutcome::allowed(None, "mcp_gate"));
         }
 
+        let engine = Self::policy_engine(command);
         let inferred_tool = Self::infer_tool_name(command);
-        let has_policy = inferred_tool
+        let decision = inferred_tool
+            .as_deref()
+            .map(|tool| Self::policy_decision(&engine, command, tool));
+        if let Some(decision) = decision.as_ref().filter(|decision| decision.is_denied()) {
+            tracing::warn!(
+                "Airlock: Denying command {} by policy (tool={:?}): {}",
+                command.id,
+                inferred_tool,
+                decision.reason
+            );
+            return Ok(PermissionOutcome::denied(None, "denied_by_policy"));
+        }
+        let has_policy = decision
             .as_ref()
-            .and_then(|tool| {
-                get_tool_policy(tool)
-                    .map(|_| ())
-                    .or_else(|| Self::third_party_tool_level(tool).map(|_| ()))
-            })
-            .is_some();
+            .and_then(|decision| decision.airlock_level)
+            .is_some()
+            || inferred_tool
+                .as_deref()
+                .and_then(Self::third_party_tool_level)
+                .is_some();
         if !has_policy {
             tracing::warn!(
                 "Airlock: Denying command {} because tool policy is missing (tool={:?})",
                 command.id,
                 inferred_tool
             );
-            return Ok(false);
+            return Ok(PermissionOutcome::denied(None, "missing_policy"));
         }
 
-        let effective_level = Self::effective_airlock_level(command);
+        let assessment = Self::assess_risk(&engine, command).await;
+        let effective_level = assessment.level;
+        let risk_summary = assessment.summary();
         if effective_level != command.airlock_level {
             tracing::warn!(
-                "Airlock: Escalating command {} level from {:?} to {:?} based on tool policy",
+                "Airlock: Adjusting command {} level from {:?} to {:?} based on tool policy and risk ({})",
                 command.id,
                 command.airlock_level,
+                effective_level,
+                risk_summary.as_deref().unwrap_or("no argument signals")
+            );
+        }
+
+        if Self::exceeds_ceiling(command, effective_level) {
+            tracing::warn!(
+                "Airlock: Denying command {} at {:?}; above the issuing agent's ceiling",
+                command.id,
                 effective_level
             );
+            return Ok(PermissionOutcome::denied(
+                Some(effective_level),
+                "above_agent_ceiling",
+            ));
         }
 
         match effective_level {
             AirlockLevel::Safe => {
                 // Level 0: Auto-approve read-only operations
                 tracing::debug!("Airlock: Auto-approved SAFE command {}", command.id);
-                Ok(true)
+                Ok(PermissionOutcome::allowed(
+                    Some(effective_level),
+                    "auto_safe",
+                ))
             }
             AirlockLevel::Sensitive => {
                 // Level 1: Write operations
@@ -285,13 +453,23 @@ impl AirlockService {
                         "Airlock: Auto-approved SENSITIVE command {} (Headless Mode)",
                         command.id
                     );
-                    Ok(true)
+                    Ok(PermissionOutcome::allowed(
+                        Some(effective_level),
+                        "headless",
+                    ))
                 } else {
+                    if let Some(grant_id) = self.approve_by_grant(command, effective_level).await {
+                        return Ok(PermissionOutcome::allowed(
+                            Some(effective_level),
+                            format!("grant:{}", grant_id),
+                        ));
+                    }
                     tracing::info!(
                         "Airlock: SENSITIVE command {} requires notification",
                         command.id
                     );
-                    self.request_approval(command, effective_level, false).await
+                    self.request_approval(command, effective_level, risk_summary.as_deref(), false)
+                        .await
                 }
             }
             AirlockLevel::Dangerous => {
@@ -304,11 +482,166 @@ impl AirlockService {
                         command.id
                     );
                 }
+                if let Some(grant_id) = self.approve_by_grant(command, effective_level).await {
+                    return Ok(PermissionOutcome::allowed(
+                        Some(effective_level),
+                        format!("grant:{}", grant_id),
+                    ));
+                }
                 tracing::warn!(
                     "Airlock: DANGEROUS command {} requires explicit approval",
                     command.id
                 );
-                self.request_approval(command, effective_level, false).await
+                self.request_approval(command, effective_level, risk_summary.as_deref(), false)
+                    .await
+            }
+        }
+    }
+
+    /// Permission for a tool call that was in flight when its run was
+    /// interrupted. It may or may not have taken effect, so the operator
+    /// decides again even where the Safe level or a grant would auto-approve.
+    pub async fn check_interrupted_permission(
+        &self,
+        command: &QueuedCommand,
+    ) -> Result<bool, String> {
+        let engine = Self::policy_engine(command);
+        let denied = Self::infer_tool_name(command)
+            .is_some_and(|tool| Self::policy_decision(&engine, command, &tool).is_denied());
+        let outcome = if denied {
+            Ok(PermissionOutcome::denied(None, "denied_by_policy"))
+        } else {
+            let assessment = Self::assess_risk(&engine, command).await;
+            let level = match assessment.level {
+                AirlockLevel::Safe => AirlockLevel::Sensitive,
+                level => level,
+            };
+            let summary = match assessment.summary() {
+                Some(risk) => format!("interrupted before it finished; {}", risk),
+                None => "interrupted before it finished".to_string(),
+            };
+            if Self::exceeds_ceiling(command, assessment.level) {
+                Ok(PermissionOutcome::denied(
+                    Some(assessment.level),
+                    "above_agent_ceiling",
+                ))
+            } else {
+                self.request_approval(command, level, Some(&summary), false)
+                    .await
+            }
+        };
+        if let Some(ledger) = self.audit_ledger.as_ref() {
+            let recorded = match outcome.as_ref() {
+                Ok(outcome) => outcome.clone(),
+                Err(_) => PermissionOutcome::denied(None, "error"),
+            };
+            ledger.note_decision(command, recorded).await;
+        }
+        outcome.map(|outcome| outcome.allowed)
+    }
+
+    /// Auto-approves `command` when a standing grant covers it, recording the
+    /// approval as "approved by grant X". Returns the grant id.
+    async fn approve_by_grant(
+        &self,
+        command: &QueuedCommand,
+        effective_level: AirlockLevel,
+    ) -> Option<String> {
+        let (Some(store), Some(tool)) = (self.grant_store.as_ref(), Self::infer_tool_name(command))
+        else {
+            return None;
+        };
+        let now = Self::now_millis();
+        let grants = match store.active_for_tool(&tool, now).await {
+            Ok(grants) => grants,
+            Err(error) => {
+                tracing::warn!("Airlock: failed to load grants for {}: {}", tool, error);
+                return None;
+            }
+        };
+        let grant = grants
+            .into_iter()
+            .find(|grant| grant.covers(command, &tool, effective_level))?;
+
+        tracing::info!(
+            "Airlock: Command {} approved by {} grant {}",
+            command.id,
+            grant.scope,
+            grant.id
+        );
+        if let Err(error) = store.record_use(&grant.id, now).await {
+            tracing::warn!(
+                "Airlock: failed to record use of grant {}: {}",
+                grant.id,
+                error
+            );
+        }
+        if let Some(messages) = self.message_store.as_ref() {
+            let resolution = format!("Approved by grant {}", grant.id);
+            let recorded = messages
+                .record_pending(
+                    &command.id,
+                    &command.intent,
+                    Some(&tool),
+                    &Self::summarize_payload(command),
+                    effective_level,
+                    now,
+                    None,
+                )
+                .await;
+            let resolved = match recorded {
+                Ok(()) => {
+                    messages
+                        .mark_resolved(&command.id, "approved", Some(&resolution), now)
+                        .await
+                }
+                Err(error) => Err(error),
+            };
+            if let Err(error) = resolved {
+                tracing::warn!(
+                    "Airlock: failed to persist grant approval for {}: {}",
+                    command.id,
+                    error
+                );
+            }
+        }
+        Some(grant.id)
+    }
+
+    async fn store_grant(
+        &self,
+        command: &QueuedCommand,
+        level: AirlockLevel,
+        scope: &ApprovalScope,
+    ) {
+        let (Some(store), Some(tool)) = (self.grant_store.as_ref(), Self::infer_tool_name(command))
+        else {
+            return;
+        };
+        match AirlockGrant::from_approval(command, &tool, level, scope, Self::now_millis()) {
+            Ok(Some(grant)) => {
+                if let Err(error) = store.insert(&grant).await {
+                    tracing::warn!(
+                        "Airlock: failed to store grant for {}: {}",
+                        command.id,
+                        error
+                    );
+                } else {
+                    tracing::info!(
+                        "Airlock: Stored {} grant {} for tool {}",
+                        grant.scope,
+                        grant.id,
+                        tool
+                    );
+                }
+            }
+            Ok(None) => {}
+            Err(error) => {
+                tracing::warn!(
+                    "Airlock: ignoring approval scope for {}: {}",
+                    command.id,
+                    error
+                );
             }
         }
     }
@@ -324,19 +657,25 @@ impl AirlockService {
         &self,
         command: &QueuedCommand,
         effective_level: AirlockLevel,
+        risk_summary: Option<&str>,
         allow_on_timeout: bool,
-    ) -> Result<bool, String> {
+    ) -> Result<PermissionOutcome, String> {
         let timeout_secs = Self::resolve_approval_timeout_secs(command, effective_level);
         let now = chrono::Utc::now().timestamp_millis();
         let request = ApprovalRequest {
             command_id: command.id.clone(),
             intent: command.intent.clone(),
             tool_name: Self::infer_tool_name(command),
-            payload_summary: Self::summarize_payload(command),
+            payload_summary: match risk_summary {
+                Some(risk) => format!("{} | risk: {}", Self::summarize_payload(command), risk),
+                None => Self::summarize_payload(command),
+            },
+            diff_preview: Self::diff_preview(command),
             airlock_level: effective_level,
             timeout_secs,
             expires_at: timeout_secs.map(|value| now + (value as i64 * 1000)),
             timestamp: now,
+            run_id: command.payload.run_id.clone(),
         };
 
         let (tx, rx) = oneshot::channel::<ApprovalResult>();
@@ -367,6 +706,7 @@ impl AirlockService {
                 );
             }
         }
+        self.sync_run_approvals(request.run_id.as_deref()).await;
 
         // Emit event to frontend
         self.app
@@ -399,15 +739,20 @@ impl AirlockService {
             let mut pending = self.pending_approvals.lock().await;
             Self::remove_pending_approval(&mut pending, &command.id);
         }
+        self.sync_run_approvals(request.run_id.as_deref()).await;
 
         match result {
-            ApprovalResult::Approved => {
+            ApprovalResult::Approved(scope) => {
                 tracing::info!("Airlock: Command {} APPROVED by user", command.id);
+                self.store_grant(command, effective_level, &scope).await;
                 self.persist_resolution(&command.id, "approved", Some("User approved command"))
                     .await;
                 // Notify frontend to clear the request from UI
                 let _ = self.app.emit("airlock:approval_resolved", &command.id);
-                Ok(true)
+                Ok(PermissionOutcome::allowed(
+                    Some(effective_level),
+                    format!("user_approved:{}", scope.label()),
+                ))
             }
             ApprovalResult::Rejected => {
                 tracing::info!("Airlock: Command {} REJECTED by user", command.id);
@@ -415,7 +760,10 @@ impl AirlockService {
                     .await;
                 // Notify frontend to clear the request from UI
                 let _ = self.app.emit("airlock:approval_resolved", &command.id);
-                Ok(false)
+                Ok(PermissionOutcome::denied(
+                    Some(effective_level),
+                    "user_rejected",
+                ))
             }
             ApprovalResult::Timeout => {
                 // Timeout or channel closed
@@ -433,7 +781,10 @@ impl AirlockService {
                         Some("Timed out and allowed by policy override"),
                     )
                     .await;
-                    return Ok(true);
+                    return Ok(PermissionOutcome::allowed(
+                        Some(effective_level),
+                        "timeout_allowed",
+                    ));
                 }
 
                 tracing::warn!(
@@ -442,7 +793,7 @@ impl AirlockService {
                 );
                 self.persist_resolution(&command.id, "timeout", Some("Approval request timed out"))
                     .await;
-                Ok(false)
+                Ok(PermissionOutcome::denied(Some(effective_level), "timeout"))
             }
         }
     }
@@ -510,12 +861,13 @@ impl AirlockService {
         &self,
         command_id: &str,
         approved: bool,
+        scope: ApprovalScope,
     ) -> Result<(), String> {
         let mut pending = self.pending_approvals.lock().await;
 
         if let Some(entry) = Self::remove_pending_approval(&mut pending, command_id) {
             let result = if approved {
-                ApprovalResult::Approved
+                ApprovalResult::Approved(scope)
             } else {
                 ApprovalResult::Rejected
             };
@@ -530,12 +882,88 @@ impl AirlockService {
         }
     }
 
+    /// Explain which policy layer and rule decides a tool call.
+    pub fn explain_policy(
+        tool: &str,
+        args: Option<&serde_json::Value>,
+        workspace_id: Option<&str>,
+        agent_spec_id: Option<&str>,
+        workspace_root: Option<&std::path::Path>,
+    ) -> crate::services::airlock_policy::PolicyExplanation {
+        AirlockPolicyEngine::load(workspace_root).explain(&PolicyRequest {
+            tool,
+            args,
+            workspace_id,
+            agent_spec_id,
+        })
+    }
+
+    pub async fn list_grants(&self, include_inactive: bool) -> Result<Vec<AirlockGrant>, String> {
+        let Some(store) = self.grant_store.as_ref() else {
+            return Ok(Vec::new());
+        };
+        store
+            .list(include_inactive, Self::now_millis())
+            .await
+            .map_err(|e| format!("Failed to list Airlock grants: {}", e))
+    }
+
+    pub async fn revoke_grant(&self, grant_id: &str) -> Result<(), String> {
+        let Some(store) = self.grant_store.as_ref() else {
+            return Err("Airlock grant store not initialized".to_string());
+        };
+        let revoked = store
+            .revoke(grant_id, Self::now_millis())
+            .await
+            .map_err(|e| format!("Failed to revoke Airlock grant: {}", e))?;
+        if revoked {
+            tracing::info!("Airlock: Revoked grant {}", grant_id);
+            Ok(())
+        } else {
+            Err(format!("No active grant {}", grant_id))
+        }
+    }
+
     /// Get all pending approval requests
     pub async fn get_pending_approvals(&self) -> Vec<ApprovalRequest> {
         let pending = self.pending_approvals.lock().await;
         Self::list_pending_approvals(&pending)
     }
 
+    /// Pending approval requests raised by one agent run.
+    pub async fn pending_approvals_for_run(&self, run_id: &str) -> Vec<ApprovalRequest> {
+        let pending = self.pending_approvals.lock().await;
+        Self::list_pending_approvals(&pending)
+            .into_iter()
+            .filter(|request| request.run_id.as_deref() == Some(run_id))
+            .collect()
+    }
+
+    /// Keeps the run's checkpoint in step with what it is waiting on, so an
+    /// interrupted run can show which approvals were outstanding.
+    async fn sync_run_approvals(&self, run_id: Option<&str>) {
+        let (Some(store), Some(run_id)) = (self.run_checkpoints.as_ref(), run_id) else {
+            return;
+        };
+        let pending = self.pending_approvals_for_run(run_id).await;
+        if let Err(error) = store.set_pending_approvals(run_id, &pending).await {
+            tracing::warn!("Airlock: run {}: {}", run_id, error);
+        }
+    }
+
+    /// Close out approvals that died with an interrupted run; nobody is
+    /// waiting on them any more.
+    pub async fn expire_interrupted(&self, command_ids: &[String]) {
+        for command_id in command_ids {
+            self.persist_resolution(
+                command_id,
+                "timeout",
+                Some("Run was interrupted before a decision"),
+            )
+            .await;
+        }
+    }
+
     pub async fn list_messages(&self, limit: Option<u32>) -> Result<Vec<AirlockMessage>, String> {
         let Some(store) = self.message_store.as_ref() else {
             return Ok(Vec::new());
@@ -603,10 +1031,12 @@ mod tests {
             intent: "filesystem.write_file".to_string(),
             tool_name: Some("write_file".to_string()),
             payload_summary: "{\"path\":\"/tmp/x\"}".to_string(),
+            diff_preview: None,
             airlock_level: AirlockLevel::Sensitive,
             timeout_secs: Some(10),
             expires_at: Some(timestamp + 10_000),
             timestamp,
+            run_id: None,
         }
     }
 
@@ -637,10 +1067,13 @@ mod tests {
 
         entry
             .responder
-            .send(ApprovalResult::Approved)
+            .send(ApprovalResult::Approved(ApprovalScope::Once))
             .expect("responder send should succeed");
         let result = rx.await.expect("receiver should get approval result");
-        assert!(matches!(result, ApprovalResult::Approved));
+        assert!(matches!(
+            result,
+            ApprovalResult::Approved(ApprovalScope::Once)
+        ));
     }
 
     #[test]
@@ -681,7 +1114,8 @@ mod tests {
             schema_version: None,
         };
 
-        let level = AirlockService::effective_airlock_level(&command);
+        let level =
+            AirlockService::effective_airlock_level_with(&AirlockPolicyEngine::builtin(), &command);
         assert_eq!(level, AirlockLevel::Dangerous);
     }
 
@@ -746,7 +1180,10 @@ mod tests {
             let declared = level_from_u8(rng.gen_range(0u8..=2u8));
             let tool = tools[rng.gen_range(0..tools.len())];
             let command = make_command_with_tool(tool, declared);
-            let effective = AirlockService::effective_airlock_level(&command);
+            let effective = AirlockService::effective_airlock_level_with(
+                &AirlockPolicyEngine::builtin(),
+                &command,
+            );
             assert!(
                 effective >= declared,
                 "effective={:?} declared={:?} tool={}",
@@ -774,7 +1211,10 @@ mod tests {
 
             let method = format!("unknown_{}", suffix);
             let command = make_command_with_tool(&method, declared);
-            let effective = AirlockService::effective_airlock_level(&command);
+            let effective = AirlockService::effective_airlock_level_with(
+                &AirlockPolicyEngine::builtin(),
+                &command,
+            );
             assert_eq!(
                 effective,
                 AirlockLevel::Dangerous,
@@ -786,6 +1226,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn policy_rules_override_builtin_level() {
+        let rules = AirlockPolicyEngine::parse_policy(
+            "[[rules]]\ntools = [\"read_file\"]\npaths = [\"**/.env\"]\nlevel = 2\n",
+            std::path::Path::new("policy.toml"),
+        )
+        .expect("valid policy");
+        let engine = AirlockPolicyEngine::builtin().with_layer("workspace", rules);
+
+        let mut command = make_command_with_tool("read_file", AirlockLevel::Safe);
+        command.payload.params = Some(serde_json::json!({"path": "/repo/.env"}));
+        assert_eq!(
+            AirlockService::effective_airlock_level_with(&engine, &command),
+            AirlockLevel::Dangerous
+        );
+
+        command.payload.params = Some(serde_json::json!({"path": "/repo/README.md"}));
+        assert_eq!(
+            AirlockService::effective_airlock_level_with(&engine, &command),
+            AirlockLevel::Safe
+        );
+    }
+
+    #[test]
+    fn risk_classifier_lowers_read_only_shell_commands() {
+        let mut command = make_command_with_tool("execute_command", AirlockLevel::Dangerous);
+        command.payload.params = Some(serde_json::json!({"command": "git", "args": ["status"]}));
+        let assessment = AirlockService::assess_risk_with(
+            &AirlockPolicyEngine::builtin(),
+            &command,
+            &ProbedFiles::new(),
+        );
+        assert_eq!(assessment.level, AirlockLevel::Safe);
+        assert!(assessment.summary().is_some());
+
+        command.payload.params =
+            Some(serde_json::json!({"command": "git", "args": ["clean", "-fd"]}));
+        assert_eq!(
+            AirlockService::effective_airlock_level_with(&AirlockPolicyEngine::builtin(), &command),
+            AirlockLevel::Dangerous
+        );
+    }
+
+    #[test]
+    fn risk_classifier_respects_stricter_declared_and_pinned_levels() {
+        let mut command = make_command_with_tool("http_post_json", AirlockLevel::Dangerous);
+        command.payload.allowed_domains = vec!["github.com".to_string()];
+        command.payload.params = Some(serde_json::json!({"url": "https://api.github.com/x"}));
+        assert_eq!(
+            AirlockService::effective_airlock_level_with(&AirlockPolicyEngine::builtin(), &command),
+            AirlockLevel::Sensitive
+        );
+
+        let rules = AirlockPolicyEngine::parse_policy(
+            "[[rules]]\ntools = [\"http_post_json\"]\nlevel = 2\n",
+            std::path::Path::new("policy.toml"),
+        )
+        .expect("valid policy");
+        let engine = AirlockPolicyEngine::builtin().with_layer("user", rules);
+        assert_eq!(
+            AirlockService::effective_airlock_level_with(&engine, &command),
+            AirlockLevel::Dangerous
+        );
+
+        let mut read = make_command_with_tool("execute_command", AirlockLevel::Dangerous);
+        read.payload.params = Some(serde_json::json!({"command": "ls", "args": []}));
+        read.airlock_level = AirlockLevel::Dangerous;
+        let rules = AirlockPolicyEngine::parse_policy(
+            "[[rules]]\ntools = [\"execute_command\"]\nlevel = 1\n",
+            std::path::Path::new("policy.toml"),
+        )
+        .expect("valid policy");
+        let engine = AirlockPolicyEngine::builtin().with_layer("user", rules);
+        assert_eq!(
+            AirlockService::effective_airlock_level_with(&engine, &read),
+            AirlockLevel::Dangerous
+        );
+    }
+
+    #[test]
+    fn agent_ceiling_applies_to_the_assessed_level() {
+        let mut status = make_command_with_tool("execute_command", AirlockLevel::Dangerous);
+        status.payload.params = Some(serde_json::json!({"command": "git", "args": ["status"]}));
+        status.payload.max_airlock_level = Some(0);
+        let level =
+            AirlockService::effective_airlock_level_with(&AirlockPolicyEngine::builtin(), &status);
+        assert!(!AirlockService::exceeds_ceiling(&status, level));
+
+        let mut write = make_command_with_tool("write_file", AirlockLevel::Sensitive);
+        write.payload.allowed_paths = vec!["/workspace".to_string()];
+        write.payload.params = Some(serde_json::json!({"path": "../outside.txt"}));
+        write.payload.max_airlock_level = Some(1);
+        let level =
+            AirlockService::effective_airlock_level_with(&AirlockPolicyEngine::builtin(), &write);
+        assert_eq!(level, AirlockLevel::Dangerous);
+        assert!(AirlockService::exceeds_ceiling(&write, level));
+
+        write.payload.max_airlock_level = None;
+        assert!(!AirlockService::exceeds_ceiling(&write, level));
+    }
+
     #[test]
     fn infer_tool_name_handles_malformed_intent() {
         let mut command = make_command_with_tool("read_file", AirlockLevel::Safe);
@@ -812,7 +1353,7 @@ mod tests {
         command.payload.method = Some("run".to_string());
         assert!(AirlockService::is_agent_run_bootstrap(&command));
         assert_eq!(
-            AirlockService::effective_airlock_level(&command),
+            AirlockService::effective_airlock_level_with(&AirlockPolicyEngine::builtin(), &command),
             AirlockLevel::Safe
         );
     }
@@ -0,0 +1,1086 @@
+//! Declarative Airlock policy engine.
+//!
+//! Operators can override the compiled-in tool table (`get_tool_policy`) with
+//! rule files instead of rebuilding the app. Within a layer the first matching
+//! rule applies; across layers:
+//!
+//! 1. User layer: `<data dir>/rainy-mate/policy.toml` (or `.yaml`) may allow,
+//!    deny, re-level (including lowering) and assign skills.
+//! 2. Workspace layer: `<workspace>/.rainy-mate/policy.toml` (or `.yaml`) ships
+//!    with the repository, so it is untrusted: its rules may only deny or raise
+//!    a level. Lower levels and `skill` overrides there are ignored.
+//! 3. Built-in layer: the static table in `tool_policy.rs`
+//!
+//! A deny from either file layer wins. Agent file tools cannot write the
+//! workspace policy files (see `is_workspace_policy_path`).
+//!
+//! ```toml
+//! [[rules]]
+//! id = "no-lockfile-writes"
+//! tools = ["write_file", "append_file"]
+//! paths = ["**/Cargo.lock", "**/pnpm-lock.yaml"]
+//! effect = "deny"
+//!
+//! [[rules]]
+//! id = "trusted-git"
+//! tools = ["execute_command"]
+//! commands = ["git"]
+//! level = 1
+//! ```
+//!
+//! The optional `[risk]` table feeds the argument-aware risk classifier
+//! (`airlock_risk.rs`); lists from every layer are merged, except that
+//! `trusted_hosts` (which lowers levels) is ignored in the workspace layer:
+//!
+//! ```toml
+//! [risk]
+//! trusted_hosts = ["api.github.com", "*.internal.example"]
+//! blocked_hosts = ["*.pastebin.com"]
+//! protected_paths = ["**/secrets/**"]
+//! ```
+//!
+//! A layer that fails to parse fails closed: every tool is denied until the
+//! file is fixed, and `explain` reports the parse error.
+
+use crate::models::neural::AirlockLevel;
+use crate::services::tool_policy::{get_tool_policy, ToolPolicy, ToolSkill};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+const POLICY_FILE_NAMES: &[&str] = &["policy.toml", "policy.yaml", "policy.yml"];
+const WORKSPACE_POLICY_DIR: &str = ".rainy-mate";
+const USER_LAYER: &str = "user";
+const WORKSPACE_LAYER: &str = "workspace";
+const PATH_ARG_KEYS: &[&str] = &["path", "paths", "source", "destination", "cwd"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyEffect {
+    #[default]
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct PolicyRule {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Tool name patterns (`*` wildcard). Empty matches every tool.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Globs matched against path-like arguments (`path`, `paths`, `source`, ...).
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Host patterns matched against the `url` argument (`*.example.com`).
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// Binary names matched against the `command` argument of shell tools.
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Arbitrary argument matchers: argument name -> glob on its string value.
+    #[serde(default)]
+    pub args: HashMap<String, String>,
+    #[serde(default)]
+    pub workspaces: Vec<String>,
+    #[serde(default)]
+    pub agents: Vec<String>,
+    #[serde(default)]
+    pub effect: PolicyEffect,
+    /// Airlock level override (0 = safe, 1 = sensitive, 2 = dangerous).
+    #[serde(default)]
+    pub level: Option<u8>,
+    /// Skill override; required to give an unknown tool a policy.
+    #[serde(default)]
+    pub skill: Option<String>,
+}
+
+/// Inputs for the risk classifier, merged across layers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct RiskSettings {
+    /// Hosts whose outbound calls are lowered by one level.
+    #[serde(default)]
+    pub trusted_hosts: Vec<String>,
+    /// Hosts whose outbound calls are always dangerous.
+    #[serde(default)]
+    pub blocked_hosts: Vec<String>,
+    /// Extra path globs treated like lockfiles and secrets.
+    #[serde(default)]
+    pub protected_paths: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyFile {
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    #[serde(default)]
+    pub risk: RiskSettings,
+}
+
+#[derive(Debug, Clone)]
+struct PolicyLayer {
+    name: String,
+    source: Option<PathBuf>,
+    rules: Vec<PolicyRule>,
+    risk: RiskSettings,
+    load_error: Option<String>,
+    /// Only the user layer may lower levels or reassign skills.
+    trusted: bool,
+}
+
+/// Parsed policy file, reused until the file's size or mtime changes.
+struct CachedLayer {
+    modified: Option<SystemTime>,
+    len: u64,
+    layer: PolicyLayer,
+}
+
+fn layer_cache() -> &'static Mutex<HashMap<PathBuf, CachedLayer>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, CachedLayer>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Inputs a rule can match on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolicyRequest<'a> {
+    pub tool: &'a str,
+    pub args: Option<&'a serde_json::Value>,
+    pub workspace_id: Option<&'a str>,
+    pub agent_spec_id: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyOrigin {
+    pub layer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyDecision {
+    pub effect: PolicyEffect,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skill: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub airlock_level: Option<AirlockLevel>,
+    pub decided_by: PolicyOrigin,
+    pub reason: String,
+    /// Set when a rule assigned the level (a user rule, or a workspace rule
+    /// that raised it); the risk classifier may raise such a level but never
+    /// lower it.
+    #[serde(skip)]
+    pub level_pinned: bool,
+}
+
+impl PolicyDecision {
+    pub fn is_denied(&self) -> bool {
+        self.effect == PolicyEffect::Deny
+    }
+
+    /// Fully resolved policy, if both skill and level are known.
+    pub fn tool_policy(&self) -> Option<ToolPolicy> {
+        let skill = self.skill.as_deref().and_then(ToolSkill::parse)?;
+        Some(ToolPolicy {
+            skill,
+            airlock_level: self.airlock_level?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyRuleTrace {
+    pub layer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    pub rule_index: usize,
+    pub matched: bool,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyExplanation {
+    pub tool: String,
+    pub decision: PolicyDecision,
+    pub layers: Vec<String>,
+    pub trace: Vec<PolicyRuleTrace>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AirlockPolicyEngine {
+    layers: Vec<PolicyLayer>,
+    workspace_root: Option<PathBuf>,
+}
+
+impl AirlockPolicyEngine {
+    /// Engine with only the built-in table.
+    #[cfg(test)]
+    pub fn builtin() -> Self {
+        Self::default()
+    }
+
+    /// Loads the user layer from the app data dir and, when a workspace root is
+    /// known, the workspace layer from `.rainy-mate/`.
+    pub fn load(workspace_root: Option<&Path>) -> Self {
+        let mut engine = Self {
+            workspace_root: workspace_root.map(Path::to_path_buf),
+            ..Self::default()
+        };
+        if let Some(dir) = Self::user_policy_dir() {
+            if let Some(layer) = Self::cached_layer(USER_LAYER, &dir) {
+                engine.layers.push(layer);
+            }
+        }
+        if let Some(root) = workspace_root {
+            let dir = root.join(WORKSPACE_POLICY_DIR);
+            if let Some(layer) = Self::cached_layer(WORKSPACE_LAYER, &dir) {
+                engine.layers.push(layer);
+            }
+        }
+        engine
+    }
+
+    /// Convenience for callers that only carry the workspace's allowed paths.
+    pub fn load_for_paths(allowed_paths: &[String]) -> Self {
+        Self::load(allowed_paths.first().map(Path::new))
+    }
+
+    #[cfg(test)]
+    pub fn with_layer(mut self, name: &str, file: PolicyFile) -> Self {
+        self.layers.push(PolicyLayer {
+            name: name.to_string(),
+            source: None,
+            rules: file.rules,
+            risk: file.risk,
+            load_error: None,
+            trusted: name != WORKSPACE_LAYER,
+        });
+        self
+    }
+
+    pub fn parse_policy(contents: &str, path: &Path) -> Result<PolicyFile, String> {
+        let is_yaml = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+        if is_yaml {
+            serde_yaml::from_str(contents).map_err(|e| format!("Invalid policy YAML: {}", e))
+        } else {
+            toml::from_str(contents).map_err(|e| format!("Invalid policy TOML: {}", e))
+        }
+    }
+
+    fn user_policy_dir() -> Option<PathBuf> {
+        dirs::data_dir().and_then(|dir| crate::services::app_identity::resolve_app_dir(dir).ok())
+    }
+
+    fn policy_file_in(dir: &Path) -> Option<PathBuf> {
+        POLICY_FILE_NAMES
+            .iter()
+            .map(|file| dir.join(file))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// `load_layer`, skipping the read and parse when the file is unchanged
+    /// since the last call.
+    fn cached_layer(name: &str, dir: &Path) -> Option<PolicyLayer> {
+        let path = Self::policy_file_in(dir)?;
+        let metadata = fs::metadata(&path).ok();
+        let modified = metadata.as_ref().and_then(|meta| meta.modified().ok());
+        let len = metadata.as_ref().map_or(0, |meta| meta.len());
+        let mut cache = layer_cache().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = cache.get(&path) {
+            if cached.modified == modified && cached.len == len && cached.layer.name == name {
+                return Some(cached.layer.clone());
+            }
+        }
+        let layer = Self::load_layer(name, dir)?;
+        cache.insert(
+            path,
+            CachedLayer {
+                modified,
+                len,
+                layer: layer.clone(),
+            },
+        );
+        Some(layer)
+    }
+
+    fn load_layer(name: &str, dir: &Path) -> Option<PolicyLayer> {
+        let path = Self::policy_file_in(dir)?;
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read policy file: {}", e))
+            .and_then(|contents| Self::parse_policy(&contents, &path));
+        let (rules, risk, load_error) = match parsed {
+            Ok(file) => (file.rules, file.risk, None),
+            Err(error) => {
+                tracing::warn!(
+                    "Airlock policy: {} layer at {} failed to load, denying all tools: {}",
+                    name,
+                    path.display(),
+                    error
+                );
+                (Vec::new(), RiskSettings::default(), Some(error))
+            }
+        };
+        Some(PolicyLayer {
+            name: name.to_string(),
+            source: Some(path),
+            rules,
+            risk,
+            load_error,
+            trusted: name != WORKSPACE_LAYER,
+        })
+    }
+
+    /// Risk classifier settings from every loaded layer. Trusted hosts lower
+    /// levels, so only trusted layers may declare them.
+    pub fn risk_settings(&self) -> RiskSettings {
+        let mut merged = RiskSettings::default();
+        for layer in &self.layers {
+            if layer.trusted {
+                merged
+                    .trusted_hosts
+                    .extend(layer.risk.trusted_hosts.iter().cloned());
+            }
+            merged
+                .blocked_hosts
+                .extend(layer.risk.blocked_hosts.iter().cloned());
+            merged
+                .protected_paths
+                .extend(layer.risk.protected_paths.iter().cloned());
+        }
+        merged
+    }
+
+    pub fn evaluate(&self, request: &PolicyRequest<'_>) -> PolicyDecision {
+        self.evaluate_with_trace(request, None)
+    }
+
+    pub fn explain(&self, request: &PolicyRequest<'_>) -> PolicyExplanation {
+        let mut trace = Vec::new();
+        let decision = self.evaluate_with_trace(request, Some(&mut trace));
+        let mut layers: Vec<String> = self
+            .layers
+            .iter()
+            .map(|layer| match layer.source.as_ref() {
+                Some(path) => format!("{} ({})", layer.name, path.display()),
+                None => layer.name.clone(),
+            })
+            .collect();
+        layers.push("builtin".to_string());
+        PolicyExplanation {
+            tool: request.tool.to_string(),
+            decision,
+            layers,
+            trace,
+        }
+    }
+
+    /// Tool names that are unconditionally denied for this workspace/agent,
+    /// i.e. deny rules that do not depend on call arguments.
+    pub fn static_denied_tools(
+        &self,
+        candidates: &[&str],
+        workspace_id: Option<&str>,
+        agent_spec_id: Option<&str>,
+    ) -> Vec<String> {
+        candidates
+            .iter()
+            .filter(|tool| {
+                let request = PolicyRequest {
+                    tool,
+                    args: None,
+                    workspace_id,
+                    agent_spec_id,
+                };
+                // Argument matchers never match without args, so only
+                // unconditional rules can decide here.
+                self.layer_matches(&request, None)
+                    .iter()
+                    .any(|(layer, rule)| {
+                        layer.load_error.is_some()
+                            || rule.is_some_and(|(_, rule)| rule.effect == PolicyEffect::Deny)
+                    })
+            })
+            .map(|tool| tool.to_string())
+            .collect()
+    }
+
+    fn evaluate_with_trace(
+        &self,
+        request: &PolicyRequest<'_>,
+        trace: Option<&mut Vec<PolicyRuleTrace>>,
+    ) -> PolicyDecision {
+        let builtin = get_tool_policy(request.tool);
+        let builtin_skill = builtin.map(|policy| policy.skill.as_str().to_string());
+        let builtin_level = builtin.map(|policy| policy.airlock_level);
+        let matches = self.layer_matches(request, trace);
+
+        if let Some((layer, _)) = matches.iter().find(|(layer, _)| layer.load_error.is_some()) {
+            return PolicyDecision {
+                effect: PolicyEffect::Deny,
+                skill: builtin_skill,
+                airlock_level: builtin_level,
+                decided_by: Self::origin(layer, None, None),
+                reason: format!(
+                    "{} policy layer failed to load: {}",
+                    layer.name,
+                    layer.load_error.as_deref().unwrap_or("unknown error")
+                ),
+                level_pinned: false,
+            };
+        }
+
+        let denied = matches.iter().find_map(|(layer, rule)| {
+            rule.filter(|(_, rule)| rule.effect == PolicyEffect::Deny)
+                .map(|(index, rule)| (*layer, index, rule))
+        });
+        if let Some((layer, index, rule)) = denied {
+            return PolicyDecision {
+                effect: PolicyEffect::Deny,
+                skill: builtin_skill,
+                airlock_level: builtin_level,
+                decided_by: Self::origin(layer, rule.id.clone(), Some(index)),
+                reason: format!("Denied by {} rule {}", layer.name, Self::label(index, rule)),
+                level_pinned: false,
+            };
+        }
+
+        let mut skill = builtin_skill;
+        let mut airlock_level = builtin_level;
+        let mut level_pinned = false;
+        let mut decided = None;
+        for (layer, rule) in &matches {
+            let Some((index, rule)) = *rule else {
+                continue;
+            };
+            let level = rule.level.map(level_from_u8);
+            if layer.trusted {
+                if rule.skill.is_some() {
+                    skill = rule.skill.clone();
+                }
+                if level.is_some() {
+                    airlock_level = level;
+                    level_pinned = true;
+                }
+                decided = Some((*layer, index, rule, "Allowed"));
+            } else if level.is_some() && airlock_level < level {
+                airlock_level = level;
+                level_pinned = true;
+                decided = Some((*layer, index, rule, "Level raised"));
+            }
+        }
+
+        match decided {
+            Some((layer, index, rule, verb)) => PolicyDecision {
+                effect: PolicyEffect::Allow,
+                skill,
+                airlock_level,
+                decided_by: Self::origin(layer, rule.id.clone(), Some(index)),
+                reason: format!(
+                    "{} by {} rule {}",
+                    verb,
+                    layer.name,
+                    Self::label(index, rule)
+                ),
+                level_pinned,
+            },
+            None => PolicyDecision {
+                effect: PolicyEffect::Allow,
+                skill,
+                airlock_level,
+                decided_by: PolicyOrigin {
+                    layer: "builtin".to_string(),
+                    source: None,
+                    rule_id: None,
+                    rule_index: None,
+                },
+                reason: if builtin.is_some() {
+                    "Built-in tool policy".to_string()
+                } else {
+                    "No policy entry for tool".to_string()
+                },
+                level_pinned: false,
+            },
+        }
+    }
+
+    /// The first matching rule of every layer, in layer order. A layer that
+    /// failed to load is reported with no rule and fails closed.
+    #[allow(clippy::type_complexity)]
+    fn layer_matches<'s>(
+        &'s self,
+        request: &PolicyRequest<'_>,
+        mut trace: Option<&mut Vec<PolicyRuleTrace>>,
+    ) -> Vec<(&'s PolicyLayer, Option<(usize, &'s PolicyRule)>)> {
+        let mut matches = Vec::new();
+        for layer in &self.layers {
+            if layer.load_error.is_some() {
+                matches.push((layer, None));
+                continue;
+            }
+            let mut matched = None;
+            for (index, rule) in layer.rules.iter().enumerate() {
+                let mismatch = self.rule_mismatch(rule, request);
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.push(PolicyRuleTrace {
+                        layer: layer.name.clone(),
+                        rule_id: rule.id.clone(),
+                        rule_index: index,
+                        matched: mismatch.is_none(),
+                        detail: mismatch.clone().unwrap_or_else(|| "matched".to_string()),
+                    });
+                }
+                if mismatch.is_none() {
+                    matched = Some((index, rule));
+                    break;
+                }
+            }
+            matches.push((layer, matched));
+        }
+        matches
+    }
+
+    fn label(index: usize, rule: &PolicyRule) -> String {
+        rule.id.clone().unwrap_or_else(|| format!("#{}", index))
+    }
+
+    fn origin(layer: &PolicyLayer, rule_id: Option<String>, index: Option<usize>) -> PolicyOrigin {
+        PolicyOrigin {
+            layer: layer.name.clone(),
+            source: layer.source.as_ref().map(|path| path.display().to_string()),
+            rule_id,
+            rule_index: index,
+        }
+    }
+
+    /// `None` when the rule matches, otherwise a short reason for the trace.
+    fn rule_mismatch(&self, rule: &PolicyRule, request: &PolicyRequest<'_>) -> Option<String> {
+        if !rule.tools.is_empty()
+            && !rule
+                .tools
+                .iter()
+                .any(|pattern| glob_match(pattern, request.tool))
+        {
+            return Some("tool did not match".to_string());
+        }
+        if !rule.workspaces.is_empty()
+            && !request.workspace_id.is_some_and(|id| {
+                rule.workspaces
+                    .iter()
+                    .any(|pattern| glob_match(pattern, id))
+            })
+        {
+            return Some("workspace did not match".to_string());
+        }
+        if !rule.agents.is_empty()
+            && !request
+                .agent_spec_id
+                .is_some_and(|id| rule.agents.iter().any(|pattern| glob_match(pattern, id)))
+        {
+            return Some("agent did not match".to_string());
+        }
+
+        let args = request.args;
+        if !rule.paths.is_empty() {
+            let paths = collect_path_args(args, self.workspace_root.as_deref());
+            let matched = paths
+                .iter()
+                .any(|path| rule.paths.iter().any(|pattern| glob_match(pattern, path)));
+            if !matched {
+                return Some("no path argument matched".to_string());
+            }
+        }
+        if !rule.hosts.is_empty() {
+            let host = args
+                .and_then(|value| value.get("url"))
+                .and_then(|value| value.as_str())
+                .and_then(|raw| url::Url::parse(raw).ok())
+                .and_then(|parsed| parsed.host_str().map(|host| host.to_ascii_lowercase()));
+            let matched = host.as_deref().is_some_and(|host| {
+                rule.hosts
+                    .iter()
+                    .any(|pattern| glob_match(&pattern.to_ascii_lowercase(), host))
+            });
+            if !matched {
+                return Some("url host did not match".to_string());
+            }
+        }
+        if !rule.commands.is_empty() {
+            let command = args.and_then(command_name);
+            let matched = command.as_deref().is_some_and(|command| {
+                rule.commands
+                    .iter()
+                    .any(|pattern| glob_match(pattern, command))
+            });
+            if !matched {
+                return Some("command did not match".to_string());
+            }
+        }
+        for (key, pattern) in &rule.args {
+            let value = args
+                .and_then(|value| value.get(key))
+                .map(|value| match value {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                });
+            if !value
+                .as_deref()
+                .is_some_and(|value| glob_match(pattern, value))
+            {
+                return Some(format!("argument '{}' did not match", key));
+            }
+        }
+        None
+    }
+}
+
+fn level_from_u8(level: u8) -> AirlockLevel {
+    match level.clamp(0, 2) {
+        0 => AirlockLevel::Safe,
+        1 => AirlockLevel::Sensitive,
+        _ => AirlockLevel::Dangerous,
+    }
+}
+
+/// Whether `path` is one of the workspace policy files (or the directory that
+/// holds them). Agent file tools refuse to modify these so a run cannot
+/// rewrite the rules that gate it.
+pub(crate) fn is_workspace_policy_path(path: &Path) -> bool {
+    let is_policy_dir = |dir: &Path| {
+        dir.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.eq_ignore_ascii_case(WORKSPACE_POLICY_DIR))
+    };
+    if is_policy_dir(path) {
+        return true;
+    }
+    let is_policy_file = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            POLICY_FILE_NAMES
+                .iter()
+                .any(|policy| name.eq_ignore_ascii_case(policy))
+        });
+    is_policy_file && path.parent().is_some_and(is_policy_dir)
+}
+
+/// Path-like arguments, with relative paths resolved against the workspace
+/// root so absolute globs still apply.
+fn collect_path_args(args: Option<&serde_json::Value>, root: Option<&Path>) -> Vec<String> {
+    let Some(args) = args else {
+        return Vec::new();
+    };
+    let mut raw = Vec::new();
+    for key in PATH_ARG_KEYS {
+        match args.get(*key) {
+            Some(serde_json::Value::String(path)) => raw.push(path.as_str()),
+            Some(serde_json::Value::Array(items)) => {
+                raw.extend(items.iter().filter_map(|item| item.as_str()))
+            }
+            _ => {}
+        }
+    }
+    raw.into_iter()
+        .map(|path| match root {
+            Some(root) if Path::new(path).is_relative() => root.join(path).display().to_string(),
+            _ => path.to_string(),
+        })
+        .collect()
+}
+
+/// Binary name of a shell call: the `command` argument, stripped to its file
+/// name and first token (`/usr/bin/git status` -> `git`).
+pub(crate) fn command_name(args: &serde_json::Value) -> Option<String> {
+    let raw = args.get("command")?.as_str()?.trim();
+    let first = raw.split_whitespace().next()?;
+    Path::new(first)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+}
+
+/// Glob matching with `*` (any run except `/`), `**` (any run) and `?`.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    fn inner(pattern: &[u8], text: &[u8]) -> bool {
+        match pattern.first() {
+            None => text.is_empty(),
+            Some(b'*') if pattern.get(1) == Some(&b'*') => {
+                let mut rest = &pattern[2..];
+                if rest.first() == Some(&b'/') {
+                    // `**/` also matches zero directories.
+                    if inner(&rest[1..], text) {
+                        return true;
+                    }
+                    rest = &pattern[2..];
+                }
+                (0..=text.len()).any(|skip| inner(rest, &text[skip..]))
+            }
+            Some(b'*') => {
+                let rest = &pattern[1..];
+                for skip in 0..=text.len() {
+                    if inner(rest, &text[skip..]) {
+                        return true;
+                    }
+                    if text.get(skip) == Some(&b'/') {
+                        break;
+                    }
+                }
+                false
+            }
+            Some(b'?') => !text.is_empty() && text[0] != b'/' && inner(&pattern[1..], &text[1..]),
+            Some(expected) => text.first() == Some(expected) && inner(&pattern[1..], &text[1..]),
+        }
+    }
+    inner(pattern.as_bytes(), text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::neural::{QueuedCommand, RainyPayload};
+    use crate::services::airlock_risk::AirlockRiskClassifier;
+    use serde_json::json;
+
+    fn engine(toml_text: &str) -> AirlockPolicyEngine {
+        let file = AirlockPolicyEngine::parse_policy(toml_text, Path::new("policy.toml"))
+            .expect("valid policy");
+        AirlockPolicyEngine::builtin().with_layer("user", file)
+    }
+
+    #[test]
+    fn glob_supports_single_and_double_star() {
+        assert!(glob_match("**/Cargo.lock", "/repo/Cargo.lock"));
+        assert!(glob_match("**/Cargo.lock", "Cargo.lock"));
+        assert!(glob_match("/repo/*.rs", "/repo/main.rs"));
+        assert!(!glob_match("/repo/*.rs", "/repo/src/main.rs"));
+        assert!(glob_match("git_*", "git_status"));
+        assert!(glob_match("*.github.com", "api.github.com"));
+    }
+
+    #[test]
+    fn builtin_layer_is_the_default() {
+        let decision = AirlockPolicyEngine::builtin().evaluate(&PolicyRequest {
+            tool: "write_file",
+            ..Default::default()
+        });
+        assert_eq!(decision.effect, PolicyEffect::Allow);
+        assert_eq!(decision.airlock_level, Some(AirlockLevel::Sensitive));
+        assert_eq!(decision.decided_by.layer, "builtin");
+    }
+
+    #[test]
+    fn path_rule_denies_matching_writes_only() {
+        let engine = engine(
+            r#"
+            [[rules]]
+            id = "lockfiles"
+            tools = ["write_file"]
+            paths = ["**/Cargo.lock"]
+            effect = "deny"
+            "#,
+        );
+        let lock_args = json!({"path": "/repo/Cargo.lock"});
+        let denied = engine.evaluate(&PolicyRequest {
+            tool: "write_file",
+            args: Some(&lock_args),
+            ..Default::default()
+        });
+        assert!(denied.is_denied());
+        assert_eq!(denied.decided_by.rule_id.as_deref(), Some("lockfiles"));
+
+        let src_args = json!({"path": "/repo/src/main.rs"});
+        let allowed = engine.evaluate(&PolicyRequest {
+            tool: "write_file",
+            args: Some(&src_args),
+            ..Default::default()
+        });
+        assert!(!allowed.is_denied());
+        assert_eq!(allowed.decided_by.layer, "builtin");
+    }
+
+    #[test]
+    fn command_rule_overrides_level() {
+        let engine = engine(
+            r#"
+            [[rules]]
+            id = "git"
+            tools = ["execute_command"]
+            commands = ["git"]
+            level = 1
+            "#,
+        );
+        let args = json!({"command": "git", "args": ["status"]});
+        let decision = engine.evaluate(&PolicyRequest {
+            tool: "execute_command",
+            args: Some(&args),
+            ..Default::default()
+        });
+        assert_eq!(decision.airlock_level, Some(AirlockLevel::Sensitive));
+        assert_eq!(
+            decision.tool_policy().map(|policy| policy.skill),
+            Some(ToolSkill::Shell)
+        );
+    }
+
+    #[test]
+    fn workspace_and_agent_scoping() {
+        let engine = engine(
+            r#"
+            [[rules]]
+            tools = ["http_post_json"]
+            workspaces = ["ws-prod"]
+            agents = ["release-*"]
+            effect = "deny"
+            "#,
+        );
+        let request = |workspace: &'static str, agent: &'static str| PolicyRequest {
+            tool: "http_post_json",
+            args: None,
+            workspace_id: Some(workspace),
+            agent_spec_id: Some(agent),
+        };
+        assert!(engine
+            .evaluate(&request("ws-prod", "release-bot"))
+            .is_denied());
+        assert!(!engine
+            .evaluate(&request("ws-dev", "release-bot"))
+            .is_denied());
+        assert!(!engine.evaluate(&request("ws-prod", "helper")).is_denied());
+        assert_eq!(
+            engine.static_denied_tools(
+                &["http_post_json", "read_file"],
+                Some("ws-prod"),
+                Some("release-bot")
+            ),
+            vec!["http_post_json".to_string()]
+        );
+    }
+
+    #[test]
+    fn rule_can_define_policy_for_unknown_tool() {
+        let engine = engine(
+            r#"
+            [[rules]]
+            tools = ["custom_tool"]
+            skill = "web"
+            level = 0
+            "#,
+        );
+        let decision = engine.evaluate(&PolicyRequest {
+            tool: "custom_tool",
+            ..Default::default()
+        });
+        let policy = decision.tool_policy().expect("rule defines policy");
+        assert_eq!(policy.skill, ToolSkill::Web);
+        assert_eq!(policy.airlock_level, AirlockLevel::Safe);
+    }
+
+    #[test]
+    fn yaml_policies_and_host_matching() {
+        let file = AirlockPolicyEngine::parse_policy(
+            "rules:\n  - id: internal\n    tools: [http_post_json]\n    hosts: ['*.corp.example']\n    level: 1\n",
+            Path::new("policy.yaml"),
+        )
+        .expect("valid yaml");
+        let engine = AirlockPolicyEngine::builtin().with_layer("user", file);
+        let args = json!({"url": "https://api.corp.example/v1"});
+        let explanation = engine.explain(&PolicyRequest {
+            tool: "http_post_json",
+            args: Some(&args),
+            ..Default::default()
+        });
+        assert_eq!(
+            explanation.decision.airlock_level,
+            Some(AirlockLevel::Sensitive)
+        );
+        assert_eq!(explanation.trace.len(), 1);
+        assert!(explanation.trace[0].matched);
+    }
+
+    #[test]
+    fn workspace_layer_may_only_deny_or_raise() {
+        let user = AirlockPolicyEngine::parse_policy(
+            r#"
+            [[rules]]
+            id = "trusted-post"
+            tools = ["http_post_json"]
+            level = 1
+            "#,
+            Path::new("policy.toml"),
+        )
+        .expect("valid user policy");
+        let workspace = AirlockPolicyEngine::parse_policy(
+            r#"
+            [[rules]]
+            id = "lower-shell"
+            tools = ["execute_command", "delete_file"]
+            level = 0
+            skill = "memory"
+
+            [[rules]]
+            id = "env-reads"
+            tools = ["read_file"]
+            paths = ["**/.env"]
+            level = 2
+
+            [[rules]]
+            id = "no-posts"
+            tools = ["http_post_json"]
+            effect = "deny"
+            "#,
+            Path::new("policy.toml"),
+        )
+        .expect("valid workspace policy");
+        let engine = AirlockPolicyEngine::builtin()
+            .with_layer("user", user)
+            .with_layer("workspace", workspace);
+        let decide = |tool: &'static str, args: Option<&serde_json::Value>| {
+            engine.evaluate(&PolicyRequest {
+                tool,
+                args,
+                ..Default::default()
+            })
+        };
+
+        for tool in ["execute_command", "delete_file"] {
+            let decision = decide(tool, None);
+            assert_eq!(
+                decision.airlock_level,
+                get_tool_policy(tool).map(|p| p.airlock_level)
+            );
+            assert_eq!(
+                decision.skill,
+                get_tool_policy(tool).map(|p| p.skill.as_str().to_string())
+            );
+            assert!(!decision.level_pinned);
+        }
+
+        let env = json!({"path": "/repo/.env"});
+        let raised = decide("read_file", Some(&env));
+        assert_eq!(raised.airlock_level, Some(AirlockLevel::Dangerous));
+        assert!(raised.level_pinned);
+        assert_eq!(raised.decided_by.rule_id.as_deref(), Some("env-reads"));
+
+        let denied = decide("http_post_json", None);
+        assert!(denied.is_denied());
+        assert_eq!(denied.decided_by.rule_id.as_deref(), Some("no-posts"));
+    }
+
+    #[test]
+    fn workspace_trusted_hosts_do_not_lower_levels() {
+        let workspace = AirlockPolicyEngine::parse_policy(
+            r#"
+            [risk]
+            trusted_hosts = ["exfil.example"]
+            blocked_hosts = ["*.pastebin.com"]
+            protected_paths = ["**/secrets/**"]
+            "#,
+            Path::new("policy.toml"),
+        )
+        .expect("valid workspace policy");
+        let engine = AirlockPolicyEngine::builtin().with_layer("workspace", workspace);
+        let settings = engine.risk_settings();
+        assert!(settings.trusted_hosts.is_empty());
+        assert_eq!(settings.blocked_hosts, vec!["*.pastebin.com"]);
+        assert_eq!(settings.protected_paths, vec!["**/secrets/**"]);
+
+        let command = QueuedCommand {
+            id: "cmd-exfil".to_string(),
+            workspace_id: Some("ws-1".to_string()),
+            desktop_node_id: None,
+            intent: "web.http_post_json".to_string(),
+            payload: RainyPayload {
+                allowed_paths: vec!["/repo".to_string()],
+                ..Default::default()
+            },
+            priority: Default::default(),
+            status: Default::default(),
+            airlock_level: AirlockLevel::Dangerous,
+            approval_timeout_secs: None,
+            approved_by: None,
+            result: None,
+            created_at: None,
+            started_at: None,
+            completed_at: None,
+            schema_version: None,
+        };
+        let params = json!({"url": "https://exfil.example/upload"});
+        let assessment = AirlockRiskClassifier::new(&settings, &command).assess(
+            "http_post_json",
+            Some(&params),
+            AirlockLevel::Dangerous,
+            &Default::default(),
+        );
+        assert_eq!(assessment.level, AirlockLevel::Dangerous);
+    }
+
+    #[test]
+    fn workspace_policy_paths_are_recognised() {
+        assert!(is_workspace_policy_path(Path::new(
+            "/repo/.rainy-mate/policy.toml"
+        )));
+        assert!(is_workspace_policy_path(Path::new(
+            "/repo/.rainy-mate/Policy.YML"
+        )));
+        assert!(is_workspace_policy_path(Path::new("/repo/.rainy-mate")));
+        assert!(!is_workspace_policy_path(Path::new(
+            "/repo/.rainy-mate/MEMORY.md"
+        )));
+        assert!(!is_workspace_policy_path(Path::new("/repo/policy.toml")));
+    }
+
+    #[test]
+    fn cached_layer_reloads_when_the_file_changes() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let dir = temp.path().join(WORKSPACE_POLICY_DIR);
+        fs::create_dir_all(&dir).expect("policy dir");
+        let path = dir.join("policy.toml");
+        fs::write(&path, "[[rules]]\ntools = [\"read_file\"]\n").expect("write policy");
+        let first = AirlockPolicyEngine::cached_layer("workspace", &dir).expect("layer");
+        assert_eq!(first.rules.len(), 1);
+        assert!(!first.trusted);
+
+        fs::write(
+            &path,
+            "[[rules]]\ntools = [\"read_file\"]\n\n[[rules]]\ntools = [\"write_file\"]\n",
+        )
+        .expect("rewrite policy");
+        let second = AirlockPolicyEngine::cached_layer("workspace", &dir).expect("layer");
+        assert_eq!(second.rules.len(), 2);
+    }
+
+    #[test]
+    fn broken_layer_fails_closed() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let dir = temp.path().join(WORKSPACE_POLICY_DIR);
+        fs::create_dir_all(&dir).expect("policy dir");
+        fs::write(dir.join("policy.toml"), "rules = [").expect("write policy");
+        let engine = AirlockPolicyEngine {
+            layers: vec![AirlockPolicyEngine::load_layer("workspace", &dir).expect("layer")],
+            workspace_root: Some(temp.path().to_path_buf()),
+        };
+        let decision = engine.evaluate(&PolicyRequest {
+            tool: "read_file",
+            ..Default::default()
+        });
+        assert!(decision.is_denied());
+        assert!(decision.reason.contains("failed to load"));
+    }
+}
@@ -0,0 +1,838 @@
+//! Argument-aware Airlock risk scoring.
+//!
+//! The policy table assigns one level per tool, so `git status` and
+//! `rm -rf` would share `execute_command`'s prompt. The classifier looks at
+//! the call arguments and moves the baseline level up or down:
+//!
+//! - Filesystem writes: target inside/outside the workspace, protected files
+//!   (lockfiles, secrets, `.git/`), overwrite vs. create, git-tracked or not
+//! - Shell: command name, read-only git subcommands, destructive flags, and
+//!   options that write files or run programs (`--output`, `-c`, ...)
+//! - Network: host allow/block lists (spec scopes + policy `[risk]` table)
+//!   and private network targets
+//!
+//! Every adjustment records a short reason that is shown to the user in the
+//! approval prompt.
+
+use crate::models::neural::{AirlockLevel, QueuedCommand};
+use crate::services::airlock_policy::{glob_match, RiskSettings};
+use crate::services::file_patch;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+const GIT_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+const PROTECTED_PATHS: &[&str] = &[
+    "**/Cargo.lock",
+    "**/package-lock.json",
+    "**/pnpm-lock.yaml",
+    "**/yarn.lock",
+    "**/bun.lockb",
+    "**/.env",
+    "**/.env.*",
+    "**/.git/**",
+    "**/.ssh/**",
+    "**/*.pem",
+    "**/*.key",
+];
+
+/// Shell commands that only read state when run with plain arguments.
+const READ_ONLY_COMMANDS: &[&str] = &["ls", "cat", "grep", "echo"];
+
+/// Options that let an otherwise read-only command write to an arbitrary
+/// path, change its config or run an external program. Short options also
+/// match their attached form (`-ofile`, `-ccore.pager=sh`).
+const ESCAPING_OPTIONS: &[&str] = &[
+    "--output",
+    "--ext-diff",
+    "--textconv",
+    "--exec",
+    "--exec-path",
+    "--upload-pack",
+    "--receive-pack",
+    "--open-files-in-pager",
+    "--config",
+    "--config-env",
+    "-c",
+    "-o",
+    "-O",
+];
+
+const READ_ONLY_GIT_SUBCOMMANDS: &[&str] = &[
+    "status",
+    "diff",
+    "log",
+    "show",
+    "blame",
+    "rev-parse",
+    "ls-files",
+    "describe",
+    "shortlog",
+];
+
+const WRITE_TOOLS: &[&str] = &[
+    "write_file",
+    "create_file",
+    "append_file",
+    "edit_file",
+    "mkdir",
+];
+const DESTRUCTIVE_FILE_TOOLS: &[&str] = &["delete_file", "move_file"];
+const NETWORK_TOOLS: &[&str] = &[
+    "http_get_json",
+    "http_get_text",
+    "http_post_json",
+    "read_web_page",
+    "browse_url",
+    "navigate",
+    "open_new_tab",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskAssessment {
+    pub level: AirlockLevel,
+    pub reasons: Vec<String>,
+}
+
+impl RiskAssessment {
+    fn new(level: AirlockLevel) -> Self {
+        Self {
+            level,
+            reasons: Vec::new(),
+        }
+    }
+
+    fn raise(&mut self, level: AirlockLevel, reason: impl Into<String>) {
+        if level > self.level {
+            self.level = level;
+        }
+        self.reasons.push(reason.into());
+    }
+
+    fn lower(&mut self, level: AirlockLevel, reason: impl Into<String>) {
+        if level < self.level {
+            self.level = level;
+        }
+        self.reasons.push(reason.into());
+    }
+
+    fn note(&mut self, reason: impl Into<String>) {
+        self.reasons.push(reason.into());
+    }
+
+    /// Reasons formatted for `ApprovalRequest.payload_summary`.
+    pub fn summary(&self) -> Option<String> {
+        if self.reasons.is_empty() {
+            None
+        } else {
+            Some(self.reasons.join("; "))
+        }
+    }
+}
+
+/// What the classifier knows about a write target on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FileFacts {
+    pub exists: bool,
+    pub tracked: bool,
+    /// Tracked with no uncommitted changes, so git can restore it exactly.
+    pub clean: bool,
+}
+
+/// Facts for each file target of a call, keyed by resolved path.
+pub type ProbedFiles = HashMap<PathBuf, FileFacts>;
+
+pub struct AirlockRiskClassifier<'a> {
+    settings: &'a RiskSettings,
+    allowed_paths: &'a [String],
+    allowed_domains: &'a [String],
+    blocked_domains: &'a [String],
+}
+
+impl<'a> AirlockRiskClassifier<'a> {
+    pub fn new(settings: &'a RiskSettings, command: &'a QueuedCommand) -> Self {
+        Self {
+            settings,
+            allowed_paths: &command.payload.allowed_paths,
+            allowed_domains: &command.payload.allowed_domains,
+            blocked_domains: &command.payload.blocked_domains,
+        }
+    }
+
+    /// Checks existence and git status for each file the call writes,
+    /// without blocking the runtime.
+    pub async fn probe(&self, tool: &str, params: Option<&serde_json::Value>) -> ProbedFiles {
+        let mut probed = ProbedFiles::new();
+        let (Some(root), Some(params)) = (self.workspace_root(), params) else {
+            return probed;
+        };
+        for target in self.file_targets(tool, params) {
+            let facts = probe_file(root, &target).await;
+            probed.insert(target, facts);
+        }
+        probed
+    }
+
+    /// Adjusts `baseline` for the given call using facts from [`Self::probe`].
+    /// Targets missing from `probed` count as new files.
+    pub fn assess(
+        &self,
+        tool: &str,
+        params: Option<&serde_json::Value>,
+        baseline: AirlockLevel,
+        probed: &ProbedFiles,
+    ) -> RiskAssessment {
+        self.assess_with(tool, params, baseline, |_, target| {
+            probed.get(target).copied().unwrap_or_default()
+        })
+    }
+
+    pub(crate) fn assess_with(
+        &self,
+        tool: &str,
+        params: Option<&serde_json::Value>,
+        baseline: AirlockLevel,
+        probe: impl Fn(&Path, &Path) -> FileFacts,
+    ) -> RiskAssessment {
+        let mut assessment = RiskAssessment::new(baseline);
+        let Some(params) = params else {
+            return assessment;
+        };
+
+        if WRITE_TOOLS.contains(&tool) || DESTRUCTIVE_FILE_TOOLS.contains(&tool) {
+            self.assess_file_write(tool, params, &mut assessment, probe);
+        } else if tool == "apply_patch" {
+            let patch = params.get("patch").and_then(|value| value.as_str());
+            for path in file_patch::patch_paths(patch.unwrap_or_default()) {
+                let target = serde_json::json!({ "path": path });
+                self.assess_file_write("edit_file", &target, &mut assessment, &probe);
+            }
+        } else if matches!(tool, "execute_command" | "start_process") {
+            self.assess_shell(params, &mut assessment);
+        } else if NETWORK_TOOLS.contains(&tool) {
+            self.assess_network(params, &mut assessment);
+        }
+        assessment
+    }
+
+    /// Resolved files `assess_file_write` looks at for this call.
+    fn file_targets(&self, tool: &str, params: &serde_json::Value) -> Vec<PathBuf> {
+        if tool == "apply_patch" {
+            let patch = params.get("patch").and_then(|value| value.as_str());
+            return file_patch::patch_paths(patch.unwrap_or_default())
+                .iter()
+                .map(|path| self.resolve(path))
+                .collect();
+        }
+        if tool == "mkdir"
+            || !(WRITE_TOOLS.contains(&tool) || DESTRUCTIVE_FILE_TOOLS.contains(&tool))
+        {
+            return Vec::new();
+        }
+        target_param(params)
+            .map(|raw| vec![self.resolve(raw)])
+            .unwrap_or_default()
+    }
+
+    fn workspace_root(&self) -> Option<&Path> {
+        self.allowed_paths.first().map(Path::new)
+    }
+
+    fn resolve(&self, raw: &str) -> PathBuf {
+        let path = Path::new(raw);
+        let joined = match self.workspace_root() {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path.to_path_buf(),
+        };
+        normalize_lexically(&joined)
+    }
+
+    fn is_inside_workspace(&self, path: &Path) -> bool {
+        self.allowed_paths
+            .iter()
+            .any(|allowed| path.starts_with(normalize_lexically(Path::new(allowed))))
+    }
+
+    fn is_protected(&self, path: &Path) -> bool {
+        let text = path.to_string_lossy();
+        PROTECTED_PATHS
+            .iter()
+            .copied()
+            .chain(self.settings.protected_paths.iter().map(String::as_str))
+            .any(|pattern| glob_match(pattern, &text))
+    }
+
+    fn assess_file_write(
+        &self,
+        tool: &str,
+        params: &serde_json::Value,
+        assessment: &mut RiskAssessment,
+        probe: impl Fn(&Path, &Path) -> FileFacts,
+    ) {
+        let Some(raw) = target_param(params) else {
+            return;
+        };
+        let target = self.resolve(raw);
+        let display = target.display();
+
+        if self.workspace_root().is_some() && !self.is_inside_workspace(&target) {
+            assessment.raise(
+                AirlockLevel::Dangerous,
+                format!("{} is outside the workspace", display),
+            );
+            return;
+        }
+        if self.is_protected(&target) {
+            assessment.raise(
+                AirlockLevel::Dangerous,
+                format!("{} is a protected file", display),
+            );
+            return;
+        }
+        if let Some(destination) = params.get("destination").and_then(|value| value.as_str()) {
+            let destination = self.resolve(destination);
+            if self.workspace_root().is_some() && !self.is_inside_workspace(&destination) {
+                assessment.raise(
+                    AirlockLevel::Dangerous,
+                    format!(
+                        "destination {} is outside the workspace",
+                        destination.display()
+                    ),
+                );
+                return;
+            }
+        }
+        if tool == "mkdir" {
+            return;
+        }
+
+        let facts = match self.workspace_root() {
+            Some(root) => probe(root, &target),
+            None => FileFacts::default(),
+        };
+        match (tool, facts.exists, facts.tracked) {
+            (_, false, _) if WRITE_TOOLS.contains(&tool) => {
+                assessment.note(format!("creates new file {}", display));
+            }
+            ("append_file", true, _) => {
+                assessment.note(format!("appends to existing file {}", display));
+            }
+            (_, true, true) if WRITE_TOOLS.contains(&tool) => {
+                assessment.note(format!(
+                    "overwrites git-tracked file {} (recoverable)",
+                    display
+                ));
+            }
+            (_, true, false) if WRITE_TOOLS.contains(&tool) => {
+                assessment.raise(
+                    AirlockLevel::Dangerous,
+                    format!("overwrites untracked file {} (not recoverable)", display),
+                );
+            }
+            (_, true, true) if facts.clean => {
+                assessment.lower(
+                    AirlockLevel::Sensitive,
+                    format!("{} is git-tracked and unmodified (recoverable)", display),
+                );
+            }
+            (_, true, true) => {
+                assessment.note(format!(
+                    "{} has uncommitted changes that would be lost",
+                    display
+                ));
+            }
+            (_, true, false) => {
+                assessment.note(format!("{} is not tracked by git", display));
+            }
+            _ => {}
+        }
+    }
+
+    fn assess_shell(&self, params: &serde_json::Value, assessment: &mut RiskAssessment) {
+        let Some(command) = crate::services::airlock_policy::command_name(params) else {
+            return;
+        };
+        let args: Vec<&str> = params
+            .get("args")
+            .and_then(|value| value.as_array())
+            .map(|items| items.iter().filter_map(|item| item.as_str()).collect())
+            .unwrap_or_default();
+
+        if let Some(reason) = destructive_shell_reason(&command, &args) {
+            assessment.raise(AirlockLevel::Dangerous, reason);
+            return;
+        }
+        if let Some(reason) = args.iter().find_map(|arg| self.escaping_argument(arg)) {
+            assessment.raise(AirlockLevel::Dangerous, reason);
+            return;
+        }
+
+        if command == "git" {
+            let subcommand = args.iter().find(|arg| !arg.starts_with('-'));
+            match subcommand {
+                Some(sub) if READ_ONLY_GIT_SUBCOMMANDS.contains(sub) => {
+                    assessment.lower(
+                        AirlockLevel::Safe,
+                        format!("read-only git {} inside the workspace", sub),
+                    );
+                }
+                Some(&"branch") if args.iter().all(|arg| !is_branch_mutation_flag(arg)) => {
+                    assessment.lower(
+                        AirlockLevel::Safe,
+                        "read-only git branch listing inside the workspace",
+                    );
+                }
+                _ => {}
+            }
+        } else if READ_ONLY_COMMANDS.contains(&command.as_str()) {
+            assessment.lower(
+                AirlockLevel::Safe,
+                format!("read-only {} inside the workspace", command),
+            );
+        }
+    }
+
+    /// Why `arg` may reach outside the workspace: an option that writes
+    /// files or runs programs, or a path (bare or after `=`) that escapes.
+    fn escaping_argument(&self, arg: &str) -> Option<String> {
+        if let Some(option) = escaping_option(arg) {
+            return Some(format!(
+                "option {} can write files or run programs outside the workspace",
+                option
+            ));
+        }
+        let embedded = arg.split_once('=').map(|(_, value)| value);
+        std::iter::once(arg)
+            .chain(embedded)
+            .any(|candidate| self.points_outside_workspace(candidate))
+            .then(|| format!("argument {} points outside the workspace", arg))
+    }
+
+    fn points_outside_workspace(&self, candidate: &str) -> bool {
+        if self.workspace_root().is_none() {
+            return false;
+        }
+        if candidate.starts_with('~') {
+            return true;
+        }
+        (Path::new(candidate).is_absolute() || candidate.contains(".."))
+            && !self.is_inside_workspace(&self.resolve(candidate))
+    }
+
+    fn assess_network(&self, params: &serde_json::Value, assessment: &mut RiskAssessment) {
+        let Some(parsed) = params
+            .get("url")
+            .and_then(|value| value.as_str())
+            .and_then(|raw| url::Url::parse(raw).ok())
+        else {
+            return;
+        };
+        let Some(host) = parsed.host_str().map(|host| host.to_ascii_lowercase()) else {
+            return;
+        };
+
+        let blocked = self
+            .blocked_domains
+            .iter()
+            .chain(self.settings.blocked_hosts.iter());
+        if host_listed(blocked, &host) {
+            assessment.raise(
+                AirlockLevel::Dangerous,
+                format!("host {} is on the block list", host),
+            );
+            return;
+        }
+        if is_private_host(&parsed) {
+            assessment.raise(
+                AirlockLevel::Sensitive,
+                format!("host {} is on a private network", host),
+            );
+            return;
+        }
+        let trusted = self
+            .allowed_domains
+            .iter()
+            .chain(self.settings.trusted_hosts.iter());
+        if host_listed(trusted, &host) {
+            let lowered = match assessment.level {
+                AirlockLevel::Dangerous => AirlockLevel::Sensitive,
+                _ => AirlockLevel::Safe,
+            };
+            assessment.lower(lowered, format!("host {} is trusted", host));
+        }
+    }
+}
+
+fn destructive_shell_reason(command: &str, args: &[&str]) -> Option<String> {
+    let has = |flag: &str| args.contains(&flag);
+    if command == "git" {
+        let subcommand = args.iter().find(|arg| !arg.starts_with('-')).copied();
+        let reason = match subcommand {
+            Some("push") if has("--force") || has("-f") || has("--force-with-lease") => {
+                "git push --force rewrites remote history"
+            }
+            Some("reset") if has("--hard") => "git reset --hard discards local changes",
+            Some("clean") => "git clean deletes untracked files",
+            Some("checkout") | Some("restore") if has("--") || has(".") => {
+                "git checkout/restore discards local changes"
+            }
+            Some("branch") if has("-D") => "git branch -D deletes an unmerged branch",
+            _ => return None,
+        };
+        return Some(reason.to_string());
+    }
+    if matches!(command, "npm" | "pnpm" | "bun") && args.first() == Some(&"publish") {
+        return Some(format!("{} publish uploads a package", command));
+    }
+    if command == "cargo" && args.first() == Some(&"publish") {
+        return Some("cargo publish uploads a crate".to_string());
+    }
+    None
+}
+
+fn escaping_option(arg: &str) -> Option<&'static str> {
+    ESCAPING_OPTIONS.iter().copied().find(|option| {
+        if option.starts_with("--") {
+            arg.strip_prefix(option)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('='))
+        } else {
+            !arg.starts_with("--") && arg.starts_with(option)
+        }
+    })
+}
+
+fn target_param(params: &serde_json::Value) -> Option<&str> {
+    params
+        .get("path")
+        .or_else(|| params.get("source"))
+        .and_then(|value| value.as_str())
+}
+
+fn is_branch_mutation_flag(arg: &str) -> bool {
+    matches!(
+        arg,
+        "-d" | "-D" | "-m" | "-M" | "-c" | "-C" | "--delete" | "--move" | "--copy" | "-f"
+    ) || (!arg.starts_with('-') && arg != "branch")
+}
+
+/// Exact or glob match, plus subdomains of a bare entry (`github.com`
+/// covers `api.github.com`).
+fn host_listed<'p>(mut patterns: impl Iterator<Item = &'p String>, host: &str) -> bool {
+    patterns.any(|pattern| {
+        let pattern = pattern.trim().to_ascii_lowercase();
+        glob_match(&pattern, host) || host.ends_with(&format!(".{}", pattern))
+    })
+}
+
+fn is_private_host(url: &url::Url) -> bool {
+    match url.host() {
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        Some(url::Host::Domain(domain)) => {
+            domain.eq_ignore_ascii_case("localhost") || domain.ends_with(".local")
+        }
+        None => false,
+    }
+}
+
+/// Collapses `.` and `..` without touching the filesystem, so targets that do
+/// not exist yet can still be compared against the workspace root.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// `git status --porcelain` prints nothing for a tracked, unmodified path,
+/// `??`/`!!` for untracked or ignored ones, and a change code otherwise. A
+/// failed or slow git counts as untracked.
+async fn probe_file(root: &Path, target: &Path) -> FileFacts {
+    if !tokio::fs::try_exists(target).await.unwrap_or(false) {
+        return FileFacts::default();
+    }
+    let mut git = tokio::process::Command::new("git");
+    git.arg("-C")
+        .arg(root)
+        .args([
+            "status",
+            "--porcelain",
+            "--ignored",
+            "--untracked-files=all",
+            "--",
+        ])
+        .arg(target)
+        .kill_on_drop(true);
+    let status = match tokio::time::timeout(GIT_PROBE_TIMEOUT, git.output()).await {
+        Ok(Ok(output)) if output.status.success() => output.stdout,
+        _ => {
+            return FileFacts {
+                exists: true,
+                ..FileFacts::default()
+            }
+        }
+    };
+    let untracked = status.starts_with(b"??") || status.starts_with(b"!!");
+    FileFacts {
+        exists: true,
+        tracked: !untracked,
+        clean: status.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::neural::RainyPayload;
+    use serde_json::json;
+
+    fn command() -> QueuedCommand {
+        QueuedCommand {
+            id: "cmd-risk".to_string(),
+            workspace_id: Some("ws-1".to_string()),
+            desktop_node_id: None,
+            intent: "tool.test".to_string(),
+            payload: RainyPayload {
+                allowed_paths: vec!["/repo".to_string()],
+                allowed_domains: vec!["github.com".to_string()],
+                blocked_domains: vec!["evil.example".to_string()],
+                ..Default::default()
+            },
+            priority: Default::default(),
+            status: Default::default(),
+            airlock_level: AirlockLevel::Safe,
+            approval_timeout_secs: None,
+            approved_by: None,
+            result: None,
+            created_at: None,
+            started_at: None,
+            completed_at: None,
+            schema_version: None,
+        }
+    }
+
+    fn assess(
+        tool: &str,
+        params: serde_json::Value,
+        baseline: AirlockLevel,
+        facts: FileFacts,
+    ) -> RiskAssessment {
+        let settings = RiskSettings::default();
+        let command = command();
+        AirlockRiskClassifier::new(&settings, &command).assess_with(
+            tool,
+            Some(&params),
+            baseline,
+            |_, _| facts,
+        )
+    }
+
+    #[test]
+    fn read_only_git_is_lowered_to_safe() {
+        let result = assess(
+            "execute_command",
+            json!({"command": "git", "args": ["status", "--short"]}),
+            AirlockLevel::Dangerous,
+            FileFacts::default(),
+        );
+        assert_eq!(result.level, AirlockLevel::Safe);
+        assert!(result.summary().unwrap().contains("git status"));
+    }
+
+    #[test]
+    fn destructive_git_stays_dangerous() {
+        let result = assess(
+            "execute_command",
+            json!({"command": "git", "args": ["reset", "--hard", "HEAD~1"]}),
+            AirlockLevel::Dangerous,
+            FileFacts::default(),
+        );
+        assert_eq!(result.level, AirlockLevel::Dangerous);
+        assert!(result.summary().unwrap().contains("reset --hard"));
+    }
+
+    #[test]
+    fn shell_arguments_outside_workspace_are_not_lowered() {
+        let result = assess(
+            "execute_command",
+            json!({"command": "cat", "args": ["/etc/passwd"]}),
+            AirlockLevel::Dangerous,
+            FileFacts::default(),
+        );
+        assert_eq!(result.level, AirlockLevel::Dangerous);
+    }
+
+    #[test]
+    fn protected_and_untracked_overwrites_are_raised() {
+        let lockfile = assess(
+            "write_file",
+            json!({"path": "Cargo.lock"}),
+            AirlockLevel::Sensitive,
+            FileFacts {
+                exists: true,
+                tracked: true,
+                clean: true,
+            },
+        );
+        assert_eq!(lockfile.level, AirlockLevel::Dangerous);
+
+        let untracked = assess(
+            "write_file",
+            json!({"path": "notes/todo.md"}),
+            AirlockLevel::Sensitive,
+            FileFacts {
+                exists: true,
+                tracked: false,
+                clean: false,
+            },
+        );
+        assert_eq!(untracked.level, AirlockLevel::Dangerous);
+
+        let created = assess(
+            "write_file",
+            json!({"path": "src/new.rs"}),
+            AirlockLevel::Sensitive,
+            FileFacts::default(),
+        );
+        assert_eq!(created.level, AirlockLevel::Sensitive);
+        assert!(created.summary().unwrap().contains("creates new file"));
+    }
+
+    #[test]
+    fn writes_escaping_the_workspace_are_dangerous() {
+        let result = assess(
+            "write_file",
+            json!({"path": "../outside.txt"}),
+            AirlockLevel::Sensitive,
+            FileFacts::default(),
+        );
+        assert_eq!(result.level, AirlockLevel::Dangerous);
+    }
+
+    #[test]
+    fn patches_are_assessed_per_target_file() {
+        let patch = "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n--- a/../outside.rs\n+++ b/../outside.rs\n@@ -1 +1 @@\n-a\n+b\n";
+        let result = assess(
+            "apply_patch",
+            json!({ "patch": patch }),
+            AirlockLevel::Sensitive,
+            FileFacts {
+                exists: true,
+                tracked: true,
+                clean: true,
+            },
+        );
+        assert_eq!(result.level, AirlockLevel::Dangerous);
+        assert!(result.summary().unwrap().contains("outside.rs"));
+    }
+
+    #[test]
+    fn deleting_tracked_file_is_lowered() {
+        let result = assess(
+            "delete_file",
+            json!({"path": "/repo/src/old.rs"}),
+            AirlockLevel::Dangerous,
+            FileFacts {
+                exists: true,
+                tracked: true,
+                clean: true,
+            },
+        );
+        assert_eq!(result.level, AirlockLevel::Sensitive);
+
+        let modified = assess(
+            "delete_file",
+            json!({"path": "/repo/src/old.rs"}),
+            AirlockLevel::Dangerous,
+            FileFacts {
+                exists: true,
+                tracked: true,
+                clean: false,
+            },
+        );
+        assert_eq!(modified.level, AirlockLevel::Dangerous);
+        assert!(modified.summary().unwrap().contains("uncommitted changes"));
+    }
+
+    #[test]
+    fn shell_options_that_write_or_execute_are_not_lowered() {
+        for args in [
+            json!(["diff", "--output=/home/u/.bashrc"]),
+            json!(["log", "--output", "notes.txt"]),
+            json!(["diff", "--ext-diff"]),
+            json!(["-c", "core.pager=sh -c id", "log"]),
+            json!(["log", "--format=%H", "--git-dir=/etc"]),
+        ] {
+            let result = assess(
+                "execute_command",
+                json!({"command": "git", "args": args}),
+                AirlockLevel::Dangerous,
+                FileFacts::default(),
+            );
+            assert_eq!(result.level, AirlockLevel::Dangerous, "{}", args);
+        }
+    }
+
+    #[tokio::test]
+    async fn probe_reports_uncommitted_changes() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .output()
+                .expect("git runs")
+        };
+        git(&["init", "-q"]);
+        std::fs::write(repo.path().join("a.txt"), "one").unwrap();
+        git(&["add", "a.txt"]);
+        git(&["commit", "-q", "-m", "init"]);
+        std::fs::write(repo.path().join("b.txt"), "new").unwrap();
+
+        let root = repo.path();
+        let clean = probe_file(root, &root.join("a.txt")).await;
+        assert!(clean.tracked && clean.clean);
+        let untracked = probe_file(root, &root.join("b.txt")).await;
+        assert!(untracked.exists && !untracked.tracked);
+        std::fs::write(root.join("a.txt"), "two").unwrap();
+        let modified = probe_file(root, &root.join("a.txt")).await;
+        assert!(modified.tracked && !modified.clean);
+    }
+
+    #[test]
+    fn host_lists_adjust_network_calls() {
+        let trusted = assess(
+            "http_post_json",
+            json!({"url": "https://api.github.com/repos"}),
+            AirlockLevel::Dangerous,
+            FileFacts::default(),
+        );
+        assert_eq!(trusted.level, AirlockLevel::Sensitive);
+
+        let blocked = assess(
+            "http_get_json",
+            json!({"url": "https://evil.example/x"}),
+            AirlockLevel::Safe,
+            FileFacts::default(),
+        );
+        assert_eq!(blocked.level, AirlockLevel::Dangerous);
+
+        let private = assess(
+            "http_get_text",
+            json!({"url": "http://127.0.0.1:8080/admin"}),
+            AirlockLevel::Safe,
+            FileFacts::default(),
+        );
+        assert_eq!(private.level, AirlockLevel::Sensitive);
+    }
+}
@@ -637,6 +637,75 @@ impl BeamRpcService {
         Ok(wallets)
     }
 
+    /// Replace the wallet master key and re-encrypt every stored wallet under it.
+    /// All records are decrypted and staged before anything is replaced; a failed
+    /// swap puts the original files back. Returns the number of wallets rotated.
+    pub fn rotate_master_key(&self) -> Result<usize, String> {
+        if !self.master_key_path.exists() {
+            return Ok(0);
+        }
+        let old_key = self.load_or_create_master_key()?;
+        let mut new_key = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut new_key);
+
+        let entries = std::fs::read_dir(&self.wallet_dir)
+            .map_err(|e| format!("Failed to list wallet dir: {}", e))?;
+        let mut staged = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map_or(true, |ext| ext != "enc") {
+                continue;
+            }
+            let original = std::fs::read(&path)
+                .map_err(|e| format!("Failed to read wallet file {:?}: {}", path, e))?;
+            let mut record: EncryptedWalletRecord = serde_json::from_slice(&original)
+                .map_err(|e| format!("Corrupt wallet file {:?}: {}", path, e))?;
+            let nonce =
+                hex::decode(&record.nonce_hex).map_err(|e| format!("Bad nonce hex: {}", e))?;
+            let ciphertext = hex::decode(&record.ciphertext_hex)
+                .map_err(|e| format!("Bad ciphertext hex: {}", e))?;
+            let private_key_bytes =
+                decrypt_private_key(&old_key, &record.address, &ciphertext, &nonce)?;
+            let (ciphertext, nonce) =
+                encrypt_private_key(&new_key, &record.address, &private_key_bytes)?;
+            record.nonce_hex = hex::encode(nonce);
+            record.ciphertext_hex = hex::encode(&ciphertext);
+            let json = serde_json::to_vec(&record)
+                .map_err(|e| format!("Failed to serialize wallet: {}", e))?;
+            staged.push((path, original, json));
+        }
+
+        let mut written = Vec::with_capacity(staged.len());
+        let mut result = Ok(());
+        for (path, original, json) in &staged {
+            match std::fs::write(path, json) {
+                Ok(()) => written.push((path, original)),
+                Err(e) => {
+                    result = Err(format!("Failed to write wallet file {:?}: {}", path, e));
+                    break;
+                }
+            }
+        }
+        if result.is_ok() {
+            result = crate::services::memory_vault::key_provider::write_private_file(
+                &self.master_key_path,
+                &new_key,
+            );
+        }
+        if let Err(e) = result {
+            for (path, original) in written {
+                let _ = std::fs::write(path, original);
+            }
+            return Err(e);
+        }
+
+        tracing::info!(
+            "BeamRpcService: rotated master key for {} wallet(s)",
+            staged.len()
+        );
+        Ok(staged.len())
+    }
+
     // ── JSON-RPC calls ───────────────────────────────────────────────────
 
     async fn rpc_call(
@@ -1086,6 +1155,17 @@ mod tests {
         assert_eq!(info.label, Some("myWallet".to_string()));
     }
 
+    #[test]
+    fn rotate_master_key_keeps_wallets_usable() {
+        let svc = make_service();
+        let wallet = svc.create_wallet(None).unwrap();
+        let before = svc.load_or_create_master_key().unwrap();
+
+        assert_eq!(svc.rotate_master_key().unwrap(), 1);
+        assert_ne!(svc.load_or_create_master_key().unwrap(), before);
+        assert!(svc.load_signing_key(&wallet.address).is_ok());
+    }
+
     #[test]
     fn master_key_persists_across_service_instances() {
         let tmp = tempfile::tempdir().expect("tmp dir");
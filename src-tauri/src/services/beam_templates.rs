@@ -353,6 +353,11 @@ impl BeamTemplateService {
                     tool_access_policy_hash: None,
                     connector_id: None,
                     user_id: None,
+                    agent_spec_id: None,
+                    run_id: None,
+                    chat_id: None,
+                    max_airlock_level: None,
+                    checkout_root: None,
                 },
                 priority: CommandPriority::High,
                 status: CommandStatus::Pending,
@@ -0,0 +1,661 @@
+//! Symbol-aware chunking for the code index.
+//!
+//! Files are parsed with the tree-sitter grammars for Rust, TypeScript/TSX
+//! (TSX also covers JavaScript), Python and Go, and cut at function, type and
+//! class boundaries so each chunk is one symbol with its doc comments,
+//! attributes and decorators. Members of `impl`/`trait`/`mod` blocks and
+//! classes are emitted on their own with a qualified name. Files with no
+//! recognised declarations fall back to fixed line windows.
+
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+/// Longer symbols are split into consecutive chunks of this many lines.
+const MAX_CHUNK_LINES: usize = 150;
+const FALLBACK_WINDOW_LINES: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    /// TypeScript or JavaScript with JSX.
+    Tsx,
+    Python,
+    Go,
+}
+
+impl Language {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::Tsx),
+            "py" | "pyi" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::TypeScript | Self::Tsx => "typescript",
+            Self::Python => "python",
+            Self::Go => "go",
+        }
+    }
+
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+
+    /// Comment and attribute nodes that belong to the declaration after them.
+    fn is_leading(self, kind: &str) -> bool {
+        match self {
+            Self::Rust => matches!(kind, "line_comment" | "block_comment" | "attribute_item"),
+            Self::TypeScript | Self::Tsx => matches!(kind, "comment" | "decorator"),
+            Self::Python | Self::Go => kind == "comment",
+        }
+    }
+
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            _ => ".",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeChunk {
+    /// Qualified name such as `Vault::put` or `Panel.render`; empty for
+    /// fallback windows.
+    pub symbol: String,
+    pub kind: &'static str,
+    /// 1-based, inclusive.
+    pub start_line: usize,
+    /// 1-based, inclusive.
+    pub end_line: usize,
+    pub content: String,
+}
+
+pub fn chunk_source(language: Language, source: &str) -> Vec<CodeChunk> {
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut spans = syntax_spans(language, source, &lines).unwrap_or_default();
+    if spans.is_empty() {
+        spans = window_spans(lines.len());
+    }
+    spans
+        .into_iter()
+        .flat_map(|span| split_span(span, &lines))
+        .collect()
+}
+
+/// A declaration's 0-based inclusive line range before it is cut to size.
+#[derive(Debug)]
+struct Span {
+    symbol: String,
+    kind: &'static str,
+    start: usize,
+    end: usize,
+}
+
+/// What a syntax node declares.
+struct Decl<'tree> {
+    name: String,
+    kind: &'static str,
+    /// For containers, the node whose children are chunked as members.
+    body: Option<Node<'tree>>,
+}
+
+fn split_span(span: Span, lines: &[&str]) -> Vec<CodeChunk> {
+    let end = span.end.min(lines.len().saturating_sub(1));
+    let mut chunks = Vec::new();
+    let mut start = span.start;
+    while start <= end {
+        let part_end = (start + MAX_CHUNK_LINES - 1).min(end);
+        let content = lines[start..=part_end].join("\n");
+        if !content.trim().is_empty() {
+            chunks.push(CodeChunk {
+                symbol: span.symbol.clone(),
+                kind: span.kind,
+                start_line: start + 1,
+                end_line: part_end + 1,
+                content,
+            });
+        }
+        start = part_end + 1;
+    }
+    chunks
+}
+
+fn window_spans(line_count: usize) -> Vec<Span> {
+    (0..line_count)
+        .step_by(FALLBACK_WINDOW_LINES)
+        .map(|start| Span {
+            symbol: String::new(),
+            kind: "block",
+            start,
+            end: (start + FALLBACK_WINDOW_LINES - 1).min(line_count - 1),
+        })
+        .collect()
+}
+
+fn qualify(container: Option<&str>, name: &str, language: Language) -> String {
+    match container {
+        Some(parent) => format!("{}{}{}", parent, language.separator(), name),
+        None => name.to_string(),
+    }
+}
+
+fn syntax_spans(language: Language, source: &str, lines: &[&str]) -> Option<Vec<Span>> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(source, None)?;
+    let mut spans = Vec::new();
+    collect_decls(language, source, lines, tree.root_node(), None, &mut spans);
+    Some(spans)
+}
+
+fn collect_decls(
+    language: Language,
+    source: &str,
+    lines: &[&str],
+    parent: Node,
+    container: Option<&str>,
+    spans: &mut Vec<Span>,
+) {
+    let mut cursor = parent.walk();
+    let children = parent.named_children(&mut cursor).collect::<Vec<_>>();
+    for (index, node) in children.iter().enumerate() {
+        let Some(decl) = declaration(language, *node, source) else {
+            continue;
+        };
+        let start = leading_start(language, lines, &children[..index], *node);
+        let end = last_row(*node);
+        let symbol = qualify(container, &decl.name, language);
+        match decl.body {
+            Some(body) => {
+                let mut members = Vec::new();
+                collect_decls(language, source, lines, body, Some(&symbol), &mut members);
+                push_container(spans, symbol, decl.kind, start, end, members);
+            }
+            None => {
+                let kind = if container.is_some() && decl.kind == "function" {
+                    "method"
+                } else {
+                    decl.kind
+                };
+                spans.push(Span {
+                    symbol,
+                    kind,
+                    start,
+                    end,
+                });
+            }
+        }
+    }
+}
+
+/// First row of `node` including the comments, attributes and decorators on
+/// the lines directly above it.
+fn leading_start(language: Language, lines: &[&str], previous: &[Node], node: Node) -> usize {
+    let mut start = node.start_position().row;
+    for sibling in previous.iter().rev() {
+        let position = sibling.start_position();
+        let own_line = lines
+            .get(position.row)
+            .and_then(|line| line.get(..position.column))
+            .is_some_and(|prefix| prefix.trim().is_empty());
+        if !language.is_leading(sibling.kind()) || !own_line || last_row(*sibling) + 1 < start {
+            break;
+        }
+        start = position.row;
+    }
+    start
+}
+
+/// Last row holding text of `node`; some nodes end at column 0 of the next
+/// line because they include the newline.
+fn last_row(node: Node) -> usize {
+    let end = node.end_position();
+    if end.column == 0 && end.row > node.start_position().row {
+        end.row - 1
+    } else {
+        end.row
+    }
+}
+
+/// A container with members becomes a header chunk (up to the first member)
+/// followed by the members; an empty one stays whole.
+fn push_container(
+    spans: &mut Vec<Span>,
+    symbol: String,
+    kind: &'static str,
+    start: usize,
+    end: usize,
+    members: Vec<Span>,
+) {
+    match members.first() {
+        Some(first) => {
+            if first.start > start {
+                spans.push(Span {
+                    symbol,
+                    kind,
+                    start,
+                    end: first.start - 1,
+                });
+            }
+            spans.extend(members);
+        }
+        None => spans.push(Span {
+            symbol,
+            kind,
+            start,
+            end,
+        }),
+    }
+}
+
+fn declaration<'tree>(language: Language, node: Node<'tree>, source: &str) -> Option<Decl<'tree>> {
+    match language {
+        Language::Rust => rust_decl(node, source),
+        Language::TypeScript | Language::Tsx => ts_decl(node, source),
+        Language::Python => python_decl(node, source),
+        Language::Go => go_decl(node, source),
+    }
+}
+
+fn decl<'tree>(name: &str, kind: &'static str, body: Option<Node<'tree>>) -> Option<Decl<'tree>> {
+    Some(Decl {
+        name: name.to_string(),
+        kind,
+        body,
+    })
+}
+
+fn text<'a>(node: Node, source: &'a str) -> &'a str {
+    node.utf8_text(source.as_bytes()).unwrap_or_default()
+}
+
+fn field_text<'a>(node: Node, field: &str, source: &'a str) -> Option<&'a str> {
+    node.child_by_field_name(field)
+        .map(|child| text(child, source))
+}
+
+/// Depth-first search for the first named descendant of one of `kinds`.
+fn first_descendant<'tree>(node: Node<'tree>, kinds: &[&str]) -> Option<Node<'tree>> {
+    let mut cursor = node.walk();
+    let children = node.named_children(&mut cursor).collect::<Vec<_>>();
+    children.into_iter().find_map(|child| {
+        if kinds.contains(&child.kind()) {
+            Some(child)
+        } else {
+            first_descendant(child, kinds)
+        }
+    })
+}
+
+fn rust_decl<'tree>(node: Node<'tree>, source: &str) -> Option<Decl<'tree>> {
+    let kind = match node.kind() {
+        "function_item" | "function_signature_item" => "function",
+        "struct_item" => "struct",
+        "enum_item" => "enum",
+        "union_item" => "union",
+        "type_item" => "type",
+        "const_item" | "static_item" => "const",
+        "macro_definition" => "macro",
+        "trait_item" => {
+            let name = field_text(node, "name", source)?;
+            return decl(name, "trait", node.child_by_field_name("body"));
+        }
+        // `mod name;` has no body and nothing to chunk.
+        "mod_item" => {
+            let body = node.child_by_field_name("body")?;
+            return decl(field_text(node, "name", source)?, "module", Some(body));
+        }
+        "impl_item" => {
+            let target = impl_target(field_text(node, "type", source)?);
+            return decl(&target, "impl", node.child_by_field_name("body"));
+        }
+        _ => return None,
+    };
+    decl(field_text(node, "name", source)?, kind, None)
+}
+
+/// The self type of an impl: `&'a dyn fmt::Display<T>` -> `Display`.
+fn impl_target(type_text: &str) -> String {
+    let target = type_text.trim_start_matches('&');
+    let target = match target.strip_prefix('\'') {
+        Some(rest) => rest
+            .split_once(char::is_whitespace)
+            .map_or(rest, |(_, rest)| rest),
+        None => target,
+    };
+    let target = target
+        .trim_start_matches("mut ")
+        .trim_start_matches("dyn ")
+        .trim();
+    let name = target.split('<').next().unwrap_or(target);
+    let name = name.rsplit("::").next().unwrap_or(name).trim();
+    if name.is_empty() {
+        "impl".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn ts_decl<'tree>(node: Node<'tree>, source: &str) -> Option<Decl<'tree>> {
+    let kind = match node.kind() {
+        // The chunk spans the whole `export`/`declare` statement.
+        "export_statement" => return ts_decl(node.child_by_field_name("declaration")?, source),
+        "ambient_declaration" => {
+            let mut cursor = node.walk();
+            let inner = node
+                .named_children(&mut cursor)
+                .find_map(|child| ts_decl(child, source));
+            return inner;
+        }
+        "function_declaration"
+        | "generator_function_declaration"
+        | "function_signature"
+        | "method_definition"
+        | "method_signature"
+        | "abstract_method_signature" => "function",
+        "class_declaration" | "abstract_class_declaration" => {
+            let name = field_text(node, "name", source)?;
+            return decl(name, "class", node.child_by_field_name("body"));
+        }
+        "interface_declaration" => "interface",
+        "type_alias_declaration" => "type",
+        "enum_declaration" => "enum",
+        "internal_module" | "module" => "module",
+        "public_field_definition" if is_function_value(node) => "function",
+        "lexical_declaration" | "variable_declaration" => {
+            let declarator = first_descendant(node, &["variable_declarator"])?;
+            let kind = if is_function_value(declarator) {
+                "function"
+            } else {
+                "const"
+            };
+            return decl(field_text(declarator, "name", source)?, kind, None);
+        }
+        _ => return None,
+    };
+    decl(field_text(node, "name", source)?, kind, None)
+}
+
+/// Whether a declarator or class field is initialised with a function.
+fn is_function_value(node: Node) -> bool {
+    node.child_by_field_name("value").is_some_and(|value| {
+        matches!(
+            value.kind(),
+            "arrow_function" | "function_expression" | "function" | "generator_function"
+        )
+    })
+}
+
+fn python_decl<'tree>(node: Node<'tree>, source: &str) -> Option<Decl<'tree>> {
+    match node.kind() {
+        // The chunk starts at the first decorator.
+        "decorated_definition" => python_decl(node.child_by_field_name("definition")?, source),
+        "function_definition" => decl(field_text(node, "name", source)?, "function", None),
+        "class_definition" => decl(
+            field_text(node, "name", source)?,
+            "class",
+            node.child_by_field_name("body"),
+        ),
+        _ => None,
+    }
+}
+
+fn go_decl<'tree>(node: Node<'tree>, source: &str) -> Option<Decl<'tree>> {
+    match node.kind() {
+        "function_declaration" => decl(field_text(node, "name", source)?, "function", None),
+        "method_declaration" => {
+            let name = field_text(node, "name", source)?;
+            let receiver = node
+                .child_by_field_name("receiver")
+                .and_then(|receiver| first_descendant(receiver, &["type_identifier"]));
+            match receiver {
+                Some(receiver) => decl(
+                    &format!("{}.{}", text(receiver, source), name),
+                    "method",
+                    None,
+                ),
+                None => decl(name, "method", None),
+            }
+        }
+        "type_declaration" => {
+            let spec = first_descendant(node, &["type_spec", "type_alias"])?;
+            let kind = match spec.child_by_field_name("type").map(|ty| ty.kind()) {
+                Some("struct_type") => "struct",
+                Some("interface_type") => "interface",
+                _ => "type",
+            };
+            decl(field_text(spec, "name", source)?, kind, None)
+        }
+        "const_declaration" | "var_declaration" => {
+            let spec = first_descendant(node, &["const_spec", "var_spec"])?;
+            decl(field_text(spec, "name", source)?, "const", None)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(chunks: &[CodeChunk]) -> Vec<(&str, &str, usize, usize)> {
+        chunks
+            .iter()
+            .map(|c| (c.symbol.as_str(), c.kind, c.start_line, c.end_line))
+            .collect()
+    }
+
+    #[test]
+    fn rust_items_and_impl_members_get_their_own_chunks() {
+        let source = r##"use std::fmt;
+
+/// A vault handle.
+#[derive(Debug)]
+pub struct Vault {
+    key: [u8; 32],
+}
+
+impl<T> fmt::Display for Wrapper<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", "}")
+    }
+}
+
+impl Vault {
+    /// Store an entry.
+    pub async fn put(&self, value: &str) -> Result<(), String> {
+        let brace = '{';
+        let raw = r#"{"#;
+        Ok(())
+    }
+
+    fn get(&self) {}
+}
+
+const LIMIT: usize = 5;
+"##;
+        let chunks = chunk_source(Language::Rust, source);
+        assert_eq!(
+            symbols(&chunks),
+            vec![
+                ("Vault", "struct", 3, 7),
+                ("Wrapper", "impl", 9, 9),
+                ("Wrapper::fmt", "method", 10, 12),
+                ("Vault", "impl", 15, 15),
+                ("Vault::put", "method", 16, 21),
+                ("Vault::get", "method", 23, 23),
+                ("LIMIT", "const", 26, 26),
+            ]
+        );
+        assert!(chunks[4].content.starts_with("    /// Store an entry."));
+    }
+
+    #[test]
+    fn typescript_classes_functions_and_arrow_consts() {
+        let source = "import x from 'y';
+
+export interface Props {
+  name: string;
+}
+
+export const Panel = ({ name }: Props) => {
+  return <div>{name}'s panel</div>;
+};
+
+export class Store {
+  private items = new Map<string, number>();
+
+  async load(id: string): Promise<void> {
+    if (id) {
+      return;
+    }
+  }
+
+  reset = () => {
+    this.items.clear();
+  };
+}
+
+export type Id = string
+";
+        let chunks = chunk_source(Language::Tsx, source);
+        assert_eq!(
+            symbols(&chunks),
+            vec![
+                ("Props", "interface", 3, 5),
+                ("Panel", "function", 7, 9),
+                ("Store", "class", 11, 13),
+                ("Store.load", "method", 14, 18),
+                ("Store.reset", "method", 20, 22),
+                ("Id", "type", 25, 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_and_go_declarations() {
+        let python = "import os
+
+@dataclass
+class Job:
+    name: str
+
+    def run(
+        self,
+    ) -> None:
+        print(self.name)
+
+
+def main():
+    Job('x').run()
+";
+        assert_eq!(
+            symbols(&chunk_source(Language::Python, python)),
+            vec![
+                ("Job", "class", 3, 6),
+                ("Job.run", "method", 7, 10),
+                ("main", "function", 13, 14),
+            ]
+        );
+
+        let go = "package main
+
+type Server struct {
+\taddr string
+}
+
+// Start listens.
+func (s *Server) Start() error {
+\treturn nil
+}
+
+func main() {
+\tfmt.Println(`{`)
+}
+";
+        assert_eq!(
+            symbols(&chunk_source(Language::Go, go)),
+            vec![
+                ("Server", "struct", 3, 5),
+                ("Server.Start", "method", 7, 10),
+                ("main", "function", 12, 14),
+            ]
+        );
+    }
+
+    #[test]
+    fn braces_in_strings_comments_and_templates_do_not_shift_boundaries() {
+        let rust = r###"mod tests;
+
+fn a() {
+    let raw = r#"}}"#;
+    /* } */
+    let quote = "\"}";
+}
+
+trait Store {
+    /// Loads one entry.
+    fn load(&self) -> String;
+}
+"###;
+        assert_eq!(
+            symbols(&chunk_source(Language::Rust, rust)),
+            vec![
+                ("a", "function", 3, 7),
+                ("Store", "trait", 9, 9),
+                ("Store::load", "method", 10, 11),
+            ]
+        );
+
+        let ts = "export default function render(x: number) {
+  const css = `${x} }}`; // }
+  return css;
+}
+
+export function after() {}
+";
+        assert_eq!(
+            symbols(&chunk_source(Language::TypeScript, ts)),
+            vec![("render", "function", 1, 4), ("after", "function", 6, 6)]
+        );
+    }
+
+    #[test]
+    fn long_symbols_are_split_and_plain_files_use_windows() {
+        let body = (0..200)
+            .map(|_| "    x += 1;")
+            .collect::<Vec<_>>()
+            .join("\n");
+        let source = format!("fn big() {{\n{}\n}}\n", body);
+        let chunks = chunk_source(Language::Rust, &source);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 150));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (151, 202));
+        assert!(chunks.iter().all(|c| c.symbol == "big"));
+
+        let script = (0..70)
+            .map(|i| format!("print({})", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let windows = chunk_source(Language::Python, &script);
+        assert_eq!(
+            symbols(&windows),
+            vec![("", "block", 1, 60), ("", "block", 61, 70)]
+        );
+    }
+}
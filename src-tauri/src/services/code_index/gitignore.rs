@@ -0,0 +1,184 @@
+//! Git ignore rules for the code index, backed by the `ignore` crate.
+//!
+//! Inside a git repository every `.gitignore` from the repository root down,
+//! `.git/info/exclude` and `core.excludesFile` apply with git's precedence.
+//! Outside one nothing is ignored by rule. Hidden directories and `.git`,
+//! `node_modules` and `target` are always skipped.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Walk, WalkBuilder};
+use std::ffi::OsStr;
+use std::path::{Component, Path};
+
+pub const ALWAYS_SKIPPED: &[&str] = &[".git", "node_modules", "target"];
+
+fn is_always_skipped(name: &OsStr) -> bool {
+    name.to_str()
+        .is_some_and(|name| ALWAYS_SKIPPED.contains(&name))
+}
+
+fn is_hidden(name: &OsStr) -> bool {
+    name.to_str()
+        .is_some_and(|name| name.starts_with('.') && name != "." && name != "..")
+}
+
+/// Walk of `start` yielding entries git would not ignore. Ignore files above
+/// `start` still apply, so walking a subdirectory agrees with a full walk.
+pub fn walk(start: &Path) -> Walk {
+    WalkBuilder::new(start)
+        .standard_filters(false)
+        .parents(true)
+        .git_ignore(true)
+        .git_exclude(true)
+        .git_global(true)
+        .follow_links(false)
+        .filter_entry(|entry| {
+            let name = entry.file_name();
+            let hidden_dir = entry.file_type().is_some_and(|kind| kind.is_dir()) && is_hidden(name);
+            !is_always_skipped(name) && !hidden_dir
+        })
+        .build()
+}
+
+/// Whether `path`, or a directory between `root` and it, is skipped or
+/// ignored by git. Paths outside `root` count as ignored.
+pub fn is_ignored(root: &Path, path: &Path, is_dir: bool) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return true;
+    };
+    let components = relative.components().collect::<Vec<_>>();
+    if components.iter().any(|component| match component {
+        Component::Normal(name) => is_always_skipped(name),
+        _ => false,
+    }) {
+        return true;
+    }
+
+    let matchers = matchers_for(path);
+    let mut current = root.to_path_buf();
+    for (idx, component) in components.iter().enumerate() {
+        current.push(component);
+        let component_is_dir = is_dir || idx + 1 < components.len();
+        // Most specific first: deeper `.gitignore` files, then the repository
+        // exclude file, then the global excludes file.
+        let matched = matchers
+            .iter()
+            .rev()
+            .filter(|matcher| current.starts_with(matcher.path()))
+            .map(|matcher| matcher.matched(&current, component_is_dir))
+            .find(|matched| !matched.is_none());
+        if matched.is_some_and(|matched| matched.is_ignore()) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Matchers that apply to `path`, least specific first; empty outside a
+/// git repository.
+fn matchers_for(path: &Path) -> Vec<Gitignore> {
+    let Some(repo) = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+    else {
+        return Vec::new();
+    };
+    let build = |dir: &Path, file: &Path| {
+        let mut builder = GitignoreBuilder::new(dir);
+        builder.add(file);
+        builder.build().unwrap_or_else(|_| Gitignore::empty())
+    };
+
+    let mut matchers = vec![
+        GitignoreBuilder::new(repo).build_global().0,
+        build(repo, &repo.join(".git/info/exclude")),
+    ];
+    let mut dirs = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(repo))
+        .collect::<Vec<_>>();
+    dirs.reverse();
+    matchers.extend(
+        dirs.into_iter()
+            .map(|dir| build(dir, &dir.join(".gitignore"))),
+    );
+    matchers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn walk_and_single_path_checks_follow_git_rules() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let root = temp.path();
+        for dir in [
+            ".git/info",
+            "src/gen",
+            "web/lib",
+            "dist",
+            "docs/x",
+            ".cache",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join(".git/info/exclude"), "scratch.rs\n").unwrap();
+        std::fs::write(
+            root.join(".gitignore"),
+            "# build output\n/dist\n*.log\n!keep.log\ndocs/**/*.tmp\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("web/.gitignore"), "generated.ts\n").unwrap();
+        for file in [
+            "src/main.rs",
+            "src/scratch.rs",
+            "src/app.log",
+            "src/keep.log",
+            "src/gen/.hidden.rs",
+            "web/lib/generated.ts",
+            "web/lib/app.ts",
+            "generated.ts",
+            "dist/bundle.js",
+            "docs/x/z.tmp",
+            ".cache/cached.rs",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut walked = walk(root)
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+            .map(|entry| entry.path().strip_prefix(root).unwrap().to_path_buf())
+            .filter(|path| path.file_name().is_some_and(|name| name != ".gitignore"))
+            .collect::<Vec<_>>();
+        walked.sort();
+        let expected = [
+            "generated.ts",
+            "src/gen/.hidden.rs",
+            "src/keep.log",
+            "src/main.rs",
+            "web/lib/app.ts",
+        ]
+        .map(PathBuf::from);
+        assert_eq!(walked, expected);
+
+        assert!(is_ignored(root, &root.join("src/scratch.rs"), false));
+        assert!(is_ignored(root, &root.join("src/app.log"), false));
+        assert!(!is_ignored(root, &root.join("src/keep.log"), false));
+        assert!(is_ignored(root, &root.join("dist/bundle.js"), false));
+        assert!(is_ignored(root, &root.join("docs/x/z.tmp"), false));
+        assert!(is_ignored(root, &root.join("web/lib/generated.ts"), false));
+        assert!(!is_ignored(root, &root.join("generated.ts"), false));
+        assert!(is_ignored(
+            root,
+            &root.join("node_modules/x/index.js"),
+            false
+        ));
+        assert!(!is_ignored(root, &root.join("src/main.rs"), false));
+        assert!(is_ignored(root, Path::new("/elsewhere/main.rs"), false));
+    }
+}
@@ -0,0 +1,747 @@
+//! Workspace code index.
+//!
+//! Walks a workspace's allowed paths (honouring `.gitignore`), cuts Rust,
+//! TypeScript/JavaScript, Python and Go sources into symbol-sized chunks with
+//! line spans (see `chunker`), and keeps them in an FTS5 index next to the app
+//! database for the `search_code` tool. Files are re-read only when their
+//! mtime or size changes, and once a workspace has been indexed a `notify`
+//! watcher keeps it current.
+
+pub mod chunker;
+pub mod gitignore;
+
+use chunker::{chunk_source, CodeChunk, Language};
+use gitignore::ALWAYS_SKIPPED;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::sync::{mpsc, Mutex};
+
+const MAX_FILES: usize = 20_000;
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Files read and written per transaction during a full pass.
+const INDEX_BATCH_FILES: usize = 200;
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(750);
+const PREVIEW_LINES: usize = 12;
+const MAX_QUERY_TERMS: usize = 24;
+const MAX_SEARCH_LIMIT: usize = 50;
+
+/// One ranked symbol hit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeSearchHit {
+    pub path: String,
+    pub symbol: String,
+    pub kind: String,
+    pub language: String,
+    pub start_line: i64,
+    pub end_line: i64,
+    /// Negated BM25; higher is better.
+    pub score: f64,
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeIndexStats {
+    pub files_indexed: usize,
+    pub files_unchanged: usize,
+    pub files_removed: usize,
+    pub chunks: usize,
+}
+
+#[derive(Clone)]
+struct SourceFile {
+    path: PathBuf,
+    modified: i64,
+    size: i64,
+}
+
+struct ParsedFile {
+    path: String,
+    modified: i64,
+    size: i64,
+    language: Language,
+    chunks: Vec<CodeChunk>,
+}
+
+struct WorkspaceWatch {
+    roots: Vec<PathBuf>,
+    _watcher: RecommendedWatcher,
+}
+
+pub struct CodeIndex {
+    pool: Pool<Sqlite>,
+    /// Live watchers for indexed workspaces, keyed by workspace ID.
+    watches: Mutex<HashMap<String, WorkspaceWatch>>,
+}
+
+impl CodeIndex {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self {
+            pool,
+            watches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn init(&self) -> Result<(), sqlx::Error> {
+        for statement in [
+            r#"
+            CREATE TABLE IF NOT EXISTS code_index_files (
+                workspace_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                modified_at INTEGER NOT NULL,
+                size INTEGER NOT NULL,
+                PRIMARY KEY (workspace_id, path)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS code_index_chunks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                workspace_id TEXT NOT NULL,
+                path TEXT NOT NULL,
+                language TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                terms TEXT NOT NULL,
+                content TEXT NOT NULL
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_code_index_chunks_path ON code_index_chunks(workspace_id, path)",
+            r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS code_index_fts USING fts5(
+                symbol, terms, content,
+                content='code_index_chunks', content_rowid='id'
+            )
+            "#,
+            r#"
+            CREATE TRIGGER IF NOT EXISTS code_index_chunks_ai AFTER INSERT ON code_index_chunks BEGIN
+                INSERT INTO code_index_fts(rowid, symbol, terms, content)
+                VALUES (new.id, new.symbol, new.terms, new.content);
+            END
+            "#,
+            r#"
+            CREATE TRIGGER IF NOT EXISTS code_index_chunks_ad AFTER DELETE ON code_index_chunks BEGIN
+                INSERT INTO code_index_fts(code_index_fts, rowid, symbol, terms, content)
+                VALUES ('delete', old.id, old.symbol, old.terms, old.content);
+            END
+            "#,
+        ] {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    /// Index `roots` once and keep watching them. A no-op when the workspace
+    /// is already watched with the same roots.
+    pub async fn ensure_indexed(
+        self: &Arc<Self>,
+        workspace_id: &str,
+        roots: Vec<PathBuf>,
+    ) -> Result<(), String> {
+        if self.is_watching(workspace_id, &roots).await {
+            return Ok(());
+        }
+        self.reindex(workspace_id, roots).await.map(|_| ())
+    }
+
+    /// Run an incremental pass now and make sure the roots are watched.
+    pub async fn reindex(
+        self: &Arc<Self>,
+        workspace_id: &str,
+        roots: Vec<PathBuf>,
+    ) -> Result<CodeIndexStats, String> {
+        let stats = self.index_workspace(workspace_id, &roots).await?;
+        if !self.is_watching(workspace_id, &roots).await {
+            self.watch(workspace_id, roots).await?;
+        }
+        Ok(stats)
+    }
+
+    async fn is_watching(&self, workspace_id: &str, roots: &[PathBuf]) -> bool {
+        self.watches
+            .lock()
+            .await
+            .get(workspace_id)
+            .is_some_and(|watch| watch.roots == roots)
+    }
+
+    /// Incremental pass over `roots`: new and modified files are re-chunked,
+    /// unchanged ones skipped, and files no longer present dropped.
+    pub async fn index_workspace(
+        &self,
+        workspace_id: &str,
+        roots: &[PathBuf],
+    ) -> Result<CodeIndexStats, String> {
+        let walk_roots = roots.to_vec();
+        let files = tokio::task::spawn_blocking(move || walk_sources(&walk_roots))
+            .await
+            .map_err(|e| format!("Code index walk failed: {}", e))?;
+        let known = self.known_files(workspace_id).await?;
+
+        let mut stats = CodeIndexStats::default();
+        let mut seen = HashSet::new();
+        let mut changed = Vec::new();
+        for file in files {
+            let key = file.path.to_string_lossy().to_string();
+            if known.get(&key) == Some(&(file.modified, file.size)) {
+                stats.files_unchanged += 1;
+            } else {
+                changed.push(file);
+            }
+            seen.insert(key);
+        }
+
+        for batch in changed.chunks(INDEX_BATCH_FILES) {
+            let batch = batch.to_vec();
+            let parsed = tokio::task::spawn_blocking(move || {
+                batch.into_iter().filter_map(parse_file).collect::<Vec<_>>()
+            })
+            .await
+            .map_err(|e| format!("Code index parse failed: {}", e))?;
+            stats.files_indexed += parsed.len();
+            stats.chunks += parsed.iter().map(|file| file.chunks.len()).sum::<usize>();
+            self.store_files(workspace_id, &parsed).await?;
+        }
+
+        let stale = known
+            .into_keys()
+            .filter(|path| !seen.contains(path))
+            .collect::<Vec<_>>();
+        stats.files_removed = stale.len();
+        self.remove_paths(workspace_id, &stale, false).await?;
+        Ok(stats)
+    }
+
+    /// Ranked symbol hits for `query`, optionally limited to files under
+    /// `path_prefix`. Exact symbol-name matches come first.
+    pub async fn search(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        path_prefix: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CodeSearchHit>, String> {
+        let Some(match_expr) = match_expression(query) else {
+            return Ok(Vec::new());
+        };
+        let limit = limit.clamp(1, MAX_SEARCH_LIMIT);
+        let rows: Vec<(String, String, String, String, i64, i64, String, f64)> = sqlx::query_as(
+            r#"
+            SELECT c.path, c.symbol, c.kind, c.language, c.start_line, c.end_line, c.content,
+                   bm25(code_index_fts, 10.0, 4.0, 1.0) AS rank
+            FROM code_index_fts
+            JOIN code_index_chunks c ON c.id = code_index_fts.rowid
+            WHERE code_index_fts MATCH ?1
+              AND c.workspace_id = ?2
+              AND (?3 IS NULL OR substr(c.path, 1, length(?3)) = ?3)
+            ORDER BY rank
+            LIMIT ?4
+            "#,
+        )
+        .bind(&match_expr)
+        .bind(workspace_id)
+        .bind(path_prefix)
+        .bind((limit * 3) as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Code search failed: {}", e))?;
+
+        let query_words = query
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect::<HashSet<_>>();
+        let mut hits = rows
+            .into_iter()
+            .map(
+                |(path, symbol, kind, language, start_line, end_line, content, rank)| {
+                    let exact = symbol
+                        .rsplit([':', '.'])
+                        .next()
+                        .is_some_and(|name| query_words.contains(&name.to_lowercase()));
+                    let hit = CodeSearchHit {
+                        path,
+                        symbol,
+                        kind,
+                        language,
+                        start_line,
+                        end_line,
+                        score: -rank,
+                        preview: content
+                            .lines()
+                            .take(PREVIEW_LINES)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    };
+                    (exact, hit)
+                },
+            )
+            .collect::<Vec<_>>();
+        hits.sort_by(|(a_exact, a), (b_exact, b)| {
+            b_exact.cmp(a_exact).then(
+                b.score
+                    .partial_cmp(&a.score)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+        Ok(hits.into_iter().take(limit).map(|(_, hit)| hit).collect())
+    }
+
+    async fn watch(
+        self: &Arc<Self>,
+        workspace_id: &str,
+        roots: Vec<PathBuf>,
+    ) -> Result<(), String> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<PathBuf>();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event {
+                    if matches!(event.kind, EventKind::Access(_)) {
+                        return;
+                    }
+                    for path in event.paths {
+                        let _ = tx.send(path);
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start code index watcher: {}", e))?;
+        for root in &roots {
+            if let Err(e) = watcher.watch(root, RecursiveMode::Recursive) {
+                tracing::warn!("Code index cannot watch {}: {}", root.display(), e);
+            }
+        }
+
+        // The task ends when the watcher (and with it the sender) is dropped.
+        let index: Weak<Self> = Arc::downgrade(self);
+        let task_workspace = workspace_id.to_string();
+        let task_roots = roots.clone();
+        tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                tokio::time::sleep(WATCH_DEBOUNCE).await;
+                let mut changed = HashSet::from([first]);
+                while let Ok(path) = rx.try_recv() {
+                    changed.insert(path);
+                }
+                let Some(index) = index.upgrade() else {
+                    break;
+                };
+                if let Err(e) = index
+                    .reindex_paths(&task_workspace, &task_roots, changed)
+                    .await
+                {
+                    tracing::warn!("Code index update failed for {}: {}", task_workspace, e);
+                }
+            }
+        });
+
+        self.watches.lock().await.insert(
+            workspace_id.to_string(),
+            WorkspaceWatch {
+                roots,
+                _watcher: watcher,
+            },
+        );
+        Ok(())
+    }
+
+    /// Apply watcher events: re-chunk changed sources, walk new directories
+    /// and drop anything deleted or now ignored.
+    async fn reindex_paths(
+        &self,
+        workspace_id: &str,
+        roots: &[PathBuf],
+        paths: HashSet<PathBuf>,
+    ) -> Result<(), String> {
+        let roots = roots.to_vec();
+        let (parsed, removed) = tokio::task::spawn_blocking(move || {
+            let mut parsed = Vec::new();
+            let mut removed = Vec::new();
+            for path in paths {
+                let Some(root) = roots.iter().find(|root| path.starts_with(root)) else {
+                    continue;
+                };
+                if has_skipped_component(root, &path) {
+                    continue;
+                }
+                let metadata = std::fs::symlink_metadata(&path).ok();
+                match metadata {
+                    Some(meta) if meta.is_dir() => {
+                        if !gitignore::is_ignored(root, &path, true) {
+                            let mut files = Vec::new();
+                            walk_tree(&path, &mut files);
+                            parsed.extend(files.into_iter().filter_map(parse_file));
+                        }
+                    }
+                    Some(meta) if meta.is_file() => {
+                        let source = Language::from_path(&path).is_some()
+                            && meta.len() <= MAX_FILE_BYTES
+                            && !gitignore::is_ignored(root, &path, false);
+                        if source {
+                            parsed.extend(parse_file(SourceFile {
+                                modified: modified_ms(&meta),
+                                size: meta.len() as i64,
+                                path,
+                            }));
+                        } else {
+                            removed.push(path.to_string_lossy().to_string());
+                        }
+                    }
+                    Some(_) => {}
+                    None => removed.push(path.to_string_lossy().to_string()),
+                }
+            }
+            (parsed, removed)
+        })
+        .await
+        .map_err(|e| format!("Code index update failed: {}", e))?;
+
+        self.store_files(workspace_id, &parsed).await?;
+        self.remove_paths(workspace_id, &removed, true).await
+    }
+
+    async fn known_files(&self, workspace_id: &str) -> Result<HashMap<String, (i64, i64)>, String> {
+        let rows: Vec<(String, i64, i64)> = sqlx::query_as(
+            "SELECT path, modified_at, size FROM code_index_files WHERE workspace_id = ?",
+        )
+        .bind(workspace_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load code index state: {}", e))?;
+        Ok(rows
+            .into_iter()
+            .map(|(path, modified, size)| (path, (modified, size)))
+            .collect())
+    }
+
+    async fn store_files(&self, workspace_id: &str, files: &[ParsedFile]) -> Result<(), String> {
+        if files.is_empty() {
+            return Ok(());
+        }
+        let map_err = |e: sqlx::Error| format!("Failed to write code index: {}", e);
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        for file in files {
+            sqlx::query("DELETE FROM code_index_chunks WHERE workspace_id = ? AND path = ?")
+                .bind(workspace_id)
+                .bind(&file.path)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_err)?;
+            for chunk in &file.chunks {
+                sqlx::query(
+                    r#"
+                    INSERT INTO code_index_chunks (
+                        workspace_id, path, language, symbol, kind, start_line, end_line, terms, content
+                    )
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(workspace_id)
+                .bind(&file.path)
+                .bind(file.language.as_str())
+                .bind(&chunk.symbol)
+                .bind(chunk.kind)
+                .bind(chunk.start_line as i64)
+                .bind(chunk.end_line as i64)
+                .bind(chunk_terms(&file.path, &chunk.symbol))
+                .bind(&chunk.content)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_err)?;
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO code_index_files (workspace_id, path, modified_at, size)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT(workspace_id, path) DO UPDATE SET
+                    modified_at = excluded.modified_at,
+                    size = excluded.size
+                "#,
+            )
+            .bind(workspace_id)
+            .bind(&file.path)
+            .bind(file.modified)
+            .bind(file.size)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+        }
+        tx.commit().await.map_err(map_err)
+    }
+
+    /// Drop files from the index. With `include_children`, each path is also
+    /// treated as a removed directory.
+    async fn remove_paths(
+        &self,
+        workspace_id: &str,
+        paths: &[String],
+        include_children: bool,
+    ) -> Result<(), String> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let map_err = |e: sqlx::Error| format!("Failed to prune code index: {}", e);
+        let mut tx = self.pool.begin().await.map_err(map_err)?;
+        for path in paths {
+            let children =
+                include_children.then(|| format!("{}{}", path, std::path::MAIN_SEPARATOR));
+            for statement in [
+                "DELETE FROM code_index_chunks WHERE workspace_id = ?1 AND (path = ?2 OR substr(path, 1, length(?3)) = ?3)",
+                "DELETE FROM code_index_files WHERE workspace_id = ?1 AND (path = ?2 OR substr(path, 1, length(?3)) = ?3)",
+            ] {
+                sqlx::query(statement)
+                .bind(workspace_id)
+                .bind(path)
+                .bind(children.as_deref())
+                .execute(&mut *tx)
+                .await
+                .map_err(map_err)?;
+            }
+        }
+        tx.commit().await.map_err(map_err)
+    }
+}
+
+fn modified_ms(meta: &std::fs::Metadata) -> i64 {
+    meta.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|age| age.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.') && name != "." && name != ".."
+}
+
+fn walk_sources(roots: &[PathBuf]) -> Vec<SourceFile> {
+    let mut files = Vec::new();
+    for root in roots {
+        if files.len() >= MAX_FILES {
+            break;
+        }
+        walk_tree(root, &mut files);
+    }
+    let mut seen = HashSet::new();
+    files.retain(|file| seen.insert(file.path.clone()));
+    files
+}
+
+/// Collect indexable sources under `start` that git would not ignore.
+fn walk_tree(start: &Path, files: &mut Vec<SourceFile>) {
+    for entry in gitignore::walk(start).flatten() {
+        if !entry.file_type().is_some_and(|kind| kind.is_file()) {
+            continue;
+        }
+        let path = entry.into_path();
+        if Language::from_path(&path).is_none() {
+            continue;
+        }
+        let Ok(meta) = std::fs::metadata(&path) else {
+            continue;
+        };
+        if meta.len() > MAX_FILE_BYTES {
+            continue;
+        }
+        files.push(SourceFile {
+            modified: modified_ms(&meta),
+            size: meta.len() as i64,
+            path,
+        });
+        if files.len() >= MAX_FILES {
+            return;
+        }
+    }
+}
+
+/// Cheap pre-filter for watcher noise from `.git`, build output and
+/// dependency trees.
+fn has_skipped_component(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .map(|relative| {
+            relative.components().any(|component| match component {
+                Component::Normal(name) => {
+                    let name = name.to_string_lossy();
+                    ALWAYS_SKIPPED.contains(&name.as_ref()) || is_hidden(&name)
+                }
+                _ => false,
+            })
+        })
+        .unwrap_or(true)
+}
+
+/// Read and chunk one file. Unreadable or binary files are kept with no
+/// chunks so the next pass does not retry them until they change.
+fn parse_file(file: SourceFile) -> Option<ParsedFile> {
+    let language = Language::from_path(&file.path)?;
+    let chunks = match std::fs::read_to_string(&file.path) {
+        Ok(source) if !source.contains('\0') => chunk_source(language, &source),
+        _ => Vec::new(),
+    };
+    Some(ParsedFile {
+        path: file.path.to_string_lossy().to_string(),
+        modified: file.modified,
+        size: file.size,
+        language,
+        chunks,
+    })
+}
+
+/// Lowercase words of an identifier: `MemoryVaultService`, `search_workspace`
+/// and `HTTPServer2` give `memory vault service`, `search workspace` and
+/// `http server2`.
+fn identifier_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in text.split(|c: char| !c.is_alphanumeric()) {
+        let chars = part.chars().collect::<Vec<_>>();
+        let mut word = String::new();
+        for (idx, &c) in chars.iter().enumerate() {
+            let prev = idx.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(idx + 1);
+            let boundary = c.is_uppercase()
+                && prev.is_some_and(|p| {
+                    p.is_lowercase()
+                        || p.is_ascii_digit()
+                        || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+                });
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word).to_lowercase());
+            }
+            word.push(c);
+        }
+        if !word.is_empty() {
+            words.push(word.to_lowercase());
+        }
+    }
+    words
+}
+
+/// Searchable words for a chunk beyond its raw text: the split symbol name
+/// plus the file stem and parent directory.
+fn chunk_terms(path: &str, symbol: &str) -> String {
+    let path = Path::new(path);
+    let location = [
+        path.file_stem(),
+        path.parent().and_then(|parent| parent.file_name()),
+    ];
+    identifier_words(symbol)
+        .into_iter()
+        .chain(
+            location
+                .into_iter()
+                .flatten()
+                .flat_map(|name| identifier_words(&name.to_string_lossy())),
+        )
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// FTS5 expression matching any query identifier, whole or split into words.
+fn match_expression(query: &str) -> Option<String> {
+    let mut seen = HashSet::new();
+    let terms = query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| std::iter::once(word.to_lowercase()).chain(identifier_words(word)))
+        .filter(|term| seen.insert(term.clone()))
+        .take(MAX_QUERY_TERMS)
+        .map(|term| format!("\"{}\"", term))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn code_index() -> CodeIndex {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory sqlite pool");
+        let index = CodeIndex::new(pool);
+        index.init().await.expect("init code index");
+        index
+    }
+
+    #[test]
+    fn identifiers_split_into_search_words() {
+        assert_eq!(
+            identifier_words("MemoryVaultService::search_workspace"),
+            vec!["memory", "vault", "service", "search", "workspace"]
+        );
+        assert_eq!(identifier_words("HTTPServer2"), vec!["http", "server2"]);
+        assert_eq!(
+            match_expression("vaultService").as_deref(),
+            Some("\"vaultservice\" OR \"vault\" OR \"service\"")
+        );
+        assert!(match_expression("?! ").is_none());
+    }
+
+    #[tokio::test]
+    async fn indexes_incrementally_and_ranks_symbol_hits() {
+        let dir = tempfile::tempdir().expect("temp dir");
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("dist")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join(".gitignore"), "dist/\n").unwrap();
+        std::fs::write(
+            root.join("src/vault.rs"),
+            "pub struct Vault;\n\nimpl Vault {\n    pub fn rotate_master_key(&self) {}\n}\n\nfn helper() {\n    // mentions rotate in a comment\n}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/panel.ts"),
+            "export function renderPanel() {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("dist/bundle.js"),
+            "function rotateMasterKey() {}\n",
+        )
+        .unwrap();
+
+        let index = code_index().await;
+        let roots = vec![root.clone()];
+        let stats = index.index_workspace("ws", &roots).await.unwrap();
+        assert_eq!(stats.files_indexed, 2);
+
+        let hits = index
+            .search("ws", "rotate master key", None, 10)
+            .await
+            .unwrap();
+        assert!(!hits.is_empty());
+        assert_eq!(hits[0].symbol, "Vault::rotate_master_key");
+        assert_eq!((hits[0].start_line, hits[0].end_line), (4, 4));
+        assert!(hits.iter().all(|hit| !hit.path.contains("dist")));
+
+        let exact = index.search("ws", "helper", None, 10).await.unwrap();
+        assert_eq!(exact[0].symbol, "helper");
+        assert!(index
+            .search("other", "helper", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let again = index.index_workspace("ws", &roots).await.unwrap();
+        assert_eq!((again.files_indexed, again.files_unchanged), (0, 2));
+
+        std::fs::remove_file(root.join("src/panel.ts")).unwrap();
+        let pruned = index.index_workspace("ws", &roots).await.unwrap();
+        assert_eq!(pruned.files_removed, 1);
+        assert!(index
+            .search("ws", "renderPanel", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -453,6 +453,7 @@ impl CommandPoller {
                         output: None,
                         error: Some("Rejected by Airlock/User".into()),
                         exit_code: Some(1),
+                        ..Default::default()
                     },
                 )
                 .await;
@@ -570,6 +571,7 @@ impl CommandPoller {
                                 output: Some("Fleet policy applied atomically".to_string()),
                                 error: None,
                                 exit_code: Some(0),
+                                ..Default::default()
                             }
                         }
                         Err(e) => CommandResult {
@@ -577,6 +579,7 @@ impl CommandPoller {
                             output: None,
                             error: Some(e),
                             exit_code: Some(1),
+                            ..Default::default()
                         },
                     }
                 }
@@ -635,6 +638,7 @@ impl CommandPoller {
                             output: Some(ack_payload.to_string()),
                             error: None,
                             exit_code: Some(0),
+                            ..Default::default()
                         }
                     } else {
                         CommandResult {
@@ -645,6 +649,7 @@ impl CommandPoller {
                                     .to_string(),
                             ),
                             exit_code: Some(1),
+                            ..Default::default()
                         }
                     }
                 }
@@ -656,6 +661,7 @@ impl CommandPoller {
                         command_for_execution.intent
                     )),
                     exit_code: Some(1),
+                    ..Default::default()
                 },
             }
         } else if command_for_execution.intent.starts_with("agent.") {
@@ -683,6 +689,7 @@ impl CommandPoller {
                         command_for_execution.intent
                     )),
                     exit_code: Some(1),
+                    ..Default::default()
                 },
             }
         } else {
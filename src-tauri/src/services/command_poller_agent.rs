@@ -226,6 +226,63 @@ fn map_agent_event(event: &AgentEvent) -> (String, serde_json::Value) {
                 "summary": payload.summary,
             }),
         ),
+        AgentEvent::ProcessOutput(payload) => (
+            "Process output".to_string(),
+            serde_json::json!({
+                "type": "process_output",
+                "processId": payload.process_id,
+                "stream": payload.stream,
+                "chunkPreview": progress_preview(&payload.chunk),
+                "cursor": payload.cursor,
+            }),
+        ),
+        AgentEvent::ProcessExited(payload) => (
+            "Process exited".to_string(),
+            serde_json::json!({
+                "type": "process_exited",
+                "processId": payload.process_id,
+                "state": payload.state,
+                "exitCode": payload.exit_code,
+            }),
+        ),
+        AgentEvent::BudgetWarning(status) => (
+            format!("Budget warning: {}", status.describe()),
+            serde_json::json!({
+                "type": "budget_warning",
+                "budgetId": status.budget_id,
+                "scope": status.scope,
+                "scopeId": status.scope_id,
+                "period": status.period,
+                "mode": status.mode,
+                "limitUsd": status.limit_usd,
+                "spentUsd": status.spent_usd,
+                "state": status.state,
+            }),
+        ),
+        AgentEvent::BudgetExceeded(status) => (
+            format!("Budget exceeded: {}", status.describe()),
+            serde_json::json!({
+                "type": "budget_exceeded",
+                "budgetId": status.budget_id,
+                "scope": status.scope,
+                "scopeId": status.scope_id,
+                "period": status.period,
+                "mode": status.mode,
+                "limitUsd": status.limit_usd,
+                "spentUsd": status.spent_usd,
+                "state": status.state,
+            }),
+        ),
+        AgentEvent::ProviderServed(served) => (
+            format!("Served by {}", served.provider_id),
+            serde_json::json!({
+                "type": "provider_served",
+                "providerId": served.provider_id,
+                "attempt": served.attempt,
+                "hedged": served.hedged,
+                "firstTokenMs": served.first_token_ms,
+            }),
+        ),
     }
 }
 
@@ -443,6 +500,7 @@ pub(crate) async fn process_agent_run(
             output: None,
             error: Some("Kill switch active: agent.run blocked until policy reset".to_string()),
             exit_code: Some(1),
+            ..Default::default()
         };
     }
 
@@ -610,6 +668,7 @@ pub(crate) async fn process_agent_run(
                             output: None,
                             error: Some(error),
                             exit_code: Some(1),
+                            ..Default::default()
                         };
                     }
                 }
@@ -709,6 +768,9 @@ pub(crate) async fn process_agent_run(
             workspace_memory_context: None,
             workspace_memory_root: None,
             workspace_memory_enabled: false,
+            run_id: Some(command.id.clone()),
+            chat_id: None,
+            checkout_root: None,
         };
 
         // Create config
@@ -790,6 +852,7 @@ GUIDELINES:
                             error
                         )),
                         exit_code: Some(1),
+                        ..Default::default()
                     };
                 }
             };
@@ -989,6 +1052,16 @@ GUIDELINES:
                                 .await;
                         });
                     }
+                    AgentEvent::ProviderServed(ref served) => {
+                        let manager = telemetry_agent_manager.clone();
+                        let chat_id = telemetry_chat_id.clone();
+                        let provider_id = served.provider_id.clone();
+                        tokio::spawn(async move {
+                            let _ = manager
+                                .record_chat_served_provider(&chat_id, &provider_id)
+                                .await;
+                        });
+                    }
                     AgentEvent::ToolCall(ref call) => {
                         let audit_emitter = audit_emitter.clone();
                         let agent_id = audit_agent_id.clone();
@@ -1103,6 +1176,7 @@ GUIDELINES:
                     output: Some(output_with_chat_id),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(e) => {
@@ -1120,6 +1194,7 @@ GUIDELINES:
                     output: None,
                     error: Some(format!("Agent error: {}", e)),
                     exit_code: Some(1),
+                    ..Default::default()
                 }
             }
         }
@@ -1129,6 +1204,7 @@ GUIDELINES:
             output: None,
             error: Some("Agent context not initialized".into()),
             exit_code: Some(1),
+            ..Default::default()
         }
     }
 }
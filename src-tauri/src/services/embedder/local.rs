@@ -0,0 +1,357 @@
+use super::{Embedder, EmbeddingTaskType};
+use crate::services::memory_vault::profiles::EmbeddingProfile;
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use reqwest::Client;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokenizers::{Encoding, PaddingParams, Tokenizer, TruncationParams};
+use tokio::sync::OnceCell;
+
+const MODEL_HUB: &str = "https://huggingface.co";
+const MODEL_FILES: [&str; 3] = ["config.json", "tokenizer.json", "model.safetensors"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pooling {
+    /// Mask-weighted mean of the token vectors (sentence-transformers)
+    Mean,
+    /// The `[CLS]` token vector (BGE)
+    Cls,
+}
+
+/// A BERT sentence-transformer checkpoint the local backend can run.
+#[derive(Debug)]
+struct LocalModel {
+    model: &'static str,
+    repo: &'static str,
+    pooling: Pooling,
+    max_tokens: usize,
+    /// Instruction prepended to queries, for models trained with one
+    query_prefix: Option<&'static str>,
+}
+
+const LOCAL_MODELS: &[LocalModel] = &[
+    LocalModel {
+        model: "all-MiniLM-L6-v2",
+        repo: "sentence-transformers/all-MiniLM-L6-v2",
+        pooling: Pooling::Mean,
+        max_tokens: 256,
+        query_prefix: None,
+    },
+    LocalModel {
+        model: "all-MiniLM-L12-v2",
+        repo: "sentence-transformers/all-MiniLM-L12-v2",
+        pooling: Pooling::Mean,
+        max_tokens: 256,
+        query_prefix: None,
+    },
+    LocalModel {
+        model: "bge-small-en-v1.5",
+        repo: "BAAI/bge-small-en-v1.5",
+        pooling: Pooling::Cls,
+        max_tokens: 512,
+        query_prefix: Some("Represent this sentence for searching relevant passages: "),
+    },
+];
+
+/// Model ids the local backend can run, for error messages.
+pub fn local_model_ids() -> Vec<&'static str> {
+    LOCAL_MODELS.iter().map(|spec| spec.model).collect()
+}
+
+/// CPU-only sentence-transformer run in-process with candle. The checkpoint
+/// is fetched once into the app data dir on first use; after that embedding
+/// needs no network or server.
+#[derive(Debug)]
+pub struct LocalSentenceEmbedder {
+    client: Client,
+    profile: EmbeddingProfile,
+    spec: &'static LocalModel,
+    loaded: OnceCell<Arc<SentenceModel>>,
+}
+
+impl LocalSentenceEmbedder {
+    /// `None` when `profile.model` is not a model the backend can run.
+    pub fn new(client: Client, profile: EmbeddingProfile) -> Option<Self> {
+        let spec = LOCAL_MODELS
+            .iter()
+            .find(|spec| spec.model == profile.model)?;
+        Some(Self {
+            client,
+            profile,
+            spec,
+            loaded: OnceCell::new(),
+        })
+    }
+
+    async fn model(&self) -> Result<Arc<SentenceModel>, String> {
+        self.loaded
+            .get_or_try_init(|| async {
+                let dir = self.download_missing_files().await?;
+                let spec = self.spec;
+                tokio::task::spawn_blocking(move || SentenceModel::load(&dir, spec))
+                    .await
+                    .map_err(|e| format!("Local embedding model load task failed: {}", e))?
+                    .map(Arc::new)
+            })
+            .await
+            .cloned()
+    }
+
+    /// Fetch whatever part of the checkpoint is not cached yet; returns the
+    /// directory holding it.
+    async fn download_missing_files(&self) -> Result<PathBuf, String> {
+        let data_dir = dirs::data_dir().ok_or("Could not find app data directory")?;
+        let model_dir =
+            crate::services::app_identity::resolve_child_dir(data_dir, "embedding-models")?
+                .join(self.spec.model);
+        tokio::fs::create_dir_all(&model_dir)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", model_dir.display(), e))?;
+        for file in MODEL_FILES {
+            let path = model_dir.join(file);
+            if path.is_file() {
+                continue;
+            }
+            let url = format!("{}/{}/resolve/main/{}", MODEL_HUB, self.spec.repo, file);
+            let bytes = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .and_then(|res| res.error_for_status())
+                .map_err(|e| format!("Failed to download {}: {}", url, e))?
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+            // Write next to the target and rename, so an interrupted download
+            // is never mistaken for a complete file
+            let partial = path.with_extension("part");
+            tokio::fs::write(&partial, &bytes)
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+            tokio::fs::rename(&partial, &path)
+                .await
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+        Ok(model_dir)
+    }
+}
+
+#[async_trait]
+impl Embedder for LocalSentenceEmbedder {
+    fn profile(&self) -> &EmbeddingProfile {
+        &self.profile
+    }
+
+    fn max_batch(&self) -> usize {
+        32
+    }
+
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        let model = self.model().await?;
+        let texts = match (task_type, self.spec.query_prefix) {
+            (EmbeddingTaskType::RetrievalQuery, Some(prefix)) => texts
+                .iter()
+                .map(|text| format!("{}{}", prefix, text))
+                .collect(),
+            _ => texts.to_vec(),
+        };
+        tokio::task::spawn_blocking(move || model.embed(&texts))
+            .await
+            .map_err(|e| format!("Local embedding task failed: {}", e))?
+    }
+}
+
+struct SentenceModel {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    pooling: Pooling,
+}
+
+impl std::fmt::Debug for SentenceModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SentenceModel")
+            .field("pooling", &self.pooling)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SentenceModel {
+    fn load(dir: &Path, spec: &LocalModel) -> Result<Self, String> {
+        let config = std::fs::read_to_string(dir.join("config.json"))
+            .map_err(|e| format!("Failed to read local model config: {}", e))?;
+        let config: Config = serde_json::from_str(&config)
+            .map_err(|e| format!("Invalid local model config: {}", e))?;
+        let tokenizer = Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| format!("Invalid local model tokenizer: {}", e))?;
+        let weights = std::fs::read(dir.join("model.safetensors"))
+            .map_err(|e| format!("Failed to read local model weights: {}", e))?;
+        let vb = VarBuilder::from_buffered_safetensors(weights, DTYPE, &Device::Cpu)
+            .map_err(|e| format!("Invalid local model weights: {}", e))?;
+        let model = BertModel::load(vb, &config)
+            .map_err(|e| format!("Failed to load local model: {}", e))?;
+        Self::from_parts(model, tokenizer, spec.pooling, spec.max_tokens)
+    }
+
+    fn from_parts(
+        model: BertModel,
+        mut tokenizer: Tokenizer,
+        pooling: Pooling,
+        max_tokens: usize,
+    ) -> Result<Self, String> {
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: max_tokens,
+                ..Default::default()
+            }))
+            .map_err(|e| format!("Invalid local model tokenizer: {}", e))?;
+        Ok(Self {
+            model,
+            tokenizer,
+            pooling,
+        })
+    }
+
+    /// One L2-normalized vector per text.
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(|e| format!("Local embedding tokenization failed: {}", e))?;
+        self.forward(&encodings)
+            .map_err(|e| format!("Local embedding failed: {}", e))
+    }
+
+    fn forward(&self, encodings: &[Encoding]) -> candle_core::Result<Vec<Vec<f32>>> {
+        let rows = |ids: fn(&Encoding) -> &[u32]| {
+            let rows = encodings
+                .iter()
+                .map(|encoding| Tensor::new(ids(encoding), &Device::Cpu))
+                .collect::<candle_core::Result<Vec<_>>>()?;
+            Tensor::stack(&rows, 0)
+        };
+        let input_ids = rows(Encoding::get_ids)?;
+        let type_ids = rows(Encoding::get_type_ids)?;
+        let mask = rows(Encoding::get_attention_mask)?;
+
+        let hidden = self.model.forward(&input_ids, &type_ids, Some(&mask))?;
+        let pooled = match self.pooling {
+            Pooling::Cls => hidden.narrow(1, 0, 1)?.squeeze(1)?,
+            Pooling::Mean => {
+                let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+                let summed = hidden.broadcast_mul(&mask)?.sum(1)?;
+                summed.broadcast_div(&mask.sum(1)?.clamp(1e-9, f32::MAX)?)?
+            }
+        };
+        let norms = pooled
+            .sqr()?
+            .sum_keepdim(1)?
+            .sqrt()?
+            .clamp(1e-12, f32::MAX)?;
+        pooled.broadcast_div(&norms)?.to_vec2::<f32>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_nn::VarMap;
+    use tokenizers::models::wordpiece::WordPiece;
+    use tokenizers::normalizers::BertNormalizer;
+    use tokenizers::pre_tokenizers::bert::BertPreTokenizer;
+    use tokenizers::processors::bert::BertProcessing;
+
+    /// A two-layer BERT with random weights and a tiny WordPiece vocabulary,
+    /// standing in for a downloaded checkpoint.
+    fn tiny_model(pooling: Pooling) -> SentenceModel {
+        let words = [
+            "[PAD]", "[UNK]", "[CLS]", "[SEP]", "vault", "master", "key", "rotate", "the",
+            "grocery", "list", "##s",
+        ];
+        let dir = tempfile::tempdir().unwrap();
+        let vocab = dir.path().join("vocab.txt");
+        std::fs::write(&vocab, words.join("\n")).unwrap();
+        let wordpiece = WordPiece::from_file(&vocab.display().to_string())
+            .unk_token("[UNK]".to_string())
+            .build()
+            .unwrap();
+        let mut tokenizer = Tokenizer::new(wordpiece);
+        tokenizer
+            .with_normalizer(Some(BertNormalizer::default()))
+            .with_pre_tokenizer(Some(BertPreTokenizer))
+            .with_post_processor(Some(BertProcessing::new(
+                ("[SEP]".to_string(), 3),
+                ("[CLS]".to_string(), 2),
+            )));
+
+        let config = Config {
+            vocab_size: words.len(),
+            hidden_size: 16,
+            num_hidden_layers: 2,
+            num_attention_heads: 2,
+            intermediate_size: 32,
+            max_position_embeddings: 64,
+            ..Config::default()
+        };
+        let varmap = VarMap::new();
+        let vb = VarBuilder::from_varmap(&varmap, DTYPE, &Device::Cpu);
+        let model = BertModel::load(vb, &config).unwrap();
+        SentenceModel::from_parts(model, tokenizer, pooling, 8).unwrap()
+    }
+
+    fn texts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn batches_pool_to_unit_vectors_independent_of_padding() {
+        for pooling in [Pooling::Mean, Pooling::Cls] {
+            let model = tiny_model(pooling);
+            let batch = model
+                .embed(&texts(&["rotate the vault master keys", "grocery list"]))
+                .unwrap();
+            let alone = model.embed(&texts(&["grocery list"])).unwrap();
+
+            assert_eq!(batch.len(), 2);
+            assert_eq!(batch[0].len(), 16);
+            for vector in &batch {
+                let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+                assert!((norm - 1.0).abs() < 1e-4);
+            }
+            // The padded second row matches the same text embedded alone
+            for (a, b) in batch[1].iter().zip(&alone[0]) {
+                assert!((a - b).abs() < 1e-4);
+            }
+            assert_ne!(batch[0], batch[1]);
+        }
+    }
+
+    #[test]
+    fn long_texts_are_truncated_to_the_model_window() {
+        let model = tiny_model(Pooling::Mean);
+        let long = vec!["the vault"; 50].join(" ");
+        assert_eq!(model.embed(&[long]).unwrap()[0].len(), 16);
+        assert!(model.embed(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn only_known_checkpoints_are_served() {
+        let client = Client::new();
+        let known = EmbeddingProfile::resolve("local", "all-MiniLM-L6-v2");
+        assert_eq!(known.dim, 384);
+        assert!(LocalSentenceEmbedder::new(client.clone(), known).is_some());
+        let unknown = EmbeddingProfile::resolve("local", "hashing-ngram-384");
+        assert!(LocalSentenceEmbedder::new(client, unknown).is_none());
+    }
+}
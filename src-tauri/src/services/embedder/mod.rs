@@ -0,0 +1,421 @@
+mod gemini;
+mod local;
+mod ollama;
+mod openai;
+
+pub use gemini::GeminiEmbedder;
+pub use local::LocalSentenceEmbedder;
+pub use ollama::OllamaEmbedder;
+pub use openai::OpenAiCompatibleEmbedder;
+
+use crate::services::memory_vault::profiles::EmbeddingProfile;
+use crate::services::memory_vault::types::VECTOR_STORAGE_DIM;
+use async_trait::async_trait;
+use reqwest::Client;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy)]
+pub enum EmbeddingTaskType {
+    RetrievalDocument,
+    RetrievalQuery,
+}
+
+impl EmbeddingTaskType {
+    fn as_api_value(self) -> &'static str {
+        match self {
+            Self::RetrievalDocument => "RETRIEVAL_DOCUMENT",
+            Self::RetrievalQuery => "RETRIEVAL_QUERY",
+        }
+    }
+}
+
+/// A backend that turns text into vectors of a single embedding profile.
+#[async_trait]
+pub trait Embedder: Send + Sync + std::fmt::Debug {
+    /// Provider, model and dimension of the vectors this backend returns
+    fn profile(&self) -> &EmbeddingProfile;
+
+    /// Largest number of texts sent in one request
+    fn max_batch(&self) -> usize {
+        64
+    }
+
+    /// Embed `texts`, returning one vector per text in the same order
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String>;
+
+    async fn embed(&self, text: &str, task_type: EmbeddingTaskType) -> Result<Vec<f32>, String> {
+        self.embed_batch(&[text.to_string()], task_type)
+            .await?
+            .pop()
+            .ok_or_else(|| format!("{} returned no embedding", self.profile().key()))
+    }
+}
+
+/// Embedding backend selected in settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedderConfig {
+    /// One of `gemini`, `openai`, `ollama` or `local`
+    pub provider: String,
+    pub model: String,
+    /// Server for the `openai` (any `/v1/embeddings` server) and `ollama` backends
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+impl EmbedderConfig {
+    pub fn new(provider: &str, model: &str, base_url: Option<String>) -> Result<Self, String> {
+        let provider = match provider.trim().to_lowercase().as_str() {
+            "g" | "google" | "gemini" => "gemini",
+            "openai" | "openai-compatible" | "openai_compatible" | "custom" => "openai",
+            "ollama" => "ollama",
+            "local" | "cpu" => "local",
+            other => return Err(format!("Unsupported embedding provider '{}'", other)),
+        };
+        let model = match model.trim() {
+            "" => EmbeddingProfile::default_model(provider).to_string(),
+            model if provider == "gemini" => normalize_gemini_model(model).to_string(),
+            model => model.to_string(),
+        };
+        Ok(Self {
+            provider: provider.to_string(),
+            model,
+            base_url: base_url
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty()),
+            api_key: None,
+        })
+    }
+
+    pub fn from_settings(
+        settings: &crate::services::settings::SettingsManager,
+    ) -> Result<Self, String> {
+        Self::new(
+            settings.get_embedder_provider(),
+            settings.get_embedder_model(),
+            settings.get_embedder_base_url(),
+        )
+    }
+
+    /// Keychain entry holding this backend's API key, if it takes one
+    pub fn keychain_id(&self) -> Option<&'static str> {
+        match self.provider.as_str() {
+            "gemini" => Some("gemini"),
+            "openai" => Some("openai"),
+            _ => None,
+        }
+    }
+
+    /// Hosted APIs need a key; self-hosted `/v1/embeddings` servers usually don't
+    pub fn requires_api_key(&self) -> bool {
+        match self.provider.as_str() {
+            "gemini" => true,
+            "openai" => self.base_url.is_none(),
+            _ => false,
+        }
+    }
+
+    pub fn profile(&self) -> EmbeddingProfile {
+        EmbeddingProfile::resolve(&self.provider, &self.model)
+    }
+}
+
+fn normalize_gemini_model(model: &str) -> &str {
+    match model {
+        "gemini-embedding-2-preview" | "gemini-embedding-001" => model,
+        "text-embedding-004"
+        | "embedding-001"
+        | "embedding-gecko-001"
+        | "gemini-embedding-exp"
+        | "gemini-embedding-exp-03-07" => "gemini-embedding-001",
+        _ => crate::services::memory_vault::types::EMBEDDING_MODEL,
+    }
+}
+
+/// The configured embedder, plus the model tried when it fails.
+#[derive(Debug)]
+pub struct EmbedderService {
+    primary: Arc<dyn Embedder>,
+    fallback: Option<Arc<dyn Embedder>>,
+}
+
+impl EmbedderService {
+    pub fn from_config(config: EmbedderConfig) -> Result<Self, String> {
+        let api_key = config.api_key.clone().unwrap_or_default();
+        if config.requires_api_key() && api_key.trim().is_empty() {
+            return Err(format!(
+                "Missing embedding API key for provider: {}",
+                config.provider
+            ));
+        }
+
+        let profile = config.profile();
+        if !profile.fits_storage() {
+            return Err(format!(
+                "Embedding model '{}' has {} dimensions; the vault stores at most {}",
+                profile.model, profile.dim, VECTOR_STORAGE_DIM
+            ));
+        }
+
+        let client = Client::new();
+        let fallback = profile.fallback().map(|profile| {
+            Arc::new(GeminiEmbedder::new(
+                client.clone(),
+                api_key.clone(),
+                profile,
+            )) as Arc<dyn Embedder>
+        });
+        let primary: Arc<dyn Embedder> = match config.provider.as_str() {
+            "gemini" => Arc::new(GeminiEmbedder::new(client, api_key, profile)),
+            "openai" => Arc::new(OpenAiCompatibleEmbedder::new(
+                client,
+                config.base_url.as_deref(),
+                Some(api_key).filter(|key| !key.trim().is_empty()),
+                profile,
+            )),
+            "ollama" => Arc::new(OllamaEmbedder::new(
+                client,
+                config.base_url.as_deref(),
+                profile,
+            )),
+            "local" => match LocalSentenceEmbedder::new(client, profile) {
+                Some(embedder) => Arc::new(embedder),
+                None => {
+                    return Err(format!(
+                        "Unknown local embedding model '{}'; use one of {} or serve the model through Ollama or an OpenAI-compatible server",
+                        config.model,
+                        local::local_model_ids().join(", ")
+                    ))
+                }
+            },
+            other => return Err(format!("Unsupported embedding provider '{}'", other)),
+        };
+        Ok(Self { primary, fallback })
+    }
+
+    /// Build the embedder selected in settings, reading its API key from the
+    /// keychain. `Ok(None)` when the backend needs a key and none is stored.
+    pub fn from_settings() -> Result<Option<Self>, String> {
+        let settings = crate::services::settings::SettingsManager::new();
+        let mut config = EmbedderConfig::from_settings(&settings)?;
+        if let Some(key_id) = config.keychain_id() {
+            let keychain = crate::services::KeychainAccessService::new();
+            config.api_key = keychain
+                .get_many_blocking(&[key_id])
+                .ok()
+                .and_then(|values| values.get(key_id).cloned().flatten())
+                .filter(|key| !key.trim().is_empty());
+        }
+        if config.requires_api_key() && config.api_key.is_none() {
+            return Ok(None);
+        }
+        Self::from_config(config).map(Some)
+    }
+
+    pub fn profile(&self) -> &EmbeddingProfile {
+        self.primary.profile()
+    }
+
+    pub fn fallback_profile(&self) -> Option<&EmbeddingProfile> {
+        self.fallback.as_ref().map(|backend| backend.profile())
+    }
+
+    /// Models whose stored vectors a query embedding is compared against, in order
+    pub fn search_models(&self) -> Vec<String> {
+        std::iter::once(self.profile())
+            .chain(self.fallback_profile())
+            .map(|profile| profile.model.clone())
+            .collect()
+    }
+
+    pub async fn embed_text_with_task(
+        &self,
+        text: &str,
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<f32>, String> {
+        let primary_error = match embed_checked(self.primary.as_ref(), text, task_type).await {
+            Ok(v) => return Ok(v),
+            Err(e) => e,
+        };
+        let Some(fallback) = &self.fallback else {
+            return Err(primary_error);
+        };
+        embed_checked(fallback.as_ref(), text, task_type)
+            .await
+            .map_err(|fallback_error| {
+                format!(
+                    "Embedding failed for '{}' and fallback '{}': {} | {}",
+                    self.profile().model,
+                    fallback.profile().model,
+                    primary_error,
+                    fallback_error
+                )
+            })
+    }
+
+    pub async fn embed_text_for_model_with_task_strict(
+        &self,
+        text: &str,
+        model: &str,
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<f32>, String> {
+        embed_checked(self.backend_for(model)?, text, task_type).await
+    }
+
+    pub async fn embed_texts_for_model_with_task(
+        &self,
+        texts: &[String],
+        model: &str,
+        task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let backend = self.backend_for(model)?;
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(backend.max_batch().max(1)) {
+            let vectors = backend.embed_batch(batch, task_type).await?;
+            if vectors.len() != batch.len() {
+                return Err(format!(
+                    "{} batch embedding size mismatch: expected {}, got {}",
+                    backend.profile().key(),
+                    batch.len(),
+                    vectors.len()
+                ));
+            }
+            for vector in &vectors {
+                check_dimension(backend.profile(), vector)?;
+            }
+            embeddings.extend(vectors);
+        }
+        Ok(embeddings)
+    }
+
+    fn backend_for(&self, model: &str) -> Result<&dyn Embedder, String> {
+        std::iter::once(&self.primary)
+            .chain(self.fallback.as_ref())
+            .find(|backend| backend.profile().model == model)
+            .map(|backend| &**backend)
+            .ok_or_else(|| {
+                format!(
+                    "Embedding model '{}' is not served by the configured '{}' embedder",
+                    model,
+                    self.profile().key()
+                )
+            })
+    }
+}
+
+async fn embed_checked(
+    backend: &dyn Embedder,
+    text: &str,
+    task_type: EmbeddingTaskType,
+) -> Result<Vec<f32>, String> {
+    let vector = backend.embed(text, task_type).await?;
+    check_dimension(backend.profile(), &vector)?;
+    Ok(vector)
+}
+
+/// Reject vectors that don't match the profile or don't fit the vault.
+fn check_dimension(profile: &EmbeddingProfile, vector: &[f32]) -> Result<(), String> {
+    if vector.is_empty() || vector.len() > VECTOR_STORAGE_DIM {
+        return Err(format!(
+            "{} returned a {}-dimensional vector; the vault stores 1 to {}",
+            profile.key(),
+            vector.len(),
+            VECTOR_STORAGE_DIM
+        ));
+    }
+    if profile.dim != 0 && vector.len() != profile.dim {
+        return Err(format!(
+            "{} returned {} dimensions, expected {}",
+            profile.key(),
+            vector.len(),
+            profile.dim
+        ));
+    }
+    Ok(())
+}
+
+/// `http://host:port` or `http://host:port/v1` -> `http://host:port`
+fn server_root(url: &str) -> String {
+    url.trim()
+        .trim_end_matches('/')
+        .trim_end_matches("/v1")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::memory_vault::profiles::LOCAL_EMBEDDING_MODEL;
+
+    #[test]
+    fn config_normalizes_providers_and_models() {
+        let config = EmbedderConfig::new("Google", "text-embedding-004", None).unwrap();
+        assert_eq!(config.provider, "gemini");
+        assert_eq!(config.model, "gemini-embedding-001");
+        assert!(config.requires_api_key());
+
+        let config = EmbedderConfig::new("ollama", "", Some(" ".to_string())).unwrap();
+        assert_eq!(config.model, "nomic-embed-text");
+        assert_eq!(config.base_url, None);
+        assert_eq!(config.keychain_id(), None);
+        assert!(!config.requires_api_key());
+
+        let hosted = EmbedderConfig::new("openai", "text-embedding-3-small", None).unwrap();
+        assert!(hosted.requires_api_key());
+        let self_hosted = EmbedderConfig::new(
+            "openai",
+            "bge-small",
+            Some("http://localhost:8080".to_string()),
+        )
+        .unwrap();
+        assert!(!self_hosted.requires_api_key());
+        assert_eq!(self_hosted.profile().dim, 0);
+
+        assert!(EmbedderConfig::new("cohere", "", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn local_embedder_serves_only_its_own_model() {
+        let service =
+            EmbedderService::from_config(EmbedderConfig::new("local", "", None).unwrap()).unwrap();
+        assert_eq!(
+            service.search_models(),
+            vec![LOCAL_EMBEDDING_MODEL.to_string()]
+        );
+        assert_eq!(service.profile().dim, 384);
+
+        let err = service
+            .embed_text_for_model_with_task_strict(
+                "alpha",
+                "gemini-embedding-001",
+                EmbeddingTaskType::RetrievalQuery,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.contains("not served"));
+
+        assert!(EmbedderService::from_config(
+            EmbedderConfig::new("local", "bge-small-en-v1.5", None).unwrap()
+        )
+        .is_ok());
+        assert!(EmbedderService::from_config(
+            EmbedderConfig::new("local", "hashing-ngram-384", None).unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn server_root_strips_api_suffix() {
+        assert_eq!(
+            server_root("http://localhost:11434/v1/"),
+            "http://localhost:11434"
+        );
+        assert_eq!(server_root("http://gpu-box:8000"), "http://gpu-box:8000");
+    }
+}
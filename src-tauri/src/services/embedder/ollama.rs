@@ -0,0 +1,83 @@
+use super::{Embedder, EmbeddingTaskType};
+use crate::services::memory_vault::profiles::EmbeddingProfile;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const OLLAMA_SERVER: &str = "http://localhost:11434";
+
+#[derive(Debug, Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Ollama's native `POST /api/embed`
+#[derive(Debug)]
+pub struct OllamaEmbedder {
+    client: Client,
+    server: String,
+    profile: EmbeddingProfile,
+}
+
+impl OllamaEmbedder {
+    pub fn new(client: Client, base_url: Option<&str>, profile: EmbeddingProfile) -> Self {
+        Self {
+            client,
+            server: super::server_root(base_url.unwrap_or(OLLAMA_SERVER)),
+            profile,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OllamaEmbedder {
+    fn profile(&self) -> &EmbeddingProfile {
+        &self.profile
+    }
+
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        _task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        let res = self
+            .client
+            .post(format!("{}/api/embed", self.server))
+            .json(&EmbedRequest {
+                model: &self.profile.model,
+                input: texts,
+            })
+            .send()
+            .await
+            .map_err(|e| format!("Ollama embedding request to {} failed: {}", self.server, e))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let text_err = res.text().await.unwrap_or_default();
+            return Err(format!(
+                "Ollama embedding API error: {} - {}",
+                status, text_err
+            ));
+        }
+
+        let parsed: EmbedResponse = res
+            .json()
+            .await
+            .map_err(|e| format!("Parsing Ollama embedding response failed: {}", e))?;
+
+        if parsed.embeddings.len() != texts.len() {
+            return Err(format!(
+                "Ollama embedding size mismatch: expected {}, got {}",
+                texts.len(),
+                parsed.embeddings.len()
+            ));
+        }
+        Ok(parsed.embeddings)
+    }
+}
@@ -0,0 +1,155 @@
+use super::{Embedder, EmbeddingTaskType};
+use crate::services::memory_vault::profiles::EmbeddingProfile;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const OPENAI_API_BASE: &str = "https://api.openai.com/v1";
+
+#[derive(Debug, Serialize)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    encoding_format: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingItem {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+/// `POST /v1/embeddings` on OpenAI or any server that mirrors it
+/// (llama.cpp, LM Studio, vLLM, text-embeddings-inference)
+#[derive(Debug)]
+pub struct OpenAiCompatibleEmbedder {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+    profile: EmbeddingProfile,
+}
+
+impl OpenAiCompatibleEmbedder {
+    pub fn new(
+        client: Client,
+        base_url: Option<&str>,
+        api_key: Option<String>,
+        profile: EmbeddingProfile,
+    ) -> Self {
+        let base_url = match base_url {
+            Some(url) => format!("{}/v1", super::server_root(url)),
+            None => OPENAI_API_BASE.to_string(),
+        };
+        Self {
+            client,
+            base_url,
+            api_key,
+            profile,
+        }
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAiCompatibleEmbedder {
+    fn profile(&self) -> &EmbeddingProfile {
+        &self.profile
+    }
+
+    async fn embed_batch(
+        &self,
+        texts: &[String],
+        _task_type: EmbeddingTaskType,
+    ) -> Result<Vec<Vec<f32>>, String> {
+        let mut request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .json(&EmbeddingsRequest {
+                model: &self.profile.model,
+                input: texts,
+                encoding_format: "float",
+            });
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+
+        let res = request
+            .send()
+            .await
+            .map_err(|e| format!("Embedding request to {} failed: {}", self.base_url, e))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let text_err = res.text().await.unwrap_or_default();
+            return Err(format!("Embedding API error: {} - {}", status, text_err));
+        }
+
+        let parsed: EmbeddingsResponse = res
+            .json()
+            .await
+            .map_err(|e| format!("Parsing embedding response failed: {}", e))?;
+
+        ordered_embeddings(parsed, texts.len())
+    }
+}
+
+/// Servers may answer out of order; `index` says which input each vector is for.
+fn ordered_embeddings(
+    response: EmbeddingsResponse,
+    expected: usize,
+) -> Result<Vec<Vec<f32>>, String> {
+    let mut data = response.data;
+    if data.len() != expected {
+        return Err(format!(
+            "Embedding size mismatch: expected {}, got {}",
+            expected,
+            data.len()
+        ));
+    }
+    data.sort_by_key(|item| item.index);
+    Ok(data.into_iter().map(|item| item.embedding).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embeddings_are_returned_in_input_order() {
+        let response: EmbeddingsResponse = serde_json::from_value(serde_json::json!({
+            "object": "list",
+            "data": [
+                { "object": "embedding", "index": 1, "embedding": [0.0, 1.0] },
+                { "object": "embedding", "index": 0, "embedding": [1.0, 0.0] }
+            ],
+            "model": "text-embedding-3-small"
+        }))
+        .unwrap();
+        let vectors = ordered_embeddings(response, 2).unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+
+        let short: EmbeddingsResponse =
+            serde_json::from_value(serde_json::json!({ "data": [] })).unwrap();
+        assert!(ordered_embeddings(short, 1).is_err());
+    }
+
+    #[test]
+    fn base_url_defaults_to_openai_and_accepts_server_roots() {
+        let profile = EmbeddingProfile::resolve("openai", "text-embedding-3-small");
+        let hosted = OpenAiCompatibleEmbedder::new(Client::new(), None, None, profile.clone());
+        assert_eq!(hosted.base_url, OPENAI_API_BASE);
+
+        let local = OpenAiCompatibleEmbedder::new(
+            Client::new(),
+            Some("http://localhost:8080/"),
+            None,
+            profile,
+        );
+        assert_eq!(local.base_url, "http://localhost:8080/v1");
+    }
+}
@@ -304,6 +304,10 @@ pub struct FileOperationEngine {
     versions_dir: PathBuf,
     /// Trash directory for safe deletes
     trash_dir: PathBuf,
+    /// One JSON manifest per agent run, so rollback points survive restarts
+    checkpoints_dir: PathBuf,
+    /// Serializes updates of persisted run manifests
+    checkpoints_lock: Mutex<()>,
     /// Current workspace context (interior mutability for shared state)
     workspace: Arc<Mutex<Option<Workspace>>>,
 }
@@ -318,6 +322,7 @@ impl FileOperationEngine {
 
         let trash_dir = base_dir.join("trash");
         let versions_dir = base_dir.join("versions");
+        let checkpoints_dir = base_dir.join("run_checkpoints");
 
         Self {
             history: DashMap::new(),
@@ -327,6 +332,8 @@ impl FileOperationEngine {
             transactions: DashMap::new(),
             versions_dir,
             trash_dir,
+            checkpoints_dir,
+            checkpoints_lock: Mutex::new(()),
             workspace: Arc::new(Mutex::new(None)),
         }
     }
@@ -1411,6 +1418,322 @@ impl FileOperationEngine {
         self.transactions.get(transaction_id).map(|t| t.clone())
     }
 
+    // ============ Agent Run Checkpoints ============
+
+    /// Record the state of `path` before an agent run mutates it.
+    ///
+    /// Every run gets one transaction keyed by its run id and persisted to the
+    /// checkpoints directory. Existing files are snapshotted into the versions
+    /// directory and the change id is set to the snapshot id; paths that do
+    /// not exist yet are recorded as `Create` so a rollback removes them again.
+    pub async fn checkpoint_run_file(
+        &self,
+        run_id: &str,
+        path: &Path,
+        operation: FileOpType,
+        dest_path: Option<&Path>,
+    ) -> FileOpResult<FileOpChange> {
+        self.init().await?;
+        let path_str = path.to_string_lossy().to_string();
+
+        let mut snapshot = None;
+        let mut change = FileOpChange {
+            id: Uuid::new_v4().to_string(),
+            operation,
+            source_path: path_str.clone(),
+            dest_path: dest_path.map(|p| p.to_string_lossy().to_string()),
+            timestamp: Utc::now(),
+            reversible: true,
+        };
+        if path.is_file() {
+            let version = self
+                .create_version_snapshot(
+                    &path_str,
+                    &format!("Before {:?} in agent run {}", change.operation, run_id),
+                )
+                .await?;
+            change.id = version.id.clone();
+            snapshot = Some(version);
+        } else if !path.exists() {
+            change.operation = FileOpType::Create;
+        } else {
+            // Directories are only reversible when they are moved; deletes go
+            // through `trash_run_directory` instead.
+            change.reversible = change.operation == FileOpType::Move;
+        }
+
+        self.record_run_change(run_id, change.clone(), snapshot)
+            .await?;
+        Ok(change)
+    }
+
+    /// Delete a directory for an agent run by moving it into the trash, so a
+    /// rollback can put it back. Refuses when the move is impossible (e.g. the
+    /// trash is on another filesystem) rather than deleting irreversibly.
+    pub async fn trash_run_directory(
+        &self,
+        run_id: &str,
+        path: &Path,
+    ) -> FileOpResult<FileOpChange> {
+        self.init().await?;
+        let file_name = path
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let trash_path = self
+            .trash_dir
+            .join(format!("{}_{}", Uuid::new_v4(), file_name));
+        fs::rename(path, &trash_path).await.map_err(|e| {
+            FileOpError::Conflict(format!(
+                "cannot move {} to the trash ({}); refusing to delete it irreversibly",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let change = FileOpChange {
+            id: Uuid::new_v4().to_string(),
+            operation: FileOpType::Delete,
+            source_path: path.to_string_lossy().to_string(),
+            dest_path: Some(trash_path.to_string_lossy().to_string()),
+            timestamp: Utc::now(),
+            reversible: true,
+        };
+        self.record_run_change(run_id, change.clone(), None).await?;
+        Ok(change)
+    }
+
+    /// List the changes recorded for an agent run, oldest first
+    pub async fn list_run_changes(&self, run_id: &str) -> Vec<FileOpChange> {
+        self.load_run_transaction(run_id).await;
+        self.transactions
+            .get(run_id)
+            .filter(|t| t.state == TransactionState::Active)
+            .map(|t| t.operations.clone())
+            .unwrap_or_default()
+    }
+
+    /// Undo every change of an agent run, newest first
+    pub async fn rollback_run(&self, run_id: &str) -> FileOpResult<Vec<FileOpChange>> {
+        self.rollback_run_changes(run_id, None).await
+    }
+
+    /// Restore a single file to its state before the agent run first touched it
+    pub async fn rollback_run_file(
+        &self,
+        run_id: &str,
+        file_path: &str,
+    ) -> FileOpResult<Vec<FileOpChange>> {
+        self.rollback_run_changes(run_id, Some(file_path)).await
+    }
+
+    fn run_transaction(run_id: &str) -> Transaction {
+        Transaction {
+            id: run_id.to_string(),
+            description: format!("Agent run {}", run_id),
+            state: TransactionState::Active,
+            start_time: Utc::now(),
+            end_time: None,
+            operations: Vec::new(),
+            snapshots: Vec::new(),
+        }
+    }
+
+    async fn record_run_change(
+        &self,
+        run_id: &str,
+        change: FileOpChange,
+        snapshot: Option<FileVersion>,
+    ) -> FileOpResult<()> {
+        let _guard = self.checkpoints_lock.lock().await;
+        self.load_run_transaction(run_id).await;
+        let transaction = {
+            let mut transaction = self
+                .transactions
+                .entry(run_id.to_string())
+                .or_insert_with(|| Self::run_transaction(run_id));
+            if transaction.state != TransactionState::Active {
+                *transaction = Self::run_transaction(run_id);
+            }
+            transaction.operations.push(change);
+            transaction.snapshots.extend(snapshot);
+            transaction.clone()
+        };
+        self.save_run_transaction(&transaction).await
+    }
+
+    fn run_manifest_path(&self, run_id: &str) -> PathBuf {
+        let name: String = run_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.checkpoints_dir.join(format!("{}.json", name))
+    }
+
+    /// Bring a run's persisted checkpoint back into memory, e.g. when an
+    /// interrupted run is resumed or rolled back after a restart.
+    async fn load_run_transaction(&self, run_id: &str) {
+        if self.transactions.contains_key(run_id) {
+            return;
+        }
+        let Ok(json) = fs::read(self.run_manifest_path(run_id)).await else {
+            return;
+        };
+        match serde_json::from_slice::<Transaction>(&json) {
+            Ok(transaction) => {
+                self.transactions
+                    .entry(run_id.to_string())
+                    .or_insert(transaction);
+            }
+            Err(e) => tracing::warn!("Ignoring corrupt checkpoint for run {}: {}", run_id, e),
+        }
+    }
+
+    /// Write the run manifest, or remove it once nothing is left to undo.
+    async fn save_run_transaction(&self, transaction: &Transaction) -> FileOpResult<()> {
+        let path = self.run_manifest_path(&transaction.id);
+        if transaction.state != TransactionState::Active || transaction.operations.is_empty() {
+            return match fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+        fs::create_dir_all(&self.checkpoints_dir).await?;
+        let json = serde_json::to_vec(transaction).map_err(std::io::Error::from)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json).await?;
+        fs::rename(&tmp, &path).await?;
+        Ok(())
+    }
+
+    async fn rollback_run_changes(
+        &self,
+        run_id: &str,
+        only_path: Option<&str>,
+    ) -> FileOpResult<Vec<FileOpChange>> {
+        self.load_run_transaction(run_id).await;
+        let transaction = self
+            .get_transaction(run_id)
+            .filter(|t| t.state == TransactionState::Active)
+            .ok_or_else(|| {
+                FileOpError::NotFound(format!("No changes recorded for run {}", run_id))
+            })?;
+
+        let mut rollback_changes = Vec::new();
+        let mut undone = Vec::new();
+        let mut errors = Vec::new();
+        for change in transaction.operations.iter().rev() {
+            if only_path.is_some_and(|p| p != change.source_path) {
+                continue;
+            }
+            let snapshot = transaction.snapshots.iter().find(|v| v.id == change.id);
+            match self.undo_run_change(change, snapshot).await {
+                Ok(Some(rollback)) => {
+                    undone.push(change.id.clone());
+                    rollback_changes.push(rollback);
+                }
+                Ok(None) => undone.push(change.id.clone()),
+                Err(e) => errors.push(format!("{}: {}", change.source_path, e)),
+            }
+        }
+        if only_path.is_some() && undone.is_empty() && errors.is_empty() {
+            return Err(FileOpError::NotFound(format!(
+                "Run {} did not change {}",
+                run_id,
+                only_path.unwrap_or_default()
+            )));
+        }
+
+        {
+            let _guard = self.checkpoints_lock.lock().await;
+            let remaining = self.transactions.get_mut(run_id).map(|mut transaction| {
+                transaction.operations.retain(|c| !undone.contains(&c.id));
+                if transaction.operations.is_empty() {
+                    transaction.state = TransactionState::RolledBack;
+                    transaction.end_time = Some(Utc::now());
+                }
+                transaction.clone()
+            });
+            if let Some(transaction) = remaining {
+                self.save_run_transaction(&transaction).await?;
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(FileOpError::Conflict(format!(
+                "Rollback of run {} incomplete: {}",
+                run_id,
+                errors.join("; ")
+            )));
+        }
+        Ok(rollback_changes)
+    }
+
+    /// Undo one recorded run change. Returns `None` when there was nothing to restore.
+    async fn undo_run_change(
+        &self,
+        change: &FileOpChange,
+        snapshot: Option<&FileVersion>,
+    ) -> FileOpResult<Option<FileOpChange>> {
+        let source = Path::new(&change.source_path);
+        if let Some(version) = snapshot {
+            if let Some(parent) = source.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::copy(&version.version_path, source).await?;
+            return Ok(Some(FileOpChange {
+                id: Uuid::new_v4().to_string(),
+                operation: FileOpType::Modify,
+                source_path: version.version_path.clone(),
+                dest_path: Some(change.source_path.clone()),
+                timestamp: Utc::now(),
+                reversible: false,
+            }));
+        }
+        if !change.reversible {
+            return Err(FileOpError::Conflict(format!(
+                "{:?} of a directory cannot be rolled back",
+                change.operation
+            )));
+        }
+
+        match change.operation {
+            FileOpType::Create if source.is_file() => {
+                fs::remove_file(source).await?;
+                Ok(Some(FileOpChange {
+                    id: Uuid::new_v4().to_string(),
+                    operation: FileOpType::Delete,
+                    source_path: change.source_path.clone(),
+                    dest_path: None,
+                    timestamp: Utc::now(),
+                    reversible: false,
+                }))
+            }
+            // A move, or a directory delete that went to the trash.
+            FileOpType::Move | FileOpType::Delete => match &change.dest_path {
+                Some(dest) if Path::new(dest).exists() && !source.exists() => {
+                    fs::rename(dest, source).await?;
+                    Ok(Some(FileOpChange {
+                        id: Uuid::new_v4().to_string(),
+                        operation: FileOpType::Move,
+                        source_path: dest.clone(),
+                        dest_path: Some(change.source_path.clone()),
+                        timestamp: Utc::now(),
+                        reversible: false,
+                    }))
+                }
+                _ => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+
     // ============ Enhanced Undo/Redo Support ============
 
     /// Enhanced undo with full operation history
@@ -1732,6 +2055,82 @@ mod tests {
         assert_eq!(version.description, "Test version");
     }
 
+    #[tokio::test]
+    async fn test_rollback_run_restores_prior_state() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let mut engine = FileOperationEngine::new();
+        engine.versions_dir = temp.path().join("versions");
+        engine.trash_dir = temp.path().join("trash");
+        engine.checkpoints_dir = temp.path().join("checkpoints");
+
+        let existing = temp.path().join("notes.txt");
+        let created = temp.path().join("new.txt");
+        fs::write(&existing, "before").await.unwrap();
+
+        engine
+            .checkpoint_run_file("run-1", &existing, FileOpType::Modify, None)
+            .await
+            .unwrap();
+        fs::write(&existing, "after").await.unwrap();
+        let change = engine
+            .checkpoint_run_file("run-1", &created, FileOpType::Modify, None)
+            .await
+            .unwrap();
+        assert_eq!(change.operation, FileOpType::Create);
+        fs::write(&created, "fresh").await.unwrap();
+        assert_eq!(engine.list_run_changes("run-1").await.len(), 2);
+
+        engine
+            .rollback_run_file("run-1", &created.to_string_lossy())
+            .await
+            .unwrap();
+        assert!(!created.exists());
+        assert_eq!(engine.list_run_changes("run-1").await.len(), 1);
+
+        engine.rollback_run("run-1").await.unwrap();
+        assert_eq!(fs::read_to_string(&existing).await.unwrap(), "before");
+        assert!(engine.list_run_changes("run-1").await.is_empty());
+        assert!(engine.rollback_run("run-1").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_checkpoints_survive_restart_and_restore_directories() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let engine_at = |root: &Path| {
+            let mut engine = FileOperationEngine::new();
+            engine.versions_dir = root.join("versions");
+            engine.trash_dir = root.join("trash");
+            engine.checkpoints_dir = root.join("checkpoints");
+            engine
+        };
+        let workspace = temp.path().join("workspace");
+        let notes = workspace.join("notes.txt");
+        let build = workspace.join("build");
+        fs::create_dir_all(build.join("out")).await.unwrap();
+        fs::write(&notes, "before").await.unwrap();
+        fs::write(build.join("out/app.js"), "bundle").await.unwrap();
+
+        let engine = engine_at(temp.path());
+        engine
+            .checkpoint_run_file("run-2", &notes, FileOpType::Modify, None)
+            .await
+            .unwrap();
+        fs::write(&notes, "after").await.unwrap();
+        engine.trash_run_directory("run-2", &build).await.unwrap();
+        assert!(!build.exists());
+        drop(engine);
+
+        let restarted = engine_at(temp.path());
+        assert_eq!(restarted.list_run_changes("run-2").await.len(), 2);
+        restarted.rollback_run("run-2").await.unwrap();
+        assert_eq!(fs::read_to_string(&notes).await.unwrap(), "before");
+        assert_eq!(
+            fs::read_to_string(build.join("out/app.js")).await.unwrap(),
+            "bundle"
+        );
+        assert!(!restarted.run_manifest_path("run-2").exists());
+    }
+
     #[test]
     fn test_file_operation_types() {
         assert_eq!(FileOpType::Move as u8, 0);
@@ -0,0 +1,700 @@
+//! Targeted file edits for the filesystem skill.
+//!
+//! `edit_file` replaces an exact string (with a uniqueness / occurrence-count
+//! check) and `apply_patch` applies a unified diff, searching near each hunk's
+//! stated position and dropping up to `fuzz` context lines when the file has
+//! drifted. Both report a unified diff of what actually changed, which the
+//! Airlock approval prompt also renders via [`preview`] before the edit runs.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+pub const DEFAULT_FUZZ: usize = 2;
+pub const MAX_FUZZ: usize = 3;
+/// Edit distance beyond which the diff degrades to one replace block.
+const MAX_DIFF_EDITS: usize = 2000;
+/// Largest approval preview shown to the user.
+const MAX_PREVIEW_BYTES: usize = 16 * 1024;
+
+/// Replaces `old` with `new`, requiring exactly `expected` occurrences
+/// (default 1, i.e. `old` must be unique).
+pub fn replace_exact(
+    content: &str,
+    old: &str,
+    new: &str,
+    expected: Option<usize>,
+) -> Result<(String, usize), String> {
+    if old.is_empty() {
+        return Err("old_string must not be empty".to_string());
+    }
+    let expected = expected.unwrap_or(1);
+    let found = content.matches(old).count();
+    if found == 0 {
+        return Err("old_string was not found in the file".to_string());
+    }
+    if found != expected {
+        return Err(if expected == 1 {
+            format!(
+                "old_string matches {} times; include more surrounding text to make it unique or set expected_occurrences",
+                found
+            )
+        } else {
+            format!(
+                "old_string matches {} times but expected_occurrences is {}",
+                found, expected
+            )
+        });
+    }
+    Ok((content.replace(old, new), found))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// Line diff (Myers) with the common prefix and suffix trimmed first.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|i| DiffOp::Equal(i, i)).collect();
+    let a = &old[prefix..old.len() - suffix];
+    let b = &new[prefix..new.len() - suffix];
+    match myers(a, b) {
+        Some(middle) => ops.extend(middle.into_iter().map(|op| match op {
+            DiffOp::Equal(i, j) => DiffOp::Equal(i + prefix, j + prefix),
+            DiffOp::Delete(i) => DiffOp::Delete(i + prefix),
+            DiffOp::Insert(j) => DiffOp::Insert(j + prefix),
+        })),
+        None => {
+            ops.extend((0..a.len()).map(|i| DiffOp::Delete(i + prefix)));
+            ops.extend((0..b.len()).map(|j| DiffOp::Insert(j + prefix)));
+        }
+    }
+    let (old_tail, new_tail) = (old.len() - suffix, new.len() - suffix);
+    ops.extend((0..suffix).map(|i| DiffOp::Equal(old_tail + i, new_tail + i)));
+    ops
+}
+
+/// Myers' O(ND) diff. Returns `None` when the edit distance exceeds
+/// `MAX_DIFF_EDITS`.
+fn myers(a: &[&str], b: &[&str]) -> Option<Vec<DiffOp>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // trace[d] holds v[k] for k in -d-1..=d+1 before step d.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut found = None;
+    'search: for d in 0..=max.min(MAX_DIFF_EDITS) as isize {
+        let lo = (offset - d - 1) as usize;
+        let hi = (offset + d + 1) as usize;
+        trace.push(v[lo..=hi].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found = Some(d);
+                break 'search;
+            }
+            k += 2;
+        }
+    }
+    let depth = found?;
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=depth).rev() {
+        let prev = &trace[d as usize];
+        let at = |k: isize| prev[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push(DiffOp::Equal(x as usize, y as usize));
+        }
+        if x == prev_x {
+            y -= 1;
+            ops.push(DiffOp::Insert(y as usize));
+        } else {
+            x -= 1;
+            ops.push(DiffOp::Delete(x as usize));
+        }
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        ops.push(DiffOp::Equal(x as usize, y as usize));
+    }
+    ops.reverse();
+    Some(ops)
+}
+
+fn push_diff_line(out: &mut String, marker: char, line: &str) {
+    out.push(marker);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Unified diff between `old` and `new`; empty when they are equal.
+pub fn unified_diff(label: &str, old: &str, new: &str, context: usize) -> String {
+    let old_lines = split_lines(old);
+    let new_lines = split_lines(new);
+    let ops = diff_lines(&old_lines, &new_lines);
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(index, _)| index)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", label, label);
+    let mut start = 0;
+    while start < changes.len() {
+        let mut end = start;
+        while end + 1 < changes.len() && changes[end + 1] - changes[end] <= 2 * context + 1 {
+            end += 1;
+        }
+        let from = changes[start].saturating_sub(context);
+        let to = (changes[end] + context + 1).min(ops.len());
+        let group = &ops[from..to];
+
+        let (mut old_start, mut new_start) = position_before(&ops, from);
+        let old_count = group
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(_)))
+            .count();
+        let new_count = group
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(_)))
+            .count();
+        if old_count > 0 {
+            old_start += 1;
+        }
+        if new_count > 0 {
+            new_start += 1;
+        }
+        out.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for op in group {
+            match *op {
+                DiffOp::Equal(i, _) => push_diff_line(&mut out, ' ', old_lines[i]),
+                DiffOp::Delete(i) => push_diff_line(&mut out, '-', old_lines[i]),
+                DiffOp::Insert(j) => push_diff_line(&mut out, '+', new_lines[j]),
+            }
+        }
+        start = end + 1;
+    }
+    out
+}
+
+/// Old/new line counts consumed before `ops[index]`.
+fn position_before(ops: &[DiffOp], index: usize) -> (usize, usize) {
+    ops[..index].iter().fold((0, 0), |(old, new), op| match op {
+        DiffOp::Equal(..) => (old + 1, new + 1),
+        DiffOp::Delete(_) => (old + 1, new),
+        DiffOp::Insert(_) => (old, new + 1),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Hunk {
+    header: String,
+    old_start: usize,
+    old_len: usize,
+    lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    /// `None` for `/dev/null`, i.e. a new file.
+    pub old_path: Option<String>,
+    /// `None` for `/dev/null`, i.e. a deletion.
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// Path the patch writes to.
+    pub fn target(&self) -> Option<&str> {
+        self.new_path.as_deref().or(self.old_path.as_deref())
+    }
+}
+
+fn parse_header_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or("").trim();
+    if path == "/dev/null" || path.is_empty() {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+fn parse_range(raw: &str) -> Option<(usize, usize)> {
+    let mut parts = raw.splitn(2, ',');
+    let start = parts.next()?.parse().ok()?;
+    let len = match parts.next() {
+        Some(len) => len.parse().ok()?,
+        None => 1,
+    };
+    Some((start, len))
+}
+
+fn parse_hunk_header(line: &str) -> Option<(usize, usize)> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (old, _) = rest.split_once(' ')?;
+    parse_range(old)
+}
+
+/// Parses a unified diff touching one or more files.
+pub fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let lines: Vec<&str> = patch.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let (Some(old), Some(new)) = (
+            line.strip_prefix("--- "),
+            lines.get(i + 1).and_then(|next| next.strip_prefix("+++ ")),
+        ) {
+            files.push(FilePatch {
+                old_path: parse_header_path(old),
+                new_path: parse_header_path(new),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        if line.starts_with("@@") {
+            let file = files
+                .last_mut()
+                .ok_or("Hunk found before a ---/+++ file header")?;
+            let (old_start, old_len) = parse_hunk_header(line)
+                .ok_or_else(|| format!("Malformed hunk header: {}", line))?;
+            let mut hunk = Hunk {
+                header: line.to_string(),
+                old_start,
+                old_len,
+                lines: Vec::new(),
+            };
+            i += 1;
+            while i < lines.len() {
+                let body = lines[i];
+                let starts_file = body.starts_with("--- ")
+                    && lines
+                        .get(i + 1)
+                        .is_some_and(|next| next.starts_with("+++ "));
+                if body.starts_with("@@") || body.starts_with("diff ") || starts_file {
+                    break;
+                }
+                let parsed = match body.chars().next() {
+                    Some(' ') => HunkLine::Context(format!("{}\n", &body[1..])),
+                    Some('-') => HunkLine::Remove(format!("{}\n", &body[1..])),
+                    Some('+') => HunkLine::Add(format!("{}\n", &body[1..])),
+                    // Editors often strip the space from blank context lines.
+                    None => HunkLine::Context("\n".to_string()),
+                    Some('\\') => {
+                        if let Some(
+                            HunkLine::Context(text) | HunkLine::Remove(text) | HunkLine::Add(text),
+                        ) = hunk.lines.last_mut()
+                        {
+                            text.pop();
+                        }
+                        i += 1;
+                        continue;
+                    }
+                    Some(_) => break,
+                };
+                hunk.lines.push(parsed);
+                i += 1;
+            }
+            trim_trailing_blank_context(&mut hunk);
+            file.hunks.push(hunk);
+            continue;
+        }
+        i += 1;
+    }
+
+    files.retain(|file| !file.hunks.is_empty());
+    if files.is_empty() {
+        return Err("Patch contains no hunks".to_string());
+    }
+    Ok(files)
+}
+
+/// Drops blank context lines past the header's old length, which are
+/// usually just the patch's trailing newline.
+fn trim_trailing_blank_context(hunk: &mut Hunk) {
+    let old_count = |hunk: &Hunk| {
+        hunk.lines
+            .iter()
+            .filter(|line| !matches!(line, HunkLine::Add(_)))
+            .count()
+    };
+    while old_count(hunk) > hunk.old_len
+        && matches!(hunk.lines.last(), Some(HunkLine::Context(text)) if text == "\n")
+    {
+        hunk.lines.pop();
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HunkReport {
+    pub index: usize,
+    pub header: String,
+    pub applied: bool,
+    /// 1-based line in the original file where the hunk matched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Lines between the stated and the actual position.
+    pub offset: isize,
+    /// Context lines ignored at each end to make the hunk match.
+    pub fuzz: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn lines_match(file: &[&str], block: &[&str], at: usize, loose: bool) -> bool {
+    block.iter().enumerate().all(|(i, expected)| {
+        let actual = file[at + i];
+        actual == *expected || (loose && actual.trim_end() == expected.trim_end())
+    })
+}
+
+/// Nearest position to `near` (at or after `min`) where `block` matches.
+fn find_block(file: &[&str], block: &[&str], near: usize, min: usize) -> Option<usize> {
+    if block.len() > file.len() {
+        return None;
+    }
+    let last = file.len() - block.len();
+    if min > last {
+        return None;
+    }
+    let near = near.clamp(min, last);
+    for loose in [false, true] {
+        for distance in 0..=(last - min) {
+            let candidates = [near.checked_sub(distance), near.checked_add(distance)];
+            for at in candidates.into_iter().flatten() {
+                if at >= min && at <= last && lines_match(file, block, at, loose) {
+                    return Some(at);
+                }
+            }
+            if near.saturating_sub(distance) <= min && near + distance >= last {
+                break;
+            }
+        }
+    }
+    None
+}
+
+/// Applies `hunks` to `content`. Fails without partial output if any hunk
+/// does not match; the reports say which.
+pub fn apply_hunks(
+    content: &str,
+    hunks: &[Hunk],
+    fuzz: usize,
+) -> Result<(String, Vec<HunkReport>), Vec<HunkReport>> {
+    let original = split_lines(content);
+    let mut output: Vec<&str> = Vec::with_capacity(original.len());
+    let mut cursor = 0usize;
+    let mut reports = Vec::with_capacity(hunks.len());
+    let mut failed = false;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let leading = hunk
+            .lines
+            .iter()
+            .take_while(|line| matches!(line, HunkLine::Context(_)))
+            .count();
+        let trailing = hunk
+            .lines
+            .iter()
+            .rev()
+            .take_while(|line| matches!(line, HunkLine::Context(_)))
+            .count();
+        let stated = hunk.old_start.saturating_sub(1);
+
+        let mut matched = None;
+        for level in 0..=fuzz.min(MAX_FUZZ) {
+            let (skip_front, skip_back) = (level.min(leading), level.min(trailing));
+            if level > 0 && skip_front + skip_back == 0 {
+                break;
+            }
+            let body = &hunk.lines[skip_front..hunk.lines.len() - skip_back];
+            let old_block: Vec<&str> = body
+                .iter()
+                .filter_map(|line| match line {
+                    HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                    HunkLine::Add(_) => None,
+                })
+                .collect();
+            let position = if old_block.is_empty() {
+                Some((stated + skip_front).clamp(cursor, original.len()))
+            } else {
+                find_block(&original, &old_block, stated + skip_front, cursor)
+            };
+            if let Some(at) = position {
+                matched = Some((at, level, body, old_block.len()));
+                break;
+            }
+        }
+
+        match matched {
+            Some((at, level, body, old_len)) => {
+                output.extend_from_slice(&original[cursor..at]);
+                // Context lines are kept from the file, not the patch, so
+                // whitespace-tolerant matches don't rewrite them.
+                let mut file_line = at;
+                for line in body {
+                    match line {
+                        HunkLine::Context(_) => {
+                            output.push(original[file_line]);
+                            file_line += 1;
+                        }
+                        HunkLine::Remove(_) => file_line += 1,
+                        HunkLine::Add(text) => output.push(text.as_str()),
+                    }
+                }
+                cursor = at + old_len;
+                reports.push(HunkReport {
+                    index,
+                    header: hunk.header.clone(),
+                    applied: true,
+                    line: Some(at + 1),
+                    offset: at as isize - (stated + level.min(leading)) as isize,
+                    fuzz: level,
+                    error: None,
+                });
+            }
+            None => {
+                failed = true;
+                reports.push(HunkReport {
+                    index,
+                    header: hunk.header.clone(),
+                    applied: false,
+                    line: None,
+                    offset: 0,
+                    fuzz: 0,
+                    error: Some(format!(
+                        "context not found near line {} (fuzz {})",
+                        hunk.old_start,
+                        fuzz.min(MAX_FUZZ)
+                    )),
+                });
+            }
+        }
+    }
+
+    if failed {
+        return Err(reports);
+    }
+    output.extend_from_slice(&original[cursor..]);
+    Ok((output.concat(), reports))
+}
+
+/// Every path a patch touches, as written in its headers.
+pub fn patch_paths(patch: &str) -> Vec<String> {
+    parse_patch(patch)
+        .map(|files| {
+            files
+                .iter()
+                .filter_map(|file| file.target().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn resolve(root: Option<&str>, raw: &str) -> PathBuf {
+    match root {
+        Some(root) if Path::new(raw).is_relative() => Path::new(root).join(raw),
+        _ => PathBuf::from(raw),
+    }
+}
+
+/// Dry-runs `edit_file` / `apply_patch` to show the approval prompt the
+/// diff instead of the raw arguments.
+pub fn preview(tool: &str, params: &serde_json::Value, root: Option<&str>) -> Option<String> {
+    let diff = match tool {
+        "edit_file" => {
+            let path = params.get("path")?.as_str()?;
+            let old = params.get("old_string")?.as_str()?;
+            let new = params.get("new_string")?.as_str()?;
+            let expected = params
+                .get("expected_occurrences")
+                .and_then(|value| value.as_u64())
+                .map(|value| value as usize);
+            let content = std::fs::read_to_string(resolve(root, path)).ok()?;
+            let (updated, _) = replace_exact(&content, old, new, expected).ok()?;
+            unified_diff(path, &content, &updated, DEFAULT_CONTEXT_LINES)
+        }
+        "apply_patch" => {
+            let patch = params.get("patch")?.as_str()?;
+            let fuzz = params
+                .get("fuzz")
+                .and_then(|value| value.as_u64())
+                .map_or(DEFAULT_FUZZ, |value| value as usize);
+            let files = parse_patch(patch).ok()?;
+            let mut diff = String::new();
+            for file in &files {
+                let target = file.target()?;
+                let content = match &file.old_path {
+                    Some(path) => std::fs::read_to_string(resolve(root, path)).ok()?,
+                    None => String::new(),
+                };
+                let (updated, _) = apply_hunks(&content, &file.hunks, fuzz).ok()?;
+                diff.push_str(&unified_diff(
+                    target,
+                    &content,
+                    &updated,
+                    DEFAULT_CONTEXT_LINES,
+                ));
+            }
+            diff
+        }
+        _ => return None,
+    };
+    if diff.is_empty() {
+        return None;
+    }
+    if diff.len() > MAX_PREVIEW_BYTES {
+        let mut cut = MAX_PREVIEW_BYTES;
+        while !diff.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        return Some(format!("{}\n[diff truncated]", &diff[..cut]));
+    }
+    Some(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace_exact_enforces_occurrence_count() {
+        let content = "let a = 1;\nlet b = 1;\n";
+        assert!(replace_exact(content, "= 1", "= 2", None)
+            .unwrap_err()
+            .contains("2 times"));
+        assert_eq!(
+            replace_exact(content, "= 1", "= 2", Some(2)).unwrap(),
+            ("let a = 2;\nlet b = 2;\n".to_string(), 2)
+        );
+        assert_eq!(
+            replace_exact(content, "a = 1", "a = 3", None).unwrap().0,
+            "let a = 3;\nlet b = 1;\n"
+        );
+        assert!(replace_exact(content, "missing", "x", None).is_err());
+    }
+
+    #[test]
+    fn unified_diff_round_trips_through_apply() {
+        let old: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let new = old
+            .replace("line 3\n", "line three\n")
+            .replace("line 17\n", "")
+            + "line 21";
+        let diff = unified_diff("src/lib.rs", &old, &new, DEFAULT_CONTEXT_LINES);
+        assert!(diff.starts_with("--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,6 +1,6 @@\n"));
+        assert!(diff.contains("-line 3\n+line three\n"));
+        assert!(diff.ends_with("+line 21\n\\ No newline at end of file\n"));
+
+        let files = parse_patch(&diff).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].target(), Some("src/lib.rs"));
+        let (patched, reports) = apply_hunks(&old, &files[0].hunks, 0).unwrap();
+        assert_eq!(patched, new);
+        assert!(reports
+            .iter()
+            .all(|report| report.applied && report.fuzz == 0));
+        assert!(unified_diff("x", &old, &old, 3).is_empty());
+    }
+
+    #[test]
+    fn apply_hunks_tolerates_drift_and_reports_failures() {
+        let patch =
+            "--- a/notes.txt\n+++ b/notes.txt\n@@ -2,3 +2,3 @@\n alpha\n-beta\n+BETA\n gamma\n";
+        let hunks = &parse_patch(patch).unwrap()[0].hunks;
+
+        // Two lines were inserted above the hunk since the patch was made.
+        let drifted = "intro\nnew 1\nnew 2\nalpha\nbeta\ngamma\n";
+        let (patched, reports) = apply_hunks(drifted, hunks, 0).unwrap();
+        assert_eq!(patched, "intro\nnew 1\nnew 2\nalpha\nBETA\ngamma\n");
+        assert_eq!(reports[0].line, Some(4));
+        assert_eq!(reports[0].offset, 2);
+
+        // Trailing context changed: needs fuzz 1.
+        let edited = "intro\nalpha\nbeta\nGAMMA!\n";
+        assert!(apply_hunks(edited, hunks, 0).is_err());
+        let (patched, reports) = apply_hunks(edited, hunks, 1).unwrap();
+        assert_eq!(patched, "intro\nalpha\nBETA\nGAMMA!\n");
+        assert_eq!(reports[0].fuzz, 1);
+
+        let reports = apply_hunks("nothing here\n", hunks, 2).unwrap_err();
+        assert!(!reports[0].applied);
+        assert!(reports[0].error.is_some());
+    }
+
+    #[test]
+    fn parses_new_files_and_blank_context_lines() {
+        let patch = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1,3 +1,3 @@\n one\n\n-two\n+2\n--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1,2 @@\n+hello\n+world\n";
+        let files = parse_patch(patch).unwrap();
+        assert_eq!(files.len(), 2);
+        let (patched, _) = apply_hunks("one\n\ntwo\n", &files[0].hunks, 0).unwrap();
+        assert_eq!(patched, "one\n\n2\n");
+        assert_eq!(files[1].old_path, None);
+        let (created, _) = apply_hunks("", &files[1].hunks, 0).unwrap();
+        assert_eq!(created, "hello\nworld\n");
+        assert_eq!(patch_paths(patch), vec!["a.txt", "new.txt"]);
+    }
+}
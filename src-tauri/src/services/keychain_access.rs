@@ -162,6 +162,19 @@ impl KeychainAccessService {
         Ok(())
     }
 
+    pub fn delete_blocking(&self, key: &str) -> KeychainOpResult<()> {
+        self.with_retry_blocking(&format!("delete:{key}"), || {
+            let manager = KeychainManager::new();
+            manager.delete_key(key).map_err(Self::classify_error)
+        })?;
+
+        self.cache
+            .lock()
+            .map_err(|_| Self::poisoned())?
+            .insert(key.to_string(), None);
+        Ok(())
+    }
+
     pub async fn delete(&self, key: &str) -> KeychainOpResult<()> {
         let key_owned = key.to_string();
         self.with_retry(&format!("delete:{key}"), move || {
@@ -1,7 +1,10 @@
 use crate::ai::specs::manifest::{AgentSpec, AirlockToolPolicy};
 use crate::models::neural::ToolAccessPolicy;
+use crate::services::process_sandbox::ShellSandboxPolicy;
 use crate::services::workspace::{WorkspaceManager, WorkspacePermissions};
-use crate::services::{settings::SettingsManager, MateLaunchpadService, Workspace};
+use crate::services::{
+    settings::SettingsManager, AirlockPolicyEngine, MateLaunchpadService, Workspace,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
 use std::path::Path;
@@ -16,6 +19,7 @@ const READ_TOOLS: &[&str] = &[
     "file_exists",
     "get_file_info",
     "search_files",
+    "search_code",
     "ingest_document",
     "git_status",
     "git_diff",
@@ -40,6 +44,8 @@ const READ_TOOLS: &[&str] = &[
 const WRITE_TOOLS: &[&str] = &[
     "write_file",
     "append_file",
+    "edit_file",
+    "apply_patch",
     "mkdir",
     "pdf_create",
     "excel_write",
@@ -63,6 +69,11 @@ const CREATE_AGENT_TOOLS: &[&str] = &[
 
 const EXECUTE_TOOLS: &[&str] = &[
     "execute_command",
+    "start_process",
+    "read_process_output",
+    "write_process_stdin",
+    "wait_process",
+    "kill_process",
     "browse_url",
     "open_new_tab",
     "click_element",
@@ -84,6 +95,9 @@ pub struct EffectiveLocalAgentPolicy {
     pub tool_access_policy_source: String,
     pub notifications_enabled: bool,
     pub can_create_agents: bool,
+    /// Isolation applied to `execute_command`; network follows the tool policy.
+    #[serde(default)]
+    pub shell_sandbox: ShellSandboxPolicy,
 }
 
 pub struct LocalAgentSecurityService;
@@ -140,6 +154,17 @@ impl LocalAgentSecurityService {
                 Self::merge_tool_policy(tool_access_policy, &spec.airlock.tool_policy);
             source.push_str("+spec");
         }
+        let policy_denied = Self::policy_denied_tools(
+            allowed_paths.first().map(Path::new),
+            workspace_id,
+            spec.map(|spec| spec.id.as_str()),
+        );
+        if !policy_denied.is_empty() {
+            let mut deny: BTreeSet<String> = tool_access_policy.deny.into_iter().collect();
+            deny.extend(policy_denied);
+            tool_access_policy.deny = deny.into_iter().collect();
+            source.push_str("+policy");
+        }
         if let Some(workspace) = resolved_workspace.as_ref() {
             let (launchpad_policy, launchpad_source) =
                 MateLaunchpadService::constrain_tool_policy_for_workspace(
@@ -150,6 +175,12 @@ impl LocalAgentSecurityService {
             source.push_str(&launchpad_source);
         }
 
+        let shell_sandbox = resolved_workspace
+            .as_ref()
+            .map(|workspace| workspace.settings.sandbox.clone())
+            .unwrap_or_default()
+            .for_tool_policy(Some(&tool_access_policy));
+
         EffectiveLocalAgentPolicy {
             workspace_id: workspace_id.to_string(),
             allowed_paths,
@@ -161,9 +192,29 @@ impl LocalAgentSecurityService {
             notifications_enabled: workspace_notifications
                 && settings.get_settings().notifications_enabled,
             can_create_agents: workspace_permissions.can_create_agents,
+            shell_sandbox,
         }
     }
 
+    /// Tools denied outright by Airlock policy rules that don't depend on call
+    /// arguments. Argument-scoped rules are enforced per call by the Airlock.
+    fn policy_denied_tools(
+        workspace_root: Option<&Path>,
+        workspace_id: &str,
+        agent_spec_id: Option<&str>,
+    ) -> Vec<String> {
+        let registered = crate::services::SkillExecutor::get_registered_tool_definitions();
+        let names: Vec<&str> = registered
+            .iter()
+            .map(|tool| tool.function.name.as_str())
+            .collect();
+        AirlockPolicyEngine::load(workspace_root).static_denied_tools(
+            &names,
+            Some(workspace_id),
+            agent_spec_id,
+        )
+    }
+
     pub fn tool_policy_from_permissions(permissions: &WorkspacePermissions) -> ToolAccessPolicy {
         let mut deny = BTreeSet::new();
 
@@ -404,6 +455,8 @@ mod tests {
                 language: "en".to_string(),
                 auto_save: true,
                 notifications_enabled: true,
+                command_policy: Default::default(),
+                sandbox: Default::default(),
             },
             launchpad: WorkspaceLaunchSettings {
                 trust_preset: "balanced".to_string(),
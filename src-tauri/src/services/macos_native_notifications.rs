@@ -251,10 +251,22 @@ impl MacOSNativeNotificationBridge {
                     if let Some(airlock) = guard.as_ref() {
                         match action.as_str() {
                             "approve" => {
-                                let _ = airlock.respond_to_approval(command_id, true).await;
+                                let _ = airlock
+                                    .respond_to_approval(
+                                        command_id,
+                                        true,
+                                        crate::services::ApprovalScope::Once,
+                                    )
+                                    .await;
                             }
                             "reject" => {
-                                let _ = airlock.respond_to_approval(command_id, false).await;
+                                let _ = airlock
+                                    .respond_to_approval(
+                                        command_id,
+                                        false,
+                                        crate::services::ApprovalScope::Once,
+                                    )
+                                    .await;
                             }
                             _ => {}
                         }
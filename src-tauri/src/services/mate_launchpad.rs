@@ -10,6 +10,11 @@ const MAX_RECENT_RUNS: usize = 8;
 
 const EXECUTE_RISK_TOOLS: &[&str] = &[
     "execute_command",
+    "start_process",
+    "read_process_output",
+    "write_process_stdin",
+    "wait_process",
+    "kill_process",
     "browse_url",
     "open_new_tab",
     "click_element",
@@ -43,6 +48,7 @@ const REPO_GUARDIAN_TOOLS: &[&str] = &[
     "list_files",
     "list_files_detailed",
     "search_files",
+    "search_code",
     "file_exists",
     "get_file_info",
     "git_status",
@@ -1032,10 +1038,17 @@ fn summarize_planned_actions(tool_ids: &[String]) -> WorkspacePlannedActionSumma
 
     for tool in tool_ids {
         match tool.as_str() {
-            "write_file" | "append_file" | "mkdir" | "pdf_create" | "excel_write"
-            | "docx_create" | "archive_create" => create_or_update.push(tool.clone()),
+            "write_file" | "append_file" | "edit_file" | "apply_patch" | "mkdir" | "pdf_create"
+            | "excel_write" | "docx_create" | "archive_create" => {
+                create_or_update.push(tool.clone())
+            }
             "move_file" | "delete_file" => move_or_delete.push(tool.clone()),
             "execute_command"
+            | "start_process"
+            | "read_process_output"
+            | "write_process_stdin"
+            | "wait_process"
+            | "kill_process"
             | "browse_url"
             | "open_new_tab"
             | "click_element"
@@ -1211,6 +1224,8 @@ mod tests {
                 language: "en".to_string(),
                 auto_save: true,
                 notifications_enabled: true,
+                command_policy: Default::default(),
+                sandbox: Default::default(),
             },
             launchpad: WorkspaceLaunchSettings {
                 trust_preset: "balanced".to_string(),
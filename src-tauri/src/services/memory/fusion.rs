@@ -0,0 +1,231 @@
+//! Hybrid ranking: reciprocal-rank fusion of vector and keyword candidates,
+//! recency/importance boosts, and an optional local rerank pass.
+
+use crate::services::memory::RetrievalScores;
+use crate::services::memory_vault::lexical::tokenize;
+use crate::services::memory_vault::types::DecryptedMemoryEntry;
+use std::collections::{HashMap, HashSet};
+
+/// Standard RRF damping constant; keeps one list's top hit from dominating.
+const RRF_K: f64 = 60.0;
+const FUSION_WEIGHT: f64 = 0.65;
+const IMPORTANCE_WEIGHT: f64 = 0.15;
+const RECENCY_WEIGHT: f64 = 0.10;
+const CATEGORY_WEIGHT: f64 = 0.10;
+/// Share of the final score given to the reranker when it runs.
+const RERANK_WEIGHT: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct FusedCandidate {
+    pub entry: DecryptedMemoryEntry,
+    pub scores: RetrievalScores,
+}
+
+/// Merge ranked vector hits (by ascending distance) and keyword hits (by
+/// descending BM25) into one list, best first.
+pub fn fuse(
+    vector: Vec<(DecryptedMemoryEntry, f32)>,
+    lexical: Vec<(DecryptedMemoryEntry, f64)>,
+    now: i64,
+) -> Vec<FusedCandidate> {
+    let lists_ran = usize::from(!vector.is_empty()) + usize::from(!lexical.is_empty());
+    let mut merged: HashMap<String, FusedCandidate> = HashMap::new();
+
+    for (idx, (entry, distance)) in vector.into_iter().enumerate() {
+        let candidate = merged
+            .entry(entry.id.clone())
+            .or_insert_with(|| new_candidate(entry));
+        candidate.scores.vector_rank = Some(idx + 1);
+        candidate.scores.vector_distance = Some(distance);
+    }
+    for (idx, (entry, score)) in lexical.into_iter().enumerate() {
+        let candidate = merged
+            .entry(entry.id.clone())
+            .or_insert_with(|| new_candidate(entry));
+        candidate.scores.lexical_rank = Some(idx + 1);
+        candidate.scores.lexical_score = Some(score);
+    }
+
+    let best_possible = lists_ran.max(1) as f64 / (RRF_K + 1.0);
+    let mut fused = merged.into_values().collect::<Vec<_>>();
+    for candidate in &mut fused {
+        let scores = &mut candidate.scores;
+        let rrf = [scores.vector_rank, scores.lexical_rank]
+            .into_iter()
+            .flatten()
+            .map(|rank| 1.0 / (RRF_K + rank as f64))
+            .sum::<f64>();
+        scores.fusion = (rrf / best_possible).clamp(0.0, 1.0);
+        scores.recency = recency_score(candidate.entry.created_at, now);
+        scores.score = FUSION_WEIGHT * scores.fusion
+            + IMPORTANCE_WEIGHT * scores.importance
+            + RECENCY_WEIGHT * scores.recency
+            + CATEGORY_WEIGHT * scores.category;
+    }
+    sort_by_score(&mut fused);
+    fused
+}
+
+fn new_candidate(entry: DecryptedMemoryEntry) -> FusedCandidate {
+    FusedCandidate {
+        scores: RetrievalScores {
+            entry_id: entry.id.clone(),
+            vector_rank: None,
+            vector_distance: None,
+            lexical_rank: None,
+            lexical_score: None,
+            fusion: 0.0,
+            recency: 0.0,
+            importance: importance_score(&entry),
+            category: category_boost(entry.metadata.get("_category").map(|s| s.as_str())),
+            rerank: None,
+            score: 0.0,
+        },
+        entry,
+    }
+}
+
+/// Re-score the first `top_n` candidates against the decrypted text: how many
+/// query words each covers and how many query word pairs appear in order.
+/// Everything stays in memory; nothing leaves the machine.
+pub fn rerank(query: &str, candidates: &mut [FusedCandidate], top_n: usize) {
+    let query_tokens = tokenize(query);
+    if query_tokens.is_empty() {
+        return;
+    }
+    let unique_terms = query_tokens.iter().collect::<HashSet<_>>();
+    let query_pairs = query_tokens
+        .windows(2)
+        .map(|pair| (&pair[0], &pair[1]))
+        .collect::<HashSet<_>>();
+
+    let top_n = top_n.min(candidates.len());
+    for candidate in &mut candidates[..top_n] {
+        let tokens = tokenize(&candidate.entry.content);
+        let present = tokens.iter().collect::<HashSet<_>>();
+        let coverage = unique_terms
+            .iter()
+            .filter(|term| present.contains(*term))
+            .count() as f64
+            / unique_terms.len() as f64;
+        let adjacency = if query_pairs.is_empty() {
+            coverage
+        } else {
+            let content_pairs = tokens
+                .windows(2)
+                .map(|pair| (&pair[0], &pair[1]))
+                .collect::<HashSet<_>>();
+            query_pairs
+                .iter()
+                .filter(|pair| content_pairs.contains(*pair))
+                .count() as f64
+                / query_pairs.len() as f64
+        };
+
+        let rerank = 0.7 * coverage + 0.3 * adjacency;
+        let scores = &mut candidate.scores;
+        scores.rerank = Some(rerank);
+        scores.score = (1.0 - RERANK_WEIGHT) * scores.score + RERANK_WEIGHT * rerank;
+    }
+    sort_by_score(&mut candidates[..top_n]);
+}
+
+fn sort_by_score(candidates: &mut [FusedCandidate]) {
+    candidates.sort_by(|a, b| {
+        b.scores
+            .score
+            .partial_cmp(&a.scores.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+pub fn recency_score(created_at: i64, now: i64) -> f64 {
+    let age_seconds = now.saturating_sub(created_at).max(0);
+    let age_hours = age_seconds as f64 / 3600.0;
+    (-age_hours / 168.0).exp().clamp(0.0, 1.0)
+}
+
+pub fn importance_score(entry: &DecryptedMemoryEntry) -> f64 {
+    entry
+        .metadata
+        .get("_importance")
+        .and_then(|v| v.parse::<f64>().ok())
+        .unwrap_or(0.5)
+}
+
+pub fn category_boost(cat: Option<&str>) -> f64 {
+    match cat {
+        Some("preference") => 1.0,
+        Some("correction") => 0.95,
+        Some("fact") => 0.7,
+        Some("procedure") => 0.5,
+        // "observation" and anything unlabelled
+        _ => 0.2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::memory_vault::MemorySensitivity;
+
+    fn entry(id: &str, content: &str, created_at: i64) -> DecryptedMemoryEntry {
+        DecryptedMemoryEntry {
+            id: id.to_string(),
+            workspace_id: "ws".to_string(),
+            content: content.to_string(),
+            tags: Vec::new(),
+            source: "test".to_string(),
+            sensitivity: MemorySensitivity::Internal,
+            created_at,
+            last_accessed: created_at,
+            access_count: 0,
+            metadata: HashMap::new(),
+            embedding: None,
+            embedding_model: None,
+            embedding_provider: None,
+            embedding_dim: None,
+        }
+    }
+
+    #[test]
+    fn entries_found_by_both_signals_outrank_single_signal_hits() {
+        let now = 1_000_000;
+        let vector = vec![
+            (entry("vector-only", "semantic neighbour", now), 0.1),
+            (entry("both", "deploy to staging", now), 0.2),
+        ];
+        let lexical = vec![
+            (entry("lexical-only", "staging deploy notes", now), 3.0),
+            (entry("both", "deploy to staging", now), 2.0),
+        ];
+
+        let fused = fuse(vector, lexical, now);
+        assert_eq!(fused.len(), 3);
+        assert_eq!(fused[0].entry.id, "both");
+        assert_eq!(fused[0].scores.vector_rank, Some(2));
+        assert_eq!(fused[0].scores.lexical_rank, Some(2));
+        assert!(fused[0].scores.fusion > fused[1].scores.fusion);
+
+        let lexical_only = fuse(Vec::new(), vec![(entry("a", "x", now), 1.0)], now);
+        assert!((lexical_only[0].scores.fusion - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rerank_promotes_candidates_covering_the_query() {
+        let now = 1_000_000;
+        let vector = vec![
+            (entry("loose", "the vault has keys", now), 0.1),
+            (
+                entry("exact", "rotate the vault master key monthly", now),
+                0.2,
+            ),
+        ];
+        let mut fused = fuse(vector, Vec::new(), now);
+        assert_eq!(fused[0].entry.id, "loose");
+
+        rerank("rotate master key", &mut fused, 10);
+        assert_eq!(fused[0].entry.id, "exact");
+        assert!(fused[0].scores.rerank > fused[1].scores.rerank);
+    }
+}
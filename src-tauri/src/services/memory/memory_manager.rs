@@ -1,24 +1,39 @@
 use crate::services::embedder::{EmbedderService, EmbeddingTaskType};
 use crate::services::memory::{
-    IngestionResult, MemoryEntry, MemoryError, MemoryStats, SemanticRetrievalMode,
+    fusion, IngestionResult, MemoryEntry, MemoryError, MemoryStats, SemanticRetrievalMode,
     SemanticSearchResult,
 };
+use crate::services::memory_vault::archive::{
+    self, ArchiveEntry, ArchiveFilters, ArchiveKey, ConflictPolicy, ImportReport, MemoryArchive,
+};
+use crate::services::memory_vault::governance::{self, MemoryStatus, ReviewDecision};
+use crate::services::memory_vault::key_provider::VaultKeyProvider;
+use crate::services::memory_vault::profiles::EmbeddingProfile;
 use crate::services::memory_vault::{MemorySensitivity, MemoryVaultService, StoreMemoryInput};
-use crate::services::memory_vault::{EMBEDDING_MODEL, EMBEDDING_PROVIDER};
 use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
 const SEMANTIC_SEARCH_TIMEOUT_MS: u64 = 4000;
 
+/// Vector hits, nearest first, and the search mode that produced them
+type VectorCandidates = (
+    Vec<(
+        crate::services::memory_vault::types::DecryptedMemoryEntry,
+        f32,
+    )>,
+    SemanticRetrievalMode,
+);
+
 #[derive(Debug, Clone)]
 pub struct MemoryManager {
     short_term: Arc<RwLock<VecDeque<MemoryEntry>>>,
     short_term_capacity: usize,
     vault_dir: PathBuf,
     vault: Arc<RwLock<Option<Arc<MemoryVaultService>>>>,
-    embedder_cache: Arc<OnceLock<Option<Arc<EmbedderService>>>>,
+    /// Outer `None` until the configured embedder has been resolved
+    embedder_cache: Arc<Mutex<Option<Option<Arc<EmbedderService>>>>>,
 }
 
 impl MemoryManager {
@@ -31,15 +46,17 @@ impl MemoryManager {
             short_term_capacity: short_term_size.max(1),
             vault_dir,
             vault: Arc::new(RwLock::new(None)),
-            embedder_cache: Arc::new(OnceLock::new()),
+            embedder_cache: Arc::new(Mutex::new(None)),
         }
     }
 
     pub async fn init(&self) {
         // Pre-warm embedder cache so the first search doesn't pay Keychain init cost.
-        let _ = self.resolve_gemini_embedder();
+        let embedder = self.resolve_embedder().ok().flatten();
         if let Ok(vault) = self.ensure_vault().await {
-            vault.spawn_reembed_backfill();
+            if let Some(embedder) = embedder {
+                vault.spawn_reembed_backfill(embedder);
+            }
             // Startup safety-net: prune entries older than 365 days across all workspaces.
             let vault_for_prune = vault.clone();
             tokio::spawn(async move {
@@ -124,6 +141,11 @@ impl MemoryManager {
         }
 
         let vault = self.ensure_vault().await?;
+        let embedder = self.resolve_embedder().ok().flatten();
+        let profile = embedder
+            .as_ref()
+            .map(|embedder| embedder.profile().clone())
+            .unwrap_or_else(EmbeddingProfile::default_profile);
 
         vault
             .put(StoreMemoryInput {
@@ -136,9 +158,9 @@ impl MemoryManager {
                 metadata: metadata.clone(),
                 created_at,
                 embedding: None,
-                embedding_model: Some(EMBEDDING_MODEL.to_string()),
-                embedding_provider: Some(EMBEDDING_PROVIDER.to_string()),
-                embedding_dim: Some(crate::services::memory_vault::EMBEDDING_DIM),
+                embedding_model: Some(profile.model.clone()),
+                embedding_provider: Some(profile.provider.clone()),
+                embedding_dim: Some(profile.dim),
                 additional_embeddings: Vec::new(),
             })
             .await
@@ -146,7 +168,7 @@ impl MemoryManager {
 
         // Embed in the background so the write path is non-blocking. The upsert in put()
         // uses INSERT OR REPLACE so the second call simply attaches the embedding.
-        if let Ok(Some(embedder)) = self.resolve_gemini_embedder() {
+        if let Some(embedder) = embedder {
             let vault_bg = vault.clone();
             let workspace_id = workspace_id.to_string();
             tokio::spawn(async move {
@@ -154,6 +176,7 @@ impl MemoryManager {
                     .embed_text_with_task(&content, EmbeddingTaskType::RetrievalDocument)
                     .await
                 {
+                    let embedding_dim = vec.len();
                     let _ = vault_bg
                         .put(StoreMemoryInput {
                             id,
@@ -165,9 +188,9 @@ impl MemoryManager {
                             metadata,
                             created_at,
                             embedding: Some(vec),
-                            embedding_model: Some(EMBEDDING_MODEL.to_string()),
-                            embedding_provider: Some(EMBEDDING_PROVIDER.to_string()),
-                            embedding_dim: Some(crate::services::memory_vault::EMBEDDING_DIM),
+                            embedding_model: Some(profile.model),
+                            embedding_provider: Some(profile.provider),
+                            embedding_dim: Some(embedding_dim),
                             additional_embeddings: Vec::new(),
                         })
                         .await;
@@ -234,6 +257,208 @@ impl MemoryManager {
         vault.delete_by_id(id).await.map_err(MemoryError::Other)
     }
 
+    /// Apply an operator's review verdict to a pending memory. Confirming or
+    /// editing activates it and retires the entries it proposed to supersede;
+    /// an edit is re-stored so the new text is re-embedded and re-indexed.
+    pub async fn review_pending_memory(
+        &self,
+        id: &str,
+        decision: ReviewDecision,
+    ) -> Result<(), MemoryError> {
+        let vault = self.ensure_vault().await?;
+        let entry = vault
+            .get_by_id(id)
+            .await
+            .map_err(MemoryError::Other)?
+            .filter(|entry| MemoryStatus::of(&entry.metadata) == MemoryStatus::Pending)
+            .ok_or_else(|| MemoryError::Other(format!("No pending memory with id {}", id)))?;
+
+        let active = MemoryStatus::Active.as_str().to_string();
+        match decision {
+            ReviewDecision::Reject => {
+                return vault.delete_by_id(id).await.map_err(MemoryError::Other);
+            }
+            ReviewDecision::Confirm => {
+                vault
+                    .update_metadata(id, |metadata| {
+                        metadata.insert(governance::STATUS_KEY.to_string(), active);
+                    })
+                    .await
+                    .map_err(MemoryError::Other)?;
+            }
+            ReviewDecision::Edit { content } => {
+                if content.trim().is_empty() {
+                    return Err(MemoryError::Other(
+                        "Edited memory content cannot be empty".to_string(),
+                    ));
+                }
+                let mut metadata = entry.metadata.clone();
+                metadata.insert(governance::STATUS_KEY.to_string(), active);
+                self.store_workspace_memory(
+                    &entry.workspace_id,
+                    entry.id.clone(),
+                    content,
+                    entry.source.clone(),
+                    entry.tags.clone(),
+                    metadata,
+                    entry.created_at,
+                    entry.sensitivity.clone(),
+                )
+                .await?;
+            }
+        }
+
+        vault
+            .supersede(&governance::superseded_ids(&entry.metadata), id)
+            .await
+            .map_err(MemoryError::Other)?;
+        Ok(())
+    }
+
+    /// Seal the entries of `workspace_id` that `filters` selects into a
+    /// signed, encrypted archive.
+    pub async fn export_workspace_memory(
+        &self,
+        workspace_id: &str,
+        filters: ArchiveFilters,
+        key: ArchiveKey,
+    ) -> Result<MemoryArchive, MemoryError> {
+        let vault = self.ensure_vault().await?;
+        let entries = vault
+            .export_entries(workspace_id, &filters)
+            .await
+            .map_err(MemoryError::Other)?
+            .into_iter()
+            .map(ArchiveEntry::from)
+            .collect::<Vec<_>>();
+        let identity = vault.archive_identity().await.map_err(MemoryError::Other)?;
+        let workspace_id = workspace_id.to_string();
+        // Argon2id is deliberately slow; keep it off the async workers.
+        tokio::task::spawn_blocking(move || {
+            archive::seal(&identity, &workspace_id, filters, &entries, &key)
+        })
+        .await
+        .map_err(|e| MemoryError::Other(format!("Archive export task failed: {}", e)))?
+        .map_err(MemoryError::Other)
+    }
+
+    /// Merge an archive into `target_workspace_id`, or the workspace it was
+    /// exported from. Vectors from the active embedding profile are kept as
+    /// they are; entries embedded with another profile are re-embedded in
+    /// the background.
+    pub async fn import_workspace_memory(
+        &self,
+        memory_archive: MemoryArchive,
+        passphrase: Option<String>,
+        expected_signer: Option<String>,
+        target_workspace_id: Option<String>,
+        policy: ConflictPolicy,
+    ) -> Result<ImportReport, MemoryError> {
+        let vault = self.ensure_vault().await?;
+        let identity = vault.archive_identity().await.map_err(MemoryError::Other)?;
+        let (memory_archive, entries) = tokio::task::spawn_blocking(move || {
+            archive::open(
+                &memory_archive,
+                &identity,
+                passphrase.as_deref(),
+                expected_signer.as_deref(),
+            )
+            .map(|entries| (memory_archive, entries))
+        })
+        .await
+        .map_err(|e| MemoryError::Other(format!("Archive import task failed: {}", e)))?
+        .map_err(MemoryError::Other)?;
+
+        let workspace_id = target_workspace_id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or(memory_archive.header.workspace_id);
+        let workspace_tag = format!("workspace:{}", workspace_id);
+        let embedder = self.resolve_embedder().ok().flatten();
+        let profile = embedder
+            .as_ref()
+            .map(|embedder| embedder.profile().clone())
+            .unwrap_or_else(EmbeddingProfile::default_profile);
+
+        let mut report = ImportReport::default();
+        for entry in entries {
+            let mut id = entry.id;
+            match vault.get_by_id(&id).await.map_err(MemoryError::Other)? {
+                // The id belongs to another workspace here, e.g. when copying
+                // memory between workspaces on one device.
+                Some(existing) if existing.workspace_id != workspace_id => {
+                    id = uuid::Uuid::new_v4().to_string();
+                    report.imported += 1;
+                }
+                Some(existing) => {
+                    let keep_existing = match policy {
+                        ConflictPolicy::Skip => true,
+                        ConflictPolicy::Overwrite => false,
+                        ConflictPolicy::KeepNewest => existing.created_at >= entry.created_at,
+                    };
+                    if keep_existing {
+                        report.skipped += 1;
+                        continue;
+                    }
+                    report.overwritten += 1;
+                }
+                None => report.imported += 1,
+            }
+
+            let embedding = entry.embedding.filter(|embedding| {
+                embedding.profile.provider == profile.provider
+                    && embedding.profile.model == profile.model
+                    && embedding.vector.len() == embedding.profile.dim
+            });
+            if embedding.is_none() {
+                report.reembedding += 1;
+            }
+            let tags = entry
+                .tags
+                .into_iter()
+                .map(|tag| {
+                    if tag.starts_with("workspace:") {
+                        workspace_tag.clone()
+                    } else {
+                        tag
+                    }
+                })
+                .collect();
+
+            vault
+                .put(StoreMemoryInput {
+                    id,
+                    workspace_id: workspace_id.clone(),
+                    content: entry.content,
+                    tags,
+                    source: entry.source,
+                    sensitivity: entry.sensitivity,
+                    metadata: entry.metadata,
+                    created_at: entry.created_at,
+                    embedding_dim: Some(
+                        embedding
+                            .as_ref()
+                            .map_or(profile.dim, |embedding| embedding.vector.len()),
+                    ),
+                    embedding: embedding.map(|embedding| embedding.vector),
+                    embedding_model: Some(profile.model.clone()),
+                    embedding_provider: Some(profile.provider.clone()),
+                    additional_embeddings: Vec::new(),
+                })
+                .await
+                .map_err(MemoryError::Other)?;
+        }
+
+        if report.reembedding > 0 {
+            if let Some(embedder) = embedder {
+                vault
+                    .reembed_missing(embedder)
+                    .await
+                    .map_err(MemoryError::Other)?;
+            }
+        }
+        Ok(report)
+    }
+
     /// Delete vault entries older than `retention_days` for a workspace.
     /// No-op when `retention_days` is 0. Spawned as a background task by the agent runtime.
     pub async fn prune_expired(
@@ -251,6 +476,24 @@ impl MemoryManager {
             .map_err(MemoryError::Other)
     }
 
+    /// Re-encrypt the vault under a fresh master key. Returns the rows rotated.
+    pub async fn rotate_master_key(&self) -> Result<usize, MemoryError> {
+        let vault = self.ensure_vault().await?;
+        vault.rotate_master_key().await.map_err(MemoryError::Other)
+    }
+
+    /// Move the vault master key to a different key provider.
+    pub async fn switch_key_provider(
+        &self,
+        provider: Arc<dyn VaultKeyProvider>,
+    ) -> Result<(), MemoryError> {
+        let vault = self.ensure_vault().await?;
+        vault
+            .adopt_key_provider(provider)
+            .await
+            .map_err(MemoryError::Other)
+    }
+
     pub async fn short_term_size(&self) -> usize {
         let stm = self.short_term.read().await;
         stm.len()
@@ -289,12 +532,18 @@ impl MemoryManager {
             })
             .collect())
     }
+
+    /// Retrieve workspace memory for `query`. `simple_buffer` reads the ring
+    /// buffer only, `vector` ranks embedding neighbours, and `hybrid` (the
+    /// default) fuses vector and BM25 keyword candidates by reciprocal rank.
+    /// `rerank` adds a local rescoring pass over the hybrid candidates.
     pub async fn search_semantic_detailed(
         &self,
         workspace_id: &str,
         query: &str,
         limit: usize,
         strategy: &str,
+        rerank: bool,
     ) -> Result<SemanticSearchResult, MemoryError> {
         // simple_buffer: ring buffer only — no vault I/O, no embedding cost
         if strategy == "simple_buffer" {
@@ -311,84 +560,30 @@ impl MemoryManager {
                 mode: SemanticRetrievalMode::SimpleBuffer,
                 reason: None,
                 confidential_entry_ids: Vec::new(),
+                scores: Vec::new(),
             });
         }
 
-        let embedder = match self.resolve_gemini_embedder() {
-            Ok(Some(embedder)) => embedder,
-            Ok(None) => {
-                let entries = self
-                    .query_workspace_memory(workspace_id, query, limit)
-                    .await?;
-                return Ok(SemanticSearchResult {
-                    entries,
-                    mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some("Missing Gemini embedding API key".to_string()),
-                    confidential_entry_ids: Vec::new(),
-                });
-            }
-            Err(reason) => {
-                let entries = self
-                    .query_workspace_memory(workspace_id, query, limit)
-                    .await?;
-                return Ok(SemanticSearchResult {
-                    entries,
-                    mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some(reason),
-                    confidential_entry_ids: Vec::new(),
-                });
-            }
-        };
-
-        let embed_future = embedder.embed_text_with_task(query, EmbeddingTaskType::RetrievalQuery);
-        let query_embedding = match tokio::time::timeout(
-            std::time::Duration::from_millis(SEMANTIC_SEARCH_TIMEOUT_MS),
-            embed_future,
-        )
-        .await
-        {
-            Ok(Ok(v)) => v,
-            Ok(Err(e)) => {
-                let entries = self
-                    .query_workspace_memory(workspace_id, query, limit)
-                    .await?;
-                return Ok(SemanticSearchResult {
-                    entries,
-                    mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some(format!("Gemini embedding request failed: {}", e)),
-                    confidential_entry_ids: Vec::new(),
-                });
-            }
-            Err(_elapsed) => {
-                let entries = self
-                    .query_workspace_memory(workspace_id, query, limit)
-                    .await?;
-                return Ok(SemanticSearchResult {
-                    entries,
-                    mode: SemanticRetrievalMode::LexicalFallback,
-                    reason: Some("Semantic search timed out; using lexical fallback".to_string()),
-                    confidential_entry_ids: Vec::new(),
-                });
-            }
-        };
-
-        let vault = self.ensure_vault().await?;
-        let (rows, mode) = vault
-            .search_workspace_vector_with_mode(workspace_id, &query_embedding, limit.max(1))
-            .await
-            .map_err(MemoryError::Other)?;
-
-        let mode = match mode {
-            crate::services::memory_vault::service::VectorSearchMode::Ann => {
-                SemanticRetrievalMode::Ann
-            }
-            crate::services::memory_vault::service::VectorSearchMode::Exact => {
-                SemanticRetrievalMode::Exact
-            }
-        };
+        let limit = limit.max(1);
 
         // vector strategy: skip lexical, score by semantic + recency + access only
         if strategy == "vector" {
+            let (rows, mode) = match self.vector_candidates(workspace_id, query, limit).await? {
+                Ok(found) => found,
+                Err(reason) => {
+                    let entries = self
+                        .query_workspace_memory(workspace_id, query, limit)
+                        .await?;
+                    return Ok(SemanticSearchResult {
+                        entries,
+                        mode: SemanticRetrievalMode::LexicalFallback,
+                        reason: Some(reason),
+                        confidential_entry_ids: Vec::new(),
+                        scores: Vec::new(),
+                    });
+                }
+            };
+
             let now = chrono::Utc::now().timestamp();
             let mut merged: HashMap<
                 String,
@@ -399,15 +594,12 @@ impl MemoryManager {
             > = HashMap::new();
 
             for (entry, distance) in rows {
-                let recency = recency_score(entry.created_at, now);
+                let recency = fusion::recency_score(entry.created_at, now);
                 let access = access_score(entry.access_count);
                 let semantic = semantic_score(distance);
-                let importance = entry
-                    .metadata
-                    .get("_importance")
-                    .and_then(|v| v.parse::<f64>().ok())
-                    .unwrap_or(0.5);
-                let cat_boost = category_boost(entry.metadata.get("_category").map(|s| s.as_str()));
+                let importance = fusion::importance_score(&entry);
+                let cat_boost =
+                    fusion::category_boost(entry.metadata.get("_category").map(|s| s.as_str()));
                 let score = 0.55 * semantic
                     + 0.13 * recency
                     + 0.05 * access
@@ -424,7 +616,7 @@ impl MemoryManager {
             });
 
             let mut confidential_entry_ids = Vec::new();
-            for (_score, entry) in ranked.iter().take(limit.max(1)) {
+            for (_score, entry) in ranked.iter().take(limit) {
                 if matches!(entry.sensitivity, MemorySensitivity::Confidential) {
                     confidential_entry_ids.push(entry.id.clone());
                 }
@@ -433,99 +625,120 @@ impl MemoryManager {
             return Ok(SemanticSearchResult {
                 entries: ranked
                     .into_iter()
-                    .take(limit.max(1))
-                    .map(|(_score, entry)| MemoryEntry {
-                        id: entry.id,
-                        content: entry.content,
-                        embedding: None,
-                        timestamp: chrono::DateTime::from_timestamp(entry.created_at, 0)
-                            .unwrap_or_else(chrono::Utc::now),
-                        tags: entry.tags,
-                    })
+                    .take(limit)
+                    .map(|(_score, entry)| vault_entry_to_memory(entry))
                     .collect(),
                 mode,
                 reason: None,
                 confidential_entry_ids,
+                scores: Vec::new(),
             });
         }
 
-        // hybrid (default): vector + lexical merged by scoring
+        // hybrid (default): vector and keyword candidates fused by rank.
+        // Without an embedder the keyword list alone is ranked the same way.
+        let candidate_limit = limit.saturating_mul(4).max(20);
+        let (vector_rows, mode, reason) = match self
+            .vector_candidates(workspace_id, query, candidate_limit)
+            .await?
+        {
+            Ok((rows, mode)) => (rows, mode, None),
+            Err(reason) => (
+                Vec::new(),
+                SemanticRetrievalMode::LexicalFallback,
+                Some(reason),
+            ),
+        };
+        let vault = self.ensure_vault().await?;
         let lexical_rows = vault
-            .search_workspace(workspace_id, query, limit.saturating_mul(3).max(20))
+            .search_workspace_lexical(workspace_id, query, candidate_limit)
             .await
             .map_err(MemoryError::Other)?;
 
-        let query_tokens = normalize_query_tokens(query);
-        let now = chrono::Utc::now().timestamp();
-        let mut merged: HashMap<
-            String,
-            (
-                f64,
-                crate::services::memory_vault::types::DecryptedMemoryEntry,
-            ),
-        > = HashMap::new();
-
-        for (entry, distance) in rows {
-            let lexical = lexical_overlap_score(&query_tokens, &entry.content);
-            let recency = recency_score(entry.created_at, now);
-            let access = access_score(entry.access_count);
-            let semantic = semantic_score(distance);
-            let importance = entry
-                .metadata
-                .get("_importance")
-                .and_then(|v| v.parse::<f64>().ok())
-                .unwrap_or(0.5);
-            let cat_boost = category_boost(entry.metadata.get("_category").map(|s| s.as_str()));
-            let score = 0.50 * semantic
-                + 0.12 * lexical
-                + 0.12 * recency
-                + 0.04 * access
-                + 0.12 * importance
-                + 0.10 * cat_boost;
-            upsert_ranked_entry(&mut merged, entry, score);
-        }
-
-        for entry in lexical_rows {
-            let lexical = lexical_overlap_score(&query_tokens, &entry.content);
-            let recency = recency_score(entry.created_at, now);
-            let access = access_score(entry.access_count);
-            let score = 0.70 * lexical + 0.20 * recency + 0.10 * access;
-            upsert_ranked_entry(&mut merged, entry, score);
+        let mut ranked = fusion::fuse(vector_rows, lexical_rows, chrono::Utc::now().timestamp());
+        if rerank {
+            fusion::rerank(query, &mut ranked, candidate_limit);
         }
+        ranked.truncate(limit);
 
-        let mut ranked = merged.into_values().collect::<Vec<_>>();
-        ranked.sort_by(|(a_score, _), (b_score, _)| {
-            b_score
-                .partial_cmp(a_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        let mut confidential_entry_ids = Vec::new();
-        for (_score, entry) in ranked.iter().take(limit.max(1)) {
-            if matches!(entry.sensitivity, MemorySensitivity::Confidential) {
-                confidential_entry_ids.push(entry.id.clone());
-            }
-        }
+        let confidential_entry_ids = ranked
+            .iter()
+            .filter(|c| matches!(c.entry.sensitivity, MemorySensitivity::Confidential))
+            .map(|c| c.entry.id.clone())
+            .collect();
+        let scores = ranked.iter().map(|c| c.scores.clone()).collect();
 
         Ok(SemanticSearchResult {
             entries: ranked
                 .into_iter()
-                .take(limit.max(1))
-                .map(|(_score, entry)| MemoryEntry {
-                    id: entry.id,
-                    content: entry.content,
-                    embedding: None,
-                    timestamp: chrono::DateTime::from_timestamp(entry.created_at, 0)
-                        .unwrap_or_else(chrono::Utc::now),
-                    tags: entry.tags,
-                })
+                .map(|c| vault_entry_to_memory(c.entry))
                 .collect(),
             mode,
-            reason: None,
+            reason,
             confidential_entry_ids,
+            scores,
         })
     }
 
+    /// Nearest vault neighbours of `query` under the configured embedder. The
+    /// inner `Err` explains why vector search could not run, so callers can
+    /// fall back to keywords.
+    async fn vector_candidates(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Result<VectorCandidates, String>, MemoryError> {
+        let embedder = match self.resolve_embedder() {
+            Ok(Some(embedder)) => embedder,
+            Ok(None) => return Ok(Err("Missing embedding API key".to_string())),
+            Err(reason) => return Ok(Err(reason)),
+        };
+
+        let embed_future = embedder.embed_text_with_task(query, EmbeddingTaskType::RetrievalQuery);
+        let query_embedding = match tokio::time::timeout(
+            std::time::Duration::from_millis(SEMANTIC_SEARCH_TIMEOUT_MS),
+            embed_future,
+        )
+        .await
+        {
+            Ok(Ok(v)) => v,
+            Ok(Err(e)) => {
+                return Ok(Err(format!(
+                    "{} embedding request failed: {}",
+                    embedder.profile().provider,
+                    e
+                )))
+            }
+            Err(_elapsed) => {
+                return Ok(Err(
+                    "Semantic search timed out; using lexical fallback".to_string()
+                ))
+            }
+        };
+
+        let vault = self.ensure_vault().await?;
+        let (rows, mode) = vault
+            .search_workspace_vector_with_mode(
+                workspace_id,
+                &query_embedding,
+                limit,
+                &embedder.search_models(),
+            )
+            .await
+            .map_err(MemoryError::Other)?;
+
+        let mode = match mode {
+            crate::services::memory_vault::service::VectorSearchMode::Ann => {
+                SemanticRetrievalMode::Ann
+            }
+            crate::services::memory_vault::service::VectorSearchMode::Exact => {
+                SemanticRetrievalMode::Exact
+            }
+        };
+        Ok(Ok((rows, mode)))
+    }
+
     pub async fn ingest_text_detailed(
         &self,
         workspace_id: &str,
@@ -535,12 +748,11 @@ impl MemoryManager {
     ) -> Result<IngestionResult, MemoryError> {
         let vault = self.ensure_vault().await?;
 
-        let embedder = self.resolve_gemini_embedder().map_err(MemoryError::Other)?;
+        let embedder = self.resolve_embedder().map_err(MemoryError::Other)?;
         let mut warnings = Vec::new();
         if embedder.is_none() {
             warnings.push(
-                "Gemini embedding API key unavailable; storing chunks without embeddings"
-                    .to_string(),
+                "Embedding API key unavailable; storing chunks without embeddings".to_string(),
             );
         }
 
@@ -577,9 +789,16 @@ impl MemoryManager {
         }
 
         let chunk_count = chunks.len();
-        let active_model = crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.model;
-        let fallback_model =
-            crate::services::memory_vault::profiles::FALLBACK_EMBEDDING_PROFILE.model;
+        let active = embedder
+            .as_ref()
+            .map(|e| e.profile().clone())
+            .unwrap_or_else(EmbeddingProfile::default_profile);
+        let active_model = active.model.as_str();
+        let fallback_model = embedder
+            .as_ref()
+            .and_then(|e| e.fallback_profile())
+            .map(|profile| profile.model.as_str())
+            .unwrap_or(active_model);
         let mut primary_embeddings: Vec<Option<Vec<f32>>> = vec![None; chunk_count];
         let mut primary_models: Vec<String> = vec![active_model.to_string(); chunk_count];
         let mut fallback_embeddings: Vec<Option<Vec<f32>>> = vec![None; chunk_count];
@@ -673,10 +892,10 @@ impl MemoryManager {
             if let Some(extra) = fallback_embeddings[idx].clone() {
                 additional_embeddings.push(
                     crate::services::memory_vault::AdditionalEmbeddingInput {
+                        embedding_dim: extra.len(),
                         embedding: extra,
                         embedding_model: fallback_model.to_string(),
-                        embedding_provider: EMBEDDING_PROVIDER.to_string(),
-                        embedding_dim: crate::services::memory_vault::EMBEDDING_DIM,
+                        embedding_provider: active.provider.clone(),
                     },
                 );
             }
@@ -703,10 +922,10 @@ impl MemoryManager {
                     sensitivity: MemorySensitivity::Internal,
                     metadata,
                     created_at: now,
+                    embedding_dim: Some(embedding.as_ref().map_or(active.dim, |v| v.len())),
                     embedding,
                     embedding_model: Some(embedding_model),
-                    embedding_provider: Some(EMBEDDING_PROVIDER.to_string()),
-                    embedding_dim: Some(crate::services::memory_vault::EMBEDDING_DIM),
+                    embedding_provider: Some(active.provider.clone()),
                     additional_embeddings,
                 })
                 .await
@@ -719,7 +938,7 @@ impl MemoryManager {
             chunks_ingested: ingested_count,
             chunks_embedded: embedded_count,
             embedding_mode: if embedded_count > 0 {
-                format!("{}:{}", EMBEDDING_PROVIDER, EMBEDDING_MODEL)
+                active.key()
             } else {
                 "none".to_string()
             },
@@ -727,36 +946,31 @@ impl MemoryManager {
         })
     }
 
-    pub fn resolve_gemini_embedder(&self) -> Result<Option<Arc<EmbedderService>>, String> {
-        let cached = self.embedder_cache.get_or_init(|| {
-            let settings = crate::services::settings::SettingsManager::new();
-            let provider_raw = settings.get_embedder_provider().to_string();
-            let provider = match provider_raw.trim().to_lowercase().as_str() {
-                "g" | "google" | "gemini" => EMBEDDING_PROVIDER.to_string(),
-                _ => return None,
-            };
-            let keychain = crate::services::KeychainAccessService::new();
-            let api_key = keychain
-                .get_many_blocking(&[EMBEDDING_PROVIDER, &provider_raw])
-                .ok()
-                .and_then(|values| {
-                    values
-                        .get(EMBEDDING_PROVIDER)
-                        .cloned()
-                        .flatten()
-                        .or_else(|| values.get(&provider_raw).cloned().flatten())
-                })
-                .unwrap_or_default();
-            if api_key.trim().is_empty() {
-                return None;
-            }
-            Some(Arc::new(EmbedderService::new(
-                provider,
-                api_key,
-                Some(EMBEDDING_MODEL.to_string()),
-            )))
-        });
-        Ok(cached.clone())
+    /// The embedder selected in settings; `Ok(None)` when it needs an API key
+    /// that isn't stored. Cached until [`Self::reload_embedder`].
+    pub fn resolve_embedder(&self) -> Result<Option<Arc<EmbedderService>>, String> {
+        let mut cache = self
+            .embedder_cache
+            .lock()
+            .map_err(|_| "Embedder cache lock poisoned".to_string())?;
+        if let Some(cached) = cache.as_ref() {
+            return Ok(cached.clone());
+        }
+        let resolved = EmbedderService::from_settings()?.map(Arc::new);
+        *cache = Some(resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Pick up a changed embedder setting and re-embed the vault for it.
+    pub async fn reload_embedder(&self) -> Result<(), String> {
+        if let Ok(mut cache) = self.embedder_cache.lock() {
+            *cache = None;
+        }
+        let embedder = self.resolve_embedder()?;
+        if let (Some(embedder), Some(vault)) = (embedder, self.get_vault().await) {
+            vault.spawn_reembed_backfill(embedder);
+        }
+        Ok(())
     }
 }
 
@@ -778,35 +992,6 @@ fn derive_source(tags: &[String]) -> String {
         .unwrap_or_else(|| "memory_manager".to_string())
 }
 
-fn normalize_query_tokens(query: &str) -> Vec<String> {
-    query
-        .to_lowercase()
-        .split_whitespace()
-        .map(|s| s.trim_matches(|c: char| !c.is_alphanumeric()).to_string())
-        .filter(|s| !s.is_empty())
-        .collect()
-}
-
-fn lexical_overlap_score(tokens: &[String], content: &str) -> f64 {
-    if tokens.is_empty() {
-        return 0.0;
-    }
-    let haystack = content.to_lowercase();
-    let mut hits = 0usize;
-    for token in tokens {
-        if haystack.contains(token) {
-            hits += 1;
-        }
-    }
-    hits as f64 / tokens.len() as f64
-}
-
-fn recency_score(created_at: i64, now: i64) -> f64 {
-    let age_seconds = now.saturating_sub(created_at).max(0);
-    let age_hours = age_seconds as f64 / 3600.0;
-    (-age_hours / 168.0).exp().clamp(0.0, 1.0)
-}
-
 fn access_score(access_count: i64) -> f64 {
     let v = (access_count.max(0) as f64 + 1.0).ln();
     let norm = (64.0_f64 + 1.0).ln();
@@ -818,13 +1003,16 @@ fn semantic_score(distance: f32) -> f64 {
     (1.0 / (1.0 + d)).clamp(0.0, 1.0)
 }
 
-fn category_boost(cat: Option<&str>) -> f64 {
-    match cat {
-        Some("preference") => 1.0,
-        Some("correction") => 0.95,
-        Some("fact") => 0.7,
-        Some("procedure") => 0.5,
-        Some("observation") | _ => 0.2,
+fn vault_entry_to_memory(
+    entry: crate::services::memory_vault::types::DecryptedMemoryEntry,
+) -> MemoryEntry {
+    MemoryEntry {
+        id: entry.id,
+        content: entry.content,
+        embedding: None,
+        timestamp: chrono::DateTime::from_timestamp(entry.created_at, 0)
+            .unwrap_or_else(chrono::Utc::now),
+        tags: entry.tags,
     }
 }
 
@@ -879,7 +1067,7 @@ mod tests {
 
         // simple_buffer strategy reads from ring buffer without vault I/O
         let result = mm
-            .search_semantic_detailed(workspace_id, "ring buffer", 5, "simple_buffer")
+            .search_semantic_detailed(workspace_id, "ring buffer", 5, "simple_buffer", false)
             .await
             .unwrap();
 
@@ -964,7 +1152,7 @@ mod tests {
         .unwrap();
 
         let result = mm
-            .search_semantic_detailed("workspace-b", "secret", 5, "simple_buffer")
+            .search_semantic_detailed("workspace-b", "secret", 5, "simple_buffer", false)
             .await
             .unwrap();
 
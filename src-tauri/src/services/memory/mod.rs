@@ -3,11 +3,14 @@
 //! Thin compatibility layer used by Tauri commands and the skill executor.
 //! Long-term storage is backed by `memory_vault` (encrypted at rest).
 
+pub mod fusion;
 pub mod memory_manager;
 pub mod types;
 
 pub use memory_manager::MemoryManager;
-pub use types::{IngestionResult, MemoryEntry, SemanticRetrievalMode, SemanticSearchResult};
+pub use types::{
+    IngestionResult, MemoryEntry, RetrievalScores, SemanticRetrievalMode, SemanticSearchResult,
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum MemoryError {
@@ -32,6 +32,28 @@ pub struct SemanticSearchResult {
     pub reason: Option<String>,
     #[serde(default)]
     pub confidential_entry_ids: Vec<String>,
+    /// Per-signal scores for `entries`, in the same order, when fusion ran
+    #[serde(default)]
+    pub scores: Vec<RetrievalScores>,
+}
+
+/// How one hybrid result was scored, for debugging retrieval quality.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RetrievalScores {
+    pub entry_id: String,
+    /// 1-based position among vector candidates
+    pub vector_rank: Option<usize>,
+    pub vector_distance: Option<f32>,
+    /// 1-based position among BM25 keyword candidates
+    pub lexical_rank: Option<usize>,
+    pub lexical_score: Option<f64>,
+    /// Reciprocal-rank fusion, normalized to 0..=1 over the lists that ran
+    pub fusion: f64,
+    pub recency: f64,
+    pub importance: f64,
+    pub category: f64,
+    pub rerank: Option<f64>,
+    pub score: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,624 @@
+//! Portable, encrypted workspace memory archives.
+//!
+//! An archive is one JSON document: a plaintext header (workspace, profiles,
+//! filters and how the payload key is derived), the AES-256-GCM payload of
+//! decrypted entries with the header as associated data, and an Ed25519
+//! signature over both. The payload key comes either from a passphrase via
+//! Argon2id or from ECDH (secp256k1) with a recipient device's public key.
+//!
+//! Each device's signing and recipient keys are derived from its vault master
+//! key, so archives addressed to a device must be imported before that
+//! device's master key is rotated.
+
+use super::key_provider::PassphraseVaultKeyProvider;
+use super::types::{DecryptedMemoryEntry, MemorySensitivity};
+use crate::services::skill_installer::verify_ed25519_signature;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
+use ed25519_dalek::{Signer, SigningKey};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+pub const ARCHIVE_FORMAT: &str = "rainy-memory-archive";
+const ARCHIVE_VERSION: u32 = 1;
+const SIGNING_KEY_CONTEXT: &[u8] = b"rainy-memory-archive-signing-v1";
+const RECIPIENT_KEY_CONTEXT: &[u8] = b"rainy-memory-archive-recipient-v1";
+const ECDH_KEY_CONTEXT: &[u8] = b"rainy-memory-archive-ecdh-v1";
+const ARGON2_M_COST: u32 = 64 * 1024;
+const ARGON2_T_COST: u32 = 3;
+const ARGON2_P_COST: u32 = 1;
+
+/// Which entries go into an archive. Empty lists match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveFilters {
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub sensitivities: Vec<String>,
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+}
+
+impl ArchiveFilters {
+    pub fn matches(&self, entry: &DecryptedMemoryEntry) -> bool {
+        let category = entry
+            .metadata
+            .get("_category")
+            .map(String::as_str)
+            .unwrap_or("observation");
+        (self.categories.is_empty() || self.categories.iter().any(|c| c == category))
+            && (self.sensitivities.is_empty()
+                || self
+                    .sensitivities
+                    .iter()
+                    .any(|s| s == entry.sensitivity.as_str()))
+            && self
+                .created_after
+                .is_none_or(|after| entry.created_at >= after)
+            && self
+                .created_before
+                .is_none_or(|before| entry.created_at <= before)
+    }
+}
+
+/// How the exporter protects the payload key.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(
+    tag = "mode",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ArchiveKey {
+    Passphrase {
+        passphrase: String,
+    },
+    /// Hex SEC1 public key from the receiving device's vault.
+    Recipient {
+        public_key: String,
+    },
+}
+
+/// What an importer does with an entry whose id is already in the workspace.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Keep whichever copy was written last.
+    KeepNewest,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Entries new to the workspace.
+    pub imported: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+    /// Entries whose vectors came from another profile and are being re-embedded.
+    pub reembedding: usize,
+}
+
+/// Public halves of this device's archive keys, for sharing with teammates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveDeviceKeys {
+    pub signer_public_key: String,
+    pub recipient_public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(
+    tag = "scheme",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+pub enum ArchiveKeyEnvelope {
+    Passphrase {
+        kdf: String,
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+        salt: String,
+    },
+    Recipient {
+        recipient_public_key: String,
+        ephemeral_public_key: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveProfile {
+    pub provider: String,
+    pub model: String,
+    pub dim: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveHeader {
+    pub workspace_id: String,
+    pub exported_at: i64,
+    pub entry_count: usize,
+    /// Profiles of the embeddings carried in the payload.
+    pub embedding_profiles: Vec<ArchiveProfile>,
+    pub filters: ArchiveFilters,
+    pub encryption: ArchiveKeyEnvelope,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryArchive {
+    pub format: String,
+    pub version: u32,
+    pub header: ArchiveHeader,
+    pub nonce: String,
+    pub payload: String,
+    /// Hex Ed25519 key of the exporting device.
+    pub signer_public_key: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEmbedding {
+    pub profile: ArchiveProfile,
+    pub vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub id: String,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub source: String,
+    pub sensitivity: MemorySensitivity,
+    pub created_at: i64,
+    pub metadata: HashMap<String, String>,
+    pub embedding: Option<ArchiveEmbedding>,
+}
+
+impl From<DecryptedMemoryEntry> for ArchiveEntry {
+    fn from(entry: DecryptedMemoryEntry) -> Self {
+        let embedding = match (
+            entry.embedding,
+            entry.embedding_model,
+            entry.embedding_provider,
+        ) {
+            (Some(vector), Some(model), Some(provider)) => Some(ArchiveEmbedding {
+                profile: ArchiveProfile {
+                    provider,
+                    model,
+                    dim: vector.len(),
+                },
+                vector,
+            }),
+            _ => None,
+        };
+        Self {
+            id: entry.id,
+            content: entry.content,
+            tags: entry.tags,
+            source: entry.source,
+            sensitivity: entry.sensitivity,
+            created_at: entry.created_at,
+            metadata: entry.metadata,
+            embedding,
+        }
+    }
+}
+
+/// A device's archive keys, derived from its vault master key.
+pub struct ArchiveIdentity {
+    signing: SigningKey,
+    recipient: k256::SecretKey,
+}
+
+impl ArchiveIdentity {
+    pub fn derive(master_key: &[u8]) -> Result<Self, String> {
+        let signing = SigningKey::from_bytes(&context_hash(SIGNING_KEY_CONTEXT, &[master_key]));
+        let recipient =
+            k256::SecretKey::from_slice(&context_hash(RECIPIENT_KEY_CONTEXT, &[master_key]))
+                .map_err(|e| format!("Failed to derive archive recipient key: {}", e))?;
+        Ok(Self { signing, recipient })
+    }
+
+    pub fn signer_public_key(&self) -> String {
+        hex::encode(self.signing.verifying_key().as_bytes())
+    }
+
+    pub fn recipient_public_key(&self) -> String {
+        encode_public_key(&self.recipient.public_key())
+    }
+
+    pub fn device_keys(&self) -> ArchiveDeviceKeys {
+        ArchiveDeviceKeys {
+            signer_public_key: self.signer_public_key(),
+            recipient_public_key: self.recipient_public_key(),
+        }
+    }
+}
+
+/// Encrypt and sign `entries` into an archive.
+pub fn seal(
+    identity: &ArchiveIdentity,
+    workspace_id: &str,
+    filters: ArchiveFilters,
+    entries: &[ArchiveEntry],
+    key: &ArchiveKey,
+) -> Result<MemoryArchive, String> {
+    let (payload_key, encryption) = match key {
+        ArchiveKey::Passphrase { passphrase } => {
+            if passphrase.is_empty() {
+                return Err("Archive passphrase cannot be empty".to_string());
+            }
+            let mut salt = [0u8; 16];
+            rand::rngs::OsRng.fill_bytes(&mut salt);
+            let key = PassphraseVaultKeyProvider::derive_wrapping_key(
+                passphrase,
+                &salt,
+                ARGON2_M_COST,
+                ARGON2_T_COST,
+                ARGON2_P_COST,
+            )?;
+            (
+                key,
+                ArchiveKeyEnvelope::Passphrase {
+                    kdf: "argon2id".to_string(),
+                    m_cost: ARGON2_M_COST,
+                    t_cost: ARGON2_T_COST,
+                    p_cost: ARGON2_P_COST,
+                    salt: BASE64_STANDARD.encode(salt),
+                },
+            )
+        }
+        ArchiveKey::Recipient { public_key } => {
+            let recipient = decode_public_key(public_key)?;
+            let ephemeral = k256::SecretKey::random(&mut rand::rngs::OsRng);
+            let ephemeral_public_key = encode_public_key(&ephemeral.public_key());
+            let recipient_public_key = encode_public_key(&recipient);
+            let key = ecdh_key(
+                &ephemeral,
+                &recipient,
+                &ephemeral_public_key,
+                &recipient_public_key,
+            );
+            (
+                key,
+                ArchiveKeyEnvelope::Recipient {
+                    recipient_public_key,
+                    ephemeral_public_key,
+                },
+            )
+        }
+    };
+
+    let mut embedding_profiles: Vec<ArchiveProfile> = Vec::new();
+    for profile in entries.iter().filter_map(|e| e.embedding.as_ref()) {
+        if !embedding_profiles.contains(&profile.profile) {
+            embedding_profiles.push(profile.profile.clone());
+        }
+    }
+    let header = ArchiveHeader {
+        workspace_id: workspace_id.to_string(),
+        exported_at: chrono::Utc::now().timestamp(),
+        entry_count: entries.len(),
+        embedding_profiles,
+        filters,
+        encryption,
+    };
+    let header_json =
+        serde_json::to_vec(&header).map_err(|e| format!("Failed to encode header: {}", e))?;
+    let plaintext =
+        serde_json::to_vec(entries).map_err(|e| format!("Failed to encode entries: {}", e))?;
+
+    let mut nonce = [0u8; 12];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&payload_key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: &header_json,
+            },
+        )
+        .map_err(|e| format!("Archive encryption failed: {}", e))?;
+
+    let nonce = BASE64_STANDARD.encode(nonce);
+    let payload = BASE64_STANDARD.encode(ciphertext);
+    let signature = identity
+        .signing
+        .sign(&signed_message(&header_json, &nonce, &payload));
+    Ok(MemoryArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        header,
+        nonce,
+        payload,
+        signer_public_key: identity.signer_public_key(),
+        signature: hex::encode(signature.to_bytes()),
+    })
+}
+
+/// Verify and decrypt an archive. `passphrase` is required for passphrase
+/// archives; recipient archives are opened with `identity`. When
+/// `expected_signer` is set the archive must be signed by that key.
+pub fn open(
+    archive: &MemoryArchive,
+    identity: &ArchiveIdentity,
+    passphrase: Option<&str>,
+    expected_signer: Option<&str>,
+) -> Result<Vec<ArchiveEntry>, String> {
+    if archive.format != ARCHIVE_FORMAT || archive.version != ARCHIVE_VERSION {
+        return Err(format!(
+            "Unsupported memory archive ({} v{})",
+            archive.format, archive.version
+        ));
+    }
+    let header_json = serde_json::to_vec(&archive.header)
+        .map_err(|e| format!("Failed to encode header: {}", e))?;
+    if !verify_ed25519_signature(
+        &signed_message(&header_json, &archive.nonce, &archive.payload),
+        &archive.signature,
+        &archive.signer_public_key,
+    ) {
+        return Err("Archive signature is invalid; the file was modified or corrupted".to_string());
+    }
+    if let Some(expected) = expected_signer {
+        if !expected
+            .trim()
+            .eq_ignore_ascii_case(&archive.signer_public_key)
+        {
+            return Err("Archive was not signed by the expected device".to_string());
+        }
+    }
+
+    let payload_key = match &archive.header.encryption {
+        ArchiveKeyEnvelope::Passphrase {
+            kdf,
+            m_cost,
+            t_cost,
+            p_cost,
+            salt,
+        } => {
+            if kdf != "argon2id" {
+                return Err(format!("Unsupported archive key derivation: {}", kdf));
+            }
+            let passphrase = passphrase
+                .filter(|p| !p.is_empty())
+                .ok_or_else(|| "This archive needs its passphrase".to_string())?;
+            let salt = BASE64_STANDARD
+                .decode(salt.as_bytes())
+                .map_err(|e| format!("Corrupt archive salt: {}", e))?;
+            PassphraseVaultKeyProvider::derive_wrapping_key(
+                passphrase, &salt, *m_cost, *t_cost, *p_cost,
+            )?
+        }
+        ArchiveKeyEnvelope::Recipient {
+            recipient_public_key,
+            ephemeral_public_key,
+        } => {
+            if !recipient_public_key.eq_ignore_ascii_case(&identity.recipient_public_key()) {
+                return Err("This archive was encrypted for a different device".to_string());
+            }
+            let ephemeral = decode_public_key(ephemeral_public_key)?;
+            ecdh_key(
+                &identity.recipient,
+                &ephemeral,
+                ephemeral_public_key,
+                recipient_public_key,
+            )
+        }
+    };
+
+    let nonce = BASE64_STANDARD
+        .decode(archive.nonce.as_bytes())
+        .map_err(|e| format!("Corrupt archive nonce: {}", e))?;
+    if nonce.len() != 12 {
+        return Err("Corrupt archive nonce".to_string());
+    }
+    let ciphertext = BASE64_STANDARD
+        .decode(archive.payload.as_bytes())
+        .map_err(|e| format!("Corrupt archive payload: {}", e))?;
+    let plaintext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&payload_key))
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &header_json,
+            },
+        )
+        .map_err(|_| match archive.header.encryption {
+            ArchiveKeyEnvelope::Passphrase { .. } => "Incorrect archive passphrase".to_string(),
+            ArchiveKeyEnvelope::Recipient { .. } => {
+                "Archive payload could not be decrypted".to_string()
+            }
+        })?;
+
+    let entries: Vec<ArchiveEntry> = serde_json::from_slice(&plaintext)
+        .map_err(|e| format!("Corrupt archive entries: {}", e))?;
+    if entries.len() != archive.header.entry_count {
+        return Err(format!(
+            "Archive declares {} entries but holds {}",
+            archive.header.entry_count,
+            entries.len()
+        ));
+    }
+    Ok(entries)
+}
+
+fn signed_message(header_json: &[u8], nonce: &str, payload: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(header_json.len() + nonce.len() + payload.len() + 32);
+    message.extend_from_slice(ARCHIVE_FORMAT.as_bytes());
+    message.extend_from_slice(&ARCHIVE_VERSION.to_le_bytes());
+    message.extend_from_slice(header_json);
+    message.extend_from_slice(nonce.as_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    message
+}
+
+fn context_hash(context: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(context);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// Payload key from the x coordinate of the shared point, bound to both keys.
+fn ecdh_key(
+    secret: &k256::SecretKey,
+    public: &k256::PublicKey,
+    ephemeral_public_key: &str,
+    recipient_public_key: &str,
+) -> [u8; 32] {
+    let shared = k256::AffinePoint::from(public.to_projective() * *secret.to_nonzero_scalar());
+    let shared = shared.to_encoded_point(false);
+    context_hash(
+        ECDH_KEY_CONTEXT,
+        &[
+            shared.x().map(|x| x.as_slice()).unwrap_or_default(),
+            ephemeral_public_key.as_bytes(),
+            recipient_public_key.as_bytes(),
+        ],
+    )
+}
+
+fn encode_public_key(key: &k256::PublicKey) -> String {
+    hex::encode(key.to_encoded_point(true).as_bytes())
+}
+
+fn decode_public_key(hex_key: &str) -> Result<k256::PublicKey, String> {
+    let bytes = hex::decode(hex_key.trim()).map_err(|_| "Invalid recipient public key")?;
+    k256::PublicKey::from_sec1_bytes(&bytes).map_err(|_| "Invalid recipient public key".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, sensitivity: MemorySensitivity) -> ArchiveEntry {
+        ArchiveEntry {
+            id: id.to_string(),
+            content: format!("memory {}", id),
+            tags: vec!["agent_memory".to_string()],
+            source: "distilled".to_string(),
+            sensitivity,
+            created_at: 10,
+            metadata: HashMap::from([("_category".to_string(), "fact".to_string())]),
+            embedding: Some(ArchiveEmbedding {
+                profile: ArchiveProfile {
+                    provider: "local".to_string(),
+                    model: "all-MiniLM-L6-v2".to_string(),
+                    dim: 2,
+                },
+                vector: vec![0.5, -0.5],
+            }),
+        }
+    }
+
+    #[test]
+    fn recipient_archives_open_only_on_the_recipient_device() {
+        let sender = ArchiveIdentity::derive(&[1u8; 32]).unwrap();
+        let receiver = ArchiveIdentity::derive(&[2u8; 32]).unwrap();
+        let key = ArchiveKey::Recipient {
+            public_key: receiver.recipient_public_key(),
+        };
+        let archive = seal(
+            &sender,
+            "ws",
+            ArchiveFilters::default(),
+            &[entry("a", MemorySensitivity::Internal)],
+            &key,
+        )
+        .unwrap();
+        assert_eq!(archive.header.embedding_profiles.len(), 1);
+
+        let opened = open(&archive, &receiver, None, Some(&sender.signer_public_key())).unwrap();
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].content, "memory a");
+        assert_eq!(
+            opened[0].embedding.as_ref().unwrap().vector,
+            vec![0.5, -0.5]
+        );
+
+        assert!(open(&archive, &sender, None, None).is_err());
+        assert!(open(
+            &archive,
+            &receiver,
+            None,
+            Some(&receiver.signer_public_key())
+        )
+        .is_err());
+
+        let mut tampered = archive.clone();
+        tampered.header.workspace_id = "other".to_string();
+        let err = open(&tampered, &receiver, None, None).unwrap_err();
+        assert!(err.contains("signature"));
+    }
+
+    #[test]
+    fn passphrase_archives_need_the_passphrase() {
+        let identity = ArchiveIdentity::derive(&[3u8; 32]).unwrap();
+        let key = ArchiveKey::Passphrase {
+            passphrase: "correct horse".to_string(),
+        };
+        let archive = seal(
+            &identity,
+            "ws",
+            ArchiveFilters::default(),
+            &[entry("a", MemorySensitivity::Public)],
+            &key,
+        )
+        .unwrap();
+
+        let other = ArchiveIdentity::derive(&[4u8; 32]).unwrap();
+        assert_eq!(
+            open(&archive, &other, Some("correct horse"), None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            open(&archive, &other, Some("wrong"), None).unwrap_err(),
+            "Incorrect archive passphrase"
+        );
+        assert!(open(&archive, &other, None, None).is_err());
+    }
+
+    #[test]
+    fn filters_exclude_confidential_entries() {
+        let filters = ArchiveFilters {
+            sensitivities: vec!["public".to_string(), "internal".to_string()],
+            created_after: Some(5),
+            ..Default::default()
+        };
+        let mut decrypted = DecryptedMemoryEntry {
+            id: "a".to_string(),
+            workspace_id: "ws".to_string(),
+            content: "x".to_string(),
+            tags: Vec::new(),
+            source: "distilled".to_string(),
+            sensitivity: MemorySensitivity::Internal,
+            created_at: 10,
+            last_accessed: 10,
+            access_count: 0,
+            metadata: HashMap::new(),
+            embedding: None,
+            embedding_model: None,
+            embedding_provider: None,
+            embedding_dim: None,
+        };
+        assert!(filters.matches(&decrypted));
+        decrypted.sensitivity = MemorySensitivity::Confidential;
+        assert!(!filters.matches(&decrypted));
+        decrypted.sensitivity = MemorySensitivity::Public;
+        decrypted.created_at = 1;
+        assert!(!filters.matches(&decrypted));
+    }
+}
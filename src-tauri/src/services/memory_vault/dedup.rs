@@ -1,12 +1,19 @@
-use super::types::{DistilledMemory, MemoryCategory};
+use super::types::{DecryptedMemoryEntry, DistilledMemory, MemoryCategory};
 use super::MemoryVaultService;
 use std::sync::Arc;
 
 const SKIP_THRESHOLD: f32 = 0.05;
 const UPDATE_THRESHOLD: f32 = 0.15;
+/// Neighbours closer than this are checked for contradictions on insert.
+/// Vector search only returns active entries, so retired facts never match.
+const RELATED_THRESHOLD: f32 = 0.35;
 
 pub enum DedupDecision {
-    Insert(DistilledMemory),
+    Insert {
+        memory: DistilledMemory,
+        /// Close active entries the new memory may contradict.
+        related: Vec<DecryptedMemoryEntry>,
+    },
     Update {
         existing_id: String,
         merged: DistilledMemory,
@@ -28,14 +35,20 @@ impl DedupGate {
         workspace_id: &str,
         distilled: DistilledMemory,
         query_embedding: &[f32],
+        embedding_model: &str,
     ) -> DedupDecision {
         let results = match self
             .vault
-            .search_workspace_vector(workspace_id, query_embedding, 3)
+            .search_workspace_vector(workspace_id, query_embedding, 5, embedding_model)
             .await
         {
             Ok(results) => results,
-            Err(_) => return DedupDecision::Insert(distilled),
+            Err(_) => {
+                return DedupDecision::Insert {
+                    memory: distilled,
+                    related: Vec::new(),
+                }
+            }
         };
 
         for (entry, distance) in &results {
@@ -64,11 +77,20 @@ impl DedupGate {
                         content: distilled.content,
                         category: distilled.category,
                         importance: distilled.importance.max(existing_importance),
+                        provenance: distilled.provenance,
                     },
                 };
             }
         }
 
-        DedupDecision::Insert(distilled)
+        let related = results
+            .into_iter()
+            .filter(|(_, distance)| *distance < RELATED_THRESHOLD)
+            .map(|(entry, _)| entry)
+            .collect();
+        DedupDecision::Insert {
+            memory: distilled,
+            related,
+        }
     }
 }
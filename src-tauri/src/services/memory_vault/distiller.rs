@@ -1,5 +1,6 @@
-use super::types::{DistilledMemory, MemoryCategory, RawMemoryTurn};
-use crate::ai::provider_types::{ChatCompletionRequest, ChatMessage};
+use super::governance::MemoryProvenance;
+use super::types::{DecryptedMemoryEntry, DistilledMemory, MemoryCategory, RawMemoryTurn};
+use crate::ai::provider_types::{AIError, ChatCompletionRequest, ChatMessage};
 use crate::ai::router::IntelligentRouter;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -11,6 +12,7 @@ const READONLY_TOOLS: &[&str] = &[
     "read_many_files",
     "read_file_chunk",
     "search_files",
+    "search_code",
     "file_exists",
     "get_file_info",
     "git_status",
@@ -28,8 +30,13 @@ Rules:
 - FOCUS: user preferences, corrections, decisions, project knowledge, procedures
 - Each fact must be self-contained (understandable without conversation context)
 
-Output JSON array:
-[{"content": "...", "category": "preference|correction|fact|procedure|observation", "importance": 0.0-1.0}]
+Turns are numbered [0], [1], ... in the order given.
+
+Output a JSON object:
+{"memories": [{"content": "...", "category": "preference|correction|fact|procedure|observation", "importance": 0.0-1.0, "confidence": 0.0-1.0, "turn": 0}]}
+
+- confidence: how certain it is that the fact is true and meant to last (1.0 = stated outright by the user, lower for guesses or inferences)
+- turn: number of the turn the fact is drawn from
 
 Categories:
 - preference: User style choices, likes/dislikes ("I prefer tabs over spaces")
@@ -38,7 +45,16 @@ Categories:
 - procedure: How to do things ("To deploy, run scripts/deploy.sh then verify on staging")
 - observation: General context that doesn't fit above
 
-If nothing is worth remembering, return []."#;
+If nothing is worth remembering, return {"memories": []}."#;
+
+const CONTRADICTION_SYSTEM_PROMPT: &str = r#"You compare a NEW memory against numbered EXISTING memories about the same project.
+
+List the existing memories that the new one contradicts: facts it makes false or out of date, or preferences and procedures it replaces. Do NOT list memories that are merely related, more specific, or still compatible with the new one.
+
+Output a JSON object:
+{"contradicted": [0, 2]}
+
+If none are contradicted, return {"contradicted": []}."#;
 
 const TRIVIAL_TURN_PREFIXES: &[&str] = &[
     "hi",
@@ -118,10 +134,10 @@ impl MemoryDistiller {
 
         // Build extraction prompt from turns
         let mut input_block = String::with_capacity(4096);
-        for turn in &meaningful_turns {
+        for (index, turn) in meaningful_turns.iter().enumerate() {
             input_block.push_str(&format!(
-                "[{}] ({}): {}\n\n",
-                turn.role, turn.source, turn.content
+                "[{}] [{}] ({}): {}\n\n",
+                index, turn.role, turn.source, turn.content
             ));
         }
 
@@ -133,30 +149,31 @@ impl MemoryDistiller {
             model: "default".to_string(),
             temperature: Some(0.1),
             max_tokens: Some(1024),
-            json_mode: true,
             ..Default::default()
         };
 
-        let response = self
+        let output = self
             .router
             .read()
             .await
-            .complete(request)
-            .await
-            .map_err(|e| format!("Distillation LLM call failed: {}", e))?;
-
-        let response_text = response.content.unwrap_or_default();
-        let mut memories = parse_distilled_json(&response_text);
-
-        // If parse failed, fail closed.
-        if memories.is_empty() && !response_text.trim().is_empty() {
-            let trimmed = response_text.trim();
-            if trimmed == "[]" {
+            .complete_structured::<DistillationOutput>(request)
+            .await;
+        let mut memories: Vec<DistilledMemory> = match output {
+            Ok(output) => output
+                .memories
+                .into_iter()
+                .map(|raw| raw.into_memory(&meaningful_turns))
+                .collect(),
+            // Fail closed: an unusable payload persists nothing.
+            Err(AIError::SchemaViolation(e)) => {
+                tracing::warn!(
+                    "Memory distillation returned a non-conforming payload; skipping persistence: {}",
+                    e
+                );
                 return Ok(vec![]);
             }
-            tracing::warn!("Memory distillation returned unparsable payload; skipping persistence");
-            return Ok(vec![]);
-        }
+            Err(e) => return Err(format!("Distillation LLM call failed: {}", e)),
+        };
 
         // Apply importance floors
         for mem in &mut memories {
@@ -169,42 +186,118 @@ impl MemoryDistiller {
 
         Ok(memories)
     }
-}
 
-fn parse_distilled_json(text: &str) -> Vec<DistilledMemory> {
-    let trimmed = text.trim();
+    /// Ids of the `candidates` that `memory` contradicts. Candidates are close
+    /// neighbours the dedup gate did not treat as restatements; an unusable
+    /// reply retires nothing.
+    pub async fn find_contradictions(
+        &self,
+        memory: &DistilledMemory,
+        candidates: &[DecryptedMemoryEntry],
+    ) -> Result<Vec<String>, String> {
+        if candidates.is_empty() {
+            return Ok(vec![]);
+        }
 
-    // Try direct parse
-    if let Ok(items) = serde_json::from_str::<Vec<RawDistilled>>(trimmed) {
-        return items.into_iter().map(|r| r.into()).collect();
-    }
+        let mut input_block = format!(
+            "NEW ({}): {}\n\nEXISTING:\n",
+            memory.category.as_str(),
+            memory.content
+        );
+        for (index, entry) in candidates.iter().enumerate() {
+            let category = entry
+                .metadata
+                .get("_category")
+                .map(String::as_str)
+                .unwrap_or("observation");
+            input_block.push_str(&format!("[{}] ({}): {}\n", index, category, entry.content));
+        }
+
+        let request = ChatCompletionRequest {
+            messages: vec![
+                ChatMessage::system(CONTRADICTION_SYSTEM_PROMPT),
+                ChatMessage::user(input_block),
+            ],
+            model: "default".to_string(),
+            temperature: Some(0.0),
+            max_tokens: Some(256),
+            ..Default::default()
+        };
 
-    // Try to find JSON array in the response (LLM might wrap in markdown)
-    if let Some(start) = trimmed.find('[') {
-        if let Some(end) = trimmed.rfind(']') {
-            let slice = &trimmed[start..=end];
-            if let Ok(items) = serde_json::from_str::<Vec<RawDistilled>>(slice) {
-                return items.into_iter().map(|r| r.into()).collect();
+        let output = self
+            .router
+            .read()
+            .await
+            .complete_structured::<ContradictionOutput>(request)
+            .await;
+        match output {
+            Ok(output) => {
+                let mut ids: Vec<String> = output
+                    .contradicted
+                    .into_iter()
+                    .filter_map(|index| candidates.get(index))
+                    .map(|entry| entry.id.clone())
+                    .collect();
+                ids.sort();
+                ids.dedup();
+                Ok(ids)
             }
+            Err(AIError::SchemaViolation(e)) => {
+                tracing::warn!(
+                    "Contradiction check returned a non-conforming payload; retiring nothing: {}",
+                    e
+                );
+                Ok(vec![])
+            }
+            Err(e) => Err(format!("Contradiction check LLM call failed: {}", e)),
         }
     }
+}
 
-    vec![]
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+struct DistillationOutput {
+    memories: Vec<RawDistilled>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, schemars::JsonSchema)]
 struct RawDistilled {
     content: String,
+    /// preference, correction, fact, procedure or observation
     category: String,
+    #[schemars(range(min = 0.0, max = 1.0))]
     importance: f32,
+    #[schemars(range(min = 0.0, max = 1.0))]
+    confidence: f32,
+    /// Number of the turn the fact is drawn from
+    turn: usize,
 }
 
-impl From<RawDistilled> for DistilledMemory {
-    fn from(raw: RawDistilled) -> Self {
-        Self {
-            content: raw.content,
-            category: MemoryCategory::from_str_loose(&raw.category),
-            importance: raw.importance.clamp(0.0, 1.0),
+impl RawDistilled {
+    /// Resolve provenance from the turn the model attributed the fact to,
+    /// falling back to the latest turn when the index is out of range.
+    fn into_memory(self, turns: &[RawMemoryTurn]) -> DistilledMemory {
+        let turn = turns.get(self.turn).or(turns.last());
+        DistilledMemory {
+            content: self.content,
+            category: MemoryCategory::from_str_loose(&self.category),
+            importance: self.importance.clamp(0.0, 1.0),
+            provenance: MemoryProvenance {
+                run_id: turn.and_then(|t| t.run_id.clone()),
+                chat_id: turn.and_then(|t| t.chat_id.clone()),
+                tool: turn
+                    .and_then(|t| t.source.strip_prefix("tool:"))
+                    .map(str::to_string),
+                observed_at: turn
+                    .map(|t| t.timestamp)
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp()),
+                confidence: self.confidence.clamp(0.0, 1.0),
+            },
         }
     }
 }
+
+#[derive(serde::Deserialize, schemars::JsonSchema)]
+struct ContradictionOutput {
+    /// Numbers of the existing memories the new one contradicts
+    contradicted: Vec<usize>,
+}
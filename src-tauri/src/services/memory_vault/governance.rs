@@ -0,0 +1,140 @@
+//! Provenance, review status and supersession for vault entries.
+//!
+//! Everything is kept in the encrypted metadata map under reserved `_` keys,
+//! next to `_category` and `_importance`. Entries written before governance
+//! existed carry no `_status` and count as active.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const STATUS_KEY: &str = "_status";
+/// Id of the entry that retired this one.
+pub const SUPERSEDED_BY_KEY: &str = "_superseded_by";
+/// Comma-separated ids this entry retires, or proposes to retire while pending.
+pub const SUPERSEDES_KEY: &str = "_supersedes";
+pub const CONFIDENCE_KEY: &str = "_confidence";
+pub const SOURCE_RUN_KEY: &str = "_source_run";
+pub const SOURCE_CHAT_KEY: &str = "_source_chat";
+pub const SOURCE_TOOL_KEY: &str = "_source_tool";
+pub const OBSERVED_AT_KEY: &str = "_observed_at";
+
+/// Distilled memories below this confidence wait in the review queue.
+/// Corrections are exempt: the user stated them outright.
+pub const REVIEW_CONFIDENCE: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryStatus {
+    Active,
+    Pending,
+    Superseded,
+}
+
+impl MemoryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Pending => "pending",
+            Self::Superseded => "superseded",
+        }
+    }
+
+    pub fn of(metadata: &HashMap<String, String>) -> Self {
+        match metadata.get(STATUS_KEY).map(String::as_str) {
+            Some("pending") => Self::Pending,
+            Some("superseded") => Self::Superseded,
+            _ => Self::Active,
+        }
+    }
+}
+
+/// Whether retrieval may surface an entry; pending and retired facts stay out.
+pub fn is_retrievable(metadata: &HashMap<String, String>) -> bool {
+    MemoryStatus::of(metadata) == MemoryStatus::Active
+}
+
+/// Where a distilled memory came from and how sure the distiller was.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryProvenance {
+    pub run_id: Option<String>,
+    pub chat_id: Option<String>,
+    pub tool: Option<String>,
+    pub observed_at: i64,
+    pub confidence: f32,
+}
+
+impl MemoryProvenance {
+    pub fn write_to(&self, metadata: &mut HashMap<String, String>) {
+        let optional = [
+            (SOURCE_RUN_KEY, &self.run_id),
+            (SOURCE_CHAT_KEY, &self.chat_id),
+            (SOURCE_TOOL_KEY, &self.tool),
+        ];
+        for (key, value) in optional {
+            match value {
+                Some(value) => metadata.insert(key.to_string(), value.clone()),
+                None => metadata.remove(key),
+            };
+        }
+        metadata.insert(OBSERVED_AT_KEY.to_string(), self.observed_at.to_string());
+        metadata.insert(
+            CONFIDENCE_KEY.to_string(),
+            format!("{:.2}", self.confidence),
+        );
+    }
+}
+
+/// Operator verdict on a pending memory from the review queue.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum ReviewDecision {
+    /// Activate it and apply the supersession it proposed.
+    Confirm,
+    /// Replace its content, then confirm it.
+    Edit { content: String },
+    /// Delete it.
+    Reject,
+}
+
+pub fn superseded_ids(metadata: &HashMap<String, String>) -> Vec<String> {
+    metadata
+        .get(SUPERSEDES_KEY)
+        .map(|ids| {
+            ids.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn set_superseded_ids(metadata: &mut HashMap<String, String>, ids: &[String]) {
+    if ids.is_empty() {
+        metadata.remove(SUPERSEDES_KEY);
+    } else {
+        metadata.insert(SUPERSEDES_KEY.to_string(), ids.join(","));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_entries_stay_retrievable() {
+        let mut metadata = HashMap::new();
+        assert!(is_retrievable(&metadata));
+
+        metadata.insert(STATUS_KEY.to_string(), "pending".to_string());
+        assert!(!is_retrievable(&metadata));
+        metadata.insert(STATUS_KEY.to_string(), "superseded".to_string());
+        assert_eq!(MemoryStatus::of(&metadata), MemoryStatus::Superseded);
+
+        set_superseded_ids(&mut metadata, &["a".to_string(), "b".to_string()]);
+        assert_eq!(superseded_ids(&metadata), vec!["a", "b"]);
+        set_superseded_ids(&mut metadata, &[]);
+        assert!(!metadata.contains_key(SUPERSEDES_KEY));
+    }
+}
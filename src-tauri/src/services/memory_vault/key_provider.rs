@@ -1,11 +1,207 @@
+use crate::services::settings::SettingsManager;
 use crate::services::KeychainAccessService;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use zeroize::Zeroizing;
 
 const VAULT_MASTER_KEY_ID: &str = "memory_vault_master_key_v1";
+const STAGED_MASTER_KEY_ID: &str = "memory_vault_master_key_v1_staged";
+const MASTER_KEY_LEN: usize = 32;
+const KEY_FILE_NAME: &str = "vault.key";
+const WRAPPED_KEY_FILE_NAME: &str = "vault.key.wrapped";
+const WRAPPED_KEY_AAD: &[u8] = b"rainy-vault-master-key-v1";
+
+/// Base64 (or 64-char hex) master key for the env provider.
+pub const VAULT_KEY_ENV: &str = "RAINY_VAULT_KEY";
+/// Passphrase used by the passphrase provider when the vault was not unlocked in-app.
+pub const VAULT_PASSPHRASE_ENV: &str = "RAINY_VAULT_PASSPHRASE";
 
 pub trait VaultKeyProvider: Send + Sync {
     fn get_or_create_master_key(&self) -> Result<Vec<u8>, String>;
+
+    /// Persist `key` as the master key, replacing the current one.
+    fn store_master_key(&self, key: &[u8]) -> Result<(), String>;
+
+    /// Whether `store_master_key` can persist a key that differs from the current one.
+    fn can_store_new_key(&self) -> bool {
+        true
+    }
+
+    /// Persist `key` next to the current master key without replacing it, so
+    /// a rotation interrupted after its rows commit can still be finished.
+    fn stage_master_key(&self, _key: &[u8]) -> Result<(), String> {
+        Err("The current vault key provider cannot stage a new key".to_string())
+    }
+
+    /// Key left by `stage_master_key`, if a rotation did not finish.
+    fn staged_master_key(&self) -> Result<Option<Vec<u8>>, String> {
+        Ok(None)
+    }
+
+    fn clear_staged_master_key(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Where the vault master key lives, selectable in settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VaultKeyProviderKind {
+    /// macOS Keychain item.
+    Keychain,
+    /// Random master key wrapped with an Argon2id-derived passphrase key.
+    Passphrase,
+    /// Plain key file readable only by the current user, for headless machines.
+    File,
+    /// Key supplied through `RAINY_VAULT_KEY`, never persisted by the app.
+    Env,
+}
+
+impl Default for VaultKeyProviderKind {
+    fn default() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::Keychain
+        } else {
+            Self::File
+        }
+    }
+}
+
+impl VaultKeyProviderKind {
+    pub fn is_supported(self) -> bool {
+        self != Self::Keychain || cfg!(target_os = "macos")
+    }
+}
+
+/// Build the provider selected in settings. An unsupported selection falls back
+/// to the platform default so a stale settings file cannot lock the vault.
+pub fn configured_provider() -> Arc<dyn VaultKeyProvider> {
+    let settings = SettingsManager::new();
+    let mut kind = settings.get_vault_key_provider();
+    if !kind.is_supported() {
+        kind = VaultKeyProviderKind::default();
+    }
+    provider_for(
+        kind,
+        &settings.data_dir(),
+        settings.get_vault_key_file().map(PathBuf::from),
+    )
+}
+
+pub fn provider_for(
+    kind: VaultKeyProviderKind,
+    key_dir: &Path,
+    key_file: Option<PathBuf>,
+) -> Arc<dyn VaultKeyProvider> {
+    match kind {
+        VaultKeyProviderKind::Keychain => Arc::new(MacOSKeychainVaultKeyProvider::new()),
+        VaultKeyProviderKind::Passphrase => Arc::new(PassphraseVaultKeyProvider::new(
+            key_dir.join(WRAPPED_KEY_FILE_NAME),
+        )),
+        VaultKeyProviderKind::File => Arc::new(FileVaultKeyProvider::new(
+            key_file.unwrap_or_else(|| key_dir.join(KEY_FILE_NAME)),
+        )),
+        VaultKeyProviderKind::Env => Arc::new(EnvVaultKeyProvider),
+    }
+}
+
+pub fn generate_master_key() -> Vec<u8> {
+    let mut key = vec![0u8; MASTER_KEY_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    key
+}
+
+fn session_passphrase() -> &'static Mutex<Option<Zeroizing<String>>> {
+    static PASSPHRASE: OnceLock<Mutex<Option<Zeroizing<String>>>> = OnceLock::new();
+    PASSPHRASE.get_or_init(|| Mutex::new(None))
+}
+
+/// Master key unwrapped by the passphrase provider, kept for the session so
+/// Argon2 only runs on unlock and rotation.
+struct UnwrappedMasterKey {
+    envelope_path: PathBuf,
+    key: Zeroizing<Vec<u8>>,
+}
+
+fn unwrapped_master_key() -> &'static Mutex<Option<UnwrappedMasterKey>> {
+    static KEY: OnceLock<Mutex<Option<UnwrappedMasterKey>>> = OnceLock::new();
+    KEY.get_or_init(|| Mutex::new(None))
+}
+
+/// Remember the passphrase entered in the app for the rest of the session.
+/// Any change, including `None` to lock the vault, drops the cached master key.
+pub fn set_session_passphrase(passphrase: Option<String>) {
+    if let Ok(mut slot) = session_passphrase().lock() {
+        *slot = passphrase.map(Zeroizing::new);
+    }
+    if let Ok(mut cached) = unwrapped_master_key().lock() {
+        *cached = None;
+    }
+}
+
+/// `path` with `.staged` appended to its file name.
+fn staged_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".staged");
+    path.with_file_name(name)
+}
+
+fn remove_staged_file(path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("Failed to remove {}: {}", path.display(), e))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn decode_master_key(encoded: &str) -> Result<Vec<u8>, String> {
+    let encoded = encoded.trim();
+    let bytes =
+        if encoded.len() == MASTER_KEY_LEN * 2 && encoded.chars().all(|c| c.is_ascii_hexdigit()) {
+            hex::decode(encoded).map_err(|e| format!("Invalid vault key encoding: {}", e))?
+        } else {
+            BASE64_STANDARD
+                .decode(encoded.as_bytes())
+                .map_err(|e| format!("Invalid vault key encoding: {}", e))?
+        };
+    if bytes.len() != MASTER_KEY_LEN {
+        return Err("Vault key must be 32 bytes".to_string());
+    }
+    Ok(bytes)
+}
+
+/// Write `contents` next to `path` with owner-only permissions, then rename into place.
+pub(crate) fn write_private_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create key directory: {}", e))?;
+    }
+    let tmp = path.with_extension("tmp");
+    // A stale temp file would keep its old mode; start from a fresh one.
+    let _ = std::fs::remove_file(&tmp);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
 }
 
 #[derive(Default)]
@@ -28,21 +224,408 @@ impl VaultKeyProvider for MacOSKeychainVaultKeyProvider {
             .get_blocking(VAULT_MASTER_KEY_ID)
             .map_err(|e| e.to_string())?
         {
-            let bytes = BASE64_STANDARD
-                .decode(encoded.as_bytes())
-                .map_err(|e| format!("Invalid vault key encoding: {}", e))?;
-            if bytes.len() != 32 {
-                return Err("Vault key must be 32 bytes".to_string());
-            }
-            return Ok(bytes);
+            return decode_master_key(&encoded);
         }
 
-        let mut key = [0u8; 32];
-        rand::rngs::OsRng.fill_bytes(&mut key);
-        let encoded = BASE64_STANDARD.encode(key);
+        let key = generate_master_key();
+        self.store_master_key(&key)?;
+        Ok(key)
+    }
+
+    fn store_master_key(&self, key: &[u8]) -> Result<(), String> {
+        self.keychain
+            .set_blocking(VAULT_MASTER_KEY_ID, &BASE64_STANDARD.encode(key))
+            .map_err(|e| e.to_string())
+    }
+
+    fn stage_master_key(&self, key: &[u8]) -> Result<(), String> {
         self.keychain
-            .set_blocking(VAULT_MASTER_KEY_ID, &encoded)
-            .map_err(|e| e.to_string())?;
-        Ok(key.to_vec())
+            .set_blocking(STAGED_MASTER_KEY_ID, &BASE64_STANDARD.encode(key))
+            .map_err(|e| e.to_string())
+    }
+
+    fn staged_master_key(&self) -> Result<Option<Vec<u8>>, String> {
+        self.keychain
+            .get_blocking(STAGED_MASTER_KEY_ID)
+            .map_err(|e| e.to_string())?
+            .map(|encoded| decode_master_key(&encoded))
+            .transpose()
+    }
+
+    fn clear_staged_master_key(&self) -> Result<(), String> {
+        self.keychain
+            .delete_blocking(STAGED_MASTER_KEY_ID)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Master key stored as base64 in a file that only the owner may read.
+pub struct FileVaultKeyProvider {
+    path: PathBuf,
+}
+
+impl FileVaultKeyProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    #[cfg(unix)]
+    fn check_permissions(&self) -> Result<(), String> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = std::fs::metadata(&self.path)
+            .map_err(|e| format!("Failed to stat {}: {}", self.path.display(), e))?
+            .permissions()
+            .mode();
+        if mode & 0o077 != 0 {
+            return Err(format!(
+                "Vault key file {} must not be accessible by group or others (chmod 600)",
+                self.path.display()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn check_permissions(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl VaultKeyProvider for FileVaultKeyProvider {
+    fn get_or_create_master_key(&self) -> Result<Vec<u8>, String> {
+        if self.path.exists() {
+            self.check_permissions()?;
+            let encoded = std::fs::read_to_string(&self.path)
+                .map_err(|e| format!("Failed to read vault key file: {}", e))?;
+            return decode_master_key(&encoded);
+        }
+
+        let key = generate_master_key();
+        self.store_master_key(&key)?;
+        Ok(key)
+    }
+
+    fn store_master_key(&self, key: &[u8]) -> Result<(), String> {
+        write_private_file(&self.path, BASE64_STANDARD.encode(key).as_bytes())
+    }
+
+    fn stage_master_key(&self, key: &[u8]) -> Result<(), String> {
+        write_private_file(
+            &staged_path(&self.path),
+            BASE64_STANDARD.encode(key).as_bytes(),
+        )
+    }
+
+    fn staged_master_key(&self) -> Result<Option<Vec<u8>>, String> {
+        match std::fs::read_to_string(staged_path(&self.path)) {
+            Ok(encoded) => decode_master_key(&encoded).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read staged vault key: {}", e)),
+        }
+    }
+
+    fn clear_staged_master_key(&self) -> Result<(), String> {
+        remove_staged_file(&staged_path(&self.path))
+    }
+}
+
+/// Master key taken from `RAINY_VAULT_KEY`; the app never writes it anywhere.
+pub struct EnvVaultKeyProvider;
+
+impl VaultKeyProvider for EnvVaultKeyProvider {
+    fn get_or_create_master_key(&self) -> Result<Vec<u8>, String> {
+        let encoded = std::env::var(VAULT_KEY_ENV)
+            .map_err(|_| format!("{} is not set; export a base64 32-byte key", VAULT_KEY_ENV))?;
+        decode_master_key(&encoded)
+    }
+
+    fn store_master_key(&self, key: &[u8]) -> Result<(), String> {
+        if self.get_or_create_master_key()? == key {
+            return Ok(());
+        }
+        Err(format!(
+            "The env key provider cannot persist keys; set {} to the vault key and retry",
+            VAULT_KEY_ENV
+        ))
+    }
+
+    fn can_store_new_key(&self) -> bool {
+        false
+    }
+}
+
+/// On-disk envelope for a passphrase-wrapped master key.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WrappedMasterKey {
+    version: u32,
+    kdf: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String,
+    nonce: String,
+    wrapped_key: String,
+}
+
+/// Random master key wrapped (AES-256-GCM) with a key derived from a passphrase
+/// via Argon2id. The passphrase comes from the in-app unlock or `RAINY_VAULT_PASSPHRASE`.
+pub struct PassphraseVaultKeyProvider {
+    path: PathBuf,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+impl PassphraseVaultKeyProvider {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            m_cost: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+
+    fn passphrase(&self) -> Result<Zeroizing<String>, String> {
+        let session = session_passphrase()
+            .lock()
+            .map_err(|_| "Vault passphrase lock poisoned".to_string())?
+            .clone();
+        session
+            .or_else(|| std::env::var(VAULT_PASSPHRASE_ENV).ok().map(Zeroizing::new))
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| "Vault is locked; unlock it with the vault passphrase".to_string())
+    }
+
+    fn cached_key(&self) -> Option<Vec<u8>> {
+        let cached = unwrapped_master_key().lock().ok()?;
+        cached
+            .as_ref()
+            .filter(|cached| cached.envelope_path == self.path)
+            .map(|cached| cached.key.to_vec())
+    }
+
+    fn cache_key(&self, key: &[u8]) {
+        if let Ok(mut cached) = unwrapped_master_key().lock() {
+            *cached = Some(UnwrappedMasterKey {
+                envelope_path: self.path.clone(),
+                key: Zeroizing::new(key.to_vec()),
+            });
+        }
+    }
+
+    pub(super) fn derive_wrapping_key(
+        passphrase: &str,
+        salt: &[u8],
+        m_cost: u32,
+        t_cost: u32,
+        p_cost: u32,
+    ) -> Result<[u8; 32], String> {
+        let params = Params::new(m_cost, t_cost, p_cost, Some(32))
+            .map_err(|e| format!("Invalid Argon2 parameters: {}", e))?;
+        let mut out = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), salt, &mut out)
+            .map_err(|e| format!("Failed to derive vault wrapping key: {}", e))?;
+        Ok(out)
+    }
+
+    fn wrap(&self, passphrase: &str, key: &[u8]) -> Result<WrappedMasterKey, String> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let kek = Zeroizing::new(Self::derive_wrapping_key(
+            passphrase,
+            &salt,
+            self.m_cost,
+            self.t_cost,
+            self.p_cost,
+        )?);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(kek.as_slice()));
+        let wrapped = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: key,
+                    aad: WRAPPED_KEY_AAD,
+                },
+            )
+            .map_err(|e| format!("Failed to wrap vault key: {}", e))?;
+
+        Ok(WrappedMasterKey {
+            version: 1,
+            kdf: "argon2id".to_string(),
+            m_cost: self.m_cost,
+            t_cost: self.t_cost,
+            p_cost: self.p_cost,
+            salt: BASE64_STANDARD.encode(salt),
+            nonce: BASE64_STANDARD.encode(nonce),
+            wrapped_key: BASE64_STANDARD.encode(wrapped),
+        })
+    }
+
+    fn write_envelope(&self, path: &Path, key: &[u8]) -> Result<(), String> {
+        let envelope = self.wrap(&self.passphrase()?, key)?;
+        let json = serde_json::to_vec_pretty(&envelope)
+            .map_err(|e| format!("Failed to serialize wrapped vault key: {}", e))?;
+        write_private_file(path, &json)
+    }
+
+    fn read_envelope(path: &Path, passphrase: &str) -> Result<Vec<u8>, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read wrapped vault key: {}", e))?;
+        let envelope: WrappedMasterKey = serde_json::from_str(&json)
+            .map_err(|e| format!("Corrupt vault key envelope: {}", e))?;
+        Self::unwrap(passphrase, &envelope)
+    }
+
+    fn unwrap(passphrase: &str, envelope: &WrappedMasterKey) -> Result<Vec<u8>, String> {
+        if envelope.version != 1 || envelope.kdf != "argon2id" {
+            return Err(format!(
+                "Unsupported vault key envelope (version {}, kdf {})",
+                envelope.version, envelope.kdf
+            ));
+        }
+        let decode = |field: &str| {
+            BASE64_STANDARD
+                .decode(field.as_bytes())
+                .map_err(|e| format!("Corrupt vault key envelope: {}", e))
+        };
+        let salt = decode(&envelope.salt)?;
+        let nonce = decode(&envelope.nonce)?;
+        let wrapped = decode(&envelope.wrapped_key)?;
+        if nonce.len() != 12 {
+            return Err("Corrupt vault key envelope: bad nonce".to_string());
+        }
+
+        let kek = Zeroizing::new(Self::derive_wrapping_key(
+            passphrase,
+            &salt,
+            envelope.m_cost,
+            envelope.t_cost,
+            envelope.p_cost,
+        )?);
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(kek.as_slice()));
+        let key = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &wrapped,
+                    aad: WRAPPED_KEY_AAD,
+                },
+            )
+            .map_err(|_| "Incorrect vault passphrase".to_string())?;
+        if key.len() != MASTER_KEY_LEN {
+            return Err("Vault key must be 32 bytes".to_string());
+        }
+        Ok(key)
+    }
+}
+
+impl VaultKeyProvider for PassphraseVaultKeyProvider {
+    fn get_or_create_master_key(&self) -> Result<Vec<u8>, String> {
+        if let Some(key) = self.cached_key() {
+            return Ok(key);
+        }
+        let passphrase = self.passphrase()?;
+        if self.path.exists() {
+            let key = Self::read_envelope(&self.path, &passphrase)?;
+            self.cache_key(&key);
+            return Ok(key);
+        }
+
+        let key = generate_master_key();
+        self.store_master_key(&key)?;
+        Ok(key)
+    }
+
+    fn store_master_key(&self, key: &[u8]) -> Result<(), String> {
+        self.write_envelope(&self.path, key)?;
+        self.cache_key(key);
+        Ok(())
+    }
+
+    fn stage_master_key(&self, key: &[u8]) -> Result<(), String> {
+        self.write_envelope(&staged_path(&self.path), key)
+    }
+
+    fn staged_master_key(&self) -> Result<Option<Vec<u8>>, String> {
+        let path = staged_path(&self.path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::read_envelope(&path, &self.passphrase()?).map(Some)
+    }
+
+    fn clear_staged_master_key(&self) -> Result<(), String> {
+        remove_staged_file(&staged_path(&self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passphrase_provider_round_trips_and_rejects_wrong_passphrase() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let provider = PassphraseVaultKeyProvider {
+            path: temp.path().join(WRAPPED_KEY_FILE_NAME),
+            m_cost: 256,
+            t_cost: 1,
+            p_cost: 1,
+        };
+
+        set_session_passphrase(Some("correct horse".to_string()));
+        let key = provider.get_or_create_master_key().expect("create");
+        assert_eq!(key.len(), MASTER_KEY_LEN);
+        assert_eq!(provider.get_or_create_master_key().expect("reload"), key);
+
+        set_session_passphrase(Some("battery staple".to_string()));
+        assert_eq!(
+            provider.get_or_create_master_key().unwrap_err(),
+            "Incorrect vault passphrase"
+        );
+
+        // Once unlocked the key is served from the session cache.
+        set_session_passphrase(Some("correct horse".to_string()));
+        assert_eq!(provider.get_or_create_master_key().expect("unlock"), key);
+        std::fs::remove_file(&provider.path).expect("remove envelope");
+        assert_eq!(provider.get_or_create_master_key().expect("cached"), key);
+
+        set_session_passphrase(None);
+        assert!(provider.get_or_create_master_key().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_provider_creates_owner_only_key_and_rejects_loose_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join(KEY_FILE_NAME);
+        let provider = FileVaultKeyProvider::new(path.clone());
+
+        let key = provider.get_or_create_master_key().expect("create");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(provider.get_or_create_master_key().expect("reload"), key);
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(provider.get_or_create_master_key().is_err());
+    }
+
+    #[test]
+    fn master_keys_decode_from_base64_or_hex() {
+        let key = [7u8; MASTER_KEY_LEN];
+        assert_eq!(
+            decode_master_key(&BASE64_STANDARD.encode(key)).unwrap(),
+            key.to_vec()
+        );
+        assert_eq!(decode_master_key(&hex::encode(key)).unwrap(), key.to_vec());
+        assert!(decode_master_key("c2hvcnQ=").is_err());
     }
 }
@@ -0,0 +1,95 @@
+//! Blind keyword index for the vault.
+//!
+//! Words are replaced by keyed HMAC tokens before they reach the FTS5 table,
+//! so BM25 ranking works on term and document frequencies while the index on
+//! disk holds no plaintext. Tokens are stored sorted rather than in word
+//! order, so bigrams and phrasing cannot be read back from the index. The key
+//! is derived from the vault master key and the index is rebuilt whenever
+//! that key rotates.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashSet;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const INDEX_KEY_CONTEXT: &[u8] = b"rainy-memory-vault-lexical-index-v1";
+/// Bytes of each HMAC kept in a token; 64 bits keeps collisions negligible.
+const TOKEN_BYTES: usize = 8;
+const MAX_QUERY_TERMS: usize = 32;
+
+pub fn derive_index_key(master_key: &[u8]) -> [u8; 32] {
+    let mut mac = HmacSha256::new_from_slice(master_key).expect("HMAC accepts any key length");
+    mac.update(INDEX_KEY_CONTEXT);
+    mac.finalize().into_bytes().into()
+}
+
+/// Lowercased alphanumeric words. Single letters are dropped; single digits kept.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 1 || word.chars().any(|c| c.is_ascii_digit()))
+        .map(|word| word.to_lowercase())
+        .collect()
+}
+
+fn blind(index_key: &[u8; 32], token: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(index_key).expect("HMAC accepts any key length");
+    mac.update(token.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    format!("t{}", hex::encode(&digest[..TOKEN_BYTES]))
+}
+
+/// Space-separated blind tokens for an entry's content and tags, sorted.
+/// Repeats are kept so BM25 still sees term frequency.
+pub fn index_document(index_key: &[u8; 32], content: &str, tags: &[String]) -> String {
+    let mut tokens = tokenize(content)
+        .into_iter()
+        .chain(tags.iter().flat_map(|tag| tokenize(tag)))
+        .map(|token| blind(index_key, &token))
+        .collect::<Vec<_>>();
+    tokens.sort_unstable();
+    tokens.join(" ")
+}
+
+/// FTS5 `MATCH` expression that hits any query word, or `None` when the
+/// query has no indexable words.
+pub fn match_query(index_key: &[u8; 32], query: &str) -> Option<String> {
+    let mut seen = HashSet::new();
+    let terms = tokenize(query)
+        .into_iter()
+        .filter(|token| seen.insert(token.clone()))
+        .take(MAX_QUERY_TERMS)
+        .map(|token| format!("\"{}\"", blind(index_key, &token)))
+        .collect::<Vec<_>>();
+    (!terms.is_empty()).then(|| terms.join(" OR "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_holds_no_plaintext_and_matches_query_words() {
+        let key = derive_index_key(b"0123456789abcdef0123456789abcdef");
+        let doc = index_document(&key, "Deploy the Rust service to staging", &[]);
+        assert!(!doc.contains("rust") && !doc.contains("staging"));
+        assert_eq!(doc.split(' ').count(), 6);
+        assert_eq!(
+            doc,
+            index_document(&key, "staging to service Rust the Deploy", &[])
+        );
+
+        let query = match_query(&key, "rust STAGING rust").unwrap();
+        assert_eq!(query.matches(" OR ").count(), 1);
+        for term in query.split(" OR ") {
+            assert!(doc.contains(term.trim_matches('"')));
+        }
+
+        let other_key = derive_index_key(b"abcdef0123456789abcdef0123456789");
+        assert_ne!(
+            doc,
+            index_document(&other_key, "Deploy the Rust service to staging", &[])
+        );
+        assert!(match_query(&key, "a ? !").is_none());
+    }
+}
@@ -1,7 +1,10 @@
+pub mod archive;
 pub mod crypto;
 pub mod dedup;
 pub mod distiller;
+pub mod governance;
 pub mod key_provider;
+pub mod lexical;
 pub mod orm;
 pub mod profiles;
 pub mod repository;
@@ -13,7 +16,4 @@ pub use service::MemoryVaultService;
 pub use service::VectorSearchMode;
 #[allow(unused_imports)]
 pub use types::EMBEDDING_DIM;
-pub use types::{
-    AdditionalEmbeddingInput, MemorySensitivity, StoreMemoryInput, EMBEDDING_MODEL,
-    EMBEDDING_PROVIDER,
-};
+pub use types::{AdditionalEmbeddingInput, MemorySensitivity, StoreMemoryInput};
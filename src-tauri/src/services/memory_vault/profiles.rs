@@ -1,18 +1,116 @@
-#[derive(Debug, Clone, Copy)]
+use super::types::{EMBEDDING_DIM, EMBEDDING_MODEL, EMBEDDING_PROVIDER, VECTOR_STORAGE_DIM};
+
+/// Embedding model that produced a vault vector. Every row records its
+/// profile (`embedding_provider`, `embedding_model`, `embedding_dim`) and
+/// vectors are only ever compared with vectors of the same model.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EmbeddingProfile {
-    pub model: &'static str,
+    pub provider: String,
+    pub model: String,
+    /// Output dimension; 0 for a model we have no record of, in which case
+    /// the length of the first returned vector is used.
     pub dim: usize,
 }
 
-pub const GEMINI_EMBEDDING_2_PREVIEW: EmbeddingProfile = EmbeddingProfile {
-    model: "gemini-embedding-2-preview",
-    dim: 3072,
-};
+/// Output dimensions of the models offered in settings.
+const KNOWN_DIMENSIONS: &[(&str, &str, usize)] = &[
+    ("gemini", "gemini-embedding-2-preview", 3072),
+    ("gemini", "gemini-embedding-001", 3072),
+    ("openai", "text-embedding-3-small", 1536),
+    ("openai", "text-embedding-3-large", 3072),
+    ("openai", "text-embedding-ada-002", 1536),
+    ("ollama", "nomic-embed-text", 768),
+    ("ollama", "mxbai-embed-large", 1024),
+    ("ollama", "all-minilm", 384),
+    ("ollama", "bge-m3", 1024),
+    ("local", "all-MiniLM-L6-v2", 384),
+    ("local", "all-MiniLM-L12-v2", 384),
+    ("local", "bge-small-en-v1.5", 384),
+];
+
+/// Default model of the built-in CPU embedder.
+pub const LOCAL_EMBEDDING_MODEL: &str = "all-MiniLM-L6-v2";
+
+const GEMINI_FALLBACK_MODEL: &str = "gemini-embedding-001";
+
+impl EmbeddingProfile {
+    pub fn new(provider: &str, model: &str, dim: usize) -> Self {
+        Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+            dim,
+        }
+    }
+
+    /// Profile for a provider/model pair, with the dimension filled in when known.
+    pub fn resolve(provider: &str, model: &str) -> Self {
+        // Ollama tags (`nomic-embed-text:latest`) share the base model's shape
+        let base_model = model.split(':').next().unwrap_or(model);
+        let dim = KNOWN_DIMENSIONS
+            .iter()
+            .find(|(p, m, _)| *p == provider && *m == base_model)
+            .map(|(_, _, dim)| *dim)
+            .unwrap_or(0);
+        Self::new(provider, model, dim)
+    }
+
+    /// The profile used when nothing else is configured.
+    pub fn default_profile() -> Self {
+        Self::new(EMBEDDING_PROVIDER, EMBEDDING_MODEL, EMBEDDING_DIM)
+    }
+
+    /// Default model for a provider when settings leave it empty.
+    pub fn default_model(provider: &str) -> &'static str {
+        match provider {
+            "openai" => "text-embedding-3-small",
+            "ollama" => "nomic-embed-text",
+            "local" => LOCAL_EMBEDDING_MODEL,
+            _ => EMBEDDING_MODEL,
+        }
+    }
 
-pub const GEMINI_EMBEDDING_001: EmbeddingProfile = EmbeddingProfile {
-    model: "gemini-embedding-001",
-    dim: 3072,
-};
+    /// Second model tried when this one fails; only Gemini has one.
+    pub fn fallback(&self) -> Option<Self> {
+        (self.provider == "gemini" && self.model != GEMINI_FALLBACK_MODEL)
+            .then(|| Self::resolve("gemini", GEMINI_FALLBACK_MODEL))
+    }
 
-pub const ACTIVE_EMBEDDING_PROFILE: EmbeddingProfile = GEMINI_EMBEDDING_2_PREVIEW;
-pub const FALLBACK_EMBEDDING_PROFILE: EmbeddingProfile = GEMINI_EMBEDDING_001;
+    /// Whether vectors of this profile fit the vault's vector columns.
+    pub fn fits_storage(&self) -> bool {
+        self.dim <= VECTOR_STORAGE_DIM
+    }
+
+    /// `provider:model`, as reported in ingestion results and migration ids.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.provider, self.model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_fills_known_dimensions() {
+        assert_eq!(
+            EmbeddingProfile::resolve("openai", "text-embedding-3-small").dim,
+            1536
+        );
+        assert_eq!(
+            EmbeddingProfile::resolve("ollama", "nomic-embed-text:latest").dim,
+            768
+        );
+        assert_eq!(EmbeddingProfile::resolve("ollama", "my-finetune").dim, 0);
+        assert_eq!(EmbeddingProfile::default_profile().dim, EMBEDDING_DIM);
+    }
+
+    #[test]
+    fn only_gemini_preview_has_a_fallback() {
+        let fallback = EmbeddingProfile::default_profile().fallback().unwrap();
+        assert_eq!(fallback.model, "gemini-embedding-001");
+        assert!(fallback.fallback().is_none());
+        assert!(EmbeddingProfile::resolve("local", LOCAL_EMBEDDING_MODEL)
+            .fallback()
+            .is_none());
+    }
+}
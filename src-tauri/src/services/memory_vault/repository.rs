@@ -25,6 +25,19 @@ pub struct VaultRow {
     pub embedding_dim: Option<usize>,
 }
 
+/// Encrypted columns of a vault row, used when re-keying the vault.
+#[derive(Debug, Clone)]
+pub struct VaultCiphertexts {
+    pub id: String,
+    pub workspace_id: String,
+    pub content_ciphertext: Vec<u8>,
+    pub content_nonce: Vec<u8>,
+    pub tags_ciphertext: Vec<u8>,
+    pub tags_nonce: Vec<u8>,
+    pub metadata_ciphertext: Option<Vec<u8>>,
+    pub metadata_nonce: Option<Vec<u8>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MemoryVaultRepository {
     conn: Connection,
@@ -184,6 +197,24 @@ impl MemoryVaultRepository {
             )
             .await;
 
+        // Superseded index that kept blind tokens in word order.
+        conn.execute("DROP TABLE IF EXISTS memory_vault_lexical", ())
+            .await
+            .map_err(|e| format!("Failed to drop the old lexical index: {}", e))?;
+
+        // Keyword index over sorted blind tokens (see `lexical`), never plaintext.
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS memory_vault_blind_terms USING fts5(
+                tokens,
+                entry_id UNINDEXED,
+                workspace_id UNINDEXED,
+                tokenize = 'ascii'
+            )",
+            (),
+        )
+        .await
+        .map_err(|e| format!("Failed to create lexical index table: {}", e))?;
+
         Ok(Self { conn })
     }
 
@@ -278,11 +309,43 @@ impl MemoryVaultRepository {
         Ok(())
     }
 
+    async fn replace_lexical_row(
+        &self,
+        entry_id: &str,
+        workspace_id: &str,
+        tokens: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM memory_vault_blind_terms WHERE entry_id = ?1",
+                params![entry_id.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to clear lexical index row: {}", e))?;
+        if tokens.is_empty() {
+            return Ok(());
+        }
+        self.conn
+            .execute(
+                "INSERT INTO memory_vault_blind_terms (tokens, entry_id, workspace_id)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    tokens.to_string(),
+                    entry_id.to_string(),
+                    workspace_id.to_string()
+                ],
+            )
+            .await
+            .map_err(|e| format!("Failed to write lexical index row: {}", e))?;
+        Ok(())
+    }
+
     pub async fn upsert_encrypted_atomic(
         &self,
         row: &VaultRow,
         key_version: i64,
         embedding_rows: Vec<(String, String, usize, Vec<u8>)>,
+        lexical_tokens: Option<&str>,
     ) -> Result<(), String> {
         self.conn
             .execute("BEGIN IMMEDIATE TRANSACTION", ())
@@ -292,6 +355,11 @@ impl MemoryVaultRepository {
         let result = async {
             self.upsert_encrypted_row(row, key_version).await?;
 
+            if let Some(tokens) = lexical_tokens {
+                self.replace_lexical_row(&row.id, &row.workspace_id, tokens)
+                    .await?;
+            }
+
             for (model, provider, dim, bytes) in embedding_rows {
                 self.upsert_embedding_vector_row(
                     &row.id,
@@ -381,6 +449,13 @@ impl MemoryVaultRepository {
                 params![id.to_string()],
             )
             .await;
+        let _ = self
+            .conn
+            .execute(
+                "DELETE FROM memory_vault_blind_terms WHERE entry_id = ?1",
+                params![id.to_string()],
+            )
+            .await;
         Ok(())
     }
 
@@ -407,6 +482,14 @@ impl MemoryVaultRepository {
                 .await
                 .map_err(|e| format!("Failed to delete vault vectors by workspace: {}", e))?;
 
+            self.conn
+                .execute(
+                    "DELETE FROM memory_vault_blind_terms WHERE workspace_id = ?1",
+                    params![workspace_id.to_string()],
+                )
+                .await
+                .map_err(|e| format!("Failed to delete lexical index by workspace: {}", e))?;
+
             Ok::<(), String>(())
         }
         .await;
@@ -507,6 +590,81 @@ impl MemoryVaultRepository {
         Ok(results)
     }
 
+    /// BM25-ranked rows whose blind tokens match `match_expr`, best first.
+    /// Scores are negated `bm25()` values, so higher is better.
+    pub async fn search_workspace_lexical(
+        &self,
+        workspace_id: &str,
+        match_expr: &str,
+        limit: usize,
+    ) -> Result<Vec<(VaultRow, f64)>, String> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT m.id, m.workspace_id, m.source, m.sensitivity, m.created_at, m.last_accessed, m.access_count,
+                        m.content_ciphertext, m.content_nonce, m.tags_ciphertext, m.tags_nonce, m.metadata_ciphertext, m.metadata_nonce, m.embedding, m.embedding_model, m.embedding_provider, m.embedding_dim,
+                        bm25(memory_vault_blind_terms) AS rank
+                 FROM memory_vault_blind_terms
+                 JOIN memory_vault_entries m ON m.id = memory_vault_blind_terms.entry_id
+                 WHERE memory_vault_blind_terms MATCH ?1
+                   AND memory_vault_blind_terms.workspace_id = ?2
+                 ORDER BY rank ASC
+                 LIMIT ?3",
+                params![match_expr.to_string(), workspace_id.to_string(), limit as i64],
+            )
+            .await
+            .map_err(|e| format!("Failed lexical search: {}", e))?;
+
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+            let rank: f64 = row.get(17).unwrap_or(0.0);
+            results.push((row_to_vault(&row)?, -rank));
+        }
+        Ok(results)
+    }
+
+    /// Write `(entry_id, workspace_id, tokens)` index rows in one transaction,
+    /// first dropping the whole index when `replace_all` is set.
+    pub async fn write_lexical_index(
+        &self,
+        entries: &[(String, String, String)],
+        replace_all: bool,
+    ) -> Result<(), String> {
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin lexical index transaction: {}", e))?;
+
+        let result: Result<(), String> = async {
+            if replace_all {
+                self.conn
+                    .execute("DELETE FROM memory_vault_blind_terms", ())
+                    .await
+                    .map_err(|e| format!("Failed to clear lexical index: {}", e))?;
+            }
+            for (entry_id, workspace_id, tokens) in entries {
+                self.replace_lexical_row(entry_id, workspace_id, tokens)
+                    .await?;
+            }
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit lexical index: {}", e))?;
+                Ok(())
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
     /// Batch-increment access counters for multiple entries in a single transaction.
     pub async fn touch_access_batch(&self, ids: &[String], now: i64) -> Result<(), String> {
         if ids.is_empty() {
@@ -620,6 +778,13 @@ impl MemoryVaultRepository {
                     .map_err(|e| {
                         format!("Failed to delete expired embedding vector {}: {}", id, e)
                     })?;
+                self.conn
+                    .execute(
+                        "DELETE FROM memory_vault_blind_terms WHERE entry_id = ?1",
+                        params![id.clone()],
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to delete expired lexical row {}: {}", id, e))?;
             }
 
             self.conn
@@ -683,6 +848,13 @@ impl MemoryVaultRepository {
                     .map_err(|e| {
                         format!("Failed to delete expired embedding vector {}: {}", id, e)
                     })?;
+                self.conn
+                    .execute(
+                        "DELETE FROM memory_vault_blind_terms WHERE entry_id = ?1",
+                        params![id.clone()],
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to delete expired lexical row {}: {}", id, e))?;
             }
 
             self.conn
@@ -878,6 +1050,14 @@ impl MemoryVaultRepository {
                     .await
                     .map_err(|e| format!("Failed to delete vectors for {}: {}", id, e))?;
 
+                self.conn
+                    .execute(
+                        "DELETE FROM memory_vault_blind_terms WHERE entry_id = ?1",
+                        params![id.clone()],
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to delete lexical row for {}: {}", id, e))?;
+
                 self.conn
                     .execute(
                         "DELETE FROM memory_vault_entries WHERE id = ?1",
@@ -905,6 +1085,126 @@ impl MemoryVaultRepository {
         }
     }
 
+    /// Encrypted columns of every row across all workspaces.
+    pub async fn list_all_ciphertexts(&self) -> Result<Vec<VaultCiphertexts>, String> {
+        self.query_ciphertexts("").await
+    }
+
+    /// Encrypted columns of rows that have no lexical index entry yet.
+    pub async fn list_ciphertexts_missing_lexical(&self) -> Result<Vec<VaultCiphertexts>, String> {
+        self.query_ciphertexts("WHERE id NOT IN (SELECT entry_id FROM memory_vault_blind_terms)")
+            .await
+    }
+
+    async fn query_ciphertexts(&self, filter: &str) -> Result<Vec<VaultCiphertexts>, String> {
+        let mut rows = self
+            .conn
+            .query(
+                &format!(
+                    "SELECT id, workspace_id, content_ciphertext, content_nonce, tags_ciphertext,
+                            tags_nonce, metadata_ciphertext, metadata_nonce
+                     FROM memory_vault_entries {}",
+                    filter
+                ),
+                (),
+            )
+            .await
+            .map_err(|e| format!("Failed to query vault ciphertexts: {}", e))?;
+
+        let columns = super::orm::ColumnMap::from_columns(&[
+            "id",
+            "workspace_id",
+            "content_ciphertext",
+            "content_nonce",
+            "tags_ciphertext",
+            "tags_nonce",
+            "metadata_ciphertext",
+            "metadata_nonce",
+        ]);
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().await.map_err(|e| e.to_string())? {
+            results.push(VaultCiphertexts {
+                id: columns.get_string(&row, "id")?,
+                workspace_id: columns.get_string(&row, "workspace_id")?,
+                content_ciphertext: columns.get_blob(&row, "content_ciphertext")?,
+                content_nonce: columns.get_blob(&row, "content_nonce")?,
+                tags_ciphertext: columns.get_blob(&row, "tags_ciphertext")?,
+                tags_nonce: columns.get_blob(&row, "tags_nonce")?,
+                metadata_ciphertext: columns.get_opt_blob(&row, "metadata_ciphertext"),
+                metadata_nonce: columns.get_opt_blob(&row, "metadata_nonce"),
+            });
+        }
+        Ok(results)
+    }
+
+    /// Overwrite only the encrypted metadata of one row. Returns whether it existed.
+    pub async fn update_metadata_ciphertext(
+        &self,
+        id: &str,
+        ciphertext: Vec<u8>,
+        nonce: Vec<u8>,
+    ) -> Result<bool, String> {
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE memory_vault_entries
+                 SET metadata_ciphertext = ?2, metadata_nonce = ?3
+                 WHERE id = ?1",
+                params![id.to_string(), ciphertext, nonce],
+            )
+            .await
+            .map_err(|e| format!("Failed to update metadata of entry {}: {}", id, e))?;
+        Ok(updated > 0)
+    }
+
+    /// Overwrite the encrypted columns of many rows in a single transaction.
+    pub async fn update_ciphertexts_atomic(&self, rows: &[VaultCiphertexts]) -> Result<(), String> {
+        self.conn
+            .execute("BEGIN IMMEDIATE TRANSACTION", ())
+            .await
+            .map_err(|e| format!("Failed to begin key rotation transaction: {}", e))?;
+
+        let result: Result<(), String> = async {
+            for row in rows {
+                self.conn
+                    .execute(
+                        "UPDATE memory_vault_entries
+                         SET content_ciphertext = ?2, content_nonce = ?3,
+                             tags_ciphertext = ?4, tags_nonce = ?5,
+                             metadata_ciphertext = ?6, metadata_nonce = ?7
+                         WHERE id = ?1",
+                        params![
+                            row.id.clone(),
+                            row.content_ciphertext.clone(),
+                            row.content_nonce.clone(),
+                            row.tags_ciphertext.clone(),
+                            row.tags_nonce.clone(),
+                            row.metadata_ciphertext.clone(),
+                            row.metadata_nonce.clone()
+                        ],
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to re-key entry {}: {}", row.id, e))?;
+            }
+            Ok(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
+                self.conn
+                    .execute("COMMIT", ())
+                    .await
+                    .map_err(|e| format!("Failed to commit key rotation: {}", e))?;
+                Ok(())
+            }
+            Err(err) => {
+                let _ = self.conn.execute("ROLLBACK", ()).await;
+                Err(err)
+            }
+        }
+    }
+
     pub async fn migration_completed(&self, id: &str) -> Result<bool, String> {
         let mut rows = self
             .conn
@@ -918,6 +1218,23 @@ impl MemoryVaultRepository {
         Ok(rows.next().await.map_err(|e| e.to_string())?.is_some())
     }
 
+    /// Drop every migration marker starting with `prefix` except `keep`.
+    pub async fn clear_migrations_with_prefix(
+        &self,
+        prefix: &str,
+        keep: &str,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "DELETE FROM memory_vault_migrations
+                 WHERE substr(id, 1, length(?1)) = ?1 AND id != ?2",
+                params![prefix.to_string(), keep.to_string()],
+            )
+            .await
+            .map_err(|e| format!("Failed to clear vault migration markers: {}", e))?;
+        Ok(())
+    }
+
     pub async fn mark_migration_completed(&self, id: &str) -> Result<(), String> {
         self.conn
             .execute(
@@ -972,7 +1289,7 @@ mod tests {
         };
 
         // Test insertion
-        repo.upsert_encrypted_atomic(&row, 1, Vec::new())
+        repo.upsert_encrypted_atomic(&row, 1, Vec::new(), None)
             .await
             .expect("Failed to upsert row");
 
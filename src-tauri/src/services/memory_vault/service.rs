@@ -1,13 +1,23 @@
+use super::archive::{ArchiveFilters, ArchiveIdentity};
 use super::crypto::{decrypt_bytes, encrypt_bytes};
-use super::key_provider::{MacOSKeychainVaultKeyProvider, VaultKeyProvider};
-use super::repository::{MemoryVaultRepository, VaultRow};
-use super::types::{DecryptedMemoryEntry, MemorySensitivity, MemoryVaultStats, StoreMemoryInput};
-use crate::services::embedder::EmbeddingTaskType;
+use super::governance::{self, MemoryStatus};
+use super::key_provider::{generate_master_key, VaultKeyProvider};
+use super::lexical;
+use super::repository::{MemoryVaultRepository, VaultCiphertexts, VaultRow};
+use super::types::{
+    DecryptedMemoryEntry, MemorySensitivity, MemoryVaultStats, StoreMemoryInput, VECTOR_STORAGE_DIM,
+};
+use crate::services::embedder::{EmbedderService, EmbeddingTaskType};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
 const MIGRATION_PLAINTEXT_DB: &str = "migrate_plaintext_memory_entries_v1";
+/// Followed by the profile key; marks rows as embedded with that profile.
+const MIGRATION_REEMBED_PREFIX: &str = "migrate_memory_reembed_profile:";
+/// Most recent distilled rows scanned for the review queue.
+const PENDING_SCAN_LIMIT: usize = 1000;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VectorSearchMode {
@@ -15,19 +25,32 @@ pub enum VectorSearchMode {
     Exact,
 }
 
+/// Master key plus the provider that persists it; rotation swaps both under one lock.
+struct VaultKeys {
+    master_key: Vec<u8>,
+    provider: Arc<dyn VaultKeyProvider>,
+}
+
+impl std::fmt::Debug for VaultKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultKeys").finish_non_exhaustive()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MemoryVaultService {
     repository: Arc<MemoryVaultRepository>,
-    master_key: Arc<Vec<u8>>,
+    keys: Arc<RwLock<VaultKeys>>,
 }
 
 impl MemoryVaultService {
     pub async fn new(app_data_dir: PathBuf) -> Result<Self, String> {
-        Self::new_with_provider(
-            app_data_dir,
-            Arc::new(MacOSKeychainVaultKeyProvider::new()) as Arc<dyn VaultKeyProvider>,
-        )
-        .await
+        #[cfg(test)]
+        let provider: Arc<dyn VaultKeyProvider> =
+            Arc::new(super::key_provider::MacOSKeychainVaultKeyProvider::new());
+        #[cfg(not(test))]
+        let provider = super::key_provider::configured_provider();
+        Self::new_with_provider(app_data_dir, provider).await
     }
 
     pub async fn new_with_provider(
@@ -35,14 +58,22 @@ impl MemoryVaultService {
         provider: Arc<dyn VaultKeyProvider>,
     ) -> Result<Self, String> {
         let repository = Arc::new(MemoryVaultRepository::new(app_data_dir).await?);
-        let master_key = Arc::new(provider.get_or_create_master_key()?);
+        let master_key = provider.get_or_create_master_key()?;
+        let master_key =
+            Self::finish_interrupted_rotation(&repository, provider.as_ref(), master_key).await?;
         let service = Self {
             repository,
-            master_key,
+            keys: Arc::new(RwLock::new(VaultKeys {
+                master_key,
+                provider,
+            })),
         };
         if !cfg!(test) {
             service.run_plaintext_migration().await?;
         }
+        if let Err(err) = service.index_missing_lexical_rows().await {
+            tracing::warn!("Memory lexical index backfill failed: {}", err);
+        }
         Ok(service)
     }
 
@@ -52,25 +83,28 @@ impl MemoryVaultService {
         let metadata_json = serde_json::to_vec(&input.metadata)
             .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
 
+        // Held until the row is written so a concurrent rotation cannot miss it.
+        let keys = self.keys.read().await;
         let content = encrypt_bytes(
-            self.master_key.as_slice(),
+            &keys.master_key,
             &input.workspace_id,
             &input.id,
             input.content.as_bytes(),
         )?;
-        let tags = encrypt_bytes(
-            self.master_key.as_slice(),
-            &input.workspace_id,
-            &input.id,
-            &tags_json,
-        )?;
+        let tags = encrypt_bytes(&keys.master_key, &input.workspace_id, &input.id, &tags_json)?;
         let metadata = encrypt_bytes(
-            self.master_key.as_slice(),
+            &keys.master_key,
             &input.workspace_id,
             &input.id,
             &metadata_json,
         )?;
 
+        let lexical_tokens = lexical::index_document(
+            &lexical::derive_index_key(&keys.master_key),
+            &input.content,
+            &input.tags,
+        );
+
         let embedding_dim = input.embedding_dim.unwrap_or(super::types::EMBEDDING_DIM);
         let embedding_model = input
             .embedding_model
@@ -82,7 +116,7 @@ impl MemoryVaultService {
             .unwrap_or_else(|| super::types::EMBEDDING_PROVIDER.to_string());
 
         let valid_embedding = if let Some(emb) = input.embedding {
-            if emb.len() != embedding_dim {
+            if emb.len() != embedding_dim || embedding_dim > VECTOR_STORAGE_DIM {
                 println!(
                     "Warning: Invalid embedding dimension {} (expected {}) for vault entry {}. Storing without embedding.",
                     emb.len(),
@@ -97,13 +131,7 @@ impl MemoryVaultService {
             None
         };
 
-        let embedding_bytes = valid_embedding.as_ref().map(|v| {
-            let mut bytes = Vec::with_capacity(v.len() * 4);
-            for f in v {
-                bytes.extend_from_slice(&f.to_le_bytes());
-            }
-            bytes
-        });
+        let embedding_bytes = valid_embedding.as_deref().map(vector_bytes);
 
         let row = VaultRow {
             id: input.id,
@@ -128,10 +156,7 @@ impl MemoryVaultService {
         let mut vector_rows = Vec::new();
 
         if let Some(primary_emb) = valid_embedding {
-            let mut bytes = Vec::with_capacity(primary_emb.len() * 4);
-            for f in primary_emb {
-                bytes.extend_from_slice(&f.to_le_bytes());
-            }
+            let bytes = vector_bytes(&primary_emb);
             vector_rows.push((
                 row.embedding_model
                     .clone()
@@ -145,13 +170,12 @@ impl MemoryVaultService {
         }
 
         for extra in input.additional_embeddings {
-            if extra.embedding.len() != extra.embedding_dim {
+            if extra.embedding.len() != extra.embedding_dim
+                || extra.embedding_dim > VECTOR_STORAGE_DIM
+            {
                 continue;
             }
-            let mut bytes = Vec::with_capacity(extra.embedding.len() * 4);
-            for f in extra.embedding {
-                bytes.extend_from_slice(&f.to_le_bytes());
-            }
+            let bytes = vector_bytes(&extra.embedding);
             vector_rows.push((
                 extra.embedding_model,
                 extra.embedding_provider,
@@ -161,16 +185,18 @@ impl MemoryVaultService {
         }
 
         self.repository
-            .upsert_encrypted_atomic(&row, 1, vector_rows)
+            .upsert_encrypted_atomic(&row, 1, vector_rows, Some(&lexical_tokens))
             .await?;
 
         Ok(())
     }
 
-    pub fn spawn_reembed_backfill(&self) {
+    /// Re-embed, in the background, every row not yet embedded with
+    /// `embedder`'s profile. Runs again whenever the configured profile changes.
+    pub fn spawn_reembed_backfill(&self, embedder: Arc<EmbedderService>) {
         let service = self.clone();
         tokio::spawn(async move {
-            if let Err(err) = service.run_reembed_backfill().await {
+            if let Err(err) = service.run_reembed_backfill(&embedder).await {
                 tracing::warn!("Memory re-embedding backfill failed: {}", err);
             }
         });
@@ -199,120 +225,156 @@ impl MemoryVaultService {
         self.repository.delete_all_entries_older_than(cutoff).await
     }
 
+    /// Keyword search over the blind lexical index, best match first. An
+    /// empty query returns the most recent entries instead.
     pub async fn search_workspace(
         &self,
         workspace_id: &str,
         query: &str,
         limit: usize,
     ) -> Result<Vec<DecryptedMemoryEntry>, String> {
+        if query.trim().is_empty() {
+            return self.recent_workspace(workspace_id, limit).await;
+        }
+        Ok(self
+            .search_workspace_lexical(workspace_id, query, limit)
+            .await?
+            .into_iter()
+            .map(|(entry, _score)| entry)
+            .collect())
+    }
+
+    /// BM25-ranked keyword hits with their scores (higher is better).
+    pub async fn search_workspace_lexical(
+        &self,
+        workspace_id: &str,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<(DecryptedMemoryEntry, f64)>, String> {
+        let keys = self.keys.read().await;
+        let Some(match_expr) =
+            lexical::match_query(&lexical::derive_index_key(&keys.master_key), query)
+        else {
+            return Ok(Vec::new());
+        };
         let rows = self
             .repository
-            .list_workspace_rows(workspace_id, limit.saturating_mul(10).max(50))
+            .search_workspace_lexical(workspace_id, &match_expr, limit)
             .await?;
-        let query_lc = query.to_lowercase();
-        let mut results = Vec::new();
 
         let now = chrono::Utc::now().timestamp();
-        for row in rows {
-            let entry = self.decrypt_row(&row)?;
-            if query_lc.is_empty() || entry.content.to_lowercase().contains(&query_lc) {
-                results.push(DecryptedMemoryEntry {
+        let mut results = Vec::with_capacity(rows.len());
+        for (row, score) in rows {
+            let entry = Self::decrypt_row(&keys.master_key, &row)?;
+            if !governance::is_retrievable(&entry.metadata) {
+                continue;
+            }
+            results.push((
+                DecryptedMemoryEntry {
                     access_count: entry.access_count + 1,
                     last_accessed: now,
                     ..entry
-                });
-            }
-            if results.len() >= limit {
-                break;
-            }
+                },
+                score,
+            ));
         }
 
-        let ids: Vec<String> = results.iter().map(|e| e.id.clone()).collect();
+        let ids: Vec<String> = results.iter().map(|(e, _)| e.id.clone()).collect();
         let _ = self.repository.touch_access_batch(&ids, now).await;
 
         Ok(results)
     }
 
-    #[allow(dead_code)]
     pub async fn search_workspace_vector(
         &self,
         workspace_id: &str,
         query_embedding: &[f32],
         limit: usize,
+        model: &str,
     ) -> Result<Vec<(DecryptedMemoryEntry, f32)>, String> {
         let (rows, _mode) = self
-            .search_workspace_vector_with_mode(workspace_id, query_embedding, limit)
+            .search_workspace_vector_with_mode(
+                workspace_id,
+                query_embedding,
+                limit,
+                &[model.to_string()],
+            )
             .await?;
         Ok(rows)
     }
 
+    /// Nearest rows embedded with the first of `models` that has any, trying
+    /// the ANN index for every model before falling back to exact search.
     pub async fn search_workspace_vector_with_mode(
         &self,
         workspace_id: &str,
         query_embedding: &[f32],
         limit: usize,
+        models: &[String],
     ) -> Result<(Vec<(DecryptedMemoryEntry, f32)>, VectorSearchMode), String> {
-        let primary_model = crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.model;
-        let fallback_model =
-            crate::services::memory_vault::profiles::FALLBACK_EMBEDDING_PROFILE.model;
-        let embedding_dim = super::types::EMBEDDING_DIM;
+        let embedding_dim = query_embedding.len();
+        if embedding_dim == 0 || embedding_dim > VECTOR_STORAGE_DIM {
+            return Err(format!(
+                "Query embedding has {} dimensions; the vault stores 1 to {}",
+                embedding_dim, VECTOR_STORAGE_DIM
+            ));
+        }
+        let mut padded = query_embedding.to_vec();
+        padded.resize(VECTOR_STORAGE_DIM, 0.0);
 
-        let (rows, mode) = match self
-            .repository
-            .search_workspace_vector_ann_for_model(
-                workspace_id,
-                query_embedding,
-                limit,
-                primary_model,
-                embedding_dim,
-            )
-            .await
-        {
-            Ok(rows) if !rows.is_empty() => (rows, VectorSearchMode::Ann),
-            _ => match self
+        let keys = self.keys.read().await;
+        let mut found = None;
+        for model in models {
+            if let Ok(rows) = self
                 .repository
                 .search_workspace_vector_ann_for_model(
                     workspace_id,
-                    query_embedding,
+                    &padded,
                     limit,
-                    fallback_model,
+                    model,
                     embedding_dim,
                 )
                 .await
             {
-                Ok(rows) if !rows.is_empty() => (rows, VectorSearchMode::Ann),
-                _ => match self
+                if !rows.is_empty() {
+                    found = Some((rows, VectorSearchMode::Ann));
+                    break;
+                }
+            }
+        }
+        if found.is_none() {
+            for (idx, model) in models.iter().enumerate() {
+                let rows = match self
                     .repository
                     .search_workspace_vector_exact_for_model(
                         workspace_id,
-                        query_embedding,
+                        &padded,
                         limit,
-                        primary_model,
+                        model,
                         embedding_dim,
                     )
                     .await
                 {
-                    Ok(rows) if !rows.is_empty() => (rows, VectorSearchMode::Exact),
-                    _ => (
-                        self.repository
-                            .search_workspace_vector_exact_for_model(
-                                workspace_id,
-                                query_embedding,
-                                limit,
-                                fallback_model,
-                                embedding_dim,
-                            )
-                            .await?,
-                        VectorSearchMode::Exact,
-                    ),
-                },
-            },
-        };
+                    Ok(rows) => rows,
+                    // Only the last model's failure is surfaced, as before
+                    Err(e) if idx + 1 == models.len() => return Err(e),
+                    Err(_) => continue,
+                };
+                if !rows.is_empty() || idx + 1 == models.len() {
+                    found = Some((rows, VectorSearchMode::Exact));
+                    break;
+                }
+            }
+        }
+        let (rows, mode) = found.unwrap_or((Vec::new(), VectorSearchMode::Exact));
         let now = chrono::Utc::now().timestamp();
         let mut results = Vec::new();
 
         for (row, distance) in rows {
-            let entry = self.decrypt_row(&row)?;
+            let entry = Self::decrypt_row(&keys.master_key, &row)?;
+            if !governance::is_retrievable(&entry.metadata) {
+                continue;
+            }
             results.push((
                 DecryptedMemoryEntry {
                     access_count: entry.access_count + 1,
@@ -329,32 +391,156 @@ impl MemoryVaultService {
         Ok((results, mode))
     }
 
-    #[allow(dead_code)]
     pub async fn recent_workspace(
         &self,
         workspace_id: &str,
         limit: usize,
     ) -> Result<Vec<DecryptedMemoryEntry>, String> {
+        let keys = self.keys.read().await;
         let rows = self
             .repository
             .list_workspace_rows(workspace_id, limit)
             .await?;
         let mut out = Vec::with_capacity(rows.len());
         for row in rows {
-            out.push(self.decrypt_row(&row)?);
+            let entry = Self::decrypt_row(&keys.master_key, &row)?;
+            if governance::is_retrievable(&entry.metadata) {
+                out.push(entry);
+            }
         }
         Ok(out)
     }
 
     pub async fn get_by_id(&self, id: &str) -> Result<Option<DecryptedMemoryEntry>, String> {
+        let keys = self.keys.read().await;
         let row = self.repository.get_by_id(id).await?;
-        row.map(|r| self.decrypt_row(&r)).transpose()
+        row.map(|r| Self::decrypt_row(&keys.master_key, &r))
+            .transpose()
     }
 
     pub async fn delete_by_id(&self, id: &str) -> Result<(), String> {
         self.repository.delete_by_id(id).await
     }
 
+    /// Rewrite an entry's metadata in place, leaving content and vectors
+    /// untouched. Returns `false` when the entry does not exist.
+    pub async fn update_metadata(
+        &self,
+        id: &str,
+        update: impl FnOnce(&mut HashMap<String, String>),
+    ) -> Result<bool, String> {
+        let keys = self.keys.read().await;
+        let Some(row) = self.repository.get_by_id(id).await? else {
+            return Ok(false);
+        };
+        let mut metadata = Self::decrypt_row(&keys.master_key, &row)?.metadata;
+        update(&mut metadata);
+        let metadata_json = serde_json::to_vec(&metadata)
+            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
+        let encrypted = encrypt_bytes(&keys.master_key, &row.workspace_id, id, &metadata_json)?;
+        self.repository
+            .update_metadata_ciphertext(id, encrypted.ciphertext, encrypted.nonce)
+            .await
+    }
+
+    /// Retire active entries in favour of `superseded_by`. Entries that are
+    /// already retired or awaiting review are left alone. Returns how many
+    /// were retired.
+    pub async fn supersede(&self, ids: &[String], superseded_by: &str) -> Result<usize, String> {
+        let mut retired = 0;
+        for id in ids.iter().filter(|id| id.as_str() != superseded_by) {
+            let mut applied = false;
+            self.update_metadata(id, |metadata| {
+                if governance::is_retrievable(metadata) {
+                    metadata.insert(
+                        governance::STATUS_KEY.to_string(),
+                        MemoryStatus::Superseded.as_str().to_string(),
+                    );
+                    metadata.insert(
+                        governance::SUPERSEDED_BY_KEY.to_string(),
+                        superseded_by.to_string(),
+                    );
+                    applied = true;
+                }
+            })
+            .await?;
+            if applied {
+                retired += 1;
+            }
+        }
+        Ok(retired)
+    }
+
+    /// Keys this device signs memory archives with and receives them under.
+    pub async fn archive_identity(&self) -> Result<ArchiveIdentity, String> {
+        ArchiveIdentity::derive(&self.keys.read().await.master_key)
+    }
+
+    /// Every entry of `workspace_id` that `filters` selects, newest first.
+    pub async fn export_entries(
+        &self,
+        workspace_id: &str,
+        filters: &ArchiveFilters,
+    ) -> Result<Vec<DecryptedMemoryEntry>, String> {
+        const PAGE_SIZE: usize = 500;
+        let keys = self.keys.read().await;
+        let mut query = super::orm::VaultQuery {
+            workspace_id: Some(workspace_id.to_string()),
+            created_after: filters.created_after,
+            created_before: filters.created_before,
+            limit: PAGE_SIZE,
+            ..Default::default()
+        };
+        let mut entries = Vec::new();
+        loop {
+            let rows = self.repository.list_entries_filtered(&query).await?;
+            for row in &rows {
+                let entry = Self::decrypt_row(&keys.master_key, row)?;
+                if filters.matches(&entry) {
+                    entries.push(entry);
+                }
+            }
+            if rows.len() < PAGE_SIZE {
+                return Ok(entries);
+            }
+            query.offset += PAGE_SIZE;
+        }
+    }
+
+    /// Re-embed rows stored without a vector for `embedder`'s profile, such
+    /// as imported entries, by re-running the backfill for that profile.
+    pub async fn reembed_missing(&self, embedder: Arc<EmbedderService>) -> Result<(), String> {
+        self.repository
+            .clear_migrations_with_prefix(MIGRATION_REEMBED_PREFIX, "")
+            .await?;
+        self.spawn_reembed_backfill(embedder);
+        Ok(())
+    }
+
+    /// Entries awaiting operator review, newest first. Only distilled
+    /// memories can be pending, so only those rows are decrypted.
+    pub async fn list_pending(
+        &self,
+        workspace_id: Option<&str>,
+    ) -> Result<Vec<DecryptedMemoryEntry>, String> {
+        let query = super::orm::VaultQuery {
+            workspace_id: workspace_id.map(str::to_string),
+            source_prefix: Some("distilled".to_string()),
+            limit: PENDING_SCAN_LIMIT,
+            ..Default::default()
+        };
+        let keys = self.keys.read().await;
+        let rows = self.repository.list_entries_filtered(&query).await?;
+        let mut pending = Vec::new();
+        for row in &rows {
+            let entry = Self::decrypt_row(&keys.master_key, row)?;
+            if MemoryStatus::of(&entry.metadata) == MemoryStatus::Pending {
+                pending.push(entry);
+            }
+        }
+        Ok(pending)
+    }
+
     pub async fn delete_workspace(&self, workspace_id: &str) -> Result<(), String> {
         self.repository.delete_workspace(workspace_id).await
     }
@@ -388,12 +574,13 @@ impl MemoryVaultService {
             offset,
         };
 
+        let keys = self.keys.read().await;
         let total_count = self.repository.count_entries_filtered(&query).await?;
         let rows = self.repository.list_entries_filtered(&query).await?;
 
         let mut entries = Vec::with_capacity(rows.len());
         for row in &rows {
-            entries.push(self.decrypt_row(row)?);
+            entries.push(Self::decrypt_row(&keys.master_key, row)?);
         }
 
         Ok(super::types::PaginatedEntries {
@@ -431,16 +618,217 @@ impl MemoryVaultService {
         self.repository.delete_batch(ids).await
     }
 
-    fn decrypt_row(&self, row: &VaultRow) -> Result<DecryptedMemoryEntry, String> {
+    /// Re-encrypt every vault row and the credential file under a fresh master key,
+    /// then persist the key with the current provider. Returns the rows rotated.
+    ///
+    /// The new key is staged with the provider before any row changes, so a crash
+    /// mid-rotation is finished on the next start. If the provider cannot store
+    /// the new key, rows and credentials are restored.
+    pub async fn rotate_master_key(&self) -> Result<usize, String> {
+        let mut keys = self.keys.write().await;
+        if !keys.provider.can_store_new_key() {
+            return Err("The current vault key provider cannot store a new key".to_string());
+        }
+
+        let new_key = generate_master_key();
+        let rows = self.repository.list_all_ciphertexts().await?;
+        let rotated = rows
+            .iter()
+            .map(|row| Self::reencrypt_row(&keys.master_key, &new_key, row))
+            .collect::<Result<Vec<_>, _>>()?;
+        keys.provider.stage_master_key(&new_key)?;
+        if let Err(e) = self.repository.update_ciphertexts_atomic(&rotated).await {
+            Self::discard_staged_key(keys.provider.as_ref());
+            return Err(e);
+        }
+
+        let stored = crate::ai::keychain::reencrypt_credentials_file(&keys.master_key, &new_key)
+            .and_then(|backup| {
+                keys.provider.store_master_key(&new_key).map_err(|e| {
+                    if let Some(backup) = backup {
+                        crate::ai::keychain::restore_credentials_file(&backup);
+                    }
+                    e
+                })
+            });
+        if let Err(e) = stored {
+            match self.repository.update_ciphertexts_atomic(&rows).await {
+                Ok(()) => Self::discard_staged_key(keys.provider.as_ref()),
+                // The staged key still opens the rows; the next start finishes the rotation
+                Err(restore) => tracing::error!(
+                    "Failed to restore vault rows after rotation error: {}",
+                    restore
+                ),
+            }
+            return Err(format!("Vault key rotation aborted: {}", e));
+        }
+        Self::discard_staged_key(keys.provider.as_ref());
+
+        // The blind index is keyed from the master key, so it follows the rotation
+        let lexical_entries = Self::lexical_entries(&keys.master_key, &new_key, &rows);
+        keys.master_key = new_key;
+        let reindexed = match lexical_entries {
+            Ok(entries) => self.repository.write_lexical_index(&entries, true).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = reindexed {
+            tracing::warn!(
+                "Rebuilding the lexical index after key rotation failed: {}",
+                err
+            );
+        }
+        Ok(rows.len())
+    }
+
+    /// Complete a rotation that staged its key but stopped before clearing it.
+    /// Rows that open under the staged key mean the swap committed, so the
+    /// staged key becomes the master key; otherwise it is dropped.
+    async fn finish_interrupted_rotation(
+        repository: &MemoryVaultRepository,
+        provider: &dyn VaultKeyProvider,
+        master_key: Vec<u8>,
+    ) -> Result<Vec<u8>, String> {
+        let Some(staged) = provider.staged_master_key()? else {
+            return Ok(master_key);
+        };
+        if staged == master_key {
+            provider.clear_staged_master_key()?;
+            return Ok(master_key);
+        }
+        let rows = repository.list_all_ciphertexts().await?;
+        let committed = rows.first().is_none_or(|row| {
+            decrypt_bytes(
+                &staged,
+                &row.workspace_id,
+                &row.id,
+                &row.content_ciphertext,
+                &row.content_nonce,
+            )
+            .is_ok()
+        });
+        if !committed {
+            provider.clear_staged_master_key()?;
+            return Ok(master_key);
+        }
+
+        tracing::warn!("Finishing a vault key rotation that was interrupted");
+        // Fails harmlessly when the credential file was already re-encrypted
+        if let Err(e) = crate::ai::keychain::reencrypt_credentials_file(&master_key, &staged) {
+            tracing::warn!("Credential file not re-encrypted during recovery: {}", e);
+        }
+        provider.store_master_key(&staged)?;
+        provider.clear_staged_master_key()?;
+        let reindexed = match Self::lexical_entries(&staged, &staged, &rows) {
+            Ok(entries) => repository.write_lexical_index(&entries, true).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = reindexed {
+            tracing::warn!(
+                "Rebuilding the lexical index after recovery failed: {}",
+                err
+            );
+        }
+        Ok(staged)
+    }
+
+    fn discard_staged_key(provider: &dyn VaultKeyProvider) {
+        if let Err(e) = provider.clear_staged_master_key() {
+            tracing::warn!("Failed to remove the staged vault key: {}", e);
+        }
+    }
+
+    /// Hand the current master key to `provider` and use it from now on.
+    pub async fn adopt_key_provider(
+        &self,
+        provider: Arc<dyn VaultKeyProvider>,
+    ) -> Result<(), String> {
+        let mut keys = self.keys.write().await;
+        provider.store_master_key(&keys.master_key)?;
+        keys.provider = provider;
+        Ok(())
+    }
+
+    fn reencrypt_row(
+        old_key: &[u8],
+        new_key: &[u8],
+        row: &VaultCiphertexts,
+    ) -> Result<VaultCiphertexts, String> {
+        let reencrypt = |ciphertext: &[u8], nonce: &[u8]| {
+            let plaintext = decrypt_bytes(old_key, &row.workspace_id, &row.id, ciphertext, nonce)?;
+            encrypt_bytes(new_key, &row.workspace_id, &row.id, &plaintext)
+        };
+        let content = reencrypt(&row.content_ciphertext, &row.content_nonce)?;
+        let tags = reencrypt(&row.tags_ciphertext, &row.tags_nonce)?;
+        let metadata = match (&row.metadata_ciphertext, &row.metadata_nonce) {
+            (Some(cipher), Some(nonce)) => Some(reencrypt(cipher, nonce)?),
+            _ => None,
+        };
+
+        Ok(VaultCiphertexts {
+            id: row.id.clone(),
+            workspace_id: row.workspace_id.clone(),
+            content_ciphertext: content.ciphertext,
+            content_nonce: content.nonce,
+            tags_ciphertext: tags.ciphertext,
+            tags_nonce: tags.nonce,
+            metadata_ciphertext: metadata.as_ref().map(|m| m.ciphertext.clone()),
+            metadata_nonce: metadata.map(|m| m.nonce),
+        })
+    }
+
+    /// `(entry_id, workspace_id, tokens)` for `rows`, decrypting with
+    /// `master_key` and blinding with the index key of `index_master_key`.
+    fn lexical_entries(
+        master_key: &[u8],
+        index_master_key: &[u8],
+        rows: &[VaultCiphertexts],
+    ) -> Result<Vec<(String, String, String)>, String> {
+        let index_key = lexical::derive_index_key(index_master_key);
+        rows.iter()
+            .map(|row| {
+                let content = decrypt_bytes(
+                    master_key,
+                    &row.workspace_id,
+                    &row.id,
+                    &row.content_ciphertext,
+                    &row.content_nonce,
+                )?;
+                let tags = decrypt_bytes(
+                    master_key,
+                    &row.workspace_id,
+                    &row.id,
+                    &row.tags_ciphertext,
+                    &row.tags_nonce,
+                )?;
+                let tags: Vec<String> = serde_json::from_slice(&tags).unwrap_or_default();
+                let tokens =
+                    lexical::index_document(&index_key, &String::from_utf8_lossy(&content), &tags);
+                Ok((row.id.clone(), row.workspace_id.clone(), tokens))
+            })
+            .collect()
+    }
+
+    /// Index rows written before the lexical index existed.
+    async fn index_missing_lexical_rows(&self) -> Result<(), String> {
+        let keys = self.keys.read().await;
+        let rows = self.repository.list_ciphertexts_missing_lexical().await?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let entries = Self::lexical_entries(&keys.master_key, &keys.master_key, &rows)?;
+        self.repository.write_lexical_index(&entries, false).await
+    }
+
+    fn decrypt_row(master_key: &[u8], row: &VaultRow) -> Result<DecryptedMemoryEntry, String> {
         let content_bytes = decrypt_bytes(
-            self.master_key.as_slice(),
+            master_key,
             &row.workspace_id,
             &row.id,
             &row.content_ciphertext,
             &row.content_nonce,
         )?;
         let tags_bytes = decrypt_bytes(
-            self.master_key.as_slice(),
+            master_key,
             &row.workspace_id,
             &row.id,
             &row.tags_ciphertext,
@@ -448,13 +836,9 @@ impl MemoryVaultService {
         )?;
 
         let metadata_bytes = match (&row.metadata_ciphertext, &row.metadata_nonce) {
-            (Some(cipher), Some(nonce)) => decrypt_bytes(
-                self.master_key.as_slice(),
-                &row.workspace_id,
-                &row.id,
-                cipher,
-                nonce,
-            )?,
+            (Some(cipher), Some(nonce)) => {
+                decrypt_bytes(master_key, &row.workspace_id, &row.id, cipher, nonce)?
+            }
             _ => b"{}".to_vec(),
         };
 
@@ -471,6 +855,10 @@ impl MemoryVaultService {
                 let f = f32::from_le_bytes(chunk.try_into().unwrap());
                 floats.push(f);
             }
+            // Drop the zero padding added by `vector_bytes`
+            if let Some(dim) = row.embedding_dim {
+                floats.truncate(dim);
+            }
             floats
         });
 
@@ -561,12 +949,17 @@ impl MemoryVaultService {
             .await
     }
 
-    async fn run_reembed_backfill(&self) -> Result<(), String> {
+    async fn run_reembed_backfill(&self, embedder: &EmbedderService) -> Result<(), String> {
         const BACKFILL_BATCH_SIZE: usize = 16;
-        let migration_key = "migrate_memory_reembed_active_profile_v2";
-        if self.repository.migration_completed(migration_key).await? {
+        let profile = embedder.profile().clone();
+        let migration_key = format!("{}{}", MIGRATION_REEMBED_PREFIX, profile.key());
+        if self.repository.migration_completed(&migration_key).await? {
             return Ok(());
         }
+        // Markers of previously active profiles no longer describe the rows
+        self.repository
+            .clear_migrations_with_prefix(MIGRATION_REEMBED_PREFIX, &migration_key)
+            .await?;
 
         // Paginate ID collection to avoid loading millions of rows into RAM.
         const PAGE_SIZE: usize = 500;
@@ -580,15 +973,15 @@ impl MemoryVaultService {
                     "SELECT e.id
                      FROM memory_vault_entries e
                      LEFT JOIN memory_vault_embedding_vectors v
-                       ON v.entry_id = e.id AND v.embedding_model = ?2
+                       ON v.entry_id = e.id AND v.embedding_model = ?1
                      WHERE e.embedding IS NULL
-                        OR e.embedding_dim != ?1
-                        OR e.embedding_model != ?2
+                        OR e.embedding_model IS NOT ?1
+                        OR e.embedding_provider IS NOT ?2
                         OR v.entry_id IS NULL
                      LIMIT ?3 OFFSET ?4",
                     (
-                        super::types::EMBEDDING_DIM as i64,
-                        super::types::EMBEDDING_MODEL.to_string(),
+                        profile.model.clone(),
+                        profile.provider.clone(),
                         PAGE_SIZE as i64,
                         offset as i64,
                     ),
@@ -616,64 +1009,24 @@ impl MemoryVaultService {
         if ids_to_reembed.is_empty() {
             return self
                 .repository
-                .mark_migration_completed(migration_key)
+                .mark_migration_completed(&migration_key)
                 .await;
         }
 
         println!(
-            "Found {} rows needing '{}' re-embedding (dim {}).",
+            "Found {} rows needing '{}' re-embedding.",
             ids_to_reembed.len(),
-            super::types::EMBEDDING_MODEL,
-            super::types::EMBEDDING_DIM
+            profile.key()
         );
 
-        // In a real production system, this should be done in a background task queue.
-        // For Tauri startup, we will process them using the global EmbedderService.
-        // Since MemoryVaultService doesn't have an embedder attached natively, we will
-        // just set up a local embedder for the backfill using standard environment/keychain.
-
-        let settings = crate::services::settings::SettingsManager::new();
-        let provider_raw = settings.get_embedder_provider().to_string();
-        let provider = match provider_raw.trim().to_lowercase().as_str() {
-            "g" | "google" | "gemini" => super::types::EMBEDDING_PROVIDER.to_string(),
-            _ => {
-                println!(
-                    "Memory vault backfill forcing Gemini embedding provider; configured '{}' is unsupported for Step 3",
-                    provider_raw
-                );
-                super::types::EMBEDDING_PROVIDER.to_string()
-            }
-        };
-        let model = super::types::EMBEDDING_MODEL.to_string();
-
-        let keychain = crate::services::KeychainAccessService::new();
-        let api_key = keychain
-            .get_many(&[provider.as_str(), provider_raw.as_str()])
-            .await
-            .ok()
-            .and_then(|values| {
-                values
-                    .get(&provider)
-                    .cloned()
-                    .flatten()
-                    .or_else(|| values.get(&provider_raw).cloned().flatten())
-            })
-            .unwrap_or_default();
-
-        let embedder =
-            crate::services::embedder::EmbedderService::new(provider, api_key.clone(), Some(model));
-
-        if api_key.is_empty() {
-            println!("Skipping re-embedding backfill: No API key available.");
-            return Ok(());
-        }
-
+        let mut failed = 0usize;
         for id_batch in ids_to_reembed.chunks(BACKFILL_BATCH_SIZE) {
             let mut entries = Vec::new();
             for id in id_batch {
                 if let Ok(Some(entry)) = self.get_by_id(id).await {
-                    if entry.embedding_dim == Some(super::types::EMBEDDING_DIM)
-                        && entry.embedding_model.as_deref() == Some(super::types::EMBEDDING_MODEL)
+                    if entry.embedding.is_some()
+                        && entry.embedding_model.as_deref() == Some(profile.model.as_str())
+                        && entry.embedding_provider.as_deref() == Some(profile.provider.as_str())
                     {
                         continue;
                     }
@@ -693,7 +1046,7 @@ impl MemoryVaultService {
             let embeddings = match embedder
                 .embed_texts_for_model_with_task(
                     &texts,
-                    super::types::EMBEDDING_MODEL,
+                    &profile.model,
                     EmbeddingTaskType::RetrievalDocument,
                 )
                 .await
@@ -723,37 +1076,301 @@ impl MemoryVaultService {
                     embedder
                         .embed_text_for_model_with_task_strict(
                             &entry.content,
-                            super::types::EMBEDDING_MODEL,
+                            &profile.model,
                             EmbeddingTaskType::RetrievalDocument,
                         )
                         .await
                         .ok()
                 };
 
-                if let Some(vec) = new_embedding {
-                    let _ = self
-                        .put(StoreMemoryInput {
-                            id: entry.id,
-                            workspace_id: entry.workspace_id,
-                            content: entry.content,
-                            tags: entry.tags,
-                            source: entry.source,
-                            sensitivity: entry.sensitivity,
-                            metadata: entry.metadata,
-                            created_at: entry.created_at,
-                            embedding: Some(vec),
-                            embedding_model: Some(super::types::EMBEDDING_MODEL.to_string()),
-                            embedding_provider: Some(super::types::EMBEDDING_PROVIDER.to_string()),
-                            embedding_dim: Some(super::types::EMBEDDING_DIM),
-                            additional_embeddings: Vec::new(),
-                        })
-                        .await;
+                let Some(vec) = new_embedding else {
+                    failed += 1;
+                    continue;
+                };
+                let embedding_dim = vec.len();
+                let stored = self
+                    .put(StoreMemoryInput {
+                        id: entry.id,
+                        workspace_id: entry.workspace_id,
+                        content: entry.content,
+                        tags: entry.tags,
+                        source: entry.source,
+                        sensitivity: entry.sensitivity,
+                        metadata: entry.metadata,
+                        created_at: entry.created_at,
+                        embedding: Some(vec),
+                        embedding_model: Some(profile.model.clone()),
+                        embedding_provider: Some(profile.provider.clone()),
+                        embedding_dim: Some(embedding_dim),
+                        additional_embeddings: Vec::new(),
+                    })
+                    .await;
+                if stored.is_err() {
+                    failed += 1;
                 }
             }
         }
 
+        // Leave the marker unset so the next start (e.g. once Ollama is up) retries
+        if failed > 0 {
+            return Err(format!(
+                "{} of {} rows could not be re-embedded with '{}'",
+                failed,
+                ids_to_reembed.len(),
+                profile.key()
+            ));
+        }
+
         self.repository
-            .mark_migration_completed(migration_key)
+            .mark_migration_completed(&migration_key)
+            .await
+    }
+}
+
+/// Little-endian f32 bytes, zero-padded to the vector column width.
+fn vector_bytes(vector: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(VECTOR_STORAGE_DIM * 4);
+    for f in vector {
+        bytes.extend_from_slice(&f.to_le_bytes());
+    }
+    bytes.resize(VECTOR_STORAGE_DIM * 4, 0);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::embedder::EmbedderConfig;
+    use crate::services::memory_vault::key_provider::FileVaultKeyProvider;
+
+    fn entry(id: &str, content: &str) -> StoreMemoryInput {
+        StoreMemoryInput {
+            id: id.to_string(),
+            workspace_id: "ws".to_string(),
+            content: content.to_string(),
+            tags: Vec::new(),
+            source: "test".to_string(),
+            sensitivity: MemorySensitivity::Internal,
+            metadata: HashMap::new(),
+            created_at: 1,
+            embedding: None,
+            embedding_model: None,
+            embedding_provider: None,
+            embedding_dim: None,
+            additional_embeddings: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn lexical_search_uses_a_blind_index() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let provider = Arc::new(FileVaultKeyProvider::new(temp.path().join("vault.key")));
+        let vault = MemoryVaultService::new_with_provider(temp.path().to_path_buf(), provider)
+            .await
+            .expect("vault");
+        vault
+            .put(entry(
+                "keys",
+                "Rotate the vault master key; the master key wraps rows",
+            ))
+            .await
+            .unwrap();
+        vault
+            .put(entry("food", "Weekly grocery list: apples, bread, coffee"))
+            .await
+            .unwrap();
+        vault
+            .put(entry("misc", "A key for the shed is under the mat"))
+            .await
+            .unwrap();
+
+        let hits = vault
+            .search_workspace_lexical("ws", "master key", 5)
+            .await
+            .unwrap();
+        let ids = hits.iter().map(|(e, _)| e.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["keys", "misc"]);
+        assert!(hits[0].1 > hits[1].1);
+
+        let mut rows = vault
+            .repository
+            .conn()
+            .query("SELECT tokens FROM memory_vault_blind_terms", ())
+            .await
+            .unwrap();
+        while let Some(row) = rows.next().await.unwrap() {
+            let tokens: String = row.get(0).unwrap();
+            assert!(!tokens.contains("master") && !tokens.contains("grocery"));
+        }
+
+        vault.delete_by_id("keys").await.unwrap();
+        let hits = vault
+            .search_workspace_lexical("ws", "master", 5)
+            .await
+            .unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn superseded_and_pending_entries_are_not_retrieved() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let provider = Arc::new(FileVaultKeyProvider::new(temp.path().join("vault.key")));
+        let vault = MemoryVaultService::new_with_provider(temp.path().to_path_buf(), provider)
+            .await
+            .expect("vault");
+        vault
+            .put(entry("old", "The project uses npm for package management"))
+            .await
+            .unwrap();
+        vault
+            .put(entry("new", "The project uses pnpm for package management"))
+            .await
+            .unwrap();
+        let mut pending = entry("guess", "The package registry is probably private");
+        pending.source = "distilled".to_string();
+        pending.metadata.insert(
+            governance::STATUS_KEY.to_string(),
+            MemoryStatus::Pending.as_str().to_string(),
+        );
+        vault.put(pending).await.unwrap();
+
+        let retired = vault
+            .supersede(&["old".to_string(), "missing".to_string()], "new")
+            .await
+            .unwrap();
+        assert_eq!(retired, 1);
+
+        let hits = vault
+            .search_workspace_lexical("ws", "package", 5)
+            .await
+            .unwrap();
+        let ids = hits.iter().map(|(e, _)| e.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["new"]);
+
+        let old = vault.get_by_id("old").await.unwrap().unwrap();
+        assert_eq!(old.content, "The project uses npm for package management");
+        assert_eq!(
+            old.metadata
+                .get(governance::SUPERSEDED_BY_KEY)
+                .map(String::as_str),
+            Some("new")
+        );
+
+        let queue = vault.list_pending(Some("ws")).await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].id, "guess");
+    }
+
+    #[tokio::test]
+    async fn backfill_reembeds_rows_with_a_narrower_local_profile() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let provider = Arc::new(FileVaultKeyProvider::new(temp.path().join("vault.key")));
+        let vault = MemoryVaultService::new_with_provider(temp.path().to_path_buf(), provider)
+            .await
+            .expect("vault");
+        vault
+            .put(entry(
+                "keys",
+                "Rotating the vault master key re-encrypts rows",
+            ))
+            .await
+            .unwrap();
+        vault
+            .put(entry("food", "Weekly grocery list: apples, bread, coffee"))
+            .await
+            .unwrap();
+
+        let embedder =
+            EmbedderService::from_config(EmbedderConfig::new("local", "", None).unwrap()).unwrap();
+        vault.run_reembed_backfill(&embedder).await.unwrap();
+
+        let stored = vault.get_by_id("keys").await.unwrap().unwrap();
+        assert_eq!(stored.embedding_provider.as_deref(), Some("local"));
+        assert_eq!(stored.embedding_dim, Some(embedder.profile().dim));
+        assert_eq!(stored.embedding.unwrap().len(), embedder.profile().dim);
+
+        let query = embedder
+            .embed_text_with_task(
+                "how do I rotate the master key",
+                EmbeddingTaskType::RetrievalQuery,
+            )
+            .await
+            .unwrap();
+        let (hits, _mode) = vault
+            .search_workspace_vector_with_mode("ws", &query, 2, &embedder.search_models())
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].0.id, "keys");
+    }
+
+    #[tokio::test]
+    async fn rotation_interrupted_after_the_row_swap_is_finished_on_open() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let provider = Arc::new(FileVaultKeyProvider::new(temp.path().join("vault.key")));
+        let vault =
+            MemoryVaultService::new_with_provider(temp.path().to_path_buf(), provider.clone())
+                .await
+                .expect("vault");
+        vault
+            .put(entry("keys", "Rotate the vault master key"))
+            .await
+            .unwrap();
+        let repository = vault.repository.clone();
+        let old_key = provider.get_or_create_master_key().unwrap();
+
+        // Staged but never swapped: the old key stays and the stage is dropped.
+        provider.stage_master_key(&generate_master_key()).unwrap();
+        let key = MemoryVaultService::finish_interrupted_rotation(
+            &repository,
+            provider.as_ref(),
+            old_key.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(key, old_key);
+        assert!(provider.staged_master_key().unwrap().is_none());
+
+        // Rows committed under the staged key, then the process died.
+        let new_key = generate_master_key();
+        provider.stage_master_key(&new_key).unwrap();
+        let rotated = repository
+            .list_all_ciphertexts()
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| MemoryVaultService::reencrypt_row(&old_key, &new_key, row))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        repository
+            .update_ciphertexts_atomic(&rotated)
+            .await
+            .unwrap();
+
+        let key = MemoryVaultService::finish_interrupted_rotation(
+            &repository,
+            provider.as_ref(),
+            old_key,
+        )
+        .await
+        .unwrap();
+        assert_eq!(key, new_key);
+        assert_eq!(provider.get_or_create_master_key().unwrap(), new_key);
+        assert!(provider.staged_master_key().unwrap().is_none());
+
+        let recovered = MemoryVaultService {
+            repository,
+            keys: Arc::new(RwLock::new(VaultKeys {
+                master_key: key,
+                provider,
+            })),
+        };
+        let stored = recovered.get_by_id("keys").await.unwrap().unwrap();
+        assert_eq!(stored.content, "Rotate the vault master key");
+        let hits = recovered
+            .search_workspace_lexical("ws", "rotate", 5)
             .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
     }
 }
@@ -1,11 +1,15 @@
+use super::governance::MemoryProvenance;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Default embedding profile, used until another one is configured.
 pub const EMBEDDING_PROVIDER: &str = "gemini";
-pub const EMBEDDING_MODEL: &str =
-    crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.model;
-pub const EMBEDDING_DIM: usize =
-    crate::services::memory_vault::profiles::ACTIVE_EMBEDDING_PROFILE.dim;
+pub const EMBEDDING_MODEL: &str = "gemini-embedding-2-preview";
+pub const EMBEDDING_DIM: usize = 3072;
+
+/// Width of the vault's `F32_BLOB` vector columns. Narrower vectors are
+/// zero-padded on write, which leaves their cosine distances unchanged.
+pub const VECTOR_STORAGE_DIM: usize = 3072;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -147,6 +151,7 @@ pub struct DistilledMemory {
     pub content: String,
     pub category: MemoryCategory,
     pub importance: f32,
+    pub provenance: MemoryProvenance,
 }
 
 #[derive(Debug, Clone)]
@@ -157,6 +162,9 @@ pub struct RawMemoryTurn {
     pub source: String,
     pub workspace_id: String,
     pub timestamp: i64,
+    /// Agent run and chat the turn was observed in, kept as provenance.
+    pub run_id: Option<String>,
+    pub chat_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
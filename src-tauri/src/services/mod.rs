@@ -5,17 +5,22 @@ pub mod agent_kill_switch;
 pub mod agent_library;
 pub mod agent_run_control;
 pub mod airlock;
+pub mod airlock_grants;
 pub mod airlock_messages;
+pub mod airlock_policy;
+pub mod airlock_risk;
 pub mod app_identity;
 pub mod atm_auth;
 pub mod atm_client;
 pub mod attachment;
 pub mod audit_emitter;
+pub mod audit_ledger;
 pub mod beam_rpc;
 pub mod beam_templates;
 pub mod browser_controller;
 pub mod chat_artifacts;
 pub mod cloud_bridge;
+pub mod code_index;
 pub mod command_poller;
 pub mod command_poller_agent;
 pub mod default_agent_spec;
@@ -24,6 +29,7 @@ pub mod embedder;
 pub mod external_agent_runtime;
 pub mod file_manager;
 pub mod file_operations;
+pub mod file_patch;
 pub mod fleet_control;
 pub mod folder_manager;
 pub mod image;
@@ -44,12 +50,16 @@ pub mod memory_vault;
 pub mod native_shell;
 pub mod neural_service;
 pub mod persistent_scheduler;
+pub mod process_sandbox;
+pub mod process_table;
 pub mod prompt_skills;
 pub mod quick_delegate_modal;
 pub mod remote_workspace_grants;
+pub mod run_checkpoints;
 pub mod security;
 pub mod session_coordinator;
 pub mod settings;
+pub mod shell_policy;
 pub mod skill_executor;
 pub mod skill_installer;
 pub mod socket_client;
@@ -57,21 +67,27 @@ pub mod task_manager;
 pub mod third_party_skill_registry;
 pub mod tool_manifest;
 pub mod tool_policy;
+pub mod usage_ledger;
 pub mod wasm_sandbox;
 pub mod workspace_memory_files;
 
 pub mod workflow_recorder;
 pub mod workspace;
+pub mod worktree;
 
 pub use agent_library::AgentLibraryService;
 pub use agent_run_control::AgentRunControl;
 pub use airlock::AirlockService;
+pub use airlock_grants::{AirlockGrant, AirlockGrantStore, ApprovalScope};
 pub use airlock_messages::{AirlockMessage, AirlockMessageStore};
+pub use airlock_policy::AirlockPolicyEngine;
 pub use atm_client::ATMClient;
 pub use audit_emitter::AuditEmitter;
+pub use audit_ledger::AuditLedger;
 pub use beam_rpc::BeamRpcService;
 pub use beam_templates::BeamTemplateService;
 pub use browser_controller::BrowserController;
+pub use code_index::CodeIndex;
 pub use command_poller::CommandPoller;
 pub use document::DocumentService;
 pub use external_agent_runtime::{
@@ -88,11 +104,13 @@ pub use mcp_service::McpService;
 pub use memory::MemoryManager;
 pub use native_shell::{NativeShellService, NativeShellSnapshot, NativeShellStatus};
 pub use neural_service::NeuralService;
+pub use process_table::ProcessTable;
 pub use prompt_skills::{
     DiscoveredPromptSkill, PromptSkillBinding, PromptSkillDiscoveryService, PromptSkillRegistry,
 };
 pub use quick_delegate_modal::{QuickDelegateModalService, QuickDelegateStatus};
 pub use remote_workspace_grants::RemoteWorkspaceGrantStore;
+pub use run_checkpoints::RunCheckpointStore;
 pub use security::NodeAuthenticator;
 pub use skill_executor::SkillExecutor;
 
@@ -112,6 +130,7 @@ pub use socket_client::SocketClient;
 pub use task_manager::TaskManager;
 pub use third_party_skill_registry::ThirdPartySkillRegistry;
 pub use tool_policy::get_tool_policy;
+pub use usage_ledger::UsageLedger;
 
 pub use workflow_recorder::WorkflowRecorderService;
 pub use workspace::{
@@ -515,6 +515,8 @@ impl NeuralService {
                     research: snapshot.tool_usage_by_role.research,
                     executor: snapshot.tool_usage_by_role.executor,
                     verifier: snapshot.tool_usage_by_role.verifier,
+                    memory_scribe: snapshot.tool_usage_by_role.memory_scribe,
+                    custom: snapshot.tool_usage_by_role.custom,
                 },
             }
         } else {
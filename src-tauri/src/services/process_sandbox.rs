@@ -0,0 +1,851 @@
+//! Process sandbox for `execute_command`.
+//!
+//! On Linux a spawned command is confined before `exec`:
+//! - Landlock: read/execute everywhere, write only beneath the workspace
+//!   roots, the temp dir and a few tool caches;
+//! - a fresh user + network namespace (loopback only) unless network access
+//!   is allowed;
+//! - rlimits for CPU time, data segment and file size;
+//! - a seccomp denylist for kernel-admin, tracing and namespace syscalls.
+//!
+//! The environment is scrubbed to an allowlist on every platform. Layers the
+//! kernel can't provide are skipped and listed in the `SandboxReport`, unless
+//! `require_isolation` is set, in which case the command is refused. The
+//! child writes back which layers it entered, and only those are reported.
+
+use crate::models::neural::SandboxReport;
+use crate::models::neural::ToolAccessPolicy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Pseudo-tool that grants network access to shell commands when listed in
+/// a tool policy's `allow`.
+pub const SHELL_NETWORK_TOOL: &str = "shell_network";
+
+const DEFAULT_ENV_ALLOWLIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "LC_ALL",
+    "LC_CTYPE",
+    "TERM",
+    "TMPDIR",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "GOPATH",
+    "GOROOT",
+    "GOCACHE",
+    "NVM_DIR",
+    "VIRTUAL_ENV",
+    "JAVA_HOME",
+];
+
+/// Package caches and registries only. Tool homes also hold binaries on PATH
+/// and config the user later runs unsandboxed (`~/.cargo/bin`,
+/// `~/.cargo/config.toml`), so they stay read-only.
+const DEFAULT_WRITABLE_PATHS: &[&str] = &[
+    "~/.cargo/registry",
+    "~/.cargo/git",
+    "~/.npm/_cacache",
+    "~/.bun/install/cache",
+    "~/.local/share/pnpm/store",
+    "~/.cache",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellSandboxPolicy {
+    pub enabled: bool,
+    pub allow_network: bool,
+    /// Refuse to run when a requested layer can't be enforced.
+    pub require_isolation: bool,
+    pub cpu_seconds: u64,
+    pub memory_mb: u64,
+    pub max_file_size_mb: u64,
+    /// Extra writable paths outside the workspace (`~` expands to home).
+    pub writable_paths: Vec<String>,
+    /// Extra environment variables passed through the scrub.
+    pub env_allowlist: Vec<String>,
+}
+
+impl Default for ShellSandboxPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_network: false,
+            require_isolation: false,
+            cpu_seconds: 600,
+            memory_mb: 8192,
+            max_file_size_mb: 2048,
+            writable_paths: DEFAULT_WRITABLE_PATHS
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
+            env_allowlist: Vec::new(),
+        }
+    }
+}
+
+impl ShellSandboxPolicy {
+    /// Network stays off unless the workspace or the tool policy allows it;
+    /// an explicit deny of `shell_network` always wins.
+    pub fn for_tool_policy(mut self, tool_policy: Option<&ToolAccessPolicy>) -> Self {
+        if let Some(policy) = tool_policy {
+            if policy.allow.iter().any(|tool| tool == SHELL_NETWORK_TOOL) {
+                self.allow_network = true;
+            }
+            if policy.deny.iter().any(|tool| tool == SHELL_NETWORK_TOOL) {
+                self.allow_network = false;
+            }
+        }
+        self
+    }
+
+    fn writable_roots(&self, allowed_paths: &[String]) -> Vec<PathBuf> {
+        let home = dirs::home_dir();
+        let mut roots: Vec<PathBuf> = allowed_paths.iter().map(PathBuf::from).collect();
+        roots.push(std::env::temp_dir());
+        for path in &self.writable_paths {
+            match (path.strip_prefix("~/"), home.as_ref()) {
+                (Some(rest), Some(home)) => roots.push(home.join(rest)),
+                (Some(_), None) => {}
+                (None, _) => roots.push(PathBuf::from(path)),
+            }
+        }
+        roots.retain(|root| root.exists());
+        roots
+    }
+}
+
+/// A sandbox configured on a command that hasn't been spawned yet.
+pub struct PreparedSandbox {
+    report: SandboxReport,
+    probe: Option<platform::LayerProbe>,
+}
+
+impl PreparedSandbox {
+    /// Call once the command has been spawned: the report then lists only
+    /// the layers the child actually entered.
+    pub fn finish(mut self) -> SandboxReport {
+        if let Some(probe) = self.probe {
+            probe.finish(&mut self.report);
+        }
+        self.report
+    }
+}
+
+/// Configures the sandbox on `command`. Fails only when `require_isolation`
+/// is set and a layer is unavailable.
+pub fn apply(
+    command: &mut tokio::process::Command,
+    policy: &ShellSandboxPolicy,
+    allowed_paths: &[String],
+    blocked_paths: &[String],
+) -> Result<PreparedSandbox, String> {
+    let mut report = SandboxReport {
+        enabled: policy.enabled,
+        ..SandboxReport::default()
+    };
+    scrub_env(command, policy);
+    report.env_scrubbed = true;
+    if !policy.enabled {
+        report.notes.push("sandbox disabled by policy".to_string());
+        return Ok(PreparedSandbox {
+            report,
+            probe: None,
+        });
+    }
+    if blocked_paths.iter().any(|blocked| {
+        allowed_paths
+            .iter()
+            .any(|root| Path::new(blocked).starts_with(root))
+    }) {
+        report.notes.push(
+            "blocked paths inside the workspace are enforced by path checks only".to_string(),
+        );
+    }
+    let probe = platform::apply(
+        command,
+        policy,
+        &policy.writable_roots(allowed_paths),
+        &mut report,
+    )?;
+    Ok(PreparedSandbox { report, probe })
+}
+
+fn scrub_env(command: &mut tokio::process::Command, policy: &ShellSandboxPolicy) {
+    command.env_clear();
+    for (key, value) in std::env::vars_os() {
+        let Some(name) = key.to_str() else {
+            continue;
+        };
+        let allowed = DEFAULT_ENV_ALLOWLIST.contains(&name)
+            || policy.env_allowlist.iter().any(|allowed| allowed == name);
+        if allowed {
+            command.env(&key, value);
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::*;
+
+    /// Never constructed: no layer is applied in the child off Linux.
+    pub(super) enum LayerProbe {}
+
+    impl LayerProbe {
+        pub(super) fn finish(self, _report: &mut SandboxReport) {
+            match self {}
+        }
+    }
+
+    pub(super) fn apply(
+        _command: &mut tokio::process::Command,
+        policy: &ShellSandboxPolicy,
+        _writable_roots: &[PathBuf],
+        report: &mut SandboxReport,
+    ) -> Result<Option<LayerProbe>, String> {
+        if policy.require_isolation {
+            return Err("Process isolation is only available on Linux".to_string());
+        }
+        report
+            .notes
+            .push("process isolation is only available on Linux".to_string());
+        Ok(None)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, ABI,
+    };
+    use seccompiler::{
+        BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+        SeccompRule, TargetArch,
+    };
+    use std::collections::BTreeMap;
+    use std::ffi::{CStr, CString};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::sync::OnceLock;
+
+    const LANDLOCK_ABI: ABI = ABI::V5;
+    /// Device nodes commands may write to; missing ones are skipped.
+    const DEVICE_PATHS: &[&str] = &["/dev/null", "/dev/zero", "/dev/full"];
+    const DENIED_SYSCALLS: &[libc::c_long] = &[
+        libc::SYS_ptrace,
+        libc::SYS_process_vm_readv,
+        libc::SYS_process_vm_writev,
+        libc::SYS_mount,
+        libc::SYS_umount2,
+        libc::SYS_pivot_root,
+        libc::SYS_swapon,
+        libc::SYS_swapoff,
+        libc::SYS_reboot,
+        libc::SYS_kexec_load,
+        libc::SYS_init_module,
+        libc::SYS_finit_module,
+        libc::SYS_delete_module,
+        libc::SYS_bpf,
+        libc::SYS_perf_event_open,
+        libc::SYS_keyctl,
+        libc::SYS_add_key,
+        libc::SYS_request_key,
+        libc::SYS_setns,
+        libc::SYS_unshare,
+        libc::SYS_userfaultfd,
+    ];
+    /// `clone` flags that create namespaces; `clone` is denied when any is set.
+    const NAMESPACE_CLONE_FLAGS: &[libc::c_int] = &[
+        libc::CLONE_NEWNS,
+        libc::CLONE_NEWUSER,
+        libc::CLONE_NEWNET,
+        libc::CLONE_NEWPID,
+        libc::CLONE_NEWUTS,
+        libc::CLONE_NEWIPC,
+        libc::CLONE_NEWCGROUP,
+    ];
+
+    #[cfg(target_env = "gnu")]
+    type RlimitResource = libc::__rlimit_resource_t;
+    #[cfg(not(target_env = "gnu"))]
+    type RlimitResource = libc::c_int;
+    const RLIMIT_RESOURCES: [RlimitResource; 3] =
+        [libc::RLIMIT_CPU, libc::RLIMIT_DATA, libc::RLIMIT_FSIZE];
+
+    // Bits the child writes back once it has entered each layer.
+    const NETWORK: u8 = 1 << 0;
+    const ID_MAP: u8 = 1 << 1;
+    const RLIMITS: u8 = 1 << 2;
+    const LANDLOCK: u8 = 1 << 3;
+    const SECCOMP: u8 = 1 << 4;
+    const FAILED_LAYER_NOTES: &[(u8, &str)] = &[
+        (NETWORK, "network namespace could not be entered"),
+        (
+            ID_MAP,
+            "uid/gid mapping failed; the command runs as the overflow user",
+        ),
+        (RLIMITS, "resource limits could not be set"),
+        (LANDLOCK, "landlock restriction was rejected by the kernel"),
+        (SECCOMP, "seccomp filter was rejected by the kernel"),
+    ];
+
+    /// Everything the child needs, built before fork so `pre_exec` only
+    /// issues raw syscalls: no locks and no allocation.
+    struct ChildPlan {
+        require: bool,
+        status: OwnedFd,
+        id_maps: Option<IdMaps>,
+        limits: [libc::rlim_t; 3],
+        landlock: Option<OwnedFd>,
+        seccomp: Option<[BpfProgram; 2]>,
+    }
+
+    /// Parent end of the status pipe, read once the command has spawned.
+    pub(super) struct LayerProbe {
+        status: OwnedFd,
+        planned: u8,
+        landlock_abi: Option<i32>,
+    }
+
+    impl LayerProbe {
+        pub(super) fn finish(self, report: &mut SandboxReport) {
+            let Some(applied) = read_status(&self.status) else {
+                report
+                    .notes
+                    .push("command did not start; no sandbox layer was applied".to_string());
+                return;
+            };
+            report.network_isolated = applied & NETWORK != 0;
+            report.rlimits = applied & RLIMITS != 0;
+            report.seccomp = applied & SECCOMP != 0;
+            report.landlock_abi = self.landlock_abi.filter(|_| applied & LANDLOCK != 0);
+            for (bit, note) in FAILED_LAYER_NOTES {
+                let skipped = *bit == ID_MAP && applied & NETWORK == 0;
+                if self.planned & bit != 0 && applied & bit == 0 && !skipped {
+                    report.notes.push(note.to_string());
+                }
+            }
+        }
+    }
+
+    pub(super) fn apply(
+        command: &mut tokio::process::Command,
+        policy: &ShellSandboxPolicy,
+        writable_roots: &[PathBuf],
+        report: &mut SandboxReport,
+    ) -> Result<Option<LayerProbe>, String> {
+        let require = policy.require_isolation;
+        let mut planned = 0;
+
+        let landlock_abi = landlock_abi();
+        let landlock = match landlock_abi {
+            Some(_) => match build_ruleset(writable_roots) {
+                Ok(ruleset) => {
+                    planned |= LANDLOCK;
+                    Some(ruleset)
+                }
+                Err(error) => {
+                    unavailable(report, require, format!("landlock: {}", error))?;
+                    None
+                }
+            },
+            None => {
+                unavailable(
+                    report,
+                    require,
+                    "landlock is not supported by this kernel".into(),
+                )?;
+                None
+            }
+        };
+
+        let id_maps = if policy.allow_network {
+            report.notes.push("network allowed by policy".to_string());
+            None
+        } else if user_namespaces_available() {
+            planned |= NETWORK | ID_MAP;
+            Some(IdMaps::current())
+        } else {
+            unavailable(
+                report,
+                require,
+                "network isolation needs unprivileged user namespaces".into(),
+            )?;
+            None
+        };
+
+        let seccomp = match build_seccomp_filters() {
+            Ok(programs) => {
+                planned |= SECCOMP;
+                Some(programs)
+            }
+            Err(error) => {
+                unavailable(report, require, format!("seccomp: {}", error))?;
+                None
+            }
+        };
+
+        let requested = [
+            policy.cpu_seconds,
+            policy.memory_mb.saturating_mul(1024 * 1024),
+            policy.max_file_size_mb.saturating_mul(1024 * 1024),
+        ];
+        let mut limits = [0; 3];
+        for ((limit, resource), value) in limits.iter_mut().zip(RLIMIT_RESOURCES).zip(requested) {
+            *limit = clamp_to_hard_limit(resource, value);
+        }
+        if limits.iter().any(|limit| *limit > 0) {
+            planned |= RLIMITS;
+        }
+
+        let (status, child_status) =
+            status_pipe().map_err(|error| format!("Failed to prepare sandbox: {}", error))?;
+        let plan = ChildPlan {
+            require,
+            status: child_status,
+            id_maps,
+            limits,
+            landlock,
+            seccomp,
+        };
+        // SAFETY: the closure runs in the forked child before exec. It only
+        // issues raw syscalls on buffers and descriptors the parent prepared.
+        unsafe {
+            command.pre_exec(move || enter_sandbox(&plan));
+        }
+        Ok(Some(LayerProbe {
+            status,
+            planned,
+            landlock_abi,
+        }))
+    }
+
+    /// Runs in the forked child. Errors are returned only when isolation is
+    /// required; otherwise the failed layer is left out of the status byte.
+    fn enter_sandbox(plan: &ChildPlan) -> std::io::Result<()> {
+        let mut applied = 0u8;
+        let fail = |require: bool| -> std::io::Result<()> {
+            if require {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        };
+
+        if let Some(maps) = plan.id_maps.as_ref() {
+            // SAFETY: single syscall in the child.
+            if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } == 0 {
+                applied |= NETWORK;
+                match write_id_maps(maps) {
+                    Ok(()) => applied |= ID_MAP,
+                    Err(error) if plan.require => return Err(error),
+                    Err(_) => {}
+                }
+            } else {
+                fail(plan.require)?;
+            }
+        }
+
+        if plan.limits.iter().any(|limit| *limit > 0) {
+            let mut limited = true;
+            for (resource, value) in RLIMIT_RESOURCES.into_iter().zip(plan.limits) {
+                if value == 0 {
+                    continue;
+                }
+                let limit = libc::rlimit {
+                    rlim_cur: value,
+                    rlim_max: value,
+                };
+                // SAFETY: `limit` is a valid rlimit on the stack.
+                if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+                    fail(plan.require)?;
+                    limited = false;
+                }
+            }
+            if limited {
+                applied |= RLIMITS;
+            }
+        }
+
+        if plan.landlock.is_some() || plan.seccomp.is_some() {
+            // Both landlock and seccomp need no_new_privs; if it fails they
+            // fail too and are reported below.
+            // SAFETY: single syscall in the child.
+            unsafe { libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) };
+        }
+
+        if let Some(ruleset) = plan.landlock.as_ref() {
+            // SAFETY: `ruleset` is a landlock ruleset fd created by the parent.
+            let restricted = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_restrict_self,
+                    ruleset.as_raw_fd(),
+                    0 as libc::c_uint,
+                )
+            };
+            if restricted == 0 {
+                applied |= LANDLOCK;
+            } else {
+                fail(plan.require)?;
+            }
+        }
+
+        if let Some(programs) = plan.seccomp.as_ref() {
+            if install_seccomp_filters(programs) {
+                applied |= SECCOMP;
+            } else {
+                fail(plan.require)?;
+            }
+        }
+
+        // SAFETY: one-byte write from a stack variable to our pipe end.
+        unsafe { libc::write(plan.status.as_raw_fd(), (&applied as *const u8).cast(), 1) };
+        Ok(())
+    }
+
+    /// Installs each filter in order; stops at the first the kernel rejects.
+    pub(super) fn install_seccomp_filters(programs: &[BpfProgram]) -> bool {
+        programs.iter().all(|program| {
+            let fprog = libc::sock_fprog {
+                len: program.len() as libc::c_ushort,
+                filter: program.as_ptr().cast::<libc::sock_filter>().cast_mut(),
+            };
+            // SAFETY: the kernel copies the filter; `program` outlives the call.
+            unsafe {
+                libc::syscall(
+                    libc::SYS_seccomp,
+                    libc::SECCOMP_SET_MODE_FILTER,
+                    0 as libc::c_uint,
+                    &fprog as *const libc::sock_fprog,
+                ) == 0
+            }
+        })
+    }
+
+    fn unavailable(report: &mut SandboxReport, require: bool, note: String) -> Result<(), String> {
+        if require {
+            return Err(format!("Sandbox layer unavailable: {}", note));
+        }
+        report.notes.push(note);
+        Ok(())
+    }
+
+    fn landlock_abi() -> Option<i32> {
+        const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+        // SAFETY: querying the ABI version takes no attribute pointer.
+        let version = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<libc::c_void>(),
+                0usize,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        (version > 0).then(|| (version as i32).min(LANDLOCK_ABI as i32))
+    }
+
+    fn build_ruleset(writable_roots: &[PathBuf]) -> Result<OwnedFd, String> {
+        let read = AccessFs::from_read(LANDLOCK_ABI);
+        let all = AccessFs::from_all(LANDLOCK_ABI);
+        let devices = DEVICE_PATHS.iter().filter(|path| Path::new(path).exists());
+        let ruleset = Ruleset::default()
+            .handle_access(all)
+            .and_then(|ruleset| ruleset.create())
+            .and_then(|ruleset| ruleset.add_rules(path_beneath_rules(["/"], read)))
+            .and_then(|ruleset| ruleset.add_rules(path_beneath_rules(devices, all)))
+            .and_then(|ruleset| ruleset.add_rules(path_beneath_rules(writable_roots, all)))
+            .map_err(|error| error.to_string())?;
+        Option::<OwnedFd>::from(ruleset)
+            .ok_or_else(|| "ruleset was not created by the kernel".to_string())
+    }
+
+    /// Two filters, since a filter has a single match action: the denylist
+    /// (plus namespace-creating `clone`) fails with EPERM, and `clone3`, whose
+    /// flags live behind a pointer seccomp can't inspect, fails with ENOSYS so
+    /// libc falls back to `clone`.
+    pub(super) fn build_seccomp_filters() -> Result<[BpfProgram; 2], String> {
+        let arch = TargetArch::try_from(std::env::consts::ARCH).map_err(|e| e.to_string())?;
+        let mut denied: BTreeMap<i64, Vec<SeccompRule>> = DENIED_SYSCALLS
+            .iter()
+            .map(|&syscall| (syscall, Vec::new()))
+            .collect();
+        let namespace_clones = NAMESPACE_CLONE_FLAGS
+            .iter()
+            .map(|&flag| {
+                let flag = flag as u64;
+                SeccompCondition::new(
+                    0,
+                    SeccompCmpArgLen::Dword,
+                    SeccompCmpOp::MaskedEq(flag),
+                    flag,
+                )
+                .and_then(|condition| SeccompRule::new(vec![condition]))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        denied.insert(libc::SYS_clone, namespace_clones);
+        let clone3 = BTreeMap::from([(libc::SYS_clone3, Vec::new())]);
+        Ok([
+            compile_filter(denied, libc::EPERM, arch)?,
+            compile_filter(clone3, libc::ENOSYS, arch)?,
+        ])
+    }
+
+    fn compile_filter(
+        rules: BTreeMap<i64, Vec<SeccompRule>>,
+        errno: libc::c_int,
+        arch: TargetArch,
+    ) -> Result<BpfProgram, String> {
+        let filter = SeccompFilter::new(
+            rules,
+            SeccompAction::Allow,
+            SeccompAction::Errno(errno as u32),
+            arch,
+        )
+        .map_err(|e| e.to_string())?;
+        filter
+            .try_into()
+            .map_err(|e: seccompiler::BackendError| e.to_string())
+    }
+
+    /// Lowering the hard limit is always allowed; asking for more than the
+    /// current hard limit is not, so cap the request there.
+    fn clamp_to_hard_limit(resource: RlimitResource, value: u64) -> libc::rlim_t {
+        if value == 0 {
+            return 0;
+        }
+        let value = value as libc::rlim_t;
+        let mut current = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: getrlimit fills the stack rlimit.
+        if unsafe { libc::getrlimit(resource, &mut current) } != 0 {
+            return value;
+        }
+        value.min(current.rlim_max)
+    }
+
+    /// Close-on-exec pipe the child writes its applied-layer bits to. Both
+    /// ends are non-blocking: the parent reads after spawn returns, by which
+    /// point the child has either written the byte or failed to exec.
+    fn status_pipe() -> std::io::Result<(OwnedFd, OwnedFd)> {
+        let mut fds = [0; 2];
+        // SAFETY: pipe2 fills both descriptors on success.
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        // SAFETY: both descriptors were just created and are owned here.
+        Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+    }
+
+    fn read_status(status: &OwnedFd) -> Option<u8> {
+        let mut applied = 0u8;
+        // SAFETY: one-byte read into a stack variable from a pipe we own.
+        let read = unsafe { libc::read(status.as_raw_fd(), (&mut applied as *mut u8).cast(), 1) };
+        (read == 1).then_some(applied)
+    }
+
+    /// uid/gid maps written after `unshare(CLONE_NEWUSER)` so the child keeps
+    /// its own ids inside the namespace.
+    struct IdMaps {
+        uid_map: CString,
+        gid_map: CString,
+    }
+
+    impl IdMaps {
+        fn current() -> Self {
+            // SAFETY: getuid/getgid cannot fail.
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            Self {
+                uid_map: CString::new(format!("{} {} 1", uid, uid)).unwrap_or_default(),
+                gid_map: CString::new(format!("{} {} 1", gid, gid)).unwrap_or_default(),
+            }
+        }
+    }
+
+    fn write_id_maps(maps: &IdMaps) -> std::io::Result<()> {
+        // Kernels before 3.19 have no setgroups file; the maps are required.
+        let _ = write_proc(c"/proc/self/setgroups", c"deny");
+        write_proc(c"/proc/self/uid_map", &maps.uid_map)?;
+        write_proc(c"/proc/self/gid_map", &maps.gid_map)
+    }
+
+    fn write_proc(path: &CStr, value: &CStr) -> std::io::Result<()> {
+        let bytes = value.to_bytes();
+        // SAFETY: plain open/write/close on NUL-terminated buffers.
+        unsafe {
+            let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let written = libc::write(fd, bytes.as_ptr().cast(), bytes.len());
+            let result = if written == bytes.len() as isize {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
+            };
+            libc::close(fd);
+            result
+        }
+    }
+
+    /// Probed once: Ubuntu and some hardened kernels disable unprivileged
+    /// user namespaces.
+    fn user_namespaces_available() -> bool {
+        static AVAILABLE: OnceLock<bool> = OnceLock::new();
+        *AVAILABLE.get_or_init(|| {
+            use std::os::unix::process::CommandExt;
+            let mut probe = std::process::Command::new("/bin/true");
+            // SAFETY: unshare is a single syscall in the forked child.
+            unsafe {
+                probe.pre_exec(|| {
+                    if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) == 0 {
+                        Ok(())
+                    } else {
+                        Err(std::io::Error::last_os_error())
+                    }
+                });
+            }
+            probe
+                .status()
+                .map(|status| status.success())
+                .unwrap_or(false)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_policy(allow: &[&str], deny: &[&str]) -> ToolAccessPolicy {
+        ToolAccessPolicy {
+            enabled: true,
+            mode: "all".to_string(),
+            allow: allow.iter().map(|tool| tool.to_string()).collect(),
+            deny: deny.iter().map(|tool| tool.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn network_requires_explicit_allow() {
+        let base = ShellSandboxPolicy::default();
+        assert!(!base.clone().for_tool_policy(None).allow_network);
+        assert!(
+            !base
+                .clone()
+                .for_tool_policy(Some(&tool_policy(&[], &[])))
+                .allow_network
+        );
+        assert!(
+            base.clone()
+                .for_tool_policy(Some(&tool_policy(&[SHELL_NETWORK_TOOL], &[])))
+                .allow_network
+        );
+        let workspace_allowed = ShellSandboxPolicy {
+            allow_network: true,
+            ..ShellSandboxPolicy::default()
+        };
+        assert!(
+            !workspace_allowed
+                .for_tool_policy(Some(&tool_policy(&[], &[SHELL_NETWORK_TOOL])))
+                .allow_network
+        );
+    }
+
+    #[test]
+    fn writable_roots_include_workspace_and_temp_dir() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let roots =
+            ShellSandboxPolicy::default().writable_roots(&[workspace.path().display().to_string()]);
+        assert!(roots.contains(&workspace.path().to_path_buf()));
+        assert!(roots.contains(&std::env::temp_dir()));
+    }
+
+    #[tokio::test]
+    async fn disabled_policy_only_scrubs_environment() {
+        let mut command = tokio::process::Command::new("env");
+        let policy = ShellSandboxPolicy {
+            enabled: false,
+            ..ShellSandboxPolicy::default()
+        };
+        let report = apply(&mut command, &policy, &[], &[])
+            .expect("sandbox applies")
+            .finish();
+        assert!(report.env_scrubbed);
+        assert!(!report.network_isolated);
+        assert_eq!(report.landlock_abi, None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn report_lists_layers_the_child_entered() {
+        let workspace = tempfile::tempdir().expect("tempdir");
+        let mut command = tokio::process::Command::new("/bin/true");
+        let sandbox = apply(
+            &mut command,
+            &ShellSandboxPolicy::default(),
+            &[workspace.path().display().to_string()],
+            &[],
+        )
+        .expect("sandbox applies");
+        let status = command.status().await.expect("command runs");
+        let report = sandbox.finish();
+        assert!(status.success());
+        assert!(report.rlimits);
+        assert!(!report
+            .notes
+            .iter()
+            .any(|note| note.contains("did not start")));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn seccomp_denies_namespace_clones_and_clone3() {
+        let filters = platform::build_seccomp_filters().expect("filters compile");
+        // SAFETY: the child only issues raw syscalls and then `_exit`s.
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0, "fork failed");
+        if pid == 0 {
+            let errno = || std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
+            // SAFETY: raw syscalls in the forked child; no clone here succeeds.
+            let code = unsafe {
+                libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0);
+                if !platform::install_seccomp_filters(&filters) {
+                    1
+                } else if libc::syscall(
+                    libc::SYS_clone,
+                    (libc::CLONE_NEWUSER | libc::SIGCHLD) as libc::c_ulong,
+                    0usize,
+                    0usize,
+                    0usize,
+                    0usize,
+                ) != -1
+                    || errno() != libc::EPERM
+                {
+                    2
+                } else if libc::syscall(libc::SYS_clone3, 0usize, 0usize) != -1
+                    || errno() != libc::ENOSYS
+                {
+                    3
+                } else {
+                    0
+                }
+            };
+            // SAFETY: leave the forked child without running test teardown.
+            unsafe { libc::_exit(code) };
+        }
+        let mut status = 0;
+        // SAFETY: waits for the child forked above.
+        unsafe { libc::waitpid(pid, &mut status, 0) };
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+    }
+}
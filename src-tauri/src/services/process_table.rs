@@ -0,0 +1,616 @@
+//! Background processes started by the shell skill.
+//!
+//! `start_process` spawns a policy-checked, sandboxed command with piped
+//! stdio and returns at once. Output from both streams is appended to a
+//! bounded buffer addressed by byte cursor, so `read_process_output` can page
+//! through it incrementally; each chunk is also emitted on the owning run as
+//! `AgentEvent::ProcessOutput`.
+//!
+//! A process started by an agent run watches that run's kill switch, and
+//! `AgentRunControl::cancel_run` kills whatever the run left behind.
+
+use crate::ai::agent::events::{AgentEvent, ProcessExitedPayload, ProcessOutputPayload};
+use crate::commands::agent_frontend_events::FrontendAgentEvent;
+use crate::services::agent_kill_switch::AgentKillSwitch;
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{watch, Mutex, Notify, RwLock};
+
+/// Output retained per process; older bytes are dropped and reported.
+const MAX_BUFFER_BYTES: usize = 1024 * 1024;
+pub const DEFAULT_READ_BYTES: usize = 16 * 1024;
+pub const MAX_READ_BYTES: usize = 48 * 1024;
+const MAX_RUNNING_PROCESSES: usize = 16;
+/// Finished processes kept for reading before the oldest are forgotten.
+const MAX_RETAINED_PROCESSES: usize = 64;
+const KILL_SWITCH_POLL: Duration = Duration::from_millis(250);
+/// How long to drain pipes after exit; grandchildren may hold them open.
+const PIPE_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+    Running,
+    Exited,
+    Killed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProcessStatus {
+    state: ProcessState,
+    exit_code: Option<i32>,
+}
+
+/// What was launched, for listings and tool results.
+#[derive(Debug, Clone)]
+pub struct ProcessSpec {
+    pub run_id: Option<String>,
+    pub workspace_id: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessInfo {
+    pub process_id: String,
+    pub run_id: Option<String>,
+    pub command: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+    pub started_at: i64,
+    pub state: ProcessState,
+    pub exit_code: Option<i32>,
+    /// Cursor just past the newest output byte.
+    pub output_end: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutputPage {
+    pub process_id: String,
+    pub output: String,
+    /// Cursor of the first returned byte; larger than the requested cursor
+    /// when older output was dropped from the buffer.
+    pub cursor: u64,
+    /// Pass this back to continue reading.
+    pub next_cursor: u64,
+    pub dropped_bytes: u64,
+    pub has_more: bool,
+    pub state: ProcessState,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Default)]
+struct OutputBuffer {
+    text: String,
+    /// Absolute cursor of `text[0]`.
+    base: u64,
+}
+
+impl OutputBuffer {
+    fn end(&self) -> u64 {
+        self.base + self.text.len() as u64
+    }
+
+    /// Appends `chunk`, returning its starting cursor.
+    fn push(&mut self, chunk: &str) -> u64 {
+        let start = self.end();
+        self.text.push_str(chunk);
+        if self.text.len() > MAX_BUFFER_BYTES {
+            let mut excess = self.text.len() - MAX_BUFFER_BYTES;
+            while !self.text.is_char_boundary(excess) {
+                excess += 1;
+            }
+            self.text.drain(..excess);
+            self.base += excess as u64;
+        }
+        start
+    }
+
+    /// Returns `(text, start, next)` for up to `max_bytes` from `cursor`.
+    fn read(&self, cursor: u64, max_bytes: usize) -> (String, u64, u64) {
+        let start = cursor.clamp(self.base, self.end());
+        let mut offset = (start - self.base) as usize;
+        while !self.text.is_char_boundary(offset) {
+            offset += 1;
+        }
+        let mut stop = offset.saturating_add(max_bytes).min(self.text.len());
+        while !self.text.is_char_boundary(stop) {
+            stop -= 1;
+        }
+        (
+            self.text[offset..stop].to_string(),
+            self.base + offset as u64,
+            self.base + stop as u64,
+        )
+    }
+}
+
+struct ManagedProcess {
+    id: String,
+    spec: ProcessSpec,
+    started_at: i64,
+    output: std::sync::Mutex<OutputBuffer>,
+    stdin: Mutex<Option<ChildStdin>>,
+    status: watch::Sender<ProcessStatus>,
+    kill: Notify,
+}
+
+impl ManagedProcess {
+    fn status(&self) -> ProcessStatus {
+        *self.status.borrow()
+    }
+
+    fn info(&self) -> ProcessInfo {
+        let status = self.status();
+        ProcessInfo {
+            process_id: self.id.clone(),
+            run_id: self.spec.run_id.clone(),
+            command: self.spec.command.clone(),
+            args: self.spec.args.clone(),
+            cwd: self.spec.cwd.clone(),
+            started_at: self.started_at,
+            state: status.state,
+            exit_code: status.exit_code,
+            output_end: self.output.lock().expect("process output poisoned").end(),
+        }
+    }
+}
+
+/// Process table shared by the shell skill and `AgentRunControl`.
+#[derive(Clone, Default)]
+pub struct ProcessTable {
+    processes: Arc<RwLock<HashMap<String, Arc<ManagedProcess>>>>,
+    app_handle: Arc<RwLock<Option<AppHandle>>>,
+}
+
+impl ProcessTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables streaming output to the frontend.
+    pub async fn set_app_handle(&self, app_handle: AppHandle) {
+        let mut lock = self.app_handle.write().await;
+        *lock = Some(app_handle);
+    }
+
+    /// Spawns `command` (already policy-checked and sandboxed) in the
+    /// background. The process is killed if `kill_switch` is triggered.
+    pub async fn start(
+        &self,
+        mut command: Command,
+        spec: ProcessSpec,
+        kill_switch: Option<AgentKillSwitch>,
+    ) -> Result<ProcessInfo, String> {
+        let mut processes = self.processes.write().await;
+        let running = processes
+            .values()
+            .filter(|process| process.status().state == ProcessState::Running)
+            .count();
+        if running >= MAX_RUNNING_PROCESSES {
+            return Err(format!(
+                "Too many background processes ({} running); kill one first",
+                running
+            ));
+        }
+        prune_finished(&mut processes);
+
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start process: {}", e))?;
+
+        let (status, _) = watch::channel(ProcessStatus {
+            state: ProcessState::Running,
+            exit_code: None,
+        });
+        let process = Arc::new(ManagedProcess {
+            id: format!("proc_{}", uuid::Uuid::new_v4().simple()),
+            spec,
+            started_at: Utc::now().timestamp_millis(),
+            output: std::sync::Mutex::new(OutputBuffer::default()),
+            stdin: Mutex::new(child.stdin.take()),
+            status,
+            kill: Notify::new(),
+        });
+        processes.insert(process.id.clone(), process.clone());
+        drop(processes);
+
+        let pumps = [
+            child
+                .stdout
+                .take()
+                .map(|pipe| self.spawn_pump(process.clone(), pipe, "stdout")),
+            child
+                .stderr
+                .take()
+                .map(|pipe| self.spawn_pump(process.clone(), pipe, "stderr")),
+        ];
+        let table = self.clone();
+        let monitored = process.clone();
+        tokio::spawn(async move {
+            table.monitor(monitored, child, pumps, kill_switch).await;
+        });
+
+        Ok(process.info())
+    }
+
+    fn spawn_pump(
+        &self,
+        process: Arc<ManagedProcess>,
+        mut pipe: impl AsyncRead + Unpin + Send + 'static,
+        stream: &'static str,
+    ) -> tokio::task::JoinHandle<()> {
+        let table = self.clone();
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 8 * 1024];
+            loop {
+                let read = match pipe.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => read,
+                };
+                let chunk = String::from_utf8_lossy(&buf[..read]).into_owned();
+                let cursor = process
+                    .output
+                    .lock()
+                    .expect("process output poisoned")
+                    .push(&chunk);
+                table
+                    .emit(
+                        &process,
+                        AgentEvent::ProcessOutput(ProcessOutputPayload {
+                            process_id: process.id.clone(),
+                            stream: stream.to_string(),
+                            chunk,
+                            cursor,
+                        }),
+                    )
+                    .await;
+            }
+        })
+    }
+
+    async fn monitor(
+        &self,
+        process: Arc<ManagedProcess>,
+        mut child: Child,
+        pumps: [Option<tokio::task::JoinHandle<()>>; 2],
+        kill_switch: Option<AgentKillSwitch>,
+    ) {
+        let outcome = tokio::select! {
+            status = child.wait() => Some(status),
+            _ = process.kill.notified() => None,
+            _ = wait_for_trigger(kill_switch) => None,
+        };
+        let status = match outcome {
+            Some(Ok(status)) => ProcessStatus {
+                state: ProcessState::Exited,
+                exit_code: status.code(),
+            },
+            Some(Err(_)) => ProcessStatus {
+                state: ProcessState::Failed,
+                exit_code: None,
+            },
+            None => {
+                kill_process_group(&mut child).await;
+                ProcessStatus {
+                    state: ProcessState::Killed,
+                    exit_code: None,
+                }
+            }
+        };
+
+        for pump in pumps.into_iter().flatten() {
+            let abort = pump.abort_handle();
+            if tokio::time::timeout(PIPE_DRAIN_TIMEOUT, pump)
+                .await
+                .is_err()
+            {
+                abort.abort();
+            }
+        }
+        process.stdin.lock().await.take();
+        process.status.send_replace(status);
+
+        self.emit(
+            &process,
+            AgentEvent::ProcessExited(ProcessExitedPayload {
+                process_id: process.id.clone(),
+                state: status.state,
+                exit_code: status.exit_code,
+            }),
+        )
+        .await;
+    }
+
+    async fn emit(&self, process: &ManagedProcess, event: AgentEvent) {
+        let Some(run_id) = process.spec.run_id.as_ref() else {
+            return;
+        };
+        if let Some(app_handle) = self.app_handle.read().await.as_ref() {
+            let _ = app_handle.emit(
+                "agent://event",
+                FrontendAgentEvent {
+                    run_id: run_id.clone(),
+                    timestamp_ms: Utc::now().timestamp_millis(),
+                    payload: event,
+                },
+            );
+        }
+    }
+
+    async fn get(
+        &self,
+        process_id: &str,
+        workspace_id: &str,
+    ) -> Result<Arc<ManagedProcess>, String> {
+        self.processes
+            .read()
+            .await
+            .get(process_id)
+            .filter(|process| process.spec.workspace_id == workspace_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown process: {}", process_id))
+    }
+
+    pub async fn read_output(
+        &self,
+        process_id: &str,
+        workspace_id: &str,
+        cursor: u64,
+        max_bytes: usize,
+    ) -> Result<ProcessOutputPage, String> {
+        let process = self.get(process_id, workspace_id).await?;
+        let status = process.status();
+        let output = process.output.lock().expect("process output poisoned");
+        let (text, start, next) = output.read(cursor, max_bytes.clamp(1, MAX_READ_BYTES));
+        Ok(ProcessOutputPage {
+            process_id: process.id.clone(),
+            output: text,
+            cursor: start,
+            next_cursor: next,
+            dropped_bytes: start.saturating_sub(cursor),
+            has_more: next < output.end(),
+            state: status.state,
+            exit_code: status.exit_code,
+        })
+    }
+
+    pub async fn write_stdin(
+        &self,
+        process_id: &str,
+        workspace_id: &str,
+        data: &str,
+        close: bool,
+    ) -> Result<usize, String> {
+        let process = self.get(process_id, workspace_id).await?;
+        let mut stdin = process.stdin.lock().await;
+        let pipe = stdin
+            .as_mut()
+            .ok_or_else(|| format!("Process {} has no open stdin", process_id))?;
+        pipe.write_all(data.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to process stdin: {}", e))?;
+        pipe.flush()
+            .await
+            .map_err(|e| format!("Failed to flush process stdin: {}", e))?;
+        if close {
+            stdin.take();
+        }
+        Ok(data.len())
+    }
+
+    /// Waits up to `timeout` for the process to finish.
+    pub async fn wait(
+        &self,
+        process_id: &str,
+        workspace_id: &str,
+        timeout: Duration,
+    ) -> Result<ProcessInfo, String> {
+        let process = self.get(process_id, workspace_id).await?;
+        let mut status = process.status.subscribe();
+        let _ = tokio::time::timeout(
+            timeout,
+            status.wait_for(|status| status.state != ProcessState::Running),
+        )
+        .await;
+        Ok(process.info())
+    }
+
+    pub async fn kill(&self, process_id: &str, workspace_id: &str) -> Result<ProcessInfo, String> {
+        let process = self.get(process_id, workspace_id).await?;
+        terminate(&process).await;
+        Ok(process.info())
+    }
+
+    /// Kills every running process started by `run_id`, returning how many.
+    pub async fn kill_run(&self, run_id: &str) -> usize {
+        let targets: Vec<Arc<ManagedProcess>> = self
+            .processes
+            .read()
+            .await
+            .values()
+            .filter(|process| {
+                process.spec.run_id.as_deref() == Some(run_id)
+                    && process.status().state == ProcessState::Running
+            })
+            .cloned()
+            .collect();
+        for process in &targets {
+            terminate(process).await;
+        }
+        targets.len()
+    }
+}
+
+async fn terminate(process: &ManagedProcess) {
+    let mut status = process.status.subscribe();
+    if status.borrow().state != ProcessState::Running {
+        return;
+    }
+    process.kill.notify_one();
+    let _ = tokio::time::timeout(
+        PIPE_DRAIN_TIMEOUT * 2,
+        status.wait_for(|status| status.state != ProcessState::Running),
+    )
+    .await;
+}
+
+async fn wait_for_trigger(kill_switch: Option<AgentKillSwitch>) {
+    let Some(kill_switch) = kill_switch else {
+        return std::future::pending().await;
+    };
+    while !kill_switch.is_triggered() {
+        tokio::time::sleep(KILL_SWITCH_POLL).await;
+    }
+}
+
+/// Kills the whole process group so dev servers don't orphan their workers.
+async fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: signalling our own child's process group has no memory effects.
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill().await;
+}
+
+fn prune_finished(processes: &mut HashMap<String, Arc<ManagedProcess>>) {
+    if processes.len() < MAX_RETAINED_PROCESSES {
+        return;
+    }
+    let mut finished: Vec<(i64, String)> = processes
+        .values()
+        .filter(|process| process.status().state != ProcessState::Running)
+        .map(|process| (process.started_at, process.id.clone()))
+        .collect();
+    finished.sort();
+    let excess = processes.len() + 1 - MAX_RETAINED_PROCESSES;
+    for (_, id) in finished.into_iter().take(excess) {
+        processes.remove(&id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(run_id: &str, command: &str) -> ProcessSpec {
+        ProcessSpec {
+            run_id: Some(run_id.to_string()),
+            workspace_id: "ws".to_string(),
+            command: command.to_string(),
+            args: Vec::new(),
+            cwd: ".".to_string(),
+        }
+    }
+
+    #[test]
+    fn output_buffer_drops_oldest_bytes_and_keeps_cursors() {
+        let mut buffer = OutputBuffer::default();
+        assert_eq!(buffer.push("hello "), 0);
+        assert_eq!(buffer.push("world"), 6);
+
+        let (text, start, next) = buffer.read(6, 3);
+        assert_eq!((text.as_str(), start, next), ("wor", 6, 9));
+
+        buffer.push(&"x".repeat(MAX_BUFFER_BYTES));
+        assert_eq!(buffer.base, 11);
+        let (text, start, _) = buffer.read(0, 4);
+        assert_eq!((text.as_str(), start), ("xxxx", 11));
+    }
+
+    #[test]
+    fn output_buffer_reads_on_char_boundaries() {
+        let mut buffer = OutputBuffer::default();
+        buffer.push("añb");
+        let (text, _, next) = buffer.read(0, 2);
+        assert_eq!((text.as_str(), next), ("a", 1));
+        let (text, start, _) = buffer.read(2, 8);
+        assert_eq!((text.as_str(), start), ("b", 3));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn captures_output_and_stdin_until_exit() {
+        let table = ProcessTable::new();
+        let info = table
+            .start(Command::new("cat"), spec("run-1", "cat"), None)
+            .await
+            .expect("start cat");
+        table
+            .write_stdin(&info.process_id, "ws", "ping\n", true)
+            .await
+            .expect("write stdin");
+        let done = table
+            .wait(&info.process_id, "ws", Duration::from_secs(5))
+            .await
+            .expect("wait");
+        assert_eq!(done.state, ProcessState::Exited);
+        assert_eq!(done.exit_code, Some(0));
+
+        let page = table
+            .read_output(&info.process_id, "ws", 0, DEFAULT_READ_BYTES)
+            .await
+            .expect("read output");
+        assert_eq!(page.output, "ping\n");
+        assert!(!page.has_more);
+        assert!(table
+            .read_output(&info.process_id, "other-ws", 0, 16)
+            .await
+            .is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kill_switch_and_kill_run_stop_processes() {
+        let table = ProcessTable::new();
+        let switch = AgentKillSwitch::new();
+        let mut sleep = Command::new("sleep");
+        sleep.arg("30");
+        let watched = table
+            .start(sleep, spec("run-1", "sleep"), Some(switch.clone()))
+            .await
+            .expect("start sleep");
+        let mut sleep = Command::new("sleep");
+        sleep.arg("30");
+        let other = table
+            .start(sleep, spec("run-2", "sleep"), None)
+            .await
+            .expect("start sleep");
+
+        switch.trigger();
+        let done = table
+            .wait(&watched.process_id, "ws", Duration::from_secs(5))
+            .await
+            .expect("wait");
+        assert_eq!(done.state, ProcessState::Killed);
+
+        assert_eq!(table.kill_run("run-2").await, 1);
+        let done = table
+            .wait(&other.process_id, "ws", Duration::from_secs(1))
+            .await
+            .expect("wait");
+        assert_eq!(done.state, ProcessState::Killed);
+        assert_eq!(table.kill_run("run-2").await, 0);
+    }
+}
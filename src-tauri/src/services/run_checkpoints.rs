@@ -0,0 +1,402 @@
+//! Durable checkpoints for agent runs.
+//!
+//! A run's checkpoint is rewritten after every completed think/act step (or,
+//! for supervisor modes, after every finished lane or branch), so a crash or
+//! app restart loses at most the step in flight. Rows still marked `running`
+//! at startup belonged to a process that died and are flipped to
+//! `interrupted`; `resume_agent_run` picks them up from the last completed
+//! step. The Airlock mirrors a run's outstanding approval requests into the
+//! same row while it waits on the operator.
+
+use crate::ai::agent::hierarchical_supervisor::HierarchicalProgress;
+use crate::ai::agent::runtime::RuntimeOptions;
+use crate::ai::agent::supervisor::SupervisorProgress;
+use crate::ai::agent::workflow::WorkflowProgress;
+use crate::ai::specs::manifest::AgentSpec;
+use crate::services::airlock::ApprovalRequest;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Interrupted,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl RunStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Running => "running",
+            RunStatus::Interrupted => "interrupted",
+            RunStatus::Completed => "completed",
+            RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "running" => Some(RunStatus::Running),
+            "interrupted" => Some(RunStatus::Interrupted),
+            "completed" => Some(RunStatus::Completed),
+            "failed" => Some(RunStatus::Failed),
+            "cancelled" => Some(RunStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// How far a run got, in the shape of the runtime mode that drives it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RunProgress {
+    /// Nothing has completed yet; resuming starts the run over.
+    Started,
+    Workflow(WorkflowProgress),
+    Supervisor(Box<SupervisorProgress>),
+    Hierarchical(HierarchicalProgress),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunCheckpoint {
+    pub run_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    pub status: RunStatus,
+    /// The user prompt that started the run.
+    pub input: String,
+    pub spec: AgentSpec,
+    pub options: RuntimeOptions,
+    pub progress: RunProgress,
+    /// Approval requests the run was blocked on when last written.
+    #[serde(default)]
+    pub pending_approvals: Vec<ApprovalRequest>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl RunCheckpoint {
+    pub fn start(input: &str, spec: AgentSpec, options: RuntimeOptions) -> Result<Self, String> {
+        let run_id = options
+            .run_id
+            .clone()
+            .ok_or("A checkpointed run needs a run id")?;
+        let now = chrono::Utc::now().timestamp_millis();
+        Ok(Self {
+            run_id,
+            chat_id: options.chat_id.clone(),
+            status: RunStatus::Running,
+            input: input.to_string(),
+            spec,
+            options,
+            progress: RunProgress::Started,
+            pending_approvals: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+/// Listing entry for the "resume interrupted run" UI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunCheckpointSummary {
+    pub run_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<String>,
+    pub agent_name: String,
+    pub status: RunStatus,
+    pub input: String,
+    pub pending_approvals: usize,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunCheckpointStore {
+    pool: Pool<Sqlite>,
+}
+
+impl RunCheckpointStore {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    /// Marks runs left `running` by the previous process as interrupted. The
+    /// table itself comes from the `add_run_checkpoints` migration.
+    pub async fn init(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE run_checkpoints SET status = 'interrupted' WHERE status = 'running'")
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn save(&self, checkpoint: &RunCheckpoint) -> Result<(), String> {
+        let json = serde_json::to_string(checkpoint)
+            .map_err(|e| format!("Failed to serialize run checkpoint: {}", e))?;
+        sqlx::query(
+            r#"
+            INSERT INTO run_checkpoints (
+                run_id, chat_id, agent_name, status, input, checkpoint_json, created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(run_id) DO UPDATE SET
+                chat_id=excluded.chat_id,
+                agent_name=excluded.agent_name,
+                status=excluded.status,
+                input=excluded.input,
+                checkpoint_json=excluded.checkpoint_json,
+                updated_at=excluded.updated_at
+            "#,
+        )
+        .bind(&checkpoint.run_id)
+        .bind(&checkpoint.chat_id)
+        .bind(&checkpoint.spec.soul.name)
+        .bind(checkpoint.status.as_str())
+        .bind(&checkpoint.input)
+        .bind(json)
+        .bind(checkpoint.created_at)
+        .bind(checkpoint.updated_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to save run checkpoint: {}", e))?;
+        Ok(())
+    }
+
+    /// Mirror of the Airlock approvals `run_id` is waiting on. A no-op for runs
+    /// that are not checkpointed.
+    pub async fn set_pending_approvals(
+        &self,
+        run_id: &str,
+        approvals: &[ApprovalRequest],
+    ) -> Result<(), String> {
+        let json = serde_json::to_string(approvals)
+            .map_err(|e| format!("Failed to serialize pending approvals: {}", e))?;
+        sqlx::query("UPDATE run_checkpoints SET pending_approvals_json = ? WHERE run_id = ?")
+            .bind(json)
+            .bind(run_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to save pending approvals: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn load(&self, run_id: &str) -> Result<Option<RunCheckpoint>, String> {
+        let row = sqlx::query(
+            "SELECT status, checkpoint_json, pending_approvals_json FROM run_checkpoints WHERE run_id = ?",
+        )
+        .bind(run_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to load run checkpoint: {}", e))?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let json: String = row.get("checkpoint_json");
+        let mut checkpoint: RunCheckpoint = serde_json::from_str(&json)
+            .map_err(|e| format!("Run checkpoint {} is unreadable: {}", run_id, e))?;
+        // The status column is authoritative: startup flips it without
+        // rewriting the JSON.
+        let status: String = row.get("status");
+        if let Some(status) = RunStatus::parse(&status) {
+            checkpoint.status = status;
+        }
+        let approvals: String = row.get("pending_approvals_json");
+        checkpoint.pending_approvals = serde_json::from_str(&approvals).unwrap_or_default();
+        Ok(Some(checkpoint))
+    }
+
+    /// Runs that stopped before finishing, most recent first.
+    pub async fn list_resumable(&self) -> Result<Vec<RunCheckpointSummary>, String> {
+        let rows = sqlx::query(
+            r#"
+            SELECT run_id, chat_id, agent_name, status, input, pending_approvals_json,
+                   created_at, updated_at
+            FROM run_checkpoints
+            WHERE status IN ('interrupted', 'failed')
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to list run checkpoints: {}", e))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let status: String = row.get("status");
+                let approvals: String = row.get("pending_approvals_json");
+                Some(RunCheckpointSummary {
+                    run_id: row.get("run_id"),
+                    chat_id: row.get("chat_id"),
+                    agent_name: row.get("agent_name"),
+                    status: RunStatus::parse(&status)?,
+                    input: row.get("input"),
+                    pending_approvals: serde_json::from_str::<Vec<serde_json::Value>>(&approvals)
+                        .map(|approvals| approvals.len())
+                        .unwrap_or_default(),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                })
+            })
+            .collect())
+    }
+
+    pub async fn delete(&self, run_id: &str) -> Result<(), String> {
+        sqlx::query("DELETE FROM run_checkpoints WHERE run_id = ?")
+            .bind(run_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to delete run checkpoint: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Handle a running agent uses to write its checkpoint. Cheap to clone, so
+/// supervisor lanes can share it. Write failures are logged rather than
+/// failing the run: losing durability is better than losing the run.
+#[derive(Clone)]
+pub struct RunRecorder {
+    store: Arc<RunCheckpointStore>,
+    checkpoint: Arc<Mutex<RunCheckpoint>>,
+}
+
+impl std::fmt::Debug for RunRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunRecorder").finish_non_exhaustive()
+    }
+}
+
+impl RunRecorder {
+    pub fn new(store: Arc<RunCheckpointStore>, checkpoint: RunCheckpoint) -> Self {
+        Self {
+            store,
+            checkpoint: Arc::new(Mutex::new(checkpoint)),
+        }
+    }
+
+    pub async fn record(&self, progress: RunProgress) {
+        let mut checkpoint = self.checkpoint.lock().await;
+        checkpoint.progress = progress;
+        checkpoint.status = RunStatus::Running;
+        checkpoint.updated_at = chrono::Utc::now().timestamp_millis();
+        if let Err(error) = self.store.save(&checkpoint).await {
+            tracing::warn!("Run {}: {}", checkpoint.run_id, error);
+        }
+    }
+
+    /// Completed and cancelled runs are done with; failed ones keep their
+    /// checkpoint so they can be retried from the last good step.
+    pub async fn finish(&self, status: RunStatus) {
+        let mut checkpoint = self.checkpoint.lock().await;
+        let result = match status {
+            RunStatus::Completed | RunStatus::Cancelled => {
+                self.store.delete(&checkpoint.run_id).await
+            }
+            _ => {
+                checkpoint.status = status;
+                checkpoint.updated_at = chrono::Utc::now().timestamp_millis();
+                self.store.save(&checkpoint).await
+            }
+        };
+        if let Err(error) = result {
+            tracing::warn!("Run {}: {}", checkpoint.run_id, error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn store() -> RunCheckpointStore {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory sqlite pool");
+        sqlx::raw_sql(include_str!(
+            "../../migrations/20260410090000_add_run_checkpoints.sql"
+        ))
+        .execute(&pool)
+        .await
+        .expect("run checkpoints migration");
+        let store = RunCheckpointStore::new(pool);
+        store.init().await.expect("init run checkpoints");
+        store
+    }
+
+    fn checkpoint(run_id: &str) -> RunCheckpoint {
+        let spec =
+            crate::services::default_agent_spec::build_default_local_agent_spec("default", "Rainy");
+        let options: RuntimeOptions = serde_json::from_value(serde_json::json!({
+            "workspace_id": "/tmp/workspace",
+            "run_id": run_id,
+            "chat_id": "chat-1",
+        }))
+        .expect("runtime options");
+        RunCheckpoint::start("fix the build", spec, options).expect("checkpoint")
+    }
+
+    #[tokio::test]
+    async fn running_checkpoints_are_interrupted_on_restart_and_cleared_on_completion() {
+        let store = Arc::new(store().await);
+        let recorder = RunRecorder::new(store.clone(), checkpoint("run-1"));
+        recorder.record(RunProgress::Started).await;
+        store
+            .set_pending_approvals(
+                "run-1",
+                &[ApprovalRequest {
+                    command_id: "cmd-1".to_string(),
+                    intent: "filesystem.write_file".to_string(),
+                    tool_name: Some("write_file".to_string()),
+                    payload_summary: "write src/main.rs".to_string(),
+                    diff_preview: None,
+                    airlock_level: crate::models::neural::AirlockLevel::Sensitive,
+                    timeout_secs: None,
+                    expires_at: None,
+                    timestamp: 0,
+                    run_id: Some("run-1".to_string()),
+                }],
+            )
+            .await
+            .unwrap();
+        assert!(store.list_resumable().await.unwrap().is_empty());
+
+        // Simulate the next launch.
+        store.init().await.unwrap();
+        let resumable = store.list_resumable().await.unwrap();
+        assert_eq!(resumable.len(), 1);
+        assert_eq!(resumable[0].status, RunStatus::Interrupted);
+        assert_eq!(resumable[0].pending_approvals, 1);
+        let loaded = store.load("run-1").await.unwrap().expect("checkpoint");
+        assert_eq!(loaded.status, RunStatus::Interrupted);
+        assert_eq!(loaded.input, "fix the build");
+        assert_eq!(loaded.pending_approvals[0].command_id, "cmd-1");
+
+        recorder.finish(RunStatus::Completed).await;
+        assert!(store.load("run-1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn failed_runs_stay_resumable() {
+        let store = Arc::new(store().await);
+        let recorder = RunRecorder::new(store.clone(), checkpoint("run-2"));
+        recorder.record(RunProgress::Started).await;
+        recorder.finish(RunStatus::Failed).await;
+
+        let resumable = store.list_resumable().await.unwrap();
+        assert_eq!(resumable.len(), 1);
+        assert_eq!(resumable[0].status, RunStatus::Failed);
+        assert_eq!(resumable[0].chat_id.as_deref(), Some("chat-1"));
+    }
+}
@@ -5,6 +5,7 @@ use crate::ai::model_catalog::{ensure_supported_model_slug, find_catalog_model,
 use crate::ai::provider::AIProviderManager;
 use crate::models::neural::ToolAccessPolicy;
 use crate::services::mcp_service::{McpPermissionMode, PersistedMcpServerConfig};
+use crate::services::memory_vault::key_provider::VaultKeyProviderKind;
 use rainy_sdk::models::{CapabilityFlag, ModelCatalogItem};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -43,10 +44,18 @@ pub struct UserSettings {
     pub workspace_tool_access_policies: HashMap<String, WorkspaceToolPolicyState>,
     pub embedder_provider: String,
     pub embedder_model: String,
+    /// Server for the `openai` and `ollama` embedders; their defaults when unset
+    #[serde(default)]
+    pub embedder_base_url: Option<String>,
     #[serde(default)]
     pub mcp_permission_mode: McpPermissionMode,
     #[serde(default)]
     pub mcp_servers: Vec<PersistedMcpServerConfig>,
+    #[serde(default)]
+    pub vault_key_provider: VaultKeyProviderKind,
+    /// Custom key file path for the `file` vault key provider
+    #[serde(default)]
+    pub vault_key_file: Option<String>,
 }
 
 /// User profile metadata for desktop personalization and cloud identity sync
@@ -82,8 +91,11 @@ impl Default for UserSettings {
             workspace_tool_access_policies: HashMap::new(),
             embedder_provider: "gemini".to_string(),
             embedder_model: crate::services::memory_vault::types::EMBEDDING_MODEL.to_string(),
+            embedder_base_url: None,
             mcp_permission_mode: McpPermissionMode::Ask,
             mcp_servers: Vec::new(),
+            vault_key_provider: VaultKeyProviderKind::default(),
+            vault_key_file: None,
         }
     }
 }
@@ -173,6 +185,14 @@ impl SettingsManager {
         &self.settings
     }
 
+    /// Directory holding settings.json and app-level key material
+    pub fn data_dir(&self) -> PathBuf {
+        self.settings_path
+            .parent()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
     /// Get selected model
     pub fn get_selected_model(&self) -> &str {
         &self.settings.selected_model
@@ -242,6 +262,46 @@ impl SettingsManager {
         self.save_to_disk()
     }
 
+    /// Get the embedder server URL
+    pub fn get_embedder_base_url(&self) -> Option<String> {
+        self.settings.embedder_base_url.clone()
+    }
+
+    /// Set the embedder server URL and persist
+    pub fn set_embedder_base_url(&mut self, base_url: Option<String>) -> Result<(), String> {
+        self.settings.embedder_base_url = base_url
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+        self.save_to_disk()
+    }
+
+    /// Get the vault key provider
+    pub fn get_vault_key_provider(&self) -> VaultKeyProviderKind {
+        self.settings.vault_key_provider
+    }
+
+    /// Get the custom key file for the file vault key provider
+    pub fn get_vault_key_file(&self) -> Option<String> {
+        self.settings.vault_key_file.clone()
+    }
+
+    /// Set the vault key provider and persist
+    pub fn set_vault_key_provider(
+        &mut self,
+        provider: VaultKeyProviderKind,
+        key_file: Option<String>,
+    ) -> Result<(), String> {
+        if !provider.is_supported() {
+            return Err(format!(
+                "Vault key provider {:?} is not supported on this platform",
+                provider
+            ));
+        }
+        self.settings.vault_key_provider = provider;
+        self.settings.vault_key_file = key_file.filter(|path| !path.trim().is_empty());
+        self.save_to_disk()
+    }
+
     /// Get the persisted minimum accepted tool policy version for a workspace.
     pub fn get_tool_policy_floor(&self, workspace_id: &str) -> u64 {
         self.settings
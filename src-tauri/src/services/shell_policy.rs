@@ -0,0 +1,303 @@
+//! Per-workspace command policy for the shell skill.
+//!
+//! A workspace lists the binaries `execute_command` may launch and, per
+//! binary, which subcommands are allowed and which argument patterns are
+//! denied. Patterns are whitespace-separated globs that must all match some
+//! argument, so `push --force*` rejects `git push --force-with-lease` while
+//! `git push` stays allowed.
+//!
+//! Workspaces without a stored policy get `ShellCommandPolicy::default()`,
+//! which keeps the historical nine-binary allowlist and denies publishing and
+//! force-pushing. `git push` arguments are parsed before matching so bundled
+//! flags (`-fu`), `+refspec` and abbreviated options (`--forc`) are seen in
+//! their canonical `-f`/`--force` form.
+
+use crate::services::airlock_policy::glob_match;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Long `git push` options that force a non-fast-forward update.
+const GIT_PUSH_FORCE_OPTIONS: &[&str] = &["force", "force-with-lease", "force-if-includes"];
+/// Git options whose value is the next argument.
+const GIT_GLOBAL_VALUE_OPTIONS: &[&str] = &["-c", "-C", "--git-dir", "--work-tree", "--namespace"];
+const GIT_PUSH_VALUE_OPTIONS: &[&str] =
+    &["-o", "--push-option", "--repo", "--receive-pack", "--exec"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShellCommandPolicy {
+    pub binaries: Vec<ShellBinaryRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShellBinaryRule {
+    /// Executable name, e.g. `cargo`. Paths are rejected.
+    pub binary: String,
+    /// Allowed subcommands (first non-flag argument). Empty allows any.
+    #[serde(default)]
+    pub allow_subcommands: Vec<String>,
+    /// Denied argument patterns, e.g. `publish` or `push --force*`.
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// Why a command was rejected, naming the exact rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellPolicyViolation {
+    /// Rule location, e.g. `binaries`, `cargo.allow_subcommands`, `git.deny[1]`.
+    pub rule: String,
+    pub detail: String,
+}
+
+impl fmt::Display for ShellPolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (workspace command policy rule `{}`)",
+            self.detail, self.rule
+        )
+    }
+}
+
+impl ShellBinaryRule {
+    fn new(binary: &str, deny: &[&str]) -> Self {
+        Self {
+            binary: binary.to_string(),
+            allow_subcommands: Vec::new(),
+            deny: deny.iter().map(|pattern| pattern.to_string()).collect(),
+        }
+    }
+}
+
+impl Default for ShellCommandPolicy {
+    fn default() -> Self {
+        Self {
+            binaries: vec![
+                ShellBinaryRule::new("npm", &["publish"]),
+                ShellBinaryRule::new("pnpm", &["publish"]),
+                ShellBinaryRule::new("bun", &["publish"]),
+                ShellBinaryRule::new("cargo", &["publish", "login", "owner", "yank"]),
+                ShellBinaryRule::new("git", &["push --force*", "push -f"]),
+                ShellBinaryRule::new("ls", &[]),
+                ShellBinaryRule::new("grep", &[]),
+                ShellBinaryRule::new("echo", &[]),
+                ShellBinaryRule::new("cat", &[]),
+            ],
+        }
+    }
+}
+
+impl ShellCommandPolicy {
+    pub fn allows_binary(&self, binary: &str) -> bool {
+        self.rule_for(binary).is_some()
+    }
+
+    fn rule_for(&self, binary: &str) -> Option<&ShellBinaryRule> {
+        self.binaries.iter().find(|rule| rule.binary == binary)
+    }
+
+    /// Checks `binary args...` against the policy.
+    pub fn check(&self, binary: &str, args: &[String]) -> Result<(), ShellPolicyViolation> {
+        if binary.contains('/') || binary.contains('\\') {
+            return Err(ShellPolicyViolation {
+                rule: "binaries".to_string(),
+                detail: format!("Command '{}' must be a bare binary name", binary),
+            });
+        }
+        let Some(rule) = self.rule_for(binary) else {
+            return Err(ShellPolicyViolation {
+                rule: "binaries".to_string(),
+                detail: format!("Command '{}' is not allowed", binary),
+            });
+        };
+
+        if !rule.allow_subcommands.is_empty() {
+            let subcommand = args.iter().find(|arg| !arg.starts_with('-'));
+            let allowed = subcommand.is_some_and(|subcommand| {
+                rule.allow_subcommands
+                    .iter()
+                    .any(|allowed| glob_match(allowed, subcommand))
+            });
+            if !allowed {
+                return Err(ShellPolicyViolation {
+                    rule: format!("{}.allow_subcommands", binary),
+                    detail: format!(
+                        "Subcommand '{}' of '{}' is not allowed (allowed: {})",
+                        subcommand.map(String::as_str).unwrap_or(""),
+                        binary,
+                        rule.allow_subcommands.join(", ")
+                    ),
+                });
+            }
+        }
+
+        let matched_args = deny_match_args(binary, args);
+        for (index, pattern) in rule.deny.iter().enumerate() {
+            let mut tokens = pattern.split_whitespace().peekable();
+            if tokens.peek().is_none() {
+                continue;
+            }
+            if tokens.all(|token| matched_args.iter().any(|arg| glob_match(token, arg))) {
+                return Err(ShellPolicyViolation {
+                    rule: format!("{}.deny[{}]", binary, index),
+                    detail: format!(
+                        "Command '{} {}' matches denied pattern '{}'",
+                        binary,
+                        args.join(" "),
+                        pattern
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Arguments as deny patterns see them: the originals plus, for `git push`,
+/// the canonical spelling of every flag or refspec that forces the update.
+fn deny_match_args(binary: &str, args: &[String]) -> Vec<String> {
+    let mut matched = args.to_vec();
+    if binary != "git" {
+        return matched;
+    }
+    let Some(push) = git_subcommand_index(args).filter(|index| args[*index] == "push") else {
+        return matched;
+    };
+    let mut rest = args[push + 1..].iter();
+    while let Some(arg) = rest.next() {
+        if GIT_PUSH_VALUE_OPTIONS.contains(&arg.as_str()) {
+            rest.next();
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let name = long.split('=').next().unwrap_or_default();
+            // git accepts any unambiguous prefix of a long option.
+            let canonical = GIT_PUSH_FORCE_OPTIONS
+                .iter()
+                .find(|option| name.len() >= 3 && option.starts_with(name));
+            if let Some(option) = canonical {
+                matched.push(format!("--{}", option));
+            }
+        } else if let Some(flags) = arg.strip_prefix('-') {
+            for flag in flags.chars() {
+                matched.push(format!("-{}", flag));
+                // `-o` takes the rest of the argument as its value.
+                if flag == 'o' {
+                    break;
+                }
+            }
+        } else if arg.starts_with('+') {
+            matched.push("--force".to_string());
+        }
+    }
+    matched
+}
+
+/// Index of the git subcommand, skipping global options and their values.
+fn git_subcommand_index(args: &[String]) -> Option<usize> {
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_str();
+        if GIT_GLOBAL_VALUE_OPTIONS.contains(&arg) {
+            index += 2;
+        } else if arg.starts_with('-') {
+            index += 1;
+        } else {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn default_policy_keeps_historical_allowlist() {
+        let policy = ShellCommandPolicy::default();
+        for binary in [
+            "npm", "pnpm", "bun", "cargo", "git", "ls", "grep", "echo", "cat",
+        ] {
+            assert!(policy.allows_binary(binary));
+        }
+        for binary in ["rm", "curl", "python"] {
+            assert_eq!(policy.check(binary, &[]).unwrap_err().rule, "binaries");
+        }
+        assert!(policy.check("/bin/ls", &[]).is_err());
+    }
+
+    #[test]
+    fn deny_patterns_report_the_matching_rule() {
+        let policy = ShellCommandPolicy::default();
+        assert!(policy
+            .check("git", &args(&["push", "origin", "main"]))
+            .is_ok());
+        let violation = policy
+            .check("git", &args(&["push", "--force-with-lease"]))
+            .unwrap_err();
+        assert_eq!(violation.rule, "git.deny[0]");
+        assert_eq!(
+            policy.check("cargo", &args(&["publish"])).unwrap_err().rule,
+            "cargo.deny[0]"
+        );
+    }
+
+    #[test]
+    fn force_push_spellings_are_denied() {
+        let policy = ShellCommandPolicy::default();
+        for push in [
+            &["push", "-fu", "origin", "main"][..],
+            &["push", "origin", "+main"],
+            &["push", "origin", "+HEAD:refs/heads/main"],
+            &["push", "--forc", "origin"],
+            &["push", "--force-with-lease=main:abc123"],
+            &["push", "--force-if", "origin"],
+            &["-c", "push.default=current", "push", "-f"],
+        ] {
+            assert!(policy.check("git", &args(push)).is_err(), "{:?}", push);
+        }
+        for push in [
+            &["push", "-u", "origin", "main"][..],
+            &["push", "-o", "+ci.skip", "origin", "main"],
+            &["push", "--follow-tags"],
+            &["commit", "-m", "+force"],
+        ] {
+            assert!(policy.check("git", &args(push)).is_ok(), "{:?}", push);
+        }
+    }
+
+    #[test]
+    fn allowed_subcommands_restrict_the_binary() {
+        let policy: ShellCommandPolicy = serde_json::from_value(serde_json::json!({
+            "binaries": [
+                {"binary": "cargo", "allow_subcommands": ["test", "check", "clippy"]},
+                {"binary": "make"},
+                {"binary": "python3", "deny": ["-c"]}
+            ]
+        }))
+        .expect("valid policy");
+
+        assert!(policy
+            .check("cargo", &args(&["test", "--workspace"]))
+            .is_ok());
+        assert!(policy.check("cargo", &args(&["--locked", "check"])).is_ok());
+        assert_eq!(
+            policy.check("cargo", &args(&["run"])).unwrap_err().rule,
+            "cargo.allow_subcommands"
+        );
+        assert!(policy.check("make", &args(&["build"])).is_ok());
+        assert!(policy.check("python3", &args(&["-m", "pytest"])).is_ok());
+        assert_eq!(
+            policy
+                .check("python3", &args(&["-c", "print(1)"]))
+                .unwrap_err()
+                .rule,
+            "python3.deny[0]"
+        );
+        assert!(!policy.allows_binary("git"));
+    }
+}
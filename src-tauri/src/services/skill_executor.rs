@@ -1,24 +1,33 @@
 mod args;
 mod browser;
+mod code_search;
 mod documents;
+mod edits;
 mod evm;
 mod external_agents;
 mod filesystem;
+mod processes;
 mod registry;
 mod scheduler;
 mod shell;
 mod web;
 
 use crate::models::neural::{CommandResult, QueuedCommand, ToolAccessPolicy};
+use crate::services::agent_run_control::AgentRunControl;
+use crate::services::audit_ledger::AuditLedger;
 use crate::services::beam_rpc::BeamRpcService;
 use crate::services::browser_controller::BrowserController;
+use crate::services::code_index::CodeIndex;
 use crate::services::external_agent_runtime::ExternalAgentRuntime;
+use crate::services::file_operations::FileOperationEngine;
+use crate::services::process_table::ProcessTable;
 use crate::services::settings::SettingsManager;
 use crate::services::third_party_skill_registry::{
     InstalledThirdPartySkill, ThirdPartySkillRegistry,
 };
 use crate::services::wasm_sandbox::{WasmExecutionRequest, WasmSandboxService};
 use crate::services::workspace::WorkspaceManager;
+use crate::services::worktree;
 use crate::services::ManagedResearchService;
 use crate::services::MemoryManager;
 use sha2::{Digest, Sha256};
@@ -46,6 +55,7 @@ fn truncate_output(input: &str) -> String {
     out
 }
 
+#[derive(Clone)]
 pub struct SkillExecutor {
     workspace_manager: Arc<WorkspaceManager>,
     managed_research: Arc<ManagedResearchService>,
@@ -58,6 +68,19 @@ pub struct SkillExecutor {
     /// Beam RPC + Secure Local Signing Bridge — injected during setup
     beam_rpc: Arc<RwLock<Option<Arc<BeamRpcService>>>>,
     external_agent_runtime: Arc<RwLock<Option<Arc<ExternalAgentRuntime>>>>,
+    /// Hash-chained record of every executed tool call — injected during setup
+    audit_ledger: Arc<RwLock<Option<Arc<AuditLedger>>>>,
+    /// Background processes started by the shell skill
+    processes: ProcessTable,
+    /// Run kill switches for background processes — injected during setup
+    run_control: Arc<RwLock<Option<Arc<AgentRunControl>>>>,
+    /// Per-run file checkpoints for rollback — injected during setup
+    file_checkpoints: Arc<RwLock<Option<Arc<FileOperationEngine>>>>,
+    /// Symbol index behind `search_code` — injected during setup
+    code_index: Arc<RwLock<Option<Arc<CodeIndex>>>>,
+    /// Checkout standing in for the workspace root, set only on the copy
+    /// that runs a specialist lane's command
+    checkout_root: Option<String>,
 }
 
 impl SkillExecutor {
@@ -65,13 +88,6 @@ impl SkillExecutor {
         registry::registered_tool_definitions()
     }
 
-    fn is_allowed_shell_command(command: &str) -> bool {
-        matches!(
-            command,
-            "npm" | "pnpm" | "bun" | "cargo" | "git" | "ls" | "grep" | "echo" | "cat"
-        )
-    }
-
     fn is_tool_allowed(method: &str, policy: Option<&ToolAccessPolicy>) -> bool {
         let Some(policy) = policy else {
             return true;
@@ -135,6 +151,12 @@ impl SkillExecutor {
             mcp_service,
             beam_rpc: Arc::new(RwLock::new(None)),
             external_agent_runtime: Arc::new(RwLock::new(None)),
+            audit_ledger: Arc::new(RwLock::new(None)),
+            processes: ProcessTable::new(),
+            run_control: Arc::new(RwLock::new(None)),
+            file_checkpoints: Arc::new(RwLock::new(None)),
+            code_index: Arc::new(RwLock::new(None)),
+            checkout_root: None,
         }
     }
 
@@ -153,6 +175,30 @@ impl SkillExecutor {
         *lock = Some(runtime);
     }
 
+    pub async fn set_audit_ledger(&self, ledger: Arc<AuditLedger>) {
+        let mut lock = self.audit_ledger.write().await;
+        *lock = Some(ledger);
+    }
+
+    pub async fn set_run_control(&self, run_control: Arc<AgentRunControl>) {
+        let mut lock = self.run_control.write().await;
+        *lock = Some(run_control);
+    }
+
+    pub async fn set_file_checkpoints(&self, engine: Arc<FileOperationEngine>) {
+        let mut lock = self.file_checkpoints.write().await;
+        *lock = Some(engine);
+    }
+
+    pub async fn set_code_index(&self, index: Arc<CodeIndex>) {
+        let mut lock = self.code_index.write().await;
+        *lock = Some(index);
+    }
+
+    pub fn process_table(&self) -> ProcessTable {
+        self.processes.clone()
+    }
+
     pub async fn set_scheduler(
         &self,
         scheduler: Arc<crate::services::persistent_scheduler::PersistentScheduler>,
@@ -163,14 +209,19 @@ impl SkillExecutor {
 
     #[cfg(test)]
     pub fn mock() -> Self {
+        let wm = Arc::new(WorkspaceManager::new().unwrap_or_else(|_| {
+            panic!("Failed to create mock WorkspaceManager for test");
+        }));
+        Self::mock_with_workspaces(wm)
+    }
+
+    #[cfg(test)]
+    pub fn mock_with_workspaces(wm: Arc<WorkspaceManager>) -> Self {
         let provider_manager = Arc::new(crate::ai::provider::AIProviderManager::new(
             crate::services::KeychainAccessService::new(),
         ));
         let research = Arc::new(ManagedResearchService::new(provider_manager));
         let browser = Arc::new(BrowserController::new());
-        let wm = Arc::new(WorkspaceManager::new().unwrap_or_else(|_| {
-            panic!("Failed to create mock WorkspaceManager for test");
-        }));
 
         Self {
             workspace_manager: wm,
@@ -185,10 +236,40 @@ impl SkillExecutor {
             mcp_service: Arc::new(crate::services::mcp_service::McpService::new()),
             beam_rpc: Arc::new(RwLock::new(None)),
             external_agent_runtime: Arc::new(RwLock::new(None)),
+            audit_ledger: Arc::new(RwLock::new(None)),
+            processes: ProcessTable::new(),
+            run_control: Arc::new(RwLock::new(None)),
+            file_checkpoints: Arc::new(RwLock::new(None)),
+            code_index: Arc::new(RwLock::new(None)),
+            checkout_root: None,
         }
     }
 
     pub async fn execute(&self, command: &QueuedCommand) -> CommandResult {
+        let result = match command.payload.checkout_root.as_deref() {
+            Some(root) if worktree::is_checkout_path(Path::new(root)) => {
+                self.in_checkout(root).execute_inner(command).await
+            }
+            Some(root) => self.error(&format!("'{}' is not an isolated checkout", root)),
+            None => self.execute_inner(command).await,
+        };
+        let ledger = self.audit_ledger.read().await.clone();
+        if let Some(ledger) = ledger {
+            ledger.record_execution(command, &result).await;
+        }
+        result
+    }
+
+    /// Copy whose file and shell tools resolve against `root` instead of the
+    /// registered workspace.
+    fn in_checkout(&self, root: &str) -> Self {
+        Self {
+            checkout_root: Some(root.to_string()),
+            ..self.clone()
+        }
+    }
+
+    async fn execute_inner(&self, command: &QueuedCommand) -> CommandResult {
         let payload = &command.payload;
         let skill = payload.skill.as_deref().unwrap_or("unknown");
         let method = payload.method.as_deref().unwrap_or("unknown");
@@ -231,7 +312,10 @@ impl SkillExecutor {
             .as_ref()
             .map(|policy| policy.blocked_domains.clone())
             .unwrap_or_default();
-        let allowed_paths = if payload.allowed_paths.is_empty() {
+        let checkout_paths: Vec<String> = self.checkout_root.iter().cloned().collect();
+        let allowed_paths = if !checkout_paths.is_empty() {
+            &checkout_paths
+        } else if payload.allowed_paths.is_empty() {
             &fallback_allowed_paths
         } else {
             &payload.allowed_paths
@@ -300,6 +384,7 @@ impl SkillExecutor {
                 output: Some(result.unwrap_or_else(|e| format!("MCP Error: {}", e))),
                 error: None,
                 exit_code: Some(0), // we map all to output in rainy architecture generally unless explicitly errored
+                ..Default::default()
             };
         } else if crate::services::mcp_service::McpService::is_mcp_tool(method) {
             return CommandResult {
@@ -309,6 +394,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(1),
+                ..Default::default()
             };
         }
 
@@ -316,6 +402,7 @@ impl SkillExecutor {
             "filesystem" => {
                 self.execute_filesystem(
                     workspace_id,
+                    payload.run_id.as_deref(),
                     method,
                     &payload.params,
                     allowed_paths,
@@ -326,10 +413,12 @@ impl SkillExecutor {
             "shell" => {
                 self.execute_shell(
                     workspace_id,
+                    payload.run_id.as_deref(),
                     method,
                     &payload.params,
                     allowed_paths,
                     blocked_paths,
+                    tool_policy,
                 )
                 .await
             }
@@ -390,6 +479,7 @@ impl SkillExecutor {
                     output: None,
                     error: Some(format!("Unknown skill: {}", skill)),
                     exit_code: Some(1),
+                    ..Default::default()
                 }),
         }
     }
@@ -483,6 +573,7 @@ impl SkillExecutor {
             output: None,
             error: Some(msg.to_string()),
             exit_code: Some(1),
+            ..Default::default()
         }
     }
 
@@ -533,6 +624,7 @@ impl SkillExecutor {
                     output: Some(serde_json::to_string(&combined).unwrap_or_default()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             "search_memory" => {
@@ -545,6 +637,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&results).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e.to_string()),
                 }
@@ -586,6 +679,7 @@ impl SkillExecutor {
                         output: Some(format!("Memory saved: {}", preview)),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e.to_string()),
                 }
@@ -785,6 +879,7 @@ mod policy_tests {
             tool_access_policy_source: "workspace_permissions".to_string(),
             notifications_enabled: true,
             can_create_agents: true,
+            shell_sandbox: Default::default(),
         };
 
         let chosen =
@@ -801,21 +896,24 @@ mod policy_tests {
 #[cfg(test)]
 mod tests {
     use super::SkillExecutor;
+    use crate::services::shell_policy::ShellCommandPolicy;
     use std::path::Path;
 
     #[test]
     fn shell_allowlist_matches_agents_policy() {
+        let policy = ShellCommandPolicy::default();
         for cmd in [
             "npm", "pnpm", "bun", "cargo", "git", "ls", "grep", "echo", "cat",
         ] {
-            assert!(SkillExecutor::is_allowed_shell_command(cmd));
+            assert!(policy.allows_binary(cmd));
         }
     }
 
     #[test]
     fn shell_allowlist_blocks_dangerous_commands() {
+        let policy = ShellCommandPolicy::default();
         for cmd in ["rm", "curl", "wget", "kill", "mv", "cp", "node"] {
-            assert!(!SkillExecutor::is_allowed_shell_command(cmd));
+            assert!(!policy.allows_binary(cmd));
         }
     }
 
@@ -30,6 +30,26 @@ pub struct WriteFileArgs {
     pub content: String,
 }
 
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct EditFileArgs {
+    /// The file to edit
+    pub path: String,
+    /// Exact text to replace, including enough surrounding lines to be unique
+    pub old_string: String,
+    /// Replacement text
+    pub new_string: String,
+    /// Number of occurrences that must match; all are replaced (default: 1)
+    pub expected_occurrences: Option<usize>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ApplyPatchArgs {
+    /// Unified diff with ---/+++ file headers and @@ hunks; may touch several files
+    pub patch: String,
+    /// Context lines that may be ignored at each end of a hunk when the file has drifted (default: 2, max: 3)
+    pub fuzz: Option<usize>,
+}
+
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct ListFilesArgs {
     /// The directory path to list
@@ -92,6 +112,16 @@ pub struct SearchFilesArgs {
     pub max_files: Option<usize>,
 }
 
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct SearchCodeArgs {
+    /// Symbol names or words to look for (e.g. "rotate master key" or "MemoryVaultService")
+    pub query: String,
+    /// Only return symbols from files under this path
+    pub path: Option<String>,
+    /// Maximum number of hits (default: 10, max: 50)
+    pub limit: Option<usize>,
+}
+
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct ExecuteCommandArgs {
     /// The command to execute (e.g., npm, cargo, git)
@@ -102,6 +132,50 @@ pub struct ExecuteCommandArgs {
     pub timeout_ms: Option<u64>,
 }
 
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct StartProcessArgs {
+    /// The command to start in the background (e.g., npm, cargo)
+    pub command: String,
+    /// Arguments for the command
+    pub args: Vec<String>,
+    /// Optional workspace path used as working directory
+    pub cwd: Option<String>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ReadProcessOutputArgs {
+    /// Process id returned by start_process
+    pub process_id: String,
+    /// Byte cursor to read from; pass the previous next_cursor (default: 0)
+    pub cursor: Option<u64>,
+    /// Maximum bytes to return (default: 16384, max: 49152)
+    pub max_bytes: Option<usize>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct WriteProcessStdinArgs {
+    /// Process id returned by start_process
+    pub process_id: String,
+    /// Text to write; include a trailing newline to submit a line
+    pub data: String,
+    /// Close stdin after writing (default: false)
+    pub close: Option<bool>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct WaitProcessArgs {
+    /// Process id returned by start_process
+    pub process_id: String,
+    /// Maximum time to wait in milliseconds (default: 30000, max: 600000)
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct KillProcessArgs {
+    /// Process id returned by start_process
+    pub process_id: String,
+}
+
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitStatusArgs {
     /// Optional workspace path used as git working directory
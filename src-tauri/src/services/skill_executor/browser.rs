@@ -39,6 +39,7 @@ impl SkillExecutor {
                             output: Some(output.to_string()),
                             error: None,
                             exit_code: Some(0),
+                            ..Default::default()
                         }
                     }
                     Err(e) => {
@@ -65,6 +66,7 @@ impl SkillExecutor {
                         output: Some(output.to_string()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     }
                 }
                 Err(e) => self.error(&e),
@@ -80,6 +82,7 @@ impl SkillExecutor {
                         output: Some(format!("Clicked element: {}", args.selector)),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -103,6 +106,7 @@ impl SkillExecutor {
                         )),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -123,6 +127,7 @@ impl SkillExecutor {
                         output: Some(format!("Typed text into '{}'", args.selector)),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -171,6 +176,7 @@ impl SkillExecutor {
                             output: Some(result),
                             error: None,
                             exit_code: Some(0),
+                            ..Default::default()
                         }
                     }
                     Err(e) => self.error(&format!("Failed to submit form: {}", e)),
@@ -194,6 +200,7 @@ impl SkillExecutor {
                                 output: Some(snapshot),
                                 error: None,
                                 exit_code: Some(0),
+                                ..Default::default()
                             },
                             Err(e) => self.error(&format!(
                                 "Back navigation completed but snapshot failed: {}",
@@ -210,6 +217,7 @@ impl SkillExecutor {
                     output: Some(truncate_output(&content)),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(e) => self.error(&e),
             },
@@ -221,6 +229,7 @@ impl SkillExecutor {
                         output: Some(snapshot),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&format!("Failed to snapshot page: {}", e)),
                 }
@@ -239,6 +248,7 @@ impl SkillExecutor {
                         output: Some(result),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&format!("Failed to extract links: {}", e)),
                 }
@@ -0,0 +1,88 @@
+use super::args::SearchCodeArgs;
+use super::SkillExecutor;
+use crate::models::neural::CommandResult;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+impl SkillExecutor {
+    /// Ranked symbol hits from the workspace code index. The first call for a
+    /// workspace indexes its allowed paths and starts watching them.
+    pub(super) async fn handle_search_code(
+        &self,
+        workspace_id: String,
+        params: &Value,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) -> CommandResult {
+        let args: SearchCodeArgs = match serde_json::from_value(params.clone()) {
+            Ok(a) => a,
+            Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+        };
+        let Some(index) = self.code_index.read().await.clone() else {
+            return self.error("Code index is not available");
+        };
+
+        let allowed_roots = match self.workspace_roots(&workspace_id) {
+            Some(roots) if !roots.is_empty() => roots,
+            _ => allowed_paths.to_vec(),
+        };
+        let roots = allowed_roots
+            .iter()
+            .map(PathBuf::from)
+            .filter(|root| root.is_absolute())
+            .collect::<Vec<_>>();
+        if roots.is_empty() {
+            return self.error("Workspace has no allowed paths to index");
+        }
+
+        let scope = match args.path.as_deref() {
+            Some(path) => match self
+                .resolve_path(workspace_id.clone(), path, allowed_paths, blocked_paths)
+                .await
+            {
+                Ok(resolved) if resolved.is_dir() => Some(format!(
+                    "{}{}",
+                    resolved
+                        .to_string_lossy()
+                        .trim_end_matches(std::path::MAIN_SEPARATOR),
+                    std::path::MAIN_SEPARATOR
+                )),
+                Ok(resolved) => Some(resolved.to_string_lossy().to_string()),
+                Err(e) => return self.error(&e),
+            },
+            None => None,
+        };
+
+        if let Err(e) = index.ensure_indexed(&workspace_id, roots).await {
+            return self.error(&e);
+        }
+        let hits = match index
+            .search(
+                &workspace_id,
+                &args.query,
+                scope.as_deref(),
+                args.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+            )
+            .await
+        {
+            Ok(hits) => hits,
+            Err(e) => return self.error(&e),
+        };
+        let hits = hits
+            .into_iter()
+            .filter(|hit| {
+                !Self::is_path_blocked(Path::new(&hit.path), blocked_paths, &allowed_roots)
+            })
+            .collect::<Vec<_>>();
+
+        CommandResult {
+            success: true,
+            output: Some(serde_json::to_string(&hits).unwrap_or_else(|_| "[]".to_string())),
+            error: None,
+            exit_code: Some(0),
+            ..Default::default()
+        }
+    }
+}
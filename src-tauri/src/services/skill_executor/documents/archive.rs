@@ -70,6 +70,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Ok(Err(error)) => self.error(&format!("Archive creation failed: {}", error)),
             Err(error) => self.error(&format!("Archive task panicked: {}", error)),
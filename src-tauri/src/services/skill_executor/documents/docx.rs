@@ -59,6 +59,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Ok(Err(error)) => self.error(&format!("DOCX generation failed: {}", error)),
             Err(error) => self.error(&format!("DOCX task panicked: {}", error)),
@@ -106,6 +107,7 @@ impl SkillExecutor {
                     ),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Ok(Err(error)) => self.error(&format!("DOCX read failed: {}", error)),
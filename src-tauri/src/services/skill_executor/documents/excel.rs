@@ -56,6 +56,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Ok(Err(error)) => self.error(&format!("Excel generation failed: {}", error)),
             Err(error) => self.error(&format!("Excel task panicked: {}", error)),
@@ -96,6 +97,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Ok(Err(error)) => self.error(&format!("Excel read failed: {}", error)),
             Err(error) => self.error(&format!("Excel task panicked: {}", error)),
@@ -61,6 +61,7 @@ impl SkillExecutor {
                 output: None,
                 error: Some(format!("Unknown documents method: {}", method)),
                 exit_code: Some(1),
+                ..Default::default()
             },
         }
     }
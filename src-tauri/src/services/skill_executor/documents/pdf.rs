@@ -64,6 +64,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Ok(Err(error)) => self.error(&format!("PDF generation failed: {}", error)),
             Err(error) => self.error(&format!("PDF task panicked: {}", error)),
@@ -104,6 +105,7 @@ impl SkillExecutor {
                 ),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Ok(Err(error)) => self.error(&format!("PDF read failed: {}", error)),
             Err(error) => self.error(&format!("PDF task panicked: {}", error)),
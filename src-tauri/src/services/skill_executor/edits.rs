@@ -0,0 +1,205 @@
+use super::args::*;
+use super::SkillExecutor;
+use crate::models::neural::CommandResult;
+use crate::services::file_operations::FileOpType;
+use crate::services::file_patch::{self, DEFAULT_CONTEXT_LINES, DEFAULT_FUZZ, MAX_FUZZ};
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::fs;
+
+/// One file of an `apply_patch` call, fully computed before anything is written.
+struct PlannedWrite {
+    label: String,
+    path: PathBuf,
+    created: bool,
+    original: String,
+    updated: String,
+    hunks: Vec<file_patch::HunkReport>,
+}
+
+impl SkillExecutor {
+    pub(super) async fn handle_edit_file(
+        &self,
+        workspace_id: String,
+        run_id: Option<&str>,
+        params: &Value,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) -> CommandResult {
+        let args: EditFileArgs = match serde_json::from_value(params.clone()) {
+            Ok(a) => a,
+            Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+        };
+
+        let path = match self
+            .resolve_write_path(workspace_id, &args.path, allowed_paths, blocked_paths)
+            .await
+        {
+            Ok(p) => p,
+            Err(e) => return self.error(&e),
+        };
+        let original = match fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) => return self.error(&format!("Failed to read file: {}", e)),
+        };
+        let (updated, replacements) = match file_patch::replace_exact(
+            &original,
+            &args.old_string,
+            &args.new_string,
+            args.expected_occurrences,
+        ) {
+            Ok(result) => result,
+            Err(e) => return self.error(&format!("{}: {}", args.path, e)),
+        };
+
+        if let Err(e) = self
+            .checkpoint_path(run_id, &path, FileOpType::Modify, None)
+            .await
+        {
+            return self.error(&e);
+        }
+        if let Err(e) = fs::write(&path, &updated).await {
+            return self.error(&format!("Failed to write file: {}", e));
+        }
+        let output = serde_json::json!({
+            "path": args.path,
+            "replacements": replacements,
+            "diff": file_patch::unified_diff(&args.path, &original, &updated, DEFAULT_CONTEXT_LINES),
+        });
+
+        CommandResult {
+            success: true,
+            output: Some(output.to_string()),
+            error: None,
+            exit_code: Some(0),
+            ..Default::default()
+        }
+    }
+
+    pub(super) async fn handle_apply_patch(
+        &self,
+        workspace_id: String,
+        run_id: Option<&str>,
+        params: &Value,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) -> CommandResult {
+        let args: ApplyPatchArgs = match serde_json::from_value(params.clone()) {
+            Ok(a) => a,
+            Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+        };
+        let fuzz = args.fuzz.unwrap_or(DEFAULT_FUZZ).min(MAX_FUZZ);
+        let files = match file_patch::parse_patch(&args.patch) {
+            Ok(files) => files,
+            Err(e) => return self.error(&format!("Invalid patch: {}", e)),
+        };
+
+        let mut planned = Vec::with_capacity(files.len());
+        let mut failures = Vec::new();
+        for file in &files {
+            let label = match (&file.old_path, &file.new_path) {
+                (_, None) => {
+                    return self.error("Patch deletes a file; use delete_file instead");
+                }
+                (Some(old), Some(new)) if old != new => {
+                    return self.error(&format!(
+                        "Patch renames {} to {}; use move_file instead",
+                        old, new
+                    ));
+                }
+                (_, Some(new)) => new.clone(),
+            };
+            let path = match self
+                .resolve_write_path(workspace_id.clone(), &label, allowed_paths, blocked_paths)
+                .await
+            {
+                Ok(p) => p,
+                Err(e) => return self.error(&e),
+            };
+            let created = file.old_path.is_none();
+            let original = if created {
+                if fs::try_exists(&path).await.unwrap_or(false) {
+                    return self.error(&format!("{} already exists", label));
+                }
+                String::new()
+            } else {
+                match fs::read_to_string(&path).await {
+                    Ok(content) => content,
+                    Err(e) => return self.error(&format!("Failed to read {}: {}", label, e)),
+                }
+            };
+
+            match file_patch::apply_hunks(&original, &file.hunks, fuzz) {
+                Ok((updated, hunks)) => planned.push(PlannedWrite {
+                    label,
+                    path,
+                    created,
+                    original,
+                    updated,
+                    hunks,
+                }),
+                Err(hunks) => {
+                    failures.extend(hunks.into_iter().filter(|h| !h.applied).map(|hunk| {
+                        format!(
+                            "{} hunk #{} {}: {}",
+                            label,
+                            hunk.index + 1,
+                            hunk.header,
+                            hunk.error.unwrap_or_default()
+                        )
+                    }))
+                }
+            }
+        }
+        if !failures.is_empty() {
+            return self.error(&format!(
+                "Patch did not apply; no files were changed:\n{}",
+                failures.join("\n")
+            ));
+        }
+
+        let mut diff = String::new();
+        let mut reports = Vec::with_capacity(planned.len());
+        for write in &planned {
+            if let Err(e) = self
+                .checkpoint_path(run_id, &write.path, FileOpType::Modify, None)
+                .await
+            {
+                return self.error(&e);
+            }
+        }
+        for write in planned {
+            if let Some(parent) = write.path.parent() {
+                if let Err(e) = fs::create_dir_all(parent).await {
+                    return self.error(&format!("Failed to create parent directories: {}", e));
+                }
+            }
+            if let Err(e) = fs::write(&write.path, &write.updated).await {
+                return self.error(&format!("Failed to write {}: {}", write.label, e));
+            }
+            diff.push_str(&file_patch::unified_diff(
+                &write.label,
+                &write.original,
+                &write.updated,
+                DEFAULT_CONTEXT_LINES,
+            ));
+            reports.push(serde_json::json!({
+                "path": write.label,
+                "created": write.created,
+                "hunks": write.hunks,
+            }));
+        }
+        let output = serde_json::json!({
+            "files": reports,
+            "diff": diff,
+        });
+
+        CommandResult {
+            success: true,
+            output: Some(output.to_string()),
+            error: None,
+            exit_code: Some(0),
+            ..Default::default()
+        }
+    }
+}
@@ -63,6 +63,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string_pretty(&cfg).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -80,6 +81,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&info).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -97,6 +99,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&info).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -114,6 +117,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&info).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -126,6 +130,7 @@ impl SkillExecutor {
                     output: Some(serde_json::to_string(&wallets).unwrap_or_default()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(e) => self.error(&e),
             },
@@ -154,6 +159,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&estimate).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -184,6 +190,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&signed).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
@@ -214,6 +221,7 @@ impl SkillExecutor {
                         output: Some(serde_json::to_string(&receipt).unwrap_or_default()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     },
                     Err(e) => self.error(&e),
                 }
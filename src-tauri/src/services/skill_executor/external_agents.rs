@@ -48,6 +48,7 @@ impl SkillExecutor {
                 output: None,
                 error: Some(format!("Unknown external agent method: {}", method)),
                 exit_code: Some(1),
+                ..Default::default()
             },
         }
     }
@@ -114,6 +115,7 @@ impl SkillExecutor {
                 output: Some(serde_json::to_string(&session).unwrap_or_default()),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(error) => self.error(&error),
         }
@@ -138,6 +140,7 @@ impl SkillExecutor {
                 output: Some(serde_json::to_string(&session).unwrap_or_default()),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(error) => self.error(&error),
         }
@@ -165,6 +168,7 @@ impl SkillExecutor {
                 output: Some(serde_json::to_string(&session).unwrap_or_default()),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(error) => self.error(&error),
         }
@@ -219,6 +223,7 @@ impl SkillExecutor {
                 output: Some(serde_json::to_string(&sessions).unwrap_or_default()),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(error) => self.error(&error),
         }
@@ -243,6 +248,7 @@ impl SkillExecutor {
                 output: Some(serde_json::to_string(&session).unwrap_or_default()),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(error) => self.error(&error),
         }
@@ -1,6 +1,8 @@
 use super::args::*;
 use super::SkillExecutor;
 use crate::models::neural::CommandResult;
+use crate::services::airlock_policy::is_workspace_policy_path;
+use crate::services::file_operations::FileOpType;
 use base64::prelude::*;
 use serde_json::Value;
 use std::path::{Component, Path, PathBuf};
@@ -64,6 +66,19 @@ impl SkillExecutor {
         false
     }
 
+    /// Roots of the checkout standing in for the workspace, else of the
+    /// registered workspace.
+    pub(super) fn workspace_roots(&self, workspace_id: &str) -> Option<Vec<String>> {
+        match self.checkout_root.as_ref() {
+            Some(root) => Some(vec![root.clone()]),
+            None => self
+                .workspace_manager
+                .load_workspace(workspace_id)
+                .ok()
+                .map(|ws| ws.allowed_paths),
+        }
+    }
+
     /// Resolve a path within the workspace. First tries to load local workspace,
     /// falls back to using allowed_paths from the command payload (Cloud-provided).
     pub(super) async fn resolve_path(
@@ -78,9 +93,9 @@ impl SkillExecutor {
         if path_buf.is_absolute() {
             let normalized_target = Self::normalize_absolute_path(&path_buf)?;
 
-            let workspace_allowed = match self.workspace_manager.load_workspace(&workspace_id) {
-                Ok(ws) => ws.allowed_paths,
-                Err(_) => {
+            let workspace_allowed = match self.workspace_roots(&workspace_id) {
+                Some(roots) => roots,
+                None => {
                     if !allowed_paths.is_empty() {
                         allowed_paths.to_vec()
                     } else {
@@ -114,9 +129,9 @@ impl SkillExecutor {
             return Ok(normalized_target);
         }
 
-        let workspace_allowed_paths = match self.workspace_manager.load_workspace(&workspace_id) {
-            Ok(ws) => ws.allowed_paths,
-            Err(_) => {
+        let workspace_allowed_paths = match self.workspace_roots(&workspace_id) {
+            Some(roots) => roots,
+            None => {
                 if allowed_paths.is_empty() {
                     return Err(
                         "No workspace context found. Please provide an absolute path (e.g. /Users/name/Projects) to start."
@@ -156,9 +171,70 @@ impl SkillExecutor {
         Ok(normalized_target)
     }
 
+    /// `resolve_path` for tools that create, change or remove the target.
+    /// Workspace Airlock policy files are off limits to them.
+    pub(super) async fn resolve_write_path(
+        &self,
+        workspace_id: String,
+        path_str: &str,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) -> Result<PathBuf, String> {
+        let path = self
+            .resolve_path(workspace_id, path_str, allowed_paths, blocked_paths)
+            .await?;
+        if is_workspace_policy_path(&path) {
+            return Err(format!(
+                "Path '{}' holds the workspace Airlock policy and cannot be modified by agent tools",
+                path_str
+            ));
+        }
+        Ok(path)
+    }
+
+    /// Snapshot `path` into the run's checkpoint before a tool mutates it.
+    ///
+    /// A no-op outside agent runs or before the file engine is injected.
+    pub(super) async fn checkpoint_path(
+        &self,
+        run_id: Option<&str>,
+        path: &Path,
+        operation: FileOpType,
+        dest_path: Option<&Path>,
+    ) -> Result<(), String> {
+        let Some(run_id) = run_id else {
+            return Ok(());
+        };
+        let Some(engine) = self.file_checkpoints.read().await.clone() else {
+            return Ok(());
+        };
+        engine
+            .checkpoint_run_file(run_id, path, operation, dest_path)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to checkpoint {}: {}", path.display(), e))
+    }
+
+    /// Delete a directory during an agent run by moving it into the run's
+    /// trash. Returns `Ok(false)` outside runs, where it is removed outright.
+    async fn trash_run_directory(&self, run_id: Option<&str>, path: &Path) -> Result<bool, String> {
+        let Some(run_id) = run_id else {
+            return Ok(false);
+        };
+        let Some(engine) = self.file_checkpoints.read().await.clone() else {
+            return Ok(false);
+        };
+        engine
+            .trash_run_directory(run_id, path)
+            .await
+            .map(|_| true)
+            .map_err(|e| e.to_string())
+    }
+
     pub(super) async fn execute_filesystem(
         &self,
         workspace_id: String,
+        run_id: Option<&str>,
         method: &str,
         params: &Option<Value>,
         allowed_paths: &[String],
@@ -198,16 +274,28 @@ impl SkillExecutor {
                 self.handle_search_files(workspace_id, params, allowed_paths, blocked_paths)
                     .await
             }
+            "search_code" => {
+                self.handle_search_code(workspace_id, params, allowed_paths, blocked_paths)
+                    .await
+            }
             "read_file_chunk" => {
                 self.handle_read_file_chunk(workspace_id, params, allowed_paths, blocked_paths)
                     .await
             }
             "write_file" => {
-                self.handle_write_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_write_file(workspace_id, run_id, params, allowed_paths, blocked_paths)
                     .await
             }
             "append_file" => {
-                self.handle_append_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_append_file(workspace_id, run_id, params, allowed_paths, blocked_paths)
+                    .await
+            }
+            "edit_file" => {
+                self.handle_edit_file(workspace_id, run_id, params, allowed_paths, blocked_paths)
+                    .await
+            }
+            "apply_patch" => {
+                self.handle_apply_patch(workspace_id, run_id, params, allowed_paths, blocked_paths)
                     .await
             }
             "mkdir" => {
@@ -215,11 +303,11 @@ impl SkillExecutor {
                     .await
             }
             "delete_file" => {
-                self.handle_delete_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_delete_file(workspace_id, run_id, params, allowed_paths, blocked_paths)
                     .await
             }
             "move_file" => {
-                self.handle_move_file(workspace_id, params, allowed_paths, blocked_paths)
+                self.handle_move_file(workspace_id, run_id, params, allowed_paths, blocked_paths)
                     .await
             }
             "ingest_document" => {
@@ -231,6 +319,7 @@ impl SkillExecutor {
                 output: None,
                 error: Some(format!("Unknown filesystem method: {}", method)),
                 exit_code: Some(1),
+                ..Default::default()
             },
         }
     }
@@ -279,6 +368,7 @@ impl SkillExecutor {
                         output: Some(output),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     }
                 }
                 Err(e) => self.error(&format!("Failed to read binary file: {}", e)),
@@ -290,6 +380,7 @@ impl SkillExecutor {
                     output: Some(content),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(e) => self.error(&format!("Failed to read file: {}", e)),
             }
@@ -369,6 +460,7 @@ impl SkillExecutor {
             output: Some(serde_json::json!({ "files": results }).to_string()),
             error: None,
             exit_code: Some(0),
+            ..Default::default()
         }
     }
 
@@ -407,6 +499,7 @@ impl SkillExecutor {
                     output: Some(serde_json::to_string(&entries).unwrap()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(e) => self.error(&format!("Failed to list files: {}", e)),
@@ -483,6 +576,7 @@ impl SkillExecutor {
                     output: Some(output.to_string()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(e) => self.error(&format!("Failed to list files: {}", e)),
@@ -633,6 +727,7 @@ impl SkillExecutor {
             output: Some(serde_json::to_string(&results).unwrap()),
             error: None,
             exit_code: Some(0),
+            ..Default::default()
         }
     }
 
@@ -669,6 +764,7 @@ impl SkillExecutor {
                     output: Some(output.to_string()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
@@ -683,6 +779,7 @@ impl SkillExecutor {
                     output: Some(output.to_string()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(e) => self.error(&format!("Failed to inspect path: {}", e)),
@@ -741,6 +838,7 @@ impl SkillExecutor {
             output: Some(output.to_string()),
             error: None,
             exit_code: Some(0),
+            ..Default::default()
         }
     }
 
@@ -816,12 +914,14 @@ impl SkillExecutor {
             output: Some(output.to_string()),
             error: None,
             exit_code: Some(0),
+            ..Default::default()
         }
     }
 
     async fn handle_write_file(
         &self,
         workspace_id: String,
+        run_id: Option<&str>,
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -832,13 +932,19 @@ impl SkillExecutor {
         };
 
         let path = match self
-            .resolve_path(workspace_id, &args.path, allowed_paths, blocked_paths)
+            .resolve_write_path(workspace_id, &args.path, allowed_paths, blocked_paths)
             .await
         {
             Ok(p) => p,
             Err(e) => return self.error(&e),
         };
 
+        if let Err(e) = self
+            .checkpoint_path(run_id, &path, FileOpType::Modify, None)
+            .await
+        {
+            return self.error(&e);
+        }
         if let Some(parent) = path.parent() {
             if let Err(e) = fs::create_dir_all(parent).await {
                 return self.error(&format!("Failed to create parent directories: {}", e));
@@ -851,6 +957,7 @@ impl SkillExecutor {
                 output: Some("File written successfully".to_string()),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(e) => self.error(&format!("Failed to write file: {}", e)),
         }
@@ -859,6 +966,7 @@ impl SkillExecutor {
     async fn handle_append_file(
         &self,
         workspace_id: String,
+        run_id: Option<&str>,
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -869,13 +977,19 @@ impl SkillExecutor {
         };
 
         let path = match self
-            .resolve_path(workspace_id, &args.path, allowed_paths, blocked_paths)
+            .resolve_write_path(workspace_id, &args.path, allowed_paths, blocked_paths)
             .await
         {
             Ok(p) => p,
             Err(e) => return self.error(&e),
         };
 
+        if let Err(e) = self
+            .checkpoint_path(run_id, &path, FileOpType::Modify, None)
+            .await
+        {
+            return self.error(&e);
+        }
         let file_res = fs::OpenOptions::new()
             .write(true)
             .append(true)
@@ -890,6 +1004,7 @@ impl SkillExecutor {
                     output: Some("Content appended successfully".to_string()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(e) => self.error(&format!("Failed to append content: {}", e)),
             },
@@ -910,7 +1025,7 @@ impl SkillExecutor {
         };
 
         let path = match self
-            .resolve_path(workspace_id, &args.path, allowed_paths, blocked_paths)
+            .resolve_write_path(workspace_id, &args.path, allowed_paths, blocked_paths)
             .await
         {
             Ok(p) => p,
@@ -923,6 +1038,7 @@ impl SkillExecutor {
                 output: Some(format!("Successfully created directory {}", args.path)),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(e) => self.error(&format!("Failed to create directory: {}", e)),
         }
@@ -931,6 +1047,7 @@ impl SkillExecutor {
     async fn handle_delete_file(
         &self,
         workspace_id: String,
+        run_id: Option<&str>,
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -941,7 +1058,7 @@ impl SkillExecutor {
         };
 
         let path = match self
-            .resolve_path(workspace_id, &args.path, allowed_paths, blocked_paths)
+            .resolve_write_path(workspace_id, &args.path, allowed_paths, blocked_paths)
             .await
         {
             Ok(p) => p,
@@ -949,22 +1066,35 @@ impl SkillExecutor {
         };
 
         if path.is_dir() {
-            match fs::remove_dir_all(&path).await {
-                Ok(_) => CommandResult {
+            let deleted = match self.trash_run_directory(run_id, &path).await {
+                Ok(true) => Ok(()),
+                Ok(false) => fs::remove_dir_all(&path).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            match deleted {
+                Ok(()) => CommandResult {
                     success: true,
                     output: Some(format!("Successfully deleted directory {}", args.path)),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(e) => self.error(&format!("Failed to delete directory: {}", e)),
             }
         } else {
+            if let Err(e) = self
+                .checkpoint_path(run_id, &path, FileOpType::Delete, None)
+                .await
+            {
+                return self.error(&e);
+            }
             match fs::remove_file(&path).await {
                 Ok(_) => CommandResult {
                     success: true,
                     output: Some(format!("Successfully deleted file {}", args.path)),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(e) => self.error(&format!("Failed to delete file: {}", e)),
             }
@@ -974,6 +1104,7 @@ impl SkillExecutor {
     async fn handle_move_file(
         &self,
         workspace_id: String,
+        run_id: Option<&str>,
         params: &Value,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -984,7 +1115,7 @@ impl SkillExecutor {
         };
 
         let source = match self
-            .resolve_path(
+            .resolve_write_path(
                 workspace_id.clone(),
                 &args.source,
                 allowed_paths,
@@ -997,7 +1128,7 @@ impl SkillExecutor {
         };
 
         let destination = match self
-            .resolve_path(
+            .resolve_write_path(
                 workspace_id,
                 &args.destination,
                 allowed_paths,
@@ -1009,6 +1140,18 @@ impl SkillExecutor {
             Err(e) => return self.error(&e),
         };
 
+        if let Err(e) = self
+            .checkpoint_path(run_id, &destination, FileOpType::Modify, None)
+            .await
+        {
+            return self.error(&e);
+        }
+        if let Err(e) = self
+            .checkpoint_path(run_id, &source, FileOpType::Move, Some(&destination))
+            .await
+        {
+            return self.error(&e);
+        }
         if let Some(parent) = destination.parent() {
             if let Err(e) = fs::create_dir_all(parent).await {
                 return self.error(&format!(
@@ -1027,6 +1170,7 @@ impl SkillExecutor {
                 )),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(e) => self.error(&format!("Failed to move file: {}", e)),
         }
@@ -1134,6 +1278,7 @@ impl SkillExecutor {
                 output: Some(msg),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             }
         } else {
             self.error("MemoryManager not configured; cannot ingest documents")
@@ -0,0 +1,150 @@
+use super::args::*;
+use super::shell::ShellContext;
+use super::SkillExecutor;
+use crate::models::neural::CommandResult;
+use crate::services::process_table::{ProcessSpec, DEFAULT_READ_BYTES};
+use serde::Serialize;
+use std::time::Duration;
+
+impl SkillExecutor {
+    pub(super) async fn handle_start_process(
+        &self,
+        workspace_id: String,
+        run_id: Option<&str>,
+        context: &ShellContext<'_>,
+        args: StartProcessArgs,
+        allowed_paths: &[String],
+        blocked_paths: &[String],
+    ) -> CommandResult {
+        if let Err(e) = context.check_command_policy(&args.command, &args.args) {
+            return self.error(&e);
+        }
+        let cwd = match self
+            .resolve_path(
+                workspace_id.clone(),
+                args.cwd.as_deref().unwrap_or("."),
+                allowed_paths,
+                blocked_paths,
+            )
+            .await
+        {
+            Ok(path) if path.is_dir() => path,
+            Ok(path) => return self.error(&format!("{} is not a directory", path.display())),
+            Err(e) => return self.error(&e),
+        };
+        let (process, sandbox) =
+            match Self::prepare_command(context, &args.command, &args.args, &cwd) {
+                Ok(prepared) => prepared,
+                Err(e) => return self.error(&e),
+            };
+
+        let run_control = self.run_control.read().await.clone();
+        let kill_switch = match (run_control, run_id) {
+            (Some(run_control), Some(run_id)) => run_control.kill_switch(run_id).await,
+            _ => None,
+        };
+        let spec = ProcessSpec {
+            run_id: run_id.map(str::to_string),
+            workspace_id,
+            command: args.command,
+            args: args.args,
+            cwd: cwd.to_string_lossy().to_string(),
+        };
+        let started = self.processes.start(process, spec, kill_switch).await;
+        let sandbox = sandbox.finish();
+        match started {
+            Ok(info) => CommandResult {
+                sandbox: Some(sandbox),
+                ..Self::json_result(&info)
+            },
+            Err(e) => CommandResult {
+                sandbox: Some(sandbox),
+                ..self.error(&e)
+            },
+        }
+    }
+
+    pub(super) async fn handle_read_process_output(
+        &self,
+        workspace_id: &str,
+        args: ReadProcessOutputArgs,
+    ) -> CommandResult {
+        match self
+            .processes
+            .read_output(
+                &args.process_id,
+                workspace_id,
+                args.cursor.unwrap_or(0),
+                args.max_bytes.unwrap_or(DEFAULT_READ_BYTES),
+            )
+            .await
+        {
+            Ok(page) => Self::json_result(&page),
+            Err(e) => self.error(&e),
+        }
+    }
+
+    pub(super) async fn handle_write_process_stdin(
+        &self,
+        workspace_id: &str,
+        args: WriteProcessStdinArgs,
+    ) -> CommandResult {
+        match self
+            .processes
+            .write_stdin(
+                &args.process_id,
+                workspace_id,
+                &args.data,
+                args.close.unwrap_or(false),
+            )
+            .await
+        {
+            Ok(written) => Self::json_result(&serde_json::json!({
+                "processId": args.process_id,
+                "bytesWritten": written,
+            })),
+            Err(e) => self.error(&e),
+        }
+    }
+
+    pub(super) async fn handle_wait_process(
+        &self,
+        workspace_id: &str,
+        args: WaitProcessArgs,
+    ) -> CommandResult {
+        let timeout = args.timeout_ms.unwrap_or(30_000).clamp(100, 600_000);
+        match self
+            .processes
+            .wait(
+                &args.process_id,
+                workspace_id,
+                Duration::from_millis(timeout),
+            )
+            .await
+        {
+            Ok(info) => Self::json_result(&info),
+            Err(e) => self.error(&e),
+        }
+    }
+
+    pub(super) async fn handle_kill_process(
+        &self,
+        workspace_id: &str,
+        args: KillProcessArgs,
+    ) -> CommandResult {
+        match self.processes.kill(&args.process_id, workspace_id).await {
+            Ok(info) => Self::json_result(&info),
+            Err(e) => self.error(&e),
+        }
+    }
+
+    fn json_result(value: &impl Serialize) -> CommandResult {
+        CommandResult {
+            success: true,
+            output: Some(serde_json::to_string(value).unwrap_or_default()),
+            error: None,
+            exit_code: Some(0),
+            ..Default::default()
+        }
+    }
+}
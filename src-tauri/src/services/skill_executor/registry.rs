@@ -18,6 +18,16 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
             "Append content to a file",
             schema_for!(WriteFileArgs),
         ),
+        tool(
+            "edit_file",
+            "Replace an exact string in a file (must match expected_occurrences, default once) and return the diff",
+            schema_for!(EditFileArgs),
+        ),
+        tool(
+            "apply_patch",
+            "Apply a unified diff to one or more files with fuzzy hunk matching; reports each hunk and returns the resulting diff",
+            schema_for!(ApplyPatchArgs),
+        ),
         tool("list_files", "List files in a directory", schema_for!(ListFilesArgs)),
         tool(
             "list_files_detailed",
@@ -39,6 +49,11 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
             "Search files by regex in names and (by default) text content",
             schema_for!(SearchFilesArgs),
         ),
+        tool(
+            "search_code",
+            "Search the workspace code index for functions, types and classes; returns ranked symbols with file paths and line ranges",
+            schema_for!(SearchCodeArgs),
+        ),
         tool(
             "read_file_chunk",
             "Read a chunk of a text file by byte offset for large-file processing",
@@ -49,6 +64,31 @@ pub fn registered_tool_definitions() -> Vec<Tool> {
             "Execute a shell command (npm, cargo, git, ls, grep)",
             schema_for!(ExecuteCommandArgs),
         ),
+        tool(
+            "start_process",
+            "Start a long-running command (dev server, watcher, test suite) in the background and return its process id",
+            schema_for!(StartProcessArgs),
+        ),
+        tool(
+            "read_process_output",
+            "Read new output from a background process starting at a byte cursor",
+            schema_for!(ReadProcessOutputArgs),
+        ),
+        tool(
+            "write_process_stdin",
+            "Write text to the stdin of a background process",
+            schema_for!(WriteProcessStdinArgs),
+        ),
+        tool(
+            "wait_process",
+            "Wait for a background process to exit, up to a timeout",
+            schema_for!(WaitProcessArgs),
+        ),
+        tool(
+            "kill_process",
+            "Kill a background process and its children",
+            schema_for!(KillProcessArgs),
+        ),
         tool(
             "git_status",
             "Get git status with stable wrapper options",
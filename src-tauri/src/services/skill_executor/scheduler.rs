@@ -280,6 +280,7 @@ impl SkillExecutor {
                     output: Some(serde_json::to_string(&response).unwrap_or_default()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(error) => self.error(&error),
@@ -320,6 +321,7 @@ impl SkillExecutor {
                     output: Some(serde_json::to_string(&runs).unwrap_or_default()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(error) => self.error(&error),
@@ -366,6 +368,7 @@ impl SkillExecutor {
                     )),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(error) => self.error(&error),
@@ -462,6 +465,7 @@ impl SkillExecutor {
                     output: Some(serde_json::to_string(&response).unwrap_or_default()),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 }
             }
             Err(error) => self.error(&error),
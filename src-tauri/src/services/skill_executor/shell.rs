@@ -1,22 +1,49 @@
 use super::args::*;
 use super::{truncate_output, SkillExecutor};
-use crate::models::neural::CommandResult;
+use crate::models::neural::{CommandResult, ToolAccessPolicy};
+use crate::services::process_sandbox::{self, PreparedSandbox, ShellSandboxPolicy};
+use crate::services::shell_policy::ShellCommandPolicy;
 use serde_json::Value;
 use std::path::{Path, PathBuf};
 
+/// Workspace command policy and sandbox settings for one shell call.
+pub(super) struct ShellContext<'a> {
+    command_policy: ShellCommandPolicy,
+    sandbox: ShellSandboxPolicy,
+    allowed_paths: &'a [String],
+    blocked_paths: &'a [String],
+}
+
+impl ShellContext<'_> {
+    /// The workspace command policy governs commands the agent names
+    /// (`execute_command`, `start_process`), not the built-in `git_*` tools.
+    pub(super) fn check_command_policy(
+        &self,
+        command: &str,
+        args: &[String],
+    ) -> Result<(), String> {
+        self.command_policy
+            .check(command, args)
+            .map_err(|violation| violation.to_string())
+    }
+}
+
 impl SkillExecutor {
     pub(super) async fn execute_shell(
         &self,
         workspace_id: String,
+        run_id: Option<&str>,
         method: &str,
         params: &Option<Value>,
         allowed_paths: &[String],
         blocked_paths: &[String],
+        tool_policy: Option<&ToolAccessPolicy>,
     ) -> CommandResult {
         let params = match params {
             Some(p) => p,
             None => return self.error("Missing parameters"),
         };
+        let context = self.shell_context(&workspace_id, tool_policy, allowed_paths, blocked_paths);
 
         match method {
             "execute_command" => {
@@ -24,6 +51,9 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
+                if let Err(e) = context.check_command_policy(&args.command, &args.args) {
+                    return self.error(&e);
+                }
 
                 let root_path = match self
                     .resolve_path(workspace_id, ".", allowed_paths, blocked_paths)
@@ -33,15 +63,64 @@ impl SkillExecutor {
                     Err(e) => return self.error(&e),
                 };
 
-                self.execute_command(&args.command, args.args, args.timeout_ms, &root_path)
-                    .await
+                self.execute_command(
+                    &context,
+                    &args.command,
+                    args.args,
+                    args.timeout_ms,
+                    &root_path,
+                )
+                .await
+            }
+            "start_process" => {
+                let args: StartProcessArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                self.handle_start_process(
+                    workspace_id,
+                    run_id,
+                    &context,
+                    args,
+                    allowed_paths,
+                    blocked_paths,
+                )
+                .await
+            }
+            "read_process_output" => {
+                let args: ReadProcessOutputArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                self.handle_read_process_output(&workspace_id, args).await
+            }
+            "write_process_stdin" => {
+                let args: WriteProcessStdinArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                self.handle_write_process_stdin(&workspace_id, args).await
+            }
+            "wait_process" => {
+                let args: WaitProcessArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                self.handle_wait_process(&workspace_id, args).await
+            }
+            "kill_process" => {
+                let args: KillProcessArgs = match serde_json::from_value(params.clone()) {
+                    Ok(a) => a,
+                    Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
+                };
+                self.handle_kill_process(&workspace_id, args).await
             }
             "git_status" => {
                 let args: GitStatusArgs = match serde_json::from_value(params.clone()) {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_status(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_status(workspace_id, &context, args, allowed_paths, blocked_paths)
                     .await
             }
             "git_diff" => {
@@ -49,7 +128,7 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_diff(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_diff(workspace_id, &context, args, allowed_paths, blocked_paths)
                     .await
             }
             "git_log" => {
@@ -57,7 +136,7 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_log(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_log(workspace_id, &context, args, allowed_paths, blocked_paths)
                     .await
             }
             "git_show" => {
@@ -65,7 +144,7 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_show(workspace_id, args, allowed_paths, blocked_paths)
+                self.handle_git_show(workspace_id, &context, args, allowed_paths, blocked_paths)
                     .await
             }
             "git_branch_list" => {
@@ -73,14 +152,21 @@ impl SkillExecutor {
                     Ok(a) => a,
                     Err(e) => return self.error(&format!("Invalid parameters: {}", e)),
                 };
-                self.handle_git_branch_list(workspace_id, args, allowed_paths, blocked_paths)
-                    .await
+                self.handle_git_branch_list(
+                    workspace_id,
+                    &context,
+                    args,
+                    allowed_paths,
+                    blocked_paths,
+                )
+                .await
             }
             _ => CommandResult {
                 success: false,
                 output: None,
                 error: Some(format!("Unknown shell method: {}", method)),
                 exit_code: Some(1),
+                ..Default::default()
             },
         }
     }
@@ -88,6 +174,7 @@ impl SkillExecutor {
     async fn handle_git_status(
         &self,
         workspace_id: String,
+        context: &ShellContext<'_>,
         args: GitStatusArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -104,12 +191,14 @@ impl SkillExecutor {
         if args.short.unwrap_or(true) {
             git_args.push("--short".to_string());
         }
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command(context, "git", git_args, None, &cwd)
+            .await
     }
 
     async fn handle_git_diff(
         &self,
         workspace_id: String,
+        context: &ShellContext<'_>,
         args: GitDiffArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -126,12 +215,14 @@ impl SkillExecutor {
         if args.staged.unwrap_or(false) {
             git_args.push("--staged".to_string());
         }
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command(context, "git", git_args, None, &cwd)
+            .await
     }
 
     async fn handle_git_log(
         &self,
         workspace_id: String,
+        context: &ShellContext<'_>,
         args: GitLogArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -151,12 +242,14 @@ impl SkillExecutor {
             "--oneline".to_string(),
             format!("-{}", max_count),
         ];
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command(context, "git", git_args, None, &cwd)
+            .await
     }
 
     async fn handle_git_show(
         &self,
         workspace_id: String,
+        context: &ShellContext<'_>,
         args: GitShowArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -181,7 +274,9 @@ impl SkillExecutor {
             "1".to_string(),
             target,
         ];
-        let result = self.execute_command("git", git_args, None, &cwd).await;
+        let result = self
+            .execute_command(context, "git", git_args, None, &cwd)
+            .await;
         if !result.success {
             return result;
         }
@@ -204,12 +299,14 @@ impl SkillExecutor {
             output: Some(clipped.trim_end().to_string()),
             error: None,
             exit_code: Some(0),
+            ..Default::default()
         }
     }
 
     async fn handle_git_branch_list(
         &self,
         workspace_id: String,
+        context: &ShellContext<'_>,
         args: GitBranchListArgs,
         allowed_paths: &[String],
         blocked_paths: &[String],
@@ -232,7 +329,8 @@ impl SkillExecutor {
             git_args.push("--all".to_string());
         }
 
-        self.execute_command("git", git_args, None, &cwd).await
+        self.execute_command(context, "git", git_args, None, &cwd)
+            .await
     }
 
     pub(super) async fn resolve_git_working_dir(
@@ -256,26 +354,70 @@ impl SkillExecutor {
         }
     }
 
+    /// Command policy and sandbox stored on the workspace, or the defaults
+    /// when the workspace has none. Sandbox network access follows the
+    /// effective tool policy.
+    fn shell_context<'a>(
+        &self,
+        workspace_id: &str,
+        tool_policy: Option<&ToolAccessPolicy>,
+        allowed_paths: &'a [String],
+        blocked_paths: &'a [String],
+    ) -> ShellContext<'a> {
+        let (command_policy, sandbox) = self
+            .workspace_manager
+            .load_workspace(workspace_id)
+            .map(|workspace| {
+                (
+                    workspace.settings.command_policy,
+                    workspace.settings.sandbox,
+                )
+            })
+            .unwrap_or_default();
+        ShellContext {
+            command_policy,
+            sandbox: sandbox.for_tool_policy(tool_policy),
+            allowed_paths,
+            blocked_paths,
+        }
+    }
+
+    /// Builds a sandboxed process for `command args...`.
+    pub(super) fn prepare_command(
+        context: &ShellContext<'_>,
+        command: &str,
+        args: &[String],
+        cwd: &Path,
+    ) -> Result<(tokio::process::Command, PreparedSandbox), String> {
+        let mut process = tokio::process::Command::new(command);
+        process.args(args).current_dir(cwd).kill_on_drop(true);
+        let sandbox = process_sandbox::apply(
+            &mut process,
+            &context.sandbox,
+            context.allowed_paths,
+            context.blocked_paths,
+        )?;
+        Ok((process, sandbox))
+    }
+
     /// Execute a shell command
     pub(super) async fn execute_command(
         &self,
+        context: &ShellContext<'_>,
         command: &str,
         args: Vec<String>,
         timeout_ms: Option<u64>,
         cwd: &PathBuf,
     ) -> CommandResult {
-        if !Self::is_allowed_shell_command(command) {
-            return self.error(&format!("Command '{}' is not allowed", command));
-        }
-
         let timeout = timeout_ms.unwrap_or(120_000).clamp(500, 600_000);
-        let command_future = tokio::process::Command::new(command)
-            .args(&args)
-            .current_dir(Path::new(cwd))
-            .kill_on_drop(true)
-            .output();
+        let (mut process, sandbox) = match Self::prepare_command(context, command, &args, cwd) {
+            Ok(prepared) => prepared,
+            Err(e) => return self.error(&e),
+        };
+        let command_future = process.output();
         let output =
             tokio::time::timeout(tokio::time::Duration::from_millis(timeout), command_future).await;
+        let sandbox = sandbox.finish();
 
         match output {
             Ok(Ok(out)) => {
@@ -293,15 +435,22 @@ impl SkillExecutor {
                         None
                     },
                     exit_code: Some(exit_code),
+                    sandbox: Some(sandbox),
                 }
             }
-            Ok(Err(e)) => self.error(&format!("Failed to execute command: {}", e)),
-            Err(_) => self.error(&format!(
-                "Command timed out after {}ms: {} {}",
-                timeout,
-                command,
-                args.join(" ")
-            )),
+            Ok(Err(e)) => CommandResult {
+                sandbox: Some(sandbox),
+                ..self.error(&format!("Failed to execute command: {}", e))
+            },
+            Err(_) => CommandResult {
+                sandbox: Some(sandbox),
+                ..self.error(&format!(
+                    "Command timed out after {}ms: {} {}",
+                    timeout,
+                    command,
+                    args.join(" ")
+                ))
+            },
         }
     }
 }
@@ -62,6 +62,7 @@ impl SkillExecutor {
                 output: None,
                 error: Some(format!("Unknown web method: {}", method)),
                 exit_code: Some(1),
+                ..Default::default()
             },
         }
     }
@@ -80,6 +81,7 @@ impl SkillExecutor {
                 )),
                 error: None,
                 exit_code: Some(0),
+                ..Default::default()
             },
             Err(e) => self.error(&format!("Web search failed: {}", e)),
         }
@@ -101,12 +103,14 @@ impl SkillExecutor {
                     output: Some(truncate_output(&content)),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
                 Err(_) => CommandResult {
                     success: true,
                     output: Some(truncate_output(&nav_result.content_preview)),
                     error: None,
                     exit_code: Some(0),
+                    ..Default::default()
                 },
             },
             Err(e) => self.error(&format!("Failed to read web page: {}", e)),
@@ -241,6 +245,7 @@ impl SkillExecutor {
                         output: Some(output.to_string()),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     };
                 }
                 Err(e) => {
@@ -375,6 +380,7 @@ impl SkillExecutor {
                         output: Some(truncate_output(&output.to_string())),
                         error: None,
                         exit_code: Some(0),
+                        ..Default::default()
                     };
                 }
                 Err(e) => {
@@ -31,6 +31,23 @@ impl ToolSkill {
             Self::ExternalAgent => "external_agent",
         }
     }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let skill = match value {
+            "filesystem" => Self::Filesystem,
+            "browser" => Self::Browser,
+            "shell" => Self::Shell,
+            "web" => Self::Web,
+            "memory" => Self::Memory,
+            "remote_session" => Self::RemoteSession,
+            "documents" => Self::Documents,
+            "workspace" => Self::Workspace,
+            "evm" => Self::Evm,
+            "external_agent" => Self::ExternalAgent,
+            _ => return None,
+        };
+        Some(skill)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -41,6 +58,7 @@ pub struct ToolPolicy {
 
 /// Canonical tool policy for desktop runtime.
 /// Unknown tools have no policy entry and must be denied by caller logic.
+/// This is the built-in (lowest precedence) layer of `AirlockPolicyEngine`.
 pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
     let policy = match function_name {
         // Level 0: read-only
@@ -50,6 +68,7 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
         | "list_files"
         | "list_files_detailed"
         | "search_files"
+        | "search_code"
         | "file_exists"
         | "get_file_info"
         | "read_file_chunk"
@@ -58,6 +77,8 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
         | "git_log"
         | "git_show"
         | "git_branch_list"
+        | "read_process_output"
+        | "wait_process"
         | "web_search"
         | "read_web_page"
         | "http_get_json"
@@ -73,9 +94,13 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
                 }
                 "screenshot" | "get_page_content" | "get_page_snapshot" | "wait_for_selector"
                 | "extract_links" => ToolSkill::Browser,
-                "git_status" | "git_diff" | "git_log" | "git_show" | "git_branch_list" => {
-                    ToolSkill::Shell
-                }
+                "git_status"
+                | "git_diff"
+                | "git_log"
+                | "git_show"
+                | "git_branch_list"
+                | "read_process_output"
+                | "wait_process" => ToolSkill::Shell,
                 _ => ToolSkill::Filesystem,
             },
             airlock_level: AirlockLevel::Safe,
@@ -86,6 +111,10 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
             skill: ToolSkill::Filesystem,
             airlock_level: AirlockLevel::Sensitive,
         },
+        "edit_file" | "apply_patch" => ToolPolicy {
+            skill: ToolSkill::Filesystem,
+            airlock_level: AirlockLevel::Sensitive,
+        },
         "browse_url" | "click_element" | "navigate" | "open_new_tab" | "type_text" | "go_back" => {
             ToolPolicy {
                 skill: ToolSkill::Browser,
@@ -93,12 +122,17 @@ pub fn get_tool_policy(function_name: &str) -> Option<ToolPolicy> {
             }
         }
 
+        "write_process_stdin" | "kill_process" => ToolPolicy {
+            skill: ToolSkill::Shell,
+            airlock_level: AirlockLevel::Sensitive,
+        },
+
         // Level 2: destructive or external command execution
         "remote_workspace_access" => ToolPolicy {
             skill: ToolSkill::RemoteSession,
             airlock_level: AirlockLevel::Dangerous,
         },
-        "execute_command" => ToolPolicy {
+        "execute_command" | "start_process" => ToolPolicy {
             skill: ToolSkill::Shell,
             airlock_level: AirlockLevel::Dangerous,
         },
@@ -0,0 +1,839 @@
+//! Persisted token usage ledger and spend budgets.
+//!
+//! The `IntelligentRouter` appends one row per successful model call, keyed by
+//! provider, model, workspace, agent spec and run, and priced from the
+//! per-model table in `ai::router::pricing`. Budgets cap spend per period for
+//! the whole app, a workspace or an agent spec; they are checked before every
+//! dispatch. `soft_warn` budgets only warn, `hard_stop` budgets refuse new
+//! calls once the limit is reached.
+
+use crate::ai::provider_types::{TokenUsage, UsageScope};
+use chrono::{Datelike, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use sqlx::query::QueryAs;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{Pool, Sqlite};
+
+const MAX_QUERY_LIMIT: u32 = 5_000;
+const DEFAULT_WARN_RATIO: f64 = 0.8;
+
+/// One model call to append to the ledger.
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub provider_id: String,
+    pub provider_type: String,
+    pub model: String,
+    pub scope: UsageScope,
+    pub usage: TokenUsage,
+    /// `None` when the model has no known price.
+    pub cost_usd: Option<f64>,
+    /// Token counts were estimated because the provider did not report usage.
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageLedgerEntry {
+    pub id: i64,
+    pub recorded_at: i64,
+    pub provider_id: String,
+    pub provider_type: String,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_spec_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cached_prompt_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub total_tokens: i64,
+    pub cost_usd: f64,
+    pub priced: bool,
+    pub estimated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageLedgerQuery {
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub agent_spec_id: Option<String>,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Inclusive lower bound on `recorded_at` (ms since epoch).
+    #[serde(default)]
+    pub since: Option<i64>,
+    /// Exclusive upper bound on `recorded_at` (ms since epoch).
+    #[serde(default)]
+    pub until: Option<i64>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBreakdown {
+    pub provider_id: String,
+    pub model: String,
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cached_prompt_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cost_usd: f64,
+    /// Calls whose model had no known price and were recorded at $0.
+    pub unpriced_requests: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    pub requests: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub cached_prompt_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cost_usd: f64,
+    pub unpriced_requests: i64,
+    pub by_model: Vec<UsageBreakdown>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Global,
+    Workspace,
+    Agent,
+}
+
+impl BudgetScope {
+    fn as_str(self) -> &'static str {
+        match self {
+            BudgetScope::Global => "global",
+            BudgetScope::Workspace => "workspace",
+            BudgetScope::Agent => "agent",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "global" => Ok(BudgetScope::Global),
+            "workspace" => Ok(BudgetScope::Workspace),
+            "agent" => Ok(BudgetScope::Agent),
+            other => Err(format!("Unknown budget scope '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    fn as_str(self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Monthly => "monthly",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "daily" => Ok(BudgetPeriod::Daily),
+            "monthly" => Ok(BudgetPeriod::Monthly),
+            other => Err(format!("Unknown budget period '{}'", other)),
+        }
+    }
+
+    /// Start of the period containing `now_ms`, in local time (ms since epoch).
+    pub fn start_ms(self, now_ms: i64) -> i64 {
+        let now = Local
+            .timestamp_millis_opt(now_ms)
+            .single()
+            .unwrap_or_else(Local::now);
+        let date = match self {
+            BudgetPeriod::Daily => now.date_naive(),
+            BudgetPeriod::Monthly => now.date_naive().with_day(1).unwrap_or(now.date_naive()),
+        };
+        date.and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .map(|start| start.timestamp_millis())
+            .unwrap_or(now_ms)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetMode {
+    SoftWarn,
+    HardStop,
+}
+
+impl BudgetMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            BudgetMode::SoftWarn => "soft_warn",
+            BudgetMode::HardStop => "hard_stop",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "soft_warn" => Ok(BudgetMode::SoftWarn),
+            "hard_stop" => Ok(BudgetMode::HardStop),
+            other => Err(format!("Unknown budget mode '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBudget {
+    pub id: String,
+    pub scope: BudgetScope,
+    /// Workspace or agent spec ID. `None` on a workspace/agent budget applies
+    /// the limit to each workspace/agent separately.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_id: Option<String>,
+    pub period: BudgetPeriod,
+    pub limit_usd: f64,
+    pub mode: BudgetMode,
+    /// Fraction of the limit at which a warning is raised.
+    pub warn_ratio: f64,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBudgetInput {
+    /// Existing budget to replace; a new ID is generated when absent.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub scope: BudgetScope,
+    #[serde(default)]
+    pub scope_id: Option<String>,
+    pub period: BudgetPeriod,
+    pub limit_usd: f64,
+    pub mode: BudgetMode,
+    #[serde(default)]
+    pub warn_ratio: Option<f64>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(sqlx::FromRow)]
+struct BudgetRow {
+    id: String,
+    scope: String,
+    scope_id: Option<String>,
+    period: String,
+    limit_usd: f64,
+    mode: String,
+    warn_ratio: f64,
+    enabled: bool,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl TryFrom<BudgetRow> for UsageBudget {
+    type Error = String;
+
+    fn try_from(row: BudgetRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            scope: BudgetScope::parse(&row.scope)?,
+            scope_id: row.scope_id,
+            period: BudgetPeriod::parse(&row.period)?,
+            limit_usd: row.limit_usd,
+            mode: BudgetMode::parse(&row.mode)?,
+            warn_ratio: row.warn_ratio,
+            enabled: row.enabled,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetState {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+/// Spend against one budget for the scope of a pending call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub budget_id: String,
+    pub scope: BudgetScope,
+    /// The workspace or agent spec the spend was summed for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_id: Option<String>,
+    pub period: BudgetPeriod,
+    pub mode: BudgetMode,
+    pub limit_usd: f64,
+    pub spent_usd: f64,
+    pub period_start: i64,
+    pub state: BudgetState,
+}
+
+impl BudgetStatus {
+    pub fn blocks(&self) -> bool {
+        self.mode == BudgetMode::HardStop && self.state == BudgetState::Exceeded
+    }
+
+    pub fn describe(&self) -> String {
+        let target = match (&self.scope, &self.scope_id) {
+            (BudgetScope::Global, _) => "global".to_string(),
+            (scope, Some(id)) => format!("{} '{}'", scope.as_str(), id),
+            (scope, None) => scope.as_str().to_string(),
+        };
+        format!(
+            "{} {} budget: ${:.4} of ${:.2} spent",
+            target,
+            self.period.as_str(),
+            self.spent_usd,
+            self.limit_usd
+        )
+    }
+}
+
+/// Result of checking every budget that applies to a call.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetCheck {
+    pub statuses: Vec<BudgetStatus>,
+}
+
+impl BudgetCheck {
+    /// The first hard-stop budget that is already exhausted.
+    pub fn blocking(&self) -> Option<&BudgetStatus> {
+        self.statuses.iter().find(|status| status.blocks())
+    }
+
+    /// Budgets past their warning threshold that do not block the call.
+    pub fn warnings(&self) -> impl Iterator<Item = &BudgetStatus> {
+        self.statuses
+            .iter()
+            .filter(|status| status.state != BudgetState::Ok && !status.blocks())
+    }
+}
+
+#[derive(Debug)]
+pub struct UsageLedger {
+    pool: Pool<Sqlite>,
+}
+
+impl UsageLedger {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn init(&self) -> Result<(), sqlx::Error> {
+        for statement in [
+            r#"
+            CREATE TABLE IF NOT EXISTS usage_ledger (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                recorded_at INTEGER NOT NULL,
+                provider_id TEXT NOT NULL,
+                provider_type TEXT NOT NULL,
+                model TEXT NOT NULL,
+                workspace_id TEXT,
+                agent_spec_id TEXT,
+                run_id TEXT,
+                prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                completion_tokens INTEGER NOT NULL DEFAULT 0,
+                cached_prompt_tokens INTEGER NOT NULL DEFAULT 0,
+                reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+                total_tokens INTEGER NOT NULL DEFAULT 0,
+                cost_usd REAL NOT NULL DEFAULT 0,
+                priced INTEGER NOT NULL DEFAULT 1,
+                estimated INTEGER NOT NULL DEFAULT 0
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_usage_ledger_recorded ON usage_ledger(recorded_at)",
+            "CREATE INDEX IF NOT EXISTS idx_usage_ledger_workspace ON usage_ledger(workspace_id, recorded_at)",
+            "CREATE INDEX IF NOT EXISTS idx_usage_ledger_agent ON usage_ledger(agent_spec_id, recorded_at)",
+            "CREATE INDEX IF NOT EXISTS idx_usage_ledger_run ON usage_ledger(run_id)",
+            r#"
+            CREATE TABLE IF NOT EXISTS usage_budgets (
+                id TEXT PRIMARY KEY NOT NULL,
+                scope TEXT NOT NULL,
+                scope_id TEXT,
+                period TEXT NOT NULL,
+                limit_usd REAL NOT NULL,
+                mode TEXT NOT NULL,
+                warn_ratio REAL NOT NULL DEFAULT 0.8,
+                enabled INTEGER NOT NULL DEFAULT 1,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        ] {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn record(&self, record: &UsageRecord) -> Result<(), String> {
+        let usage = &record.usage;
+        sqlx::query(
+            r#"
+            INSERT INTO usage_ledger (
+                recorded_at, provider_id, provider_type, model, workspace_id, agent_spec_id,
+                run_id, prompt_tokens, completion_tokens, cached_prompt_tokens,
+                reasoning_tokens, total_tokens, cost_usd, priced, estimated
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(chrono::Utc::now().timestamp_millis())
+        .bind(&record.provider_id)
+        .bind(&record.provider_type)
+        .bind(&record.model)
+        .bind(&record.scope.workspace_id)
+        .bind(&record.scope.agent_spec_id)
+        .bind(&record.scope.run_id)
+        .bind(usage.prompt_tokens as i64)
+        .bind(usage.completion_tokens as i64)
+        .bind(usage.cached_prompt_tokens as i64)
+        .bind(usage.reasoning_tokens as i64)
+        .bind(usage.total_tokens as i64)
+        .bind(record.cost_usd.unwrap_or(0.0))
+        .bind(record.cost_usd.is_some())
+        .bind(record.estimated)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to record usage: {}", e))?;
+        Ok(())
+    }
+
+    /// Ledger rows matching `query`, newest first.
+    pub async fn query(&self, query: &UsageLedgerQuery) -> Result<Vec<UsageLedgerEntry>, String> {
+        let limit = query.limit.unwrap_or(500).clamp(1, MAX_QUERY_LIMIT);
+        let sql = format!(
+            "SELECT * FROM usage_ledger WHERE {} ORDER BY recorded_at DESC, id DESC LIMIT ?",
+            LEDGER_FILTER
+        );
+        bind_filters(sqlx::query_as::<_, UsageLedgerEntry>(&sql), query)
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to query usage ledger: {}", e))
+    }
+
+    /// Totals for rows matching `query`, broken down by provider and model.
+    pub async fn summarize(&self, query: &UsageLedgerQuery) -> Result<UsageSummary, String> {
+        let sql = format!(
+            r#"
+            SELECT
+                provider_id,
+                model,
+                COUNT(*) AS requests,
+                COALESCE(SUM(prompt_tokens), 0) AS prompt_tokens,
+                COALESCE(SUM(completion_tokens), 0) AS completion_tokens,
+                COALESCE(SUM(cached_prompt_tokens), 0) AS cached_prompt_tokens,
+                COALESCE(SUM(reasoning_tokens), 0) AS reasoning_tokens,
+                COALESCE(SUM(cost_usd), 0.0) AS cost_usd,
+                COALESCE(SUM(CASE WHEN priced = 0 THEN 1 ELSE 0 END), 0) AS unpriced_requests
+              FROM usage_ledger
+             WHERE {}
+          GROUP BY provider_id, model
+          ORDER BY cost_usd DESC, requests DESC
+            "#,
+            LEDGER_FILTER
+        );
+        let by_model = bind_filters(sqlx::query_as::<_, UsageBreakdown>(&sql), query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to summarize usage ledger: {}", e))?;
+
+        let mut summary = UsageSummary::default();
+        for row in &by_model {
+            summary.requests += row.requests;
+            summary.prompt_tokens += row.prompt_tokens;
+            summary.completion_tokens += row.completion_tokens;
+            summary.cached_prompt_tokens += row.cached_prompt_tokens;
+            summary.reasoning_tokens += row.reasoning_tokens;
+            summary.cost_usd += row.cost_usd;
+            summary.unpriced_requests += row.unpriced_requests;
+        }
+        summary.by_model = by_model;
+        Ok(summary)
+    }
+
+    pub async fn list_budgets(&self) -> Result<Vec<UsageBudget>, String> {
+        let rows: Vec<BudgetRow> =
+            sqlx::query_as("SELECT * FROM usage_budgets ORDER BY created_at ASC")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| format!("Failed to list budgets: {}", e))?;
+        rows.into_iter().map(UsageBudget::try_from).collect()
+    }
+
+    pub async fn upsert_budget(&self, input: UsageBudgetInput) -> Result<UsageBudget, String> {
+        if !input.limit_usd.is_finite() || input.limit_usd <= 0.0 {
+            return Err("Budget limit must be a positive amount".to_string());
+        }
+        let warn_ratio = input.warn_ratio.unwrap_or(DEFAULT_WARN_RATIO);
+        if !(warn_ratio > 0.0 && warn_ratio <= 1.0) {
+            return Err("Budget warning ratio must be between 0 and 1".to_string());
+        }
+        let scope_id = input
+            .scope_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+        if input.scope == BudgetScope::Global && scope_id.is_some() {
+            return Err("Global budgets cannot target a workspace or agent".to_string());
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let id = input.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        sqlx::query(
+            r#"
+            INSERT INTO usage_budgets (
+                id, scope, scope_id, period, limit_usd, mode, warn_ratio, enabled,
+                created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                scope = excluded.scope,
+                scope_id = excluded.scope_id,
+                period = excluded.period,
+                limit_usd = excluded.limit_usd,
+                mode = excluded.mode,
+                warn_ratio = excluded.warn_ratio,
+                enabled = excluded.enabled,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&id)
+        .bind(input.scope.as_str())
+        .bind(&scope_id)
+        .bind(input.period.as_str())
+        .bind(input.limit_usd)
+        .bind(input.mode.as_str())
+        .bind(warn_ratio)
+        .bind(input.enabled.unwrap_or(true))
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| format!("Failed to save budget: {}", e))?;
+
+        let row: BudgetRow = sqlx::query_as("SELECT * FROM usage_budgets WHERE id = ?")
+            .bind(&id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to load budget: {}", e))?;
+        UsageBudget::try_from(row)
+    }
+
+    pub async fn delete_budget(&self, id: &str) -> Result<bool, String> {
+        let result = sqlx::query("DELETE FROM usage_budgets WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to delete budget: {}", e))?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Spend against every enabled budget that applies to a call made for `scope`.
+    pub async fn check_budgets(&self, scope: &UsageScope) -> Result<BudgetCheck, String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut check = BudgetCheck::default();
+        for budget in self.list_budgets().await? {
+            if !budget.enabled {
+                continue;
+            }
+            let target = match budget.scope {
+                BudgetScope::Global => None,
+                BudgetScope::Workspace => scope.workspace_id.as_ref(),
+                BudgetScope::Agent => scope.agent_spec_id.as_ref(),
+            };
+            let scope_id = match (budget.scope, &budget.scope_id, target) {
+                (BudgetScope::Global, _, _) => None,
+                (_, _, None) => continue,
+                (_, Some(expected), Some(actual)) if expected != actual => continue,
+                (_, _, Some(actual)) => Some(actual.clone()),
+            };
+
+            let period_start = budget.period.start_ms(now);
+            let spent_usd = self
+                .spend_since(budget.scope, scope_id.as_deref(), period_start)
+                .await?;
+            let state = if spent_usd >= budget.limit_usd {
+                BudgetState::Exceeded
+            } else if spent_usd >= budget.limit_usd * budget.warn_ratio {
+                BudgetState::Warning
+            } else {
+                BudgetState::Ok
+            };
+            check.statuses.push(BudgetStatus {
+                budget_id: budget.id,
+                scope: budget.scope,
+                scope_id,
+                period: budget.period,
+                mode: budget.mode,
+                limit_usd: budget.limit_usd,
+                spent_usd,
+                period_start,
+                state,
+            });
+        }
+        Ok(check)
+    }
+
+    async fn spend_since(
+        &self,
+        scope: BudgetScope,
+        scope_id: Option<&str>,
+        since: i64,
+    ) -> Result<f64, String> {
+        let query = UsageLedgerQuery {
+            workspace_id: (scope == BudgetScope::Workspace)
+                .then(|| scope_id.map(str::to_string))
+                .flatten(),
+            agent_spec_id: (scope == BudgetScope::Agent)
+                .then(|| scope_id.map(str::to_string))
+                .flatten(),
+            since: Some(since),
+            ..Default::default()
+        };
+        let sql = format!(
+            "SELECT COALESCE(SUM(cost_usd), 0.0) FROM usage_ledger WHERE {}",
+            LEDGER_FILTER
+        );
+        let (spent,): (f64,) = bind_filters(sqlx::query_as(&sql), &query)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to sum usage: {}", e))?;
+        Ok(spent)
+    }
+}
+
+/// `WHERE` clause shared by ledger queries; bound by `bind_filters`.
+const LEDGER_FILTER: &str = "(? IS NULL OR workspace_id = ?)
+               AND (? IS NULL OR agent_spec_id = ?)
+               AND (? IS NULL OR run_id = ?)
+               AND (? IS NULL OR recorded_at >= ?)
+               AND (? IS NULL OR recorded_at < ?)";
+
+fn bind_filters<'q, O>(
+    statement: QueryAs<'q, Sqlite, O, SqliteArguments<'q>>,
+    query: &UsageLedgerQuery,
+) -> QueryAs<'q, Sqlite, O, SqliteArguments<'q>> {
+    statement
+        .bind(query.workspace_id.clone())
+        .bind(query.workspace_id.clone())
+        .bind(query.agent_spec_id.clone())
+        .bind(query.agent_spec_id.clone())
+        .bind(query.run_id.clone())
+        .bind(query.run_id.clone())
+        .bind(query.since)
+        .bind(query.since)
+        .bind(query.until)
+        .bind(query.until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn ledger() -> UsageLedger {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .expect("in-memory sqlite pool");
+        let ledger = UsageLedger::new(pool);
+        ledger.init().await.expect("init usage ledger");
+        ledger
+    }
+
+    fn scope(workspace: &str, agent: &str) -> UsageScope {
+        UsageScope {
+            workspace_id: Some(workspace.to_string()),
+            agent_spec_id: Some(agent.to_string()),
+            run_id: Some("run-1".to_string()),
+        }
+    }
+
+    fn record(scope: UsageScope, model: &str, cost_usd: Option<f64>) -> UsageRecord {
+        UsageRecord {
+            provider_id: "openai".to_string(),
+            provider_type: "openai".to_string(),
+            model: model.to_string(),
+            scope,
+            usage: TokenUsage {
+                prompt_tokens: 1_000,
+                completion_tokens: 200,
+                total_tokens: 1_200,
+                cached_prompt_tokens: 400,
+                reasoning_tokens: 50,
+            },
+            cost_usd,
+            estimated: false,
+        }
+    }
+
+    fn budget(
+        scope: BudgetScope,
+        scope_id: Option<&str>,
+        limit_usd: f64,
+        mode: BudgetMode,
+    ) -> UsageBudgetInput {
+        UsageBudgetInput {
+            id: None,
+            scope,
+            scope_id: scope_id.map(str::to_string),
+            period: BudgetPeriod::Daily,
+            limit_usd,
+            mode,
+            warn_ratio: Some(0.5),
+            enabled: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn records_and_summarizes_by_model() {
+        let ledger = ledger().await;
+        ledger
+            .record(&record(scope("ws-1", "agent-a"), "gpt-4o", Some(0.25)))
+            .await
+            .unwrap();
+        ledger
+            .record(&record(scope("ws-1", "agent-a"), "gpt-4o", Some(0.5)))
+            .await
+            .unwrap();
+        ledger
+            .record(&record(scope("ws-2", "agent-b"), "mystery", None))
+            .await
+            .unwrap();
+
+        let all = ledger
+            .summarize(&UsageLedgerQuery::default())
+            .await
+            .unwrap();
+        assert_eq!(all.requests, 3);
+        assert_eq!(all.unpriced_requests, 1);
+        assert_eq!(all.cached_prompt_tokens, 1_200);
+        assert!((all.cost_usd - 0.75).abs() < 1e-9);
+        assert_eq!(all.by_model[0].model, "gpt-4o");
+
+        let ws1 = ledger
+            .query(&UsageLedgerQuery {
+                workspace_id: Some("ws-1".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(ws1.len(), 2);
+        assert!(ws1.iter().all(|entry| entry.priced));
+        assert_eq!(ws1[0].reasoning_tokens, 50);
+    }
+
+    #[tokio::test]
+    async fn budgets_warn_then_stop_per_scope() {
+        let ledger = ledger().await;
+        ledger
+            .upsert_budget(budget(
+                BudgetScope::Workspace,
+                Some("ws-1"),
+                1.0,
+                BudgetMode::HardStop,
+            ))
+            .await
+            .unwrap();
+        ledger
+            .upsert_budget(budget(BudgetScope::Agent, None, 0.5, BudgetMode::SoftWarn))
+            .await
+            .unwrap();
+
+        let fresh = ledger
+            .check_budgets(&scope("ws-1", "agent-a"))
+            .await
+            .unwrap();
+        assert_eq!(fresh.statuses.len(), 2);
+        assert!(fresh.blocking().is_none());
+        assert_eq!(fresh.warnings().count(), 0);
+
+        ledger
+            .record(&record(scope("ws-1", "agent-a"), "gpt-4o", Some(0.6)))
+            .await
+            .unwrap();
+        let warned = ledger
+            .check_budgets(&scope("ws-1", "agent-a"))
+            .await
+            .unwrap();
+        assert!(warned.blocking().is_none());
+        assert_eq!(warned.warnings().count(), 2);
+
+        ledger
+            .record(&record(scope("ws-1", "agent-b"), "gpt-4o", Some(0.5)))
+            .await
+            .unwrap();
+        let stopped = ledger
+            .check_budgets(&scope("ws-1", "agent-a"))
+            .await
+            .unwrap();
+        let blocking = stopped.blocking().expect("workspace budget blocks");
+        assert_eq!(blocking.scope, BudgetScope::Workspace);
+        assert!((blocking.spent_usd - 1.1).abs() < 1e-9);
+
+        // Other workspaces are unaffected by the ws-1 budget.
+        let other = ledger
+            .check_budgets(&scope("ws-2", "agent-c"))
+            .await
+            .unwrap();
+        assert!(other.blocking().is_none());
+        assert_eq!(other.statuses.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_budgets() {
+        let ledger = ledger().await;
+        assert!(ledger
+            .upsert_budget(budget(BudgetScope::Global, None, 0.0, BudgetMode::HardStop))
+            .await
+            .is_err());
+        assert!(ledger
+            .upsert_budget(budget(
+                BudgetScope::Global,
+                Some("ws-1"),
+                5.0,
+                BudgetMode::HardStop
+            ))
+            .await
+            .is_err());
+
+        let saved = ledger
+            .upsert_budget(budget(BudgetScope::Global, None, 5.0, BudgetMode::SoftWarn))
+            .await
+            .unwrap();
+        assert!(ledger.delete_budget(&saved.id).await.unwrap());
+        assert!(ledger.list_budgets().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn period_start_is_local_midnight() {
+        let now = Local::now();
+        let daily = BudgetPeriod::Daily.start_ms(now.timestamp_millis());
+        let monthly = BudgetPeriod::Monthly.start_ms(now.timestamp_millis());
+        assert!(monthly <= daily && daily <= now.timestamp_millis());
+        let start = Local.timestamp_millis_opt(monthly).single().unwrap();
+        assert_eq!(start.day(), 1);
+    }
+}
@@ -29,6 +29,7 @@ impl WasmExecutionResult {
                     Some(self.stderr)
                 },
                 exit_code: Some(0),
+                ..Default::default()
             }
         } else {
             CommandResult {
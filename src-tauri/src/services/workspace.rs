@@ -8,6 +8,8 @@ use std::path::PathBuf;
 use uuid::Uuid;
 
 use crate::services::mate_launchpad::WorkspaceLaunchSettings;
+use crate::services::process_sandbox::ShellSandboxPolicy;
+use crate::services::shell_policy::ShellCommandPolicy;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Workspace {
@@ -69,6 +71,12 @@ pub struct WorkspaceSettings {
     pub language: String,
     pub auto_save: bool,
     pub notifications_enabled: bool,
+    /// Binaries and argument rules allowed for `execute_command`.
+    #[serde(default)]
+    pub command_policy: ShellCommandPolicy,
+    /// Process isolation for `execute_command` (Linux).
+    #[serde(default)]
+    pub sandbox: ShellSandboxPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -109,6 +117,11 @@ impl WorkspaceManager {
         Ok(Self { workspaces_dir })
     }
 
+    #[cfg(test)]
+    pub fn in_dir(workspaces_dir: PathBuf) -> Self {
+        Self { workspaces_dir }
+    }
+
     pub fn create_workspace(
         &self,
         name: String,
@@ -138,6 +151,8 @@ impl WorkspaceManager {
                 language: "en".to_string(),
                 auto_save: true,
                 notifications_enabled: true,
+                command_policy: ShellCommandPolicy::default(),
+                sandbox: ShellSandboxPolicy::default(),
             },
             launchpad: WorkspaceLaunchSettings::default(),
         };
@@ -435,6 +450,8 @@ impl WorkspaceManager {
                 language: "en".to_string(),
                 auto_save: true,
                 notifications_enabled: true,
+                command_policy: ShellCommandPolicy::default(),
+                sandbox: ShellSandboxPolicy::default(),
             },
             default_memory: WorkspaceMemory {
                 max_size: 1024 * 1024 * 100, // 100MB
@@ -463,6 +480,8 @@ impl WorkspaceManager {
                 language: "en".to_string(),
                 auto_save: true,
                 notifications_enabled: true,
+                command_policy: ShellCommandPolicy::default(),
+                sandbox: ShellSandboxPolicy::default(),
             },
             default_memory: WorkspaceMemory {
                 max_size: 1024 * 1024 * 500, // 500MB
@@ -494,6 +513,8 @@ impl WorkspaceManager {
                 language: "en".to_string(),
                 auto_save: false,
                 notifications_enabled: false,
+                command_policy: ShellCommandPolicy::default(),
+                sandbox: ShellSandboxPolicy::default(),
             },
             default_memory: WorkspaceMemory {
                 max_size: 1024 * 1024 * 10, // 10MB
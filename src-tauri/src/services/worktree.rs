@@ -0,0 +1,390 @@
+//! Isolated checkouts for write-capable specialists running in parallel.
+//!
+//! A checkout is a detached `git worktree` of a snapshot commit of the
+//! workspace, uncommitted and untracked files included. The snapshot goes
+//! through a scratch index, so the workspace's own index and HEAD are never
+//! touched. Workspaces outside git get a private repository under the temp
+//! dir instead, which keeps merge-back identical for both: file by file,
+//! three-way against the snapshot with `git merge-file`.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const SCRATCH_DIR: &str = "rainy-worktrees";
+const SNAPSHOT_IDENTITY: [(&str, &str); 4] = [
+    ("GIT_AUTHOR_NAME", "Rainy MaTE"),
+    ("GIT_AUTHOR_EMAIL", "agent@rainy.local"),
+    ("GIT_COMMITTER_NAME", "Rainy MaTE"),
+    ("GIT_COMMITTER_EMAIL", "agent@rainy.local"),
+];
+
+/// Result of merging a checkout back into its workspace. Paths are relative
+/// to the repository (or workspace) top.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeReport {
+    pub applied: Vec<String>,
+    pub conflicts: Vec<String>,
+    /// Checkout left on disk so the conflicting versions can be reviewed.
+    pub kept_at: Option<PathBuf>,
+}
+
+impl MergeReport {
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Merged {} file(s) back", self.applied.len());
+        if !self.conflicts.is_empty() {
+            summary.push_str(&format!("; conflicts in {}", self.conflicts.join(", ")));
+            if let Some(path) = self.kept_at.as_ref() {
+                summary.push_str(&format!(" (lane checkout kept at {})", path.display()));
+            }
+        }
+        summary
+    }
+}
+
+pub struct Worktree {
+    /// Directory that stands in for the workspace root.
+    pub root: PathBuf,
+    /// Workspace root the checkout mirrors.
+    pub source: PathBuf,
+    /// Top of the source repository; the workspace itself outside git.
+    top: PathBuf,
+    /// Where `git worktree` commands run.
+    repo: PathBuf,
+    checkout: PathBuf,
+    scratch: PathBuf,
+    base_tree: String,
+    keep: bool,
+}
+
+impl Worktree {
+    /// Snapshot `source` and check the snapshot out under the temp dir.
+    pub fn create(source: &Path) -> Result<Self, String> {
+        let source = source
+            .canonicalize()
+            .map_err(|e| format!("Cannot isolate {}: {}", source.display(), e))?;
+        let scratch = std::env::temp_dir()
+            .join(SCRATCH_DIR)
+            .join(uuid::Uuid::new_v4().to_string());
+        fs::create_dir_all(&scratch)
+            .map_err(|e| format!("Failed to create {}: {}", scratch.display(), e))?;
+        let checkout = scratch.join("checkout");
+
+        let mut worktree = Self {
+            root: checkout.clone(),
+            source: source.clone(),
+            top: source.clone(),
+            repo: source.clone(),
+            checkout,
+            scratch,
+            base_tree: String::new(),
+            keep: false,
+        };
+
+        let prefix = match git_text(&source, &["rev-parse", "--show-toplevel"]) {
+            Ok(top) => {
+                worktree.top = PathBuf::from(top);
+                worktree.repo = worktree.top.clone();
+                git_text(&source, &["rev-parse", "--show-prefix"])?
+            }
+            Err(_) => {
+                let private = worktree.scratch.join("repo");
+                git(&worktree.scratch, &["init", "-q", "repo"])?;
+                worktree.repo = private;
+                String::new()
+            }
+        };
+
+        let (tree, commit) = worktree.snapshot()?;
+        worktree.base_tree = tree;
+        let checkout = worktree.checkout.display().to_string();
+        git(
+            &worktree.repo,
+            &["worktree", "add", "-q", "--detach", &checkout, &commit],
+        )?;
+        worktree.root = worktree.checkout.join(prefix);
+        Ok(worktree)
+    }
+
+    /// Commit the workspace as it is on disk, through a scratch index seeded
+    /// from the real one so unchanged files are not rehashed.
+    fn snapshot(&self) -> Result<(String, String), String> {
+        let index = self.scratch.join("index");
+        let git_dir = PathBuf::from(git_text(
+            &self.repo,
+            &["rev-parse", "--path-format=absolute", "--git-dir"],
+        )?);
+        if self.top == self.repo && git_dir.join("index").is_file() {
+            fs::copy(git_dir.join("index"), &index)
+                .map_err(|e| format!("Failed to seed snapshot index: {}", e))?;
+        }
+        let work_tree = self.top.display().to_string();
+        let git_dir = git_dir.display().to_string();
+        let run = |args: &[&str]| -> Result<String, String> {
+            let mut command = Command::new("git");
+            command
+                .args(["--git-dir", &git_dir, "--work-tree", &work_tree])
+                .args(args)
+                .current_dir(&self.top)
+                .env("GIT_INDEX_FILE", &index)
+                .envs(SNAPSHOT_IDENTITY);
+            checked(command.output(), args).map(|output| stdout_text(&output))
+        };
+        run(&["add", "-A"])?;
+        let tree = run(&["write-tree"])?;
+        let commit = run(&["commit-tree", &tree, "-m", "Specialist checkout snapshot"])?;
+        Ok((tree, commit))
+    }
+
+    /// Apply what changed in the checkout to the workspace. A file the
+    /// workspace changed meanwhile is merged three-way; one that cannot be
+    /// merged cleanly is left alone and reported as a conflict.
+    pub fn merge_back(&self) -> Result<MergeReport, String> {
+        git(&self.checkout, &["add", "-A"])?;
+        let result_tree = git_text(&self.checkout, &["write-tree"])?;
+        let changes = git(
+            &self.checkout,
+            &[
+                "diff-tree",
+                "-r",
+                "--no-renames",
+                "--name-status",
+                "-z",
+                &self.base_tree,
+                &result_tree,
+            ],
+        )?;
+
+        let mut report = MergeReport::default();
+        let fields = changes
+            .stdout
+            .split(|byte| *byte == 0)
+            .map(|field| String::from_utf8_lossy(field).to_string())
+            .collect::<Vec<_>>();
+        for pair in fields.chunks(2) {
+            let [status, path] = pair else {
+                continue;
+            };
+            if status.is_empty() {
+                continue;
+            }
+            let base = self.blob(&self.base_tree, path)?;
+            let theirs = self.blob(&result_tree, path)?;
+            let target = self.top.join(path);
+            let current = fs::read(&target).ok();
+
+            let merged = if current == theirs {
+                continue;
+            } else if current == base {
+                theirs
+            } else if status == "M" {
+                match (current.as_ref(), base.as_ref(), theirs.as_ref()) {
+                    (Some(current), Some(base), Some(theirs)) => {
+                        match self.merge_file(path, current, base, theirs)? {
+                            Some(merged) => Some(merged),
+                            None => {
+                                report.conflicts.push(path.clone());
+                                continue;
+                            }
+                        }
+                    }
+                    _ => {
+                        report.conflicts.push(path.clone());
+                        continue;
+                    }
+                }
+            } else {
+                report.conflicts.push(path.clone());
+                continue;
+            };
+
+            match merged {
+                Some(bytes) => {
+                    if let Some(parent) = target.parent() {
+                        fs::create_dir_all(parent)
+                            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                    }
+                    fs::write(&target, bytes)
+                        .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+                }
+                None => {
+                    if target.exists() {
+                        fs::remove_file(&target)
+                            .map_err(|e| format!("Failed to delete {}: {}", target.display(), e))?;
+                    }
+                }
+            }
+            report.applied.push(path.clone());
+        }
+        Ok(report)
+    }
+
+    fn blob(&self, tree: &str, path: &str) -> Result<Option<Vec<u8>>, String> {
+        let object = format!("{}:{}", tree, path);
+        let exists = Command::new("git")
+            .args(["cat-file", "-e", &object])
+            .current_dir(&self.checkout)
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false);
+        if !exists {
+            return Ok(None);
+        }
+        git(&self.checkout, &["cat-file", "blob", &object]).map(|output| Some(output.stdout))
+    }
+
+    /// `git merge-file` on scratch copies; `None` when the merge conflicts.
+    fn merge_file(
+        &self,
+        path: &str,
+        current: &[u8],
+        base: &[u8],
+        theirs: &[u8],
+    ) -> Result<Option<Vec<u8>>, String> {
+        let dir = self.scratch.join("merge");
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to prepare merge: {}", e))?;
+        let files = [
+            (dir.join("current"), current),
+            (dir.join("base"), base),
+            (dir.join("theirs"), theirs),
+        ];
+        for (file, bytes) in &files {
+            fs::write(file, bytes).map_err(|e| format!("Failed to prepare merge: {}", e))?;
+        }
+        let output = Command::new("git")
+            .arg("merge-file")
+            .arg("-p")
+            .args(files.iter().map(|(file, _)| file))
+            .output()
+            .map_err(|e| format!("Failed to merge {}: {}", path, e))?;
+        Ok(output.status.success().then_some(output.stdout))
+    }
+
+    /// Leave the checkout on disk instead of removing it on drop.
+    pub fn keep_for_review(&mut self) -> PathBuf {
+        self.keep = true;
+        self.checkout.clone()
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        if self.keep {
+            return;
+        }
+        let checkout = self.checkout.display().to_string();
+        let _ = git(&self.repo, &["worktree", "remove", "--force", &checkout]);
+        let _ = fs::remove_dir_all(&self.scratch);
+        let _ = git(&self.repo, &["worktree", "prune"]);
+    }
+}
+
+/// Whether `path` lies inside a checkout made by [`Worktree::create`].
+pub fn is_checkout_path(path: &Path) -> bool {
+    let scratch = std::env::temp_dir().join(SCRATCH_DIR);
+    let (Ok(path), Ok(scratch)) = (path.canonicalize(), scratch.canonicalize()) else {
+        return false;
+    };
+    // <scratch>/<lane id>/checkout/...
+    path.strip_prefix(scratch)
+        .is_ok_and(|rest| rest.iter().nth(1).is_some_and(|part| part == "checkout"))
+}
+
+/// Create a checkout of `source` off the async runtime.
+pub async fn isolate(source: PathBuf) -> Result<Worktree, String> {
+    tokio::task::spawn_blocking(move || Worktree::create(&source))
+        .await
+        .map_err(|e| format!("Worktree task failed: {}", e))?
+}
+
+/// Merge `worktree` back and remove it. A checkout with conflicts is kept
+/// so an operator can review the lane's versions.
+pub async fn merge_back(mut worktree: Worktree) -> Result<MergeReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut report = worktree.merge_back()?;
+        if !report.conflicts.is_empty() {
+            report.kept_at = Some(worktree.keep_for_review());
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Worktree task failed: {}", e))?
+}
+
+/// Remove `worktree` without merging, e.g. after a cancelled run.
+pub async fn discard(worktree: Worktree) {
+    let _ = tokio::task::spawn_blocking(move || drop(worktree)).await;
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<Output, String> {
+    checked(
+        Command::new("git").args(args).current_dir(dir).output(),
+        args,
+    )
+}
+
+fn git_text(dir: &Path, args: &[&str]) -> Result<String, String> {
+    git(dir, args).map(|output| stdout_text(&output))
+}
+
+fn checked(output: std::io::Result<Output>, args: &[&str]) -> Result<Output, String> {
+    let output = output.map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn stdout_text(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_directory_round_trips_through_a_checkout() {
+        let workspace = tempfile::tempdir().expect("workspace");
+        fs::write(workspace.path().join("notes.txt"), "one\ntwo\nthree\n").unwrap();
+        fs::write(workspace.path().join("shared.txt"), "a\nb\nc\nd\ne\n").unwrap();
+        fs::write(workspace.path().join("old.txt"), "gone soon\n").unwrap();
+
+        let worktree = Worktree::create(workspace.path()).expect("checkout");
+        let checkout = worktree.checkout.clone();
+        fs::write(worktree.root.join("notes.txt"), "one\nTWO\nthree\n").unwrap();
+        fs::write(worktree.root.join("shared.txt"), "a\nB\nc\nd\ne\n").unwrap();
+        fs::write(worktree.root.join("added.txt"), "new\n").unwrap();
+        fs::remove_file(worktree.root.join("old.txt")).unwrap();
+
+        // The workspace moved on meanwhile: a clean merge and a conflict
+        fs::write(workspace.path().join("shared.txt"), "a\nb\nc\nd\nE\n").unwrap();
+        fs::write(workspace.path().join("added.txt"), "also new\n").unwrap();
+
+        let report = worktree.merge_back().expect("merge");
+        assert_eq!(report.conflicts, vec!["added.txt"]);
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("shared.txt")).unwrap(),
+            "a\nB\nc\nd\nE\n"
+        );
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("notes.txt")).unwrap(),
+            "one\nTWO\nthree\n"
+        );
+        assert!(!workspace.path().join("old.txt").exists());
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("added.txt")).unwrap(),
+            "also new\n"
+        );
+
+        drop(worktree);
+        assert!(!checkout.exists());
+    }
+}
@@ -261,6 +261,7 @@ const TelemetryBar = React.memo(function TelemetryBar({
     executionMode?: string;
     workspaceMemoryEnabled?: boolean;
     lastModel?: string;
+    lastProvider?: string;
     totalTokens?: number;
     compressionApplied?: boolean;
     compressionTriggerTokens?: number;
@@ -277,6 +278,9 @@ const TelemetryBar = React.memo(function TelemetryBar({
   if (telemetry?.lastModel) {
     items.push(`model:${telemetry.lastModel}`);
   }
+  if (telemetry?.lastProvider) {
+    items.push(`provider:${telemetry.lastProvider}`);
+  }
   if (typeof telemetry?.totalTokens === "number" && telemetry.totalTokens > 0) {
     items.push(`tokens:${telemetry.totalTokens.toLocaleString()}`);
   }
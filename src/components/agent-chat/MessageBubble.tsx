@@ -441,7 +441,11 @@ function SupervisorRail({
             >
               <div className="flex items-center justify-between gap-2">
                 <span className="text-sm font-medium text-foreground">
-                  {roleLabel[specialist.role]}
+                  {roleLabel[specialist.role] ??
+                    specialist.role
+                      .split("_")
+                      .map((word) => word.charAt(0).toUpperCase() + word.slice(1))
+                      .join(" ")}
                 </span>
                 <span
                   className={`text-[11px] font-medium uppercase tracking-wide ${statusTone[specialist.status]}`}
@@ -20,6 +20,7 @@ const methodIcons: Record<string, React.ElementType> = {
   read_file: FileText,
   list_files: FolderOpen,
   search_files: Search,
+  search_code: Search,
   default: FileCode,
 };
 
@@ -29,6 +30,7 @@ const methodColors: Record<string, string> = {
   read_file: "text-blue-400 bg-blue-400/10 border-blue-400/20",
   list_files: "text-yellow-400 bg-yellow-400/10 border-yellow-400/20",
   search_files: "text-pink-400 bg-pink-400/10 border-pink-400/20",
+  search_code: "text-pink-400 bg-pink-400/10 border-pink-400/20",
   default: "text-gray-400 bg-gray-400/10 border-gray-400/20",
 };
 
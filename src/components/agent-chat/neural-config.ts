@@ -49,6 +49,7 @@ export const TOOL_STATE_MAP: Record<string, NeuralState> = {
   read_many_files: "observing",
   list_files: "observing",
   search_files: "observing",
+  search_code: "observing",
   file_exists: "observing",
   get_file_info: "observing",
   ingest_document: "observing",
@@ -123,6 +124,7 @@ const TOOL_DISPLAY_NAMES: Record<string, string> = {
   delete_file: "Deleting File",
   list_files: "Listing Files",
   search_files: "Searching Files",
+  search_code: "Searching Code",
   file_exists: "Checking File",
   get_file_info: "Inspecting File",
   ingest_document: "Ingesting Document",
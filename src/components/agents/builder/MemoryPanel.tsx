@@ -241,6 +241,17 @@ export function MemoryPanel({ agentId, memoryConfig, onChange }: MemoryPanelProp
               </Slider.Track>
             </Slider>
           </div>
+
+          <Switch
+            isSelected={memoryConfig.retrieval.rerank ?? false}
+            isDisabled={memoryConfig.strategy !== "hybrid"}
+            onChange={(rerank) => updateRetrieval({ rerank })}
+          >
+            <Switch.Control>
+              <Switch.Thumb />
+            </Switch.Control>
+            Local rerank
+          </Switch>
         </div>
       </section>
 
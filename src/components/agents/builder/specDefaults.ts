@@ -44,6 +44,7 @@ export function createDefaultAgentSpec(id: string = crypto.randomUUID()): AgentS
       retrieval: {
         retention_days: 30,
         max_tokens: 32000,
+        rerank: false,
       },
       persistence: {
         cross_session: false,
@@ -186,6 +186,24 @@ export function ProviderManagementPanel() {
                 className="w-full px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500"
               />
             </div>
+            {(registerForm.provider_type === "local" ||
+              registerForm.provider_type === "custom") && (
+              <div>
+                <label className="block text-sm font-medium text-gray-700 mb-1">
+                  Base URL
+                </label>
+                <input
+                  type="url"
+                  value={registerForm.base_url}
+                  onChange={(e) =>
+                    setRegisterForm({ ...registerForm, base_url: e.target.value })
+                  }
+                  placeholder="http://localhost:11434/v1"
+                  className="w-full px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-blue-500"
+                  required={registerForm.provider_type === "custom"}
+                />
+              </div>
+            )}
             <div>
               <label className="block text-sm font-medium text-gray-700 mb-1">
                 Model
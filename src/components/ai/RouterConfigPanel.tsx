@@ -30,6 +30,7 @@ export function RouterConfigPanel() {
     cost_optimization_enabled: boolean;
     capability_matching_enabled: boolean;
     max_retries: number;
+    hedging_enabled: boolean;
   } | null>(null);
 
   const [availableProviersToAdd, setAvailableProvidersToAdd] = useState<
@@ -218,6 +219,24 @@ export function RouterConfigPanel() {
                 </label>
               </div>
 
+              <div className="flex items-center gap-2">
+                <input
+                  type="checkbox"
+                  id="hedging"
+                  checked={editConfig.hedging_enabled}
+                  onChange={(e) =>
+                    handleConfigChange("hedging_enabled", e.target.checked)
+                  }
+                  className="w-4 h-4 text-blue-600 rounded focus:ring-2 focus:ring-blue-500"
+                />
+                <label
+                  htmlFor="hedging"
+                  className="text-sm font-medium text-gray-700"
+                >
+                  Hedge Slow Requests
+                </label>
+              </div>
+
               <div>
                 <label className="block text-sm font-medium text-gray-700 mb-1">
                   Max Retries
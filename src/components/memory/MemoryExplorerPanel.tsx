@@ -1,17 +1,22 @@
 import { useState, useEffect, useCallback, useMemo } from "react";
 import {
   Brain,
+  Check,
   ChevronDown,
   ChevronLeft,
   ChevronRight,
   Clock,
+  Download,
   Hash,
+  Pencil,
   RefreshCw,
   Search,
   Shield,
   Sparkles,
   Trash2,
   TriangleAlert,
+  Upload,
+  X,
 } from "lucide-react";
 import {
   Button,
@@ -22,10 +27,14 @@ import {
   Select,
   Spinner,
 } from "@heroui/react";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { toast } from "sonner";
 import * as tauri from "../../services/tauri";
 import type {
   DecryptedMemoryEntry,
+  MemoryArchiveKey,
+  MemoryConflictPolicy,
+  MemoryReviewDecision,
   VaultDetailedStats,
   WorkspaceSummary,
 } from "../../types/memory";
@@ -53,6 +62,13 @@ const CATEGORY_STYLES: Record<string, string> = {
     "border-zinc-500/20 bg-zinc-500/12 text-zinc-700 dark:text-zinc-300",
 };
 
+const STATUS_STYLES: Record<string, string> = {
+  pending:
+    "border-amber-500/20 bg-amber-500/12 text-amber-700 dark:text-amber-300",
+  superseded:
+    "border-zinc-500/20 bg-zinc-500/12 text-zinc-600 line-through dark:text-zinc-400",
+};
+
 const PANEL_CLASS =
   "border border-border/50 dark:border-white/8 bg-background/80 dark:bg-background/24 backdrop-blur-md shadow-[0_18px_42px_-34px_rgba(0,0,0,0.38)]";
 const POPOVER_CLASS = `${PANEL_CLASS} rounded-[18px] p-1.5`;
@@ -88,6 +104,17 @@ function getCategory(entry: DecryptedMemoryEntry): string {
   return entry.metadata["_category"] ?? "observation";
 }
 
+function getStatus(entry: DecryptedMemoryEntry): string {
+  return entry.metadata["_status"] ?? "active";
+}
+
+function getConfidence(entry: DecryptedMemoryEntry): number | null {
+  const raw = entry.metadata["_confidence"];
+  if (!raw) return null;
+  const parsed = Number.parseFloat(raw);
+  return Number.isFinite(parsed) ? Math.max(0, Math.min(1, parsed)) : null;
+}
+
 function getImportance(entry: DecryptedMemoryEntry): number | null {
   const raw = entry.metadata["_importance"];
   if (!raw) return null;
@@ -156,6 +183,7 @@ function EntryCard({
 }: EntryCardProps) {
   const category = getCategory(entry);
   const importance = getImportance(entry);
+  const status = getStatus(entry);
 
   return (
     <Card
@@ -175,6 +203,15 @@ function EntryCard({
             >
               {category}
             </Chip>
+            {STATUS_STYLES[status] ? (
+              <Chip
+                className={`rounded-full border px-2 ${STATUS_STYLES[status]}`}
+                variant="soft"
+                size="sm"
+              >
+                {status}
+              </Chip>
+            ) : null}
             <Chip
               className={`rounded-full border px-2 ${SENSITIVITY_STYLES[entry.sensitivity] ?? ""}`}
               variant="soft"
@@ -312,6 +349,291 @@ function EntryCard({
   );
 }
 
+interface PendingCardProps {
+  entry: DecryptedMemoryEntry;
+  onReview: (decision: MemoryReviewDecision) => void;
+}
+
+function PendingCard({ entry, onReview }: PendingCardProps) {
+  const [editing, setEditing] = useState(false);
+  const [draft, setDraft] = useState(entry.content);
+  const category = getCategory(entry);
+  const confidence = getConfidence(entry);
+  const supersedes = (entry.metadata["_supersedes"] ?? "")
+    .split(",")
+    .filter(Boolean);
+  const tool = entry.metadata["_source_tool"];
+
+  return (
+    <Card className={`${PANEL_CLASS} rounded-[20px] px-4 py-3 shadow-none`}>
+      <Card.Content className="space-y-2.5 p-0">
+        <div className="flex flex-wrap items-center gap-2">
+          <Chip
+            className={`rounded-full border px-2 ${CATEGORY_STYLES[category] ?? CATEGORY_STYLES.observation}`}
+            variant="soft"
+            size="sm"
+          >
+            {category}
+          </Chip>
+          {confidence !== null ? (
+            <EntryMetadataChip>
+              {Math.round(confidence * 100)}% confident
+            </EntryMetadataChip>
+          ) : null}
+          {tool ? <EntryMetadataChip>{tool}</EntryMetadataChip> : null}
+          <EntryMetadataChip>
+            <Clock className="mr-1 size-3" />
+            {timeAgo(entry.created_at)}
+          </EntryMetadataChip>
+          {supersedes.length > 0 ? (
+            <EntryMetadataChip className="text-amber-700 dark:text-amber-300">
+              Replaces {supersedes.length}{" "}
+              {supersedes.length === 1 ? "memory" : "memories"}
+            </EntryMetadataChip>
+          ) : null}
+        </div>
+
+        {editing ? (
+          <Input
+            className="h-9 w-full rounded-[16px] border border-border/50 bg-background/75 text-sm dark:bg-background/18"
+            value={draft}
+            onChange={(event) => setDraft(event.target.value)}
+          />
+        ) : (
+          <p className="text-[13px] leading-5.5 text-foreground/92">
+            {entry.content}
+          </p>
+        )}
+
+        <div className="flex flex-wrap items-center gap-1.5">
+          {editing ? (
+            <Button
+              variant="primary"
+              size="sm"
+              className={BUTTON_ACTIVE_CLASS}
+              isDisabled={draft.trim().length === 0}
+              onPress={() => onReview({ action: "edit", content: draft.trim() })}
+            >
+              <Check className="size-4" />
+              Save & confirm
+            </Button>
+          ) : (
+            <Button
+              variant="primary"
+              size="sm"
+              className={BUTTON_ACTIVE_CLASS}
+              onPress={() => onReview({ action: "confirm" })}
+            >
+              <Check className="size-4" />
+              Confirm
+            </Button>
+          )}
+          <Button
+            variant="secondary"
+            size="sm"
+            className={BUTTON_BASE_CLASS}
+            onPress={() => {
+              setDraft(entry.content);
+              setEditing((current) => !current);
+            }}
+          >
+            <Pencil className="size-4" />
+            {editing ? "Cancel" : "Edit"}
+          </Button>
+          <Button
+            variant="danger"
+            size="sm"
+            className={BUTTON_DANGER_CLASS}
+            onPress={() => onReview({ action: "reject" })}
+          >
+            <X className="size-4" />
+            Reject
+          </Button>
+        </div>
+      </Card.Content>
+    </Card>
+  );
+}
+
+const CONFLICT_POLICIES: { id: MemoryConflictPolicy; label: string }[] = [
+  { id: "skip", label: "Skip existing" },
+  { id: "keep_newest", label: "Keep newest" },
+  { id: "overwrite", label: "Overwrite" },
+];
+
+interface TransferCardProps {
+  workspaceId: string | null;
+  category: string | null;
+  onImported: () => void;
+}
+
+function TransferCard({ workspaceId, category, onImported }: TransferCardProps) {
+  const [mode, setMode] = useState<MemoryArchiveKey["mode"]>("passphrase");
+  const [secret, setSecret] = useState("");
+  const [includeConfidential, setIncludeConfidential] = useState(false);
+  const [policy, setPolicy] = useState<MemoryConflictPolicy>("skip");
+  const [importPassphrase, setImportPassphrase] = useState("");
+  const [deviceKey, setDeviceKey] = useState<string | null>(null);
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    tauri
+      .getMemoryArchiveKeys()
+      .then((keys) => setDeviceKey(keys.recipient_public_key))
+      .catch(() => setDeviceKey(null));
+  }, []);
+
+  const handleExport = async () => {
+    if (!workspaceId) return;
+    const key: MemoryArchiveKey =
+      mode === "passphrase"
+        ? { mode, passphrase: secret }
+        : { mode, publicKey: secret.trim() };
+    const path = await save({
+      defaultPath: `${workspaceId}-memory.json`,
+      filters: [{ name: "Memory archive", extensions: ["json"] }],
+    });
+    if (!path) return;
+    setBusy(true);
+    try {
+      const summary = await tauri.exportWorkspaceMemory(workspaceId, path, key, {
+        categories: category ? [category] : [],
+        sensitivities: includeConfidential ? [] : ["public", "internal"],
+      });
+      toast.success(`Exported ${summary.entry_count} entries`);
+    } catch (error) {
+      toast.error(`Export failed: ${error}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleImport = async () => {
+    const path = await open({
+      multiple: false,
+      filters: [{ name: "Memory archive", extensions: ["json"] }],
+    });
+    if (!path || typeof path !== "string") return;
+    setBusy(true);
+    try {
+      const report = await tauri.importWorkspaceMemory({
+        path,
+        passphrase: importPassphrase || undefined,
+        targetWorkspaceId: workspaceId ?? undefined,
+        conflictPolicy: policy,
+      });
+      toast.success(
+        `Imported ${report.imported}, overwrote ${report.overwritten}, skipped ${report.skipped}` +
+          (report.reembedding > 0 ? `; re-embedding ${report.reembedding}` : ""),
+      );
+      onImported();
+    } catch (error) {
+      toast.error(`Import failed: ${error}`);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  return (
+    <Card className={`${PANEL_CLASS} rounded-[24px] px-3 py-3`}>
+      <Card.Header className="flex flex-col gap-1 border-b border-border/40 p-0 pb-3">
+        <Card.Title className="text-sm font-semibold text-foreground">
+          Export & Import
+        </Card.Title>
+        <Card.Description className="text-[13px] text-muted-foreground/80">
+          Signed archives re-encrypted under a passphrase or another device's key.
+        </Card.Description>
+      </Card.Header>
+      <Card.Content className="grid gap-4 p-0 pt-3 md:grid-cols-2">
+        <div className="space-y-2.5">
+          <div className="flex flex-wrap items-center gap-1.5">
+            <Button
+              size="sm"
+              variant={mode === "passphrase" ? "primary" : "tertiary"}
+              className={mode === "passphrase" ? BUTTON_ACTIVE_CLASS : BUTTON_BASE_CLASS}
+              onPress={() => setMode("passphrase")}
+            >
+              Passphrase
+            </Button>
+            <Button
+              size="sm"
+              variant={mode === "recipient" ? "primary" : "tertiary"}
+              className={mode === "recipient" ? BUTTON_ACTIVE_CLASS : BUTTON_BASE_CLASS}
+              onPress={() => setMode("recipient")}
+            >
+              Recipient key
+            </Button>
+            <Button
+              size="sm"
+              variant={includeConfidential ? "primary" : "tertiary"}
+              className={includeConfidential ? BUTTON_ACTIVE_CLASS : BUTTON_BASE_CLASS}
+              onPress={() => setIncludeConfidential((current) => !current)}
+            >
+              <Shield className="size-4" />
+              {includeConfidential ? "Confidential included" : "Confidential excluded"}
+            </Button>
+          </div>
+          <Input
+            className="h-9 w-full rounded-[16px] border border-border/50 bg-background/75 text-sm dark:bg-background/18"
+            type={mode === "passphrase" ? "password" : "text"}
+            placeholder={mode === "passphrase" ? "Archive passphrase" : "Recipient public key"}
+            value={secret}
+            onChange={(event) => setSecret(event.target.value)}
+          />
+          <Button
+            size="sm"
+            variant="secondary"
+            className={BUTTON_BASE_CLASS}
+            isDisabled={!workspaceId || !secret.trim() || busy}
+            onPress={handleExport}
+          >
+            <Download className="size-4" />
+            {workspaceId ? `Export ${workspaceId}` : "Select a workspace to export"}
+          </Button>
+        </div>
+
+        <div className="space-y-2.5">
+          <div className="flex flex-wrap items-center gap-1.5">
+            {CONFLICT_POLICIES.map((option) => (
+              <Button
+                key={option.id}
+                size="sm"
+                variant={policy === option.id ? "primary" : "tertiary"}
+                className={policy === option.id ? BUTTON_ACTIVE_CLASS : BUTTON_BASE_CLASS}
+                onPress={() => setPolicy(option.id)}
+              >
+                {option.label}
+              </Button>
+            ))}
+          </div>
+          <Input
+            className="h-9 w-full rounded-[16px] border border-border/50 bg-background/75 text-sm dark:bg-background/18"
+            type="password"
+            placeholder="Passphrase (passphrase archives only)"
+            value={importPassphrase}
+            onChange={(event) => setImportPassphrase(event.target.value)}
+          />
+          <Button
+            size="sm"
+            variant="secondary"
+            className={BUTTON_BASE_CLASS}
+            isDisabled={busy}
+            onPress={handleImport}
+          >
+            <Upload className="size-4" />
+            {workspaceId ? `Import into ${workspaceId}` : "Import archive"}
+          </Button>
+          {deviceKey ? (
+            <p className="break-all text-xs text-muted-foreground/80">
+              This device's recipient key: {deviceKey}
+            </p>
+          ) : null}
+        </div>
+      </Card.Content>
+    </Card>
+  );
+}
+
 export function MemoryExplorerPanel() {
   const [entries, setEntries] = useState<DecryptedMemoryEntry[]>([]);
   const [totalCount, setTotalCount] = useState(0);
@@ -327,6 +649,9 @@ export function MemoryExplorerPanel() {
   const [showStats, setShowStats] = useState(false);
   const [selected, setSelected] = useState<Set<string>>(new Set());
   const [expandedId, setExpandedId] = useState<string | null>(null);
+  const [pending, setPending] = useState<DecryptedMemoryEntry[]>([]);
+  const [showReview, setShowReview] = useState(false);
+  const [showTransfer, setShowTransfer] = useState(false);
 
   const fetchEntries = useCallback(async () => {
     setLoading(true);
@@ -370,10 +695,25 @@ export function MemoryExplorerPanel() {
     }
   }, [selectedWorkspace]);
 
+  const fetchPending = useCallback(async () => {
+    try {
+      const result = await tauri.listPendingMemories(
+        selectedWorkspace === "__all__" ? undefined : selectedWorkspace,
+      );
+      setPending(result);
+    } catch {
+      // ignore review queue fetch failures
+    }
+  }, [selectedWorkspace]);
+
   useEffect(() => {
     fetchWorkspaces();
   }, [fetchWorkspaces]);
 
+  useEffect(() => {
+    fetchPending();
+  }, [fetchPending]);
+
   useEffect(() => {
     fetchEntries();
   }, [fetchEntries]);
@@ -406,8 +746,25 @@ export function MemoryExplorerPanel() {
   const handleRefresh = useCallback(() => {
     fetchEntries();
     fetchWorkspaces();
+    fetchPending();
     if (showStats) fetchStats();
-  }, [fetchEntries, fetchStats, fetchWorkspaces, showStats]);
+  }, [fetchEntries, fetchPending, fetchStats, fetchWorkspaces, showStats]);
+
+  const handleReview = useCallback(
+    async (id: string, decision: MemoryReviewDecision) => {
+      try {
+        await tauri.reviewPendingMemory(id, decision);
+        toast.success(
+          decision.action === "reject" ? "Memory rejected" : "Memory confirmed",
+        );
+        fetchPending();
+        fetchEntries();
+      } catch (error) {
+        toast.error(`Review failed: ${error}`);
+      }
+    },
+    [fetchEntries, fetchPending],
+  );
 
   const handleDeleteSelected = useCallback(async () => {
     if (selected.size === 0) return;
@@ -517,6 +874,24 @@ export function MemoryExplorerPanel() {
                 <Sparkles className="size-4" />
                 Stats
               </Button>
+              <Button
+                variant={showReview ? "primary" : "tertiary"}
+                size="sm"
+                className={showReview ? BUTTON_ACTIVE_CLASS : BUTTON_BASE_CLASS}
+                onPress={() => setShowReview((current) => !current)}
+              >
+                <Check className="size-4" />
+                Review ({pending.length})
+              </Button>
+              <Button
+                variant={showTransfer ? "primary" : "tertiary"}
+                size="sm"
+                className={showTransfer ? BUTTON_ACTIVE_CLASS : BUTTON_BASE_CLASS}
+                onPress={() => setShowTransfer((current) => !current)}
+              >
+                <Upload className="size-4" />
+                Transfer
+              </Button>
               <Button
                 variant="secondary"
                 size="sm"
@@ -572,6 +947,42 @@ export function MemoryExplorerPanel() {
           </div>
         ) : null}
 
+        {showTransfer ? (
+          <TransferCard
+            workspaceId={selectedWorkspace === "__all__" ? null : selectedWorkspace}
+            category={selectedCategory === "__all__" ? null : selectedCategory}
+            onImported={handleRefresh}
+          />
+        ) : null}
+
+        {showReview ? (
+          <Card className={`${PANEL_CLASS} rounded-[24px] px-3 py-3`}>
+            <Card.Header className="flex flex-col gap-1 border-b border-border/40 p-0 pb-3">
+              <Card.Title className="text-sm font-semibold text-foreground">
+                Pending Review
+              </Card.Title>
+              <Card.Description className="text-[13px] text-muted-foreground/80">
+                Low-confidence memories stay out of retrieval until confirmed. Confirming applies any replacement they propose.
+              </Card.Description>
+            </Card.Header>
+            <Card.Content className="max-h-[320px] space-y-2.5 overflow-y-auto p-0 pt-3">
+              {pending.length === 0 ? (
+                <p className="px-1 text-sm text-muted-foreground/80">
+                  Nothing is waiting for review.
+                </p>
+              ) : (
+                pending.map((entry) => (
+                  <PendingCard
+                    key={entry.id}
+                    entry={entry}
+                    onReview={(decision) => handleReview(entry.id, decision)}
+                  />
+                ))
+              )}
+            </Card.Content>
+          </Card>
+        ) : null}
+
         <Card className={`${PANEL_CLASS} rounded-[24px] px-3 py-3`}>
           <Card.Content className="grid gap-2.5 p-0 md:grid-cols-2 xl:grid-cols-[1.1fr_1fr_1fr_1.15fr_0.9fr]">
             <Select
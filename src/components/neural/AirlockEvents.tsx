@@ -1,5 +1,5 @@
 import { Modal, Button } from "@heroui/react";
-import { ShieldCheck, ShieldAlert, Terminal } from "lucide-react";
+import { FileDiff, ShieldCheck, ShieldAlert, Terminal } from "lucide-react";
 
 import { useAirlock } from "../../hooks";
 import { AirlockLevel } from "../../types";
@@ -96,6 +96,37 @@ export function AirlockEvents() {
               </div>
             </div>
 
+            {request.diffPreview && (
+              <div className="overflow-hidden rounded-2xl border border-border/60 bg-background/45 dark:bg-background/20">
+                <div className="flex items-center gap-2 border-b border-border/50 bg-foreground/[0.03] px-4 py-2 dark:bg-white/[0.03]">
+                  <FileDiff className="size-3 text-muted-foreground" />
+                  <span className="text-[10px] font-medium uppercase tracking-wider text-muted-foreground">
+                    Diff Preview
+                  </span>
+                </div>
+                <div className="max-h-64 overflow-y-auto scrollbar-thin scrollbar-thumb-white/10 scrollbar-track-transparent">
+                  <pre className="p-4 font-mono text-[11px] leading-relaxed whitespace-pre [font-variant-ligatures:none]">
+                    {request.diffPreview.split("\n").map((line, index) => (
+                      <div
+                        key={index}
+                        className={
+                          line.startsWith("+") && !line.startsWith("+++")
+                            ? "text-emerald-600 dark:text-emerald-300"
+                            : line.startsWith("-") && !line.startsWith("---")
+                              ? "text-red-600 dark:text-red-300"
+                              : line.startsWith("@@")
+                                ? "text-blue-500 dark:text-blue-300"
+                                : "text-foreground/70"
+                        }
+                      >
+                        {line || " "}
+                      </div>
+                    ))}
+                  </pre>
+                </div>
+              </div>
+            )}
+
             {isDangerous && (
               <div className="flex items-start gap-3 rounded-xl border border-red-500/20 bg-red-500/5 p-3">
                 <ShieldAlert className="mt-0.5 size-4 shrink-0 text-red-500" />
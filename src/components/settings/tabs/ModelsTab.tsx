@@ -2,7 +2,41 @@ import { useState, useEffect } from "react";
 import { Zap, Bot, Database } from "lucide-react";
 import * as tauri from "../../../services/tauri";
 import { useAIProvider } from "../../../hooks";
-import { Select, ListBox, Card, Skeleton } from "@heroui/react";
+import { Select, ListBox, Card, Skeleton, Input } from "@heroui/react";
+
+const EMBEDDER_PROVIDERS = [
+  { id: "gemini", label: "Gemini" },
+  { id: "openai", label: "OpenAI-compatible" },
+  { id: "ollama", label: "Ollama" },
+  { id: "local", label: "Local (CPU, offline)" },
+];
+
+const EMBEDDER_MODELS: Record<string, { id: string; label: string }[]> = {
+  gemini: [
+    { id: "gemini-embedding-2-preview", label: "gemini-embedding-2-preview (3072d)" },
+    { id: "gemini-embedding-001", label: "gemini-embedding-001 (3072d)" },
+  ],
+  openai: [
+    { id: "text-embedding-3-small", label: "text-embedding-3-small (1536d)" },
+    { id: "text-embedding-3-large", label: "text-embedding-3-large (3072d)" },
+  ],
+  ollama: [
+    { id: "nomic-embed-text", label: "nomic-embed-text (768d)" },
+    { id: "mxbai-embed-large", label: "mxbai-embed-large (1024d)" },
+    { id: "bge-m3", label: "bge-m3 (1024d)" },
+    { id: "all-minilm", label: "all-minilm (384d)" },
+  ],
+  local: [
+    { id: "all-MiniLM-L6-v2", label: "all-MiniLM-L6-v2 (384d)" },
+    { id: "all-MiniLM-L12-v2", label: "all-MiniLM-L12-v2 (384d)" },
+    { id: "bge-small-en-v1.5", label: "bge-small-en-v1.5 (384d)" },
+  ],
+};
+
+const EMBEDDER_URL_PLACEHOLDERS: Record<string, string> = {
+  openai: "https://api.openai.com/v1",
+  ollama: "http://localhost:11434",
+};
 
 const ModelCard = ({
   name,
@@ -37,29 +71,27 @@ export function ModelsTab() {
   const [geminiModels, setGeminiModels] = useState<string[]>([]);
   const [embedderProvider, setEmbedderProvider] = useState<string>("gemini");
   const [embedderModel, setEmbedderModel] = useState<string>(
-    "gemini-embedding-001",
+    "gemini-embedding-2-preview",
   );
+  const [embedderBaseUrl, setEmbedderBaseUrl] = useState<string>("");
   const { hasApiKey } = useAIProvider();
 
   useEffect(() => {
     async function loadData() {
       try {
-        const [rainyModels, geminiModelsList, eProvider, eModel] =
+        const [rainyModels, geminiModelsList, eProvider, eModel, eBaseUrl] =
           await Promise.all([
             tauri.getProviderModels("rainy_api").catch(() => []),
             tauri.getProviderModels("gemini").catch(() => []),
             tauri.getEmbedderProvider().catch(() => "gemini"),
-            tauri.getEmbedderModel().catch(() => "gemini-embedding-001"),
+            tauri.getEmbedderModel().catch(() => "gemini-embedding-2-preview"),
+            tauri.getEmbedderBaseUrl().catch(() => null),
           ]);
         setRainyApiModels(rainyModels || []);
         setGeminiModels(geminiModelsList || []);
-        setEmbedderProvider("gemini");
-        setEmbedderModel("gemini-embedding-001");
-
-        if (eProvider !== "gemini")
-          tauri.setEmbedderProvider("gemini").catch(console.error);
-        if (eModel !== "gemini-embedding-001")
-          tauri.setEmbedderModel("gemini-embedding-001").catch(console.error);
+        setEmbedderProvider(eProvider);
+        setEmbedderModel(eModel);
+        setEmbedderBaseUrl(eBaseUrl ?? "");
       } catch (error) {
         console.error("Failed to load settings:", error);
       } finally {
@@ -73,7 +105,10 @@ export function ModelsTab() {
     if (!provider) return;
     setEmbedderProvider(provider);
     try {
+      // The backend resets the model and server URL to the provider's defaults
       await tauri.setEmbedderProvider(provider);
+      setEmbedderModel(await tauri.getEmbedderModel());
+      setEmbedderBaseUrl("");
     } catch (error) {
       console.error("Failed to save embedder provider:", error);
     }
@@ -89,6 +124,19 @@ export function ModelsTab() {
     }
   };
 
+  const handleEmbedderBaseUrlCommit = async () => {
+    try {
+      await tauri.setEmbedderBaseUrl(embedderBaseUrl.trim() || null);
+    } catch (error) {
+      console.error("Failed to save embedder URL:", error);
+    }
+  };
+
+  const embedderModelOptions = EMBEDDER_MODELS[embedderProvider] ?? [];
+  const modelOptions = embedderModelOptions.some((m) => m.id === embedderModel)
+    ? embedderModelOptions
+    : [...embedderModelOptions, { id: embedderModel, label: embedderModel }];
+
   if (isLoading) {
     return (
       <div className="space-y-6">
@@ -132,7 +180,6 @@ export function ModelsTab() {
                   if (!value) return;
                   handleEmbedderProviderChange(value);
                 }}
-                isDisabled
               >
                 <Select.Trigger className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl hover:bg-success/10 text-foreground">
                   <Select.Value />
@@ -140,8 +187,9 @@ export function ModelsTab() {
                 </Select.Trigger>
                 <Select.Popover className="bg-background/95 dark:bg-background/35 border border-default-200/70 dark:border-white/15 backdrop-blur-xl">
                   <ListBox className="bg-transparent">
-                    <ListBox.Item id="gemini" textValue="Gemini (Active)">Gemini (Active)</ListBox.Item>
-                    <ListBox.Item id="openai" textValue="OpenAI (Coming Soon)">OpenAI (Coming Soon)</ListBox.Item>
+                    {EMBEDDER_PROVIDERS.map((p) => (
+                      <ListBox.Item key={p.id} id={p.id} textValue={p.label}>{p.label}</ListBox.Item>
+                    ))}
                   </ListBox>
                 </Select.Popover>
               </Select>
@@ -158,7 +206,6 @@ export function ModelsTab() {
                   if (!value) return;
                   handleEmbedderModelChange(value);
                 }}
-                isDisabled
               >
                 <Select.Trigger className="h-10 px-4 bg-success/5 border border-success/10 rounded-xl hover:bg-success/10 text-foreground">
                   <Select.Value />
@@ -166,11 +213,26 @@ export function ModelsTab() {
                 </Select.Trigger>
                 <Select.Popover className="bg-background/95 dark:bg-background/35 border border-default-200/70 dark:border-white/15 backdrop-blur-xl">
                   <ListBox className="bg-transparent">
-                    <ListBox.Item id="gemini-embedding-001" textValue="gemini-embedding-001 (3072d)">gemini-embedding-001 (3072d)</ListBox.Item>
+                    {modelOptions.map((m) => (
+                      <ListBox.Item key={m.id} id={m.id} textValue={m.label}>{m.label}</ListBox.Item>
+                    ))}
                   </ListBox>
                 </Select.Popover>
               </Select>
             </div>
+
+            {EMBEDDER_URL_PLACEHOLDERS[embedderProvider] && (
+              <div className="space-y-1">
+                <label className="text-xs font-medium text-muted-foreground ml-1">Server URL</label>
+                <Input
+                  className="h-10 w-full bg-success/5 border border-success/10 rounded-xl"
+                  placeholder={EMBEDDER_URL_PLACEHOLDERS[embedderProvider]}
+                  value={embedderBaseUrl}
+                  onChange={(e) => setEmbedderBaseUrl(e.target.value)}
+                  onBlur={handleEmbedderBaseUrlCommit}
+                />
+              </div>
+            )}
           </div>
         </section>
 
@@ -28,6 +28,7 @@ const TOOL_POLICY_MAP: Record<string, ToolPolicy> = {
     airlockLevel: AirlockLevels.Safe,
   },
   search_files: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
+  search_code: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
   file_exists: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
   get_file_info: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
   read_file_chunk: { skill: "filesystem", airlockLevel: AirlockLevels.Safe },
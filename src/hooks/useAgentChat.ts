@@ -60,6 +60,7 @@ type RuntimeAgentEventData = {
   arguments?: string;
   index?: number;
   model?: string;
+  provider_id?: string;
   prompt_tokens?: number;
   completion_tokens?: number;
   total_tokens?: number;
@@ -106,7 +107,12 @@ type RuntimeAgentEvent =
         | "rag_telemetry"
         | "context_compaction"
         | "tool_call"
-        | "tool_result";
+        | "tool_result"
+        | "process_output"
+        | "process_exited"
+        | "budget_warning"
+        | "provider_served"
+        | "budget_exceeded";
       data?: RuntimeAgentEventData;
     }
   | {
@@ -348,6 +354,7 @@ function applyRagTelemetryUpdate(
         defaults.workspaceMemoryEnabled,
       workspaceMemoryRoot: message.ragTelemetry?.workspaceMemoryRoot,
       lastModel: message.ragTelemetry?.lastModel,
+      lastProvider: message.ragTelemetry?.lastProvider,
       promptTokens:
         message.ragTelemetry?.promptTokens ?? defaults.promptTokens,
       completionTokens:
@@ -672,6 +679,16 @@ export function useAgentChat(
             ],
           };
         }
+        case "provider_served": {
+          return {
+            ...message,
+            ragTelemetry: {
+              ...message.ragTelemetry,
+              lastProvider:
+                payload.data?.provider_id || message.ragTelemetry?.lastProvider,
+            },
+          };
+        }
         case "usage": {
           return {
             ...message,
@@ -851,6 +868,19 @@ export function useAgentChat(
             activeToolName:
               payload.type === "stream_chunk" ? message.activeToolName : undefined,
           };
+        case "budget_warning":
+        case "budget_exceeded": {
+          const spent = Number(payload.data?.spentUsd ?? 0).toFixed(2);
+          const limit = Number(payload.data?.limitUsd ?? 0).toFixed(2);
+          const label =
+            payload.type === "budget_exceeded" ? "Budget exceeded" : "Budget warning";
+          return {
+            ...message,
+            statusText: `${label}: $${spent} of $${limit} ${String(
+              payload.data?.period ?? "",
+            )} ${String(payload.data?.scope ?? "")} budget`,
+          };
+        }
         case "status": {
           const statusText = String(payload.data || "");
           const lower = statusText.toLowerCase();
@@ -1449,6 +1479,7 @@ export function useAgentChat(
                 workspaceMemoryRoot:
                   runtimeTelemetry.workspace_memory_root ?? undefined,
                 lastModel: runtimeTelemetry.last_model ?? undefined,
+                lastProvider: runtimeTelemetry.last_provider ?? undefined,
                 promptTokens:
                   runtimeTelemetry.prompt_tokens ?? defaultRagTelemetry.promptTokens,
                 completionTokens:
@@ -2275,6 +2306,16 @@ export function useAgentChat(
                 ],
               };
             }
+            case "provider_served": {
+              return {
+                ...message,
+                ragTelemetry: {
+                  ...message.ragTelemetry,
+                  lastProvider:
+                    payload.data?.provider_id || message.ragTelemetry?.lastProvider,
+                },
+              };
+            }
             case "usage": {
               return {
                 ...message,
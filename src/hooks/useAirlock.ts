@@ -4,7 +4,7 @@ import {
   getPendingAirlockApprovals,
   respondToAirlock,
 } from "../services/tauri";
-import type { ApprovalRequest } from "../services/tauri";
+import type { ApprovalRequest, ApprovalScope } from "../services/tauri";
 
 export function useAirlock() {
   const [pendingRequests, setPendingRequests] = useState<ApprovalRequest[]>([]);
@@ -77,10 +77,13 @@ export function useAirlock() {
     };
   }, []);
 
-  const respond = useCallback(async (commandId: string, approved: boolean) => {
-    await respondToAirlock(commandId, approved);
-    setPendingRequests((prev) => prev.filter((r) => r.commandId !== commandId));
-  }, []);
+  const respond = useCallback(
+    async (commandId: string, approved: boolean, scope?: ApprovalScope) => {
+      await respondToAirlock(commandId, approved, scope);
+      setPendingRequests((prev) => prev.filter((r) => r.commandId !== commandId));
+    },
+    [],
+  );
 
   return {
     pendingRequests,
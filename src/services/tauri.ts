@@ -454,6 +454,9 @@ export interface UserSettings {
   profile: UserProfile;
   embedderProvider: string;
   embedderModel: string;
+  embedderBaseUrl?: string | null;
+  vaultKeyProvider?: VaultKeyProviderKind;
+  vaultKeyFile?: string | null;
 }
 
 export interface UserProfile {
@@ -591,6 +594,57 @@ export async function setEmbedderModel(model: string): Promise<void> {
   return invoke<void>("set_embedder_model", { model });
 }
 
+export async function getEmbedderBaseUrl(): Promise<string | null> {
+  return invoke<string | null>("get_embedder_base_url");
+}
+
+export async function setEmbedderBaseUrl(
+  baseUrl: string | null,
+): Promise<void> {
+  return invoke<void>("set_embedder_base_url", { baseUrl });
+}
+
+export type VaultKeyProviderKind = "keychain" | "passphrase" | "file" | "env";
+
+export interface VaultKeyProviderStatus {
+  provider: VaultKeyProviderKind;
+  keyFile: string | null;
+  supported: VaultKeyProviderKind[];
+}
+
+export interface MasterKeyRotationReport {
+  vaultEntries: number;
+  beamWallets: number;
+}
+
+export async function getVaultKeyProvider(): Promise<VaultKeyProviderStatus> {
+  return invoke<VaultKeyProviderStatus>("get_vault_key_provider");
+}
+
+export async function setVaultKeyProvider(
+  provider: VaultKeyProviderKind,
+  keyFile?: string | null,
+  passphrase?: string | null,
+): Promise<void> {
+  return invoke<void>("set_vault_key_provider", {
+    provider,
+    keyFile: keyFile ?? null,
+    passphrase: passphrase ?? null,
+  });
+}
+
+export async function unlockVault(passphrase: string): Promise<void> {
+  return invoke<void>("unlock_vault", { passphrase });
+}
+
+export async function lockVault(): Promise<void> {
+  return invoke<void>("lock_vault");
+}
+
+export async function rotateMasterKey(): Promise<MasterKeyRotationReport> {
+  return invoke<MasterKeyRotationReport>("rotate_master_key");
+}
+
 // ============ Workspace Types ============
 
 export interface AdvancedWorkspace {
@@ -807,6 +861,17 @@ export async function deleteWorkspace(id: string): Promise<void> {
   return invoke<void>("delete_workspace", { id });
 }
 
+export interface CodeIndexStats {
+  filesIndexed: number;
+  filesUnchanged: number;
+  filesRemoved: number;
+  chunks: number;
+}
+
+export async function indexWorkspaceCode(id: string): Promise<CodeIndexStats> {
+  return invoke<CodeIndexStats>("index_workspace_code", { id });
+}
+
 export async function getWorkspaceTemplates(): Promise<
   Array<{
     id: string;
@@ -1331,6 +1396,23 @@ export async function getFileTransaction(
   return invoke<Transaction | null>("get_file_transaction", { transactionId });
 }
 
+// ============ Agent Run Checkpoint Commands ============
+
+export async function listRunChanges(runId: string): Promise<FileOpChange[]> {
+  return invoke<FileOpChange[]>("list_run_changes", { runId });
+}
+
+export async function rollbackRun(runId: string): Promise<FileOpChange[]> {
+  return invoke<FileOpChange[]>("rollback_run", { runId });
+}
+
+export async function rollbackRunFile(
+  runId: string,
+  filePath: string,
+): Promise<FileOpChange[]> {
+  return invoke<FileOpChange[]>("rollback_run_file", { runId, filePath });
+}
+
 // ============ Enhanced Undo/Redo Commands ============
 
 export async function undoFileOperationEnhanced(
@@ -1533,6 +1615,7 @@ export interface RouterConfigDto {
   cost_optimization_enabled: boolean;
   capability_matching_enabled: boolean;
   max_retries: number;
+  hedging_enabled: boolean;
 }
 
 export interface RouterStatsDto {
@@ -1577,6 +1660,7 @@ export async function updateRouterConfig(
     cost_optimization_enabled: boolean;
     capability_matching_enabled: boolean;
     max_retries: number;
+    hedging_enabled: boolean;
   }>,
 ): Promise<RouterConfigDto> {
   return invoke<RouterConfigDto>("update_router_config", config);
@@ -1629,6 +1713,129 @@ export async function routerHasProviders(): Promise<boolean> {
   return invoke<boolean>("router_has_providers");
 }
 
+// ============ Usage Ledger & Budgets ============
+
+export interface UsageLedgerEntry {
+  id: number;
+  recordedAt: number;
+  providerId: string;
+  providerType: string;
+  model: string;
+  workspaceId?: string;
+  agentSpecId?: string;
+  runId?: string;
+  promptTokens: number;
+  completionTokens: number;
+  cachedPromptTokens: number;
+  reasoningTokens: number;
+  totalTokens: number;
+  costUsd: number;
+  priced: boolean;
+  estimated: boolean;
+}
+
+export interface UsageLedgerQuery {
+  workspaceId?: string;
+  agentSpecId?: string;
+  runId?: string;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+export interface UsageBreakdown {
+  providerId: string;
+  model: string;
+  requests: number;
+  promptTokens: number;
+  completionTokens: number;
+  cachedPromptTokens: number;
+  reasoningTokens: number;
+  costUsd: number;
+  unpricedRequests: number;
+}
+
+export interface UsageSummary {
+  requests: number;
+  promptTokens: number;
+  completionTokens: number;
+  cachedPromptTokens: number;
+  reasoningTokens: number;
+  costUsd: number;
+  unpricedRequests: number;
+  byModel: UsageBreakdown[];
+}
+
+export type TokenizerFamily = "open_ai" | "xai" | "anthropic" | "gemini" | "generic";
+
+export interface TokenizerCalibration {
+  family: TokenizerFamily;
+  samples: number;
+  ratio: number;
+  estimatedTotal: number;
+  actualTotal: number;
+  lastEstimated: number;
+  lastActual: number;
+}
+
+export type BudgetScope = "global" | "workspace" | "agent";
+export type BudgetPeriod = "daily" | "monthly";
+export type BudgetMode = "soft_warn" | "hard_stop";
+
+export interface UsageBudget {
+  id: string;
+  scope: BudgetScope;
+  scopeId?: string;
+  period: BudgetPeriod;
+  limitUsd: number;
+  mode: BudgetMode;
+  warnRatio: number;
+  enabled: boolean;
+  createdAt: number;
+  updatedAt: number;
+}
+
+export interface UsageBudgetInput {
+  id?: string;
+  scope: BudgetScope;
+  scopeId?: string;
+  period: BudgetPeriod;
+  limitUsd: number;
+  mode: BudgetMode;
+  warnRatio?: number;
+  enabled?: boolean;
+}
+
+export async function queryUsageLedger(
+  query?: UsageLedgerQuery,
+): Promise<UsageLedgerEntry[]> {
+  return invoke<UsageLedgerEntry[]>("query_usage_ledger", { query });
+}
+
+export async function summarizeUsage(
+  query?: UsageLedgerQuery,
+): Promise<UsageSummary> {
+  return invoke<UsageSummary>("summarize_usage", { query });
+}
+
+export async function getTokenCalibration(): Promise<TokenizerCalibration[]> {
+  return invoke<TokenizerCalibration[]>("get_token_calibration");
+}
+
+export async function listUsageBudgets(): Promise<UsageBudget[]> {
+  return invoke<UsageBudget[]>("list_usage_budgets");
+}
+
+export async function upsertUsageBudget(
+  budget: UsageBudgetInput,
+): Promise<UsageBudget> {
+  return invoke<UsageBudget>("upsert_usage_budget", { budget });
+}
+
+export async function deleteUsageBudget(id: string): Promise<boolean> {
+  return invoke<boolean>("delete_usage_budget", { id });
+}
+
 // ============ Neural System Types ============
 
 /**
@@ -1654,6 +1861,8 @@ export interface ApprovalRequest {
   timeoutSecs?: number | null;
   expiresAt?: number | null;
   timestamp: number;
+  diffPreview?: string | null;
+  runId?: string | null;
 }
 
 export interface AirlockMessage {
@@ -1671,6 +1880,103 @@ export interface AirlockMessage {
   acknowledgedAt?: number | null;
 }
 
+export type ApprovalScope =
+  | { kind: "once" }
+  | { kind: "run" }
+  | { kind: "session" }
+  | {
+      kind: "pattern";
+      /** Absolute, or relative to the workspace root. */
+      pathPrefix?: string | null;
+      host?: string | null;
+      /** Required within 24 hours for Dangerous approvals. */
+      expiresAt?: number | null;
+    };
+
+export interface AirlockGrant {
+  id: string;
+  toolName: string;
+  scope: "run" | "session" | "pattern";
+  workspaceId?: string | null;
+  runId?: string | null;
+  chatId?: string | null;
+  pathPrefix?: string | null;
+  host?: string | null;
+  shellCommand?: string | null;
+  maxLevel: AirlockLevel;
+  sourceCommandId: string;
+  createdAt: number;
+  expiresAt?: number | null;
+  revokedAt?: number | null;
+  useCount: number;
+  lastUsedAt?: number | null;
+}
+
+export interface AuditLedgerEntry {
+  seq: number;
+  recordedAt: number;
+  commandId: string;
+  runId?: string;
+  workspaceId?: string;
+  toolName?: string;
+  skill?: string;
+  /** JSON preview with secrets masked and long strings truncated. */
+  arguments: string;
+  argumentsSha256?: string;
+  airlockLevel?: AirlockLevel;
+  decision: string;
+  success?: boolean;
+  resultDigest?: string;
+  touchedPaths: string;
+  prevHash: string;
+  entryHash: string;
+}
+
+export interface AuditLedgerQuery {
+  runId?: string;
+  workspaceId?: string;
+  since?: number;
+  until?: number;
+  limit?: number;
+}
+
+export interface AuditChainVerification {
+  valid: boolean;
+  entriesChecked: number;
+  firstInvalidSeq?: number;
+  reason?: string;
+}
+
+export interface AirlockPolicyOrigin {
+  layer: string;
+  source?: string | null;
+  ruleId?: string | null;
+  ruleIndex?: number | null;
+}
+
+export interface AirlockPolicyDecision {
+  effect: "allow" | "deny";
+  skill?: string | null;
+  airlockLevel?: AirlockLevel | null;
+  decidedBy: AirlockPolicyOrigin;
+  reason: string;
+}
+
+export interface AirlockPolicyRuleTrace {
+  layer: string;
+  ruleId?: string | null;
+  ruleIndex: number;
+  matched: boolean;
+  detail: string;
+}
+
+export interface AirlockPolicyExplanation {
+  tool: string;
+  decision: AirlockPolicyDecision;
+  layers: string[];
+  trace: AirlockPolicyRuleTrace[];
+}
+
 export interface ParameterSchema {
   type: string;
   required?: boolean;
@@ -1725,14 +2031,43 @@ export async function sendHeartbeat(
 export async function respondToAirlock(
   commandId: string,
   approved: boolean,
+  scope?: ApprovalScope | null,
 ): Promise<void> {
-  const payload = { commandId, approved } satisfies {
+  const payload = { commandId, approved, scope: scope ?? null } satisfies {
     commandId: string;
     approved: boolean;
+    scope: ApprovalScope | null;
   };
   return invoke("respond_to_airlock", payload);
 }
 
+export async function listAirlockGrants(
+  includeInactive?: boolean,
+): Promise<AirlockGrant[]> {
+  return invoke("list_airlock_grants", {
+    includeInactive: includeInactive ?? null,
+  });
+}
+
+export async function revokeAirlockGrant(grantId: string): Promise<void> {
+  return invoke("revoke_airlock_grant", { grantId });
+}
+
+export async function queryAuditLedger(query?: AuditLedgerQuery): Promise<AuditLedgerEntry[]> {
+  return invoke("query_audit_ledger", { query });
+}
+
+export async function verifyAuditLedgerChain(): Promise<AuditChainVerification> {
+  return invoke("verify_audit_ledger_chain");
+}
+
+export async function exportAuditLedger(
+  query?: AuditLedgerQuery,
+  format: "json" | "ndjson" = "json",
+): Promise<string> {
+  return invoke("export_audit_ledger", { query, format });
+}
+
 export async function getPendingAirlockApprovals(): Promise<ApprovalRequest[]> {
   return invoke("get_pending_airlock_approvals");
 }
@@ -1757,6 +2092,20 @@ export async function sendAirlockMessage(
   });
 }
 
+export async function explainAirlockPolicy(
+  tool: string,
+  args?: Record<string, unknown> | null,
+  workspaceId?: string | null,
+  agentSpecId?: string | null,
+): Promise<AirlockPolicyExplanation> {
+  return invoke("explain_airlock_policy", {
+    tool,
+    args: args ?? null,
+    workspaceId: workspaceId ?? null,
+    agentSpecId: agentSpecId ?? null,
+  });
+}
+
 export async function setHeadlessMode(enabled: boolean): Promise<void> {
   return invoke("set_headless_mode", { enabled });
 }
@@ -2775,6 +3124,8 @@ export interface PersistedChatMessage {
   artifacts?: ChatArtifact[] | null;
   created_at: string;
   cursor_rowid: number;
+  archived_message_count: number;
+  archived_messages?: PersistedChatMessage[];
 }
 
 export interface ChatHistoryWindow {
@@ -2793,6 +3144,7 @@ export interface ChatCompactionState {
   compaction_count: number;
   compressed_at: string;
   updated_at: string;
+  summary_message_id: string | null;
 }
 
 export interface ChatRuntimeTelemetry {
@@ -2804,6 +3156,7 @@ export interface ChatRuntimeTelemetry {
   workspace_memory_enabled: boolean;
   workspace_memory_root?: string | null;
   last_model?: string | null;
+  last_provider?: string | null;
   prompt_tokens: number;
   completion_tokens: number;
   total_tokens: number;
@@ -2818,6 +3171,8 @@ export interface ChatSession {
   updated_at: string;
   message_count: number;
   last_message_at: string | null;
+  forked_from_chat_id: string | null;
+  forked_from_message_id: string | null;
 }
 
 export interface RemoteSessionBinding {
@@ -2874,6 +3229,17 @@ export interface CancelAgentRunResponse {
   status: "cancelled" | "unknown_run";
 }
 
+export interface ResumableAgentRun {
+  runId: string;
+  chatId?: string | null;
+  agentName: string;
+  status: "interrupted" | "failed";
+  input: string;
+  pendingApprovals: number;
+  createdAt: number;
+  updatedAt: number;
+}
+
 export async function getDefaultChatScope(): Promise<string> {
   return invoke<string>("get_default_chat_scope");
 }
@@ -2970,11 +3336,23 @@ export async function getChatHistoryWindow(
   chatScopeId: string,
   cursorRowid?: number,
   limit?: number,
+  expandArchived?: boolean,
 ): Promise<ChatHistoryWindow> {
   return invoke<ChatHistoryWindow>("get_chat_history_window", {
     chatScopeId,
     cursorRowid,
     limit,
+    expandArchived,
+  });
+}
+
+export async function forkChat(
+  chatId: string,
+  atMessageId: string,
+): Promise<ChatSession> {
+  return invoke<ChatSession>("fork_chat", {
+    chatId,
+    atMessageId,
   });
 }
 
@@ -3031,6 +3409,20 @@ export async function cancelAgentRun(
   return invoke<CancelAgentRunResponse>("cancel_agent_run", { runId });
 }
 
+export async function resumeAgentRun(
+  runId: string,
+): Promise<RunAgentWorkflowResponse> {
+  return invoke<RunAgentWorkflowResponse>("resume_agent_run", { runId });
+}
+
+export async function listResumableAgentRuns(): Promise<ResumableAgentRun[]> {
+  return invoke<ResumableAgentRun[]>("list_resumable_agent_runs");
+}
+
+export async function dismissAgentRun(runId: string): Promise<void> {
+  return invoke<void>("dismiss_agent_run", { runId });
+}
+
 // ─── Memory Vault Explorer ───────────────────────────────────────────
 
 import type {
@@ -3038,6 +3430,14 @@ import type {
   WorkspaceSummary,
   VaultDetailedStats,
   DeleteBatchResult,
+  DecryptedMemoryEntry,
+  MemoryReviewDecision,
+  MemoryArchiveKey,
+  MemoryArchiveFilters,
+  MemoryConflictPolicy,
+  MemoryExportSummary,
+  MemoryImportReport,
+  MemoryArchiveDeviceKeys,
 } from "../types/memory";
 
 export async function listVaultEntries(opts: {
@@ -3086,6 +3486,55 @@ export async function clearWorkspaceVault(
   return invoke<DeleteBatchResult>("clear_workspace_vault", { workspaceId });
 }
 
+export async function listPendingMemories(
+  workspaceId?: string,
+): Promise<DecryptedMemoryEntry[]> {
+  return invoke<DecryptedMemoryEntry[]>("list_pending_memories", {
+    workspaceId,
+  });
+}
+
+export async function reviewPendingMemory(
+  id: string,
+  decision: MemoryReviewDecision,
+): Promise<void> {
+  return invoke<void>("review_pending_memory", { id, decision });
+}
+
+export async function exportWorkspaceMemory(
+  workspaceId: string,
+  path: string,
+  key: MemoryArchiveKey,
+  filters?: MemoryArchiveFilters,
+): Promise<MemoryExportSummary> {
+  return invoke<MemoryExportSummary>("export_workspace_memory", {
+    workspaceId,
+    path,
+    key,
+    filters,
+  });
+}
+
+export async function importWorkspaceMemory(opts: {
+  path: string;
+  passphrase?: string;
+  expectedSigner?: string;
+  targetWorkspaceId?: string;
+  conflictPolicy?: MemoryConflictPolicy;
+}): Promise<MemoryImportReport> {
+  return invoke<MemoryImportReport>("import_workspace_memory", {
+    path: opts.path,
+    passphrase: opts.passphrase,
+    expectedSigner: opts.expectedSigner,
+    targetWorkspaceId: opts.targetWorkspaceId,
+    conflictPolicy: opts.conflictPolicy,
+  });
+}
+
+export async function getMemoryArchiveKeys(): Promise<MemoryArchiveDeviceKeys> {
+  return invoke<MemoryArchiveDeviceKeys>("get_memory_archive_keys");
+}
+
 // --- Session Coordinator ---
 
 export interface ActiveSessionInfo {
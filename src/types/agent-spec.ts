@@ -102,6 +102,7 @@ export interface RuntimeConfig {
   verification_required?: boolean;
   delegation?: DelegationConfig;
   language_policy?: LanguagePolicyConfig;
+  specialists?: SpecialistDefinition[];
 
   // @deprecated Legacy flat runtime fields (kept for backwards compatibility)
   delegation_policy?: string;
@@ -114,6 +115,18 @@ export interface RuntimeConfig {
   final_response_language_mode?: string;
 }
 
+/** Specialist role declared in the spec; an id of a built-in role overrides it. */
+export interface SpecialistDefinition {
+  id: string;
+  description?: string;
+  systemPrompt?: string;
+  model?: string;
+  tools?: string[];
+  maxSteps?: number;
+  /** Highest Airlock level the role may run: 0 safe, 1 sensitive, 2 dangerous. */
+  airlockCeiling?: number;
+}
+
 export interface DelegationConfig {
   policy?: "explicit_only" | "hybrid_intent_gated" | "auto_heuristic";
   max_depth?: number;
@@ -121,6 +134,8 @@ export interface DelegationConfig {
   max_parallel_subagents?: number;
   job_max_runtime_seconds?: number;
   final_synthesis_required?: boolean;
+  /** Give write-capable specialists their own git worktree and merge back afterwards. */
+  isolate_writers?: boolean;
 }
 
 export interface LanguagePolicyConfig {
@@ -272,6 +272,7 @@ export interface AgentMessage {
     workspaceMemoryEnabled?: boolean;
     workspaceMemoryRoot?: string;
     lastModel?: string;
+    lastProvider?: string;
     promptTokens?: number;
     completionTokens?: number;
     totalTokens?: number;
@@ -22,4 +22,6 @@ export interface AirlockConfig {
   tool_levels: Record<string, AirlockLevel>;
   scopes: AirlockScopes;
   rate_limits: AirlockRateLimits;
+  /** Tools resolving above this level are blocked outright. */
+  max_level?: AirlockLevel;
 }
@@ -53,11 +53,48 @@ export interface DeleteBatchResult {
   deleted: number;
 }
 
+export type MemoryArchiveKey =
+  | { mode: "passphrase"; passphrase: string }
+  | { mode: "recipient"; publicKey: string };
+
+export interface MemoryArchiveFilters {
+  categories?: string[];
+  sensitivities?: string[];
+  createdAfter?: number;
+  createdBefore?: number;
+}
+
+export type MemoryConflictPolicy = "skip" | "overwrite" | "keep_newest";
+
+export interface MemoryExportSummary {
+  path: string;
+  entry_count: number;
+  signer_public_key: string;
+}
+
+export interface MemoryImportReport {
+  imported: number;
+  overwritten: number;
+  skipped: number;
+  reembedding: number;
+}
+
+export interface MemoryArchiveDeviceKeys {
+  signer_public_key: string;
+  recipient_public_key: string;
+}
+
+export type MemoryReviewDecision =
+  | { action: "confirm" }
+  | { action: "edit"; content: string }
+  | { action: "reject" };
+
 export interface MemoryConfig {
   strategy: "vector" | "simple_buffer" | "hybrid";
   retrieval: {
     retention_days: number;
     max_tokens: number;
+    rerank?: boolean;
   };
   persistence: {
     cross_session: boolean;
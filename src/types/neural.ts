@@ -14,4 +14,6 @@ export interface ApprovalRequest {
   timeoutSecs?: number | null;
   expiresAt?: number | null;
   timestamp: number;
+  diffPreview?: string | null;
+  runId?: string | null;
 }